@@ -0,0 +1,340 @@
+//! Host-boot orchestration for `autostart` instances.
+//!
+//! `nova up` starts every VM and container marked `autostart = true`, along
+//! with anything they list in `depends_on`, dependencies first. Bulk
+//! `nova run` uses the same ordering for the instances it selects. The same
+//! module renders the systemd unit written by `nova install-service`.
+
+use crate::{NovaError, Result, config::NovaConfig, instance::InstanceType};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as FmtWrite;
+use std::path::Path;
+
+/// One instance to start, in boot order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupEntry {
+    pub instance_type: InstanceType,
+    pub name: String,
+    pub depends_on: Vec<String>,
+}
+
+/// Resolve the start order for autostart instances and their dependencies.
+///
+/// Instances with no ordering constraint between them start alphabetically so
+/// boots are reproducible. Unknown or ambiguous dependencies and cycles are
+/// reported as configuration errors.
+pub fn startup_order(config: &NovaConfig) -> Result<Vec<StartupEntry>> {
+    let mut instances: BTreeMap<&str, (InstanceType, &[String], bool)> = BTreeMap::new();
+    for (name, vm) in &config.vm {
+        instances.insert(name, (InstanceType::Vm, &vm.depends_on, vm.autostart));
+    }
+    for (name, container) in &config.container {
+        if instances.contains_key(name.as_str()) {
+            return Err(NovaError::ConfigError(format!(
+                "'{}' is defined as both a VM and a container; rename one to use autostart",
+                name
+            )));
+        }
+        instances.insert(
+            name,
+            (
+                InstanceType::Container,
+                &container.depends_on,
+                container.autostart,
+            ),
+        );
+    }
+
+    // Autostart instances pull in their dependencies even when those are not
+    // marked autostart themselves.
+    let mut selected = BTreeSet::new();
+    let mut pending: Vec<&str> = instances
+        .iter()
+        .filter(|(_, (_, _, autostart))| *autostart)
+        .map(|(name, _)| *name)
+        .collect();
+    while let Some(name) = pending.pop() {
+        if !selected.insert(name) {
+            continue;
+        }
+        for dependency in instances[name].1 {
+            if !instances.contains_key(dependency.as_str()) {
+                return Err(NovaError::ConfigError(format!(
+                    "'{}' depends on unknown instance '{}'",
+                    name, dependency
+                )));
+            }
+            pending.push(dependency);
+        }
+    }
+
+    let order = dependency_order(&selected, |name| instances[name].1).map_err(|blocked| {
+        NovaError::ConfigError(format!(
+            "Dependency cycle between autostart instances: {}",
+            blocked.join(", ")
+        ))
+    })?;
+
+    Ok(order
+        .into_iter()
+        .map(|name| {
+            let (instance_type, depends_on, _) = instances[name];
+            StartupEntry {
+                instance_type,
+                name: name.to_string(),
+                depends_on: depends_on.to_vec(),
+            }
+        })
+        .collect())
+}
+
+/// Order `names` for a bulk `nova run`: each instance starts after those of
+/// its `depends_on` that are part of the same run. Dependencies outside the
+/// selection are not pulled in, unlike [`startup_order`].
+pub fn bulk_start_order(
+    config: &NovaConfig,
+    instance_type: InstanceType,
+    names: &[String],
+) -> Result<Vec<String>> {
+    let selected: BTreeSet<&str> = names.iter().map(String::as_str).collect();
+    let depends_on = |name: &str| -> &[String] {
+        match instance_type {
+            InstanceType::Vm => config.vm.get(name).map(|vm| &vm.depends_on[..]),
+            InstanceType::Container => config
+                .container
+                .get(name)
+                .map(|container| &container.depends_on[..]),
+        }
+        .unwrap_or_default()
+    };
+
+    let order = dependency_order(&selected, depends_on).map_err(|blocked| {
+        NovaError::ConfigError(format!(
+            "Dependency cycle between selected instances: {}",
+            blocked.join(", ")
+        ))
+    })?;
+    Ok(order.into_iter().map(str::to_string).collect())
+}
+
+/// Topological order of `selected`, ignoring dependencies outside it.
+/// Instances with no ordering constraint between them come alphabetically.
+/// On a cycle, returns the instances that could not be ordered.
+fn dependency_order<'a, 'c>(
+    selected: &BTreeSet<&'a str>,
+    depends_on: impl Fn(&str) -> &'c [String],
+) -> std::result::Result<Vec<&'a str>, Vec<&'a str>> {
+    let mut order = Vec::with_capacity(selected.len());
+    let mut started = BTreeSet::new();
+    while started.len() < selected.len() {
+        let ready: Vec<&str> = selected
+            .iter()
+            .copied()
+            .filter(|name| !started.contains(name))
+            .filter(|name| {
+                depends_on(name).iter().all(|dependency| {
+                    started.contains(dependency.as_str()) || !selected.contains(dependency.as_str())
+                })
+            })
+            .collect();
+
+        if ready.is_empty() {
+            return Err(selected
+                .iter()
+                .copied()
+                .filter(|name| !started.contains(name))
+                .collect());
+        }
+
+        for name in ready {
+            order.push(name);
+            started.insert(name);
+        }
+    }
+    Ok(order)
+}
+
+/// Render the systemd unit that runs `nova up` at boot.
+///
+/// Without `metrics_port` the unit is a oneshot that stays active after the
+/// instances are started; with it, `nova up` keeps serving metrics.
+pub fn render_systemd_unit(binary: &Path, config: &Path, metrics_port: Option<u16>) -> String {
+    let mut exec = format!("{} --config {} up", binary.display(), config.display());
+    if let Some(port) = metrics_port {
+        let _ = write!(exec, " --serve-metrics --metrics-port {}", port);
+    }
+
+    let service = if metrics_port.is_some() {
+        "Type=simple\nRestart=on-failure\nRestartSec=5s\n"
+    } else {
+        "Type=oneshot\nRemainAfterExit=yes\n"
+    };
+
+    format!(
+        "[Unit]\n\
+         Description=Nova autostart VMs and containers\n\
+         Documentation=https://github.com/nova-project/nova\n\
+         Requires=libvirtd.service\n\
+         Wants=network-online.target\n\
+         After=network-online.target libvirtd.service\n\
+         \n\
+         [Service]\n\
+         {service}\
+         ExecStart={exec}\n\
+         TimeoutStartSec=10min\n\
+         # Leave started VMs running when the unit stops or restarts\n\
+         KillMode=process\n\
+         \n\
+         # Security hardening. QEMU guests started directly by `nova up`\n\
+         # inherit this sandbox, so their images (anywhere under /var,\n\
+         # /home or /tmp) must stay reachable and writable.\n\
+         NoNewPrivileges=true\n\
+         ProtectSystem=full\n\
+         \n\
+         # Resource limits\n\
+         LimitNOFILE=65536\n\
+         LimitNPROC=512\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> NovaConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn names(order: &[StartupEntry]) -> Vec<&str> {
+        order.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn dependencies_start_first_and_are_pulled_in() {
+        let config = config(
+            r#"
+[vm.router]
+depends_on = []
+
+[vm.app]
+autostart = true
+depends_on = ["db", "router"]
+
+[container.db]
+autostart = true
+depends_on = ["router"]
+
+[vm.scratch]
+"#,
+        );
+
+        let order = startup_order(&config).unwrap();
+        assert_eq!(names(&order), vec!["router", "db", "app"]);
+        assert_eq!(order[1].instance_type, InstanceType::Container);
+    }
+
+    #[test]
+    fn unknown_dependencies_and_cycles_are_rejected() {
+        let missing = config(
+            r#"
+[vm.app]
+autostart = true
+depends_on = ["ghost"]
+"#,
+        );
+        assert!(startup_order(&missing).is_err());
+
+        let cycle = config(
+            r#"
+[vm.a]
+autostart = true
+depends_on = ["b"]
+
+[container.b]
+depends_on = ["a"]
+"#,
+        );
+        let err = startup_order(&cycle).unwrap_err().to_string();
+        assert!(err.contains("cycle"), "{}", err);
+    }
+
+    #[test]
+    fn bulk_start_follows_dependencies_within_the_selection() {
+        let config = config(
+            r#"
+[vm.web]
+depends_on = ["db", "router"]
+
+[vm.db]
+depends_on = ["storage"]
+
+[vm.router]
+
+[vm.storage]
+
+[vm.a]
+depends_on = ["b"]
+
+[vm.b]
+depends_on = ["a"]
+"#,
+        );
+        let names = |list: &[&str]| list.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            bulk_start_order(&config, InstanceType::Vm, &names(&["web", "db", "router"])).unwrap(),
+            names(&["db", "router", "web"])
+        );
+        // storage is not selected, so db does not wait for it
+        assert_eq!(
+            bulk_start_order(&config, InstanceType::Vm, &names(&["web", "db"])).unwrap(),
+            names(&["db", "web"])
+        );
+        // Instances only known to libvirt have no dependencies
+        assert_eq!(
+            bulk_start_order(&config, InstanceType::Vm, &names(&["web", "legacy"])).unwrap(),
+            names(&["legacy", "web"])
+        );
+        let err = bulk_start_order(&config, InstanceType::Vm, &names(&["a", "b", "web"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cycle") && err.contains("a, b"), "{}", err);
+    }
+
+    #[test]
+    fn unit_runs_nova_up_after_libvirt() {
+        let unit = render_systemd_unit(
+            Path::new("/usr/bin/nova"),
+            Path::new("/etc/nova/NovaFile"),
+            None,
+        );
+        assert!(unit.contains("ExecStart=/usr/bin/nova --config /etc/nova/NovaFile up\n"));
+        assert!(unit.contains("After=network-online.target libvirtd.service"));
+        assert!(unit.contains("Type=oneshot"));
+        assert!(unit.contains("ProtectSystem=full\n"));
+        assert!(unit.contains("NoNewPrivileges=true\n"));
+        // Guest images may live in home directories or /tmp
+        assert!(!unit.contains("ProtectHome="));
+        assert!(!unit.contains("PrivateTmp="));
+        assert_eq!(
+            unit,
+            std::fs::read_to_string(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/packaging/systemd/nova.service"
+            ))
+            .unwrap(),
+            "packaged unit drifted from the rendered one"
+        );
+
+        let metrics = render_systemd_unit(
+            Path::new("/usr/bin/nova"),
+            Path::new("/etc/nova/NovaFile"),
+            Some(9640),
+        );
+        assert!(metrics.contains("up --serve-metrics --metrics-port 9640"));
+        assert!(metrics.contains("Type=simple"));
+    }
+}
@@ -4,9 +4,11 @@
 //! Bolt provides ultra-fast GPU passthrough (<100μs vs Docker's ~10ms),
 //! gaming optimizations, and BTRFS/ZFS snapshots.
 
+use crate::container_ports;
 use crate::container_runtime::*;
-use crate::{log_debug, log_error, log_info};
+use crate::{exec, log_debug, log_error, log_info};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::process::Command;
 
 /// Bolt runtime implementation
@@ -16,6 +18,11 @@ pub struct BoltRuntime {
 }
 
 impl BoltRuntime {
+    /// Arguments resolving `image` against its registry without pulling it.
+    fn check_image_args(image: &str) -> [&str; 3] {
+        ["pull", "--dry-run", image]
+    }
+
     pub fn new() -> Self {
         let available = Self::check_bolt_installed();
         let version = if available {
@@ -163,9 +170,32 @@ impl BoltRuntime {
             network: None,
             pid: None,
             ip_address: None,
+            restart_count: 0,
+            exit_code: None,
+            exit_reason: None,
+            restart_policy: None,
         })
     }
 
+    /// Parsed `bolt inspect` output for a container.
+    fn inspect_data(id_or_name: &str) -> Result<BoltInspectData> {
+        let output = Command::new("bolt")
+            .args(["inspect", id_or_name])
+            .output()
+            .map_err(|e| {
+                ContainerRuntimeError::Other(format!("Failed to execute bolt inspect: {}", e))
+            })?;
+
+        if !output.status.success() {
+            return Err(ContainerRuntimeError::ContainerNotFound(
+                id_or_name.to_string(),
+            ));
+        }
+
+        let json_str = String::from_utf8_lossy(&output.stdout);
+        Ok(serde_json::from_str(&json_str)?)
+    }
+
     fn parse_status(status_str: &str) -> ContainerStatus {
         let status_lower = status_str.to_lowercase();
         if status_lower.contains("running") || status_lower == "up" {
@@ -228,7 +258,7 @@ impl ContainerRuntime for BoltRuntime {
             let args = self.build_bolt_args(name, &bolt_config);
 
             // Execute bolt run command
-            let output = Command::new("bolt").args(&args).output().map_err(|e| {
+            let output = exec::run(Command::new("bolt").args(&args)).map_err(|e| {
                 ContainerRuntimeError::StartFailed(format!("Failed to execute bolt: {}", e))
             })?;
 
@@ -250,10 +280,8 @@ impl ContainerRuntime for BoltRuntime {
         Box::pin(async move {
             log_info!("Stopping Bolt container: {}", id_or_name);
 
-            let output = Command::new("bolt")
-                .args(["stop", id_or_name])
-                .output()
-                .map_err(|e| {
+            let output =
+                exec::run(Command::new("bolt").args(["stop", id_or_name])).map_err(|e| {
                     ContainerRuntimeError::StopFailed(format!("Failed to execute bolt stop: {}", e))
                 })?;
 
@@ -276,7 +304,7 @@ impl ContainerRuntime for BoltRuntime {
             }
             args.push(id_or_name);
 
-            let output = Command::new("bolt").args(&args).output().map_err(|e| {
+            let output = exec::run(Command::new("bolt").args(&args)).map_err(|e| {
                 ContainerRuntimeError::Other(format!("Failed to execute bolt rm: {}", e))
             })?;
 
@@ -317,21 +345,7 @@ impl ContainerRuntime for BoltRuntime {
 
     fn inspect_container<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerInfo> {
         Box::pin(async move {
-            let output = Command::new("bolt")
-                .args(["inspect", id_or_name])
-                .output()
-                .map_err(|e| {
-                    ContainerRuntimeError::Other(format!("Failed to execute bolt inspect: {}", e))
-                })?;
-
-            if !output.status.success() {
-                return Err(ContainerRuntimeError::ContainerNotFound(
-                    id_or_name.to_string(),
-                ));
-            }
-
-            let json_str = String::from_utf8_lossy(&output.stdout);
-            let inspect_data: BoltInspectData = serde_json::from_str(&json_str)?;
+            let inspect_data = Self::inspect_data(id_or_name)?;
 
             Ok(ContainerInfo {
                 id: inspect_data.id,
@@ -339,10 +353,21 @@ impl ContainerRuntime for BoltRuntime {
                 image: inspect_data.image,
                 status: Self::parse_status(&inspect_data.status),
                 created: inspect_data.created.unwrap_or_else(chrono::Utc::now),
-                ports: Vec::new(),
+                ports: inspect_data
+                    .ports
+                    .iter()
+                    .filter_map(|port| container_ports::parse_port_spec(port).ok())
+                    .collect(),
                 network: inspect_data.network,
                 pid: inspect_data.pid,
                 ip_address: inspect_data.ip_address,
+                restart_count: inspect_data.restart_count,
+                exit_code: inspect_data.exit_code,
+                exit_reason: inspect_data.exit_reason,
+                restart_policy: inspect_data
+                    .restart_policy
+                    .as_deref()
+                    .map(RestartPolicy::from_name),
             })
         })
     }
@@ -351,16 +376,36 @@ impl ContainerRuntime for BoltRuntime {
         Box::pin(async move {
             log_info!("Pulling Bolt image: {}", image);
 
-            let output = Command::new("bolt")
-                .args(["pull", image])
+            let output = exec::run(Command::new("bolt").args(["pull", image])).map_err(|e| {
+                ContainerRuntimeError::Other(format!("Failed to execute bolt pull: {}", e))
+            })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(ContainerRuntimeError::Other(stderr.to_string()));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn check_image<'a>(&'a self, image: &'a str) -> RuntimeFuture<'a, ()> {
+        Box::pin(async move {
+            let output = tokio::process::Command::new("bolt")
+                .args(Self::check_image_args(image))
                 .output()
+                .await
                 .map_err(|e| {
-                    ContainerRuntimeError::Other(format!("Failed to execute bolt pull: {}", e))
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute bolt pull --dry-run: {}",
+                        e
+                    ))
                 })?;
 
             if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return Err(ContainerRuntimeError::Other(stderr.to_string()));
+                return Err(ContainerRuntimeError::ImageNotFound(image_check_reason(
+                    &String::from_utf8_lossy(&output.stderr),
+                )));
             }
 
             Ok(())
@@ -404,6 +449,50 @@ impl ContainerRuntime for BoltRuntime {
         })
     }
 
+    fn exec<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        command: &'a [String],
+        tty: bool,
+    ) -> RuntimeFuture<'a, i32> {
+        Box::pin(async move {
+            let mut cmd = tokio::process::Command::new("bolt");
+            cmd.args(["exec", if tty { "-it" } else { "-i" }, id_or_name]);
+            let status = cmd.args(command).status().await.map_err(|e| {
+                ContainerRuntimeError::Other(format!("Failed to execute bolt exec: {}", e))
+            })?;
+            // Killed by a signal: report it the way a shell would
+            Ok(status.code().unwrap_or_else(|| {
+                128 + std::os::unix::process::ExitStatusExt::signal(&status).unwrap_or(0)
+            }))
+        })
+    }
+
+    fn stream_logs<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        lines: usize,
+        follow: bool,
+    ) -> RuntimeFuture<'a, ()> {
+        Box::pin(async move {
+            let mut cmd = tokio::process::Command::new("bolt");
+            cmd.args(["logs", "--tail", &lines.to_string()]);
+            if follow {
+                cmd.arg("--follow");
+            }
+            let status = cmd.arg(id_or_name).status().await.map_err(|e| {
+                ContainerRuntimeError::Other(format!("Failed to execute bolt logs: {}", e))
+            })?;
+
+            if !status.success() {
+                return Err(ContainerRuntimeError::ContainerNotFound(
+                    id_or_name.to_string(),
+                ));
+            }
+            Ok(())
+        })
+    }
+
     fn get_stats<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerStats> {
         Box::pin(async move {
             let output = Command::new("bolt")
@@ -430,6 +519,56 @@ impl ContainerRuntime for BoltRuntime {
             })
         })
     }
+
+    fn get_all_stats<'a>(&'a self) -> RuntimeFuture<'a, Vec<(String, ContainerStats)>> {
+        Box::pin(async move {
+            let mut all = Vec::new();
+            for container in self.list_containers(false).await? {
+                // A container that stopped since the listing has no stats
+                if let Ok(stats) = self.get_stats(&container.name).await {
+                    all.push((container.name, stats));
+                }
+            }
+            Ok(all)
+        })
+    }
+
+    fn container_config<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerConfig> {
+        Box::pin(async move {
+            let inspect_data = Self::inspect_data(id_or_name)?;
+
+            Ok(ContainerConfig {
+                capsule: inspect_data.image,
+                ports: inspect_data.ports,
+                volumes: inspect_data.volumes,
+                env: inspect_data.env,
+                network: inspect_data.network,
+                gpu_passthrough: inspect_data.gpu_passthrough,
+                memory_mb: inspect_data.memory_mb,
+                cpus: inspect_data.cpus,
+                restart_policy: RestartPolicy::from_name(
+                    inspect_data.restart_policy.as_deref().unwrap_or_default(),
+                ),
+                detach: true,
+            })
+        })
+    }
+
+    fn disk_usage<'a>(&'a self) -> RuntimeFuture<'a, Vec<DiskUsage>> {
+        Box::pin(async move {
+            Err(ContainerRuntimeError::Other(
+                "Bolt does not report disk usage; use `bolt images` instead".to_string(),
+            ))
+        })
+    }
+
+    fn prune<'a>(&'a self, _options: PruneOptions) -> RuntimeFuture<'a, u64> {
+        Box::pin(async move {
+            Err(ContainerRuntimeError::Other(
+                "Bolt does not support pruning images or volumes".to_string(),
+            ))
+        })
+    }
 }
 
 /// Bolt inspect data structure
@@ -443,6 +582,27 @@ struct BoltInspectData {
     network: Option<String>,
     pid: Option<u32>,
     ip_address: Option<String>,
+    #[serde(default)]
+    restart_count: u32,
+    #[serde(default)]
+    exit_code: Option<i32>,
+    #[serde(default)]
+    exit_reason: Option<String>,
+    /// Settings the container was created with, for recreating it
+    #[serde(default)]
+    ports: Vec<String>,
+    #[serde(default)]
+    volumes: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    memory_mb: Option<u64>,
+    #[serde(default)]
+    cpus: Option<u32>,
+    #[serde(default)]
+    restart_policy: Option<String>,
+    #[serde(default)]
+    gpu_passthrough: bool,
 }
 
 impl Default for BoltRuntime {
@@ -450,3 +610,20 @@ impl Default for BoltRuntime {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_check_is_a_dry_run_pull() {
+        assert_eq!(
+            BoltRuntime::check_image_args("ghcr.io/ghostkellz/app:latest"),
+            ["pull", "--dry-run", "ghcr.io/ghostkellz/app:latest"]
+        );
+        assert_eq!(
+            image_check_reason("error: image ghcr.io/ghostkellz/app:nope not found\n"),
+            "image ghcr.io/ghostkellz/app:nope not found"
+        );
+    }
+}
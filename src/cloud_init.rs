@@ -0,0 +1,186 @@
+//! cloud-init NoCloud seed ISOs for unattended VM provisioning.
+//!
+//! Cloud images look for a small ISO labelled `cidata` holding `user-data`
+//! and `meta-data` files on first boot. `nova wizard vm --cloud-init` builds
+//! one next to the VM's disk image and adds it to the NovaFile entry as a
+//! CD-ROM. Each seed gets a fresh `instance-id`, since cloud-init only
+//! re-runs its per-instance modules when the ID changes.
+
+use crate::{NovaError, Result, log_info};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Volume label cloud-init's NoCloud datasource looks for.
+pub const SEED_VOLUME_ID: &str = "cidata";
+
+/// Tools that can write an ISO 9660 image with Joliet and Rock Ridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsoTool {
+    Genisoimage,
+    Mkisofs,
+    Xorriso,
+}
+
+impl IsoTool {
+    pub fn binary(&self) -> &'static str {
+        match self {
+            IsoTool::Genisoimage => "genisoimage",
+            IsoTool::Mkisofs => "mkisofs",
+            IsoTool::Xorriso => "xorriso",
+        }
+    }
+
+    /// First tool installed on the host.
+    pub fn detect() -> Option<Self> {
+        [IsoTool::Genisoimage, IsoTool::Mkisofs, IsoTool::Xorriso]
+            .into_iter()
+            .find(|tool| {
+                Command::new("which")
+                    .arg(tool.binary())
+                    .output()
+                    .map(|output| output.status.success())
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Arguments writing `files` into `output` as a seed ISO.
+    pub fn args(&self, output: &Path, files: &[PathBuf]) -> Vec<String> {
+        let mut args = Vec::new();
+        if *self == IsoTool::Xorriso {
+            args.extend(["-as", "mkisofs"].map(str::to_string));
+        }
+        args.extend([
+            "-output".to_string(),
+            output.display().to_string(),
+            "-volid".to_string(),
+            SEED_VOLUME_ID.to_string(),
+            "-joliet".to_string(),
+            "-rock".to_string(),
+        ]);
+        args.extend(files.iter().map(|file| file.display().to_string()));
+        args
+    }
+}
+
+/// cloud-init ignores user-data without a `#cloud-config` header or a
+/// script shebang, so catch that before building the seed.
+pub fn validate_user_data(path: &Path) -> Result<String> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        NovaError::ConfigError(format!(
+            "Cannot read cloud-init user-data {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let first_line = content.lines().next().unwrap_or_default().trim();
+    if first_line != "#cloud-config" && !first_line.starts_with("#!") {
+        return Err(NovaError::ConfigError(format!(
+            "{} must start with `#cloud-config` (or `#!` for a script); cloud-init ignores it otherwise",
+            path.display()
+        )));
+    }
+    Ok(content)
+}
+
+/// A random instance ID, so cloud-init treats every seed as a new instance.
+pub fn generate_instance_id() -> String {
+    format!("nova-{}", uuid::Uuid::new_v4())
+}
+
+pub fn meta_data(instance_id: &str, hostname: &str) -> String {
+    format!(
+        "instance-id: {}\nlocal-hostname: {}\n",
+        instance_id, hostname
+    )
+}
+
+/// Where the seed for `vm_name` is written: next to its disk image.
+pub fn seed_iso_path(vm_name: &str, image_path: &Path) -> PathBuf {
+    image_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(format!("{}-seed.iso", vm_name))
+}
+
+/// Build a NoCloud seed ISO at `output` from `user_data` and return the
+/// instance ID written into its meta-data.
+pub fn build_seed_iso(vm_name: &str, user_data: &Path, output: &Path) -> Result<String> {
+    let content = validate_user_data(user_data)?;
+    let tool = IsoTool::detect().ok_or_else(|| {
+        NovaError::ConfigError(
+            "Building a cloud-init seed needs genisoimage, mkisofs or xorriso; install one \
+             (e.g. `pacman -S cdrtools` or `apt install genisoimage`)"
+                .to_string(),
+        )
+    })?;
+
+    let staging = tempfile::tempdir()?;
+    let instance_id = generate_instance_id();
+    let user_data_file = staging.path().join("user-data");
+    let meta_data_file = staging.path().join("meta-data");
+    fs::write(&user_data_file, content)?;
+    fs::write(&meta_data_file, meta_data(&instance_id, vm_name))?;
+
+    if let Some(parent) = output.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let result = Command::new(tool.binary())
+        .args(tool.args(output, &[user_data_file, meta_data_file]))
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !result.status.success() {
+        return Err(NovaError::ConfigError(format!(
+            "{} failed to build {}: {}",
+            tool.binary(),
+            output.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+
+    log_info!(
+        "Built cloud-init seed {} for '{}' ({})",
+        output.display(),
+        vm_name,
+        instance_id
+    );
+    Ok(instance_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn seed_contents_and_tool_arguments() {
+        let mut user_data = tempfile::NamedTempFile::new().unwrap();
+        writeln!(user_data, "#cloud-config\npackages: [htop]").unwrap();
+        assert!(validate_user_data(user_data.path()).is_ok());
+
+        let mut plain = tempfile::NamedTempFile::new().unwrap();
+        writeln!(plain, "packages: [htop]").unwrap();
+        let err = validate_user_data(plain.path()).unwrap_err().to_string();
+        assert!(err.contains("#cloud-config"), "{}", err);
+
+        let id = generate_instance_id();
+        assert!(id.starts_with("nova-"));
+        assert_ne!(id, generate_instance_id());
+        assert_eq!(
+            meta_data("nova-1", "web"),
+            "instance-id: nova-1\nlocal-hostname: web\n"
+        );
+
+        assert_eq!(
+            seed_iso_path("web", Path::new("/var/lib/nova/images/web.qcow2")),
+            PathBuf::from("/var/lib/nova/images/web-seed.iso")
+        );
+        let files = [PathBuf::from("user-data"), PathBuf::from("meta-data")];
+        let args = IsoTool::Xorriso.args(Path::new("seed.iso"), &files);
+        assert_eq!(&args[..2], ["-as", "mkisofs"]);
+        assert!(args.windows(2).any(|pair| pair == ["-volid", "cidata"]));
+        assert_eq!(args.last().map(String::as_str), Some("meta-data"));
+    }
+}
@@ -1,6 +1,19 @@
 use crate::{
-    NovaError, Result, gpu_passthrough::GpuPassthroughConfig, looking_glass::LookingGlassConfig,
+    NovaError, Result,
+    container_health::CrashLoopPolicy,
+    container_runtime::{RestartPolicy, RuntimeKind},
+    container_scaling::ScalingThresholds,
+    firewall::FirewallSettings,
+    gpu_passthrough::GpuPassthroughConfig,
+    looking_glass::LookingGlassConfig,
+    naming::NamingConfig,
+    templates_snapshots::SnapshotQuota,
     theme,
+    vm_cpu::{self, CpuTopology, VcpuPin},
+    vm_devices::DeviceDefaults,
+    vm_hugepages::{self, HugePageSize},
+    vm_netlimit::NetLimit,
+    vm_nics::{self, VmNic},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -10,6 +23,9 @@ use std::path::{Path, PathBuf};
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NovaConfig {
     pub project: Option<String>,
+    /// Container backend to use instead of auto-detecting one
+    #[serde(default)]
+    pub container_runtime: Option<RuntimeKind>,
     #[serde(default)]
     pub vm: HashMap<String, VmConfig>,
     #[serde(default)]
@@ -24,6 +40,25 @@ pub struct NovaConfig {
     pub iso: IsoConfig,
     #[serde(default)]
     pub templates: TemplatesConfig,
+    #[serde(default)]
+    pub naming: NamingConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub container_scaling: ScalingThresholds,
+    #[serde(default)]
+    pub container_crash_loop: CrashLoopPolicy,
+    #[serde(default)]
+    pub device_defaults: DeviceDefaults,
+    #[serde(default)]
+    pub firewall: FirewallSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LoggingConfig {
+    /// Also write logs to this file (read back by `nova logs`)
+    #[serde(default)]
+    pub file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -105,17 +140,56 @@ pub struct VmConfig {
     pub image: Option<String>,
     #[serde(default = "default_cpu")]
     pub cpu: u32,
+    /// Guest sockets, cores and threads; defaults to one socket with a
+    /// core per vCPU
+    #[serde(default)]
+    pub cpu_topology: Option<CpuTopology>,
+    /// vCPUs pinned to host CPUs, e.g. `["0=2", "1=3"]`
+    #[serde(default)]
+    pub cpuset: Vec<VcpuPin>,
     #[serde(default = "default_memory")]
     pub memory: String,
+    /// Ceiling for hot-added memory (e.g. "16Gi"); enables DIMM hotplug
+    #[serde(default)]
+    pub max_memory: Option<String>,
+    /// DIMM slots available below `max_memory`
+    #[serde(default)]
+    pub memory_slots: Option<u32>,
+    /// Back guest memory with hugepages: `true` for 2 MiB pages, or "1G"
+    #[serde(default, deserialize_with = "vm_hugepages::deserialize_setting")]
+    pub hugepages: Option<HugePageSize>,
     #[serde(default)]
     pub gpu_passthrough: bool,
     #[serde(default)]
     pub gpu: Option<GpuPassthroughConfig>,
-    pub network: Option<String>,
+    /// Network name, or a list of NICs with optional model and MAC
+    #[serde(
+        default,
+        deserialize_with = "vm_nics::deserialize_networks",
+        serialize_with = "vm_nics::serialize_networks",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub network: Vec<VmNic>,
+    /// Bandwidth cap on each of the VM's interfaces, in MiB/s
+    #[serde(default)]
+    pub net_limit: Option<NetLimit>,
     #[serde(default)]
     pub autostart: bool,
+    /// Instances (VMs or containers) that `nova up` must start first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Free-form labels for `--label key=value` selection
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Raw QEMU arguments emitted as `<qemu:commandline>`; unsupported and
+    /// can break migration
+    #[serde(default)]
+    pub extra_qemu_args: Vec<String>,
     #[serde(default)]
     pub storage: VmStorageConfig,
+    /// Additional disks attached after the primary image
+    #[serde(default)]
+    pub disks: Vec<VmDiskConfig>,
     #[serde(default)]
     pub looking_glass: LookingGlassConfig,
     #[serde(default)]
@@ -124,6 +198,8 @@ pub struct VmConfig {
     pub tpm: VmTpmConfig,
     #[serde(default)]
     pub compliance_profile: Option<VmComplianceProfile>,
+    #[serde(default)]
+    pub snapshot_quota: SnapshotQuota,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,6 +217,20 @@ pub struct StoragePoolConfig {
     pub auto_create: bool,
     #[serde(default)]
     pub labels: Vec<String>,
+    /// ZFS dataset settings; required when `pool_type = "zfs"`
+    #[serde(default)]
+    pub zfs: Option<ZfsPoolSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ZfsPoolSettings {
+    /// Dataset backing the pool (e.g. `tank/nova`)
+    pub dataset: String,
+    /// ZFS `compression` property (off, lz4, gzip-N, zstd-N)
+    #[serde(default = "default_zfs_compression")]
+    pub compression: String,
+    #[serde(default)]
+    pub dedup: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +259,13 @@ pub struct UiConfig {
     pub container_logs_auto_refresh: bool,
     #[serde(default = "default_ui_container_logs_refresh_interval_seconds")]
     pub container_logs_refresh_interval_seconds: u64,
+    /// Reload the NovaFile in the GUI when it changes on disk
+    #[serde(default)]
+    pub watch_config: bool,
+    /// Status colors safe for red-green colorblindness, with a distinct
+    /// symbol per status in the GUI and CLI tables
+    #[serde(default)]
+    pub colorblind_safe: bool,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -177,6 +274,7 @@ pub enum StoragePoolType {
     Directory,
     Btrfs,
     Nfs,
+    Zfs,
 }
 
 impl StoragePoolType {
@@ -185,6 +283,7 @@ impl StoragePoolType {
             StoragePoolType::Directory => "directory",
             StoragePoolType::Btrfs => "btrfs",
             StoragePoolType::Nfs => "nfs",
+            StoragePoolType::Zfs => "zfs",
         }
     }
 }
@@ -203,6 +302,44 @@ pub struct VmStorageConfig {
     pub create_if_missing: bool,
 }
 
+/// Extra disk attached to a VM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VmDiskConfig {
+    /// Whole host block device passed through raw (e.g. `/dev/sdb`). The VM
+    /// gets exclusive access unless `shareable` is set; the device must not
+    /// be mounted on the host.
+    BlockDevice {
+        path: String,
+        /// Guest target such as `vdb`; picked automatically when omitted
+        #[serde(default)]
+        target: Option<String>,
+        /// Present the disk read-only to the guest
+        #[serde(default)]
+        readonly: bool,
+        /// Allow other VMs to attach the same device at the same time
+        #[serde(default)]
+        shareable: bool,
+        #[serde(default)]
+        cache: DiskCache,
+    },
+    /// ISO image attached read-only as a CD-ROM, e.g. a cloud-init seed
+    Cdrom { path: String },
+}
+
+/// Host page cache mode of a disk (`<driver cache=...>`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskCache {
+    /// Bypass the host page cache (required for shareable disks)
+    #[default]
+    None,
+    Directsync,
+    Writethrough,
+    Writeback,
+    Unsafe,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum VmBootType {
@@ -289,6 +426,9 @@ pub enum DiskFormat {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ContainerConfig {
     pub capsule: Option<String>,
+    /// Published ports as `host:container[/udp]`
+    #[serde(default)]
+    pub ports: Vec<String>,
     #[serde(default)]
     pub volumes: Vec<String>,
     pub network: Option<String>,
@@ -296,6 +436,16 @@ pub struct ContainerConfig {
     pub env: HashMap<String, String>,
     #[serde(default)]
     pub autostart: bool,
+    /// Instances (VMs or containers) that `nova up` must start first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Free-form labels for `--label key=value` selection
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Restart policy passed to the runtime: no, on-failure, always or
+    /// unless-stopped
+    #[serde(default)]
+    pub restart: Option<RestartPolicy>,
     pub runtime: Option<String>, // "bolt", "docker", "podman", or auto-detect
     #[serde(default)]
     pub bolt: BoltConfig, // Bolt-specific configuration
@@ -344,21 +494,39 @@ pub enum NetworkType {
     None,
 }
 
+impl VmConfig {
+    /// Network of the first NIC, shown where a VM has a single network.
+    pub fn primary_network(&self) -> Option<String> {
+        self.network.first().map(|nic| nic.network.clone())
+    }
+}
+
 impl Default for VmConfig {
     fn default() -> Self {
         Self {
             image: None,
             cpu: default_cpu(),
+            cpu_topology: None,
+            cpuset: Vec::new(),
             memory: default_memory(),
+            max_memory: None,
+            memory_slots: None,
+            hugepages: None,
             gpu_passthrough: false,
             gpu: None,
-            network: None,
+            network: Vec::new(),
+            net_limit: None,
             autostart: false,
+            depends_on: Vec::new(),
+            labels: HashMap::new(),
+            extra_qemu_args: Vec::new(),
             storage: VmStorageConfig::default(),
+            disks: Vec::new(),
             looking_glass: LookingGlassConfig::default(),
             firmware: VmFirmwareConfig::default(),
             tpm: VmTpmConfig::default(),
             compliance_profile: None,
+            snapshot_quota: SnapshotQuota::default(),
         }
     }
 }
@@ -373,6 +541,7 @@ impl Default for StoragePoolConfig {
             default_format: default_disk_format(),
             auto_create: default_create_if_missing(),
             labels: Vec::new(),
+            zfs: None,
         }
     }
 }
@@ -393,6 +562,8 @@ impl Default for UiConfig {
             container_logs_auto_refresh: default_ui_container_logs_auto_refresh(),
             container_logs_refresh_interval_seconds:
                 default_ui_container_logs_refresh_interval_seconds(),
+            watch_config: false,
+            colorblind_safe: false,
         }
     }
 }
@@ -519,6 +690,10 @@ fn default_storage_pool_type() -> StoragePoolType {
     StoragePoolType::Directory
 }
 
+fn default_zfs_compression() -> String {
+    "lz4".to_string()
+}
+
 impl VmStorageConfig {
     const DEFAULT_DIRECTORY: &str = "/var/lib/nova/disks";
 
@@ -538,6 +713,24 @@ impl VmStorageConfig {
     }
 }
 
+impl DiskCache {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiskCache::None => "none",
+            DiskCache::Directsync => "directsync",
+            DiskCache::Writethrough => "writethrough",
+            DiskCache::Writeback => "writeback",
+            DiskCache::Unsafe => "unsafe",
+        }
+    }
+
+    /// Whether I/O bypasses the host page cache, so every VM sharing the
+    /// disk sees the others' writes.
+    pub fn is_direct(&self) -> bool {
+        matches!(self, DiskCache::None | DiskCache::Directsync)
+    }
+}
+
 impl DiskFormat {
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -607,6 +800,268 @@ impl NovaConfig {
     pub fn list_storage_pools(&self) -> Vec<&String> {
         self.storage.keys().collect()
     }
+
+    /// Problems that parse fine but will bite later: memory sizes Nova can't
+    /// read and references to networks or instances that aren't defined.
+    pub fn validate(&self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        let mut check_memory = |key: String, value: &str| {
+            if parse_memory_to_bytes(value).is_err() {
+                warnings.push(ConfigWarning {
+                    key,
+                    message: format!(
+                        "invalid memory size \"{}\" (use e.g. \"8Gi\" or \"512Mi\")",
+                        value
+                    ),
+                });
+            }
+        };
+        for (name, vm) in &self.vm {
+            check_memory(format!("vm.{}.memory", name), &vm.memory);
+            if let Some(max_memory) = &vm.max_memory {
+                check_memory(format!("vm.{}.max_memory", name), max_memory);
+            }
+        }
+        for (name, container) in &self.container {
+            if let Some(limit) = &container.bolt.memory_limit {
+                check_memory(format!("container.{}.bolt.memory_limit", name), limit);
+            }
+        }
+
+        for (name, vm) in &self.vm {
+            // Host CPUs are checked when the VM is defined, on its host
+            if let Err(err) = vm_cpu::validate_pins(&vm.cpuset, vm.cpu, None) {
+                warnings.push(ConfigWarning {
+                    key: format!("vm.{}.cpuset", name),
+                    message: match err {
+                        NovaError::ConfigError(message) => message,
+                        other => other.to_string(),
+                    },
+                });
+            }
+            for nic in &vm.network {
+                if !self.network.contains_key(&nic.network) {
+                    warnings.push(ConfigWarning {
+                        key: format!("vm.{}.network", name),
+                        message: format!(
+                            "network \"{}\" is not defined under [network]; it must already exist on the host",
+                            nic.network
+                        ),
+                    });
+                }
+            }
+            let mut macs: Vec<&str> = vm
+                .network
+                .iter()
+                .filter_map(|nic| nic.mac.as_deref())
+                .collect();
+            macs.sort_by_key(|mac| mac.to_lowercase());
+            if let Some(pair) = macs
+                .windows(2)
+                .find(|pair| pair[0].eq_ignore_ascii_case(pair[1]))
+            {
+                warnings.push(ConfigWarning {
+                    key: format!("vm.{}.network", name),
+                    message: format!("MAC address {} is used by more than one NIC", pair[0]),
+                });
+            }
+        }
+        let dependencies =
+            self.vm
+                .iter()
+                .map(|(name, vm)| (format!("vm.{}", name), &vm.depends_on))
+                .chain(self.container.iter().map(|(name, container)| {
+                    (format!("container.{}", name), &container.depends_on)
+                }));
+        for (key, depends_on) in dependencies {
+            for dependency in depends_on {
+                if !self.vm.contains_key(dependency) && !self.container.contains_key(dependency) {
+                    warnings.push(ConfigWarning {
+                        key: format!("{}.depends_on", key),
+                        message: format!(
+                            "\"{}\" is not a VM or container in this file",
+                            dependency
+                        ),
+                    });
+                }
+            }
+        }
+
+        warnings.sort_by(|a, b| a.key.cmp(&b.key));
+        warnings
+    }
+
+    /// Parse a NovaFile and collect every warning: keys Nova doesn't know
+    /// (which parsing ignores) followed by [`Self::validate`].
+    pub fn check_file<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<ConfigWarning>)> {
+        let contents = fs::read_to_string(path)?;
+        let config: NovaConfig = toml::from_str(&contents)?;
+        let mut warnings = unknown_keys(&contents, &config)?;
+        warnings.extend(config.validate());
+        Ok((config, warnings))
+    }
+}
+
+/// A NovaFile problem at a dotted key such as `vm.web.memory`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning {
+    pub key: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Keys in `contents` that none of the config's fields declare, found
+/// while deserializing so that keys holding an empty or default value still
+/// count as known. `config` supplies the "did you mean" candidates.
+pub fn unknown_keys(contents: &str, config: &NovaConfig) -> Result<Vec<ConfigWarning>> {
+    let mut ignored = Vec::new();
+    let _: NovaConfig = serde_ignored::deserialize(toml::Deserializer::parse(contents)?, |path| {
+        let mut segments = Vec::new();
+        key_segments(&path, &mut segments);
+        ignored.push(segments);
+    })
+    .map_err(|e| NovaError::SerdeError(e.to_string()))?;
+
+    let known = toml::Value::try_from(config).map_err(|e| NovaError::SerdeError(e.to_string()))?;
+    let mut warnings: Vec<ConfigWarning> = ignored
+        .into_iter()
+        .filter_map(|segments| {
+            let Some((KeySegment::Key(key), parents)) = segments.split_last() else {
+                return None;
+            };
+            let siblings = parents
+                .iter()
+                .try_fold(&known, |value, segment| match segment {
+                    KeySegment::Key(key) => value.get(key.as_str()),
+                    KeySegment::Index(index) => value.get(*index),
+                })
+                .and_then(toml::Value::as_table);
+            Some(unknown_key_warning(dotted_key(&segments), key, siblings))
+        })
+        .collect();
+
+    // Internally tagged enums (such as `disks` entries) buffer their fields
+    // before picking a variant, which hides unknown ones from serde_ignored.
+    // Entries of lists are compared against what serializes back instead.
+    let source: toml::Value = toml::from_str(contents)?;
+    collect_unknown_list_keys(&source, &known, "", false, &mut warnings);
+    warnings.sort_by(|a, b| a.key.cmp(&b.key));
+    warnings.dedup_by(|a, b| a.key == b.key);
+    Ok(warnings)
+}
+
+fn unknown_key_warning(path: String, key: &str, siblings: Option<&toml::Table>) -> ConfigWarning {
+    let suggestion = siblings.and_then(|siblings| {
+        siblings
+            .keys()
+            .filter(|candidate| edit_distance(key, candidate) <= 2)
+            .min_by_key(|candidate| edit_distance(key, candidate))
+    });
+    ConfigWarning {
+        key: path,
+        message: match suggestion {
+            Some(candidate) => format!("unknown key, ignored (did you mean `{}`?)", candidate),
+            None => "unknown key, ignored".to_string(),
+        },
+    }
+}
+
+fn collect_unknown_list_keys(
+    source: &toml::Value,
+    known: &toml::Value,
+    prefix: &str,
+    in_list: bool,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    match (source, known) {
+        (toml::Value::Table(source), toml::Value::Table(known)) => {
+            for (key, value) in source {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                match known.get(key) {
+                    Some(known_value) => {
+                        collect_unknown_list_keys(value, known_value, &path, in_list, warnings)
+                    }
+                    None if in_list => warnings.push(unknown_key_warning(path, key, Some(known))),
+                    None => {}
+                }
+            }
+        }
+        (toml::Value::Array(source), toml::Value::Array(known)) => {
+            for (index, (value, known_value)) in source.iter().zip(known).enumerate() {
+                collect_unknown_list_keys(
+                    value,
+                    known_value,
+                    &format!("{}[{}]", prefix, index),
+                    true,
+                    warnings,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+enum KeySegment {
+    Key(String),
+    Index(usize),
+}
+
+fn key_segments(path: &serde_ignored::Path<'_>, segments: &mut Vec<KeySegment>) {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => {}
+        Path::Seq { parent, index } => {
+            key_segments(parent, segments);
+            segments.push(KeySegment::Index(*index));
+        }
+        Path::Map { parent, key } => {
+            key_segments(parent, segments);
+            segments.push(KeySegment::Key(key.clone()));
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => key_segments(parent, segments),
+    }
+}
+
+/// `vm.web.disks[0].bus` style key for a path.
+fn dotted_key(segments: &[KeySegment]) -> String {
+    let mut key = String::new();
+    for segment in segments {
+        match segment {
+            KeySegment::Key(name) => {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(name);
+            }
+            KeySegment::Index(index) => key.push_str(&format!("[{}]", index)),
+        }
+    }
+    key
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
 }
 
 // Parse memory string like "1Gi", "512Mi", "2G" to bytes
@@ -662,6 +1117,7 @@ mod tests {
     fn test_config_parsing() {
         let toml_str = r#"
 project = "test-lab"
+container_runtime = "podman"
 
 [vm.win11]
 image = "/var/lib/nova/images/win11.qcow2"
@@ -690,6 +1146,7 @@ dns = true
 
         let config: NovaConfig = toml_str.parse().unwrap();
         assert_eq!(config.project, Some("test-lab".to_string()));
+        assert_eq!(config.container_runtime, Some(RuntimeKind::Podman));
 
         let vm = config.get_vm("win11").unwrap();
         assert_eq!(vm.cpu, 8);
@@ -701,6 +1158,58 @@ dns = true
         assert_eq!(container.volumes, vec!["./api:/srv/api"]);
     }
 
+    #[test]
+    fn check_reports_unknown_keys_and_dangling_references() {
+        let toml_str = r#"
+[vm.web]
+cpu = 2
+memory = "4Gi"
+memroy = "8Gi"
+hugepages = false
+autostart = false
+network = "br-missing"
+depends_on = ["db"]
+
+[vm.web.firmware]
+boot_tpe = "uefi"
+
+[[vm.web.disks]]
+type = "cdrom"
+path = "/var/lib/nova/images/web-seed.iso"
+bus = "ide"
+
+[vm.idle]
+network = []
+depends_on = []
+
+[container.cache]
+capsule = "redis:7"
+
+[container.cache.bolt]
+memory_limit = "lots"
+"#;
+        let config: NovaConfig = toml_str.parse().unwrap();
+        let unknown = unknown_keys(toml_str, &config).unwrap();
+        let keys: Vec<&str> = unknown.iter().map(|w| w.key.as_str()).collect();
+        assert!(keys.contains(&"vm.web.memroy"), "{:?}", keys);
+        assert!(keys.contains(&"vm.web.firmware.boot_tpe"), "{:?}", keys);
+        assert!(keys.contains(&"vm.web.disks[0].bus"), "{:?}", keys);
+        assert_eq!(unknown.len(), 3, "{:?}", unknown);
+        let typo = unknown.iter().find(|w| w.key == "vm.web.memroy").unwrap();
+        assert!(typo.message.contains("`memory`"), "{}", typo);
+
+        let warnings = config.validate();
+        let keys: Vec<&str> = warnings.iter().map(|w| w.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "container.cache.bolt.memory_limit",
+                "vm.web.depends_on",
+                "vm.web.network"
+            ]
+        );
+    }
+
     #[test]
     fn vm_storage_defaults() {
         let storage = VmStorageConfig::default();
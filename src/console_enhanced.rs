@@ -2,8 +2,10 @@
 // Supports SPICE, VNC, and Looking Glass protocols
 
 use crate::console::{ConsoleConfig, ConsoleManager};
+use crate::gpu_passthrough;
 use crate::looking_glass::{LookingGlassConfig, LookingGlassManager, LookingGlassProfile};
 use crate::{NovaError, Result, log_info, log_warn};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Command;
@@ -37,9 +39,12 @@ pub struct UnifiedConsoleSession {
     pub last_accessed: chrono::DateTime<chrono::Utc>,
     pub active: bool,
     pub features: SessionFeatures,
+    /// Why this protocol was picked, from [`score_breakdown`]
+    #[serde(default)]
+    pub selection_reasons: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ActiveProtocol {
     LookingGlass,
     SPICE,
@@ -98,26 +103,25 @@ impl EnhancedConsoleManager {
 
         // Analyze VM to determine best protocol
         let analysis = self.analyze_vm_capabilities(vm_name).await?;
-        let selected_protocol = self.select_optimal_protocol(&analysis);
+        let selected = self.select_optimal_protocol(&analysis);
 
         log_info!(
-            "Selected protocol for '{}': {:?}",
+            "Selected protocol for '{}': {:?} ({})",
             vm_name,
-            selected_protocol
+            selected.protocol,
+            selected.reasons.join("; ")
         );
 
-        let session = match selected_protocol {
-            PreferredProtocol::LookingGlass => {
+        let mut session = match selected.protocol {
+            ActiveProtocol::LookingGlass => {
                 self.create_looking_glass_session(vm_name, &analysis)
                     .await?
             }
-            PreferredProtocol::SPICE => self.create_spice_session(vm_name, &analysis).await?,
-            PreferredProtocol::VNC => self.create_vnc_session(vm_name).await?,
-            PreferredProtocol::Auto => {
-                // Auto already resolved by select_optimal_protocol
-                self.create_spice_session(vm_name, &analysis).await?
-            }
+            ActiveProtocol::SPICE => self.create_spice_session(vm_name, &analysis).await?,
+            ActiveProtocol::VNC => self.create_vnc_session(vm_name).await?,
+            ActiveProtocol::Serial => self.create_serial_session(vm_name),
         };
+        session.selection_reasons = selected.reasons;
 
         // Start performance monitoring if enabled
         if self.config.performance_monitoring {
@@ -155,6 +159,14 @@ impl EnhancedConsoleManager {
         };
 
         let lg_config = profile.to_config();
+        self.looking_glass_manager
+            .verify_shmem(vm_name, &lg_config)
+            .map_err(|problems| {
+                NovaError::ConfigError(format!(
+                    "Looking Glass shared memory for '{}' is not usable:\n{}",
+                    vm_name, problems
+                ))
+            })?;
         self.looking_glass_manager
             .register_config(vm_name.to_string(), lg_config.clone());
 
@@ -187,6 +199,7 @@ impl EnhancedConsoleManager {
             last_accessed: chrono::Utc::now(),
             active: true,
             features,
+            selection_reasons: Vec::new(),
         })
     }
 
@@ -229,6 +242,7 @@ impl EnhancedConsoleManager {
             last_accessed: chrono::Utc::now(),
             active: true,
             features,
+            selection_reasons: Vec::new(),
         })
     }
 
@@ -270,9 +284,40 @@ impl EnhancedConsoleManager {
             last_accessed: chrono::Utc::now(),
             active: true,
             features,
+            selection_reasons: Vec::new(),
         })
     }
 
+    fn create_serial_session(&self, vm_name: &str) -> UnifiedConsoleSession {
+        log_info!("Using serial console for VM: {}", vm_name);
+
+        UnifiedConsoleSession {
+            vm_name: vm_name.to_string(),
+            session_id: format!("serial-{}-{}", vm_name, chrono::Utc::now().timestamp()),
+            protocol_used: ActiveProtocol::Serial,
+            connection_info: ConnectionDetails {
+                host: "localhost".to_string(),
+                port: 0,
+                protocol: "serial".to_string(),
+                viewer_command: format!("virsh console {}", vm_name),
+                shmem_path: None,
+            },
+            performance_score: 30.0,
+            created_at: chrono::Utc::now(),
+            last_accessed: chrono::Utc::now(),
+            active: true,
+            features: SessionFeatures {
+                clipboard_sync: false,
+                audio_enabled: false,
+                multi_monitor: false,
+                hardware_acceleration: false,
+                usb_redirect: false,
+                low_latency: true,
+            },
+            selection_reasons: Vec::new(),
+        }
+    }
+
     async fn analyze_vm_capabilities(&self, vm_name: &str) -> Result<VmAnalysis> {
         log_info!("Analyzing VM capabilities: {}", vm_name);
 
@@ -304,12 +349,12 @@ impl EnhancedConsoleManager {
             .args(["dumpxml", vm_name])
             .output()
             .await
+            && output.status.success()
         {
-            let xml = String::from_utf8_lossy(&output.stdout);
-            analysis.has_gpu = xml.contains("<hostdev") && xml.contains("type='pci'");
-            analysis.has_looking_glass = xml.contains("looking-glass") || xml.contains("ivshmem");
-            analysis.has_spice = xml.contains("<graphics type='spice'");
-            analysis.has_vnc = xml.contains("<graphics type='vnc'");
+            analysis.apply_domain_xml(
+                &String::from_utf8_lossy(&output.stdout),
+                gpu_passthrough::is_display_device,
+            );
         }
 
         // Check guest agent
@@ -319,40 +364,50 @@ impl EnhancedConsoleManager {
         analysis.supports_multi_monitor = analysis.has_gpu || analysis.has_spice;
 
         log_info!(
-            "VM '{}': {} cores, {}MB, GPU={}, LG={}, SPICE={}, VNC={}",
+            "VM '{}': {} cores, {}MB, GPU={}, LG={}, SPICE={}, VNC={}, video={}, serial={}",
             vm_name,
             analysis.cpu_cores,
             analysis.memory_mb,
             analysis.has_gpu,
             analysis.has_looking_glass,
             analysis.has_spice,
-            analysis.has_vnc
+            analysis.has_vnc,
+            analysis.video_model.as_deref().unwrap_or("none"),
+            analysis.has_serial
         );
 
         Ok(analysis)
     }
 
-    fn select_optimal_protocol(&self, analysis: &VmAnalysis) -> PreferredProtocol {
-        match &self.config.preferred_protocol {
-            PreferredProtocol::Auto => {
-                // Looking Glass for GPU passthrough VMs with IVSHMEM configured
-                if analysis.has_looking_glass && analysis.has_gpu {
-                    if self.looking_glass_manager.check_client_installed() {
-                        return PreferredProtocol::LookingGlass;
-                    }
-                    log_warn!("Looking Glass configured but client not installed");
-                }
-
-                // SPICE for VMs with SPICE graphics
-                if analysis.has_spice && analysis.supports_guest_agent {
-                    return PreferredProtocol::SPICE;
-                }
+    fn select_optimal_protocol(&self, analysis: &VmAnalysis) -> ProtocolScore {
+        let forced = match &self.config.preferred_protocol {
+            PreferredProtocol::Auto => None,
+            PreferredProtocol::LookingGlass => Some(ActiveProtocol::LookingGlass),
+            PreferredProtocol::SPICE => Some(ActiveProtocol::SPICE),
+            PreferredProtocol::VNC => Some(ActiveProtocol::VNC),
+        };
+        if let Some(protocol) = forced {
+            return ProtocolScore {
+                protocol,
+                score: 0,
+                available: true,
+                reasons: vec![format!("preferred_protocol is set to {:?}", protocol)],
+            };
+        }
 
-                // VNC as universal fallback
-                PreferredProtocol::VNC
-            }
-            other => other.clone(),
+        let breakdown = score_breakdown(
+            analysis,
+            self.looking_glass_manager.check_client_installed(),
+        );
+        if let Some(looking_glass) = breakdown.iter().find(|score| {
+            score.protocol == ActiveProtocol::LookingGlass && score.available && score.score < 100
+        }) {
+            log_warn!(
+                "Looking Glass configured but not preferred: {}",
+                looking_glass.reasons.join("; ")
+            );
         }
+        pick_protocol(breakdown)
     }
 
     async fn check_guest_agent(&self, vm_name: &str) -> bool {
@@ -501,16 +556,45 @@ impl EnhancedConsoleManager {
     }
 }
 
+/// Console-relevant facts about a VM, mostly from its domain XML.
 #[derive(Debug, Clone)]
-struct VmAnalysis {
-    cpu_cores: u32,
-    memory_mb: u64,
-    has_gpu: bool,
-    has_looking_glass: bool,
-    has_spice: bool,
-    has_vnc: bool,
-    supports_guest_agent: bool,
-    supports_multi_monitor: bool,
+pub struct VmAnalysis {
+    pub cpu_cores: u32,
+    pub memory_mb: u64,
+    /// Display-class PCI device passed through with vfio
+    pub has_gpu: bool,
+    /// IVSHMEM shared memory device for Looking Glass
+    pub has_looking_glass: bool,
+    pub has_spice: bool,
+    pub has_vnc: bool,
+    /// Emulated video model (`qxl`, `virtio`, `vga`, `none`)
+    pub video_model: Option<String>,
+    pub has_serial: bool,
+    pub supports_guest_agent: bool,
+    pub supports_multi_monitor: bool,
+}
+
+impl VmAnalysis {
+    /// Fill in the device facts from `virsh dumpxml` output. `is_display`
+    /// tells whether a host PCI address is a display controller, so passed
+    /// through NICs, NVMe drives or USB controllers don't count as a GPU.
+    pub fn apply_domain_xml(&mut self, xml: &str, is_display: impl Fn(&str) -> bool) {
+        let video = Regex::new(r"(?s)<video>.*?<model type='(\w+)'").expect("valid video pattern");
+        self.has_gpu = gpu_passthrough::hostdev_pci_addresses(xml)
+            .iter()
+            .any(|address| is_display(address));
+        self.has_looking_glass = xml.contains("looking-glass") || xml.contains("ivshmem");
+        self.has_spice = xml.contains("<graphics type='spice'");
+        self.has_vnc = xml.contains("<graphics type='vnc'");
+        self.video_model = video.captures(xml).map(|caps| caps[1].to_string());
+        self.has_serial = xml.contains("<serial") || xml.contains("<console");
+    }
+
+    /// No graphical display to connect a viewer to.
+    pub fn is_headless(&self) -> bool {
+        (!self.has_spice && !self.has_vnc && !self.has_looking_glass)
+            || (self.video_model.as_deref() == Some("none") && !self.has_looking_glass)
+    }
 }
 
 impl Default for VmAnalysis {
@@ -522,12 +606,139 @@ impl Default for VmAnalysis {
             has_looking_glass: false,
             has_spice: true,
             has_vnc: true,
+            video_model: None,
+            has_serial: false,
             supports_guest_agent: false,
             supports_multi_monitor: false,
         }
     }
 }
 
+/// How well one protocol fits a VM, with the reasons behind the score.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProtocolScore {
+    pub protocol: ActiveProtocol,
+    pub score: u32,
+    /// Whether the VM has the devices the protocol needs
+    pub available: bool,
+    pub reasons: Vec<String>,
+}
+
+/// Score every console protocol for a VM, best first.
+///
+/// Looking Glass wins for vfio GPUs with an IVSHMEM device, SPICE for
+/// emulated displays (QXL especially), VNC is the fallback, and serial is
+/// preferred only when the VM has no display at all.
+pub fn score_breakdown(analysis: &VmAnalysis, looking_glass_client: bool) -> Vec<ProtocolScore> {
+    let mut scores = Vec::with_capacity(4);
+
+    let mut looking_glass = ProtocolScore {
+        protocol: ActiveProtocol::LookingGlass,
+        score: 0,
+        available: analysis.has_gpu && analysis.has_looking_glass,
+        reasons: Vec::new(),
+    };
+    if !analysis.has_gpu {
+        looking_glass
+            .reasons
+            .push("no vfio GPU passthrough".to_string());
+    }
+    if !analysis.has_looking_glass {
+        looking_glass
+            .reasons
+            .push("no Looking Glass IVSHMEM device".to_string());
+    }
+    if looking_glass.available {
+        looking_glass.score = 100;
+        looking_glass
+            .reasons
+            .push("vfio GPU with Looking Glass shared memory".to_string());
+        if !looking_glass_client {
+            looking_glass.score -= 60;
+            looking_glass
+                .reasons
+                .push("looking-glass-client is not installed".to_string());
+        }
+    }
+    scores.push(looking_glass);
+
+    let mut spice = ProtocolScore {
+        protocol: ActiveProtocol::SPICE,
+        score: 0,
+        available: analysis.has_spice,
+        reasons: Vec::new(),
+    };
+    if analysis.has_spice {
+        spice.score = 60;
+        spice.reasons.push("SPICE graphics configured".to_string());
+        if analysis.video_model.as_deref() == Some("qxl") {
+            spice.score += 20;
+            spice.reasons.push("emulated QXL video".to_string());
+        }
+        if analysis.supports_guest_agent {
+            spice.score += 10;
+            spice
+                .reasons
+                .push("guest agent for clipboard and resizing".to_string());
+        }
+    } else {
+        spice.reasons.push("no SPICE graphics device".to_string());
+    }
+    scores.push(spice);
+
+    let mut vnc = ProtocolScore {
+        protocol: ActiveProtocol::VNC,
+        score: 0,
+        available: analysis.has_vnc,
+        reasons: Vec::new(),
+    };
+    if analysis.has_vnc {
+        vnc.score = 40;
+        vnc.reasons
+            .push("VNC graphics configured (universal fallback)".to_string());
+    } else {
+        vnc.reasons.push("no VNC graphics device".to_string());
+    }
+    scores.push(vnc);
+
+    let headless = analysis.is_headless();
+    let mut serial = ProtocolScore {
+        protocol: ActiveProtocol::Serial,
+        score: 0,
+        available: analysis.has_serial || headless,
+        reasons: Vec::new(),
+    };
+    if headless {
+        serial.score = 90;
+        serial
+            .reasons
+            .push("headless: no graphical display".to_string());
+    } else if analysis.has_serial {
+        serial.score = 10;
+        serial.reasons.push("serial console available".to_string());
+    } else {
+        serial.reasons.push("no serial console".to_string());
+    }
+    scores.push(serial);
+
+    scores.sort_by(|a, b| b.available.cmp(&a.available).then(b.score.cmp(&a.score)));
+    scores
+}
+
+/// Best available entry of a [`score_breakdown`], falling back to the serial
+/// console when nothing is usable.
+pub fn pick_protocol(breakdown: Vec<ProtocolScore>) -> ProtocolScore {
+    breakdown
+        .into_iter()
+        .find(|score| score.available)
+        .unwrap_or_else(|| ProtocolScore {
+            protocol: ActiveProtocol::Serial,
+            score: 0,
+            available: false,
+            reasons: vec!["no console device found; trying `virsh console`".to_string()],
+        })
+}
+
 impl Default for EnhancedConsoleConfig {
     fn default() -> Self {
         Self {
@@ -539,3 +750,187 @@ impl Default for EnhancedConsoleConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(xml: &str, guest_agent: bool) -> VmAnalysis {
+        let mut analysis = VmAnalysis {
+            supports_guest_agent: guest_agent,
+            ..VmAnalysis::default()
+        };
+        // Only the test GPU at 01:00.0 is a display controller
+        analysis.apply_domain_xml(xml, |address| address == "0000:01:00.0");
+        analysis
+    }
+
+    #[test]
+    fn protocol_follows_gpu_and_display_devices() {
+        let passthrough = profile(
+            "<devices><hostdev mode='subsystem' type='pci' managed='yes'>
+             <source><address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/></source>
+             </hostdev>
+             <shmem name='looking-glass'><model type='ivshmem-plain'/></shmem>
+             <graphics type='spice' autoport='yes'/>
+             <video><model type='none'/></video></devices>",
+            false,
+        );
+        let best = pick_protocol(score_breakdown(&passthrough, true));
+        assert_eq!(best.protocol, ActiveProtocol::LookingGlass);
+        // Without the client the emulated SPICE display is the better bet
+        let without_client = pick_protocol(score_breakdown(&passthrough, false));
+        assert_eq!(without_client.protocol, ActiveProtocol::SPICE);
+
+        let qxl = profile(
+            "<devices><graphics type='spice' autoport='yes'/><graphics type='vnc' port='-1'/>
+             <video><model type='qxl' ram='65536'/></video></devices>",
+            true,
+        );
+        let breakdown = score_breakdown(&qxl, true);
+        assert_eq!(breakdown[0].protocol, ActiveProtocol::SPICE);
+        assert_eq!(breakdown[0].score, 90);
+        assert!(breakdown[0].reasons.iter().any(|r| r.contains("QXL")));
+        assert_eq!(breakdown[1].protocol, ActiveProtocol::VNC);
+
+        let vnc_only = profile(
+            "<devices><graphics type='vnc' port='-1'/><video><model type='vga'/></video></devices>",
+            false,
+        );
+        assert_eq!(
+            pick_protocol(score_breakdown(&vnc_only, true)).protocol,
+            ActiveProtocol::VNC
+        );
+
+        let headless = profile(
+            "<devices><serial type='pty'/><console type='pty'/></devices>",
+            false,
+        );
+        let best = pick_protocol(score_breakdown(&headless, true));
+        assert_eq!(best.protocol, ActiveProtocol::Serial);
+        assert!(best.reasons[0].contains("headless"));
+    }
+
+    #[test]
+    fn video_none_hides_spice_and_vnc_behind_the_serial_console() {
+        let analysis = profile(
+            "<devices><graphics type='spice' autoport='yes'/><graphics type='vnc' port='-1'/>
+             <video><model type='none'/></video><serial type='pty'/></devices>",
+            true,
+        );
+        assert!(analysis.is_headless());
+        let best = pick_protocol(score_breakdown(&analysis, true));
+        assert_eq!(best.protocol, ActiveProtocol::Serial);
+        assert_eq!(best.score, 90);
+
+        // Looking Glass still shows the guest even without an emulated display
+        let passthrough = profile(
+            "<devices><hostdev mode='subsystem' type='pci' managed='yes'>
+             <source><address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/></source>
+             </hostdev>
+             <shmem name='looking-glass'/><video><model type='none'/></video></devices>",
+            false,
+        );
+        assert!(!passthrough.is_headless());
+    }
+
+    #[test]
+    fn only_display_hostdevs_count_as_a_gpu() {
+        let nic = profile(
+            "<devices><hostdev mode='subsystem' type='pci' managed='yes'>
+             <source><address domain='0x0000' bus='0x03' slot='0x00' function='0x0'/></source>
+             </hostdev>
+             <graphics type='spice' autoport='yes'/></devices>",
+            false,
+        );
+        assert!(!nic.has_gpu);
+
+        let gpu = profile(
+            "<devices><hostdev mode='subsystem' type='pci' managed='yes'>
+             <source><address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/></source>
+             </hostdev></devices>",
+            false,
+        );
+        assert!(gpu.has_gpu);
+    }
+
+    #[test]
+    fn guest_agent_raises_the_spice_score() {
+        let xml = "<devices><graphics type='spice' autoport='yes'/>\
+                   <video><model type='virtio'/></video></devices>";
+        let without_agent = score_breakdown(&profile(xml, false), true);
+        let with_agent = score_breakdown(&profile(xml, true), true);
+        assert_eq!(without_agent[0].protocol, ActiveProtocol::SPICE);
+        assert_eq!(without_agent[0].score, 60);
+        assert_eq!(with_agent[0].score, 70);
+        assert!(
+            with_agent[0]
+                .reasons
+                .iter()
+                .any(|r| r.contains("guest agent"))
+        );
+    }
+
+    #[test]
+    fn looking_glass_without_client_is_kept_but_ranked_lower() {
+        let passthrough = profile(
+            "<devices><hostdev mode='subsystem' type='pci' managed='yes'>
+             <source><address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/></source>
+             </hostdev>
+             <shmem name='looking-glass'/><graphics type='vnc' port='-1'/>
+             <video><model type='vga'/></video></devices>",
+            false,
+        );
+        let breakdown = score_breakdown(&passthrough, false);
+        let looking_glass = breakdown
+            .iter()
+            .find(|score| score.protocol == ActiveProtocol::LookingGlass)
+            .unwrap();
+        assert!(looking_glass.available);
+        assert_eq!(looking_glass.score, 40);
+        assert!(
+            looking_glass
+                .reasons
+                .iter()
+                .any(|r| r.contains("looking-glass-client"))
+        );
+        // Unavailable protocols sort after every available one
+        let last_available = breakdown.iter().rposition(|score| score.available).unwrap();
+        assert!(
+            breakdown[last_available + 1..]
+                .iter()
+                .all(|score| !score.available)
+        );
+    }
+
+    #[test]
+    fn nothing_available_falls_back_to_virsh_console() {
+        let breakdown = vec![ProtocolScore {
+            protocol: ActiveProtocol::VNC,
+            score: 40,
+            available: false,
+            reasons: vec!["no VNC graphics device".to_string()],
+        }];
+        let best = pick_protocol(breakdown);
+        assert_eq!(best.protocol, ActiveProtocol::Serial);
+        assert!(!best.available);
+        assert!(best.reasons[0].contains("virsh console"));
+        assert_eq!(pick_protocol(Vec::new()).protocol, ActiveProtocol::Serial);
+    }
+
+    #[test]
+    fn preferred_protocol_overrides_the_scores() {
+        let analysis = profile(
+            "<devices><graphics type='spice' autoport='yes'/><graphics type='vnc' port='-1'/>
+             <video><model type='qxl'/></video></devices>",
+            true,
+        );
+        let manager = EnhancedConsoleManager::new(EnhancedConsoleConfig {
+            preferred_protocol: PreferredProtocol::VNC,
+            ..EnhancedConsoleConfig::default()
+        });
+        let choice = manager.select_optimal_protocol(&analysis);
+        assert_eq!(choice.protocol, ActiveProtocol::VNC);
+        assert!(choice.reasons[0].contains("preferred_protocol"));
+    }
+}
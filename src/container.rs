@@ -2,19 +2,34 @@ use crate::{
     NovaError, Result,
     bolt_runtime::BoltRuntime,
     config::ContainerConfig as NovaContainerConfig,
+    container_health::{self, ContainerRestartInfo, CrashLoopPolicy, RestartHistory},
+    container_ports::{self, PortRemap},
+    container_runtime::ContainerStatus,
     container_runtime::{
-        ContainerConfig, ContainerInfo, ContainerRuntime as Runtime, ContainerStats, RestartPolicy,
+        ContainerConfig, ContainerInfo, ContainerRuntime as Runtime, ContainerRuntimeError,
+        ContainerStats, DiskUsage, PortMapping, PruneOptions, RestartPolicy, RuntimeKind,
     },
+    container_scaling::{self, ScalingAdvice, ScalingThresholds, StatsHistory, StatsSample},
     docker_runtime::DockerRuntime,
-    instance::Instance,
-    log_error, log_info, log_warn,
+    exec,
+    instance::{Instance, InstanceStatus, InstanceType},
+    log_debug, log_error, log_info, log_warn,
+    naming::{self, NameKind},
 };
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 /// Container manager with runtime selection (Bolt > Docker > Fallback)
 pub struct ContainerManager {
     runtime: Arc<dyn Runtime>,
     runtime_name: String,
+    stats_history: Mutex<HashMap<String, StatsHistory>>,
+    scaling_thresholds: ScalingThresholds,
+    restart_history: Mutex<HashMap<String, RestartHistory>>,
+    restart_history_path: PathBuf,
+    crash_looping: Mutex<HashMap<String, bool>>,
+    crash_loop_policy: CrashLoopPolicy,
 }
 
 impl ContainerManager {
@@ -38,12 +53,95 @@ impl ContainerManager {
             }
         };
 
+        Self::from_runtime(runtime, runtime_name)
+    }
+
+    /// Use `kind` instead of auto-detecting, e.g. on hosts with both Docker
+    /// and Podman. `None` auto-detects like [`Self::new`].
+    pub fn with_runtime(kind: Option<RuntimeKind>) -> Result<Self> {
+        let Some(kind) = kind else {
+            return Ok(Self::new());
+        };
+        let runtime = Self::runtime_for(kind);
+        if !runtime.is_available() {
+            return Err(NovaError::ConfigError(format!(
+                "Container runtime '{}' was requested but is not available on this host; \
+                 install it or pick another with --runtime or `container_runtime` in the NovaFile",
+                kind
+            )));
+        }
+        log_info!(
+            "Using {} container runtime (selected explicitly)",
+            runtime.name()
+        );
+        let runtime_name = runtime.name().to_string();
+        Ok(Self::from_runtime(runtime, runtime_name))
+    }
+
+    /// Like [`Self::with_runtime`], but keeps an explicitly selected runtime
+    /// that is missing instead of failing, so long-running callers such as
+    /// the GUI report it unavailable rather than manage another runtime's
+    /// containers.
+    pub fn selected(kind: Option<RuntimeKind>) -> Self {
+        let Some(kind) = kind else {
+            return Self::new();
+        };
+        let runtime = Self::runtime_for(kind);
+        if !runtime.is_available() {
+            log_warn!(
+                "Container runtime '{}' was selected but is not available on this host",
+                kind
+            );
+        }
+        let runtime_name = runtime.name().to_string();
+        Self::from_runtime(runtime, runtime_name)
+    }
+
+    fn runtime_for(kind: RuntimeKind) -> Arc<dyn Runtime> {
+        match kind {
+            RuntimeKind::Bolt => Arc::new(BoltRuntime::new()),
+            RuntimeKind::Docker => Arc::new(DockerRuntime::new()),
+            RuntimeKind::Podman => Arc::new(DockerRuntime::podman()),
+        }
+    }
+
+    fn from_runtime(runtime: Arc<dyn Runtime>, runtime_name: String) -> Self {
+        let restart_history_path = PathBuf::from(container_health::RESTART_HISTORY_PATH);
         Self {
             runtime,
             runtime_name,
+            stats_history: Mutex::new(HashMap::new()),
+            scaling_thresholds: ScalingThresholds::default(),
+            restart_history: Mutex::new(container_health::load_restart_history(
+                &restart_history_path,
+            )),
+            restart_history_path,
+            crash_looping: Mutex::new(HashMap::new()),
+            crash_loop_policy: CrashLoopPolicy::default(),
         }
     }
 
+    /// Use the `[container_scaling]` thresholds for [`Self::scaling_hint`].
+    pub fn set_scaling_thresholds(&mut self, thresholds: ScalingThresholds) {
+        self.scaling_thresholds = thresholds;
+    }
+
+    /// Keep restart histories in `path` instead of
+    /// [`container_health::RESTART_HISTORY_PATH`], replacing any loaded from
+    /// the default file.
+    pub fn with_restart_history_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.restart_history_path = path.into();
+        self.restart_history = Mutex::new(container_health::load_restart_history(
+            &self.restart_history_path,
+        ));
+        self
+    }
+
+    /// Use the `[container_crash_loop]` policy when reporting container status.
+    pub fn set_crash_loop_policy(&mut self, policy: CrashLoopPolicy) {
+        self.crash_loop_policy = policy;
+    }
+
     /// Get the active runtime name
     pub fn get_runtime_name(&self) -> &str {
         &self.runtime_name
@@ -55,24 +153,9 @@ impl ContainerManager {
         config: Option<&NovaContainerConfig>,
     ) -> Result<()> {
         log_info!("Starting container: {}", name);
+        naming::validate(NameKind::Container, name)?;
 
-        let nova_config = config.cloned().unwrap_or_default();
-
-        // Convert Nova config to runtime config
-        let runtime_config = ContainerConfig {
-            capsule: nova_config
-                .capsule
-                .unwrap_or_else(|| "ubuntu:latest".to_string()),
-            ports: Vec::new(),
-            volumes: nova_config.volumes,
-            env: nova_config.env,
-            network: nova_config.network,
-            gpu_passthrough: nova_config.bolt.gpu_access,
-            memory_mb: None,
-            cpus: None,
-            restart_policy: RestartPolicy::No,
-            detach: true,
-        };
+        let runtime_config = Self::runtime_config(config.cloned().unwrap_or_default());
 
         // Use runtime to start container
         let container_id = self
@@ -88,6 +171,62 @@ impl ContainerManager {
         Ok(())
     }
 
+    /// Create and start a new container, as the GUI's "Create Container"
+    /// dialog does. Unlike [`Self::start_container`], the name must not be
+    /// taken yet, ports are checked up front, and a runtime failure keeps
+    /// the runtime's own message. Returns the new container's ID.
+    pub async fn create_container(
+        &self,
+        name: &str,
+        config: &NovaContainerConfig,
+    ) -> Result<String> {
+        log_info!("Creating container: {}", name);
+        naming::validate(NameKind::Container, name)?;
+        for spec in &config.ports {
+            container_ports::parse_port_spec(spec)?;
+        }
+        let existing = self.runtime.list_containers(true).await.unwrap_or_default();
+        if existing.iter().any(|container| container.name == name) {
+            return Err(NovaError::ConfigError(format!(
+                "A container named '{}' already exists",
+                name
+            )));
+        }
+
+        let runtime_config = Self::runtime_config(config.clone());
+        let container_id = self
+            .runtime
+            .run_container(&runtime_config.capsule, Some(name), &runtime_config)
+            .await
+            .map_err(|e| {
+                NovaError::ConfigError(format!(
+                    "{} could not create container '{}': {}",
+                    self.runtime_name, name, e
+                ))
+            })?;
+
+        log_info!("Container '{}' created with ID: {}", name, container_id);
+        Ok(container_id)
+    }
+
+    /// Runtime settings for a NovaFile container entry.
+    fn runtime_config(nova_config: NovaContainerConfig) -> ContainerConfig {
+        ContainerConfig {
+            capsule: nova_config
+                .capsule
+                .unwrap_or_else(|| "ubuntu:latest".to_string()),
+            ports: nova_config.ports,
+            volumes: nova_config.volumes,
+            env: nova_config.env,
+            network: nova_config.network,
+            gpu_passthrough: nova_config.bolt.gpu_access,
+            memory_mb: None,
+            cpus: None,
+            restart_policy: nova_config.restart.unwrap_or(RestartPolicy::No),
+            detach: true,
+        }
+    }
+
     pub async fn stop_container(&self, name: &str) -> Result<()> {
         log_info!("Stopping container: {}", name);
 
@@ -115,41 +254,162 @@ impl ContainerManager {
         Ok(())
     }
 
+    /// Recreate a container with new port mappings, keeping its name, image,
+    /// bind and named volumes, environment and limits. Runtimes fix ports at
+    /// creation, so the container is stopped, removed and run again: its ID
+    /// changes and anything in anonymous volumes is lost. The new mappings
+    /// are checked for conflicts before anything is touched, and the old
+    /// container is recreated if the new one fails to start.
+    pub async fn remap_ports(&self, name: &str, ports: &[String]) -> Result<PortRemap> {
+        if ports.is_empty() {
+            return Err(NovaError::ConfigError(
+                "No port mappings given (use --ports host:container)".to_string(),
+            ));
+        }
+        let requested = ports
+            .iter()
+            .map(|spec| container_ports::parse_port_spec(spec))
+            .collect::<Result<Vec<_>>>()?;
+
+        let container = self.inspect_container(name).await?;
+        let old_config = self.runtime.container_config(name).await.map_err(|e| {
+            log_error!(
+                "Failed to read configuration of container '{}': {:?}",
+                name,
+                e
+            );
+            NovaError::ContainerNotFound(name.to_string())
+        })?;
+        let current: Vec<_> = old_config
+            .ports
+            .iter()
+            .filter_map(|spec| container_ports::parse_port_spec(spec).ok())
+            .collect();
+
+        let others: Vec<_> = self
+            .runtime
+            .list_containers(true)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|other| other.id != container.id)
+            .map(|other| (other.name, other.ports))
+            .collect();
+        let mut conflicts = container_ports::find_conflicts(name, &requested, &others);
+        for mapping in &requested {
+            let held_by_self = current.iter().any(|port| {
+                port.host_port == mapping.host_port && port.protocol == mapping.protocol
+            });
+            if !held_by_self && container_ports::host_port_in_use(mapping) {
+                conflicts.push(format!(
+                    "host port {} is already in use on this host",
+                    container_ports::port_spec(mapping)
+                ));
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(NovaError::ConfigError(format!(
+                "Cannot remap ports of container '{}': {}",
+                name,
+                conflicts.join("; ")
+            )));
+        }
+
+        log_warn!(
+            "Recreating container '{}' to change its ports: the container ID will change and data in anonymous volumes will be lost",
+            name
+        );
+
+        if container.status == ContainerStatus::Running {
+            self.stop_container(name).await?;
+        }
+        self.remove_container(name, true).await?;
+
+        let new_config = ContainerConfig {
+            ports: ports.iter().map(|spec| spec.trim().to_string()).collect(),
+            ..old_config.clone()
+        };
+        let new_id = match self
+            .runtime
+            .run_container(&new_config.capsule, Some(name), &new_config)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                log_error!("Failed to recreate container '{}': {:?}", name, e);
+                // Best effort: bring back the container as it was
+                let _ = self.runtime.remove_container(name, true).await;
+                match self
+                    .runtime
+                    .run_container(&old_config.capsule, Some(name), &old_config)
+                    .await
+                {
+                    Ok(id) => {
+                        log_warn!(
+                            "Restored container '{}' with its previous ports (new ID {})",
+                            name,
+                            id
+                        );
+                    }
+                    Err(e) => {
+                        log_error!(
+                            "Failed to restore container '{}' with its previous ports {:?}: {:?}",
+                            name,
+                            old_config.ports,
+                            e
+                        );
+                    }
+                }
+                return Err(NovaError::SystemCommandFailed);
+            }
+        };
+
+        log_info!(
+            "Container '{}' recreated with ports {} (ID {} -> {})",
+            name,
+            new_config.ports.join(", "),
+            container.id,
+            new_id
+        );
+        Ok(PortRemap {
+            container: name.to_string(),
+            old_id: container.id,
+            new_id,
+            ports: requested,
+        })
+    }
+
+    /// Host ports published by each running container.
+    pub async fn published_ports(&self) -> Vec<(String, Vec<PortMapping>)> {
+        match self.runtime.list_containers(false).await {
+            Ok(containers) => containers
+                .into_iter()
+                .filter(|container| !container.ports.is_empty())
+                .map(|container| (container.name, container.ports))
+                .collect(),
+            Err(e) => {
+                log_warn!("Failed to list containers: {:?}", e);
+                Vec::new()
+            }
+        }
+    }
+
     /// Async version of list_containers (for CLI use)
     pub async fn list_containers_async(&self) -> Vec<Instance> {
         match self.runtime.list_containers(true).await {
             Ok(containers) => {
-                // Convert ContainerInfo to Instance
-                containers
-                    .iter()
-                    .map(|c| {
-                        let mut instance =
-                            Instance::new(c.name.clone(), crate::instance::InstanceType::Container);
-                        instance.update_status(match c.status {
-                            crate::container_runtime::ContainerStatus::Running => {
-                                crate::instance::InstanceStatus::Running
-                            }
-                            crate::container_runtime::ContainerStatus::Stopped => {
-                                crate::instance::InstanceStatus::Stopped
-                            }
-                            crate::container_runtime::ContainerStatus::Paused => {
-                                crate::instance::InstanceStatus::Suspended
-                            }
-                            crate::container_runtime::ContainerStatus::Starting => {
-                                crate::instance::InstanceStatus::Starting
-                            }
-                            crate::container_runtime::ContainerStatus::Restarting => {
-                                crate::instance::InstanceStatus::Starting
-                            }
-                            _ => crate::instance::InstanceStatus::Error,
-                        });
-                        if let Some(pid) = c.pid {
-                            instance.set_pid(Some(pid));
-                        }
-                        instance.network = c.network.clone();
-                        instance
-                    })
-                    .collect()
+                let mut instances = Vec::with_capacity(containers.len());
+                for mut container in containers {
+                    // `ps` output has no restart counter, so look closer at
+                    // containers that are restarting or already being tracked
+                    if self.needs_restart_details(&container)
+                        && let Ok(details) = self.runtime.inspect_container(&container.id).await
+                    {
+                        container = details;
+                    }
+                    instances.push(self.container_instance(&container));
+                }
+                instances
             }
             Err(e) => {
                 log_warn!("Failed to list containers: {:?}", e);
@@ -188,35 +448,7 @@ impl ContainerManager {
         match runtime {
             Ok(handle) => {
                 match handle.block_on(async { self.runtime.inspect_container(name).await }) {
-                    Ok(container) => {
-                        let mut instance = Instance::new(
-                            container.name.clone(),
-                            crate::instance::InstanceType::Container,
-                        );
-                        instance.update_status(match container.status {
-                            crate::container_runtime::ContainerStatus::Running => {
-                                crate::instance::InstanceStatus::Running
-                            }
-                            crate::container_runtime::ContainerStatus::Stopped => {
-                                crate::instance::InstanceStatus::Stopped
-                            }
-                            crate::container_runtime::ContainerStatus::Paused => {
-                                crate::instance::InstanceStatus::Suspended
-                            }
-                            crate::container_runtime::ContainerStatus::Starting => {
-                                crate::instance::InstanceStatus::Starting
-                            }
-                            crate::container_runtime::ContainerStatus::Restarting => {
-                                crate::instance::InstanceStatus::Starting
-                            }
-                            _ => crate::instance::InstanceStatus::Error,
-                        });
-                        if let Some(pid) = container.pid {
-                            instance.set_pid(Some(pid));
-                        }
-                        instance.network = container.network.clone();
-                        Some(instance)
-                    }
+                    Ok(container) => Some(self.container_instance(&container)),
                     Err(_) => None,
                 }
             }
@@ -231,40 +463,188 @@ impl ContainerManager {
         })
     }
 
+    /// Collect current stats and record them in the container's history.
     pub async fn container_stats(&self, name: &str) -> Result<ContainerStats> {
-        self.runtime.get_stats(name).await.map_err(|e| {
+        let stats = self.runtime.get_stats(name).await.map_err(|e| {
             log_error!("Failed to collect stats for container '{}': {:?}", name, e);
             NovaError::SystemCommandFailed
-        })
+        })?;
+        self.record_stats(name, &stats);
+        Ok(stats)
     }
 
-    pub async fn get_container_status(
-        &self,
-        name: &str,
-    ) -> Result<crate::instance::InstanceStatus> {
+    /// [`Self::container_stats`] for every running container, sampled with a
+    /// single runtime call and sorted by name.
+    pub async fn all_container_stats(&self) -> Result<Vec<(String, ContainerStats)>> {
+        let mut all = self.runtime.get_all_stats().await.map_err(|e| {
+            log_error!("Failed to collect container stats: {:?}", e);
+            NovaError::SystemCommandFailed
+        })?;
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, stats) in &all {
+            self.record_stats(name, stats);
+        }
+        Ok(all)
+    }
+
+    fn record_stats(&self, name: &str, stats: &ContainerStats) {
+        self.stats_history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(name.to_string())
+            .or_default()
+            .push(StatsSample {
+                at: chrono::Utc::now(),
+                cpu_percent: stats.cpu_usage_percent,
+                memory_usage_mb: stats.memory_usage_mb,
+                memory_limit_mb: stats.memory_limit_mb,
+            });
+    }
+
+    /// Advisory scale up/down hints from the recorded stats history.
+    ///
+    /// CPU usage is measured against `bolt.cpu_limit` from `config` when set,
+    /// otherwise against all host cores.
+    pub fn scaling_hint(&self, name: &str, config: Option<&NovaContainerConfig>) -> ScalingAdvice {
+        let cpus = config
+            .and_then(|config| config.bolt.cpu_limit.as_deref())
+            .and_then(|limit| limit.trim().parse::<f64>().ok())
+            .filter(|limit| *limit > 0.0)
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|cores| cores.get() as f64)
+                    .unwrap_or(1.0)
+            });
+
+        let history = self
+            .stats_history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let empty = StatsHistory::default();
+        container_scaling::evaluate(
+            history.get(name).unwrap_or(&empty),
+            &self.scaling_thresholds,
+            cpus,
+        )
+    }
+
+    pub async fn get_container_status(&self, name: &str) -> Result<InstanceStatus> {
         let container = self.runtime.inspect_container(name).await.map_err(|e| {
             log_error!("Failed to get container status for '{}': {:?}", name, e);
             NovaError::ContainerNotFound(name.to_string())
         })?;
 
-        Ok(match container.status {
-            crate::container_runtime::ContainerStatus::Running => {
-                crate::instance::InstanceStatus::Running
-            }
-            crate::container_runtime::ContainerStatus::Stopped => {
-                crate::instance::InstanceStatus::Stopped
-            }
-            crate::container_runtime::ContainerStatus::Paused => {
-                crate::instance::InstanceStatus::Suspended
-            }
-            crate::container_runtime::ContainerStatus::Starting => {
-                crate::instance::InstanceStatus::Starting
-            }
-            crate::container_runtime::ContainerStatus::Restarting => {
-                crate::instance::InstanceStatus::Starting
+        Ok(self.container_instance(&container).status)
+    }
+
+    /// Container instance plus its restart count, last exit and crash-loop
+    /// state, for `nova status container`.
+    pub async fn container_details(&self, name: &str) -> Result<(Instance, ContainerRestartInfo)> {
+        let container = self.inspect_container(name).await?;
+        let instance = self.container_instance(&container);
+        let restarts = ContainerRestartInfo {
+            restart_count: container.restart_count,
+            exit_code: container.exit_code,
+            exit_reason: container.exit_reason,
+            restart_policy: container.restart_policy,
+            crash_looping: instance.status == InstanceStatus::CrashLooping,
+        };
+        Ok((instance, restarts))
+    }
+
+    fn needs_restart_details(&self, container: &ContainerInfo) -> bool {
+        container.status == ContainerStatus::Restarting
+            || self
+                .restart_history
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .contains_key(&container.name)
+    }
+
+    /// Convert runtime info to an [`Instance`], recording the restart count
+    /// and flagging crash loops.
+    fn container_instance(&self, container: &ContainerInfo) -> Instance {
+        let mut instance = Instance::new(container.name.clone(), InstanceType::Container);
+        let status = match container.status {
+            ContainerStatus::Running => InstanceStatus::Running,
+            ContainerStatus::Stopped => InstanceStatus::Stopped,
+            ContainerStatus::Paused => InstanceStatus::Suspended,
+            ContainerStatus::Starting | ContainerStatus::Restarting => InstanceStatus::Starting,
+            _ => InstanceStatus::Error,
+        };
+        let status = if self.observe_restarts(container) {
+            InstanceStatus::CrashLooping
+        } else {
+            status
+        };
+        instance.update_status(status);
+        if let Some(pid) = container.pid {
+            instance.set_pid(Some(pid));
+        }
+        instance.network = container.network.clone();
+        instance.set_ip_address(container.ip_address.clone());
+        instance
+    }
+
+    /// Record the restart count and return whether the container is crash
+    /// looping, warning in the log when it starts to.
+    fn observe_restarts(&self, container: &ContainerInfo) -> bool {
+        let restarting = container.status == ContainerStatus::Restarting;
+        if container.restart_count == 0 && !restarting {
+            return false;
+        }
+
+        let looping = {
+            let mut history = self
+                .restart_history
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let entry = history.entry(container.name.clone()).or_default();
+            // Only new restarts need saving; the latest earlier observation
+            // still marks where a quiet stretch began
+            let changed = entry.last_count() != Some(container.restart_count);
+            entry.observe(chrono::Utc::now(), container.restart_count);
+            let looping = entry.is_crash_looping(&self.crash_loop_policy, restarting);
+            if changed
+                && !exec::is_dry_run()
+                && let Err(err) =
+                    container_health::save_restart_history(&self.restart_history_path, &history)
+            {
+                log_warn!(
+                    "Failed to save container restart history to {}: {}",
+                    self.restart_history_path.display(),
+                    err
+                );
             }
-            _ => crate::instance::InstanceStatus::Error,
-        })
+            looping
+        };
+        // Manually stopped or paused containers are not looping
+        let looping = looping
+            && !matches!(
+                container.status,
+                ContainerStatus::Stopped | ContainerStatus::Paused
+            );
+
+        let was_looping = self
+            .crash_looping
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(container.name.clone(), looping)
+            .unwrap_or(false);
+        if looping && !was_looping {
+            log_warn!(
+                "Container '{}' is crash looping: {} restarts (limit {} per {}s), last exit code {}",
+                container.name,
+                container.restart_count,
+                self.crash_loop_policy.max_restarts,
+                self.crash_loop_policy.window_seconds,
+                container
+                    .exit_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            );
+        }
+        looping
     }
 
     pub async fn get_container_logs(&self, name: &str, lines: usize) -> Result<Vec<String>> {
@@ -274,6 +654,42 @@ impl ContainerManager {
         })
     }
 
+    /// Run a command inside a running container; returns its exit code.
+    pub async fn exec_in_container(
+        &self,
+        name: &str,
+        command: &[String],
+        tty: bool,
+    ) -> Result<i32> {
+        let container = self.inspect_container(name).await?;
+        if container.status != ContainerStatus::Running {
+            return Err(NovaError::ConfigError(format!(
+                "Container '{}' is not running; start it with `nova run container {}`",
+                name, name
+            )));
+        }
+        self.runtime.exec(name, command, tty).await.map_err(|e| {
+            log_error!("Failed to exec in container '{}': {:?}", name, e);
+            NovaError::SystemCommandFailed
+        })
+    }
+
+    /// Print a container's recent logs, then keep streaming with `follow`.
+    pub async fn stream_container_logs(
+        &self,
+        name: &str,
+        lines: usize,
+        follow: bool,
+    ) -> Result<()> {
+        self.runtime
+            .stream_logs(name, lines, follow)
+            .await
+            .map_err(|e| {
+                log_error!("Failed to stream logs for container '{}': {:?}", name, e);
+                NovaError::ContainerNotFound(name.to_string())
+            })
+    }
+
     pub async fn pull_image(&self, image: &str) -> Result<()> {
         log_info!("Pulling image: {}", image);
 
@@ -286,6 +702,44 @@ impl ContainerManager {
         Ok(())
     }
 
+    /// Check `image` is pullable with the active runtime without pulling it.
+    /// The error carries the registry's reason.
+    pub async fn check_image(&self, image: &str) -> Result<()> {
+        if self.runtime_name == "None" {
+            return Err(NovaError::ConfigError(
+                "No container runtime available to verify images".to_string(),
+            ));
+        }
+
+        self.runtime.check_image(image).await.map_err(|e| {
+            log_debug!("Image '{}' is not reachable: {:?}", image, e);
+            match e {
+                ContainerRuntimeError::ImageNotFound(reason) if !reason.is_empty() => {
+                    NovaError::ConfigError(reason)
+                }
+                other => NovaError::ConfigError(other.to_string()),
+            }
+        })
+    }
+
+    /// Disk used by the runtime's images, containers, volumes and build
+    /// cache, with how much a prune could reclaim.
+    pub async fn disk_usage(&self) -> Result<Vec<DiskUsage>> {
+        self.runtime.disk_usage().await.map_err(|e| {
+            log_error!("Failed to read container disk usage: {:?}", e);
+            NovaError::ConfigError(e.to_string())
+        })
+    }
+
+    /// Reclaim space with the runtime's prune; returns the bytes freed.
+    pub async fn prune(&self, options: PruneOptions) -> Result<u64> {
+        log_info!("Pruning container storage with {}", self.runtime_name);
+        self.runtime.prune(options).await.map_err(|e| {
+            log_error!("Failed to prune container storage: {:?}", e);
+            NovaError::ConfigError(e.to_string())
+        })
+    }
+
     // Runtime availability checks
     pub fn check_container_runtime(&self) -> &str {
         &self.runtime_name
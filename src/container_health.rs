@@ -0,0 +1,226 @@
+//! Crash-loop detection for containers.
+//!
+//! Runtimes restart a container with `restart: always` every time it exits,
+//! so a service that dies on startup shows up as perpetually "restarting".
+//! [`ContainerManager`](crate::container::ContainerManager) records the
+//! runtime's restart counter each time it looks at a container, and
+//! [`RestartHistory::is_crash_looping`] flags containers that restart more
+//! often than the `[container_crash_loop]` policy allows. The history is
+//! kept in [`RESTART_HISTORY_PATH`] so a loop is still seen by the next
+//! `nova` invocation.
+
+use crate::container_runtime::RestartPolicy;
+use crate::{Result, log_warn};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Where restart histories are kept between runs
+pub const RESTART_HISTORY_PATH: &str = "/var/lib/nova/container-restarts.json";
+
+/// Observations kept per container.
+const HISTORY_CAPACITY: usize = 64;
+
+/// Thresholds from the `[container_crash_loop]` section of the NovaFile.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrashLoopPolicy {
+    /// Restarts within the window that count as a crash loop
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    #[serde(default = "default_window_seconds")]
+    pub window_seconds: u64,
+}
+
+impl Default for CrashLoopPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: default_max_restarts(),
+            window_seconds: default_window_seconds(),
+        }
+    }
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_window_seconds() -> u64 {
+    300
+}
+
+/// Restart counters seen for one container over time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestartHistory {
+    observations: VecDeque<(DateTime<Utc>, u32)>,
+}
+
+impl RestartHistory {
+    /// The restart count of the latest observation.
+    pub fn last_count(&self) -> Option<u32> {
+        self.observations.back().map(|(_, count)| *count)
+    }
+
+    pub fn observe(&mut self, at: DateTime<Utc>, restart_count: u32) {
+        // A lower count means the container was recreated; start over
+        if self
+            .observations
+            .back()
+            .is_some_and(|(_, last)| restart_count < *last)
+        {
+            self.observations.clear();
+        }
+        if self.observations.len() == HISTORY_CAPACITY {
+            self.observations.pop_front();
+        }
+        self.observations.push_back((at, restart_count));
+    }
+
+    /// Restarts counted between the start of the window and the latest
+    /// observation.
+    pub fn restarts_within(&self, window: Duration) -> u32 {
+        let Some((latest_at, latest)) = self.observations.back() else {
+            return 0;
+        };
+        let cutoff = *latest_at - window;
+        // The last observation at or before the cutoff is the baseline; if
+        // every observation is inside the window, the oldest one is
+        let baseline = self
+            .observations
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= cutoff)
+            .or_else(|| self.observations.front())
+            .map(|(_, count)| *count)
+            .unwrap_or(0);
+        latest.saturating_sub(baseline)
+    }
+
+    /// Whether the container restarts faster than `policy` allows.
+    ///
+    /// With a single observation there is no rate to measure, so a container
+    /// the runtime reports as restarting counts as looping once its lifetime
+    /// restart count reaches the limit.
+    pub fn is_crash_looping(&self, policy: &CrashLoopPolicy, restarting: bool) -> bool {
+        if policy.max_restarts == 0 {
+            return false;
+        }
+        match self.observations.len() {
+            0 => false,
+            1 => restarting && self.observations[0].1 >= policy.max_restarts,
+            _ => {
+                self.restarts_within(Duration::seconds(policy.window_seconds as i64))
+                    >= policy.max_restarts
+            }
+        }
+    }
+}
+
+/// Restart histories saved in `path`, empty when there are none yet.
+pub fn load_restart_history(path: &Path) -> HashMap<String, RestartHistory> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+        Err(err) => {
+            log_warn!(
+                "Failed to read container restart history from {}: {}",
+                path.display(),
+                err
+            );
+            return HashMap::new();
+        }
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        log_warn!(
+            "Ignoring corrupt container restart history in {}: {}",
+            path.display(),
+            err
+        );
+        HashMap::new()
+    })
+}
+
+/// Write restart histories to `path` through a temporary file, so a crash
+/// never leaves a half-written file behind.
+pub fn save_restart_history(path: &Path, history: &HashMap<String, RestartHistory>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let sorted: BTreeMap<&String, &RestartHistory> = history.iter().collect();
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, serde_json::to_string_pretty(&sorted)?)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Restart details shown by `nova status container`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContainerRestartInfo {
+    pub restart_count: u32,
+    pub exit_code: Option<i32>,
+    pub exit_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+    pub crash_looping: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-14T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::seconds(seconds)
+    }
+
+    #[test]
+    fn frequent_restarts_are_a_crash_loop() {
+        let policy = CrashLoopPolicy::default();
+        let mut history = RestartHistory::default();
+        history.observe(at(0), 2);
+        assert!(!history.is_crash_looping(&policy, true));
+
+        // Six restarts in two minutes
+        history.observe(at(60), 5);
+        history.observe(at(120), 8);
+        assert_eq!(history.restarts_within(Duration::seconds(300)), 6);
+        assert!(history.is_crash_looping(&policy, true));
+
+        // Quiet for longer than the window: old restarts no longer count
+        history.observe(at(900), 8);
+        assert_eq!(history.restarts_within(Duration::seconds(300)), 0);
+        assert!(!history.is_crash_looping(&policy, false));
+
+        // Recreated container resets the counter
+        history.observe(at(960), 0);
+        assert_eq!(history.restarts_within(Duration::seconds(300)), 0);
+
+        let mut single = RestartHistory::default();
+        single.observe(at(0), 12);
+        assert!(single.is_crash_looping(&policy, true));
+        assert!(!single.is_crash_looping(&policy, false));
+    }
+
+    #[test]
+    fn history_survives_a_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("container-restarts.json");
+        assert!(load_restart_history(&path).is_empty());
+
+        let mut history = RestartHistory::default();
+        history.observe(at(0), 2);
+        history.observe(at(60), 7);
+        let saved = HashMap::from([("api".to_string(), history)]);
+        save_restart_history(&path, &saved).unwrap();
+
+        let loaded = load_restart_history(&path);
+        assert_eq!(loaded, saved);
+        assert!(loaded["api"].is_crash_looping(&CrashLoopPolicy::default(), true));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(load_restart_history(&path).is_empty());
+    }
+}
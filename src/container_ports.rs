@@ -0,0 +1,191 @@
+//! Published-port parsing and conflict checks for `nova container remap-ports`.
+//!
+//! Runtimes fix a container's port bindings when it is created, so
+//! [`ContainerManager::remap_ports`](crate::container::ContainerManager::remap_ports)
+//! recreates the container. Before anything is torn down, the new mappings
+//! are checked against each other, against ports other containers publish,
+//! and against host sockets already listening.
+
+use crate::container_runtime::{PortMapping, PortProtocol};
+use crate::{NovaError, Result};
+use serde::Serialize;
+use std::net::{TcpListener, UdpSocket};
+
+/// Outcome of recreating a container with new port mappings.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortRemap {
+    pub container: String,
+    /// Runtimes assign a new ID to the recreated container
+    pub old_id: String,
+    pub new_id: String,
+    pub ports: Vec<PortMapping>,
+}
+
+/// Parse a `[ip:]host:container[/tcp|udp]` mapping as accepted by `-p`.
+pub fn parse_port_spec(spec: &str) -> Result<PortMapping> {
+    let invalid = || {
+        NovaError::ConfigError(format!(
+            "Invalid port mapping '{}' (expected host:container, e.g. 8081:80 or 5353:53/udp)",
+            spec
+        ))
+    };
+
+    let (ports, protocol) = match spec.trim().rsplit_once('/') {
+        Some((ports, "tcp")) => (ports, PortProtocol::Tcp),
+        Some((ports, "udp")) => (ports, PortProtocol::Udp),
+        Some(_) => return Err(invalid()),
+        None => (spec.trim(), PortProtocol::Tcp),
+    };
+    let parts: Vec<&str> = ports.rsplitn(3, ':').collect();
+    let (container_port, host_port) = match parts.as_slice() {
+        [container, host] | [container, host, _] => (*container, *host),
+        _ => return Err(invalid()),
+    };
+    let parse = |port: &str| port.parse::<u16>().ok().filter(|port| *port > 0);
+
+    Ok(PortMapping {
+        host_port: parse(host_port).ok_or_else(invalid)?,
+        container_port: parse(container_port).ok_or_else(invalid)?,
+        protocol,
+    })
+}
+
+/// Ports from a `docker ps` PORTS column such as
+/// `0.0.0.0:8080->80/tcp, :::8080->80/tcp, 9000/tcp`. Unpublished ports and
+/// the IPv6 duplicate of an IPv4 binding are skipped.
+pub fn parse_published_ports(ports: &str) -> Vec<PortMapping> {
+    let mut mappings: Vec<PortMapping> = Vec::new();
+    for entry in ports.split(',').map(str::trim) {
+        let Some((host, container)) = entry.split_once("->") else {
+            continue;
+        };
+        let Some(host_port) = host.rsplit(':').next().and_then(|port| port.parse().ok()) else {
+            continue;
+        };
+        let (container_port, protocol) = match container.split_once('/') {
+            Some((port, "udp")) => (port, PortProtocol::Udp),
+            Some((port, _)) => (port, PortProtocol::Tcp),
+            None => (container, PortProtocol::Tcp),
+        };
+        let Ok(container_port) = container_port.parse() else {
+            continue;
+        };
+        let mapping = PortMapping {
+            host_port,
+            container_port,
+            protocol,
+        };
+        if !mappings.contains(&mapping) {
+            mappings.push(mapping);
+        }
+    }
+    mappings
+}
+
+/// Problems with `requested` for container `name`: duplicate host ports and
+/// host ports published by another container in `others`.
+pub fn find_conflicts(
+    name: &str,
+    requested: &[PortMapping],
+    others: &[(String, Vec<PortMapping>)],
+) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    for (index, mapping) in requested.iter().enumerate() {
+        if requested[..index]
+            .iter()
+            .any(|earlier| same_host_port(earlier, mapping))
+        {
+            conflicts.push(format!(
+                "host port {} is mapped more than once",
+                describe(mapping)
+            ));
+            continue;
+        }
+        for (other, ports) in others.iter().filter(|(other, _)| other != name) {
+            if ports.iter().any(|port| same_host_port(port, mapping)) {
+                conflicts.push(format!(
+                    "host port {} is already published by container '{}'",
+                    describe(mapping),
+                    other
+                ));
+            }
+        }
+    }
+    conflicts
+}
+
+/// Whether something on the host is already listening on the mapping's port.
+/// Ports the process may not bind (below 1024 without root) are not
+/// reported; the runtime binds them itself.
+pub fn host_port_in_use(mapping: &PortMapping) -> bool {
+    let in_use = |result: std::io::Result<()>| matches!(result, Err(err) if err.kind() == std::io::ErrorKind::AddrInUse);
+    match mapping.protocol {
+        PortProtocol::Tcp => in_use(TcpListener::bind(("0.0.0.0", mapping.host_port)).map(drop)),
+        PortProtocol::Udp => in_use(UdpSocket::bind(("0.0.0.0", mapping.host_port)).map(drop)),
+    }
+}
+
+/// `-p` value for a mapping.
+pub fn port_spec(mapping: &PortMapping) -> String {
+    format!(
+        "{}:{}/{}",
+        mapping.host_port,
+        mapping.container_port,
+        protocol_name(&mapping.protocol)
+    )
+}
+
+fn same_host_port(a: &PortMapping, b: &PortMapping) -> bool {
+    a.host_port == b.host_port && a.protocol == b.protocol
+}
+
+fn describe(mapping: &PortMapping) -> String {
+    format!("{}/{}", mapping.host_port, protocol_name(&mapping.protocol))
+}
+
+fn protocol_name(protocol: &PortProtocol) -> &'static str {
+    match protocol {
+        PortProtocol::Tcp => "tcp",
+        PortProtocol::Udp => "udp",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_parse_and_conflicts_are_reported() {
+        let web = parse_port_spec("8081:80").unwrap();
+        assert_eq!((web.host_port, web.container_port), (8081, 80));
+        assert_eq!(web.protocol, PortProtocol::Tcp);
+        let dns = parse_port_spec("127.0.0.1:5353:53/udp").unwrap();
+        assert_eq!((dns.host_port, &dns.protocol), (5353, &PortProtocol::Udp));
+        for bad in ["80", "8081:http", "0:80", "8081:80/sctp", "70000:80"] {
+            assert!(parse_port_spec(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(port_spec(&dns), "5353:53/udp");
+
+        let published = parse_published_ports("0.0.0.0:8081->80/tcp, :::8081->80/tcp, 9000/tcp");
+        assert_eq!(published, vec![web.clone()]);
+
+        let others = vec![
+            ("web".to_string(), vec![parse_port_spec("8080:80").unwrap()]),
+            ("proxy".to_string(), published),
+        ];
+        // A container's current ports never conflict with itself
+        assert!(
+            find_conflicts("web", &[parse_port_spec("8080:8080").unwrap()], &others).is_empty()
+        );
+
+        let requested = vec![
+            web.clone(),
+            dns.clone(),
+            parse_port_spec("5353:5353/udp").unwrap(),
+        ];
+        let conflicts = find_conflicts("web", &requested, &others);
+        assert_eq!(conflicts.len(), 2, "{:?}", conflicts);
+        assert!(conflicts[0].contains("'proxy'"));
+        assert!(conflicts[1].contains("more than once"));
+    }
+}
@@ -51,14 +51,48 @@ pub trait ContainerRuntime: Send + Sync {
     /// Pull an image
     fn pull_image<'a>(&'a self, image: &'a str) -> RuntimeFuture<'a, ()>;
 
+    /// Check an image can be pulled, asking the registry without downloading it
+    fn check_image<'a>(&'a self, image: &'a str) -> RuntimeFuture<'a, ()>;
+
     /// List images
     fn list_images<'a>(&'a self) -> RuntimeFuture<'a, Vec<ImageInfo>>;
 
     /// Get container logs
     fn get_logs<'a>(&'a self, id_or_name: &'a str, lines: usize) -> RuntimeFuture<'a, Vec<String>>;
 
+    /// Write the last `lines` log lines to this process's stdout/stderr;
+    /// with `follow`, keep streaming until the container stops
+    fn stream_logs<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        lines: usize,
+        follow: bool,
+    ) -> RuntimeFuture<'a, ()>;
+
+    /// Run `command` in a running container with this process's stdio,
+    /// allocating a TTY when `tty` is set; resolves to the command's exit code
+    fn exec<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        command: &'a [String],
+        tty: bool,
+    ) -> RuntimeFuture<'a, i32>;
+
     /// Get container stats/metrics
     fn get_stats<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerStats>;
+
+    /// Stats of every running container, by name, collected in one pass
+    fn get_all_stats<'a>(&'a self) -> RuntimeFuture<'a, Vec<(String, ContainerStats)>>;
+
+    /// Settings an existing container was created with, so it can be recreated
+    fn container_config<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerConfig>;
+
+    /// Disk used by images, containers, volumes and build cache
+    fn disk_usage<'a>(&'a self) -> RuntimeFuture<'a, Vec<DiskUsage>>;
+
+    /// Remove stopped containers, unused networks, dangling images and
+    /// build cache; resolves to the bytes reclaimed
+    fn prune<'a>(&'a self, options: PruneOptions) -> RuntimeFuture<'a, u64>;
 }
 
 /// Container configuration
@@ -112,8 +146,28 @@ impl Default for ContainerConfig {
     }
 }
 
+/// Backends `ContainerManager` can be told to use instead of detecting one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeKind {
+    Bolt,
+    Docker,
+    Podman,
+}
+
+impl std::fmt::Display for RuntimeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RuntimeKind::Bolt => "bolt",
+            RuntimeKind::Docker => "docker",
+            RuntimeKind::Podman => "podman",
+        })
+    }
+}
+
 /// Restart policy for containers
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
 pub enum RestartPolicy {
     No,
     Always,
@@ -121,6 +175,35 @@ pub enum RestartPolicy {
     UnlessStopped,
 }
 
+impl RestartPolicy {
+    pub const ALL: [RestartPolicy; 4] = [
+        RestartPolicy::No,
+        RestartPolicy::OnFailure,
+        RestartPolicy::Always,
+        RestartPolicy::UnlessStopped,
+    ];
+
+    /// Name passed to the runtime's `--restart`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestartPolicy::No => "no",
+            RestartPolicy::Always => "always",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::UnlessStopped => "unless-stopped",
+        }
+    }
+
+    /// Policy from its runtime name (`always`, `on-failure`, `unless-stopped`).
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "always" => RestartPolicy::Always,
+            "on-failure" => RestartPolicy::OnFailure,
+            "unless-stopped" => RestartPolicy::UnlessStopped,
+            _ => RestartPolicy::No,
+        }
+    }
+}
+
 /// Container information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainerInfo {
@@ -133,6 +216,18 @@ pub struct ContainerInfo {
     pub network: Option<String>,
     pub pid: Option<u32>,
     pub ip_address: Option<String>,
+    /// Times the runtime has restarted the container
+    #[serde(default)]
+    pub restart_count: u32,
+    /// Exit code of the last run, when it has exited at least once
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Runtime-reported reason for the last exit (OOM kill, start error)
+    #[serde(default)]
+    pub exit_reason: Option<String>,
+    /// Restart policy the container was created with, when inspected
+    #[serde(default)]
+    pub restart_policy: Option<RestartPolicy>,
 }
 
 /// Container status
@@ -148,7 +243,7 @@ pub enum ContainerStatus {
 }
 
 /// Port mapping
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PortMapping {
     pub host_port: u16,
     pub container_port: u16,
@@ -156,7 +251,7 @@ pub struct PortMapping {
 }
 
 /// Port protocol
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PortProtocol {
     Tcp,
     Udp,
@@ -183,6 +278,41 @@ pub struct ContainerStats {
     pub disk_write_bytes: u64,
 }
 
+/// One row of `system df`: disk used by a kind of runtime object
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// `Images`, `Containers`, `Local Volumes` or `Build Cache`
+    pub kind: String,
+    pub total: u64,
+    pub active: u64,
+    pub size_bytes: u64,
+    pub reclaimable_bytes: u64,
+}
+
+/// What a prune removes beyond stopped containers, unused networks,
+/// dangling images and build cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneOptions {
+    /// Also remove every image no container uses, not just dangling ones
+    pub images: bool,
+    /// Also remove volumes no container uses
+    pub volumes: bool,
+}
+
+/// Why an image check failed, from the runtime's stderr: the last non-empty
+/// line without a leading `Error:` label, e.g. `manifest unknown`.
+pub fn image_check_reason(stderr: &str) -> String {
+    let line = stderr
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .unwrap_or_default();
+    line.strip_prefix("Error: ")
+        .or_else(|| line.strip_prefix("error: "))
+        .unwrap_or(line)
+        .to_string()
+}
+
 /// Container runtime errors
 #[derive(Debug, thiserror::Error)]
 pub enum ContainerRuntimeError {
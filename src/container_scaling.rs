@@ -0,0 +1,336 @@
+//! Advisory scaling hints derived from recent container stats.
+//!
+//! [`ContainerManager`](crate::container::ContainerManager) records every
+//! stats sample it collects into a per-container [`StatsHistory`]. The
+//! [`evaluate`] function looks at the trailing window of that history and
+//! suggests raising or lowering CPU and memory limits when usage stays past a
+//! threshold for the whole window. Nothing is changed automatically.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Samples kept per container; at the GUI refresh rate this covers well over
+/// the default five minute window.
+pub const HISTORY_CAPACITY: usize = 240;
+
+/// Thresholds from the `[container_scaling]` section of the NovaFile.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScalingThresholds {
+    /// CPU usage (percent of the allotted CPUs) that suggests scaling up
+    #[serde(default = "default_cpu_high_percent")]
+    pub cpu_high_percent: f64,
+    /// CPU usage below which the allotment looks oversized
+    #[serde(default = "default_cpu_low_percent")]
+    pub cpu_low_percent: f64,
+    /// Memory usage (percent of the limit) that suggests scaling up
+    #[serde(default = "default_memory_high_percent")]
+    pub memory_high_percent: f64,
+    /// Memory usage below which the limit looks oversized
+    #[serde(default = "default_memory_low_percent")]
+    pub memory_low_percent: f64,
+    /// How long usage must stay past a threshold before a hint is given
+    #[serde(default = "default_sustained_seconds")]
+    pub sustained_seconds: u64,
+}
+
+impl Default for ScalingThresholds {
+    fn default() -> Self {
+        Self {
+            cpu_high_percent: default_cpu_high_percent(),
+            cpu_low_percent: default_cpu_low_percent(),
+            memory_high_percent: default_memory_high_percent(),
+            memory_low_percent: default_memory_low_percent(),
+            sustained_seconds: default_sustained_seconds(),
+        }
+    }
+}
+
+fn default_cpu_high_percent() -> f64 {
+    85.0
+}
+
+fn default_cpu_low_percent() -> f64 {
+    10.0
+}
+
+fn default_memory_high_percent() -> f64 {
+    90.0
+}
+
+fn default_memory_low_percent() -> f64 {
+    20.0
+}
+
+fn default_sustained_seconds() -> u64 {
+    300
+}
+
+/// One stats observation as reported by the runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsSample {
+    pub at: DateTime<Utc>,
+    /// Raw runtime CPU percentage (100 = one full core)
+    pub cpu_percent: f64,
+    pub memory_usage_mb: u64,
+    pub memory_limit_mb: u64,
+}
+
+/// Fixed-size ring buffer of recent samples for one container.
+#[derive(Debug, Clone, Default)]
+pub struct StatsHistory {
+    samples: VecDeque<StatsSample>,
+}
+
+impl StatsHistory {
+    pub fn push(&mut self, sample: StatsSample) {
+        if self.samples.len() == HISTORY_CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &StatsSample> {
+        self.samples.iter()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingResource {
+    Cpu,
+    Memory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalingDirection {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingHint {
+    pub resource: ScalingResource,
+    pub direction: ScalingDirection,
+    /// Lowest (scale up) or highest (scale down) usage seen in the window
+    pub observed_percent: f64,
+    pub threshold_percent: f64,
+    pub window: Duration,
+}
+
+impl fmt::Display for ScalingHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (label, flag) = match self.resource {
+            ScalingResource::Cpu => ("CPU", "--cpus"),
+            ScalingResource::Memory => ("Memory", "--memory"),
+        };
+        let (comparison, action) = match self.direction {
+            ScalingDirection::Up => (">", "raising"),
+            ScalingDirection::Down => ("<", "lowering"),
+        };
+        write!(
+            f,
+            "{} sustained {}{:.0}% for {}, consider {} {}",
+            label,
+            comparison,
+            self.threshold_percent,
+            format_window(self.window),
+            action,
+            flag
+        )
+    }
+}
+
+/// Result of [`evaluate`]: the hints plus how much history backed them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingAdvice {
+    pub hints: Vec<ScalingHint>,
+    pub samples: usize,
+    /// Time span covered by the history
+    pub observed: Duration,
+    pub window: Duration,
+}
+
+impl ScalingAdvice {
+    /// Whether the history covers the whole sustain window.
+    pub fn has_enough_data(&self) -> bool {
+        self.samples >= 2 && self.observed >= self.window
+    }
+}
+
+/// Compare the trailing window of `history` against `thresholds`.
+///
+/// `cpus` is the CPU allotment (limit, or host cores when unlimited) that CPU
+/// usage is measured against. No hints are produced until the history spans
+/// the full sustain window.
+pub fn evaluate(
+    history: &StatsHistory,
+    thresholds: &ScalingThresholds,
+    cpus: f64,
+) -> ScalingAdvice {
+    let window = Duration::seconds(thresholds.sustained_seconds as i64);
+    let (Some(first), Some(last)) = (history.samples.front(), history.samples.back()) else {
+        return ScalingAdvice {
+            hints: Vec::new(),
+            samples: 0,
+            observed: Duration::zero(),
+            window,
+        };
+    };
+
+    let mut advice = ScalingAdvice {
+        hints: Vec::new(),
+        samples: history.len(),
+        observed: last.at - first.at,
+        window,
+    };
+    if !advice.has_enough_data() {
+        return advice;
+    }
+
+    let cutoff = last.at - window;
+    let recent: Vec<&StatsSample> = history
+        .samples
+        .iter()
+        .filter(|sample| sample.at >= cutoff)
+        .collect();
+
+    let cpu_capacity = cpus.max(0.01) * 100.0;
+    let cpu: Vec<f64> = recent
+        .iter()
+        .map(|sample| sample.cpu_percent / cpu_capacity * 100.0)
+        .collect();
+    let memory: Vec<f64> = recent
+        .iter()
+        .filter(|sample| sample.memory_limit_mb > 0)
+        .map(|sample| sample.memory_usage_mb as f64 / sample.memory_limit_mb as f64 * 100.0)
+        .collect();
+
+    for (resource, usage, high, low) in [
+        (
+            ScalingResource::Cpu,
+            cpu,
+            thresholds.cpu_high_percent,
+            thresholds.cpu_low_percent,
+        ),
+        (
+            ScalingResource::Memory,
+            memory,
+            thresholds.memory_high_percent,
+            thresholds.memory_low_percent,
+        ),
+    ] {
+        if usage.is_empty() {
+            continue;
+        }
+        let min = usage.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = usage.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        if min > high {
+            advice.hints.push(ScalingHint {
+                resource,
+                direction: ScalingDirection::Up,
+                observed_percent: min,
+                threshold_percent: high,
+                window,
+            });
+        } else if max < low {
+            advice.hints.push(ScalingHint {
+                resource,
+                direction: ScalingDirection::Down,
+                observed_percent: max,
+                threshold_percent: low,
+                window,
+            });
+        }
+    }
+
+    advice
+}
+
+fn format_window(window: Duration) -> String {
+    let seconds = window.num_seconds();
+    if seconds >= 3600 && seconds % 3600 == 0 {
+        format!("{}h", seconds / 3600)
+    } else if seconds >= 60 && seconds % 60 == 0 {
+        format!("{}m", seconds / 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(cpu: &[f64], memory_mb: &[u64]) -> StatsHistory {
+        let start = DateTime::parse_from_rfc3339("2026-10-14T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut history = StatsHistory::default();
+        for (index, (cpu, memory)) in cpu.iter().zip(memory_mb).enumerate() {
+            history.push(StatsSample {
+                at: start + Duration::seconds(60 * index as i64),
+                cpu_percent: *cpu,
+                memory_usage_mb: *memory,
+                memory_limit_mb: 1024,
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn sustained_high_cpu_suggests_raising_cpus() {
+        // Two CPUs allotted: 180% raw is 90% of the allotment
+        let history = series(
+            &[20.0, 180.0, 176.0, 190.0, 185.0, 178.0, 182.0],
+            &[500, 500, 510, 505, 500, 498, 502],
+        );
+        let advice = evaluate(&history, &ScalingThresholds::default(), 2.0);
+
+        assert!(advice.has_enough_data());
+        assert_eq!(advice.hints.len(), 1);
+        assert_eq!(advice.hints[0].resource, ScalingResource::Cpu);
+        assert_eq!(advice.hints[0].direction, ScalingDirection::Up);
+        assert_eq!(
+            advice.hints[0].to_string(),
+            "CPU sustained >85% for 5m, consider raising --cpus"
+        );
+    }
+
+    #[test]
+    fn spikes_and_short_history_give_no_hints() {
+        // One dip below the threshold breaks the sustained run
+        let spiky = series(
+            &[95.0, 95.0, 40.0, 95.0, 95.0, 95.0],
+            &[500, 500, 500, 500, 500, 500],
+        );
+        assert!(
+            evaluate(&spiky, &ScalingThresholds::default(), 1.0)
+                .hints
+                .is_empty()
+        );
+
+        let short = series(&[99.0, 99.0], &[1000, 1000]);
+        let advice = evaluate(&short, &ScalingThresholds::default(), 1.0);
+        assert!(!advice.has_enough_data());
+        assert!(advice.hints.is_empty());
+
+        let idle = series(&[1.0; 6], &[100; 6]);
+        let hints = evaluate(&idle, &ScalingThresholds::default(), 4.0).hints;
+        assert_eq!(hints.len(), 2);
+        assert!(
+            hints
+                .iter()
+                .all(|hint| hint.direction == ScalingDirection::Down)
+        );
+    }
+}
@@ -1,49 +1,53 @@
 //! Docker Container Runtime Integration
 //!
 //! Provides Docker as a fallback container runtime when Bolt is not available.
-//! Uses Docker's standard CLI for container management.
+//! Uses Docker's standard CLI for container management, which Podman also
+//! accepts when it is selected explicitly.
 
+use crate::container_ports;
 use crate::container_runtime::*;
-use crate::{log_debug, log_error, log_info};
+use crate::{exec, log_debug, log_error, log_info};
 use std::process::Command;
 
-/// Docker runtime implementation
+/// Docker runtime implementation. Podman accepts the same CLI, so it is
+/// driven by this runtime too, see [`DockerRuntime::podman`].
 pub struct DockerRuntime {
+    binary: &'static str,
+    name: &'static str,
     available: bool,
     version: Option<String>,
 }
 
 impl DockerRuntime {
     pub fn new() -> Self {
-        let available = Self::check_docker_installed();
-        let version = if available {
-            Self::get_docker_version()
-        } else {
-            None
-        };
+        Self::with_binary("docker", "Docker")
+    }
 
-        if available {
-            log_info!(
-                "Docker runtime initialized (version: {})",
-                version.as_deref().unwrap_or("unknown")
-            );
+    /// Podman through its Docker-compatible CLI.
+    pub fn podman() -> Self {
+        Self::with_binary("podman", "Podman")
+    }
+
+    fn with_binary(binary: &'static str, name: &'static str) -> Self {
+        let version = Self::get_version(binary);
+        let available = version.is_some();
+
+        if let Some(version) = &version {
+            log_info!("{} runtime initialized (version: {})", name, version);
         } else {
-            log_debug!("Docker runtime not available");
+            log_debug!("{} runtime not available", name);
         }
 
-        Self { available, version }
-    }
-
-    fn check_docker_installed() -> bool {
-        Command::new("docker")
-            .arg("--version")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        Self {
+            binary,
+            name,
+            available,
+            version,
+        }
     }
 
-    fn get_docker_version() -> Option<String> {
-        Command::new("docker")
+    fn get_version(binary: &str) -> Option<String> {
+        Command::new(binary)
             .arg("--version")
             .output()
             .ok()
@@ -142,7 +146,7 @@ impl DockerRuntime {
 
     /// Parse Docker ps output (pipe-delimited format)
     fn parse_docker_ps_line(&self, line: &str) -> Option<ContainerInfo> {
-        // Docker ps --format output: ID|NAME|IMAGE|STATUS
+        // Docker ps --format output: ID|NAME|IMAGE|STATUS|PORTS
         let parts: Vec<&str> = line.split('|').collect();
         if parts.len() < 4 {
             return None;
@@ -156,13 +160,277 @@ impl DockerRuntime {
             image: parts[2].to_string(),
             status,
             created: chrono::Utc::now(),
-            ports: Vec::new(),
+            ports: parts
+                .get(4)
+                .map(|ports| container_ports::parse_published_ports(ports))
+                .unwrap_or_default(),
             network: None,
             pid: None,
             ip_address: None,
+            restart_count: 0,
+            exit_code: Self::parse_status_exit_code(parts[3]),
+            exit_reason: None,
+            restart_policy: None,
+        })
+    }
+
+    /// Exit code from a ps status such as `Exited (137) 2 hours ago` or
+    /// `Restarting (1) 5 seconds ago`.
+    fn parse_status_exit_code(status_str: &str) -> Option<i32> {
+        let (_, rest) = status_str.split_once('(')?;
+        let (code, _) = rest.split_once(')')?;
+        code.trim().parse().ok()
+    }
+
+    /// Fill in restart details from `docker inspect` output in the
+    /// `RestartCount|ExitCode|OOMKilled|Pid|RestartPolicy|Error` format; the
+    /// error message goes last since it may itself contain `|`.
+    fn apply_inspect_state(info: &mut ContainerInfo, state: &str) {
+        let parts: Vec<&str> = state.trim().splitn(6, '|').collect();
+        if parts.len() < 6 {
+            return;
+        }
+        info.restart_count = parts[0].parse().unwrap_or(0);
+        // Docker reports 0 for containers that never exited
+        info.exit_code = parts[1]
+            .parse()
+            .ok()
+            .filter(|code| *code != 0 || info.restart_count > 0);
+        info.exit_reason = if parts[2] == "true" {
+            Some("OOM killed".to_string())
+        } else {
+            Some(parts[5].trim().to_string()).filter(|error| !error.is_empty())
+        };
+        info.pid = parts[3].parse().ok().filter(|pid| *pid > 0);
+        info.restart_policy = Some(RestartPolicy::from_name(parts[4]));
+    }
+
+    /// Stats from one `docker stats --no-stream --format '{{json .}}'` line,
+    /// e.g. `"MemUsage":"12.5MiB / 1.9GiB"`.
+    fn parse_stats(json: &str) -> Result<ContainerStats> {
+        let value: serde_json::Value = serde_json::from_str(json.trim())?;
+        let field = |name: &str| value[name].as_str().unwrap_or_default();
+        let pair = |name: &str| {
+            let mut sides = field(name).split(" / ").map(parse_docker_size);
+            (
+                sides.next().unwrap_or_default(),
+                sides.next().unwrap_or_default(),
+            )
+        };
+        let (memory_usage, memory_limit) = pair("MemUsage");
+        let (network_rx_bytes, network_tx_bytes) = pair("NetIO");
+        let (disk_read_bytes, disk_write_bytes) = pair("BlockIO");
+
+        Ok(ContainerStats {
+            cpu_usage_percent: field("CPUPerc")
+                .trim_end_matches('%')
+                .parse()
+                .unwrap_or(0.0),
+            memory_usage_mb: memory_usage / (1024 * 1024),
+            memory_limit_mb: memory_limit / (1024 * 1024),
+            network_rx_bytes,
+            network_tx_bytes,
+            disk_read_bytes,
+            disk_write_bytes,
+        })
+    }
+
+    /// Arguments asking the registry for `image`'s manifest without pulling
+    /// it; Docker and Podman both support `manifest inspect`.
+    fn check_image_args(image: &str) -> [&str; 3] {
+        ["manifest", "inspect", image]
+    }
+
+    /// Stats of every container in `docker stats --no-stream --format
+    /// '{{json .}}'` output, by the `Name` on each line.
+    fn parse_all_stats(output: &str) -> Result<Vec<(String, ContainerStats)>> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line.trim())?;
+                let name = value["Name"].as_str().unwrap_or_default().to_string();
+                Ok((name, Self::parse_stats(line)?))
+            })
+            .collect()
+    }
+
+    /// Usage rows from `docker system df --format '{{json .}}'`, one JSON
+    /// object per line, e.g. `"Reclaimable":"1.2GB (50%)"`. Podman names
+    /// the count `Total` and may print it as a number.
+    fn parse_disk_usage(output: &str) -> Result<Vec<DiskUsage>> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line.trim())?;
+                let count = |names: &[&str]| {
+                    names
+                        .iter()
+                        .map(|name| &value[*name])
+                        .find_map(|field| {
+                            field
+                                .as_u64()
+                                .or_else(|| field.as_str().and_then(|s| s.trim().parse().ok()))
+                        })
+                        .unwrap_or(0)
+                };
+                let size = |name: &str| {
+                    let text = value[name].as_str().unwrap_or_default();
+                    parse_docker_size(text.split(" (").next().unwrap_or_default())
+                };
+                Ok(DiskUsage {
+                    kind: value["Type"].as_str().unwrap_or_default().to_string(),
+                    total: count(&["TotalCount", "Total"]),
+                    active: count(&["Active"]),
+                    size_bytes: size("Size"),
+                    reclaimable_bytes: size("Reclaimable"),
+                })
+            })
+            .collect()
+    }
+
+    /// Bytes from the `Total reclaimed space: 1.2GB` line `system prune`
+    /// ends with; 0 when nothing was removed.
+    fn parse_reclaimed(output: &str) -> u64 {
+        output
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Total reclaimed space:"))
+            .map(parse_docker_size)
+            .unwrap_or(0)
+    }
+
+    /// Recreatable settings from `docker inspect` JSON. Volumes are the
+    /// `HostConfig.Binds` plus named volumes mounted another way; anonymous
+    /// volumes are left behind. Variables equal to an entry of `image_env`
+    /// came from the image and are not carried over.
+    fn parse_inspect_config(json: &str, image_env: &[String]) -> Result<ContainerConfig> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let container = value.get(0).unwrap_or(&value);
+        let config = &container["Config"];
+        let host = &container["HostConfig"];
+        let strings = |value: &serde_json::Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut ports = Vec::new();
+        if let Some(bindings) = host["PortBindings"].as_object() {
+            for (container_port, hosts) in bindings {
+                for binding in hosts.as_array().into_iter().flatten() {
+                    let Some(host_port) = binding["HostPort"].as_str().filter(|p| !p.is_empty())
+                    else {
+                        continue;
+                    };
+                    let spec = match binding["HostIp"].as_str().unwrap_or_default() {
+                        "" => format!("{}:{}", host_port, container_port),
+                        ip if ip.contains(':') => {
+                            format!("[{}]:{}:{}", ip, host_port, container_port)
+                        }
+                        ip => format!("{}:{}:{}", ip, host_port, container_port),
+                    };
+                    ports.push(spec);
+                }
+            }
+        }
+        ports.sort();
+        ports.dedup();
+
+        let mut volumes = strings(&host["Binds"]);
+        let bound: Vec<String> = volumes
+            .iter()
+            .filter_map(|bind| bind.split(':').nth(1).map(str::to_string))
+            .collect();
+        for mount in container["Mounts"].as_array().into_iter().flatten() {
+            let (Some(name), Some(destination)) =
+                (mount["Name"].as_str(), mount["Destination"].as_str())
+            else {
+                continue;
+            };
+            let anonymous = name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit());
+            if mount["Type"].as_str() != Some("volume")
+                || anonymous
+                || bound.iter().any(|bound| bound == destination)
+            {
+                continue;
+            }
+            let read_only = mount["RW"].as_bool() == Some(false);
+            volumes.push(format!(
+                "{}:{}{}",
+                name,
+                destination,
+                if read_only { ":ro" } else { "" }
+            ));
+        }
+
+        let env = strings(&config["Env"])
+            .into_iter()
+            .filter(|entry| !image_env.contains(entry))
+            .filter_map(|entry| {
+                entry
+                    .split_once('=')
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+            })
+            .collect();
+        let network = host["NetworkMode"]
+            .as_str()
+            .filter(|mode| !matches!(*mode, "" | "default" | "bridge"))
+            .map(str::to_string);
+        let gpu_passthrough = host["DeviceRequests"].as_array().is_some_and(|requests| {
+            requests
+                .iter()
+                .any(|request| request["Capabilities"].to_string().contains("\"gpu\""))
+        });
+
+        Ok(ContainerConfig {
+            capsule: config["Image"].as_str().unwrap_or_default().to_string(),
+            ports,
+            volumes,
+            env,
+            network,
+            gpu_passthrough,
+            memory_mb: host["Memory"]
+                .as_u64()
+                .filter(|bytes| *bytes > 0)
+                .map(|bytes| bytes / (1024 * 1024)),
+            cpus: host["NanoCpus"]
+                .as_u64()
+                .filter(|nanos| *nanos > 0)
+                .map(|nanos| nanos.div_ceil(1_000_000_000) as u32),
+            restart_policy: RestartPolicy::from_name(
+                host["RestartPolicy"]["Name"].as_str().unwrap_or_default(),
+            ),
+            detach: true,
         })
     }
 
+    /// Environment the image itself sets; empty when it can't be inspected.
+    fn image_env(&self, image: &str) -> Vec<String> {
+        if image.is_empty() {
+            return Vec::new();
+        }
+        Command::new(self.binary)
+            .args([
+                "image",
+                "inspect",
+                "--format",
+                "{{json .Config.Env}}",
+                image,
+            ])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| serde_json::from_slice::<Vec<String>>(&output.stdout).ok())
+            .unwrap_or_default()
+    }
+
     fn parse_status(status_str: &str) -> ContainerStatus {
         let status_lower = status_str.to_lowercase();
         if status_lower.contains("up") || status_lower.contains("running") {
@@ -189,13 +457,13 @@ impl ContainerRuntime for DockerRuntime {
     }
 
     fn name(&self) -> &str {
-        "Docker"
+        self.name
     }
 
     fn version<'a>(&'a self) -> RuntimeFuture<'a, String> {
         Box::pin(async move {
             self.version.clone().ok_or_else(|| {
-                ContainerRuntimeError::RuntimeNotAvailable("Docker version unknown".to_string())
+                ContainerRuntimeError::RuntimeNotAvailable(format!("{} version unknown", self.name))
             })
         })
     }
@@ -208,13 +476,15 @@ impl ContainerRuntime for DockerRuntime {
     ) -> RuntimeFuture<'a, String> {
         Box::pin(async move {
             if !self.available {
-                return Err(ContainerRuntimeError::RuntimeNotAvailable(
-                    "Docker is not installed".to_string(),
-                ));
+                return Err(ContainerRuntimeError::RuntimeNotAvailable(format!(
+                    "{} is not installed",
+                    self.name
+                )));
             }
 
             log_info!(
-                "Starting Docker container: {} from image {}",
+                "Starting {} container: {} from image {}",
+                self.name,
                 name.unwrap_or("<unnamed>"),
                 image
             );
@@ -223,34 +493,35 @@ impl ContainerRuntime for DockerRuntime {
             docker_config.capsule = image.to_string();
             let args = self.build_docker_args(name, &docker_config);
 
-            let output = Command::new("docker").args(&args).output().map_err(|e| {
-                ContainerRuntimeError::StartFailed(format!("Failed to execute docker: {}", e))
+            let output = exec::run(Command::new(self.binary).args(&args)).map_err(|e| {
+                ContainerRuntimeError::StartFailed(format!(
+                    "Failed to execute {}: {}",
+                    self.binary, e
+                ))
             })?;
 
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                log_error!("Docker run failed: {}", stderr);
+                log_error!("{} run failed: {}", self.name, stderr);
                 return Err(ContainerRuntimeError::StartFailed(stderr.to_string()));
             }
 
             let container_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-            log_info!("Docker container started: {}", container_id);
+            log_info!("{} container started: {}", self.name, container_id);
             Ok(container_id)
         })
     }
 
     fn stop_container<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ()> {
         Box::pin(async move {
-            log_info!("Stopping Docker container: {}", id_or_name);
+            log_info!("Stopping {} container: {}", self.name, id_or_name);
 
-            let output = Command::new("docker")
-                .args(["stop", id_or_name])
-                .output()
-                .map_err(|e| {
+            let output =
+                exec::run(Command::new(self.binary).args(["stop", id_or_name])).map_err(|e| {
                     ContainerRuntimeError::StopFailed(format!(
-                        "Failed to execute docker stop: {}",
-                        e
+                        "Failed to execute {} stop: {}",
+                        self.binary, e
                     ))
                 })?;
 
@@ -265,7 +536,7 @@ impl ContainerRuntime for DockerRuntime {
 
     fn remove_container<'a>(&'a self, id_or_name: &'a str, force: bool) -> RuntimeFuture<'a, ()> {
         Box::pin(async move {
-            log_info!("Removing Docker container: {}", id_or_name);
+            log_info!("Removing {} container: {}", self.name, id_or_name);
 
             let mut args = vec!["rm"];
             if force {
@@ -273,8 +544,8 @@ impl ContainerRuntime for DockerRuntime {
             }
             args.push(id_or_name);
 
-            let output = Command::new("docker").args(&args).output().map_err(|e| {
-                ContainerRuntimeError::Other(format!("Failed to execute docker rm: {}", e))
+            let output = exec::run(Command::new(self.binary).args(&args)).map_err(|e| {
+                ContainerRuntimeError::Other(format!("Failed to execute {} rm: {}", self.binary, e))
             })?;
 
             if !output.status.success() {
@@ -291,15 +562,21 @@ impl ContainerRuntime for DockerRuntime {
             let mut args = vec![
                 "ps",
                 "--format",
-                "{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}",
+                "{{.ID}}|{{.Names}}|{{.Image}}|{{.Status}}|{{.Ports}}",
             ];
             if all {
                 args.push("-a");
             }
 
-            let output = Command::new("docker").args(&args).output().map_err(|e| {
-                ContainerRuntimeError::Other(format!("Failed to execute docker ps: {}", e))
-            })?;
+            let output = Command::new(self.binary)
+                .args(&args)
+                .output()
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} ps: {}",
+                        self.binary, e
+                    ))
+                })?;
 
             if !output.status.success() {
                 return Ok(Vec::new());
@@ -318,22 +595,39 @@ impl ContainerRuntime for DockerRuntime {
     fn inspect_container<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerInfo> {
         Box::pin(async move {
             let containers = self.list_containers(true).await?;
-            containers
+            let mut info = containers
                 .into_iter()
                 .find(|c| c.id == id_or_name || c.name == id_or_name)
-                .ok_or_else(|| ContainerRuntimeError::ContainerNotFound(id_or_name.to_string()))
+                .ok_or_else(|| ContainerRuntimeError::ContainerNotFound(id_or_name.to_string()))?;
+
+            let output = Command::new(self.binary)
+                .args([
+                    "inspect",
+                    "--format",
+                    "{{.RestartCount}}|{{.State.ExitCode}}|{{.State.OOMKilled}}|{{.State.Pid}}|{{.HostConfig.RestartPolicy.Name}}|{{.State.Error}}",
+                    &info.id,
+                ])
+                .output();
+            if let Ok(output) = output
+                && output.status.success()
+            {
+                Self::apply_inspect_state(&mut info, &String::from_utf8_lossy(&output.stdout));
+            }
+
+            Ok(info)
         })
     }
 
     fn pull_image<'a>(&'a self, image: &'a str) -> RuntimeFuture<'a, ()> {
         Box::pin(async move {
-            log_info!("Pulling Docker image: {}", image);
+            log_info!("Pulling {} image: {}", self.name, image);
 
-            let output = Command::new("docker")
-                .args(["pull", image])
-                .output()
-                .map_err(|e| {
-                    ContainerRuntimeError::Other(format!("Failed to execute docker pull: {}", e))
+            let output =
+                exec::run(Command::new(self.binary).args(["pull", image])).map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} pull: {}",
+                        self.binary, e
+                    ))
                 })?;
 
             if !output.status.success() {
@@ -345,6 +639,29 @@ impl ContainerRuntime for DockerRuntime {
         })
     }
 
+    fn check_image<'a>(&'a self, image: &'a str) -> RuntimeFuture<'a, ()> {
+        Box::pin(async move {
+            let output = tokio::process::Command::new(self.binary)
+                .args(Self::check_image_args(image))
+                .output()
+                .await
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} manifest inspect: {}",
+                        self.binary, e
+                    ))
+                })?;
+
+            if !output.status.success() {
+                return Err(ContainerRuntimeError::ImageNotFound(image_check_reason(
+                    &String::from_utf8_lossy(&output.stderr),
+                )));
+            }
+
+            Ok(())
+        })
+    }
+
     fn list_images<'a>(&'a self) -> RuntimeFuture<'a, Vec<ImageInfo>> {
         Box::pin(async move {
             // Simple implementation - would need proper parsing
@@ -354,11 +671,14 @@ impl ContainerRuntime for DockerRuntime {
 
     fn get_logs<'a>(&'a self, id_or_name: &'a str, lines: usize) -> RuntimeFuture<'a, Vec<String>> {
         Box::pin(async move {
-            let output = Command::new("docker")
+            let output = Command::new(self.binary)
                 .args(["logs", "--tail", &lines.to_string(), id_or_name])
                 .output()
                 .map_err(|e| {
-                    ContainerRuntimeError::Other(format!("Failed to execute docker logs: {}", e))
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} logs: {}",
+                        self.binary, e
+                    ))
                 })?;
 
             if !output.status.success() {
@@ -372,18 +692,180 @@ impl ContainerRuntime for DockerRuntime {
         })
     }
 
-    fn get_stats<'a>(&'a self, _id_or_name: &'a str) -> RuntimeFuture<'a, ContainerStats> {
+    fn exec<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        command: &'a [String],
+        tty: bool,
+    ) -> RuntimeFuture<'a, i32> {
         Box::pin(async move {
-            // Placeholder - would need proper stats parsing
-            Ok(ContainerStats {
-                cpu_usage_percent: 0.0,
-                memory_usage_mb: 0,
-                memory_limit_mb: 0,
-                network_rx_bytes: 0,
-                network_tx_bytes: 0,
-                disk_read_bytes: 0,
-                disk_write_bytes: 0,
-            })
+            let mut cmd = tokio::process::Command::new(self.binary);
+            cmd.args(["exec", if tty { "-it" } else { "-i" }, id_or_name]);
+            let status = cmd.args(command).status().await.map_err(|e| {
+                ContainerRuntimeError::Other(format!(
+                    "Failed to execute {} exec: {}",
+                    self.binary, e
+                ))
+            })?;
+            // Killed by a signal: report it the way a shell would
+            Ok(status.code().unwrap_or_else(|| {
+                128 + std::os::unix::process::ExitStatusExt::signal(&status).unwrap_or(0)
+            }))
+        })
+    }
+
+    fn stream_logs<'a>(
+        &'a self,
+        id_or_name: &'a str,
+        lines: usize,
+        follow: bool,
+    ) -> RuntimeFuture<'a, ()> {
+        Box::pin(async move {
+            let mut cmd = tokio::process::Command::new(self.binary);
+            cmd.args(["logs", "--tail", &lines.to_string()]);
+            if follow {
+                cmd.arg("--follow");
+            }
+            let status = cmd.arg(id_or_name).status().await.map_err(|e| {
+                ContainerRuntimeError::Other(format!(
+                    "Failed to execute {} logs: {}",
+                    self.binary, e
+                ))
+            })?;
+
+            if !status.success() {
+                return Err(ContainerRuntimeError::ContainerNotFound(
+                    id_or_name.to_string(),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn get_stats<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerStats> {
+        Box::pin(async move {
+            let output = tokio::process::Command::new(self.binary)
+                .args(["stats", "--no-stream", "--format", "{{json .}}", id_or_name])
+                .output()
+                .await
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} stats: {}",
+                        self.binary, e
+                    ))
+                })?;
+
+            if !output.status.success() {
+                return Err(ContainerRuntimeError::ContainerNotFound(
+                    id_or_name.to_string(),
+                ));
+            }
+
+            Self::parse_stats(&String::from_utf8_lossy(&output.stdout))
+        })
+    }
+
+    fn get_all_stats<'a>(&'a self) -> RuntimeFuture<'a, Vec<(String, ContainerStats)>> {
+        Box::pin(async move {
+            // Without names, stats covers every running container in one sample
+            let output = tokio::process::Command::new(self.binary)
+                .args(["stats", "--no-stream", "--format", "{{json .}}"])
+                .output()
+                .await
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} stats: {}",
+                        self.binary, e
+                    ))
+                })?;
+
+            if !output.status.success() {
+                return Err(ContainerRuntimeError::Other(format!(
+                    "{} stats failed: {}",
+                    self.binary,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+
+            Self::parse_all_stats(&String::from_utf8_lossy(&output.stdout))
+        })
+    }
+
+    fn container_config<'a>(&'a self, id_or_name: &'a str) -> RuntimeFuture<'a, ContainerConfig> {
+        Box::pin(async move {
+            let output = Command::new(self.binary)
+                .args(["inspect", "--type", "container", id_or_name])
+                .output()
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} inspect: {}",
+                        self.binary, e
+                    ))
+                })?;
+
+            if !output.status.success() {
+                return Err(ContainerRuntimeError::ContainerNotFound(
+                    id_or_name.to_string(),
+                ));
+            }
+
+            let inspect = String::from_utf8_lossy(&output.stdout);
+            let image = serde_json::from_str::<serde_json::Value>(&inspect)
+                .ok()
+                .and_then(|value| value[0]["Config"]["Image"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            Self::parse_inspect_config(&inspect, &self.image_env(&image))
+        })
+    }
+
+    fn disk_usage<'a>(&'a self) -> RuntimeFuture<'a, Vec<DiskUsage>> {
+        Box::pin(async move {
+            let output = tokio::process::Command::new(self.binary)
+                .args(["system", "df", "--format", "{{json .}}"])
+                .output()
+                .await
+                .map_err(|e| {
+                    ContainerRuntimeError::Other(format!(
+                        "Failed to execute {} system df: {}",
+                        self.binary, e
+                    ))
+                })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(ContainerRuntimeError::Other(stderr.trim().to_string()));
+            }
+
+            Self::parse_disk_usage(&String::from_utf8_lossy(&output.stdout))
+        })
+    }
+
+    fn prune<'a>(&'a self, options: PruneOptions) -> RuntimeFuture<'a, u64> {
+        Box::pin(async move {
+            let mut cmd = Command::new(self.binary);
+            cmd.args(["system", "prune", "--force"]);
+            if options.images {
+                cmd.arg("--all");
+            }
+            if options.volumes {
+                cmd.arg("--volumes");
+            }
+            log_info!("Pruning {} storage: {:?}", self.name, options);
+            let output = exec::run(&mut cmd).map_err(|e| {
+                ContainerRuntimeError::Other(format!(
+                    "Failed to execute {} system prune: {}",
+                    self.binary, e
+                ))
+            })?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(ContainerRuntimeError::Other(stderr.trim().to_string()));
+            }
+
+            Ok(Self::parse_reclaimed(&String::from_utf8_lossy(
+                &output.stdout,
+            )))
         })
     }
 }
@@ -393,3 +875,190 @@ impl Default for DockerRuntime {
         Self::new()
     }
 }
+
+/// Bytes from a size as `docker stats` prints it: decimal units for I/O
+/// (`1.2kB`, `3MB`), binary ones for memory (`512MiB`).
+fn parse_docker_size(size: &str) -> u64 {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier: f64 = match unit.trim() {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return 0,
+    };
+    number
+        .parse::<f64>()
+        .map(|value| (value * multiplier).round() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_line_is_parsed() {
+        let line = r#"{"BlockIO":"4.1MB / 0B","CPUPerc":"12.50%","Container":"web","MemPerc":"0.63%","MemUsage":"12.5MiB / 1.5GiB","Name":"web","NetIO":"1.2kB / 648B","PIDs":"2"}"#;
+        let stats = DockerRuntime::parse_stats(line).unwrap();
+        assert_eq!(stats.cpu_usage_percent, 12.5);
+        assert_eq!(stats.memory_usage_mb, 12);
+        assert_eq!(stats.memory_limit_mb, 1536);
+        assert_eq!(stats.network_rx_bytes, 1200);
+        assert_eq!(stats.network_tx_bytes, 648);
+        assert_eq!(stats.disk_read_bytes, 4_100_000);
+        assert_eq!(stats.disk_write_bytes, 0);
+
+        assert_eq!(parse_docker_size("--"), 0);
+        assert!(DockerRuntime::parse_stats("").is_err());
+
+        let all = format!(
+            "{}\n{}\n",
+            line,
+            r#"{"BlockIO":"0B / 0B","CPUPerc":"0.10%","MemUsage":"4MiB / 1GiB","Name":"db","NetIO":"0B / 0B"}"#
+        );
+        let all = DockerRuntime::parse_all_stats(&all).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].0, "web");
+        assert_eq!(all[0].1.cpu_usage_percent, 12.5);
+        assert_eq!(all[1].0, "db");
+        assert_eq!(all[1].1.memory_usage_mb, 4);
+        assert!(DockerRuntime::parse_all_stats("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn image_checks_ask_the_registry_and_keep_its_reason() {
+        assert_eq!(
+            DockerRuntime::check_image_args("nginx:1.27"),
+            ["manifest", "inspect", "nginx:1.27"]
+        );
+        assert_eq!(
+            image_check_reason("no such manifest: docker.io/library/nginx:nope\n"),
+            "no such manifest: docker.io/library/nginx:nope"
+        );
+        // Podman labels the message and may print warnings before it
+        assert_eq!(
+            image_check_reason(
+                "WARN[0000] Failed to decode the keys\nError: reading manifest nope in docker.io/library/nginx: manifest unknown\n\n"
+            ),
+            "reading manifest nope in docker.io/library/nginx: manifest unknown"
+        );
+        assert_eq!(image_check_reason(""), "");
+    }
+
+    #[test]
+    fn system_df_and_prune_output_are_parsed() {
+        let docker = concat!(
+            r#"{"Active":"2","Reclaimable":"1.2GB (50%)","Size":"2.4GB","TotalCount":"5","Type":"Images"}"#,
+            "\n",
+            r#"{"Active":"0","Reclaimable":"0B","Size":"0B","TotalCount":"0","Type":"Local Volumes"}"#,
+        );
+        let usage = DockerRuntime::parse_disk_usage(docker).unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].kind, "Images");
+        assert_eq!(usage[0].total, 5);
+        assert_eq!(usage[0].active, 2);
+        assert_eq!(usage[0].size_bytes, 2_400_000_000);
+        assert_eq!(usage[0].reclaimable_bytes, 1_200_000_000);
+        assert_eq!(usage[1].reclaimable_bytes, 0);
+
+        let podman = r#"{"Type":"Containers","Total":3,"Active":1,"Size":"12.5MB","Reclaimable":"4MB (32%)"}"#;
+        let usage = DockerRuntime::parse_disk_usage(podman).unwrap();
+        assert_eq!(usage[0].total, 3);
+        assert_eq!(usage[0].reclaimable_bytes, 4_000_000);
+
+        let prune = "Deleted Images:\nuntagged: nginx:1.25\n\nTotal reclaimed space: 187.3MB\n";
+        assert_eq!(DockerRuntime::parse_reclaimed(prune), 187_300_000);
+        assert_eq!(DockerRuntime::parse_reclaimed(""), 0);
+    }
+
+    #[test]
+    fn inspect_config_keeps_host_ips_named_volumes_and_user_env() {
+        let inspect = r#"[{
+            "Config": {
+                "Image": "postgres:16",
+                "Env": ["POSTGRES_PASSWORD=secret", "PATH=/usr/local/bin:/usr/bin", "PGDATA=/data"]
+            },
+            "HostConfig": {
+                "Binds": ["/srv/conf:/etc/postgresql:ro"],
+                "PortBindings": {
+                    "5432/tcp": [{"HostIp": "127.0.0.1", "HostPort": "5432"}],
+                    "9187/tcp": [{"HostIp": "", "HostPort": "9187"}, {"HostIp": "::1", "HostPort": "9187"}]
+                },
+                "NetworkMode": "bridge",
+                "RestartPolicy": {"Name": "always"}
+            },
+            "Mounts": [
+                {"Type": "bind", "Source": "/srv/conf", "Destination": "/etc/postgresql", "RW": false},
+                {"Type": "volume", "Name": "pgdata", "Destination": "/data", "RW": true},
+                {"Type": "volume", "Name": "backups", "Destination": "/backups", "RW": false},
+                {"Type": "volume", "Name": "4f1c2a9e8b7d6c5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b2a19", "Destination": "/tmp/cache", "RW": true}
+            ]
+        }]"#;
+        let image_env = vec![
+            "PATH=/usr/local/bin:/usr/bin".to_string(),
+            "PGDATA=/var/lib/postgresql/data".to_string(),
+        ];
+        let config = DockerRuntime::parse_inspect_config(inspect, &image_env).unwrap();
+
+        assert_eq!(
+            config.ports,
+            [
+                "127.0.0.1:5432:5432/tcp",
+                "9187:9187/tcp",
+                "[::1]:9187:9187/tcp"
+            ]
+        );
+        assert_eq!(
+            config.volumes,
+            [
+                "/srv/conf:/etc/postgresql:ro",
+                "pgdata:/data",
+                "backups:/backups:ro"
+            ]
+        );
+        let mut env: Vec<_> = config.env.iter().collect();
+        env.sort();
+        assert_eq!(
+            env,
+            [
+                (&"PGDATA".to_string(), &"/data".to_string()),
+                (&"POSTGRES_PASSWORD".to_string(), &"secret".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn inspect_state_fills_restart_details() {
+        let runtime = DockerRuntime {
+            binary: "docker",
+            name: "Docker",
+            available: false,
+            version: None,
+        };
+        let mut info = runtime
+            .parse_docker_ps_line("abc123|api|nginx:1.27|Restarting (1) 3 seconds ago")
+            .unwrap();
+        DockerRuntime::apply_inspect_state(
+            &mut info,
+            "4|1|false|0|on-failure|exec: \"serve\": not found | check PATH\n",
+        );
+        assert_eq!(info.restart_count, 4);
+        assert_eq!(info.exit_code, Some(1));
+        assert_eq!(info.pid, None);
+        assert_eq!(info.restart_policy, Some(RestartPolicy::OnFailure));
+        assert_eq!(
+            info.exit_reason.as_deref(),
+            Some("exec: \"serve\": not found | check PATH")
+        );
+    }
+}
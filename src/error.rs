@@ -14,6 +14,7 @@ pub enum NovaError {
     SerdeError(String),
     SnapshotNotFound(String),
     SnapshotHasChildren,
+    SnapshotQuotaExceeded(String),
 }
 
 impl fmt::Display for NovaError {
@@ -34,6 +35,7 @@ impl fmt::Display for NovaError {
                 f,
                 "Snapshot has children and cannot be deleted without --children flag"
             ),
+            NovaError::SnapshotQuotaExceeded(msg) => write!(f, "Snapshot quota exceeded: {}", msg),
         }
     }
 }
@@ -0,0 +1,138 @@
+//! Execution of commands that change the host.
+//!
+//! Commands that create, delete or rewire something (virsh lifecycle calls,
+//! `ip`/`ovs-vsctl`/`bridge`, firewall rules, sysfs writes for GPU binding)
+//! go through [`run`] and friends instead of calling `Command::output`
+//! directly. With `nova --dry-run` they are printed to stderr as shell lines
+//! and reported as successful without running. Read-only queries still run,
+//! so decisions Nova takes along the way reflect the real host.
+
+use crate::{log_debug, log_info};
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Run `cmd` and collect its output, or print it under `--dry-run`.
+pub fn run(cmd: &mut Command) -> io::Result<Output> {
+    if is_dry_run() {
+        announce(&render(cmd));
+        return Ok(dry_run_output());
+    }
+    log_debug!("Running: {}", render(cmd));
+    cmd.output()
+}
+
+/// For commands that are spawned rather than waited on: under `--dry-run`,
+/// print `cmd` and return true so the caller skips starting it.
+pub fn skip(cmd: &Command) -> bool {
+    if is_dry_run() {
+        announce(&render(cmd));
+    }
+    is_dry_run()
+}
+
+/// Run `cmd` with `input` on its stdin, e.g. `nft -f -` with a ruleset.
+pub fn run_with_input(cmd: &mut Command, input: &str) -> io::Result<Output> {
+    if is_dry_run() {
+        announce(&format!(
+            "{} <<'EOF'\n{}\nEOF",
+            render(cmd),
+            input.trim_end()
+        ));
+        return Ok(dry_run_output());
+    }
+    log_debug!("Running: {}", render(cmd));
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+/// Write `contents` to a sysfs attribute or a host config file (udev rules,
+/// dnsmasq configs), or print the equivalent `echo` under `--dry-run`.
+pub fn write_file<P: AsRef<Path>>(path: P, contents: &str) -> io::Result<()> {
+    let path = path.as_ref();
+    let line = format!(
+        "echo {} > {}",
+        quote(OsStr::new(contents.trim_end_matches('\n'))),
+        quote(path.as_os_str())
+    );
+    if is_dry_run() {
+        announce(&line);
+        return Ok(());
+    }
+    log_debug!("Running: {}", line);
+    std::fs::write(path, contents)
+}
+
+/// `cmd` as a line that can be pasted into a shell.
+pub fn render(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote(word: &OsStr) -> String {
+    let word = word.to_string_lossy();
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+    if plain {
+        word.into_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+fn announce(line: &str) {
+    log_info!("Dry run: {}", line);
+    eprintln!("[dry-run] {}", line);
+}
+
+fn dry_run_output() -> Output {
+    Output {
+        status: ExitStatus::from_raw(0),
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_render_as_shell_lines() {
+        let mut cmd = Command::new("virsh");
+        cmd.args(["snapshot-create-as", "web", "before upgrade", "it's"]);
+        assert_eq!(
+            render(&cmd),
+            r"virsh snapshot-create-as web 'before upgrade' 'it'\''s'"
+        );
+        let mut cmd = Command::new("ip");
+        cmd.args(["addr", "replace", "10.0.0.1/24", "dev", "br0"]);
+        assert_eq!(render(&cmd), "ip addr replace 10.0.0.1/24 dev br0");
+        assert_eq!(quote(OsStr::new("")), "''");
+    }
+}
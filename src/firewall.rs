@@ -1,9 +1,16 @@
-use crate::{NovaError, Result, log_debug, log_error, log_info};
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
 use std::process::Command;
 
+/// Port rules Nova added through firewalld, whose rich rules have no comment
+/// to recognise them by.
+pub const FIREWALLD_RULES_PATH: &str = "/var/lib/nova/firewalld-rules.json";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum FirewallBackend {
     Iptables,
     Nftables,
@@ -11,6 +18,122 @@ pub enum FirewallBackend {
     Ufw,
 }
 
+impl FirewallBackend {
+    /// Tool Nova drives to manage rules in this backend.
+    pub fn command(&self) -> &'static str {
+        match self {
+            FirewallBackend::Iptables => "iptables",
+            FirewallBackend::Nftables => "nft",
+            FirewallBackend::Firewalld => "firewall-cmd",
+            FirewallBackend::Ufw => "ufw",
+        }
+    }
+}
+
+impl fmt::Display for FirewallBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FirewallBackend::Iptables => "iptables",
+            FirewallBackend::Nftables => "nftables",
+            FirewallBackend::Firewalld => "firewalld",
+            FirewallBackend::Ufw => "ufw",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `[firewall]` section of the NovaFile.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FirewallSettings {
+    /// Backend Nova manages rules through; required when several are active
+    #[serde(default)]
+    pub preferred_backend: Option<FirewallBackend>,
+}
+
+/// What the host reports about each firewall backend, used to tell which
+/// ones currently hold rules.
+#[derive(Debug, Clone, Default)]
+pub struct BackendProbe {
+    pub firewalld_running: bool,
+    pub ufw_active: bool,
+    /// `nft list tables` output
+    pub nft_tables: String,
+    /// `-A` rule lines in the legacy (non-nf_tables) iptables ruleset
+    pub legacy_iptables_rules: usize,
+}
+
+impl BackendProbe {
+    /// Query the host. Probes that fail (missing tool, no permission) count
+    /// as inactive.
+    pub fn collect() -> Self {
+        let stdout = |program: &str, args: &[&str]| -> Option<String> {
+            Command::new(program)
+                .args(args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        };
+
+        let legacy_save = if FirewallManager::command_exists("iptables-legacy-save") {
+            stdout("iptables-legacy-save", &[])
+        } else if stdout("iptables", &["-V"]).is_some_and(|version| version.contains("legacy")) {
+            stdout("iptables-save", &[])
+        } else {
+            None
+        };
+
+        Self {
+            firewalld_running: stdout("firewall-cmd", &["--state"])
+                .is_some_and(|state| state.trim() == "running"),
+            ufw_active: stdout("ufw", &["status"])
+                .is_some_and(|status| status.contains("Status: active")),
+            nft_tables: stdout("nft", &["list", "tables"]).unwrap_or_default(),
+            legacy_iptables_rules: legacy_save
+                .map(|save| save.lines().filter(|line| line.starts_with("-A ")).count())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Backends that currently hold rules. nftables tables owned by
+    /// firewalld, Nova, libvirt or Docker don't count as an nftables
+    /// firewall, and legacy iptables rules are attributed to ufw when ufw is
+    /// active, since it generates them.
+    pub fn active_backends(&self) -> Vec<FirewallBackend> {
+        let mut active = Vec::new();
+        if self.firewalld_running {
+            active.push(FirewallBackend::Firewalld);
+        }
+        if self.ufw_active {
+            active.push(FirewallBackend::Ufw);
+        }
+        let foreign_nft_table = self
+            .nft_tables
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("table "))
+            .filter_map(|table| table.split_whitespace().last())
+            .any(|table| !is_service_nft_table(table));
+        if foreign_nft_table {
+            active.push(FirewallBackend::Nftables);
+        }
+        if self.legacy_iptables_rules > 0 && !self.ufw_active {
+            active.push(FirewallBackend::Iptables);
+        }
+        active
+    }
+}
+
+/// nftables tables that firewalld, Nova's own NAT, libvirt networks and
+/// Docker create for themselves.
+fn is_service_nft_table(name: &str) -> bool {
+    ["firewalld", "nova", "libvirt", "docker"]
+        .iter()
+        .any(|owner| match name.strip_prefix(owner) {
+            Some(rest) => rest.is_empty() || rest.starts_with(['_', '-']),
+            None => false,
+        })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FirewallRule {
     pub id: String,
@@ -134,8 +257,172 @@ pub enum InterfaceState {
     Unknown,
 }
 
+/// Start of the comment on every rule Nova adds
+pub const RULE_TAG_PREFIX: &str = "nova:";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+}
+
+impl RuleAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleAction::Allow => "allow",
+            RuleAction::Deny => "deny",
+        }
+    }
+}
+
+/// An inbound port rule managed with `nova firewall allow|deny`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortRule {
+    /// Chain to add the rule to, or the backend's input chain when unset.
+    /// nftables chains are written `family table chain`, e.g.
+    /// `inet filter input`; ufw and firewalld have no chains to pick.
+    pub chain: Option<String>,
+    /// `Tcp` or `Udp`
+    pub protocol: Protocol,
+    pub port: u16,
+    /// Source address or CIDR; any source when unset
+    pub source: Option<String>,
+    pub action: RuleAction,
+    pub comment: Option<String>,
+}
+
+impl PortRule {
+    pub fn validate(&self, backend: &FirewallBackend) -> Result<()> {
+        let invalid = |message: String| Err(NovaError::ConfigError(message));
+        if !matches!(self.protocol, Protocol::Tcp | Protocol::Udp) {
+            return invalid("Port rules need protocol tcp or udp".to_string());
+        }
+        if self.port == 0 {
+            return invalid("Port 0 cannot be opened or closed".to_string());
+        }
+        if let Some(source) = &self.source {
+            let (address, prefix) = match source.split_once('/') {
+                Some((address, prefix)) => (address, Some(prefix)),
+                None => (source.as_str(), None),
+            };
+            let max_prefix = match address.parse::<std::net::IpAddr>() {
+                Ok(std::net::IpAddr::V4(_)) => 32,
+                Ok(std::net::IpAddr::V6(_)) => 128,
+                Err(_) => return invalid(format!("Invalid source address '{}'", source)),
+            };
+            if prefix.is_some_and(|prefix| prefix.parse::<u8>().map_or(true, |p| p > max_prefix)) {
+                return invalid(format!("Invalid prefix length in '{}'", source));
+            }
+        }
+        if let Some(comment) = &self.comment
+            && comment.contains(['"', '\'', '\n', '\\'])
+        {
+            return invalid(
+                "Rule comments cannot contain quotes, backslashes or newlines".to_string(),
+            );
+        }
+        if let Some(chain) = &self.chain {
+            match backend {
+                FirewallBackend::Nftables if chain.split_whitespace().count() != 3 => {
+                    return invalid(format!(
+                        "nftables chain '{}' must be written as `family table chain`, e.g. `inet filter input`",
+                        chain
+                    ));
+                }
+                FirewallBackend::Iptables if chain.contains(char::is_whitespace) => {
+                    return invalid(format!("Invalid iptables chain '{}'", chain));
+                }
+                FirewallBackend::Ufw | FirewallBackend::Firewalld => {
+                    return invalid(format!("{} rules cannot be placed in a chain", backend));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Chain the rule goes in for `backend`.
+    pub fn chain_or_default(&self, backend: &FirewallBackend) -> String {
+        match (&self.chain, backend) {
+            (Some(chain), _) => chain.clone(),
+            (None, FirewallBackend::Nftables) => "inet filter input".to_string(),
+            (None, _) => "INPUT".to_string(),
+        }
+    }
+
+    /// Comment identifying the rule, e.g. `nova: allow tcp/8080 from 10.0.0.0/8 - web`.
+    /// It spells out the whole rule so each tag is unique and can be parsed
+    /// back with [`Self::from_tag`].
+    pub fn tag(&self) -> String {
+        let mut tag = format!(
+            "{} {} {}/{}",
+            RULE_TAG_PREFIX,
+            self.action.as_str(),
+            self.protocol_name(),
+            self.port
+        );
+        if let Some(source) = &self.source {
+            tag.push_str(&format!(" from {}", source));
+        }
+        if let Some(comment) = &self.comment {
+            tag.push_str(&format!(" - {}", comment));
+        }
+        tag
+    }
+
+    /// The rule a tag describes, without its chain.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let spec = tag.strip_prefix(RULE_TAG_PREFIX)?.trim_start();
+        let (spec, comment) = match spec.split_once(" - ") {
+            Some((spec, comment)) => (spec, Some(comment.to_string())),
+            None => (spec, None),
+        };
+        let mut words = spec.split_whitespace();
+        let action = match words.next()? {
+            "allow" => RuleAction::Allow,
+            "deny" => RuleAction::Deny,
+            _ => return None,
+        };
+        let (protocol, port) = words.next()?.split_once('/')?;
+        let protocol = match protocol {
+            "tcp" => Protocol::Tcp,
+            "udp" => Protocol::Udp,
+            _ => return None,
+        };
+        let source = match (words.next(), words.next()) {
+            (Some("from"), Some(source)) => Some(source.to_string()),
+            (None, _) => None,
+            _ => return None,
+        };
+        Some(Self {
+            chain: None,
+            protocol,
+            port: port.parse().ok()?,
+            source,
+            action,
+            comment,
+        })
+    }
+
+    fn protocol_name(&self) -> &'static str {
+        match self.protocol {
+            Protocol::Udp => "udp",
+            _ => "tcp",
+        }
+    }
+
+    fn is_ipv6(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.contains(':'))
+    }
+}
+
 pub struct FirewallManager {
     backend: FirewallBackend,
+    active_backends: Vec<FirewallBackend>,
+    preferred_backend: Option<FirewallBackend>,
     tables: HashMap<String, FirewallTable>,
     active_flows: Vec<TrafficFlow>,
     _interfaces: HashMap<String, NetworkInterface>,
@@ -169,11 +456,25 @@ pub enum ConflictSeverity {
 
 impl FirewallManager {
     pub fn new() -> Result<Self> {
-        let backend = Self::detect_backend()?;
+        let active_backends = BackendProbe::collect().active_backends();
+        // Prefer the one backend actually holding rules over the first tool
+        // found in PATH
+        let backend = match active_backends.as_slice() {
+            [only] => only.clone(),
+            _ => Self::detect_backend()?,
+        };
         log_info!("Detected firewall backend: {:?}", backend);
+        if active_backends.len() > 1 {
+            log_warn!(
+                "Multiple firewall backends are active: {}",
+                Self::backend_list(&active_backends)
+            );
+        }
 
         Ok(Self {
             backend,
+            active_backends,
+            preferred_backend: None,
             tables: HashMap::new(),
             active_flows: Vec::new(),
             _interfaces: HashMap::new(),
@@ -181,6 +482,81 @@ impl FirewallManager {
         })
     }
 
+    /// Manager using the NovaFile's pinned backend, if any.
+    pub fn with_settings(settings: &FirewallSettings) -> Result<Self> {
+        let mut manager = Self::new()?;
+        if let Some(backend) = &settings.preferred_backend {
+            manager.set_preferred_backend(backend.clone())?;
+        }
+        Ok(manager)
+    }
+
+    /// Pin the backend Nova manages rules through, regardless of what else
+    /// is active. Persist it as `[firewall] preferred_backend`.
+    pub fn set_preferred_backend(&mut self, backend: FirewallBackend) -> Result<()> {
+        if !Self::command_exists(backend.command()) {
+            return Err(NovaError::ConfigError(format!(
+                "Cannot use {} as the firewall backend: `{}` is not installed",
+                backend,
+                backend.command()
+            )));
+        }
+        log_info!("Pinned firewall backend: {}", backend);
+        self.backend = backend.clone();
+        self.preferred_backend = Some(backend);
+        Ok(())
+    }
+
+    pub fn preferred_backend(&self) -> Option<&FirewallBackend> {
+        self.preferred_backend.as_ref()
+    }
+
+    /// Backends found holding rules when the manager was created.
+    pub fn active_backends(&self) -> &[FirewallBackend] {
+        &self.active_backends
+    }
+
+    /// Warning for `nova firewall status` when more than one backend holds
+    /// rules.
+    pub fn backend_conflict(&self) -> Option<String> {
+        if self.active_backends.len() < 2 {
+            return None;
+        }
+        let list = Self::backend_list(&self.active_backends);
+        Some(match &self.preferred_backend {
+            Some(preferred) => format!(
+                "Multiple firewall backends are active ({}). Nova manages {} (pinned); rules \
+                 in the others still filter traffic.",
+                list, preferred
+            ),
+            None => format!(
+                "Multiple firewall backends are active ({}), so rules could land in the wrong \
+                 one. Pin the one Nova should manage with `nova firewall backend <name>`.",
+                list
+            ),
+        })
+    }
+
+    /// Refuse to change rules while it is unclear which backend they belong in.
+    fn ensure_unambiguous_backend(&self) -> Result<()> {
+        if self.preferred_backend.is_none() && self.active_backends.len() > 1 {
+            return Err(NovaError::ConfigError(format!(
+                "Refusing to change firewall rules: {} are all active. Pin one with `nova \
+                 firewall backend <name>` (stored as [firewall] preferred_backend).",
+                Self::backend_list(&self.active_backends)
+            )));
+        }
+        Ok(())
+    }
+
+    fn backend_list(backends: &[FirewallBackend]) -> String {
+        backends
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     fn detect_backend() -> Result<FirewallBackend> {
         // Check for firewalld first (most user-friendly)
         if Self::command_exists("firewall-cmd") {
@@ -405,167 +781,97 @@ impl FirewallManager {
         Ok(())
     }
 
-    pub async fn add_rule(&mut self, rule: FirewallRule) -> Result<()> {
-        log_info!(
-            "Adding firewall rule: {:?}",
-            rule.comment.as_deref().unwrap_or("unnamed")
-        );
-
-        // Validate rule
-        self.validate_rule(&rule)?;
-
-        // Apply rule to backend
-        match self.backend {
-            FirewallBackend::Iptables => self.add_iptables_rule(&rule).await,
-            FirewallBackend::Nftables => self.add_nftables_rule(&rule).await,
-            FirewallBackend::Firewalld => self.add_firewalld_rule(&rule).await,
-            FirewallBackend::Ufw => self.add_ufw_rule(&rule).await,
-        }?;
-
-        // Add to local state
-        if let Some(table) = self.tables.get_mut(&rule.table)
-            && let Some(chain) = table.chains.get_mut(&rule.chain)
-        {
-            chain.rules.push(rule);
-        }
-
-        // Reanalyze for conflicts
-        self.analyze_rule_conflicts().await?;
-
-        Ok(())
-    }
-
-    fn validate_rule(&self, rule: &FirewallRule) -> Result<()> {
-        // Validate rule syntax and parameters
-        if rule.chain.is_empty() {
-            return Err(NovaError::ConfigError(
-                "Chain name cannot be empty".to_string(),
-            ));
+    /// Add `rule` through the managed backend, tagged with
+    /// [`PortRule::tag`] so [`Self::list_rules`] finds it again.
+    pub async fn add_rule(&self, rule: &PortRule) -> Result<()> {
+        log_info!("Adding firewall rule: {}", rule.tag());
+        self.ensure_unambiguous_backend()?;
+        rule.validate(&self.backend)?;
+        for args in add_rule_args(&self.backend, rule) {
+            run_firewall_command(&args)?;
         }
 
-        if rule.table.is_empty() {
-            return Err(NovaError::ConfigError(
-                "Table name cannot be empty".to_string(),
-            ));
+        if self.backend == FirewallBackend::Firewalld {
+            let path = Path::new(FIREWALLD_RULES_PATH);
+            let mut recorded = load_firewalld_record(path);
+            if !recorded.contains(rule) {
+                recorded.push(rule.clone());
+                save_firewalld_record(path, &recorded)?;
+            }
         }
-
-        // Additional validation logic
         Ok(())
     }
 
-    async fn add_iptables_rule(&self, rule: &FirewallRule) -> Result<()> {
-        let mut cmd = Command::new("iptables");
-        cmd.args(["-t", &rule.table, "-A", &rule.chain]);
-
-        // Build iptables command from rule
-        if let Some(protocol) = &rule.protocol {
-            match protocol {
-                Protocol::Tcp => cmd.args(["-p", "tcp"]),
-                Protocol::Udp => cmd.args(["-p", "udp"]),
-                Protocol::Icmp => cmd.args(["-p", "icmp"]),
-                Protocol::All => &mut cmd,
-                Protocol::Custom(p) => cmd.args(["-p", p]),
-            };
-        }
-
-        if let Some(source) = &rule.source {
-            if source.negated {
-                cmd.arg("!");
+    /// Remove a rule added by [`Self::add_rule`].
+    pub async fn remove_rule(&self, rule: &PortRule) -> Result<()> {
+        log_info!("Removing firewall rule: {}", rule.tag());
+        self.ensure_unambiguous_backend()?;
+        rule.validate(&self.backend)?;
+
+        // Only remove firewalld rules Nova added, not a user's identical one
+        let firewalld_record = Path::new(FIREWALLD_RULES_PATH);
+        let mut recorded = Vec::new();
+        if self.backend == FirewallBackend::Firewalld {
+            recorded = load_firewalld_record(firewalld_record);
+            if !recorded.contains(rule) {
+                return Err(NovaError::NetworkNotFound(format!(
+                    "Nova has no firewalld rule '{}' on record",
+                    firewalld_rich_rule(rule)
+                )));
             }
-            cmd.args(["-s", &source.address]);
         }
 
-        if let Some(dest) = &rule.destination {
-            if dest.negated {
-                cmd.arg("!");
-            }
-            cmd.args(["-d", &dest.address]);
-        }
-
-        if let Some(port_range) = &rule.port_range {
-            if port_range.negated {
-                cmd.arg("!");
-            }
-            cmd.args(["--dport", &port_range.start.to_string()]);
-        }
-
-        match &rule.target {
-            RuleTarget::Accept => cmd.args(["-j", "ACCEPT"]),
-            RuleTarget::Drop => cmd.args(["-j", "DROP"]),
-            RuleTarget::Reject => cmd.args(["-j", "REJECT"]),
-            RuleTarget::Log => cmd.args(["-j", "LOG"]),
-            _ => &mut cmd,
+        let handle = if self.backend == FirewallBackend::Nftables {
+            let chain = rule.chain_or_default(&self.backend);
+            let mut args = vec!["nft".to_string(), "-a".to_string(), "list".to_string()];
+            args.push("chain".to_string());
+            args.extend(chain.split_whitespace().map(str::to_string));
+            let listing = run_firewall_command(&args)?;
+            let handle = find_nft_handle(&listing, &rule.tag()).ok_or_else(|| {
+                NovaError::NetworkNotFound(format!("No rule tagged '{}' in {}", rule.tag(), chain))
+            })?;
+            Some(handle)
+        } else {
+            None
         };
 
-        let output = cmd.output().map_err(|_| NovaError::SystemCommandFailed)?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            log_error!("Failed to add iptables rule: {}", error);
-            return Err(NovaError::SystemCommandFailed);
+        for args in remove_rule_args(&self.backend, rule, handle) {
+            run_firewall_command(&args)?;
         }
 
+        if self.backend == FirewallBackend::Firewalld {
+            recorded.retain(|recorded| recorded != rule);
+            save_firewalld_record(firewalld_record, &recorded)?;
+        }
         Ok(())
     }
 
-    async fn add_nftables_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        // Implement nftables rule addition
-        Ok(())
-    }
-
-    async fn add_firewalld_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        // Implement firewalld rule addition
-        Ok(())
-    }
-
-    async fn add_ufw_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        // Implement UFW rule addition
-        Ok(())
-    }
-
-    pub async fn remove_rule(&mut self, rule_id: &str) -> Result<()> {
-        log_info!("Removing firewall rule: {}", rule_id);
-
-        // Find and remove rule from local state
-        for table in self.tables.values_mut() {
-            for chain in table.chains.values_mut() {
-                if let Some(pos) = chain.rules.iter().position(|r| r.id == rule_id) {
-                    let rule = chain.rules.remove(pos);
-
-                    // Remove from backend
-                    match self.backend {
-                        FirewallBackend::Iptables => self.remove_iptables_rule(&rule).await?,
-                        FirewallBackend::Nftables => self.remove_nftables_rule(&rule).await?,
-                        FirewallBackend::Firewalld => self.remove_firewalld_rule(&rule).await?,
-                        FirewallBackend::Ufw => self.remove_ufw_rule(&rule).await?,
-                    }
-
-                    return Ok(());
+    /// Rules Nova added, recognised by their tag. firewalld rich rules
+    /// cannot carry a comment, so those come from Nova's own record.
+    pub async fn list_rules(&self) -> Result<Vec<PortRule>> {
+        let argv = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        match self.backend {
+            FirewallBackend::Iptables => {
+                let mut rules =
+                    parse_iptables_rules(&run_firewall_command(&argv(&["iptables", "-S"]))?);
+                if FirewallManager::command_exists("ip6tables")
+                    && let Ok(listing) = run_firewall_command(&argv(&["ip6tables", "-S"]))
+                {
+                    rules.extend(parse_iptables_rules(&listing));
                 }
+                Ok(rules)
             }
+            FirewallBackend::Nftables => Ok(parse_nft_rules(&run_firewall_command(&argv(&[
+                "nft", "list", "ruleset",
+            ]))?)),
+            FirewallBackend::Ufw => Ok(parse_ufw_rules(&run_firewall_command(&argv(&[
+                "ufw", "status",
+            ]))?)),
+            FirewallBackend::Firewalld => Ok(parse_firewalld_rules(
+                &run_firewall_command(&argv(&["firewall-cmd", "--list-rich-rules"]))?,
+                &load_firewalld_record(Path::new(FIREWALLD_RULES_PATH)),
+            )),
         }
-
-        Err(NovaError::NetworkNotFound(format!(
-            "Rule {} not found",
-            rule_id
-        )))
-    }
-
-    async fn remove_iptables_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        // Implement iptables rule removal
-        Ok(())
-    }
-
-    async fn remove_nftables_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        Ok(())
-    }
-
-    async fn remove_firewalld_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        Ok(())
-    }
-
-    async fn remove_ufw_rule(&self, _rule: &FirewallRule) -> Result<()> {
-        Ok(())
     }
 
     pub async fn analyze_rule_conflicts(&mut self) -> Result<()> {
@@ -754,6 +1060,8 @@ impl Default for FirewallManager {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self {
             backend: FirewallBackend::Iptables,
+            active_backends: Vec::new(),
+            preferred_backend: None,
             tables: HashMap::new(),
             active_flows: Vec::new(),
             _interfaces: HashMap::new(),
@@ -762,6 +1070,245 @@ impl Default for FirewallManager {
     }
 }
 
+/// Commands that add `rule` with `backend`.
+pub fn add_rule_args(backend: &FirewallBackend, rule: &PortRule) -> Vec<Vec<String>> {
+    rule_args(backend, rule, true, None)
+}
+
+/// Commands that remove `rule`; nftables deletes by the rule's `handle`.
+pub fn remove_rule_args(
+    backend: &FirewallBackend,
+    rule: &PortRule,
+    handle: Option<u64>,
+) -> Vec<Vec<String>> {
+    rule_args(backend, rule, false, handle)
+}
+
+fn rule_args(
+    backend: &FirewallBackend,
+    rule: &PortRule,
+    add: bool,
+    handle: Option<u64>,
+) -> Vec<Vec<String>> {
+    let protocol = rule.protocol_name().to_string();
+    let port = rule.port.to_string();
+    let tag = rule.tag();
+    let chain = rule.chain_or_default(backend);
+    let owned = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    match backend {
+        FirewallBackend::Iptables => {
+            let program = if rule.is_ipv6() {
+                "ip6tables"
+            } else {
+                "iptables"
+            };
+            // Inserted at the head so an earlier catch-all rule cannot shadow it
+            let mut args = owned(&[program, if add { "-I" } else { "-D" }, &chain]);
+            args.extend(owned(&["-p", &protocol, "--dport", &port]));
+            if let Some(source) = &rule.source {
+                args.extend(owned(&["-s", source]));
+            }
+            args.extend(owned(&["-m", "comment", "--comment", &tag]));
+            args.extend(owned(&[
+                "-j",
+                match rule.action {
+                    RuleAction::Allow => "ACCEPT",
+                    RuleAction::Deny => "DROP",
+                },
+            ]));
+            vec![args]
+        }
+        FirewallBackend::Nftables => {
+            let mut args = owned(&["nft", if add { "insert" } else { "delete" }, "rule"]);
+            args.extend(chain.split_whitespace().map(str::to_string));
+            if !add {
+                args.extend(owned(&["handle", &handle.unwrap_or_default().to_string()]));
+                return vec![args];
+            }
+            if let Some(source) = &rule.source {
+                let family = if rule.is_ipv6() { "ip6" } else { "ip" };
+                args.extend(owned(&[family, "saddr", source]));
+            }
+            args.extend(owned(&[&protocol, "dport", &port, "counter"]));
+            args.push(
+                match rule.action {
+                    RuleAction::Allow => "accept",
+                    RuleAction::Deny => "drop",
+                }
+                .to_string(),
+            );
+            // nft joins its arguments and parses them, so the quotes are its own
+            args.extend(owned(&["comment", &format!("\"{}\"", tag)]));
+            vec![args]
+        }
+        FirewallBackend::Ufw => {
+            let mut args = owned(&["ufw"]);
+            if !add {
+                args.push("delete".to_string());
+            }
+            args.extend(owned(&[rule.action.as_str(), "proto", &protocol, "from"]));
+            args.push(rule.source.clone().unwrap_or_else(|| "any".to_string()));
+            args.extend(owned(&["to", "any", "port", &port]));
+            if add {
+                args.extend(owned(&["comment", &tag]));
+            }
+            vec![args]
+        }
+        FirewallBackend::Firewalld => {
+            let option = format!(
+                "--{}-rich-rule={}",
+                if add { "add" } else { "remove" },
+                firewalld_rich_rule(rule)
+            );
+            // Runtime and permanent configuration are separate in firewalld
+            vec![
+                owned(&["firewall-cmd", &option]),
+                owned(&["firewall-cmd", "--permanent", &option]),
+            ]
+        }
+    }
+}
+
+/// `rule` as a firewalld rich rule, in the form `--list-rich-rules` prints.
+pub fn firewalld_rich_rule(rule: &PortRule) -> String {
+    let mut rich = "rule".to_string();
+    if let Some(source) = &rule.source {
+        let family = if rule.is_ipv6() { "ipv6" } else { "ipv4" };
+        rich.push_str(&format!(
+            " family=\"{}\" source address=\"{}\"",
+            family, source
+        ));
+    }
+    rich.push_str(&format!(
+        " port port=\"{}\" protocol=\"{}\" {}",
+        rule.port,
+        rule.protocol_name(),
+        match rule.action {
+            RuleAction::Allow => "accept",
+            RuleAction::Deny => "drop",
+        }
+    ));
+    rich
+}
+
+/// Recorded firewalld rules that are still in `--list-rich-rules` output.
+pub fn parse_firewalld_rules(listing: &str, recorded: &[PortRule]) -> Vec<PortRule> {
+    let normalize = |rule: &str| rule.split_whitespace().collect::<Vec<_>>().join(" ");
+    let present: Vec<String> = listing.lines().map(normalize).collect();
+    recorded
+        .iter()
+        .filter(|rule| present.contains(&normalize(&firewalld_rich_rule(rule))))
+        .cloned()
+        .collect()
+}
+
+/// Firewalld rules Nova recorded in `path`; none if it is missing or unreadable.
+pub fn load_firewalld_record(path: &Path) -> Vec<PortRule> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_firewalld_record(path: &Path, rules: &[PortRule]) -> Result<()> {
+    if exec::is_dry_run() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(rules)?)?;
+    Ok(())
+}
+
+fn run_firewall_command(args: &[String]) -> Result<String> {
+    let output = exec::run(Command::new(&args[0]).args(&args[1..]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        log_error!("`{}` failed: {}", args.join(" "), error.trim());
+        return Err(NovaError::NetworkError(format!(
+            "{} failed: {}",
+            args[0],
+            error.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Text of a double-quoted value starting at `text`, e.g. `"a b" rest`.
+fn quoted_value(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('"')?;
+    rest.find('"').map(|end| &rest[..end])
+}
+
+/// Nova rules in `iptables -S` output.
+pub fn parse_iptables_rules(listing: &str) -> Vec<PortRule> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let chain = line.strip_prefix("-A ")?.split_whitespace().next()?;
+            let after = &line[line.find("--comment ")? + "--comment ".len()..];
+            // iptables quotes comments only when they contain spaces
+            let tag = quoted_value(after)
+                .unwrap_or_else(|| after.split_whitespace().next().unwrap_or(""));
+            let mut rule = PortRule::from_tag(tag)?;
+            rule.chain = Some(chain.to_string());
+            Some(rule)
+        })
+        .collect()
+}
+
+/// Nova rules in `nft list ruleset` output, with chains as `family table chain`.
+pub fn parse_nft_rules(listing: &str) -> Vec<PortRule> {
+    let mut rules = Vec::new();
+    let mut table = String::new();
+    let mut chain = String::new();
+    for line in listing.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("table ") {
+            table = rest.trim_end_matches('{').trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("chain ") {
+            chain = rest.trim_end_matches('{').trim().to_string();
+        } else if let Some(index) = line.find("comment \"")
+            && let Some(tag) = quoted_value(&line[index + "comment ".len()..])
+            && let Some(mut rule) = PortRule::from_tag(tag)
+        {
+            rule.chain = Some(format!("{} {}", table, chain));
+            rules.push(rule);
+        }
+    }
+    rules
+}
+
+/// Handle of the rule tagged `tag` in `nft -a list ...` output.
+pub fn find_nft_handle(listing: &str, tag: &str) -> Option<u64> {
+    let needle = format!("comment \"{}\"", tag);
+    listing
+        .lines()
+        .find(|line| line.contains(&needle))?
+        .rsplit_once("# handle ")?
+        .1
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Nova rules in `ufw status` output. ufw lists a rule with no source once
+/// per address family, so duplicates are dropped.
+pub fn parse_ufw_rules(listing: &str) -> Vec<PortRule> {
+    let mut rules: Vec<PortRule> = Vec::new();
+    for line in listing.lines() {
+        if let Some((_, comment)) = line.split_once("# ")
+            && let Some(rule) = PortRule::from_tag(comment.trim())
+            && !rules.contains(&rule)
+        {
+            rules.push(rule);
+        }
+    }
+    rules
+}
+
 // Network visualization data structures for GUI
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkTopology {
@@ -837,3 +1384,156 @@ pub struct VisualTrafficFlow {
     pub flow_color: (u8, u8, u8), // RGB color for visualization
     pub animation_speed: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nftables_and_legacy_iptables_are_both_active() {
+        let probe = BackendProbe {
+            nft_tables: "table inet nova\ntable ip filter\n".to_string(),
+            legacy_iptables_rules: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            probe.active_backends(),
+            vec![FirewallBackend::Nftables, FirewallBackend::Iptables]
+        );
+
+        // firewalld's own table and ufw's generated rules are not separate backends
+        let probe = BackendProbe {
+            firewalld_running: true,
+            nft_tables: "table inet firewalld\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(probe.active_backends(), vec![FirewallBackend::Firewalld]);
+        let probe = BackendProbe {
+            nft_tables:
+                "table inet nova_lab\ntable ip libvirt_network\ntable ip6 libvirt_network\n\
+                         table ip docker-bridges\n"
+                    .to_string(),
+            legacy_iptables_rules: 3,
+            ..Default::default()
+        };
+        assert_eq!(probe.active_backends(), vec![FirewallBackend::Iptables]);
+        let probe = BackendProbe {
+            nft_tables: "table inet novafilter\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(probe.active_backends(), vec![FirewallBackend::Nftables]);
+        let probe = BackendProbe {
+            ufw_active: true,
+            legacy_iptables_rules: 40,
+            ..Default::default()
+        };
+        assert_eq!(probe.active_backends(), vec![FirewallBackend::Ufw]);
+
+        let settings: FirewallSettings =
+            toml::from_str("preferred_backend = \"nftables\"").unwrap();
+        assert_eq!(settings.preferred_backend, Some(FirewallBackend::Nftables));
+    }
+
+    #[test]
+    fn port_rules_generate_commands_and_round_trip_through_listings() {
+        let rule = PortRule {
+            chain: None,
+            protocol: Protocol::Tcp,
+            port: 8080,
+            source: Some("10.0.0.0/8".to_string()),
+            action: RuleAction::Allow,
+            comment: Some("web vm".to_string()),
+        };
+        let tag = "nova: allow tcp/8080 from 10.0.0.0/8 - web vm";
+        assert_eq!(rule.tag(), tag);
+        assert_eq!(PortRule::from_tag(tag), Some(rule.clone()));
+        assert!(rule.validate(&FirewallBackend::Nftables).is_ok());
+
+        let bad = |rule: PortRule| rule.validate(&FirewallBackend::Nftables).is_err();
+        assert!(bad(PortRule {
+            port: 0,
+            ..rule.clone()
+        }));
+        assert!(bad(PortRule {
+            protocol: Protocol::Icmp,
+            ..rule.clone()
+        }));
+        assert!(bad(PortRule {
+            source: Some("10.0.0.0/33".to_string()),
+            ..rule.clone()
+        }));
+        assert!(bad(PortRule {
+            chain: Some("input".to_string()),
+            ..rule.clone()
+        }));
+
+        assert_eq!(
+            add_rule_args(&FirewallBackend::Iptables, &rule)[0].join(" "),
+            format!(
+                "iptables -I INPUT -p tcp --dport 8080 -s 10.0.0.0/8 -m comment --comment {} -j ACCEPT",
+                tag
+            )
+        );
+        assert_eq!(
+            add_rule_args(&FirewallBackend::Nftables, &rule)[0].join(" "),
+            format!(
+                "nft insert rule inet filter input ip saddr 10.0.0.0/8 tcp dport 8080 counter accept comment \"{}\"",
+                tag
+            )
+        );
+        assert_eq!(
+            remove_rule_args(&FirewallBackend::Nftables, &rule, Some(7))[0].join(" "),
+            "nft delete rule inet filter input handle 7"
+        );
+        assert_eq!(
+            remove_rule_args(&FirewallBackend::Ufw, &rule, None)[0].join(" "),
+            "ufw delete allow proto tcp from 10.0.0.0/8 to any port 8080"
+        );
+        let firewalld = add_rule_args(&FirewallBackend::Firewalld, &rule);
+        assert_eq!(firewalld.len(), 2);
+        assert_eq!(
+            firewalld[1].join(" "),
+            "firewall-cmd --permanent --add-rich-rule=rule family=\"ipv4\" source address=\"10.0.0.0/8\" port port=\"8080\" protocol=\"tcp\" accept"
+        );
+
+        // Only recorded rich rules that firewalld still has are Nova's
+        let dir = tempfile::tempdir().unwrap();
+        let record = dir.path().join("firewalld-rules.json");
+        let ssh = PortRule {
+            port: 22,
+            source: None,
+            ..rule.clone()
+        };
+        save_firewalld_record(&record, &[rule.clone(), ssh.clone()]).unwrap();
+        let recorded = load_firewalld_record(&record);
+        let rich_rules = "rule family=\"ipv4\" source address=\"10.0.0.0/8\" port port=\"8080\" protocol=\"tcp\" accept\nrule port port=\"443\" protocol=\"tcp\" accept\n";
+        assert_eq!(
+            parse_firewalld_rules(rich_rules, &recorded),
+            vec![rule.clone()]
+        );
+
+        let listing = format!(
+            "-P INPUT ACCEPT\n-A INPUT -s 10.0.0.0/8 -p tcp -m tcp --dport 8080 -m comment --comment \"{}\" -j ACCEPT\n-A INPUT -p tcp --dport 22 -j ACCEPT\n",
+            tag
+        );
+        let rules = parse_iptables_rules(&listing);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].chain.as_deref(), Some("INPUT"));
+        assert_eq!(rules[0].port, 8080);
+
+        let ruleset = format!(
+            "table inet filter {{\n\tchain input {{\n\t\ttype filter hook input priority filter; policy accept;\n\t\tip saddr 10.0.0.0/8 tcp dport 8080 counter packets 0 bytes 0 accept comment \"{}\" # handle 12\n\t}}\n}}\n",
+            tag
+        );
+        let rules = parse_nft_rules(&ruleset);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].chain.as_deref(), Some("inet filter input"));
+        assert_eq!(find_nft_handle(&ruleset, tag), Some(12));
+
+        let status = "Status: active\n\nTo                         Action      From\n--                         ------      ----\n9000/udp                   DENY        Anywhere                   # nova: deny udp/9000\n9000/udp (v6)              DENY        Anywhere (v6)              # nova: deny udp/9000\n";
+        let rules = parse_ufw_rules(status);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].action, RuleAction::Deny);
+        assert_eq!(rules[0].protocol, Protocol::Udp);
+    }
+}
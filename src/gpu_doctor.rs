@@ -24,7 +24,7 @@ pub enum SystemStatus {
     NotSupported,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DiagnosticCheck {
     pub name: String,
     pub status: CheckStatus,
@@ -32,13 +32,342 @@ pub struct DiagnosticCheck {
     pub fix_command: Option<String>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CheckStatus {
     Pass,
     Warn,
     Fail,
 }
 
+/// Passthrough readiness of a single GPU: Pass (green), Warn (yellow) or
+/// Fail (red), with the per-device checks behind it.
+#[derive(Debug, Clone)]
+pub struct DeviceReadiness {
+    pub address: String,
+    /// Worst status among `checks`
+    pub status: CheckStatus,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DeviceReadiness {
+    pub fn label(&self) -> &'static str {
+        match self.status {
+            CheckStatus::Pass => "Ready",
+            CheckStatus::Warn => "Caution",
+            CheckStatus::Fail => "Blocked",
+        }
+    }
+
+    /// One line per check, for tooltips
+    pub fn summary(&self) -> String {
+        self.checks
+            .iter()
+            .map(|check| {
+                let icon = match check.status {
+                    CheckStatus::Pass => "✓",
+                    CheckStatus::Warn => "⚠",
+                    CheckStatus::Fail => "✗",
+                };
+                format!("{} {}: {}", icon, check.name, check.message)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Readiness of the GPU at `address` as seen by `manager`, or `None` if the
+/// manager doesn't know the device.
+pub fn device_readiness(manager: &GpuManager, address: &str) -> Option<DeviceReadiness> {
+    let device = manager
+        .list_gpus()
+        .iter()
+        .find(|gpu| gpu.address == address)?;
+    let group = device.iommu_group.and_then(|id| {
+        manager
+            .list_iommu_groups()
+            .iter()
+            .find(|group| group.id == id)
+    });
+    let device_dir = Path::new("/sys/bus/pci/devices").join(address);
+
+    Some(evaluate_device(
+        device,
+        group,
+        &HostDisplayUse::read(&device_dir),
+        &read_reset_methods(&device_dir),
+        manager
+            .capabilities_for(address)
+            .is_some_and(|caps| caps.reset_bug),
+        manager.get_reservations().get(address).map(String::as_str),
+    ))
+}
+
+/// Reset methods the kernel offers for a device (`reset_method`, or a bare
+/// `reset` file on kernels before 5.15).
+pub fn read_reset_methods(device_dir: &Path) -> Vec<String> {
+    match fs::read_to_string(device_dir.join("reset_method")) {
+        Ok(methods) => methods.split_whitespace().map(str::to_string).collect(),
+        Err(_) if device_dir.join("reset").exists() => vec!["reset".to_string()],
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Combine IOMMU isolation, driver, reset support and reservation into a
+/// readiness verdict for one GPU.
+pub fn evaluate_device(
+    device: &PciDevice,
+    group: Option<&IommuGroup>,
+    display: &HostDisplayUse,
+    reset_methods: &[String],
+    reset_bug: bool,
+    reserved_for: Option<&str>,
+) -> DeviceReadiness {
+    let check = |name: &str, status, message: String, fix: Option<&str>| DiagnosticCheck {
+        name: name.to_string(),
+        status,
+        message,
+        fix_command: fix.map(str::to_string),
+    };
+    let mut checks = Vec::new();
+
+    checks.push(match group {
+        None => check(
+            "IOMMU Isolation",
+            CheckStatus::Fail,
+            "Device has no IOMMU group; IOMMU is disabled".to_string(),
+            Some("Enable VT-d/AMD-Vi in firmware and add intel_iommu=on or amd_iommu=on"),
+        ),
+        Some(group) if group.isolated => check(
+            "IOMMU Isolation",
+            CheckStatus::Pass,
+            format!("Alone in IOMMU group {}", group.id),
+            None,
+        ),
+        Some(group) if group.viable_for_passthrough => check(
+            "IOMMU Isolation",
+            CheckStatus::Pass,
+            format!(
+                "IOMMU group {} holds only this card's functions ({} devices)",
+                group.id,
+                group.devices.len()
+            ),
+            None,
+        ),
+        Some(group) => check(
+            "IOMMU Isolation",
+            CheckStatus::Fail,
+            format!(
+                "IOMMU group {} is shared with {} unrelated device(s)",
+                group.id,
+                group.devices.len().saturating_sub(1)
+            ),
+            Some("Move the card to another slot or use the ACS override patch"),
+        ),
+    });
+
+    let driver = device.driver.as_deref();
+    checks.push(match driver {
+        Some("vfio-pci") => check(
+            "Driver",
+            CheckStatus::Pass,
+            "Bound to vfio-pci".to_string(),
+            None,
+        ),
+        _ if display.drives_display() => check(
+            "Driver",
+            CheckStatus::Fail,
+            "Drives the host display; binding requires --force".to_string(),
+            Some("Move the host display to another GPU"),
+        ),
+        Some("nouveau") => check(
+            "Driver",
+            CheckStatus::Warn,
+            "nouveau is bound and may not release the card cleanly".to_string(),
+            Some("echo 'blacklist nouveau' | sudo tee /etc/modprobe.d/blacklist-nouveau.conf"),
+        ),
+        Some(driver) if device.in_use => check(
+            "Driver",
+            CheckStatus::Warn,
+            format!(
+                "Host driver {} is in use; stop its users before binding",
+                driver
+            ),
+            None,
+        ),
+        Some(driver) => check(
+            "Driver",
+            CheckStatus::Pass,
+            format!("On {}; Nova rebinds it to vfio-pci", driver),
+            None,
+        ),
+        None => check(
+            "Driver",
+            CheckStatus::Pass,
+            "No driver bound".to_string(),
+            None,
+        ),
+    });
+
+    checks.push(if reset_bug {
+        check(
+            "Reset",
+            CheckStatus::Warn,
+            "Known reset bug: the card may not reinitialize after the VM stops".to_string(),
+            Some("Install vendor-reset (AMD) or avoid restarting the VM without a host reboot"),
+        )
+    } else if reset_methods.is_empty() {
+        check(
+            "Reset",
+            CheckStatus::Warn,
+            "Kernel exposes no reset method for this device".to_string(),
+            Some("Install vendor-reset if the card supports it"),
+        )
+    } else {
+        check(
+            "Reset",
+            CheckStatus::Pass,
+            format!("Reset via {}", reset_methods.join(", ")),
+            None,
+        )
+    });
+
+    checks.push(match reserved_for {
+        Some(vm) => check(
+            "Reservation",
+            CheckStatus::Warn,
+            format!("Reserved for VM '{}'", vm),
+            None,
+        ),
+        None => check(
+            "Reservation",
+            CheckStatus::Pass,
+            "Not reserved".to_string(),
+            None,
+        ),
+    });
+
+    let status = if checks.iter().any(|c| c.status == CheckStatus::Fail) {
+        CheckStatus::Fail
+    } else if checks.iter().any(|c| c.status == CheckStatus::Warn) {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+
+    DeviceReadiness {
+        address: device.address.clone(),
+        status,
+        checks,
+    }
+}
+
+/// Whether AMD GPUs will come back after a VM stops. Cards whose
+/// `reset_methods` lack FLR fall back to a bus reset that many Polaris, Vega
+/// and Navi 1x parts don't recover from (the "AMD reset bug"); the
+/// out-of-tree `vendor-reset` module works around it. `gpus` holds each AMD
+/// GPU's address and reset methods.
+pub fn evaluate_amd_reset(
+    gpus: &[(String, Vec<String>)],
+    vendor_reset_loaded: bool,
+) -> DiagnosticCheck {
+    let check = |status, message: String, fix: Option<String>| DiagnosticCheck {
+        name: "AMD Reset".to_string(),
+        status,
+        message,
+        fix_command: fix,
+    };
+    if gpus.is_empty() {
+        return check(CheckStatus::Pass, "No AMD GPUs detected".to_string(), None);
+    }
+
+    let without_flr: Vec<&(String, Vec<String>)> = gpus
+        .iter()
+        .filter(|(_, methods)| !methods.iter().any(|method| method == "flr"))
+        .collect();
+    if without_flr.is_empty() {
+        return check(
+            CheckStatus::Pass,
+            format!("All {} AMD GPU(s) support FLR", gpus.len()),
+            None,
+        );
+    }
+    let addresses = without_flr
+        .iter()
+        .map(|(address, _)| address.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if !vendor_reset_loaded {
+        return check(
+            CheckStatus::Warn,
+            format!(
+                "{} without FLR; likely hit by the AMD reset bug and may not reinitialize after the VM shuts down",
+                addresses
+            ),
+            Some(
+                "Install vendor-reset (https://github.com/gnif/vendor-reset), then: sudo modprobe vendor-reset && echo vendor-reset | sudo tee /etc/modules-load.d/vendor-reset.conf"
+                    .to_string(),
+            ),
+        );
+    }
+
+    // Since 5.15 the kernel lists `device_specific` for cards vendor-reset
+    // supports, but only uses it when it comes first. Older kernels have no
+    // `reset_method` file and always try it.
+    let unsupported: Vec<&str> = without_flr
+        .iter()
+        .filter(|(_, methods)| {
+            methods != &["reset"] && !methods.iter().any(|m| m == "device_specific")
+        })
+        .map(|(address, _)| address.as_str())
+        .collect();
+    let not_first: Vec<&str> = without_flr
+        .iter()
+        .filter(|(_, methods)| methods.iter().skip(1).any(|m| m == "device_specific"))
+        .map(|(address, _)| address.as_str())
+        .collect();
+
+    if !unsupported.is_empty() {
+        check(
+            CheckStatus::Warn,
+            format!(
+                "vendor-reset is loaded but does not handle {}; they may not reinitialize after the VM shuts down",
+                unsupported.join(", ")
+            ),
+            Some(
+                "Check the vendor-reset supported device list, or reboot the host between VM runs"
+                    .to_string(),
+            ),
+        )
+    } else if !not_first.is_empty() {
+        check(
+            CheckStatus::Warn,
+            format!(
+                "vendor-reset is loaded but {} try another reset method before device_specific",
+                not_first.join(", ")
+            ),
+            Some(
+                not_first
+                    .iter()
+                    .map(|address| {
+                        format!(
+                            "echo device_specific | sudo tee /sys/bus/pci/devices/{}/reset_method",
+                            address
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" && "),
+            ),
+        )
+    } else {
+        check(
+            CheckStatus::Pass,
+            format!("vendor-reset is loaded and handles {}", addresses),
+            None,
+        )
+    }
+}
+
 impl GpuDoctor {
     pub fn new() -> Self {
         let mut manager = GpuManager::new();
@@ -88,6 +417,12 @@ impl GpuDoctor {
         // Check 10: PCI resizable BAR
         checks.push(self.check_resizable_bar());
 
+        // Check 11: Host display GPU
+        checks.push(self.check_host_display());
+
+        // Check 12: AMD reset bug / vendor-reset
+        checks.push(self.check_amd_reset());
+
         // Analyze results
         let failures = checks
             .iter()
@@ -415,6 +750,54 @@ impl GpuDoctor {
         }
     }
 
+    /// Check which GPU keeps the host display alive
+    fn check_host_display(&self) -> DiagnosticCheck {
+        let gpus = self.gpu_manager.list_gpus();
+        let display_gpus: Vec<&PciDevice> = gpus
+            .iter()
+            .filter(|gpu| {
+                gpu.driver.as_deref() != Some("vfio-pci") && self.gpu_manager.is_boot_vga(gpu)
+            })
+            .collect();
+
+        if gpus.len() == 1 {
+            DiagnosticCheck {
+                name: "Host Display".to_string(),
+                status: CheckStatus::Warn,
+                message: format!(
+                    "Single-GPU system: passing {} through leaves the host without a display",
+                    gpus[0].address
+                ),
+                fix_command: Some(
+                    "Use an iGPU or second GPU for the host, or manage the host over SSH and bind with `nova gpu bind --force`"
+                        .to_string(),
+                ),
+            }
+        } else if display_gpus.is_empty() {
+            DiagnosticCheck {
+                name: "Host Display".to_string(),
+                status: CheckStatus::Pass,
+                message: "No GPU is driving the host display".to_string(),
+                fix_command: None,
+            }
+        } else {
+            let addresses = display_gpus
+                .iter()
+                .map(|gpu| gpu.address.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            DiagnosticCheck {
+                name: "Host Display".to_string(),
+                status: CheckStatus::Pass,
+                message: format!(
+                    "Host display runs on {}; binding it requires --force",
+                    addresses
+                ),
+                fix_command: None,
+            }
+        }
+    }
+
     /// Check IOMMU groups viability
     fn check_iommu_groups(&self) -> DiagnosticCheck {
         let groups = self.gpu_manager.list_iommu_groups();
@@ -574,6 +957,26 @@ impl GpuDoctor {
         }
     }
 
+    /// Check AMD GPUs for FLR support or the vendor-reset module
+    fn check_amd_reset(&self) -> DiagnosticCheck {
+        let amd_gpus: Vec<(String, Vec<String>)> = self
+            .gpu_manager
+            .list_gpus()
+            .iter()
+            .filter(|gpu| gpu.vendor_id == "1002")
+            .map(|gpu| {
+                let device_dir = Path::new("/sys/bus/pci/devices").join(&gpu.address);
+                (gpu.address.clone(), read_reset_methods(&device_dir))
+            })
+            .collect();
+        evaluate_amd_reset(&amd_gpus, Path::new("/sys/module/vendor_reset").exists())
+    }
+
+    /// Passthrough readiness of a single GPU
+    pub fn diagnose_device(&self, address: &str) -> Option<DeviceReadiness> {
+        device_readiness(&self.gpu_manager, address)
+    }
+
     /// Print a formatted diagnostic report
     pub fn print_report(&self, report: &DiagnosticReport) {
         println!("\n╔══════════════════════════════════════════════════════════════════╗");
@@ -1,11 +1,16 @@
-use crate::{NovaError, Result, log_debug, log_error, log_info, log_warn};
+use crate::mdev::{self, MdevType};
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Where reservations are kept between runs
+pub const RESERVATIONS_PATH: &str = "/var/lib/nova/gpu-reservations.json";
+
 /// IOMMU Group information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IommuGroup {
@@ -26,6 +31,9 @@ pub struct PciDevice {
     pub iommu_group: Option<u32>,
     pub driver: Option<String>, // vfio-pci, nvidia, nouveau, etc.
     pub in_use: bool,
+    /// Mediated-device (vGPU) profiles, when an mdev-capable driver is bound
+    #[serde(default)]
+    pub mdev_types: Vec<MdevType>,
 }
 
 /// Snapshot of a GPU's current binding state
@@ -36,6 +44,118 @@ pub struct DeviceBindingInfo {
     pub reserved_for: Option<String>,
 }
 
+/// Outcome of returning one vfio-bound GPU to the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    Restored,
+    /// Assigned to this running VM, left on vfio-pci
+    InUse(String),
+    Failed(String),
+}
+
+/// Per-GPU result of [`GpuManager::reset_all_to_host`]
+#[derive(Debug, Clone)]
+pub struct HostRestore {
+    pub address: String,
+    pub device_name: String,
+    /// Driver bound after the attempt
+    pub driver: Option<String>,
+    pub outcome: RestoreOutcome,
+}
+
+/// How a GPU takes part in the host's own display, read from sysfs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostDisplayUse {
+    /// Firmware picked this GPU for the boot console (`boot_vga`)
+    pub boot_vga: bool,
+    /// Connected, enabled DRM connectors such as `card0-DP-1`
+    pub active_connectors: Vec<String>,
+}
+
+impl HostDisplayUse {
+    pub fn drives_display(&self) -> bool {
+        self.boot_vga || !self.active_connectors.is_empty()
+    }
+
+    /// Read `boot_vga` and the DRM connectors under a PCI device directory
+    pub fn read(device_dir: &Path) -> Self {
+        let boot_vga = fs::read_to_string(device_dir.join("boot_vga"))
+            .map(|value| value.trim() == "1")
+            .unwrap_or(false);
+
+        let mut active_connectors = Vec::new();
+        let cards = fs::read_dir(device_dir.join("drm")).into_iter().flatten();
+        for card in cards.flatten() {
+            let card_name = card.file_name().to_string_lossy().into_owned();
+            if !card_name.starts_with("card") {
+                continue;
+            }
+            // Connectors are `cardN-<port>` directories inside `cardN`
+            for connector in fs::read_dir(card.path()).into_iter().flatten().flatten() {
+                let name = connector.file_name().to_string_lossy().into_owned();
+                if !name.starts_with(&format!("{}-", card_name)) {
+                    continue;
+                }
+                let read = |file: &str| {
+                    fs::read_to_string(connector.path().join(file))
+                        .map(|value| value.trim().to_string())
+                        .unwrap_or_default()
+                };
+                if read("status") == "connected" && read("enabled") == "enabled" {
+                    active_connectors.push(name);
+                }
+            }
+        }
+        active_connectors.sort();
+
+        Self {
+            boot_vga,
+            active_connectors,
+        }
+    }
+}
+
+/// A PCI function in a GPU's IOMMU group (video, HDMI audio, USB-C),
+/// read from sysfs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupFunction {
+    pub address: String,
+    pub vendor_id: String,
+    pub device_id: String,
+    pub driver: Option<String>,
+}
+
+impl GroupFunction {
+    /// Every function in the IOMMU group of the PCI device directory
+    /// `device_dir`, sorted by address. Empty without IOMMU information.
+    pub fn read_group(device_dir: &Path) -> Vec<Self> {
+        let entries = fs::read_dir(device_dir.join("iommu_group/devices"))
+            .into_iter()
+            .flatten()
+            .flatten();
+        let mut functions: Vec<Self> = entries
+            .map(|entry| {
+                let path = entry.path();
+                let id = |file: &str| {
+                    fs::read_to_string(path.join(file))
+                        .map(|value| value.trim().trim_start_matches("0x").to_string())
+                        .unwrap_or_default()
+                };
+                Self {
+                    address: entry.file_name().to_string_lossy().into_owned(),
+                    vendor_id: id("vendor"),
+                    device_id: id("device"),
+                    driver: fs::read_link(path.join("driver"))
+                        .ok()
+                        .and_then(|link| Some(link.file_name()?.to_string_lossy().into_owned())),
+                }
+            })
+            .collect();
+        functions.sort_by(|a, b| a.address.cmp(&b.address));
+        functions
+    }
+}
+
 /// GPU capabilities and features
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GpuCapabilities {
@@ -50,6 +170,10 @@ pub struct GpuCapabilities {
     pub minimum_driver: Option<String>,
     pub recommended_kernel: Option<String>,
     pub tcc_supported: bool,
+    /// nvidia-smi's current MIG mode ("Enabled"/"Disabled") on cards that
+    /// support Multi-Instance GPU partitioning
+    #[serde(default)]
+    pub mig_mode: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -88,6 +212,9 @@ pub struct GpuPassthroughConfig {
     pub usb_controller: Option<String>, // USB controller for looking glass
     pub x_vga: bool,                    // Primary VGA device
     pub display: DisplayMode,
+    /// Bind the GPU at VM start even if it drives the host display
+    #[serde(default)]
+    pub allow_host_display: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +244,9 @@ pub struct GpuManager {
     /// GPU reservations (device_address -> vm_name)
     reservations: HashMap<String, String>,
 
+    /// File the reservations are saved to after every change
+    reservations_path: PathBuf,
+
     /// Discovered GPU capabilities by PCI address
     gpu_capabilities: HashMap<String, GpuCapabilities>,
 
@@ -140,10 +270,12 @@ pub struct GpuSystemConfig {
 
 impl GpuManager {
     pub fn new() -> Self {
+        let reservations_path = PathBuf::from(RESERVATIONS_PATH);
         Self {
             gpus: Vec::new(),
             iommu_groups: Vec::new(),
-            reservations: HashMap::new(),
+            reservations: load_reservations(&reservations_path),
+            reservations_path,
             gpu_capabilities: HashMap::new(),
             nvbind_available: Self::check_nvbind(),
             config: GpuSystemConfig::detect(),
@@ -151,6 +283,29 @@ impl GpuManager {
         }
     }
 
+    /// Keep reservations in `path` instead of [`RESERVATIONS_PATH`],
+    /// replacing any loaded from the default file.
+    pub fn with_reservations_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.reservations_path = path.into();
+        self.reservations = load_reservations(&self.reservations_path);
+        self
+    }
+
+    /// Save reservations so they outlive this process. Failing to save is
+    /// logged rather than returned; the device has already been rebound.
+    fn persist_reservations(&self) {
+        if exec::is_dry_run() {
+            return;
+        }
+        if let Err(err) = save_reservations(&self.reservations_path, &self.reservations) {
+            log_warn!(
+                "Failed to save GPU reservations to {}: {}",
+                self.reservations_path.display(),
+                err
+            );
+        }
+    }
+
     /// Check if nvbind is available on the system
     fn check_nvbind() -> bool {
         Command::new("nvbind")
@@ -167,6 +322,7 @@ impl GpuManager {
         self.gpus.clear();
         self.iommu_groups.clear();
         self.gpu_capabilities.clear();
+        self.reservations = load_reservations(&self.reservations_path);
 
         // Discover PCI devices
         self.discover_pci_devices()?;
@@ -268,6 +424,7 @@ impl GpuManager {
 
         // Check current driver
         let driver = Self::get_device_driver(&address);
+        let mdev_types = mdev::supported_types(&address);
 
         Some(PciDevice {
             address,
@@ -278,6 +435,7 @@ impl GpuManager {
             iommu_group: None,
             driver,
             in_use: false,
+            mdev_types,
         })
     }
 
@@ -537,6 +695,7 @@ impl GpuManager {
                         }
                     }
                 }
+                self.discover_mig_modes();
             } else {
                 log_warn!("nvidia-smi command failed or no NVIDIA GPUs detected");
             }
@@ -547,6 +706,30 @@ impl GpuManager {
         Ok(())
     }
 
+    /// Query MIG mode on its own: drivers that predate MIG reject the whole
+    /// query when they see the field, so a failure here only leaves
+    /// `mig_mode` unset.
+    fn discover_mig_modes(&mut self) {
+        let output = Command::new("nvidia-smi")
+            .args([
+                "--query-gpu=pci.bus_id,mig.mode.current",
+                "--format=csv,noheader",
+            ])
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                for (address, mode) in parse_mig_modes(&String::from_utf8_lossy(&output.stdout)) {
+                    if let Some(caps) = self.gpu_capabilities.get_mut(&address) {
+                        caps.mig_mode = mode;
+                    }
+                }
+            }
+            _ => {
+                log_debug!("nvidia-smi cannot report MIG mode; leaving it unknown");
+            }
+        }
+    }
+
     fn apply_blackwell_requirements(caps: &mut GpuCapabilities) {
         caps.minimum_driver = Some("560.0".to_string());
         caps.recommended_kernel = Some("7.0".to_string());
@@ -607,8 +790,66 @@ impl GpuManager {
         }
     }
 
-    /// Configure a GPU for passthrough
-    pub fn configure_passthrough(&mut self, device_address: &str, vm_name: &str) -> Result<()> {
+    /// Whether `device` is driving the host console or desktop session: it is
+    /// the firmware's boot VGA device or has an active DRM connector
+    pub fn is_boot_vga(&self, device: &PciDevice) -> bool {
+        Self::host_display_use(&device.address).drives_display()
+    }
+
+    pub fn host_display_use(address: &str) -> HostDisplayUse {
+        HostDisplayUse::read(&Path::new("/sys/bus/pci/devices").join(address))
+    }
+
+    /// Refuse to take a GPU away from the host display unless `force` is set
+    fn ensure_not_host_display(&self, device_address: &str, force: bool) -> Result<()> {
+        let Some(gpu) = self.gpus.iter().find(|g| g.address == device_address) else {
+            return Ok(());
+        };
+        // Already on vfio-pci, so the host isn't using it
+        if gpu.driver.as_deref() == Some("vfio-pci") {
+            return Ok(());
+        }
+        let display = Self::host_display_use(device_address);
+        if !display.drives_display() {
+            return Ok(());
+        }
+
+        let mut reasons = Vec::new();
+        if display.boot_vga {
+            reasons.push("it is the boot VGA device".to_string());
+        }
+        if !display.active_connectors.is_empty() {
+            reasons.push(format!(
+                "outputs {} are active",
+                display.active_connectors.join(", ")
+            ));
+        }
+
+        if force {
+            log_warn!(
+                "Binding GPU {} although {}; the host display will go dark",
+                device_address,
+                reasons.join(" and ")
+            );
+            return Ok(());
+        }
+        Err(NovaError::ConfigError(format!(
+            "GPU {} is driving the host display ({}). Binding it to vfio-pci would leave \
+             the host without a console; pass --force (or set allow_host_display in the \
+             VM's gpu config) to bind it anyway",
+            device_address,
+            reasons.join(" and ")
+        )))
+    }
+
+    /// Configure a GPU for passthrough. A GPU driving the host display is
+    /// refused unless `force` is set.
+    pub fn configure_passthrough(
+        &mut self,
+        device_address: &str,
+        vm_name: &str,
+        force: bool,
+    ) -> Result<()> {
         log_info!(
             "Configuring GPU {} for passthrough to VM '{}'",
             device_address,
@@ -630,6 +871,8 @@ impl GpuManager {
             )));
         }
 
+        self.ensure_not_host_display(device_address, force)?;
+
         // Unbind from current driver
         self.unbind_driver(device_address)?;
 
@@ -639,6 +882,7 @@ impl GpuManager {
         // Reserve the GPU
         self.reservations
             .insert(device_address.to_string(), vm_name.to_string());
+        self.persist_reservations();
 
         log_info!(
             "GPU {} successfully configured for passthrough",
@@ -652,7 +896,7 @@ impl GpuManager {
         let driver_path = format!("/sys/bus/pci/devices/{}/driver/unbind", device_address);
 
         if Path::new(&driver_path).exists() {
-            fs::write(&driver_path, device_address).map_err(|e| {
+            exec::write_file(&driver_path, device_address).map_err(|e| {
                 log_error!("Failed to unbind driver: {}", e);
                 NovaError::SystemCommandFailed
             })?;
@@ -665,7 +909,7 @@ impl GpuManager {
     /// Bind a device to vfio-pci driver
     fn bind_vfio_pci(&self, device_address: &str) -> Result<()> {
         // Load vfio-pci module
-        let _ = Command::new("modprobe").arg("vfio-pci").output();
+        let _ = exec::run(Command::new("modprobe").arg("vfio-pci"));
 
         // Write device IDs to vfio-pci new_id
         let gpu = self
@@ -677,7 +921,7 @@ impl GpuManager {
         let new_id_path = "/sys/bus/pci/drivers/vfio-pci/new_id";
         let device_ids = format!("{} {}", gpu.vendor_id, gpu.device_id);
 
-        fs::write(new_id_path, device_ids).map_err(|e| {
+        exec::write_file(new_id_path, &device_ids).map_err(|e| {
             log_error!("Failed to bind to vfio-pci: {}", e);
             NovaError::SystemCommandFailed
         })?;
@@ -686,9 +930,11 @@ impl GpuManager {
         Ok(())
     }
 
-    /// Public helper to bind a device to vfio without reserving it to a VM
-    pub fn bind_device_to_vfio(&mut self, device_address: &str) -> Result<()> {
+    /// Public helper to bind a device to vfio without reserving it to a VM.
+    /// A GPU driving the host display is refused unless `force` is set.
+    pub fn bind_device_to_vfio(&mut self, device_address: &str, force: bool) -> Result<()> {
         log_info!("Binding GPU {} to vfio-pci", device_address);
+        self.ensure_not_host_display(device_address, force)?;
         self.unbind_driver(device_address)?;
         self.bind_vfio_pci(device_address)?;
         self.refresh_device_status();
@@ -742,7 +988,7 @@ impl GpuManager {
 
         let probe_path = Path::new("/sys/bus/pci/drivers_probe");
         if probe_path.exists() {
-            if let Err(err) = fs::write(probe_path, device_address) {
+            if let Err(err) = exec::write_file(probe_path, device_address) {
                 log_error!(
                     "Failed to trigger drivers_probe for {}: {}",
                     device_address,
@@ -754,15 +1000,139 @@ impl GpuManager {
             log_warn!("drivers_probe interface not available on this kernel");
         }
 
-        self.reservations.remove(device_address);
+        if self.reservations.remove(device_address).is_some() {
+            self.persist_reservations();
+        }
         self.refresh_device_status();
         Ok(())
     }
 
+    /// Return every GPU bound to vfio-pci to its host driver, along with the
+    /// other vfio-bound functions of its IOMMU group (HDMI audio, USB-C)
+    /// that passthrough claimed with it. GPUs any of whose functions are
+    /// assigned to a running VM, by reservation or in its live definition,
+    /// are left alone.
+    pub fn reset_all_to_host(&mut self) -> Vec<HostRestore> {
+        self.refresh_device_status();
+        let mut assigned = running_vm_gpu_assignments();
+        let running: Vec<String> = assigned.values().cloned().collect();
+        for (address, vm) in &self.reservations {
+            if running.contains(vm) || vm_is_running(vm) {
+                assigned
+                    .entry(address.clone())
+                    .or_insert_with(|| vm.clone());
+            }
+        }
+
+        let vfio_gpus: Vec<PciDevice> = self
+            .gpus
+            .iter()
+            .filter(|gpu| gpu.driver.as_deref() == Some("vfio-pci"))
+            .cloned()
+            .collect();
+
+        let mut results = Vec::new();
+        let mut handled = Vec::new();
+        for gpu in vfio_gpus {
+            if handled.contains(&gpu.address) {
+                continue;
+            }
+            let group = GroupFunction::read_group(Path::new(&format!(
+                "/sys/bus/pci/devices/{}",
+                gpu.address
+            )));
+            let mut functions: Vec<PciDevice> = group
+                .into_iter()
+                .filter(|function| {
+                    function.address == gpu.address
+                        || function.driver.as_deref() == Some("vfio-pci")
+                })
+                .map(|function| {
+                    if function.address == gpu.address {
+                        return gpu.clone();
+                    }
+                    PciDevice {
+                        device_name: format!("{} (function {})", gpu.device_name, function.address),
+                        address: function.address,
+                        vendor_id: function.vendor_id,
+                        device_id: function.device_id,
+                        driver: function.driver,
+                        mdev_types: Vec::new(),
+                        ..gpu.clone()
+                    }
+                })
+                .collect();
+            if functions.is_empty() {
+                functions.push(gpu.clone());
+            }
+            handled.extend(functions.iter().map(|function| function.address.clone()));
+
+            let in_use = functions
+                .iter()
+                .find_map(|function| assigned.get(&function.address));
+            for function in functions {
+                let outcome = match in_use {
+                    Some(vm) => RestoreOutcome::InUse(vm.clone()),
+                    None => match self.restore_host_driver(&function) {
+                        Ok(()) => RestoreOutcome::Restored,
+                        Err(reason) => RestoreOutcome::Failed(reason),
+                    },
+                };
+                results.push(HostRestore {
+                    driver: Self::get_device_driver(&function.address),
+                    address: function.address,
+                    device_name: function.device_name,
+                    outcome,
+                });
+            }
+        }
+
+        self.refresh_device_status();
+        results
+    }
+
+    /// Drop vfio-pci's claim on a device and probe host drivers for it,
+    /// loading the vendor driver if nothing picks it up.
+    fn restore_host_driver(&mut self, gpu: &PciDevice) -> std::result::Result<(), String> {
+        log_info!("Returning GPU {} to the host", gpu.address);
+
+        // `bind` registered the vendor:device pair with vfio-pci, which would
+        // otherwise grab the device again on probe
+        let _ = exec::write_file(
+            "/sys/bus/pci/drivers/vfio-pci/remove_id",
+            &format!("{} {}", gpu.vendor_id, gpu.device_id),
+        );
+        let override_path = format!("/sys/bus/pci/devices/{}/driver_override", gpu.address);
+        if Path::new(&override_path).exists() {
+            let _ = exec::write_file(&override_path, "\n");
+        }
+
+        self.reattach_device_driver(&gpu.address)
+            .map_err(|_| "unbind or driver probe failed".to_string())?;
+        // Nothing was unbound, so the driver checks below would only fail
+        if exec::is_dry_run() {
+            return Ok(());
+        }
+
+        let bound = Self::get_device_driver(&gpu.address);
+        if bound.is_none()
+            && let Some(driver) = host_driver_for_vendor(&gpu.vendor_id)
+        {
+            let _ = exec::run(Command::new("modprobe").arg(driver));
+            let _ = exec::write_file("/sys/bus/pci/drivers_probe", &gpu.address);
+        }
+
+        match Self::get_device_driver(&gpu.address).as_deref() {
+            None => Err("no host driver claimed the device".to_string()),
+            Some("vfio-pci") => Err("vfio-pci reclaimed the device".to_string()),
+            Some(_) => Ok(()),
+        }
+    }
+
     /// Load required VFIO kernel modules
     pub fn load_vfio_stack(&self) -> Result<()> {
         for module in ["vfio", "vfio_pci", "vfio_iommu_type1"] {
-            match Command::new("modprobe").arg(module).output() {
+            match exec::run(Command::new("modprobe").arg(module)) {
                 Ok(output) if output.status.success() => {
                     log_debug!("Loaded module {}", module);
                 }
@@ -844,7 +1214,9 @@ impl GpuManager {
         // This is handled automatically by the kernel in most cases
 
         // Remove reservation
-        self.reservations.remove(device_address);
+        if self.reservations.remove(device_address).is_some() {
+            self.persist_reservations();
+        }
 
         log_info!("GPU {} released", device_address);
         self.refresh_device_status();
@@ -873,6 +1245,24 @@ impl GpuManager {
         &self.iommu_groups
     }
 
+    /// Drop every reservation held by `vm`, e.g. once it is deleted, and
+    /// return the addresses that were freed. Devices stay on their current
+    /// driver.
+    pub fn clear_reservations_for(&mut self, vm: &str) -> Vec<String> {
+        let mut freed: Vec<String> = self
+            .reservations
+            .iter()
+            .filter(|(_, owner)| owner.as_str() == vm)
+            .map(|(address, _)| address.clone())
+            .collect();
+        freed.sort();
+        if !freed.is_empty() {
+            self.reservations.retain(|_, owner| owner != vm);
+            self.persist_reservations();
+        }
+        freed
+    }
+
     /// Get reservations
     pub fn get_reservations(&self) -> &HashMap<String, String> {
         &self.reservations
@@ -936,6 +1326,95 @@ impl GpuManager {
     }
 }
 
+/// MIG mode per GPU from `nvidia-smi --query-gpu=pci.bus_id,mig.mode.current
+/// --format=csv,noheader`, keyed by normalized PCI address. Cards without
+/// MIG report `[N/A]`, which maps to `None`.
+pub fn parse_mig_modes(output: &str) -> Vec<(String, Option<String>)> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(','))
+        .map(|(bus_id, mode)| {
+            (
+                GpuManager::normalize_pci_address(bus_id),
+                GpuManager::parse_non_empty(mode.trim().trim_matches(['[', ']'])),
+            )
+        })
+        .collect()
+}
+
+/// Kernel driver that normally drives GPUs from this PCI vendor.
+pub fn host_driver_for_vendor(vendor_id: &str) -> Option<&'static str> {
+    match vendor_id.trim_start_matches("0x") {
+        "10de" => Some("nvidia"),
+        "1002" => Some("amdgpu"),
+        "8086" => Some("i915"),
+        _ => None,
+    }
+}
+
+/// PCI addresses (`0000:01:00.0`) of the PCI hostdevs in a domain definition.
+pub fn hostdev_pci_addresses(domain_xml: &str) -> Vec<String> {
+    let hostdev = Regex::new(r"(?s)<hostdev\b[^>]*type='pci'[^>]*>.*?</hostdev>")
+        .expect("valid hostdev pattern");
+    let address = Regex::new(
+        r"<address\s+domain='0x([0-9a-fA-F]+)'\s+bus='0x([0-9a-fA-F]+)'\s+slot='0x([0-9a-fA-F]+)'\s+function='0x([0-7])'",
+    )
+    .expect("valid address pattern");
+
+    hostdev
+        .find_iter(domain_xml)
+        .filter_map(|block| {
+            // The source address is the first one; a later <address> is the
+            // guest-side slot
+            let source = block.as_str().split("</source>").next()?;
+            let caps = address.captures(source)?;
+            let number = |index: usize| u32::from_str_radix(&caps[index], 16).ok();
+            Some(format!(
+                "{:04x}:{:02x}:{:02x}.{}",
+                number(1)?,
+                number(2)?,
+                number(3)?,
+                number(4)?
+            ))
+        })
+        .collect()
+}
+
+/// Whether the host PCI device at `address` is a display controller
+/// (PCI class 0x03: VGA, 3D or other display).
+pub fn is_display_device(address: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/bus/pci/devices/{}/class", address))
+        .is_ok_and(|class| class.trim().trim_start_matches("0x").starts_with("03"))
+}
+
+/// PCI devices assigned to running VMs, keyed by address.
+pub fn running_vm_gpu_assignments() -> HashMap<String, String> {
+    let mut assignments = HashMap::new();
+    let Ok(output) = Command::new("virsh").args(["list", "--name"]).output() else {
+        return assignments;
+    };
+    for vm in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|vm| !vm.is_empty())
+    {
+        if let Ok(xml) = Command::new("virsh").args(["dumpxml", vm]).output() {
+            for address in hostdev_pci_addresses(&String::from_utf8_lossy(&xml.stdout)) {
+                assignments.insert(address, vm.to_string());
+            }
+        }
+    }
+    assignments
+}
+
+fn vm_is_running(vm: &str) -> bool {
+    Command::new("virsh")
+        .args(["domstate", vm])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "running")
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GpuSystemStatus {
     pub iommu_enabled: bool,
@@ -971,6 +1450,44 @@ impl GpuSystemConfig {
     }
 }
 
+/// Reservations saved in `path`. A missing file means none; an unreadable
+/// or corrupt one is logged and treated the same way.
+fn load_reservations(path: &Path) -> HashMap<String, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+        Err(err) => {
+            log_warn!(
+                "Failed to read GPU reservations from {}: {}",
+                path.display(),
+                err
+            );
+            return HashMap::new();
+        }
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|err| {
+        log_warn!(
+            "Ignoring corrupt GPU reservations in {}: {}",
+            path.display(),
+            err
+        );
+        HashMap::new()
+    })
+}
+
+/// Write reservations to `path` through a temporary file, so a crash never
+/// leaves a half-written file behind.
+fn save_reservations(path: &Path, reservations: &HashMap<String, String>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let sorted: BTreeMap<&String, &String> = reservations.iter().collect();
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, serde_json::to_string_pretty(&sorted)?)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
 impl Default for GpuManager {
     fn default() -> Self {
         Self::new()
@@ -988,6 +1505,7 @@ impl Default for GpuPassthroughConfig {
             usb_controller: None,
             x_vga: true,
             display: DisplayMode::None,
+            allow_host_display: false,
         }
     }
 }
@@ -1035,3 +1553,136 @@ impl GpuPassthroughConfig {
         args
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mig_modes_are_keyed_by_pci_address() {
+        let modes = parse_mig_modes(
+            "00000000:01:00.0, Enabled\n00000000:41:00.0, [N/A]\n00000000:81:00.0, Disabled\n",
+        );
+        assert_eq!(
+            modes,
+            vec![
+                (
+                    GpuManager::normalize_pci_address("00000000:01:00.0"),
+                    Some("Enabled".to_string())
+                ),
+                (GpuManager::normalize_pci_address("00000000:41:00.0"), None),
+                (
+                    GpuManager::normalize_pci_address("00000000:81:00.0"),
+                    Some("Disabled".to_string())
+                ),
+            ]
+        );
+        assert!(parse_mig_modes("").is_empty());
+    }
+
+    #[test]
+    fn display_use_reads_boot_vga_and_active_connectors() {
+        let device = tempfile::tempdir().unwrap();
+        assert!(!HostDisplayUse::read(device.path()).drives_display());
+
+        let card = device.path().join("drm/card1");
+        for (connector, status, enabled) in [
+            ("card1-DP-1", "connected", "enabled"),
+            ("card1-HDMI-A-1", "disconnected", "disabled"),
+        ] {
+            let dir = card.join(connector);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("status"), format!("{}\n", status)).unwrap();
+            fs::write(dir.join("enabled"), format!("{}\n", enabled)).unwrap();
+        }
+        fs::write(device.path().join("boot_vga"), "0\n").unwrap();
+
+        let display = HostDisplayUse::read(device.path());
+        assert!(!display.boot_vga);
+        assert_eq!(display.active_connectors, vec!["card1-DP-1".to_string()]);
+        assert!(display.drives_display());
+
+        fs::remove_dir_all(card.join("card1-DP-1")).unwrap();
+        fs::write(device.path().join("boot_vga"), "1\n").unwrap();
+        let display = HostDisplayUse::read(device.path());
+        assert!(display.boot_vga && display.active_connectors.is_empty());
+        assert!(display.drives_display());
+    }
+
+    #[test]
+    fn group_functions_come_from_the_iommu_group() {
+        let sysfs = tempfile::tempdir().unwrap();
+        let group = sysfs.path().join("iommu_groups/14/devices");
+        fs::create_dir_all(&group).unwrap();
+        for (address, device, driver) in [
+            ("0000:01:00.1", "22ba", Some("vfio-pci")),
+            ("0000:01:00.0", "2684", Some("vfio-pci")),
+            ("0000:01:00.2", "1ada", None),
+        ] {
+            let dir = sysfs.path().join("devices").join(address);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("vendor"), "0x10de\n").unwrap();
+            fs::write(dir.join("device"), format!("0x{}\n", device)).unwrap();
+            if let Some(driver) = driver {
+                let driver_dir = sysfs.path().join("drivers").join(driver);
+                fs::create_dir_all(&driver_dir).unwrap();
+                std::os::unix::fs::symlink(&driver_dir, dir.join("driver")).unwrap();
+            }
+            std::os::unix::fs::symlink(&dir, group.join(address)).unwrap();
+            std::os::unix::fs::symlink(
+                sysfs.path().join("iommu_groups/14"),
+                dir.join("iommu_group"),
+            )
+            .unwrap();
+        }
+
+        let functions = GroupFunction::read_group(&sysfs.path().join("devices/0000:01:00.0"));
+        let addresses: Vec<&str> = functions.iter().map(|f| f.address.as_str()).collect();
+        assert_eq!(addresses, ["0000:01:00.0", "0000:01:00.1", "0000:01:00.2"]);
+        assert_eq!(
+            functions[1],
+            GroupFunction {
+                address: "0000:01:00.1".to_string(),
+                vendor_id: "10de".to_string(),
+                device_id: "22ba".to_string(),
+                driver: Some("vfio-pci".to_string()),
+            }
+        );
+        assert_eq!(functions[2].driver, None);
+
+        // Without an IOMMU there is no group to walk
+        fs::create_dir_all(sysfs.path().join("devices/0000:02:00.0")).unwrap();
+        assert!(GroupFunction::read_group(&sysfs.path().join("devices/0000:02:00.0")).is_empty());
+    }
+
+    #[test]
+    fn reservations_round_trip_and_tolerate_bad_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state/gpu-reservations.json");
+        assert!(load_reservations(&path).is_empty());
+
+        let reservations = HashMap::from([
+            ("0000:01:00.0".to_string(), "win11".to_string()),
+            ("0000:02:00.0".to_string(), "llm".to_string()),
+        ]);
+        save_reservations(&path, &reservations).unwrap();
+        assert_eq!(load_reservations(&path), reservations);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let mut manager = GpuManager::new().with_reservations_path(&path);
+        assert_eq!(
+            manager.clear_reservations_for("win11"),
+            vec!["0000:01:00.0"]
+        );
+        assert!(manager.clear_reservations_for("win11").is_empty());
+        assert_eq!(
+            load_reservations(&path),
+            HashMap::from([("0000:02:00.0".to_string(), "llm".to_string())])
+        );
+
+        fs::write(&path, "{\"0000:01:00.0\": ").unwrap();
+        assert!(load_reservations(&path).is_empty());
+        save_reservations(&path, &HashMap::new()).unwrap();
+        assert!(load_reservations(&path).is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 use crate::NovaError;
-use crate::gpu_doctor::{CheckStatus, DiagnosticReport as DoctorReport, GpuDoctor, SystemStatus};
-use crate::gpu_passthrough::{DeviceBindingInfo, GpuCapabilities, GpuManager, PciDevice};
+use crate::gpu_doctor::{
+    self, CheckStatus, DeviceReadiness, DiagnosticReport as DoctorReport, GpuDoctor, SystemStatus,
+};
+use crate::gpu_passthrough::{
+    DeviceBindingInfo, GpuCapabilities, GpuManager, HostDisplayUse, PciDevice,
+};
 use crate::theme::{self, ButtonIntent, ButtonRole, GuiTheme};
 use eframe::egui::{self, Align, Id, Layout};
 use serde::{Deserialize, Serialize};
@@ -10,6 +14,12 @@ use std::time::{Duration, Instant};
 
 const GPU_STATE_KEY: &str = "nova.gpu-manager.state";
 
+struct PendingDisplayAssign {
+    pci_address: String,
+    vm_name: String,
+    display: HostDisplayUse,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 enum GpuTab {
@@ -93,6 +103,13 @@ pub struct GpuManagerGui {
     // Cached GPU capabilities
     capabilities: HashMap<String, GpuCapabilities>,
 
+    // Per-GPU passthrough readiness, and the device whose report is open
+    readiness: HashMap<String, DeviceReadiness>,
+    device_report: Option<String>,
+
+    // Assignment of a GPU that drives the host display, awaiting confirmation
+    pending_display_assign: Option<PendingDisplayAssign>,
+
     // Diagnostics
     diagnostic_text: String,
     diagnostic_report: Option<DoctorReport>,
@@ -123,6 +140,9 @@ impl GpuManagerGui {
             iommu_groups: HashMap::new(),
             reservations: HashMap::new(),
             capabilities: HashMap::new(),
+            readiness: HashMap::new(),
+            device_report: None,
+            pending_display_assign: None,
             diagnostic_text: String::new(),
             diagnostic_report: None,
             last_message: None,
@@ -173,11 +193,23 @@ impl GpuManagerGui {
                     self.capabilities.insert(gpu.address.clone(), caps);
                 }
             }
+
+            self.readiness = Self::collect_readiness(&manager, &self.gpus);
         }
 
         self.last_status_refresh = Some(Instant::now());
     }
 
+    fn collect_readiness(
+        manager: &GpuManager,
+        gpus: &[PciDevice],
+    ) -> HashMap<String, DeviceReadiness> {
+        gpus.iter()
+            .filter_map(|gpu| gpu_doctor::device_readiness(manager, &gpu.address))
+            .map(|readiness| (readiness.address.clone(), readiness))
+            .collect()
+    }
+
     /// Run diagnostics
     pub fn run_diagnostics(&mut self) {
         let doctor = GpuDoctor::new();
@@ -246,6 +278,7 @@ impl GpuManagerGui {
             for (addr, vm) in manager.get_reservations() {
                 self.reservations.insert(addr.clone(), vm.clone());
             }
+            self.readiness = Self::collect_readiness(&manager, &self.gpus);
         }
 
         self.last_status_refresh = Some(Instant::now());
@@ -459,7 +492,7 @@ impl GpuManagerGui {
         let device_context = device_address.clone();
         self.with_gpu_manager(Some(device_context), move |manager| {
             let before = manager.binding_info(&device_address);
-            manager.bind_device_to_vfio(&device_address)?;
+            manager.bind_device_to_vfio(&device_address, false)?;
             let after = manager.binding_info(&device_address);
             Ok(compose_binding_transition(
                 "Bound",
@@ -573,10 +606,27 @@ impl GpuManagerGui {
         }
     }
 
-    /// Assign GPU to VM
+    /// Assign GPU to VM, asking first if the GPU drives the host display
     fn assign_gpu(&mut self, pci_address: String, vm_name: String) {
+        let on_vfio = self
+            .gpus
+            .iter()
+            .any(|gpu| gpu.address == pci_address && gpu.driver.as_deref() == Some("vfio-pci"));
+        let display = GpuManager::host_display_use(&pci_address);
+        if !on_vfio && display.drives_display() {
+            self.pending_display_assign = Some(PendingDisplayAssign {
+                pci_address,
+                vm_name,
+                display,
+            });
+            return;
+        }
+        self.configure_assignment(pci_address, vm_name, false);
+    }
+
+    fn configure_assignment(&mut self, pci_address: String, vm_name: String, force: bool) {
         let result = if let Ok(mut manager) = self.gpu_manager.lock() {
-            manager.configure_passthrough(&pci_address, &vm_name)
+            manager.configure_passthrough(&pci_address, &vm_name, force)
         } else {
             return;
         };
@@ -600,6 +650,68 @@ impl GpuManagerGui {
         }
     }
 
+    fn draw_display_assign_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(pending) = self.pending_display_assign.as_ref() else {
+            return;
+        };
+
+        let mut reasons = Vec::new();
+        if pending.display.boot_vga {
+            reasons.push("it is the boot VGA device".to_string());
+        }
+        if !pending.display.active_connectors.is_empty() {
+            reasons.push(format!(
+                "outputs {} are active",
+                pending.display.active_connectors.join(", ")
+            ));
+        }
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        egui::Window::new("GPU drives the host display")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "GPU {} is driving the host display: {}.",
+                    pending.pci_address,
+                    reasons.join(" and ")
+                ));
+                ui.label(format!(
+                    "Assigning it to '{}' binds it to vfio-pci and the host display will go dark.",
+                    pending.vm_name
+                ));
+                ui.small("Only continue if you can reach this host another way, e.g. over SSH.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if self
+                        .themed_button(ui, "Cancel", ButtonRole::Secondary, true)
+                        .clicked()
+                    {
+                        cancelled = true;
+                    }
+                    if self
+                        .themed_button(ui, "Assign anyway", ButtonRole::Stop, true)
+                        .clicked()
+                    {
+                        confirmed = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            if let Some(pending) = self.pending_display_assign.take() {
+                self.configure_assignment(pending.pci_address, pending.vm_name, true);
+            }
+        } else if cancelled || !open {
+            self.pending_display_assign = None;
+        }
+    }
+
     /// Release GPU from VM
     fn release_gpu(&mut self, pci_address: String) {
         let result = if let Ok(mut manager) = self.gpu_manager.lock() {
@@ -890,6 +1002,26 @@ impl GpuManagerGui {
                         ui.label(format!("IOMMU Group: {}", group));
                     }
 
+                    if let Some(readiness) = self.readiness.get(&gpu.address) {
+                        ui.add_space(6.0);
+                        let badge = ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new(format!("● {}", readiness.label()))
+                                        .color(check_color(readiness.status)),
+                                )
+                                .small(),
+                            )
+                            .on_hover_text(format!(
+                                "{}\n\nClick for the full device report",
+                                readiness.summary()
+                            ));
+                        if badge.clicked() {
+                            self.device_report = Some(gpu.address.clone());
+                            self.active_tab = GpuTab::Diagnostics;
+                        }
+                    }
+
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         let label = if expanded {
                             "Hide details"
@@ -1159,11 +1291,67 @@ impl GpuManagerGui {
             });
     }
 
+    /// Readiness checks for the GPU whose badge was clicked
+    fn draw_device_report(&mut self, ui: &mut egui::Ui) {
+        let Some(address) = self.device_report.clone() else {
+            return;
+        };
+        let Some(readiness) = self.readiness.get(&address) else {
+            self.device_report = None;
+            return;
+        };
+        let name = self
+            .gpus
+            .iter()
+            .find(|gpu| gpu.address == address)
+            .map(|gpu| gpu.device_name.clone())
+            .unwrap_or_default();
+
+        let mut close = false;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{} ({})", name, address)).strong());
+                ui.colored_label(
+                    check_color(readiness.status),
+                    format!("● {}", readiness.label()),
+                );
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    close = ui.small_button("Close").clicked();
+                });
+            });
+            ui.add_space(4.0);
+
+            egui::Grid::new("gpu_device_readiness_checks")
+                .striped(true)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    for check in &readiness.checks {
+                        ui.colored_label(check_color(check.status), check_icon(check.status));
+                        ui.label(&check.name);
+                        ui.label(&check.message);
+                        if let Some(fix) = &check.fix_command {
+                            ui.monospace(fix);
+                        } else {
+                            ui.label("—");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        ui.add_space(8.0);
+
+        if close {
+            self.device_report = None;
+        }
+    }
+
     /// Draw diagnostics tab
     fn draw_diagnostics_tab(&mut self, ui: &mut egui::Ui) {
         ui.heading("GPU Passthrough Diagnostics");
         ui.separator();
 
+        self.draw_device_report(ui);
+
         if self.diagnostic_report.is_none() {
             if self.diagnostic_text.is_empty() {
                 ui.group(|ui| {
@@ -1291,6 +1479,22 @@ impl GpuManagerGui {
     }
 }
 
+fn check_icon(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Pass => "✓",
+        CheckStatus::Warn => "⚠",
+        CheckStatus::Fail => "✗",
+    }
+}
+
+fn check_color(status: CheckStatus) -> egui::Color32 {
+    match status {
+        CheckStatus::Pass => egui::Color32::from_rgb(96, 200, 140),
+        CheckStatus::Warn => egui::Color32::from_rgb(255, 170, 0),
+        CheckStatus::Fail => egui::Color32::from_rgb(220, 80, 80),
+    }
+}
+
 /// Standalone window for GPU manager
 pub struct GpuManagerWindow {
     gui: GpuManagerGui,
@@ -1317,6 +1521,9 @@ impl GpuManagerWindow {
             .show(ctx, |ui| {
                 self.gui.draw(ui);
             });
+        if self.open {
+            self.gui.draw_display_assign_confirmation(ctx);
+        }
         self.gui.persist_state(ctx);
     }
 
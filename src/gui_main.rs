@@ -6,20 +6,22 @@ use nova::{
         ActiveProtocol, EnhancedConsoleConfig, EnhancedConsoleManager, UnifiedConsoleSession,
     },
     container::ContainerManager,
-    container_runtime::{ContainerInfo, ContainerStats},
+    container_runtime::{ContainerInfo, ContainerStats, RestartPolicy},
     firewall::FirewallManager,
     gui_gpu::GpuManagerWindow,
     gui_network::NetworkingGui,
     instance::{Instance, InstanceStatus, InstanceType},
     logger,
+    manager_handle::ManagerHandle,
+    naming::{self, NameKind},
     network::{
-        InterfaceState, NetworkInterface, NetworkManager, NetworkSummary, SwitchOrigin,
-        SwitchProfile, SwitchStatus, SwitchType, VirtualSwitch,
+        ConnectedVm, InterfaceState, NetworkInterface, NetworkManager, NetworkSummary,
+        SwitchOrigin, SwitchProfile, SwitchStatus, SwitchType, VirtualSwitch,
     },
     preflight::PreflightSummary,
     sriov::SriovManager,
     storage_pool::StoragePoolManager,
-    templates_snapshots::{OperatingSystem, TemplateManager, VmTemplate},
+    templates_snapshots::{OperatingSystem, SnapshotQuota, TemplateManager, VmTemplate},
     theme::{self, ButtonIntent, ButtonRole},
     usb_passthrough::{UsbDevice, UsbManager},
     vm::VmManager,
@@ -32,7 +34,7 @@ use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex as AsyncMutex;
 use tokio::time::sleep;
@@ -50,6 +52,7 @@ const MAX_NETWORK_REFRESH_SECONDS: i32 = 180;
 const MIN_LOG_REFRESH_SECONDS: i32 = 5;
 const MAX_LOG_REFRESH_SECONDS: i32 = 120;
 const DEFAULT_LOG_REFRESH_SECONDS: u64 = 15;
+const CONFIG_WATCH_SECONDS: u64 = 2;
 
 #[derive(Clone, Copy)]
 struct FontChoice {
@@ -85,6 +88,7 @@ struct UiPreferencesSnapshot {
     confirm_actions: bool,
     container_logs_auto_refresh: bool,
     container_logs_refresh_secs: u64,
+    colorblind_safe: bool,
 }
 
 #[derive(Clone)]
@@ -169,7 +173,7 @@ impl InstanceSummary {
                 InstanceStatus::Running => summary.running += 1,
                 InstanceStatus::Stopped => summary.stopped += 1,
                 InstanceStatus::Suspended => summary.suspended += 1,
-                InstanceStatus::Error => summary.errors += 1,
+                InstanceStatus::Error | InstanceStatus::CrashLooping => summary.errors += 1,
                 InstanceStatus::Starting | InstanceStatus::Stopping => summary.pending += 1,
             }
         }
@@ -234,6 +238,15 @@ enum SessionEvent {
     Closed(String),
 }
 
+/// Results of background create operations, drained on the UI thread.
+#[derive(Debug)]
+enum ProvisionEvent {
+    ContainerCreated { name: String, id: String },
+    ContainerFailed { name: String, message: String },
+    VmCreated { name: String },
+    VmFailed { name: String, message: String },
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum DetailTab {
     Overview,
@@ -252,7 +265,7 @@ enum SwitchProfileMode {
 struct NovaApp {
     vm_manager: Arc<VmManager>,
     container_manager: Arc<ContainerManager>,
-    network_manager: Arc<Mutex<NetworkManager>>,
+    network_manager: ManagerHandle<NetworkManager>,
     // Canonical, app-root instances of the networking managers. They are shared
     // by `Arc::clone` into `networking_gui`, which owns all direct interaction
     // (discovery, create/delete, monitoring, arch tuning). NovaApp retains them
@@ -267,12 +280,17 @@ struct NovaApp {
     enhanced_console: Arc<AsyncMutex<EnhancedConsoleManager>>,
     template_manager: Arc<AsyncMutex<TemplateManager>>,
     session_events: Arc<Mutex<Vec<SessionEvent>>>,
-    usb_manager: Arc<Mutex<UsbManager>>,
-    storage_pool_manager: Arc<Mutex<StoragePoolManager>>,
-    sriov_manager: Arc<Mutex<SriovManager>>,
-    firewall_manager: Arc<Mutex<FirewallManager>>,
+    provision_events: Arc<Mutex<Vec<ProvisionEvent>>>,
+    usb_manager: ManagerHandle<UsbManager>,
+    storage_pool_manager: ManagerHandle<StoragePoolManager>,
+    sriov_manager: ManagerHandle<SriovManager>,
+    firewall_manager: ManagerHandle<FirewallManager>,
     _config: NovaConfig,
     config_path: PathBuf,
+    /// NovaFile mtime as of the last load or save, to spot external edits
+    config_modified: Option<SystemTime>,
+    last_config_check: Option<Instant>,
+    config_reload_error: Option<String>,
     runtime: Runtime,
     template_summary: TemplateCatalogSummary,
     networking_gui: NetworkingGui,
@@ -305,6 +323,7 @@ struct NovaApp {
     network_refresh_interval: Duration,
     network_summary: Option<NetworkSummary>,
     network_switches: Vec<VirtualSwitch>,
+    network_connected_vms: HashMap<String, Vec<ConnectedVm>>,
     network_interfaces: Vec<NetworkInterface>,
     network_attach_selection: HashMap<String, String>,
     show_create_switch_modal: bool,
@@ -359,7 +378,13 @@ struct NovaApp {
     new_vm_enable_secure_boot: bool,
     new_vm_enable_tpm: bool,
     new_vm_autostart: bool,
+    /// Name of the VM virt-install is still creating
+    creating_vm: Option<String>,
+    new_vm_error: Option<String>,
     new_vm_selected_template: Option<String>,
+    new_vm_gpu_selection: Option<nova::vm_templates::TemplateGpuSelection>,
+    new_vm_gpu_confirmed: bool,
+    new_vm_gpu_notice: Option<String>,
     available_templates: std::collections::HashMap<String, nova::config::VmTemplateConfig>,
     available_isos: Vec<nova::vm_templates::IsoFile>,
 
@@ -370,6 +395,10 @@ struct NovaApp {
     new_container_volumes: String,
     new_container_env_vars: String,
     new_container_network: String,
+    new_container_restart: RestartPolicy,
+    /// Name of the container the dialog is waiting on
+    creating_container: Option<String>,
+    new_container_error: Option<String>,
 
     // Cached data from managers
     usb_devices_cache: Vec<UsbDevice>,
@@ -385,6 +414,32 @@ struct NovaApp {
     migration_dest_host: String,
     migration_offline: bool,
     migration_copy_storage: bool,
+    migration_precheck: Option<Result<nova::migration::MigrationPrecheck, String>>,
+}
+
+/// Per-VM `snapshot_quota` tables from the NovaFile.
+fn snapshot_quotas(config: &NovaConfig) -> Vec<(&str, SnapshotQuota)> {
+    config
+        .vm
+        .iter()
+        .map(|(name, vm)| (name.as_str(), vm.snapshot_quota.clone()))
+        .collect()
+}
+
+/// VM templates offered in the New VM dialog: the built-in set unless
+/// `[templates] enable_builtin = false`, plus the NovaFile's custom ones.
+fn template_catalog(config: &NovaConfig) -> HashMap<String, nova::config::VmTemplateConfig> {
+    let mut templates = if config.templates.enable_builtin {
+        nova::vm_templates::builtin_templates()
+    } else {
+        HashMap::new()
+    };
+    templates.extend(config.templates.custom.clone());
+    templates
+}
+
+fn novafile_modified(path: &std::path::Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
 }
 
 impl NovaApp {
@@ -401,6 +456,19 @@ impl NovaApp {
                 NovaConfig::default()
             }
         };
+        naming::configure(config.naming.clone());
+        theme::set_colorblind_safe(config.ui.colorblind_safe);
+
+        if let Some(themes_dir) = theme::custom_themes_dir() {
+            let custom = theme::load_custom_themes(&themes_dir);
+            if !custom.is_empty() {
+                info!(
+                    "Loaded {} custom theme(s) from {}",
+                    custom.len(),
+                    themes_dir.display()
+                );
+            }
+        }
 
         let theme = match theme::GuiTheme::from_name(config.ui.theme.as_str()) {
             Some(theme) => theme,
@@ -526,8 +594,11 @@ impl NovaApp {
         }
 
         let vm_manager = Arc::new(VmManager::new());
-        let container_manager = Arc::new(ContainerManager::new());
-        let network_manager = Arc::new(Mutex::new(NetworkManager::new()));
+        let mut container_manager = ContainerManager::selected(config.container_runtime);
+        container_manager.set_scaling_thresholds(config.container_scaling.clone());
+        container_manager.set_crash_loop_policy(config.container_crash_loop.clone());
+        let container_manager = Arc::new(container_manager);
+        let network_manager = ManagerHandle::new("Network manager", NetworkManager::new());
         let libvirt_manager = Arc::new(Mutex::new(LibvirtManager::new()));
         let network_monitor = Arc::new(Mutex::new(NetworkMonitor::new()));
         let arch_network_manager = Arc::new(Mutex::new(ArchNetworkManager::new()));
@@ -535,7 +606,7 @@ impl NovaApp {
             EnhancedConsoleConfig::default(),
         )));
         let networking_gui = NetworkingGui::with_managers(
-            Arc::clone(&network_manager),
+            network_manager.shared(),
             Arc::clone(&libvirt_manager),
             Arc::clone(&network_monitor),
             Arc::clone(&arch_network_manager),
@@ -545,7 +616,7 @@ impl NovaApp {
             .unwrap_or_else(|| PathBuf::from("/var/lib/nova"))
             .join("nova")
             .join("templates");
-        let template_manager = match TemplateManager::new(templates_root.clone()) {
+        let mut template_manager = match TemplateManager::new(templates_root.clone()) {
             Ok(manager) => manager,
             Err(err) => {
                 error!(
@@ -561,22 +632,29 @@ impl NovaApp {
                 })
             }
         };
+        template_manager.set_quotas(snapshot_quotas(&config));
         let template_manager = Arc::new(AsyncMutex::new(template_manager));
         let session_events = Arc::new(Mutex::new(Vec::new()));
 
         // Additional managers
-        let usb_manager = Arc::new(Mutex::new(UsbManager::new()));
-        let storage_pool_manager = Arc::new(Mutex::new(StoragePoolManager::new()));
-        let sriov_manager = Arc::new(Mutex::new(SriovManager::new()));
-        let firewall_manager = Arc::new(Mutex::new(FirewallManager::new().unwrap_or_else(|e| {
-            warn!("Failed to initialize firewall manager: {:?}", e);
-            FirewallManager::default()
-        })));
+        let usb_manager = ManagerHandle::new("USB manager", UsbManager::new());
+        let storage_pool_manager =
+            ManagerHandle::new("Storage pool manager", StoragePoolManager::new());
+        let sriov_manager = ManagerHandle::new("SR-IOV manager", SriovManager::new());
+        let firewall_manager = ManagerHandle::new(
+            "Firewall manager",
+            FirewallManager::with_settings(&config.firewall).unwrap_or_else(|e| {
+                warn!("Failed to initialize firewall manager: {:?}", e);
+                FirewallManager::default()
+            }),
+        );
 
         let runtime = Runtime::new().expect("failed to initialize Tokio runtime");
 
         let compact_layout = config.ui.compact_layout;
         let iso_paths = config.iso.paths.clone();
+        let available_templates = template_catalog(&config);
+        let config_modified = novafile_modified(&config_path);
 
         let mut app = Self {
             vm_manager,
@@ -588,12 +666,16 @@ impl NovaApp {
             enhanced_console,
             template_manager,
             session_events,
+            provision_events: Arc::new(Mutex::new(Vec::new())),
             usb_manager,
             storage_pool_manager,
             sriov_manager,
             firewall_manager,
             _config: config,
             config_path,
+            config_modified,
+            last_config_check: None,
+            config_reload_error: None,
             runtime,
             template_summary: TemplateCatalogSummary::default(),
             networking_gui,
@@ -621,6 +703,7 @@ impl NovaApp {
             network_refresh_interval,
             network_summary: None,
             network_switches: Vec::new(),
+            network_connected_vms: HashMap::new(),
             network_interfaces: Vec::new(),
             network_attach_selection: HashMap::new(),
             show_create_switch_modal: false,
@@ -675,8 +758,13 @@ impl NovaApp {
             new_vm_enable_secure_boot: false,
             new_vm_enable_tpm: false,
             new_vm_autostart: false,
+            creating_vm: None,
+            new_vm_error: None,
             new_vm_selected_template: None,
-            available_templates: nova::vm_templates::builtin_templates(),
+            new_vm_gpu_selection: None,
+            new_vm_gpu_confirmed: false,
+            new_vm_gpu_notice: None,
+            available_templates,
             available_isos: nova::vm_templates::scan_iso_directories(&iso_paths),
 
             // New Container dialog state
@@ -686,6 +774,9 @@ impl NovaApp {
             new_container_volumes: String::new(),
             new_container_env_vars: String::new(),
             new_container_network: "bridge".to_string(),
+            new_container_restart: RestartPolicy::No,
+            creating_container: None,
+            new_container_error: None,
 
             // Cached data from managers
             usb_devices_cache: Vec::new(),
@@ -701,6 +792,7 @@ impl NovaApp {
             migration_dest_host: String::new(),
             migration_offline: false,
             migration_copy_storage: false,
+            migration_precheck: None,
         };
 
         // Share the app's Tokio runtime with the networking GUI so it can drive
@@ -738,6 +830,24 @@ impl NovaApp {
         all_instances.extend(self.container_manager.list_containers());
         all_instances.sort_by_key(|a| a.name.to_lowercase());
 
+        let newly_crash_looping: Vec<String> = all_instances
+            .iter()
+            .filter(|instance| instance.status == InstanceStatus::CrashLooping)
+            .filter(|instance| {
+                !self.instances_cache.iter().any(|previous| {
+                    previous.name == instance.name
+                        && previous.status == InstanceStatus::CrashLooping
+                })
+            })
+            .map(|instance| instance.name.clone())
+            .collect();
+        for name in newly_crash_looping {
+            self.log_console(format!(
+                "Container '{}' is crash looping; check `nova status container {}`",
+                name, name
+            ));
+        }
+
         self.instances_cache = all_instances;
         self.summary = InstanceSummary::from_instances(&self.instances_cache);
         self.last_refresh = Some(Instant::now());
@@ -785,6 +895,72 @@ impl NovaApp {
         }
     }
 
+    /// Re-read the NovaFile and refresh everything derived from it. The
+    /// `[ui]` section stays as the GUI has it, since Preferences owns it and
+    /// writes it back. On a parse error the previous config is kept.
+    fn reload_config(&mut self) {
+        self.config_modified = novafile_modified(&self.config_path);
+        let mut config = match NovaConfig::from_file(&self.config_path) {
+            Ok(config) => config,
+            Err(err) => {
+                let message = format!(
+                    "Could not reload {}: {}. Keeping the previous configuration.",
+                    self.config_path.display(),
+                    err
+                );
+                warn!("{}", message);
+                self.log_console(message.clone());
+                self.config_reload_error = Some(message);
+                return;
+            }
+        };
+        config.ui = self._config.ui.clone();
+
+        naming::configure(config.naming.clone());
+        self.runtime.block_on(async {
+            self.template_manager
+                .lock()
+                .await
+                .set_quotas(snapshot_quotas(&config));
+        });
+        self.available_templates = template_catalog(&config);
+        self.available_isos = nova::vm_templates::scan_iso_directories(&config.iso.paths);
+        self._config = config;
+        self.config_reload_error = None;
+        self.container_details.clear();
+        self.refresh_template_summary();
+        self.refresh_instances(true);
+        self.log_console(format!(
+            "Reloaded {} ({} VM templates, {} ISOs)",
+            self.config_path.display(),
+            self.available_templates.len(),
+            self.available_isos.len()
+        ));
+    }
+
+    /// With `[ui] watch_config`, reload when the NovaFile's mtime changes.
+    fn watch_config_file(&mut self) {
+        if !self._config.ui.watch_config {
+            return;
+        }
+        let interval = Duration::from_secs(CONFIG_WATCH_SECONDS);
+        if self
+            .last_config_check
+            .is_some_and(|checked| checked.elapsed() < interval)
+        {
+            return;
+        }
+        self.last_config_check = Some(Instant::now());
+        let modified = novafile_modified(&self.config_path);
+        if modified.is_some() && modified != self.config_modified {
+            self.log_console(format!(
+                "{} changed on disk; reloading",
+                self.config_path.display()
+            ));
+            self.reload_config();
+        }
+    }
+
     fn refresh_template_summary(&mut self) {
         let summary = self.runtime.block_on(async {
             let manager = self.template_manager.lock().await;
@@ -836,41 +1012,52 @@ impl NovaApp {
         let mut error_msg: Option<String> = None;
         let mut should_reconcile = false;
 
-        if let Ok(mut manager) = self.network_manager.lock() {
-            match self
-                .runtime
-                .block_on(async { manager.ensure_fresh_state().await })
-            {
-                Ok(_) => {
-                    self.network_summary = Some(manager.summary());
-                    self.network_switches = manager
-                        .list_switches()
-                        .into_iter()
-                        .cloned()
-                        .collect::<Vec<_>>();
-                    self.network_switches.sort_by_key(|a| a.name.to_lowercase());
+        let refreshed = self
+            .network_manager
+            .block_on(self.runtime.handle(), async |manager| {
+                manager.ensure_fresh_state().await.map(|_| {
+                    (
+                        manager.summary(),
+                        manager
+                            .list_switches()
+                            .into_iter()
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                        manager
+                            .list_interfaces()
+                            .into_iter()
+                            .cloned()
+                            .collect::<Vec<_>>(),
+                        manager.connected_vms_by_bridge(),
+                    )
+                })
+            });
 
-                    self.network_interfaces = manager
-                        .list_interfaces()
-                        .into_iter()
-                        .cloned()
-                        .collect::<Vec<_>>();
-                    self.network_interfaces
-                        .sort_by_key(|a| a.name.to_lowercase());
+        match refreshed {
+            Ok(Ok((summary, switches, interfaces, connected_vms))) => {
+                self.network_summary = Some(summary);
+                self.network_switches = switches;
+                self.network_connected_vms = connected_vms;
+                self.network_switches.sort_by_key(|a| a.name.to_lowercase());
 
-                    self.network_attach_selection.retain(|_, iface| {
-                        self.network_interfaces
-                            .iter()
-                            .any(|candidate| candidate.name == *iface)
-                    });
+                self.network_interfaces = interfaces;
+                self.network_interfaces
+                    .sort_by_key(|a| a.name.to_lowercase());
 
-                    self.last_network_refresh = Some(Instant::now());
-                    should_reconcile = true;
-                }
-                Err(err) => {
-                    error_msg = Some(format!("Network refresh failed: {}", err));
-                }
+                self.network_attach_selection.retain(|_, iface| {
+                    self.network_interfaces
+                        .iter()
+                        .any(|candidate| candidate.name == *iface)
+                });
+
+                self.last_network_refresh = Some(Instant::now());
+                should_reconcile = true;
+            }
+            Ok(Err(err)) => {
+                error_msg = Some(format!("Network refresh failed: {}", err));
             }
+            // A user action currently owns the manager; retry on the next frame.
+            Err(_) => {}
         }
 
         if should_reconcile {
@@ -950,11 +1137,11 @@ impl NovaApp {
     }
 
     fn theme_menu(&mut self, ui: &mut egui::Ui) {
-        for option in theme::ALL_THEMES.iter() {
-            let is_selected = self.theme == *option;
+        for option in theme::all_themes() {
+            let is_selected = self.theme == option;
             let response = ui.selectable_label(is_selected, option.label());
             if response.clicked() && !is_selected {
-                self.set_theme(*option);
+                self.set_theme(option);
                 theme::apply_theme(ui.ctx(), self.theme);
                 self.apply_text_style_overrides(ui.ctx());
                 self.ensure_font_definitions(ui.ctx());
@@ -1144,6 +1331,7 @@ impl NovaApp {
                 confirm_actions: self.confirm_instance_actions,
                 container_logs_auto_refresh: self.container_logs_auto_refresh,
                 container_logs_refresh_secs: self.container_logs_refresh_interval.as_secs(),
+                colorblind_safe: self._config.ui.colorblind_safe,
             });
             self.preferences_dirty = false;
             self.show_preferences = true;
@@ -1181,6 +1369,8 @@ impl NovaApp {
             let restored_log_refresh = snapshot.container_logs_refresh_secs.clamp(min_log, max_log);
             self.container_logs_refresh_interval = Duration::from_secs(restored_log_refresh);
             self._config.ui.container_logs_refresh_interval_seconds = restored_log_refresh;
+            self._config.ui.colorblind_safe = snapshot.colorblind_safe;
+            theme::set_colorblind_safe(snapshot.colorblind_safe);
             self.last_refresh = None;
             self.last_network_refresh = None;
             self.fonts_dirty = true;
@@ -1197,6 +1387,8 @@ impl NovaApp {
     fn persist_ui_preferences(&mut self, message: Option<String>) -> bool {
         match self._config.save_to_file(&self.config_path) {
             Ok(_) => {
+                // Our own write is not an external edit
+                self.config_modified = novafile_modified(&self.config_path);
                 if let Some(msg) = message {
                     self.log_console(msg);
                 }
@@ -1230,10 +1422,10 @@ impl NovaApp {
             .show(ctx, |ui| {
                 ui.heading("Appearance");
                 ui.separator();
-                for option in theme::ALL_THEMES.iter() {
-                    let is_selected = self.theme == *option;
+                for option in theme::all_themes() {
+                    let is_selected = self.theme == option;
                     if ui.selectable_label(is_selected, option.label()).clicked() && !is_selected {
-                        self.set_theme(*option);
+                        self.set_theme(option);
                         theme::apply_theme(ui.ctx(), self.theme);
                         self.apply_text_style_overrides(ui.ctx());
                         self.ensure_font_definitions(ui.ctx());
@@ -1266,7 +1458,7 @@ impl NovaApp {
 
                 if self.font_family == FONT_CHOICES[0].id {
                     if let Some(warning) = &self.font_load_error {
-                        ui.colored_label(theme::STATUS_WARNING, warning);
+                        ui.colored_label(theme::status_warning_color(), warning);
                     }
                     if self
                         .themed_button(ui, "Retry font discovery", ButtonRole::Secondary, true)
@@ -1302,6 +1494,20 @@ impl NovaApp {
                     self.apply_text_style_overrides(ui.ctx());
                 }
 
+                if ui
+                    .checkbox(
+                        &mut self._config.ui.colorblind_safe,
+                        "Colorblind-safe status colors and symbols",
+                    )
+                    .on_hover_text(
+                        "Blue/vermillion instead of green/red, and a distinct shape per status",
+                    )
+                    .changed()
+                {
+                    theme::set_colorblind_safe(self._config.ui.colorblind_safe);
+                    self.preferences_dirty = true;
+                }
+
                 ui.add_space(12.0);
                 ui.heading("Behaviour");
                 ui.separator();
@@ -1528,14 +1734,8 @@ impl NovaApp {
             return;
         }
 
-        let sanitized = name
-            .chars()
-            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
-        if !sanitized {
-            self.push_network_feedback(
-                "Switch name must be alphanumeric and may include '-' or '_'",
-                true,
-            );
+        if let Err(err) = naming::validate(NameKind::Switch, name) {
+            self.push_network_feedback(err.to_string(), true);
             return;
         }
 
@@ -1559,18 +1759,20 @@ impl NovaApp {
         let switch_name = name.to_string();
         let switch_type = self.new_switch_type.clone();
 
-        let manager_arc = Arc::clone(&self.network_manager);
-        let create_result = match manager_arc.lock() {
-            Ok(mut manager) => self.runtime.block_on(manager.create_virtual_switch(
-                &switch_name,
-                switch_type,
-                profile,
-            )),
-            Err(_) => {
-                self.push_network_feedback("Network manager is currently busy", true);
-                return;
-            }
-        };
+        let create_result =
+            match self
+                .network_manager
+                .block_on(self.runtime.handle(), async |manager| {
+                    manager
+                        .create_virtual_switch(&switch_name, switch_type, profile, None, false)
+                        .await
+                }) {
+                Ok(result) => result,
+                Err(busy) => {
+                    self.push_network_feedback(busy.to_string(), true);
+                    return;
+                }
+            };
 
         match create_result {
             Ok(_) => {
@@ -1592,17 +1794,18 @@ impl NovaApp {
     }
 
     fn handle_delete_switch(&mut self, name: &str) {
-        let manager_arc = Arc::clone(&self.network_manager);
-        let mut manager = match manager_arc.lock() {
-            Ok(manager) => manager,
-            Err(_) => {
-                self.push_network_feedback("Network manager is currently busy", true);
+        let delete_result = match self
+            .network_manager
+            .block_on(self.runtime.handle(), async |manager| {
+                manager.delete_virtual_switch(name).await
+            }) {
+            Ok(result) => result,
+            Err(busy) => {
+                self.push_network_feedback(busy.to_string(), true);
                 return;
             }
         };
 
-        let delete_result = self.runtime.block_on(manager.delete_virtual_switch(name));
-
         match delete_result {
             Ok(_) => {
                 self.push_network_feedback(format!("Deleted virtual switch '{}'", name), false);
@@ -1647,16 +1850,20 @@ impl NovaApp {
             return;
         }
 
-        let manager_arc = Arc::clone(&self.network_manager);
-        let attach_result = match manager_arc.lock() {
-            Ok(mut manager) => self
-                .runtime
-                .block_on(manager.add_interface_to_switch(switch_name, interface)),
-            Err(_) => {
-                self.push_network_feedback("Network manager is currently busy", true);
-                return;
-            }
-        };
+        let attach_result =
+            match self
+                .network_manager
+                .block_on(self.runtime.handle(), async |manager| {
+                    manager
+                        .add_interface_to_switch(switch_name, interface, false)
+                        .await
+                }) {
+                Ok(result) => result,
+                Err(busy) => {
+                    self.push_network_feedback(busy.to_string(), true);
+                    return;
+                }
+            };
 
         match attach_result {
             Ok(_) => {
@@ -1681,18 +1888,20 @@ impl NovaApp {
     }
 
     fn handle_detach_interface(&mut self, switch_name: &str, interface: &str) {
-        let manager_arc = Arc::clone(&self.network_manager);
-        let mut manager = match manager_arc.lock() {
-            Ok(manager) => manager,
-            Err(_) => {
-                self.push_network_feedback("Network manager is currently busy", true);
-                return;
-            }
-        };
-
-        let detach_result = self
-            .runtime
-            .block_on(manager.remove_interface_from_switch(switch_name, interface));
+        let detach_result =
+            match self
+                .network_manager
+                .block_on(self.runtime.handle(), async |manager| {
+                    manager
+                        .remove_interface_from_switch(switch_name, interface)
+                        .await
+                }) {
+                Ok(result) => result,
+                Err(busy) => {
+                    self.push_network_feedback(busy.to_string(), true);
+                    return;
+                }
+            };
 
         match detach_result {
             Ok(_) => {
@@ -1892,7 +2101,7 @@ impl NovaApp {
                 InstanceStatus::Running => (false, true, true),
                 InstanceStatus::Stopped => (true, false, false),
                 InstanceStatus::Suspended => (true, true, true),
-                InstanceStatus::Error => (true, false, true),
+                InstanceStatus::Error | InstanceStatus::CrashLooping => (true, false, true),
                 InstanceStatus::Starting | InstanceStatus::Stopping => (false, false, false),
             }
         } else {
@@ -2108,6 +2317,13 @@ impl NovaApp {
                         "Session '{}' ready ({})",
                         session.session_id, session.vm_name
                     ));
+                    if !session.selection_reasons.is_empty() {
+                        self.log_console(format!(
+                            "Chose {:?}: {}",
+                            session.protocol_used,
+                            session.selection_reasons.join("; ")
+                        ));
+                    }
                 }
                 SessionEvent::Error { vm, message } => {
                     self.last_session_error = Some(message.clone());
@@ -2575,7 +2791,7 @@ impl NovaApp {
                     });
 
                     if let Some(error) = &state.error {
-                        ui.colored_label(theme::STATUS_WARNING, error);
+                        ui.colored_label(theme::status_warning_color(), error);
                     }
 
                     egui::ScrollArea::vertical()
@@ -2739,7 +2955,10 @@ impl NovaApp {
                 ui.label(format!("vCPUs: {}", instance.cpu_cores));
                 ui.label(format!("Memory: {} MB", instance.memory_mb));
                 ui.label("Storage: template managed (coming soon)");
-                ui.label("Graphics: emulated (QXL)");
+                ui.label(format!(
+                    "Video: {} (device default)",
+                    self._config.device_defaults.video
+                ));
             });
 
             columns[1].group(|ui| {
@@ -2923,6 +3142,13 @@ impl NovaApp {
                         if let Some(pid) = info.pid {
                             ui.monospace(format!("PID {pid}"));
                         }
+                        if let Some(policy) = info.restart_policy {
+                            ui.label(format!(
+                                "Restart policy: {} ({} restarts)",
+                                policy.as_str(),
+                                info.restart_count
+                            ));
+                        }
                     });
 
                     columns[1].group(|ui| {
@@ -2980,6 +3206,25 @@ impl NovaApp {
                             Self::format_bytes(stats.disk_read_bytes),
                             Self::format_bytes(stats.disk_write_bytes)
                         ));
+
+                        let advice = self.container_manager.scaling_hint(
+                            &instance.name,
+                            self._config.container.get(&instance.name),
+                        );
+                        ui.add_space(4.0);
+                        if !advice.has_enough_data() {
+                            ui.small(format!(
+                                "Scaling advice: collecting samples ({}s of {}s)",
+                                advice.observed.num_seconds(),
+                                advice.window.num_seconds()
+                            ));
+                        } else if advice.hints.is_empty() {
+                            ui.small("Scaling advice: usage within thresholds");
+                        } else {
+                            for hint in &advice.hints {
+                                ui.colored_label(theme::status_warning_color(), hint.to_string());
+                            }
+                        }
                     });
                 }
 
@@ -3024,7 +3269,7 @@ impl NovaApp {
             None => {
                 if let Some(error) = error_opt {
                     ui.colored_label(
-                        theme::STATUS_WARNING,
+                        theme::status_warning_color(),
                         format!("Inspection failed: {}", error.message),
                     );
                     ui.small(format!(
@@ -3284,6 +3529,24 @@ impl NovaApp {
                                 }
                             }
 
+                            match self.network_connected_vms.get(&switch.name) {
+                                Some(vms) if !vms.is_empty() => {
+                                    ui.small(format!("Connected VMs ({}):", vms.len()));
+                                    for vm in vms {
+                                        ui.small(format!(
+                                            "  {} — {} via {} ({})",
+                                            vm.vm,
+                                            vm.mac,
+                                            vm.tap.as_deref().unwrap_or("-"),
+                                            vm.ip_address.as_deref().unwrap_or("no IP yet")
+                                        ));
+                                    }
+                                }
+                                _ => {
+                                    ui.small("Connected VMs: none");
+                                }
+                            }
+
                             ui.add_space(6.0);
                             ui.horizontal(|ui| {
                                 let available: Vec<String> = self
@@ -3470,7 +3733,7 @@ impl NovaApp {
         });
 
         if let Some(err) = &self.last_session_error {
-            ui.colored_label(theme::STATUS_STOPPED, format!("Last error: {err}"));
+            ui.colored_label(theme::status_stopped_color(), format!("Last error: {err}"));
             ui.add_space(6.0);
         }
 
@@ -3496,9 +3759,9 @@ impl NovaApp {
                             ui.strong(&session.session_id);
                             ui.add_space(6.0);
                             let status_text = if session.active {
-                                egui::RichText::new("Active").color(theme::STATUS_RUNNING)
+                                egui::RichText::new("Active").color(theme::status_running_color())
                             } else {
-                                egui::RichText::new("Inactive").color(theme::STATUS_WARNING)
+                                egui::RichText::new("Inactive").color(theme::status_warning_color())
                             };
                             ui.label(status_text);
                             ui.separator();
@@ -3583,8 +3846,18 @@ impl NovaApp {
 
             ui.add_space(8.0);
             ui.horizontal(|ui| {
-                Self::summary_chip(ui, "Running", self.summary.running, theme::STATUS_RUNNING);
-                Self::summary_chip(ui, "Stopped", self.summary.stopped, theme::STATUS_STOPPED);
+                Self::summary_chip(
+                    ui,
+                    "Running",
+                    self.summary.running,
+                    theme::status_running_color(),
+                );
+                Self::summary_chip(
+                    ui,
+                    "Stopped",
+                    self.summary.stopped,
+                    theme::status_stopped_color(),
+                );
                 let container_count = self
                     .instances_cache
                     .iter()
@@ -3601,7 +3874,7 @@ impl NovaApp {
                     ui,
                     "Active switches",
                     active_switches,
-                    theme::STATUS_WARNING,
+                    theme::status_warning_color(),
                 );
             });
             ui.add_space(4.0);
@@ -3902,6 +4175,29 @@ impl NovaApp {
                     && ui.small_button("Clear template").clicked()
                 {
                     self.new_vm_selected_template = None;
+                    self.clear_new_vm_gpu_selection();
+                }
+
+                if let Some(selection) = self.new_vm_gpu_selection.clone() {
+                    ui.add_space(4.0);
+                    ui.group(|ui| {
+                        ui.label(format!(
+                            "🎮 Detected GPU: {} ({})",
+                            selection.device_name, selection.device_address
+                        ));
+                        let mut options = vec![format!("machine {}", selection.machine)];
+                        if selection.hide_hypervisor {
+                            options.push("hypervisor hidden (NVIDIA)".to_string());
+                        }
+                        ui.label(format!("Passthrough options: {}", options.join(", ")));
+                        ui.checkbox(
+                            &mut self.new_vm_gpu_confirmed,
+                            "Pass this GPU through to the new VM",
+                        );
+                    });
+                } else if let Some(notice) = &self.new_vm_gpu_notice {
+                    ui.add_space(4.0);
+                    ui.colored_label(egui::Color32::from_rgb(230, 180, 80), notice);
                 }
 
                 ui.add_space(8.0);
@@ -4011,25 +4307,41 @@ impl NovaApp {
                 ui.separator();
                 ui.add_space(8.0);
 
+                if let Some(error) = &self.new_vm_error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                    ui.add_space(8.0);
+                }
+
                 ui.horizontal(|ui| {
                     if self
                         .themed_button(
                             ui,
                             "Create VM",
                             ButtonRole::Primary,
-                            !self.new_vm_name.is_empty(),
+                            self.creating_vm.is_none() && !self.new_vm_name.is_empty(),
                         )
                         .clicked()
                     {
                         self.create_new_vm();
-                        self.show_new_vm_dialog = false;
                     }
+                    let close_label = if self.creating_vm.is_some() {
+                        "Close"
+                    } else {
+                        "Cancel"
+                    };
                     if self
-                        .themed_button(ui, "Cancel", ButtonRole::Secondary, true)
+                        .themed_button(ui, close_label, ButtonRole::Secondary, true)
                         .clicked()
                     {
                         self.show_new_vm_dialog = false;
                     }
+                    if let Some(name) = &self.creating_vm {
+                        ui.spinner();
+                        ui.label(format!("virt-install is creating '{}'…", name))
+                            .on_hover_text(
+                                "This continues in the background if you close the dialog",
+                            );
+                    }
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui.small_button("🔄 Rescan ISOs").clicked() {
@@ -4058,6 +4370,7 @@ impl NovaApp {
             if let Some(net) = &template.network {
                 self.new_vm_network = net.clone();
             }
+            let template = template.clone();
 
             // Try to auto-select matching ISO
             if let Some(pattern) = &template.iso_pattern
@@ -4073,13 +4386,74 @@ impl NovaApp {
 
             self.new_vm_selected_template = Some(template_key);
             self.log_console(format!("Applied template: {}", template.name));
+            self.detect_template_gpu(&template);
         }
     }
 
+    fn detect_template_gpu(&mut self, template: &nova::config::VmTemplateConfig) {
+        self.clear_new_vm_gpu_selection();
+        if !template.gpu_passthrough {
+            return;
+        }
+
+        let gpu_manager = self.vm_manager.gpu_manager_handle();
+        let selection = match gpu_manager.lock() {
+            Ok(mut manager) => match manager.ensure_discovered() {
+                Ok(()) => {
+                    manager.refresh_device_status();
+                    Ok(nova::vm_templates::select_template_gpu(
+                        template,
+                        manager.list_gpus(),
+                        manager.get_reservations(),
+                    ))
+                }
+                Err(err) => Err(format!("GPU discovery failed: {}", err)),
+            },
+            Err(_) => Err("GPU manager is busy; try selecting the template again".to_string()),
+        };
+
+        match selection {
+            Ok(Some(selection)) => {
+                self.log_console(format!(
+                    "Detected GPU {} ({}) for passthrough",
+                    selection.device_name, selection.device_address
+                ));
+                self.new_vm_gpu_selection = Some(selection);
+                self.new_vm_gpu_confirmed = true;
+            }
+            Ok(None) => {
+                let vendor = if template.tags.iter().any(|tag| tag == "nvidia") {
+                    "NVIDIA "
+                } else {
+                    ""
+                };
+                let notice = format!(
+                    "No available {}GPU was detected for '{}'. GPU passthrough has been disabled; bind a GPU to vfio-pci and reselect the template to enable it.",
+                    vendor, template.name
+                );
+                self.log_console(notice.clone());
+                self.new_vm_gpu_notice = Some(notice);
+                self.new_vm_enable_gpu = false;
+            }
+            Err(message) => {
+                self.log_console(message.clone());
+                self.new_vm_gpu_notice = Some(message);
+                self.new_vm_enable_gpu = false;
+            }
+        }
+    }
+
+    fn clear_new_vm_gpu_selection(&mut self) {
+        self.new_vm_gpu_selection = None;
+        self.new_vm_gpu_confirmed = false;
+        self.new_vm_gpu_notice = None;
+    }
+
     fn create_new_vm(&mut self) {
         let name = self.new_vm_name.clone();
-        if name.is_empty() {
-            self.log_console("VM name cannot be empty");
+        if let Err(err) = naming::validate(NameKind::Vm, &name) {
+            self.log_console(err.to_string());
+            self.new_vm_error = Some(err.to_string());
             return;
         }
 
@@ -4115,7 +4489,22 @@ impl NovaApp {
             self.log_console(format!("  ISO: {}", self.new_vm_iso_path));
         }
 
+        let gpu_selection = if self.new_vm_enable_gpu {
+            match (&self.new_vm_gpu_selection, self.new_vm_gpu_confirmed) {
+                (Some(selection), true) => Some(selection.clone()),
+                _ => {
+                    let message = "GPU passthrough is enabled but no GPU was confirmed; select a GPU template or disable GPU passthrough";
+                    self.log_console(message);
+                    self.new_vm_error = Some(message.to_string());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
         // Build virt-install command
+        let devices = self._config.device_defaults.clone();
         let mut cmd = std::process::Command::new("virt-install");
 
         cmd.arg("--name")
@@ -4125,19 +4514,20 @@ impl NovaApp {
             .arg("--memory")
             .arg(self.parse_memory_for_virt_install(&self.new_vm_memory))
             .arg("--disk")
-            .arg(format!(
+            .arg(devices.disk_option(&format!(
                 "size={}",
                 self.parse_disk_size_gb(&self.new_vm_disk_size)
-            ))
+            )))
             .arg("--os-variant")
             .arg(self.detect_os_variant());
 
         // Network
         if !self.new_vm_network.is_empty() {
             cmd.arg("--network")
-                .arg(format!("bridge={}", self.new_vm_network));
+                .arg(devices.network_option(&format!("bridge={}", self.new_vm_network)));
         } else {
-            cmd.arg("--network").arg("default");
+            cmd.arg("--network")
+                .arg(devices.network_option("network=default"));
         }
 
         // ISO/CDROM
@@ -4162,9 +4552,22 @@ impl NovaApp {
                 .arg("backend.type=emulator,backend.version=2.0,model=tpm-crb");
         }
 
-        // Graphics
+        // GPU passthrough
+        if let Some(selection) = &gpu_selection {
+            cmd.arg("--machine").arg(&selection.machine);
+            cmd.arg("--hostdev").arg(&selection.device_address);
+            if selection.hide_hypervisor {
+                cmd.arg("--features").arg("kvm.hidden.state=on");
+            }
+            self.log_console(format!(
+                "  GPU: {} ({})",
+                selection.device_name, selection.device_address
+            ));
+        }
+
+        // Graphics, video, RNG and balloon from `[device_defaults]`
         cmd.arg("--graphics").arg("spice,listen=none");
-        cmd.arg("--video").arg("qxl");
+        cmd.args(devices.virt_install_args());
 
         // Don't start immediately (define only)
         cmd.arg("--noautoconsole");
@@ -4176,24 +4579,32 @@ impl NovaApp {
 
         self.log_console(format!("Running: virt-install --name {} ...", name));
 
-        // Execute virt-install
-        match cmd.output() {
-            Ok(output) => {
-                if output.status.success() {
-                    self.log_console(format!("VM '{}' created successfully!", name));
-                    self.log_console("Note: VM is defined but not started. Use Start to boot.");
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    self.log_console(format!("Failed to create VM: {}", stderr));
-                }
-            }
-            Err(e) => {
-                self.log_console(format!("Failed to run virt-install: {}", e));
-                self.log_console("Make sure virt-install is installed (libvirt package)");
-            }
-        }
+        // virt-install can take minutes (ISO checks, disk allocation), so it
+        // runs on the Tokio runtime and reports back through provision_events
+        self.creating_vm = Some(name.clone());
+        self.new_vm_error = None;
+        let events = Arc::clone(&self.provision_events);
+        let mut cmd = tokio::process::Command::from(cmd);
+        self.runtime.spawn(async move {
+            let event = match cmd.output().await {
+                Ok(output) if output.status.success() => ProvisionEvent::VmCreated { name },
+                Ok(output) => ProvisionEvent::VmFailed {
+                    name,
+                    message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                },
+                Err(e) => ProvisionEvent::VmFailed {
+                    name,
+                    message: format!(
+                        "Failed to run virt-install: {} (make sure virt-install is installed; it ships with the libvirt packages)",
+                        e
+                    ),
+                },
+            };
+            events.lock().unwrap().push(event);
+        });
+    }
 
-        // Reset form
+    fn reset_new_vm_form(&mut self) {
         self.new_vm_name.clear();
         self.new_vm_cpu = 4;
         self.new_vm_memory = "8G".to_string();
@@ -4206,8 +4617,8 @@ impl NovaApp {
         self.new_vm_enable_tpm = false;
         self.new_vm_autostart = false;
         self.new_vm_selected_template = None;
-
-        self.refresh_instances(true);
+        self.clear_new_vm_gpu_selection();
+        self.new_vm_error = None;
     }
 
     fn parse_memory_for_virt_install(&self, memory: &str) -> String {
@@ -4358,27 +4769,51 @@ impl NovaApp {
                                 .hint_text("bridge"),
                         );
                         ui.end_row();
+
+                        ui.label("Restart:");
+                        egui::ComboBox::from_id_salt("nova.new_container.restart")
+                            .selected_text(self.new_container_restart.as_str())
+                            .show_ui(ui, |ui| {
+                                for policy in RestartPolicy::ALL {
+                                    ui.selectable_value(
+                                        &mut self.new_container_restart,
+                                        policy,
+                                        policy.as_str(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
                     });
 
                 ui.add_space(16.0);
                 ui.separator();
                 ui.add_space(8.0);
 
+                if let Some(error) = &self.new_container_error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                    ui.add_space(8.0);
+                }
+
                 ui.horizontal(|ui| {
-                    let can_create =
-                        !self.new_container_name.is_empty() && !self.new_container_image.is_empty();
+                    let can_create = self.creating_container.is_none()
+                        && !self.new_container_name.is_empty()
+                        && !self.new_container_image.is_empty();
                     if self
                         .themed_button(ui, "Create Container", ButtonRole::Primary, can_create)
                         .clicked()
                     {
                         self.create_new_container();
-                        self.show_new_container_dialog = false;
                     }
                     if self
                         .themed_button(ui, "Cancel", ButtonRole::Secondary, true)
                         .clicked()
                     {
                         self.show_new_container_dialog = false;
+                        self.new_container_error = None;
+                    }
+                    if let Some(name) = &self.creating_container {
+                        ui.spinner();
+                        ui.label(format!("Creating '{}'…", name));
                     }
                 });
             });
@@ -4416,52 +4851,126 @@ impl NovaApp {
         let mut env: std::collections::HashMap<String, String> = std::collections::HashMap::new();
         for entry in env_str.split([',', '\n']) {
             let entry = entry.trim();
-            if let Some((key, value)) = entry.split_once('=') {
-                env.insert(key.trim().to_string(), value.trim().to_string());
+            if entry.is_empty() {
+                continue;
+            }
+            match entry.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => {
+                    env.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                _ => {
+                    self.new_container_error = Some(format!(
+                        "Environment entry '{}' must look like KEY=value",
+                        entry
+                    ));
+                    return;
+                }
             }
         }
 
         // Parse ports (comma or newline separated)
-        let _ports: Vec<String> = ports_str
+        let ports: Vec<String> = ports_str
             .split([',', '\n'])
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
+        if let Some(err) = ports
+            .iter()
+            .find_map(|spec| nova::container_ports::parse_port_spec(spec).err())
+        {
+            self.new_container_error = Some(err.to_string());
+            return;
+        }
 
         // Build config
         let config = nova::config::ContainerConfig {
             capsule: Some(image.clone()),
+            ports,
             volumes,
             network,
             env,
             autostart: false,
+            depends_on: Vec::new(),
+            labels: Default::default(),
+            restart: Some(self.new_container_restart),
             runtime: None,
             bolt: nova::config::BoltConfig::default(),
         };
 
-        // Start container via ContainerManager
+        // Create it in the background; the result comes back as a ProvisionEvent
         let container_manager = self.container_manager.clone();
+        let events = self.provision_events.clone();
         let name_for_task = name.clone();
         self.runtime.spawn(async move {
-            if let Err(err) = container_manager
-                .start_container(&name_for_task, Some(&config))
+            let event = match container_manager
+                .create_container(&name_for_task, &config)
                 .await
             {
-                tracing::error!("Failed to create container '{}': {:?}", name_for_task, err);
-            }
+                Ok(id) => ProvisionEvent::ContainerCreated {
+                    name: name_for_task,
+                    id,
+                },
+                Err(err) => ProvisionEvent::ContainerFailed {
+                    name: name_for_task,
+                    message: err.to_string(),
+                },
+            };
+            events.lock().unwrap().push(event);
         });
 
-        self.log_console(format!("Container '{}' creation initiated", name));
+        self.new_container_error = None;
+        self.creating_container = Some(name);
+    }
 
-        // Reset form
+    fn drain_provision_events(&mut self) {
+        let drained: Vec<ProvisionEvent> =
+            self.provision_events.lock().unwrap().drain(..).collect();
+        for event in drained {
+            match event {
+                ProvisionEvent::ContainerCreated { name, id } => {
+                    let short_id: String = id.chars().take(12).collect();
+                    self.log_console(format!("Container '{}' created ({})", name, short_id));
+                    self.creating_container = None;
+                    self.show_new_container_dialog = false;
+                    self.reset_new_container_form();
+                    self.refresh_instances(true);
+                }
+                ProvisionEvent::ContainerFailed { name, message } => {
+                    error!("Failed to create container '{}': {}", name, message);
+                    self.log_console(format!(
+                        "Failed to create container '{}': {}",
+                        name, message
+                    ));
+                    self.creating_container = None;
+                    self.new_container_error = Some(message);
+                }
+                ProvisionEvent::VmCreated { name } => {
+                    self.log_console(format!("VM '{}' created successfully!", name));
+                    self.log_console("Note: VM is defined but not started. Use Start to boot.");
+                    self.creating_vm = None;
+                    self.show_new_vm_dialog = false;
+                    self.reset_new_vm_form();
+                    self.refresh_instances(true);
+                }
+                ProvisionEvent::VmFailed { name, message } => {
+                    error!("Failed to create VM '{}': {}", name, message);
+                    self.log_console(format!("Failed to create VM '{}': {}", name, message));
+                    self.creating_vm = None;
+                    self.new_vm_error = Some(message);
+                }
+            }
+        }
+    }
+
+    fn reset_new_container_form(&mut self) {
         self.new_container_name.clear();
         self.new_container_image.clear();
         self.new_container_ports.clear();
         self.new_container_volumes.clear();
         self.new_container_env_vars.clear();
         self.new_container_network = "bridge".to_string();
-
-        self.refresh_instances(true);
+        self.new_container_restart = RestartPolicy::No;
+        self.new_container_error = None;
     }
 
     fn draw_about_dialog(&mut self, ctx: &egui::Context) {
@@ -4567,7 +5076,7 @@ impl NovaApp {
                                         |ui| {
                                             if let Some(vm) = &device.attached_to_vm {
                                                 ui.colored_label(
-                                                    theme::STATUS_RUNNING,
+                                                    theme::status_running_color(),
                                                     format!("→ {}", vm),
                                                 );
                                                 if self
@@ -4626,7 +5135,7 @@ impl NovaApp {
                     ));
                 } else {
                     ui.colored_label(
-                        theme::STATUS_WARNING,
+                        theme::status_warning_color(),
                         "Select a VM first to attach USB devices",
                     );
                 }
@@ -4635,11 +5144,11 @@ impl NovaApp {
 
     fn refresh_usb_devices(&mut self) {
         self.log_console("Scanning USB devices...");
-        let devices = if let Ok(mut manager) = self.usb_manager.lock() {
-            manager.discover_devices().ok()
-        } else {
-            None
-        };
+        let devices = self
+            .usb_manager
+            .try_with(|manager| manager.discover_devices().ok())
+            .ok()
+            .flatten();
         if let Some(devs) = devices {
             let count = devs.len();
             self.usb_devices_cache = devs;
@@ -4792,11 +5301,11 @@ impl NovaApp {
 
                                     // Progress bar for usage
                                     let bar_color = if usage_ratio > 0.9 {
-                                        theme::STATUS_STOPPED  // Red for critical
+                                        theme::status_stopped_color()  // Red for critical
                                     } else if usage_ratio > 0.75 {
-                                        theme::STATUS_WARNING
+                                        theme::status_warning_color()
                                     } else {
-                                        theme::STATUS_RUNNING
+                                        theme::status_running_color()
                                     };
                                     ui.horizontal(|ui| {
                                         let (rect, _) = ui.allocate_exact_size(
@@ -4814,9 +5323,9 @@ impl NovaApp {
 
                                 ui.horizontal(|ui| {
                                     if state == "running" {
-                                        ui.colored_label(theme::STATUS_RUNNING, "● Active");
+                                        ui.colored_label(theme::status_running_color(), "● Active");
                                     } else {
-                                        ui.colored_label(theme::STATUS_STOPPED, "○ Inactive");
+                                        ui.colored_label(theme::status_stopped_color(), "○ Inactive");
                                     }
                                     ui.separator();
 
@@ -4852,35 +5361,40 @@ impl NovaApp {
 
         // Discover pools through the storage pool manager so the GUI and the
         // backend share a single implementation.
-        let manager = Arc::clone(&self.storage_pool_manager);
-        // Lock held across the await: sound because `block_on` runs the future
-        // synchronously on the UI thread; the guard is released before it
-        // returns and no other thread contends for it.
-        #[allow(clippy::await_holding_lock)]
-        let discovery = self.runtime.block_on(async {
-            let mut mgr = manager.lock().unwrap();
-            mgr.discover_pools().await
-        });
-
-        if let Err(e) = discovery {
-            self.log_console(format!("Failed to discover storage pools: {:?}", e));
-            return;
-        }
+        let discovery = self
+            .storage_pool_manager
+            .block_on(self.runtime.handle(), async |mgr| {
+                mgr.discover_pools().await?;
+                Ok::<_, nova::NovaError>(
+                    mgr.list_pools()
+                        .into_iter()
+                        .map(|pool| {
+                            let capacity = pool.capacity.as_ref();
+                            (
+                                pool.name.clone(),
+                                Self::pool_type_label(&pool.pool_type).to_string(),
+                                pool.path.display().to_string(),
+                                Self::pool_state_label(&pool.state).to_string(),
+                                capacity.map(|c| c.total_bytes).unwrap_or(0),
+                                capacity.map(|c| c.used_bytes).unwrap_or(0),
+                                capacity.map(|c| c.available_bytes).unwrap_or(0),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
 
-        let mgr = self.storage_pool_manager.lock().unwrap();
-        for pool in mgr.list_pools() {
-            let capacity = pool.capacity.as_ref();
-            self.storage_pools_cache.push((
-                pool.name.clone(),
-                Self::pool_type_label(&pool.pool_type).to_string(),
-                pool.path.display().to_string(),
-                Self::pool_state_label(&pool.state).to_string(),
-                capacity.map(|c| c.total_bytes).unwrap_or(0),
-                capacity.map(|c| c.used_bytes).unwrap_or(0),
-                capacity.map(|c| c.available_bytes).unwrap_or(0),
-            ));
+        match discovery {
+            Ok(Ok(pools)) => self.storage_pools_cache = pools,
+            Ok(Err(e)) => {
+                self.log_console(format!("Failed to discover storage pools: {:?}", e));
+                return;
+            }
+            Err(busy) => {
+                self.log_console(busy.to_string());
+                return;
+            }
         }
-        drop(mgr);
 
         self.log_console(format!(
             "Found {} storage pools",
@@ -4994,7 +5508,7 @@ impl NovaApp {
 
                 if !iommu_enabled {
                     ui.horizontal(|ui| {
-                        ui.colored_label(theme::STATUS_WARNING, "⚠");
+                        ui.colored_label(theme::status_warning_color(), "⚠");
                         ui.label(
                             "IOMMU not detected. SR-IOV requires IOMMU enabled in BIOS and kernel.",
                         );
@@ -5040,9 +5554,12 @@ impl NovaApp {
                                     ));
 
                                     if device.active_vfs > 0 {
-                                        ui.colored_label(theme::STATUS_RUNNING, "● VFs Active");
+                                        ui.colored_label(
+                                            theme::status_running_color(),
+                                            "● VFs Active",
+                                        );
                                     } else {
-                                        ui.colored_label(theme::STATUS_STOPPED, "○ No VFs");
+                                        ui.colored_label(theme::status_stopped_color(), "○ No VFs");
                                     }
                                 });
 
@@ -5104,14 +5621,16 @@ impl NovaApp {
 
         // Discover devices through the SR-IOV manager (authoritative source for
         // VF state, driver binding and capability detection).
-        let devices = {
-            let mut mgr = self.sriov_manager.lock().unwrap();
-            mgr.discover_sriov_devices()
-        };
-
-        let devices = match devices {
-            Ok(devices) => devices,
-            Err(e) => {
+        let devices = match self
+            .sriov_manager
+            .try_with(|mgr| mgr.discover_sriov_devices())
+        {
+            Ok(Ok(devices)) => devices,
+            Err(busy) => {
+                self.log_console(busy.to_string());
+                return;
+            }
+            Ok(Err(e)) => {
                 self.log_console(format!("SR-IOV scan failed: {}", e));
                 return;
             }
@@ -5191,10 +5710,10 @@ impl NovaApp {
     fn enable_sriov_vfs(&mut self, pf_address: &str, num_vfs: u32) {
         self.log_console(format!("Enabling {} VFs on {}...", num_vfs, pf_address));
 
-        let result = {
-            let mut mgr = self.sriov_manager.lock().unwrap();
-            mgr.enable_sriov(pf_address, num_vfs)
-        };
+        let result = self
+            .sriov_manager
+            .try_with(|mgr| mgr.enable_sriov(pf_address, num_vfs))
+            .unwrap_or_else(|busy| Err(busy.to_string()));
 
         match result {
             Ok(_) => {
@@ -5210,10 +5729,10 @@ impl NovaApp {
     fn disable_sriov_vfs(&mut self, pf_address: &str) {
         self.log_console(format!("Disabling VFs on {}...", pf_address));
 
-        let result = {
-            let mut mgr = self.sriov_manager.lock().unwrap();
-            mgr.disable_sriov(pf_address)
-        };
+        let result = self
+            .sriov_manager
+            .try_with(|mgr| mgr.disable_sriov(pf_address))
+            .unwrap_or_else(|busy| Err(busy.to_string()));
 
         match result {
             Ok(_) => {
@@ -5269,17 +5788,22 @@ impl NovaApp {
                 if let Some(vm_name) = &self.selected_instance {
                     ui.label(format!("Migrate VM: {}", vm_name));
                 } else {
-                    ui.colored_label(theme::STATUS_WARNING, "No VM selected");
+                    ui.colored_label(theme::status_warning_color(), "No VM selected");
                 }
 
                 ui.add_space(8.0);
                 ui.label("Destination Host:");
                 ui.add_space(4.0);
-                ui.add(
-                    egui::TextEdit::singleline(&mut self.migration_dest_host)
-                        .hint_text("e.g., user@192.168.1.100 or qemu+ssh://host/system")
-                        .desired_width(350.0),
-                );
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.migration_dest_host)
+                            .hint_text("e.g., user@192.168.1.100 or qemu+ssh://host/system")
+                            .desired_width(350.0),
+                    )
+                    .changed()
+                {
+                    self.migration_precheck = None;
+                }
 
                 ui.add_space(12.0);
                 ui.checkbox(
@@ -5301,10 +5825,45 @@ impl NovaApp {
                     }
                 });
 
+                ui.add_space(8.0);
+                let has_target =
+                    self.selected_instance.is_some() && !self.migration_dest_host.trim().is_empty();
+                if self
+                    .themed_button(ui, "Run Prechecks", ButtonRole::Secondary, has_target)
+                    .clicked()
+                {
+                    self.run_migration_precheck();
+                }
+                let mut blocked = false;
+                match &self.migration_precheck {
+                    Some(Ok(precheck)) => {
+                        let issues = precheck.blocking_issues(self.migration_copy_storage);
+                        blocked = !issues.is_empty() && !self.migration_offline;
+                        if issues.is_empty() {
+                            ui.colored_label(theme::status_running_color(), "✓ Prechecks passed");
+                        }
+                        for issue in &issues {
+                            ui.colored_label(theme::status_stopped_color(), format!("✗ {}", issue));
+                        }
+                        for warning in precheck.warnings() {
+                            ui.colored_label(
+                                theme::status_warning_color(),
+                                format!("⚠ {}", warning),
+                            );
+                        }
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(
+                            theme::status_stopped_color(),
+                            format!("Precheck failed: {}", err),
+                        );
+                    }
+                    None => {}
+                }
+
                 ui.add_space(16.0);
                 ui.horizontal(|ui| {
-                    let can_migrate = self.selected_instance.is_some()
-                        && !self.migration_dest_host.trim().is_empty();
+                    let can_migrate = has_target && !blocked;
                     if self
                         .themed_button(ui, "Start Migration", ButtonRole::Primary, can_migrate)
                         .clicked()
@@ -5326,6 +5885,37 @@ impl NovaApp {
         }
     }
 
+    fn run_migration_precheck(&mut self) {
+        let Some(vm_name) = self.selected_instance.clone() else {
+            return;
+        };
+        let destination = self.migration_dest_host.trim().to_string();
+        self.log_console(format!(
+            "Running migration prechecks for '{}' -> '{}'",
+            vm_name, destination
+        ));
+
+        let manager = nova::migration::MigrationManager::new(
+            nova::migration::MigrationConfig::default(),
+            None,
+        );
+        let result = self
+            .runtime
+            .block_on(manager.precheck(&vm_name, &destination))
+            .map_err(|e| e.to_string());
+        match &result {
+            Ok(precheck) => {
+                let issues = precheck.blocking_issues(self.migration_copy_storage);
+                self.log_console(format!(
+                    "Migration precheck: {} blocking issue(s)",
+                    issues.len()
+                ));
+            }
+            Err(err) => self.log_console(format!("Migration precheck failed: {}", err)),
+        }
+        self.migration_precheck = Some(result);
+    }
+
     fn start_vm_migration(&mut self) {
         let Some(vm_name) = self.selected_instance.clone() else {
             self.log_console("No VM selected for migration");
@@ -5406,6 +5996,7 @@ impl NovaApp {
         self.migration_dest_host.clear();
         self.migration_offline = false;
         self.migration_copy_storage = false;
+        self.migration_precheck = None;
     }
 
     fn draw_preflight_dialog(&mut self, ctx: &egui::Context) {
@@ -5453,12 +6044,12 @@ impl NovaApp {
                     // Overall status
                     if result.is_ready() {
                         ui.colored_label(
-                            theme::STATUS_RUNNING,
+                            theme::status_running_color(),
                             "✓ System is ready for Nova workloads",
                         );
                     } else {
                         ui.colored_label(
-                            theme::STATUS_WARNING,
+                            theme::status_warning_color(),
                             format!("⚠ {} issues found", result.issues.len()),
                         );
                     }
@@ -5485,11 +6076,7 @@ impl NovaApp {
                         // Additional checks not in preflight module
                         ui.collapsing("Hardware Features", |ui| {
                             // IOMMU check
-                            let iommu_enabled = std::path::Path::new("/sys/kernel/iommu_groups")
-                                .exists()
-                                && std::fs::read_dir("/sys/kernel/iommu_groups")
-                                    .map(|d| d.count() > 0)
-                                    .unwrap_or(false);
+                            let iommu_enabled = result.host.iommu_groups > 0;
                             Self::preflight_item(
                                 ui,
                                 "IOMMU",
@@ -5502,10 +6089,7 @@ impl NovaApp {
                             );
 
                             // Hugepages
-                            let hugepages = std::fs::read_to_string("/proc/sys/vm/nr_hugepages")
-                                .ok()
-                                .and_then(|s| s.trim().parse::<u64>().ok())
-                                .unwrap_or(0);
+                            let hugepages = result.host.hugepages;
                             Self::preflight_item(
                                 ui,
                                 "Hugepages",
@@ -5514,25 +6098,37 @@ impl NovaApp {
                             );
 
                             // Nested virtualization
-                            let nested =
-                                std::fs::read_to_string("/sys/module/kvm_intel/parameters/nested")
-                                    .or_else(|_| {
-                                        std::fs::read_to_string(
-                                            "/sys/module/kvm_amd/parameters/nested",
-                                        )
-                                    })
-                                    .map(|s| s.trim() == "Y" || s.trim() == "1")
-                                    .unwrap_or(false);
-                            Self::preflight_item(
-                                ui,
-                                "Nested Virt",
-                                nested,
-                                if nested {
-                                    "enabled"
-                                } else {
-                                    "disabled (optional)"
-                                },
-                            );
+                            match &result.nested_virt {
+                                Some(status) => {
+                                    let detail = match (status.loaded, status.nested) {
+                                        (false, _) => format!("{} not loaded", status.module),
+                                        (true, true) => format!("enabled ({})", status.module),
+                                        (true, false) => {
+                                            format!("disabled (optional, {})", status.module)
+                                        }
+                                    };
+                                    Self::preflight_item(ui, "Nested Virt", status.nested, &detail);
+                                    if status.loaded && !status.nested {
+                                        ui.horizontal(|ui| {
+                                            ui.add_space(24.0);
+                                            ui.label(
+                                                egui::RichText::new(status.enable_hint())
+                                                    .monospace()
+                                                    .small(),
+                                            );
+                                            if ui.small_button("Copy").clicked() {
+                                                ui.ctx().copy_text(status.enable_hint());
+                                            }
+                                        });
+                                    }
+                                }
+                                None => Self::preflight_item(
+                                    ui,
+                                    "Nested Virt",
+                                    false,
+                                    "unknown CPU vendor",
+                                ),
+                            }
                         });
 
                         // Tools
@@ -5623,7 +6219,7 @@ impl NovaApp {
                             ui.heading("Issues");
                             for issue in &result.issues {
                                 ui.horizontal(|ui| {
-                                    ui.colored_label(theme::STATUS_WARNING, "⚠");
+                                    ui.colored_label(theme::status_warning_color(), "⚠");
                                     ui.label(issue);
                                 });
                             }
@@ -5659,9 +6255,9 @@ impl NovaApp {
     fn preflight_item(ui: &mut egui::Ui, name: &str, ok: bool, detail: &str) {
         ui.horizontal(|ui| {
             if ok {
-                ui.colored_label(theme::STATUS_RUNNING, "✓");
+                ui.colored_label(theme::status_running_color(), "✓");
             } else {
-                ui.colored_label(theme::STATUS_WARNING, "✗");
+                ui.colored_label(theme::status_warning_color(), "✗");
             }
             ui.strong(name);
             ui.separator();
@@ -5796,7 +6392,7 @@ impl NovaApp {
                 ui.horizontal(|ui| {
                     ui.label("Backend:");
                     if self.firewall_backend.is_empty() {
-                        ui.colored_label(theme::STATUS_WARNING, "Not detected");
+                        ui.colored_label(theme::status_warning_color(), "Not detected");
                     } else {
                         ui.strong(&self.firewall_backend);
                     }
@@ -5891,9 +6487,9 @@ impl NovaApp {
                                     for rule in &visible_rules {
                                         // Action with color
                                         let action_color = match rule.action.as_str() {
-                                            "ACCEPT" => theme::STATUS_RUNNING,
-                                            "DROP" => theme::STATUS_STOPPED,
-                                            "REJECT" => theme::STATUS_WARNING,
+                                            "ACCEPT" => theme::status_running_color(),
+                                            "DROP" => theme::status_stopped_color(),
+                                            "REJECT" => theme::status_warning_color(),
                                             _ => theme::STATUS_UNKNOWN,
                                         };
                                         ui.colored_label(action_color, &rule.action);
@@ -5945,11 +6541,14 @@ impl NovaApp {
                     .default_open(true)
                     .show(ui, |ui| {
                         if self.firewall_conflicts.is_empty() {
-                            ui.colored_label(theme::STATUS_RUNNING, "No rule conflicts detected.");
+                            ui.colored_label(
+                                theme::status_running_color(),
+                                "No rule conflicts detected.",
+                            );
                         } else {
                             ui.strong("Conflicts:");
                             for conflict in &self.firewall_conflicts {
-                                ui.colored_label(theme::STATUS_WARNING, conflict);
+                                ui.colored_label(theme::status_warning_color(), conflict);
                             }
                         }
                         if self.firewall_optimizations.is_empty() {
@@ -6040,31 +6639,27 @@ impl NovaApp {
     /// rather than duplicating the logic.
     fn analyze_firewall(&mut self) {
         self.log_console("Analyzing firewall rules for conflicts...");
-        let manager = Arc::clone(&self.firewall_manager);
-        // Lock held across the await: sound because `block_on` runs the future
-        // synchronously on the UI thread; the guard is released before it
-        // returns and no other thread contends for it.
-        #[allow(clippy::await_holding_lock)]
-        let result = self.runtime.block_on(async move {
-            let mut mgr = manager.lock().unwrap();
-            mgr.load_current_rules().await?;
-            mgr.analyze_rule_conflicts().await?;
-            let conflicts: Vec<String> = mgr
-                .get_rule_conflicts()
-                .iter()
-                .map(|c| {
-                    format!(
-                        "[{:?}/{:?}] {} ({} vs {})",
-                        c.severity, c.conflict_type, c.description, c.rule1_id, c.rule2_id
-                    )
-                })
-                .collect();
-            let optimizations = mgr.optimize_rules().await?;
-            Ok::<(Vec<String>, Vec<String>), nova::NovaError>((conflicts, optimizations))
-        });
+        let result = self
+            .firewall_manager
+            .block_on(self.runtime.handle(), async |mgr| {
+                mgr.load_current_rules().await?;
+                mgr.analyze_rule_conflicts().await?;
+                let conflicts: Vec<String> = mgr
+                    .get_rule_conflicts()
+                    .iter()
+                    .map(|c| {
+                        format!(
+                            "[{:?}/{:?}] {} ({} vs {})",
+                            c.severity, c.conflict_type, c.description, c.rule1_id, c.rule2_id
+                        )
+                    })
+                    .collect();
+                let optimizations = mgr.optimize_rules().await?;
+                Ok::<(Vec<String>, Vec<String>), nova::NovaError>((conflicts, optimizations))
+            });
 
         match result {
-            Ok((conflicts, optimizations)) => {
+            Ok(Ok((conflicts, optimizations))) => {
                 self.log_console(format!(
                     "Firewall analysis complete: {} conflict(s), {} hint(s)",
                     conflicts.len(),
@@ -6074,9 +6669,12 @@ impl NovaApp {
                 self.firewall_optimizations = optimizations;
                 self.firewall_analyzed = true;
             }
-            Err(err) => {
+            Ok(Err(err)) => {
                 self.log_console(format!("Firewall analysis failed: {:?}", err));
             }
+            Err(busy) => {
+                self.log_console(busy.to_string());
+            }
         }
     }
 
@@ -6533,6 +7131,14 @@ impl eframe::App for NovaApp {
         self.refresh_instances(false);
         self.refresh_network_summary(false);
         self.drain_session_events();
+        self.drain_provision_events();
+        if self.creating_container.is_some() || self.creating_vm.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        self.watch_config_file();
+        if self._config.ui.watch_config {
+            ctx.request_repaint_after(Duration::from_secs(CONFIG_WATCH_SECONDS));
+        }
 
         let filter = self.filter_text.trim().to_lowercase();
         let (can_start, can_stop, can_restart) = self.compute_action_state();
@@ -6571,6 +7177,23 @@ impl eframe::App for NovaApp {
                         ui.close();
                     }
                     ui.separator();
+                    if ui.button("Reload config").clicked() {
+                        self.reload_config();
+                        ui.close();
+                    }
+                    let mut watch = self._config.ui.watch_config;
+                    if ui
+                        .checkbox(&mut watch, "Watch NovaFile for changes")
+                        .changed()
+                    {
+                        self._config.ui.watch_config = watch;
+                        self.persist_ui_preferences(Some(if watch {
+                            "Watching the NovaFile for changes".to_string()
+                        } else {
+                            "Stopped watching the NovaFile".to_string()
+                        }));
+                    }
+                    ui.separator();
                     if ui.button("Import...").clicked() {
                         self.log_console("Import functionality coming soon");
                         ui.close();
@@ -6694,6 +7317,20 @@ impl eframe::App for NovaApp {
             });
         });
 
+        if let Some(message) = self.config_reload_error.clone() {
+            egui::Panel::top("nova.config_error").show_inside(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(theme::status_warning_color(), format!("⚠ {}", message));
+                    if ui.button("Retry").clicked() {
+                        self.reload_config();
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.config_reload_error = None;
+                    }
+                });
+            });
+        }
+
         self.draw_header(ui);
         self.draw_navigation_panel(ui, &filter);
 
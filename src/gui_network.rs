@@ -5,6 +5,7 @@ use crate::libvirt::{
 use crate::monitoring::{
     self, BandwidthUsage, NetworkMonitor, NetworkTopology, PacketCaptureConfig,
 };
+use crate::naming::{self, NameKind};
 use crate::network::{NetworkInterface, NetworkManager, SwitchType, VirtualSwitch};
 use crate::theme::{self, ButtonIntent, ButtonRole, GuiTheme};
 use crate::{log_info, log_warn};
@@ -2425,8 +2426,8 @@ impl NetworkingGui {
 
     fn create_switch(&mut self) {
         let name = self.switch_creation_dialog.name.trim().to_string();
-        if name.is_empty() {
-            self.record_error("Switch name cannot be empty");
+        if let Err(err) = naming::validate(NameKind::Switch, &name) {
+            self.record_error(err.to_string());
             return;
         }
         let switch_type = self.switch_creation_dialog.switch_type.clone();
@@ -2446,10 +2447,11 @@ impl NetworkingGui {
         #[allow(clippy::await_holding_lock)]
         let result = self.block_on(async move {
             let mut mgr = manager.lock().unwrap();
-            mgr.create_virtual_switch(&create_name, switch_type, None)
+            mgr.create_virtual_switch(&create_name, switch_type, None, None, false)
                 .await?;
             for iface in &selected {
-                mgr.add_interface_to_switch(&create_name, iface).await?;
+                mgr.add_interface_to_switch(&create_name, iface, false)
+                    .await?;
             }
             Ok::<(), crate::NovaError>(())
         });
@@ -2524,6 +2526,14 @@ impl NetworkingGui {
         if name.is_empty() {
             return Err("Network name cannot be empty".to_string());
         }
+        if naming::validate_interface_name(&format!("virbr-{}", name)).is_err() {
+            return Err(format!(
+                "Network name '{}' is too long: its bridge virbr-{} must fit in {} characters",
+                name,
+                name,
+                naming::MAX_INTERFACE_NAME
+            ));
+        }
 
         let gateway = Ipv4Addr::from_str(dialog.gateway.trim())
             .map_err(|_| format!("Invalid gateway address '{}'", dialog.gateway))?;
@@ -0,0 +1,125 @@
+//! Name resolution for `nova inspect`.
+//!
+//! The CLI probes each resource type for the given name and hands the hits to
+//! [`resolve`], which picks the one to show or explains the collision.
+
+use crate::{NovaError, Result};
+use serde::Serialize;
+use std::fmt;
+
+/// Resource types `nova inspect` can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Vm,
+    Container,
+    Switch,
+    Interface,
+    StoragePool,
+    Gpu,
+}
+
+impl ResourceKind {
+    /// Value accepted by `nova inspect --type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceKind::Vm => "vm",
+            ResourceKind::Container => "container",
+            ResourceKind::Switch => "switch",
+            ResourceKind::Interface => "interface",
+            ResourceKind::StoragePool => "pool",
+            ResourceKind::Gpu => "gpu",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ResourceKind::Vm => "VM",
+            ResourceKind::Container => "container",
+            ResourceKind::Switch => "network switch",
+            ResourceKind::Interface => "network interface",
+            ResourceKind::StoragePool => "storage pool",
+            ResourceKind::Gpu => "GPU",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Pick the resource to inspect from the types `name` was found as.
+///
+/// With `requested` set only that type is considered. Without it, a name
+/// found as more than one type is an error listing the candidates.
+pub fn resolve(
+    name: &str,
+    found: &[ResourceKind],
+    requested: Option<ResourceKind>,
+) -> Result<ResourceKind> {
+    if let Some(kind) = requested {
+        return if found.contains(&kind) {
+            Ok(kind)
+        } else {
+            Err(NovaError::ConfigError(format!(
+                "No {} named '{}' found",
+                kind, name
+            )))
+        };
+    }
+
+    match found {
+        [] => Err(NovaError::ConfigError(format!(
+            "'{}' is not a known VM, container, network, storage pool or GPU",
+            name
+        ))),
+        [kind] => Ok(*kind),
+        kinds => {
+            let candidates: Vec<String> = kinds
+                .iter()
+                .map(|kind| format!("{} (--type {})", kind, kind.as_str()))
+                .collect();
+            Err(NovaError::ConfigError(format!(
+                "'{}' matches several resources: {}",
+                name,
+                candidates.join(", ")
+            )))
+        }
+    }
+}
+
+/// Whether `name` refers to the PCI device at `address`, accepting the short
+/// form without the `0000:` domain.
+pub fn matches_pci_address(address: &str, name: &str) -> bool {
+    address.eq_ignore_ascii_case(name)
+        || address
+            .strip_prefix("0000:")
+            .is_some_and(|short| short.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_match_resolves_and_collisions_need_a_type() {
+        assert_eq!(
+            resolve("web", &[ResourceKind::Container], None).unwrap(),
+            ResourceKind::Container
+        );
+        assert!(resolve("ghost", &[], None).is_err());
+
+        let found = [ResourceKind::Vm, ResourceKind::Switch];
+        let err = resolve("lab", &found, None).unwrap_err().to_string();
+        assert!(err.contains("--type vm"), "{}", err);
+        assert!(err.contains("--type switch"), "{}", err);
+
+        assert_eq!(
+            resolve("lab", &found, Some(ResourceKind::Switch)).unwrap(),
+            ResourceKind::Switch
+        );
+        assert!(resolve("lab", &found, Some(ResourceKind::Gpu)).is_err());
+
+        assert!(matches_pci_address("0000:01:00.0", "01:00.0"));
+        assert!(!matches_pci_address("0000:01:00.0", "01:00.1"));
+    }
+}
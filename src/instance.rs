@@ -16,6 +16,23 @@ pub enum InstanceStatus {
     Stopping,
     Error,
     Suspended,
+    /// Container the runtime keeps restarting faster than the crash-loop policy allows
+    CrashLooping,
+}
+
+impl InstanceStatus {
+    /// A shape per status, so states can be told apart without color.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            InstanceStatus::Running => "▶",
+            InstanceStatus::Stopped => "■",
+            InstanceStatus::Starting => "▲",
+            InstanceStatus::Stopping => "▼",
+            InstanceStatus::Error => "✖",
+            InstanceStatus::Suspended => "‖",
+            InstanceStatus::CrashLooping => "⟳",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,195 @@
+//! Downloading installer ISOs into the NovaFile's `[iso] paths`.
+//!
+//! `nova iso fetch` drives `curl`, which already handles HTTPS, redirects
+//! and proxies. The download goes to a `.part` file next to the destination
+//! and progress comes from that file's size against the server's
+//! `Content-Length`. With `--sha256` the finished file is checked before it
+//! is renamed into place, so a corrupt download never shows up in the ISO
+//! list.
+
+use crate::progress::Reporter;
+use crate::snapshot_archive::sha256_file;
+use crate::{NovaError, Result, exec, log_info};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// File name for a download from `url`: its last path segment, without
+/// any query string or fragment.
+pub fn file_name_from_url(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let (_, rest) = path.split_once("://")?;
+    let (_, path) = rest.split_once('/')?;
+    path.rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// `Content-Length` of the final response in `curl -sIL` output, which
+/// prints the headers of every redirect along the way.
+pub fn parse_content_length(headers: &str) -> Option<u64> {
+    headers
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .filter_map(|(_, value)| value.trim().parse().ok())
+        .next_back()
+}
+
+/// A SHA-256 given on the command line, lowercased, with an optional
+/// `sha256:` prefix removed.
+pub fn normalize_sha256(expected: &str) -> Result<String> {
+    let digest = expected.trim();
+    let digest = digest.strip_prefix("sha256:").unwrap_or(digest);
+    if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(NovaError::ConfigError(format!(
+            "'{}' is not a SHA-256 digest (64 hex characters)",
+            expected
+        )));
+    }
+    Ok(digest.to_ascii_lowercase())
+}
+
+fn content_length(url: &str) -> Option<u64> {
+    let output = Command::new("curl").args(["-sfIL", url]).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_content_length(&String::from_utf8_lossy(&output.stdout)))
+        .flatten()
+}
+
+/// Download the ISO at `url` into `dest_dir` as `file_name` (or the name in
+/// the URL), checking it against `sha256` if given. Returns the final path.
+pub fn fetch(
+    url: &str,
+    dest_dir: &Path,
+    file_name: Option<&str>,
+    sha256: Option<&str>,
+    reporter: &mut Reporter,
+) -> Result<PathBuf> {
+    if !["http://", "https://", "ftp://"]
+        .iter()
+        .any(|scheme| url.starts_with(scheme))
+    {
+        return Err(NovaError::ConfigError(format!(
+            "'{}' is not an http(s) or ftp URL",
+            url
+        )));
+    }
+    let expected = sha256.map(normalize_sha256).transpose()?;
+    let name = match file_name {
+        Some(name) => name.to_string(),
+        None => file_name_from_url(url).ok_or_else(|| {
+            NovaError::ConfigError(format!(
+                "Cannot tell a file name from '{}'; pass --name",
+                url
+            ))
+        })?,
+    };
+    if !name.to_ascii_lowercase().ends_with(".iso") {
+        return Err(NovaError::ConfigError(format!(
+            "'{}' does not end in .iso, so the ISO scan would not find it; pass --name <file>.iso",
+            name
+        )));
+    }
+    let dest = dest_dir.join(&name);
+    if dest.exists() {
+        return Err(NovaError::ConfigError(format!(
+            "{} already exists",
+            dest.display()
+        )));
+    }
+
+    let part = dest_dir.join(format!("{}.part", name));
+    let mut cmd = Command::new("curl");
+    cmd.args(["-fL", "--silent", "--show-error", "-o"])
+        .arg(&part)
+        .arg(url);
+    if exec::skip(&cmd) {
+        return Ok(dest);
+    }
+    fs::create_dir_all(dest_dir)?;
+
+    reporter.stage(format!("Contacting {}", url));
+    let total = content_length(url).filter(|&total| total > 0);
+    match total {
+        Some(total) => reporter.stage_bytes(format!("Downloading {}", name), total),
+        None => reporter.stage(format!("Downloading {} (size unknown)", name)),
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| NovaError::ConfigError(format!("Failed to run curl: {}", e)))?;
+    while child.try_wait()?.is_none() {
+        if let Some(total) = total {
+            let done = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+            reporter.progress((done as f64 * 100.0 / total as f64).min(100.0), None);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let _ = fs::remove_file(&part);
+        return Err(NovaError::ConfigError(format!(
+            "Download of {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    if total.is_some() {
+        reporter.progress(100.0, None);
+    }
+
+    if let Some(expected) = expected {
+        reporter.stage("Verifying SHA-256");
+        let actual = sha256_file(&part)?;
+        if actual != expected {
+            let _ = fs::remove_file(&part);
+            return Err(NovaError::ConfigError(format!(
+                "SHA-256 mismatch for {}: expected {}, got {}; the download was discarded",
+                name, expected, actual
+            )));
+        }
+    }
+
+    fs::rename(&part, &dest)?;
+    log_info!("Downloaded {} to {}", url, dest.display());
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_sizes_and_digests() {
+        assert_eq!(
+            file_name_from_url("https://example.org/releases/42/Fedora-42.iso?mirror=1#x")
+                .as_deref(),
+            Some("Fedora-42.iso")
+        );
+        assert_eq!(file_name_from_url("https://example.org/"), None);
+        assert_eq!(file_name_from_url("example.org/a.iso"), None);
+
+        let headers = "HTTP/2 302\r\nlocation: https://mirror/a.iso\r\ncontent-length: 0\r\n\r\n\
+                       HTTP/2 200\r\nContent-Length: 2147483648\r\n\r\n";
+        assert_eq!(parse_content_length(headers), Some(2147483648));
+        assert_eq!(parse_content_length("HTTP/2 200\r\n"), None);
+
+        let digest = "A".repeat(64);
+        assert_eq!(normalize_sha256(&digest).unwrap(), "a".repeat(64));
+        assert_eq!(
+            normalize_sha256(&format!("sha256:{}", "b".repeat(64))).unwrap(),
+            "b".repeat(64)
+        );
+        assert!(normalize_sha256("abc").is_err());
+    }
+}
@@ -1,33 +1,51 @@
 pub mod arch_integration;
+pub mod autostart;
 pub mod bolt_runtime;
+pub mod cloud_init;
 pub mod config;
 pub mod console;
 pub mod console_enhanced;
 pub mod container;
+pub mod container_health;
+pub mod container_ports;
 pub mod container_runtime;
+pub mod container_scaling;
 pub mod docker_runtime;
 pub mod error;
+pub mod exec;
 pub mod firewall;
 pub mod gpu_doctor;
 pub mod gpu_passthrough;
 pub mod gui_gpu;
 pub mod gui_network;
+pub mod inspect;
 pub mod instance;
+pub mod iso;
 pub mod libvirt;
 pub mod logger;
 pub mod looking_glass;
+pub mod manager_handle;
+pub mod mdev;
 pub mod migration;
 pub mod monitoring;
+pub mod naming;
 pub mod network;
+pub mod network_dashboard;
+pub mod output;
 pub mod pci_passthrough;
 pub mod performance;
 pub mod performance_monitor;
 pub mod port_monitor;
 pub mod preflight;
+pub mod progress;
 pub mod prometheus;
+pub mod rustdesk_integration;
+pub mod selector;
+pub mod snapshot_archive;
 pub mod spice_console;
 pub mod sriov;
 pub mod storage;
+pub mod storage_backend;
 pub mod storage_pool;
 pub mod support;
 pub mod templates;
@@ -35,8 +53,17 @@ pub mod templates_snapshots;
 pub mod theme;
 pub mod usb_passthrough;
 pub mod vm;
+pub mod vm_boot;
+pub mod vm_cpu;
+pub mod vm_devices;
 pub mod vm_enhanced;
+pub mod vm_hugepages;
+pub mod vm_memory;
+pub mod vm_netlimit;
+pub mod vm_nics;
+pub mod vm_qemu_args;
 pub mod vm_templates;
+pub mod vm_xml;
 
 pub use error::NovaError;
 pub use instance::{Instance, InstanceStatus, InstanceType};
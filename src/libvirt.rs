@@ -1,4 +1,4 @@
-use crate::{NovaError, Result, log_debug, log_error, log_info, log_warn};
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::net::Ipv4Addr;
@@ -133,9 +133,7 @@ impl LibvirtManager {
             args.insert(1, "--disable");
         }
 
-        let output = Command::new("virsh")
-            .args(&args)
-            .output()
+        let output = exec::run(Command::new("virsh").args(&args))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -326,9 +324,7 @@ impl LibvirtManager {
         })?;
 
         // Define the network
-        let output = Command::new("virsh")
-            .args(["net-define", &temp_file])
-            .output()
+        let output = exec::run(Command::new("virsh").args(["net-define", &temp_file]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -340,9 +336,7 @@ impl LibvirtManager {
         }
 
         // Start the network
-        let output = Command::new("virsh")
-            .args(["net-start", &network.name])
-            .output()
+        let output = exec::run(Command::new("virsh").args(["net-start", &network.name]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -352,9 +346,7 @@ impl LibvirtManager {
 
         // Set autostart if requested
         if network.autostart {
-            let _ = Command::new("virsh")
-                .args(["net-autostart", &network.name])
-                .output();
+            let _ = exec::run(Command::new("virsh").args(["net-autostart", &network.name]));
         }
 
         // Clean up temp file
@@ -486,12 +478,10 @@ impl LibvirtManager {
         log_info!("Deleting libvirt network: {}", name);
 
         // Stop the network if it's running
-        let _ = Command::new("virsh").args(["net-destroy", name]).output();
+        let _ = exec::run(Command::new("virsh").args(["net-destroy", name]));
 
         // Undefine the network
-        let output = Command::new("virsh")
-            .args(["net-undefine", name])
-            .output()
+        let output = exec::run(Command::new("virsh").args(["net-undefine", name]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -511,9 +501,7 @@ impl LibvirtManager {
     pub async fn start_network(&self, name: &str) -> Result<()> {
         log_info!("Starting libvirt network: {}", name);
 
-        let output = Command::new("virsh")
-            .args(["net-start", name])
-            .output()
+        let output = exec::run(Command::new("virsh").args(["net-start", name]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -528,9 +516,7 @@ impl LibvirtManager {
     pub async fn stop_network(&self, name: &str) -> Result<()> {
         log_info!("Stopping libvirt network: {}", name);
 
-        let output = Command::new("virsh")
-            .args(["net-destroy", name])
-            .output()
+        let output = exec::run(Command::new("virsh").args(["net-destroy", name]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -1,14 +1,185 @@
+use crate::{NovaError, Result};
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::Level;
+use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+/// Environment variable that enables file logging, overriding `[logging] file`.
+pub const LOG_FILE_ENV: &str = "NOVA_LOG_FILE";
+
 pub fn init_logger() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "nova=info".into()),
-        )
+    init_logger_with_file(log_file_path(None).as_deref());
+}
+
+/// Initialize console logging and, when `file` is set, append the same events
+/// to that file without ANSI colors so `nova logs` can parse them.
+pub fn init_logger_with_file(file: Option<&Path>) {
+    let filter =
+        || EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("nova=info"));
+
+    // Logs go to stderr so `--format json` output on stdout stays parseable
+    let console = fmt::layer()
+        .with_writer(std::io::stderr)
         .with_target(false)
         .with_thread_ids(true)
         .with_file(true)
         .with_line_number(true)
+        .with_filter(filter());
+
+    let mut file_error = None;
+    let file_layer = file.and_then(|path| {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && let Err(err) = std::fs::create_dir_all(parent)
+        {
+            file_error = Some(format!("{}: {}", parent.display(), err));
+            return None;
+        }
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(handle) => Some(
+                fmt::layer()
+                    .with_writer(Mutex::new(handle))
+                    .with_ansi(false)
+                    .with_target(true)
+                    .with_thread_ids(true)
+                    .with_file(true)
+                    .with_line_number(true)
+                    .with_filter(filter()),
+            ),
+            Err(err) => {
+                file_error = Some(format!("{}: {}", path.display(), err));
+                None
+            }
+        }
+    });
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file_layer)
         .init();
+
+    if let Some(err) = file_error {
+        tracing::warn!("File logging disabled, cannot open log file {}", err);
+    }
+}
+
+/// Resolve the log file: `NOVA_LOG_FILE` wins over the NovaFile setting.
+pub fn log_file_path(configured: Option<&Path>) -> Option<PathBuf> {
+    std::env::var_os(LOG_FILE_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| configured.map(Path::to_path_buf))
+}
+
+/// One event parsed back from the log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: Level,
+    /// Module path the event came from (e.g. `nova::network`)
+    pub target: String,
+    /// Source location (`src/network.rs:42`)
+    pub location: Option<String>,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Short subsystem name derived from the target: `nova::network` becomes
+    /// `network`, the CLI and GUI binaries become `cli` and `gui`.
+    pub fn subsystem(&self) -> &str {
+        match self.target.split("::").collect::<Vec<_>>().as_slice() {
+            ["nova"] => "cli",
+            ["nova_gui", ..] => "gui",
+            ["nova", module, ..] => module,
+            [other, ..] => other,
+            [] => "",
+        }
+    }
+}
+
+/// Parse a line written by the file layer of [`init_logger_with_file`].
+///
+/// Returns `None` for continuation lines of multi-line messages.
+pub fn parse_log_line(line: &str) -> Option<LogRecord> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(
+            r"^(\S+)\s+(TRACE|DEBUG|INFO|WARN|ERROR)\s+(?:ThreadId\(\d+\)\s+)?(?:([A-Za-z_][\w:]*):\s+)?(?:(\S+\.rs:\d+):\s+)?(.*)$",
+        )
+        .expect("valid log line pattern")
+    });
+
+    let captures = pattern.captures(line)?;
+    let timestamp = DateTime::parse_from_rfc3339(&captures[1])
+        .ok()?
+        .with_timezone(&Utc);
+    let level = captures[2].parse().ok()?;
+
+    Some(LogRecord {
+        timestamp,
+        level,
+        target: captures
+            .get(3)
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default(),
+        location: captures.get(4).map(|m| m.as_str().to_string()),
+        message: captures[5].to_string(),
+    })
+}
+
+/// Filters applied by `nova logs`.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    pub since: Option<DateTime<Utc>>,
+    /// Most verbose level to show; `WARN` shows warnings and errors
+    pub level: Option<Level>,
+    pub subsystem: Option<String>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.since.is_none_or(|since| record.timestamp >= since)
+            && self.level.is_none_or(|level| record.level <= level)
+            && self
+                .subsystem
+                .as_deref()
+                .is_none_or(|subsystem| record.subsystem().eq_ignore_ascii_case(subsystem))
+    }
+}
+
+/// Parse `--since`: a relative duration (`30s`, `15m`, `2h`, `7d`) or an
+/// RFC 3339 timestamp.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let invalid = || {
+        NovaError::ConfigError(format!(
+            "Invalid --since value '{}'; use e.g. 30s, 15m, 2h, 7d or an RFC 3339 timestamp",
+            value
+        ))
+    };
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    Ok(now - duration)
 }
 
 // Re-export tracing macros for convenience
@@ -41,3 +212,46 @@ macro_rules! log_error {
         tracing::error!($($arg)*);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_file_layer_lines_and_filters() {
+        let line = "2026-10-14T08:30:01.123456Z  WARN ThreadId(01) nova::network: src/network.rs:42: Bridge br0 has no uplink";
+        let record = parse_log_line(line).expect("record");
+        assert_eq!(record.level, Level::WARN);
+        assert_eq!(record.target, "nova::network");
+        assert_eq!(record.subsystem(), "network");
+        assert_eq!(record.location.as_deref(), Some("src/network.rs:42"));
+        assert_eq!(record.message, "Bridge br0 has no uplink");
+
+        let cli = parse_log_line("2026-10-14T08:30:02Z  INFO ThreadId(01) nova: src/main.rs:7: up")
+            .expect("cli record");
+        assert_eq!(cli.subsystem(), "cli");
+        assert!(parse_log_line("  continuation of a multi-line message").is_none());
+
+        let filter = LogFilter {
+            since: Some(parse_since("2026-10-14T08:30:00Z", Utc::now()).unwrap()),
+            level: Some(Level::WARN),
+            subsystem: Some("Network".to_string()),
+        };
+        assert!(filter.matches(&record));
+        assert!(!filter.matches(&cli));
+    }
+
+    #[test]
+    fn since_accepts_relative_durations() {
+        let now = DateTime::parse_from_rfc3339("2026-10-14T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_since("90m", now).unwrap().to_rfc3339(),
+            "2026-10-14T10:30:00+00:00"
+        );
+        assert!(parse_since("2d", now).is_ok());
+        assert!(parse_since("soon", now).is_err());
+        assert!(parse_since("5w", now).is_err());
+    }
+}
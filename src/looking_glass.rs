@@ -111,13 +111,7 @@ impl Default for LookingGlassConfig {
 impl LookingGlassConfig {
     /// Calculate required framebuffer size based on resolution
     pub fn calculate_framebuffer_size(&self) -> u64 {
-        // Formula: width * height * 4 bytes per pixel * 2 buffers + overhead
-        let pixels = self.resolution.width as u64 * self.resolution.height as u64;
-        let bytes = pixels * 4 * 2; // RGBA, double buffered
-        let mb = (bytes / (1024 * 1024)) + 10; // Add 10MB overhead
-
-        // Round up to nearest power of 2 for alignment
-        mb.next_power_of_two()
+        recommended_shmem_mb(required_shmem_bytes(&self.resolution))
     }
 
     /// Validate configuration
@@ -138,6 +132,144 @@ impl LookingGlassConfig {
     }
 }
 
+const MIB: u64 = 1024 * 1024;
+
+/// Room for the KVMFR header, cursor data and alignment ahead of the frames.
+pub const SHMEM_OVERHEAD_BYTES: u64 = 10 * MIB;
+
+/// Shared memory the host application needs for `resolution`: two 32-bit
+/// frames plus the overhead.
+pub fn required_shmem_bytes(resolution: &Resolution) -> u64 {
+    resolution.width as u64 * resolution.height as u64 * 4 * 2 + SHMEM_OVERHEAD_BYTES
+}
+
+/// `size` in MiB for the `<shmem>` device: `required` rounded up to a power
+/// of two, which ivshmem requires.
+pub fn recommended_shmem_mb(required: u64) -> u64 {
+    required.div_ceil(MIB).next_power_of_two()
+}
+
+/// Size in bytes of the `<shmem name='looking-glass'>` device in a domain XML.
+pub fn parse_shmem_size(xml: &str) -> Option<u64> {
+    let start = xml
+        .find("<shmem name='looking-glass'")
+        .or_else(|| xml.find("<shmem name=\"looking-glass\""))?;
+    let device = &xml[start..];
+    let device = &device[..device.find("</shmem>")?];
+    let size = &device[device.find("<size")?..];
+    let (tag, rest) = size.split_once('>')?;
+    let value: u64 = rest.split('<').next()?.trim().parse().ok()?;
+    let unit = tag
+        .split("unit=")
+        .nth(1)
+        .map(|unit| unit.trim_matches(|c| c == '\'' || c == '"' || c == '/'))
+        .unwrap_or("b");
+    let multiplier = match unit {
+        "b" | "bytes" => 1,
+        "k" | "K" | "KiB" => 1024,
+        "M" | "MiB" => MIB,
+        "G" | "GiB" => 1024 * MIB,
+        "KB" => 1000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        _ => return None,
+    };
+    value.checked_mul(multiplier)
+}
+
+/// What is known about the shared memory backing one Looking Glass session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShmemCheck {
+    pub required_bytes: u64,
+    /// `size` of the VM's `<shmem>` device
+    pub configured_bytes: Option<u64>,
+    /// Size of the shared memory file, when it is a regular file
+    pub file_bytes: Option<u64>,
+    pub problems: Vec<String>,
+}
+
+impl ShmemCheck {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Compare what `resolution` needs with the VM's `<shmem>` size and the
+/// shared memory file. `domain_xml` is `None` for VMs Nova cannot read.
+pub fn check_shmem(
+    vm_name: &str,
+    resolution: &Resolution,
+    domain_xml: Option<&str>,
+    shmem_path: &Path,
+) -> ShmemCheck {
+    let required = required_shmem_bytes(resolution);
+    let recommended = recommended_shmem_mb(required);
+    let mut check = ShmemCheck {
+        required_bytes: required,
+        ..Default::default()
+    };
+    let mib = |bytes: u64| bytes as f64 / MIB as f64;
+
+    if let Some(xml) = domain_xml {
+        check.configured_bytes = parse_shmem_size(xml);
+        match check.configured_bytes {
+            None => check.problems.push(format!(
+                "VM '{}' has no <shmem name='looking-glass'> device; add one with \
+                 <size unit='M'>{}</size> (see `nova looking-glass setup {}`)",
+                vm_name, recommended, vm_name
+            )),
+            Some(configured) if configured < required => check.problems.push(format!(
+                "VM '{}' gives Looking Glass {:.0} MiB of shared memory but {}x{} needs {:.1} MiB; \
+                 set <size unit='M'>{}</size> in its <shmem name='looking-glass'> device",
+                vm_name,
+                mib(configured),
+                resolution.width,
+                resolution.height,
+                mib(required),
+                recommended
+            )),
+            Some(_) => {}
+        }
+    }
+
+    match std::fs::metadata(shmem_path) {
+        Err(_) => check.problems.push(format!(
+            "{} does not exist; QEMU creates it when '{}' starts, or create it ahead of time \
+             with systemd-tmpfiles (f {} 0660 <user> kvm -)",
+            shmem_path.display(),
+            vm_name,
+            shmem_path.display()
+        )),
+        Ok(meta) => {
+            // KVMFR character devices are sized by the module's static_size_mb
+            if meta.is_file() {
+                check.file_bytes = Some(meta.len());
+                if meta.len() < required {
+                    check.problems.push(format!(
+                        "{} is {:.1} MiB but {}x{} needs {:.1} MiB; remove it so QEMU \
+                         recreates it at the <shmem> size",
+                        shmem_path.display(),
+                        mib(meta.len()),
+                        resolution.width,
+                        resolution.height,
+                        mib(required)
+                    ));
+                }
+            }
+            let access = nix::unistd::AccessFlags::R_OK | nix::unistd::AccessFlags::W_OK;
+            if nix::unistd::access(shmem_path, access).is_err() {
+                check.problems.push(format!(
+                    "{} is not readable and writable by the current user; make it group kvm \
+                     with mode 0660 and add yourself to that group",
+                    shmem_path.display()
+                ));
+            }
+        }
+    }
+
+    check
+}
+
 pub struct LookingGlassManager {
     configs: std::collections::HashMap<String, LookingGlassConfig>,
 }
@@ -455,6 +587,32 @@ vsync={}
         }
     }
 
+    /// Check the VM's shared memory against `config`. The error lists every
+    /// problem that would leave the client with a black screen.
+    pub fn verify_shmem(
+        &self,
+        vm_name: &str,
+        config: &LookingGlassConfig,
+    ) -> Result<ShmemCheck, String> {
+        let xml = Command::new("virsh")
+            .args(["dumpxml", vm_name])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned());
+        let check = check_shmem(
+            vm_name,
+            &config.resolution,
+            xml.as_deref(),
+            &config.shmem_path,
+        );
+        if check.is_ok() {
+            Ok(check)
+        } else {
+            Err(check.problems.join("\n"))
+        }
+    }
+
     /// Launch Looking Glass client
     pub async fn launch_client(&self, config: &LookingGlassConfig) -> Result<(), String> {
         if !self.check_client_installed() {
@@ -968,4 +1126,42 @@ mod tests {
         assert!(xml.contains("<shmem name='looking-glass'>"));
         assert!(xml.contains("<model type='ivshmem-plain'/>"));
     }
+
+    #[test]
+    fn shmem_size_is_checked_against_the_resolution() {
+        let uhd = Resolution {
+            width: 3840,
+            height: 2160,
+        };
+        assert_eq!(required_shmem_bytes(&uhd), 3840 * 2160 * 8 + 10 * MIB);
+        assert_eq!(recommended_shmem_mb(required_shmem_bytes(&uhd)), 128);
+
+        let manager = LookingGlassManager::new();
+        let xml = format!(
+            "<domain>\n  <devices>\n{}\n  </devices>\n</domain>",
+            manager.generate_ivshmem_xml(&LookingGlassConfig::default())
+        );
+        assert_eq!(parse_shmem_size(&xml), Some(64 * MIB));
+        assert_eq!(parse_shmem_size("<domain/>"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let shm = dir.path().join("looking-glass");
+        std::fs::write(&shm, vec![0u8; 1024]).unwrap();
+
+        let check = check_shmem("win11", &uhd, Some(&xml), &shm);
+        assert_eq!(check.configured_bytes, Some(64 * MIB));
+        assert_eq!(check.file_bytes, Some(1024));
+        assert_eq!(check.problems.len(), 2, "{:?}", check.problems);
+        assert!(check.problems[0].contains("gives Looking Glass 64 MiB"));
+        assert!(check.problems[0].contains("<size unit='M'>128</size>"));
+
+        let hd = Resolution {
+            width: 1920,
+            height: 1080,
+        };
+        std::fs::write(&shm, vec![0u8; 32 * MIB as usize]).unwrap();
+        assert!(check_shmem("win11", &hd, Some(&xml), &shm).is_ok());
+        let missing = check_shmem("win11", &hd, None, &dir.path().join("none"));
+        assert!(missing.problems[0].contains("does not exist"));
+    }
 }
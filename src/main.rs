@@ -1,35 +1,65 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use nova::{
-    NovaError, Result,
-    config::{DiskFormat, NovaConfig, StoragePoolConfig, StoragePoolType},
+    Instance, NovaError, Result, autostart, cloud_init,
+    config::{
+        ContainerConfig, DiskCache, DiskFormat, NovaConfig, StoragePoolConfig, StoragePoolType,
+        VmConfig, ZfsPoolSettings,
+    },
     container::ContainerManager,
+    container_health::{ContainerRestartInfo, CrashLoopPolicy},
+    container_ports,
+    container_runtime::{ContainerStats, PruneOptions, RestartPolicy, RuntimeKind},
+    container_scaling::{self, ScalingThresholds},
+    exec,
+    firewall::{FirewallBackend, FirewallManager, PortRule, Protocol, RuleAction},
     gpu_doctor::GpuDoctor,
-    gpu_passthrough::{DeviceBindingInfo, GpuManager, PciDevice},
+    gpu_passthrough::{DeviceBindingInfo, GpuCapabilities, GpuManager, PciDevice, RestoreOutcome},
+    inspect::{self, ResourceKind},
     libvirt::LibvirtManager,
     logger,
-    migration::{MigrationConfig, MigrationManager},
+    migration::{MigrationConfig, MigrationManager, MigrationStatus},
+    naming::{self, NameKind},
     network::{
-        BridgeConfig, InterfaceState, NetworkManager, SwitchOrigin, SwitchProfile, SwitchStatus,
-        SwitchType,
+        self, BridgeConfig, InterfaceState, NetworkInterface, NetworkManager, PersistentNames,
+        SwitchOrigin, SwitchProfile, SwitchStatus, SwitchType, VirtualSwitch,
     },
+    network_dashboard,
+    output::{Cell, Table, Tone},
     pci_passthrough::PciPassthroughManager,
-    preflight,
-    prometheus::PrometheusExporter,
+    performance_monitor::{self, DomainUsage},
+    port_monitor, preflight,
+    progress::{ProgressStyle, Reporter},
+    prometheus::{PrometheusExporter, parse_bind_addr},
+    rustdesk_integration::{RustDeskInfo, RustDeskManager},
+    selector::{BulkReport, LabelSelector, Selector},
+    snapshot_archive::ImportTarget,
     spice_console::{SpiceConfig, SpiceManager},
     sriov::SriovManager,
     storage::StorageManager,
     storage_pool::{StoragePoolManager, VolumeFormat},
     support::{self, SupportBundleOptions},
     templates::TemplateManager,
-    templates_snapshots::TemplateManager as SnapshotManager,
+    templates_snapshots::{
+        SnapshotQuota, TemplateManager as SnapshotManager, forget_linked_clone,
+        linked_clone_source, linked_clones_of,
+    },
     usb_passthrough::UsbManager,
-    vm::VmManager,
+    vm::{DiskAccess, DiskAttachMode, VmDeleteOptions, VmManager},
+    vm_boot::{BootDevice, BootOverride},
+    vm_cpu::{CpuTopology, VcpuPin},
+    vm_devices::{DeviceChanges, DiskBus, NetModel, VideoModel},
+    vm_hugepages::HugePageSize,
+    vm_memory::MemoryLayout,
+    vm_netlimit::{InterfaceNetLimit, NetLimitChange},
+    vm_nics::VmNic,
+    vm_qemu_args,
 };
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::fmt::Write as FmtWrite;
 use std::io::{self, Write};
 use std::net::Ipv4Addr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::thread::sleep;
 use std::time::Duration;
@@ -49,6 +79,79 @@ struct Cli {
     /// Verbose logging
     #[arg(short, long)]
     verbose: bool,
+
+    /// Suppress progress output; final results are still printed
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Progress style for long operations (default: bar on a terminal, plain otherwise)
+    #[arg(value_enum, long, global = true)]
+    progress: Option<ProgressArg>,
+
+    /// Print the commands that would change the host instead of running them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Container runtime to use instead of auto-detecting one
+    #[arg(value_enum, long, global = true)]
+    runtime: Option<RuntimeArg>,
+
+    /// Prefix statuses in tables with a shape (also on with `[ui] colorblind_safe`)
+    #[arg(long, global = true)]
+    status_symbols: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RestartPolicyArg {
+    No,
+    OnFailure,
+    Always,
+    UnlessStopped,
+}
+
+impl From<RestartPolicyArg> for RestartPolicy {
+    fn from(value: RestartPolicyArg) -> Self {
+        match value {
+            RestartPolicyArg::No => RestartPolicy::No,
+            RestartPolicyArg::OnFailure => RestartPolicy::OnFailure,
+            RestartPolicyArg::Always => RestartPolicy::Always,
+            RestartPolicyArg::UnlessStopped => RestartPolicy::UnlessStopped,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum RuntimeArg {
+    Bolt,
+    Docker,
+    Podman,
+}
+
+impl From<RuntimeArg> for RuntimeKind {
+    fn from(value: RuntimeArg) -> Self {
+        match value {
+            RuntimeArg::Bolt => RuntimeKind::Bolt,
+            RuntimeArg::Docker => RuntimeKind::Docker,
+            RuntimeArg::Podman => RuntimeKind::Podman,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ProgressArg {
+    Plain,
+    Bar,
+    Json,
+}
+
+impl From<ProgressArg> for ProgressStyle {
+    fn from(value: ProgressArg) -> Self {
+        match value {
+            ProgressArg::Plain => ProgressStyle::Plain,
+            ProgressArg::Bar => ProgressStyle::Bar,
+            ProgressArg::Json => ProgressStyle::Json,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -58,22 +161,117 @@ enum Commands {
         /// Type of instance (vm or container)
         #[arg(value_enum)]
         instance_type: InstanceType,
-        /// Name of the instance
-        name: String,
+        /// Names of the instances; globs like "web-*" are allowed
+        #[arg(required_unless_present_any = ["all", "label"])]
+        names: Vec<String>,
+        /// Start every instance of this type
+        #[arg(long, conflicts_with = "names")]
+        all: bool,
+        /// Only instances whose NovaFile labels match (repeatable)
+        #[arg(long, value_name = "KEY=VALUE")]
+        label: Vec<String>,
+        /// Attach an ISO for this start only and boot from it (VMs only)
+        #[arg(long, value_name = "PATH")]
+        boot_iso: Option<PathBuf>,
+        /// Boot device order for this start only, e.g. `cdrom,hd` (VMs only)
+        #[arg(long, value_enum, value_delimiter = ',', value_name = "DEVICES")]
+        boot_order: Option<Vec<BootDeviceArg>>,
     },
     /// Stop a VM or container
     Stop {
         /// Type of instance (vm or container)
         #[arg(value_enum)]
         instance_type: InstanceType,
+        /// Names of the instances; globs like "web-*" are allowed
+        #[arg(required_unless_present_any = ["all", "label"])]
+        names: Vec<String>,
+        /// Stop every instance of this type
+        #[arg(long, conflicts_with = "names")]
+        all: bool,
+        /// Only instances whose NovaFile labels match (repeatable)
+        #[arg(long, value_name = "KEY=VALUE")]
+        label: Vec<String>,
+    },
+    /// Pause a running VM without losing its state
+    Suspend {
+        /// Type of instance (only vm is supported)
+        #[arg(value_enum)]
+        instance_type: InstanceType,
+        /// Name of the instance
+        name: String,
+    },
+    /// Resume a suspended VM
+    Resume {
+        /// Type of instance (only vm is supported)
+        #[arg(value_enum)]
+        instance_type: InstanceType,
         /// Name of the instance
         name: String,
     },
     /// List all instances
     #[command(alias = "ls")]
-    List,
+    List {
+        /// Output format
+        #[arg(value_enum, long, default_value_t = OutputFormatArg::Table)]
+        format: OutputFormatArg,
+    },
     /// Show version information
     Version,
+    /// Start all autostart VMs and containers in dependency order
+    Up {
+        /// Keep running and serve Prometheus metrics after startup
+        #[arg(long)]
+        serve_metrics: bool,
+        /// Metrics port used with --serve-metrics
+        #[arg(long, default_value_t = 9640, requires = "serve_metrics")]
+        metrics_port: u16,
+    },
+    /// Write a systemd unit that runs `nova up` at boot
+    InstallService {
+        /// Unit file to write
+        #[arg(long, default_value = "/etc/systemd/system/nova.service")]
+        output: PathBuf,
+        /// Have the unit keep serving metrics on this port
+        #[arg(long)]
+        metrics_port: Option<u16>,
+    },
+    /// Run a command inside a running container, e.g. `nova exec web -- sh`
+    Exec {
+        /// Container name
+        name: String,
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Show a container's logs or a VM's serial console log; without a
+    /// name, Nova's own log filtered by time, level and subsystem
+    Logs {
+        /// Container or VM name
+        name: Option<String>,
+        /// Number of recent lines to show before following
+        #[arg(short = 'n', long, default_value_t = 100, requires = "name")]
+        lines: usize,
+        /// Only show entries newer than this (e.g. 15m, 2h, 1d or RFC 3339)
+        #[arg(long, conflicts_with = "name")]
+        since: Option<String>,
+        /// Minimum level to show
+        #[arg(value_enum, long, conflicts_with = "name")]
+        level: Option<LogLevelArg>,
+        /// Only show entries from this subsystem (e.g. network, vm, cli)
+        #[arg(long, conflicts_with = "name")]
+        subsystem: Option<String>,
+        /// Keep printing new entries as they are written, until Ctrl-C
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Live CPU, memory, network and block I/O for running containers
+    Stats {
+        /// Container name; all running containers when omitted
+        name: Option<String>,
+        /// Seconds between refreshes
+        #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
     /// Show status of a specific instance
     Status {
         /// Type of instance (vm or container)
@@ -81,12 +279,41 @@ enum Commands {
         instance_type: InstanceType,
         /// Name of the instance
         name: String,
+        /// Output format
+        #[arg(value_enum, long, default_value_t = OutputFormatArg::Table)]
+        format: OutputFormatArg,
+    },
+    /// Inspect a VM, container, network, storage pool or GPU by name
+    Inspect {
+        /// Resource name (GPUs by PCI address)
+        name: String,
+        /// Resource type, needed when the name matches more than one
+        #[arg(value_enum, long = "type")]
+        resource_type: Option<ResourceTypeArg>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// VM operations
+    Vm {
+        #[command(subcommand)]
+        vm_command: VmCommands,
+    },
+    /// Container operations
+    Container {
+        #[command(subcommand)]
+        container_command: ContainerCommands,
     },
     /// Container template management
     Template {
         #[command(subcommand)]
         template_command: TemplateCommands,
     },
+    /// Installer ISO downloads
+    Iso {
+        #[command(subcommand)]
+        iso_command: IsoCommands,
+    },
     /// Guided configuration wizards
     Wizard {
         #[command(subcommand)]
@@ -113,11 +340,16 @@ enum Commands {
         snapshot_command: SnapshotCommands,
     },
     /// VM cloning operations
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Clone {
+        #[command(subcommand)]
+        clone_command: Option<CloneCommands>,
         /// Source VM name
-        source: String,
+        #[arg(required = true)]
+        source: Option<String>,
         /// New VM name
-        target: String,
+        #[arg(required = true)]
+        target: Option<String>,
         /// Create linked clone (saves disk space)
         #[arg(long)]
         linked: bool,
@@ -131,6 +363,26 @@ enum Commands {
         /// Force offline migration
         #[arg(long)]
         offline: bool,
+        /// Switch to post-copy after the first pass (for VMs that won't converge)
+        #[arg(long, conflicts_with = "offline")]
+        post_copy: bool,
+        /// Cap the transfer rate (MiB/s) so the uplink stays usable
+        #[arg(long, value_name = "MIB_S", value_parser = clap::value_parser!(u32).range(1..))]
+        max_bandwidth: Option<u32>,
+        /// Compress memory pages in flight
+        #[arg(long)]
+        compress: bool,
+        /// Throttle guest vCPUs until the copy converges
+        #[arg(long)]
+        auto_converge: bool,
+        /// Copy the disks too, for destinations without shared storage
+        #[arg(long)]
+        copy_storage: bool,
+    },
+    /// Inspect and control migration jobs
+    Migration {
+        #[command(subcommand)]
+        migration_command: MigrationCommands,
     },
     /// USB passthrough management
     Usb {
@@ -147,16 +399,50 @@ enum Commands {
         #[command(subcommand)]
         sriov_command: SriovCommands,
     },
+    /// Mediated devices (vGPU slices shared between VMs)
+    Mdev {
+        #[command(subcommand)]
+        mdev_command: MdevCommands,
+    },
     /// SPICE console management
     Spice {
         #[command(subcommand)]
         spice_command: SpiceCommands,
     },
+    /// RustDesk remote desktop for guests
+    Rustdesk {
+        #[command(subcommand)]
+        rustdesk_command: RustdeskCommands,
+    },
+    /// List listening TCP/UDP ports and the VM or container behind each
+    Ports {
+        /// Only show this host port
+        port: Option<u16>,
+        /// Output format
+        #[arg(value_enum, long, default_value_t = OutputFormatArg::Table)]
+        format: OutputFormatArg,
+    },
+    /// Host firewall backend selection
+    Firewall {
+        #[command(subcommand)]
+        firewall_command: FirewallCommands,
+    },
     /// Observability and metrics tooling
     Metrics {
         #[command(subcommand)]
         metrics_command: MetricsCommands,
     },
+    /// Check that this host is ready to run Nova workloads
+    Preflight {
+        /// Automatically fix detected issues where possible
+        #[arg(long)]
+        fix: bool,
+    },
+    /// NovaFile inspection
+    Config {
+        #[command(subcommand)]
+        config_command: ConfigCommands,
+    },
     /// Support bundles and diagnostics
     Support {
         #[command(subcommand)]
@@ -178,8 +464,15 @@ enum Commands {
 enum WizardCommands {
     /// Generate a NovaFile VM entry from guided inputs
     Vm(WizardVmArgs),
+    /// Generate a NovaFile container entry from guided inputs
+    Container(WizardContainerArgs),
 }
 
+const TEMPLATES_DIR: &str = "/var/lib/nova/templates";
+
+/// How long `nova status vm` watches a running VM to work out its CPU use
+const VM_USAGE_SAMPLE_WINDOW: Duration = Duration::from_millis(500);
+
 const WIZARD_DEFAULT_CPU: u32 = 4;
 const WIZARD_DEFAULT_MEMORY: &str = "8Gi";
 
@@ -193,9 +486,9 @@ struct WizardVmArgs {
     /// Memory allocation (e.g. "8Gi", defaults to 8Gi)
     #[arg(long)]
     memory: Option<String>,
-    /// Target network bridge (omit to choose interactively)
-    #[arg(long)]
-    network: Option<String>,
+    /// Target network bridge, repeatable for one NIC each (omit to choose interactively)
+    #[arg(long = "network")]
+    networks: Vec<String>,
     /// Override the disk image path (defaults to /var/lib/nova/images/<name>.qcow2)
     #[arg(long)]
     image: Option<String>,
@@ -208,6 +501,45 @@ struct WizardVmArgs {
     /// Apply a preset with sensible defaults (e.g. windows11)
     #[arg(long, value_enum)]
     preset: Option<VmPreset>,
+    /// cloud-init user-data to build a NoCloud seed ISO from (attached as a CD-ROM)
+    #[arg(long = "cloud-init", value_name = "USER_DATA")]
+    cloud_init: Option<PathBuf>,
+    /// Persist the generated entry to a NovaFile
+    #[arg(long)]
+    apply: bool,
+    /// Alternate output file (defaults to --config/NovaFile)
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct WizardContainerArgs {
+    /// Name of the container to generate (letters, numbers, '-', '_')
+    name: String,
+    /// Image to run (omit to be prompted)
+    #[arg(long)]
+    image: Option<String>,
+    /// Published port as host:container[/udp]; repeat or comma-separate
+    #[arg(long = "port", value_delimiter = ',')]
+    ports: Vec<String>,
+    /// Volume as host:container[:ro]; repeatable
+    #[arg(long = "volume")]
+    volumes: Vec<String>,
+    /// Environment variable as KEY=VALUE; repeatable
+    #[arg(long = "env")]
+    env: Vec<String>,
+    /// Network to attach to (defaults to the runtime's own)
+    #[arg(long)]
+    network: Option<String>,
+    /// Give the container GPU access through Bolt
+    #[arg(long)]
+    gpu: bool,
+    /// Start the container automatically with Nova
+    #[arg(long)]
+    autostart: bool,
+    /// Restart policy the runtime applies when the container exits
+    #[arg(value_enum, long)]
+    restart: Option<RestartPolicyArg>,
     /// Persist the generated entry to a NovaFile
     #[arg(long)]
     apply: bool,
@@ -236,121 +568,651 @@ enum VmPreset {
 }
 
 #[derive(Subcommand)]
-enum TemplateCommands {
-    /// List available container templates
-    List {
-        /// Filter by category
-        #[arg(short, long)]
-        category: Option<String>,
+enum VmCommands {
+    /// Undefine a shut-off VM, optionally removing its disks and snapshots
+    Delete {
+        /// VM name
+        vm: String,
+        /// Also delete disks that are storage pool volumes, with their backing chains
+        #[arg(long)]
+        remove_disks: bool,
+        /// Also delete the VM's snapshots (required when it has any)
+        #[arg(long)]
+        remove_snapshots: bool,
+        /// Delete disks even when linked clones use them as a backing file
+        #[arg(long, requires = "remove_disks")]
+        force: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
-    /// Show template details
-    Show {
-        /// Template name
-        name: String,
+    /// Manage VM disks
+    Disk {
+        #[command(subcommand)]
+        disk_command: VmDiskCommands,
     },
-    /// Deploy a template
-    Deploy {
-        /// Template name
-        template: String,
-        /// Project name
-        #[arg(short, long, default_value = "nova-project")]
-        project: String,
-        /// Output file for generated NovaFile
-        #[arg(short, long, default_value = "NovaFile")]
-        output: String,
+    /// Print a VM's libvirt domain XML
+    Dumpxml {
+        /// VM name
+        vm: String,
+        /// Show the persistent definition instead of the running configuration
+        #[arg(long)]
+        inactive: bool,
     },
-}
-
-#[derive(Subcommand)]
-enum NetworkCommands {
-    /// List known bridges and interfaces
-    List,
-    /// Show details for a bridge or interface
-    Inspect {
-        /// Bridge or interface name
-        name: String,
+    /// Edit a VM's domain XML in $EDITOR and redefine it
+    Edit {
+        /// VM name
+        vm: String,
+        /// Restore the definition saved before the last edit
+        #[arg(long)]
+        rollback: bool,
     },
-    /// Create a new virtual switch/bridge
-    Create {
-        /// Name of the bridge to create
-        name: String,
-        /// Switch type backend
-        #[arg(value_enum, long = "type", default_value = "bridge")]
-        switch_type: NetworkSwitchTypeArg,
-        /// Profile to apply after creating the bridge
-        #[arg(value_enum, long = "profile")]
-        profile: Option<NetworkProfileArg>,
-        /// Uplink interface for external or NAT profiles
-        #[arg(long = "uplink", value_name = "IFACE")]
-        uplink: Option<String>,
-        /// Subnet in CIDR form for NAT profile (e.g. 192.168.200.1/24)
-        #[arg(long = "subnet", value_name = "CIDR")]
-        subnet: Option<String>,
-        /// DHCP allocation range for NAT profile (format: start-end)
-        #[arg(long = "dhcp-range", value_name = "START-END")]
-        dhcp_range: Option<String>,
-        /// Interfaces to attach after creation
-        #[arg(long = "attach", value_name = "IFACE")]
-        attach_interfaces: Vec<String>,
-        /// Enable Spanning Tree Protocol after creation
+    /// Switch an existing (shut off) VM between virtio and emulated devices
+    Devices {
+        /// VM name
+        vm: String,
+        /// Bus for all disks
+        #[arg(value_enum, long)]
+        disk_bus: Option<DiskBusArg>,
+        /// NIC model for all interfaces
+        #[arg(value_enum, long)]
+        net_model: Option<NetModelArg>,
+        /// Video adapter
+        #[arg(value_enum, long)]
+        video: Option<VideoModelArg>,
+    },
+    /// Hot-add a memory DIMM (needs max_memory set when the VM was created)
+    MemAdd {
+        /// VM name
+        vm: String,
+        /// DIMM size in MiB
         #[arg(long)]
-        stp: bool,
+        size: u64,
     },
-    /// Delete an existing virtual switch/bridge
-    Delete {
-        /// Name of the bridge to delete
-        name: String,
+    /// Unplug a previously hot-added memory DIMM
+    MemRemove {
+        /// VM name
+        vm: String,
+        /// Size in MiB of the DIMM to remove
+        #[arg(long)]
+        size: u64,
     },
-    /// Attach a host interface to a bridge
-    Attach {
-        /// Bridge name
-        switch: String,
-        /// Interface to attach
-        interface: String,
+    /// Balloon a VM's memory up or down, live when it is running
+    SetMemory {
+        /// VM name
+        vm: String,
+        /// New size (e.g. 4G, 6144M)
+        size: String,
     },
-    /// Detach a host interface from a bridge
-    Detach {
-        /// Bridge name
-        switch: String,
-        /// Interface to detach
-        interface: String,
+    /// Back a shut-off VM's memory with hugepages
+    SetHugepages {
+        /// VM name
+        vm: String,
+        /// Page size
+        #[arg(value_enum, long, default_value = "2M")]
+        size: HugePageSizeArg,
+        /// Go back to normal pages
+        #[arg(long, conflicts_with = "size")]
+        off: bool,
     },
-    /// Manage libvirt networks
-    Libvirt {
-        #[command(subcommand)]
-        command: LibvirtNetworkCommands,
+    /// Attach a storage pool volume as a new disk, live when the VM is running
+    AttachDisk {
+        /// VM name
+        vm: String,
+        /// Storage pool
+        pool: String,
+        /// Volume in the pool
+        volume: String,
+        /// Disk bus
+        #[arg(value_enum, long, default_value = "virtio")]
+        bus: AttachBusArg,
+        /// Present the disk read-only to the guest
+        #[arg(long)]
+        readonly: bool,
+        /// Let other VMs attach the same volume concurrently (raw images only)
+        #[arg(long)]
+        shareable: bool,
+        /// Host page cache mode (shareable disks need none or directsync)
+        #[arg(value_enum, long, default_value = "none")]
+        cache: DiskCacheArg,
     },
-}
-
-#[derive(Subcommand)]
-enum LibvirtNetworkCommands {
-    /// List libvirt networks and their state
-    List,
-    /// Start a libvirt network
-    Start {
-        /// Network name
-        name: String,
+    /// Detach a pool volume; refused while the guest has it mounted
+    DetachDisk {
+        /// VM name
+        vm: String,
+        /// Storage pool
+        pool: String,
+        /// Volume in the pool
+        volume: String,
     },
-    /// Stop a libvirt network
-    Stop {
-        /// Network name
-        name: String,
+    /// Change a VM's online vCPUs, live when it is running
+    SetVcpus {
+        /// VM name
+        vm: String,
+        /// New vCPU count, up to the domain's maximum
+        count: u32,
     },
-    /// Toggle autostart flag for a libvirt network
+    /// Cap a VM's network bandwidth, live when it is running
+    SetNetlimit {
+        /// VM name
+        vm: String,
+        /// Receive limit in MiB/s (0 for unlimited)
+        #[arg(long, value_name = "MIB")]
+        inbound: Option<u32>,
+        /// Send limit in MiB/s (0 for unlimited)
+        #[arg(long, value_name = "MIB")]
+        outbound: Option<u32>,
+        /// Remove both limits
+        #[arg(long, conflicts_with_all = ["inbound", "outbound"])]
+        unlimited: bool,
+        /// Only this interface (MAC or tap name); defaults to all
+        #[arg(long)]
+        interface: Option<String>,
+    },
+    /// Plug a VM into another network, live when it is running
+    AddNic {
+        /// VM name
+        vm: String,
+        /// Libvirt network or host bridge
+        network: String,
+        /// NIC model; defaults to [device_defaults] net_model
+        #[arg(value_enum, long)]
+        model: Option<NetModelArg>,
+        /// MAC address; libvirt picks one when omitted
+        #[arg(long)]
+        mac: Option<String>,
+    },
+    /// Unplug one of a VM's NICs, live when it is running
+    RemoveNic {
+        /// VM name
+        vm: String,
+        /// MAC or tap name of the NIC
+        interface: String,
+    },
+    /// Start a libvirt domain when the host boots
     Autostart {
-        /// Network name
-        name: String,
-        /// Disable autostart instead of enabling
+        /// VM name
+        vm: String,
+        /// Turn autostart off instead
         #[arg(long)]
         disable: bool,
     },
-    /// Show detailed XML definition for a network
-    DumpXml {
-        /// Network name
-        name: String,
+    /// Show or change which host CPUs a VM's vCPUs run on
+    Pin {
+        /// VM name
+        vm: String,
+        /// vCPU and host CPUs as VCPU=CPUS, e.g. 0=2 or 1=4-5; repeatable
+        #[arg(long = "vcpu", value_name = "VCPU=CPUS")]
+        pins: Vec<String>,
     },
-}
-
+    /// Show or change a shut-off VM's CPU sockets, cores and threads
+    Topology {
+        /// VM name
+        vm: String,
+        /// Sockets (default 1)
+        #[arg(long)]
+        sockets: Option<u32>,
+        /// Cores per socket (default: vCPUs / (sockets * threads))
+        #[arg(long)]
+        cores: Option<u32>,
+        /// Threads per core (default 1)
+        #[arg(long)]
+        threads: Option<u32>,
+    },
+    /// Pass raw QEMU arguments through <qemu:commandline> (unsupported)
+    QemuArg {
+        #[command(subcommand)]
+        qemu_arg_command: VmQemuArgCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum VmQemuArgCommands {
+    /// Append arguments, e.g. `nova vm qemu-arg add win11 -- -device ivshmem-plain,...`
+    Add {
+        /// VM name
+        vm: String,
+        /// Raw QEMU arguments, after `--`
+        #[arg(last = true, required = true)]
+        args: Vec<String>,
+    },
+    /// Show a VM's raw QEMU arguments
+    List {
+        /// VM name
+        vm: String,
+    },
+    /// Remove all raw QEMU arguments from a VM
+    Clear {
+        /// VM name
+        vm: String,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum DiskBusArg {
+    Virtio,
+    Sata,
+    Scsi,
+    Ide,
+}
+
+impl From<DiskBusArg> for DiskBus {
+    fn from(value: DiskBusArg) -> Self {
+        match value {
+            DiskBusArg::Virtio => DiskBus::Virtio,
+            DiskBusArg::Sata => DiskBus::Sata,
+            DiskBusArg::Scsi => DiskBus::Scsi,
+            DiskBusArg::Ide => DiskBus::Ide,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum HugePageSizeArg {
+    #[value(name = "2M")]
+    Size2M,
+    #[value(name = "1G")]
+    Size1G,
+}
+
+impl From<HugePageSizeArg> for HugePageSize {
+    fn from(value: HugePageSizeArg) -> Self {
+        match value {
+            HugePageSizeArg::Size2M => HugePageSize::Size2M,
+            HugePageSizeArg::Size1G => HugePageSize::Size1G,
+        }
+    }
+}
+
+/// Buses a disk can be hot-plugged on.
+#[derive(ValueEnum, Clone, Copy)]
+enum AttachBusArg {
+    Virtio,
+    Scsi,
+}
+
+impl From<AttachBusArg> for DiskBus {
+    fn from(value: AttachBusArg) -> Self {
+        match value {
+            AttachBusArg::Virtio => DiskBus::Virtio,
+            AttachBusArg::Scsi => DiskBus::Scsi,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum DiskCacheArg {
+    None,
+    Directsync,
+    Writethrough,
+    Writeback,
+    Unsafe,
+}
+
+impl From<DiskCacheArg> for DiskCache {
+    fn from(value: DiskCacheArg) -> Self {
+        match value {
+            DiskCacheArg::None => DiskCache::None,
+            DiskCacheArg::Directsync => DiskCache::Directsync,
+            DiskCacheArg::Writethrough => DiskCache::Writethrough,
+            DiskCacheArg::Writeback => DiskCache::Writeback,
+            DiskCacheArg::Unsafe => DiskCache::Unsafe,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum NetModelArg {
+    Virtio,
+    E1000e,
+    Rtl8139,
+}
+
+impl From<NetModelArg> for NetModel {
+    fn from(value: NetModelArg) -> Self {
+        match value {
+            NetModelArg::Virtio => NetModel::Virtio,
+            NetModelArg::E1000e => NetModel::E1000e,
+            NetModelArg::Rtl8139 => NetModel::Rtl8139,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum VideoModelArg {
+    Virtio,
+    Qxl,
+    Vga,
+    None,
+}
+
+impl From<VideoModelArg> for VideoModel {
+    fn from(value: VideoModelArg) -> Self {
+        match value {
+            VideoModelArg::Virtio => VideoModel::Virtio,
+            VideoModelArg::Qxl => VideoModel::Qxl,
+            VideoModelArg::Vga => VideoModel::Vga,
+            VideoModelArg::None => VideoModel::None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum VmDiskCommands {
+    /// Pass a whole host block device (e.g. /dev/sdb) to a VM
+    AttachBlock {
+        /// VM name
+        vm: String,
+        /// Host block device
+        #[arg(long = "dev", value_name = "DEVICE")]
+        device: PathBuf,
+        /// Guest target (e.g. vdb); the next free virtio target by default
+        #[arg(long)]
+        target: Option<String>,
+        /// Attach to the running VM
+        #[arg(long)]
+        live: bool,
+        /// Add to the persistent VM definition
+        #[arg(long)]
+        persistent: bool,
+        /// Present the disk read-only to the guest
+        #[arg(long)]
+        readonly: bool,
+        /// Let other VMs attach the same device concurrently
+        #[arg(long)]
+        shareable: bool,
+        /// Host page cache mode (shareable disks need none or directsync)
+        #[arg(value_enum, long, default_value = "none")]
+        cache: DiskCacheArg,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContainerCommands {
+    /// Sample stats and suggest raising or lowering CPU/memory limits
+    Advise {
+        /// Container name
+        name: String,
+        /// Sampling window in seconds (defaults to `sustained_seconds`)
+        #[arg(long)]
+        window: Option<u64>,
+        /// Seconds between samples
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+    },
+    /// Recreate a container with new port mappings (its ID changes)
+    RemapPorts {
+        /// Container name
+        name: String,
+        /// New mappings as host:container[/udp]; repeat or comma-separate
+        #[arg(long, required = true, value_delimiter = ',')]
+        ports: Vec<String>,
+    },
+    /// Pull an image with the active container runtime
+    Pull {
+        /// Image reference, e.g. nginx:latest
+        image: String,
+    },
+    /// Show disk used by images, containers, volumes and build cache
+    Df,
+    /// Remove stopped containers, unused networks, dangling images and build cache
+    Prune {
+        /// Also remove every image no container uses
+        #[arg(long)]
+        images: bool,
+        /// Also remove volumes no container uses
+        #[arg(long)]
+        volumes: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CloneCommands {
+    /// Check that every backing file of a clone's disks still exists
+    Verify {
+        /// VM name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// List available container templates
+    List {
+        /// Filter by category
+        #[arg(short, long)]
+        category: Option<String>,
+    },
+    /// Show template details
+    Show {
+        /// Template name
+        name: String,
+    },
+    /// Deploy a template
+    Deploy {
+        /// Template name
+        template: String,
+        /// Project name
+        #[arg(short, long, default_value = "nova-project")]
+        project: String,
+        /// Output file for generated NovaFile
+        #[arg(short, long, default_value = "NovaFile")]
+        output: String,
+        /// Check every image is pullable before writing the NovaFile
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Capture a VM's disk and settings as a reusable VM template
+    CreateFrom {
+        /// VM to capture; it is shut down first if running
+        vm: String,
+        /// Name of the new template
+        name: String,
+        /// Template description
+        #[arg(short, long, default_value = "")]
+        description: String,
+        /// Tag to attach (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Generalize the copied disk with virt-sysprep (machine ID, SSH keys, logs)
+        #[arg(long)]
+        sysprep: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NetworkCommands {
+    /// List known bridges and interfaces
+    List,
+    /// Show details and current throughput for a bridge or interface
+    Inspect {
+        /// Bridge or interface name
+        name: String,
+        /// Refresh the traffic counters every second until Ctrl+C
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Create a new virtual switch/bridge
+    Create {
+        /// Name of the bridge to create
+        name: String,
+        /// Switch type backend
+        #[arg(value_enum, long = "type", default_value = "bridge")]
+        switch_type: NetworkSwitchTypeArg,
+        /// Profile to apply after creating the bridge
+        #[arg(value_enum, long = "profile")]
+        profile: Option<NetworkProfileArg>,
+        /// Uplink interface for external or NAT profiles
+        #[arg(long = "uplink", value_name = "IFACE")]
+        uplink: Option<String>,
+        /// Subnet in CIDR form for NAT profile (e.g. 192.168.200.1/24)
+        #[arg(long = "subnet", value_name = "CIDR")]
+        subnet: Option<String>,
+        /// DHCP allocation range for NAT profile (format: start-end)
+        #[arg(long = "dhcp-range", value_name = "START-END")]
+        dhcp_range: Option<String>,
+        /// Interfaces to attach after creation
+        #[arg(long = "attach", value_name = "IFACE")]
+        attach_interfaces: Vec<String>,
+        /// Enable Spanning Tree Protocol after creation
+        #[arg(long)]
+        stp: bool,
+        /// Put every port in this VLAN (1-4094); an external uplink carries it tagged
+        #[arg(long, value_name = "ID")]
+        vlan: Option<u16>,
+        /// MTU for the bridge and its attached interfaces (576-9216)
+        #[arg(long, value_name = "BYTES")]
+        mtu: Option<u32>,
+        /// Move the default-route interface's IPv4 addresses and gateway onto the bridge
+        #[arg(long)]
+        move_ip: bool,
+        /// Enslave the default-route interface without moving its IP (drops remote sessions)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete an existing virtual switch/bridge
+    Delete {
+        /// Name of the bridge to delete
+        name: String,
+    },
+    /// Attach a host interface to a bridge
+    Attach {
+        /// Bridge name
+        switch: String,
+        /// Interface to attach
+        interface: String,
+        /// Move the interface's IPv4 addresses and gateway onto the bridge
+        #[arg(long)]
+        move_ip: bool,
+        /// Attach the default-route interface without moving its IP (drops remote sessions)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Detach a host interface from a bridge
+    Detach {
+        /// Bridge name
+        switch: String,
+        /// Interface to detach
+        interface: String,
+    },
+    /// Make a bridge member (uplink or VM tap) an 802.1Q trunk port
+    Trunk {
+        /// Bridge name (must have VLAN filtering enabled)
+        switch: String,
+        /// Member interface, e.g. an uplink or a VM's vnetN tap
+        interface: String,
+        /// VLAN IDs to carry tagged, e.g. 10,20,30 or 100-110
+        #[arg(long, value_name = "VIDS")]
+        vids: String,
+    },
+    /// Set or clear a persistent alias on a host interface
+    Alias {
+        /// Interface name
+        interface: String,
+        /// Alias to apply (e.g. "uplink-10g")
+        #[arg(required_unless_present = "clear")]
+        alias: Option<String>,
+        /// Remove the alias and its persisted definition
+        #[arg(long, conflicts_with = "alias")]
+        clear: bool,
+    },
+    /// Show how bridges, host interfaces and running VMs are connected
+    Topology {
+        /// Output format; `dot` renders with graphviz (`| dot -Tsvg > net.svg`)
+        #[arg(value_enum, long, default_value_t = TopologyFormatArg::Table)]
+        format: TopologyFormatArg,
+    },
+    /// List DHCP leases on a NAT switch or libvirt network bridge
+    Leases {
+        /// Bridge name, e.g. a NAT switch or virbr0
+        bridge: String,
+        /// Output format
+        #[arg(value_enum, long, default_value_t = OutputFormatArg::Table)]
+        format: OutputFormatArg,
+    },
+    /// Live dashboard of switches, members and throughput
+    Monitor {
+        /// Refresh interval in seconds
+        #[arg(long, default_value_t = 1, value_name = "SECONDS")]
+        interval: u64,
+    },
+    /// Manage libvirt networks
+    Libvirt {
+        #[command(subcommand)]
+        command: LibvirtNetworkCommands,
+    },
+    /// Open vSwitch bridge inspection
+    Ovs {
+        #[command(subcommand)]
+        command: OvsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum OvsCommands {
+    /// Show a bridge's OpenFlow table
+    Flows {
+        /// Open vSwitch bridge name
+        bridge: String,
+        /// Only show flows in this table
+        #[arg(long)]
+        table: Option<u8>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LibvirtNetworkCommands {
+    /// List libvirt networks and their state
+    List,
+    /// Start a libvirt network
+    Start {
+        /// Network name
+        name: String,
+    },
+    /// Stop a libvirt network
+    Stop {
+        /// Network name
+        name: String,
+    },
+    /// Toggle autostart flag for a libvirt network
+    Autostart {
+        /// Network name
+        name: String,
+        /// Disable autostart instead of enabling
+        #[arg(long)]
+        disable: bool,
+    },
+    /// Show detailed XML definition for a network
+    DumpXml {
+        /// Network name
+        name: String,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum LogLevelArg {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevelArg> for tracing::Level {
+    fn from(value: LogLevelArg) -> Self {
+        match value {
+            LogLevelArg::Error => tracing::Level::ERROR,
+            LogLevelArg::Warn => tracing::Level::WARN,
+            LogLevelArg::Info => tracing::Level::INFO,
+            LogLevelArg::Debug => tracing::Level::DEBUG,
+            LogLevelArg::Trace => tracing::Level::TRACE,
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone)]
 enum NetworkSwitchTypeArg {
     Bridge,
@@ -422,12 +1284,132 @@ enum InstanceType {
     Container,
 }
 
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum OutputFormatArg {
+    Table,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum TopologyFormatArg {
+    Table,
+    Dot,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ResourceTypeArg {
+    Vm,
+    Container,
+    Switch,
+    Interface,
+    Pool,
+    Gpu,
+}
+
+impl From<ResourceTypeArg> for ResourceKind {
+    fn from(value: ResourceTypeArg) -> Self {
+        match value {
+            ResourceTypeArg::Vm => ResourceKind::Vm,
+            ResourceTypeArg::Container => ResourceKind::Container,
+            ResourceTypeArg::Switch => ResourceKind::Switch,
+            ResourceTypeArg::Interface => ResourceKind::Interface,
+            ResourceTypeArg::Pool => ResourceKind::StoragePool,
+            ResourceTypeArg::Gpu => ResourceKind::Gpu,
+        }
+    }
+}
+
+/// One instance in `nova list --format json` and `nova status --format json`
+#[derive(serde::Serialize)]
+struct InstanceReport<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    instance_type: nova::InstanceType,
+    status: nova::instance::InstanceStatus,
+    pid: Option<u32>,
+    memory_mb: u64,
+    cpu_cores: u32,
+    network: Option<&'a str>,
+    ip_address: Option<&'a str>,
+    /// Libvirt autostart, for `nova status vm`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autostart: Option<bool>,
+    /// Live CPU and memory use of a running VM, for `nova status vm`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<DomainUsage>,
+    /// Bandwidth limits per interface, for `nova status vm`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    net_limits: Vec<InterfaceNetLimit>,
+    /// Restart details, for `nova status container`
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    restarts: Option<&'a ContainerRestartInfo>,
+}
+
+impl<'a> InstanceReport<'a> {
+    fn new(instance: &'a Instance) -> Self {
+        Self {
+            name: &instance.name,
+            instance_type: instance.instance_type,
+            status: instance.status,
+            pid: instance.pid,
+            memory_mb: instance.memory_mb,
+            cpu_cores: instance.cpu_cores,
+            network: instance.network.as_deref(),
+            ip_address: instance.ip_address.as_deref(),
+            autostart: None,
+            usage: None,
+            net_limits: Vec::new(),
+            restarts: None,
+        }
+    }
+}
+
+/// Payload emitted by `nova inspect --json`
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InspectReport<'a> {
+    Vm {
+        name: &'a str,
+        instance: Option<Instance>,
+        libvirt_state: Option<String>,
+        config: Option<&'a VmConfig>,
+    },
+    Container {
+        instance: &'a Instance,
+        config: Option<&'a ContainerConfig>,
+    },
+    Switch {
+        switch: &'a VirtualSwitch,
+        connected_vms: Vec<network::ConnectedVm>,
+    },
+    Interface {
+        interface: &'a NetworkInterface,
+        persistent_names: PersistentNames,
+    },
+    StoragePool {
+        name: &'a str,
+        pool: &'a StoragePoolConfig,
+    },
+    Gpu {
+        device: &'a PciDevice,
+        capabilities: Option<&'a GpuCapabilities>,
+    },
+}
+
 #[derive(Subcommand)]
 enum GpuCommands {
     /// Run comprehensive GPU passthrough diagnostics
     Doctor,
     /// List all detected GPUs
-    List,
+    List {
+        /// Emit machine-readable JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Nest GPUs under their IOMMU group
+        #[arg(long)]
+        by_group: bool,
+    },
     /// Show detailed GPU information
     Info {
         /// PCI address (e.g., 0000:01:00.0) or "all"
@@ -437,18 +1419,27 @@ enum GpuCommands {
     Bind {
         /// PCI address of GPU to bind
         device: String,
+        /// Bind even if the GPU is driving the host display
+        #[arg(long)]
+        force: bool,
     },
     /// Release a GPU from vfio-pci
     Release {
         /// PCI address of GPU to release
         device: String,
     },
+    /// Return every vfio-bound GPU not used by a running VM to its host driver
+    #[command(alias = "unbind-all")]
+    ResetHost,
     /// Reserve a GPU for a VM
     Reserve {
         /// PCI address of GPU
         device: String,
         /// VM name
         vm_name: String,
+        /// Reserve even if the GPU is driving the host display
+        #[arg(long)]
+        force: bool,
     },
     /// Show live GPU binding status
     Status {
@@ -472,6 +1463,23 @@ enum GpuCommands {
         #[arg(long)]
         device: Option<String>,
     },
+    /// Mediated-device (vGPU) profiles
+    Mdev {
+        #[command(subcommand)]
+        mdev_command: GpuMdevCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum GpuMdevCommands {
+    /// List the vGPU/mdev profiles a GPU supports
+    List {
+        /// PCI address of the GPU
+        device: String,
+        /// Emit machine-readable JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(ValueEnum, Clone)]
@@ -515,6 +1523,15 @@ enum StorageCommands {
         /// Labels applied to the pool (repeat flag)
         #[arg(long = "label")]
         labels: Vec<String>,
+        /// ZFS dataset backing the pool (required for --pool-type zfs)
+        #[arg(long)]
+        dataset: Option<String>,
+        /// ZFS compression (off, lz4, gzip-N, zstd-N)
+        #[arg(long, default_value = "lz4", requires = "dataset")]
+        compression: String,
+        /// Enable ZFS deduplication
+        #[arg(long, requires = "dataset")]
+        dedup: bool,
     },
     /// Delete a storage pool
     #[command(name = "delete-pool")]
@@ -525,6 +1542,20 @@ enum StorageCommands {
         #[arg(long)]
         keep_directory: bool,
     },
+    /// Re-read a pool's capacity after growing the disk or filesystem under it
+    #[command(name = "refresh-pool")]
+    RefreshPool {
+        /// Pool name
+        name: String,
+    },
+    /// Show a pool's state, path and capacity
+    #[command(name = "pool-info")]
+    PoolInfo {
+        /// Pool name
+        name: String,
+    },
+    /// Check all pools for overlapping paths and other misconfigurations
+    Verify,
     /// List volumes in a pool
     #[command(name = "list-volumes")]
     ListVolumes {
@@ -544,6 +1575,46 @@ enum StorageCommands {
         #[arg(value_enum, long, default_value = "qcow2")]
         format: VolumeFormatArg,
     },
+    /// Delete a volume and its backing file
+    #[command(name = "delete-volume")]
+    DeleteVolume {
+        /// Pool name
+        pool: String,
+        /// Volume name
+        name: String,
+        /// Delete even if a defined VM still uses the volume
+        #[arg(long)]
+        force: bool,
+    },
+    /// Clone a volume (copy-on-write on ZFS pools)
+    #[command(name = "clone-volume")]
+    CloneVolume {
+        /// Pool name
+        pool: String,
+        /// Source volume name
+        source: String,
+        /// New volume name
+        target: String,
+    },
+    /// Grow a volume, optionally notifying the running VM it is attached to
+    #[command(name = "resize-volume")]
+    ResizeVolume {
+        /// Pool name
+        pool: String,
+        /// Volume name
+        name: String,
+        /// New size (e.g., 100G, 1T)
+        size: String,
+        /// VM the volume is attached to; a running VM grows it live
+        #[arg(long, requires = "target")]
+        vm: Option<String>,
+        /// Disk target inside the VM (e.g., vda)
+        #[arg(long, requires = "vm")]
+        target: Option<String>,
+        /// Ask the guest agent which filesystems to grow and print the commands
+        #[arg(long, requires = "vm")]
+        grow_guest: bool,
+    },
 }
 
 #[derive(ValueEnum, Clone)]
@@ -551,6 +1622,7 @@ enum StoragePoolTypeArg {
     Dir,
     Btrfs,
     Nfs,
+    Zfs,
 }
 
 impl From<StoragePoolTypeArg> for StoragePoolType {
@@ -559,6 +1631,7 @@ impl From<StoragePoolTypeArg> for StoragePoolType {
             StoragePoolTypeArg::Dir => StoragePoolType::Directory,
             StoragePoolTypeArg::Btrfs => StoragePoolType::Btrfs,
             StoragePoolTypeArg::Nfs => StoragePoolType::Nfs,
+            StoragePoolTypeArg::Zfs => StoragePoolType::Zfs,
         }
     }
 }
@@ -619,6 +1692,12 @@ enum SnapshotCommands {
         vm: String,
         /// Snapshot name
         snapshot: String,
+        /// Take a pre-revert-<timestamp> snapshot first so the revert can be undone
+        #[arg(long)]
+        safe: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
     /// Delete a snapshot
     Delete {
@@ -630,6 +1709,76 @@ enum SnapshotCommands {
         #[arg(long)]
         children: bool,
     },
+    /// Show or set a VM's snapshot quota (stored in the NovaFile)
+    Quota {
+        /// VM name
+        vm: String,
+        /// Maximum number of snapshots
+        #[arg(long)]
+        max: Option<usize>,
+        /// Maximum total snapshot storage (e.g. 50G)
+        #[arg(long, value_name = "SIZE")]
+        max_bytes: Option<String>,
+        /// Prune the oldest snapshots instead of refusing new ones
+        #[arg(long)]
+        auto_prune: Option<bool>,
+        /// Remove all limits
+        #[arg(long, conflicts_with_all = ["max", "max_bytes", "auto_prune"])]
+        clear: bool,
+    },
+    /// Export a snapshot to a standalone archive for off-host backup
+    Export {
+        /// VM name
+        vm: String,
+        /// Snapshot name
+        snapshot: String,
+        /// Archive to write (a tar file)
+        output: PathBuf,
+        /// Include the external memory state, if the snapshot has one
+        #[arg(long)]
+        memory: bool,
+    },
+    /// Import an exported snapshot archive
+    Import {
+        /// Archive written by `nova snapshot export`
+        archive: PathBuf,
+        /// VM to add the snapshot to (defaults to the archived VM)
+        #[arg(long, conflicts_with = "as_vm")]
+        vm: Option<String>,
+        /// Define a new VM from the snapshot instead
+        #[arg(long, value_name = "NAME")]
+        as_vm: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrationCommands {
+    /// Show the phase and transfer progress of a job
+    Status {
+        /// Job ID printed by `nova migrate`
+        job_id: String,
+    },
+    /// List migration jobs
+    List {
+        /// Include finished jobs
+        #[arg(long)]
+        all: bool,
+    },
+    /// Abort a running migration
+    Cancel {
+        /// Job ID printed by `nova migrate`
+        job_id: String,
+    },
+    /// Check CPU compatibility and storage on a destination without migrating
+    Precheck {
+        /// VM name
+        vm: String,
+        /// Destination host
+        destination: String,
+        /// Disks will be copied, so they need not exist on the destination
+        #[arg(long)]
+        copy_storage: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -646,6 +1795,9 @@ enum UsbCommands {
         /// Product ID (e.g., c52b)
         #[arg(long)]
         product: String,
+        /// Also add it to the VM definition so it survives a reboot
+        #[arg(long)]
+        persistent: bool,
     },
     /// Detach USB device from VM
     Detach {
@@ -657,6 +1809,9 @@ enum UsbCommands {
         /// Product ID
         #[arg(long)]
         product: String,
+        /// Also remove it from the VM definition
+        #[arg(long)]
+        persistent: bool,
     },
 }
 
@@ -673,12 +1828,16 @@ enum PciCommands {
         /// PCI address (e.g., 0000:01:00.0)
         device: String,
     },
-    /// Attach PCI device to VM
+    /// Attach PCI devices to VM (pass a whole IOMMU group together)
     Attach {
         /// VM name
         vm: String,
-        /// PCI address
-        device: String,
+        /// PCI addresses
+        #[arg(required = true)]
+        devices: Vec<String>,
+        /// Attach even if other devices in the IOMMU group are bound to host drivers
+        #[arg(long)]
+        force: bool,
     },
     /// Detach PCI device from VM
     Detach {
@@ -703,6 +1862,9 @@ enum SriovCommands {
         /// Number of Virtual Functions to create
         #[arg(long)]
         num_vfs: u32,
+        /// Seconds to wait for the VF devices to appear
+        #[arg(long, default_value_t = 10, value_name = "SECONDS")]
+        timeout: u64,
     },
     /// Disable SR-IOV on a device
     Disable {
@@ -727,48 +1889,242 @@ enum SriovCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum IsoCommands {
+    /// Download an installer ISO into an ISO directory
+    Fetch {
+        /// http(s) or ftp URL of the ISO
+        url: String,
+        /// Directory to save into (default: the first `[iso] paths` entry)
+        #[arg(long)]
+        dest: Option<PathBuf>,
+        /// File name to save as (default: the last part of the URL)
+        #[arg(long)]
+        name: Option<String>,
+        /// Expected SHA-256; the download is discarded if it doesn't match
+        #[arg(long, value_name = "DIGEST")]
+        sha256: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MdevCommands {
+    /// Create a mediated device from one of a GPU's profiles
+    Create {
+        /// PCI address of the parent GPU
+        pf: String,
+        /// Profile type ID or name (see `nova gpu mdev list`)
+        profile: String,
+    },
+    /// Add a mediated device to a VM's definition
+    Assign {
+        /// UUID printed by `nova mdev create`
+        uuid: String,
+        /// VM name
+        vm: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum SpiceCommands {
     /// Connect to VM via SPICE
     Connect {
         /// VM name
         vm: String,
+        /// Print the SPICE URI instead of launching a client
+        #[arg(long)]
+        print_uri: bool,
     },
     /// Show SPICE connection info
     Info {
         /// VM name
         vm: String,
     },
-    /// Configure SPICE for a VM
-    Config {
-        /// VM name
-        vm: String,
-        /// Enable/disable feature
-        #[arg(long)]
-        audio: Option<bool>,
-        /// Enable/disable clipboard sharing
-        #[arg(long)]
-        clipboard: Option<bool>,
-        /// Enable/disable USB redirection
-        #[arg(long)]
-        usb: Option<bool>,
-        /// Number of monitors
-        #[arg(long)]
-        monitors: Option<u32>,
+    /// Configure SPICE for a VM
+    Config {
+        /// VM name
+        vm: String,
+        /// Enable/disable feature
+        #[arg(long)]
+        audio: Option<bool>,
+        /// Enable/disable clipboard sharing
+        #[arg(long)]
+        clipboard: Option<bool>,
+        /// Enable/disable USB redirection
+        #[arg(long)]
+        usb: Option<bool>,
+        /// Number of monitors
+        #[arg(long)]
+        monitors: Option<u32>,
+    },
+}
+
+fn print_rustdesk_info(info: &RustDeskInfo) {
+    println!("RustDesk for VM '{}':", info.vm_name);
+    println!(
+        "  Guest agent: {}",
+        if info.guest_agent {
+            "reachable"
+        } else {
+            "not reachable"
+        }
+    );
+    if let Some(guest) = info.guest {
+        println!("  Guest OS: {:?}", guest);
+    }
+    if let Some(id) = &info.id {
+        println!("  ID: {}", id);
+    }
+    if let Some(password) = &info.password {
+        println!(
+            "  Password: {} (new permanent RustDesk password, enter it in the client)",
+            password
+        );
+    }
+    if let Some(problem) = &info.problem {
+        println!("⚠️  {}", problem);
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum BootDeviceArg {
+    #[value(alias = "disk")]
+    Hd,
+    #[value(alias = "cd")]
+    Cdrom,
+    #[value(alias = "net", alias = "pxe")]
+    Network,
+}
+
+impl From<BootDeviceArg> for BootDevice {
+    fn from(device: BootDeviceArg) -> Self {
+        match device {
+            BootDeviceArg::Hd => BootDevice::Hd,
+            BootDeviceArg::Cdrom => BootDevice::Cdrom,
+            BootDeviceArg::Network => BootDevice::Network,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum RustdeskCommands {
+    /// Replace the guest's permanent RustDesk password and launch the client
+    Connect {
+        /// VM name
+        vm: String,
+        /// Print the ID and password instead of launching a client
+        #[arg(long)]
+        print: bool,
+    },
+    /// Show the guest's RustDesk ID and guest agent status
+    Info {
+        /// VM name
+        vm: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FirewallCommands {
+    /// Show the managed backend and warn when several hold rules
+    Status,
+    /// Pin the backend Nova manages rules through (stored in the NovaFile)
+    Backend {
+        /// Backend to manage, or `auto` to clear the pin
+        #[arg(value_enum)]
+        backend: FirewallBackendArg,
+    },
+    /// Open a port (rules are tagged so `nova firewall rules` can find them)
+    Allow(FirewallRuleArgs),
+    /// Block a port
+    Deny(FirewallRuleArgs),
+    /// List the rules Nova added, numbered for `remove`
+    Rules,
+    /// Remove a rule Nova added
+    Remove {
+        /// Rule number from `nova firewall rules`
+        index: usize,
     },
 }
 
+async fn add_firewall_rule(config: &NovaConfig, rule: PortRule) -> Result<()> {
+    let manager = FirewallManager::with_settings(&config.firewall)?;
+    manager.add_rule(&rule).await?;
+    println!("✅ Added to {}: {}", manager.get_backend(), rule.tag());
+    Ok(())
+}
+
+#[derive(Args, Debug)]
+struct FirewallRuleArgs {
+    /// Destination port
+    #[arg(long)]
+    port: u16,
+    #[arg(long, value_enum, default_value = "tcp")]
+    proto: RuleProtoArg,
+    /// Source address or CIDR (any source when omitted)
+    #[arg(long)]
+    source: Option<String>,
+    /// Chain to use (iptables `INPUT`, nftables `inet filter input`)
+    #[arg(long)]
+    chain: Option<String>,
+    /// Note stored in the rule's tag
+    #[arg(long)]
+    comment: Option<String>,
+}
+
+impl FirewallRuleArgs {
+    fn rule(self, action: RuleAction) -> PortRule {
+        PortRule {
+            chain: self.chain,
+            protocol: match self.proto {
+                RuleProtoArg::Tcp => Protocol::Tcp,
+                RuleProtoArg::Udp => Protocol::Udp,
+            },
+            port: self.port,
+            source: self.source,
+            action,
+            comment: self.comment,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RuleProtoArg {
+    Tcp,
+    Udp,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum FirewallBackendArg {
+    Auto,
+    Nftables,
+    Iptables,
+    Firewalld,
+    Ufw,
+}
+
+impl FirewallBackendArg {
+    fn backend(self) -> Option<FirewallBackend> {
+        match self {
+            FirewallBackendArg::Auto => None,
+            FirewallBackendArg::Nftables => Some(FirewallBackend::Nftables),
+            FirewallBackendArg::Iptables => Some(FirewallBackend::Iptables),
+            FirewallBackendArg::Firewalld => Some(FirewallBackend::Firewalld),
+            FirewallBackendArg::Ufw => Some(FirewallBackend::Ufw),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum MetricsCommands {
     /// Run the Prometheus exporter until interrupted
     Serve {
-        /// TCP port to bind the exporter to
-        #[arg(long, default_value_t = 9100)]
-        port: u16,
+        /// TCP port to bind the exporter to (defaults to 9100)
+        #[arg(long)]
+        port: Option<u16>,
         /// Metrics collection interval in seconds
         #[arg(long, default_value_t = 15)]
         interval: u64,
-        /// Bind address (defaults to 0.0.0.0)
+        /// Bind address, optionally with a port (e.g. 0.0.0.0:9100)
         #[arg(long, default_value = "0.0.0.0")]
         bind: String,
     },
@@ -776,6 +2132,12 @@ enum MetricsCommands {
     Snapshot,
 }
 
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Report unknown keys, invalid sizes and dangling references in the NovaFile
+    Check,
+}
+
 #[derive(Subcommand)]
 enum SupportCommands {
     /// Generate a compressed support bundle for troubleshooting
@@ -873,7 +2235,11 @@ impl From<PerformanceProfileArg> for nova::performance::PerformanceProfile {
 #[derive(Subcommand)]
 enum LookingGlassCommands {
     /// Check system requirements for Looking Glass
-    Check,
+    Check {
+        /// Also check this VM's shared memory size and permissions
+        #[arg(long)]
+        vm: Option<String>,
+    },
     /// Setup Looking Glass for a VM
     Setup {
         /// VM name
@@ -942,6 +2308,156 @@ fn parse_cli_dhcp_range(range: &str) -> Result<(Ipv4Addr, Ipv4Addr)> {
     Ok((start, end))
 }
 
+/// Print matching entries from Nova's log file, optionally following it.
+///
+/// Continuation lines of multi-line messages follow the record they belong to.
+async fn print_nova_logs(
+    path: &std::path::Path,
+    filter: &logger::LogFilter,
+    follow: bool,
+) -> Result<()> {
+    use std::io::{BufRead, BufReader, IsTerminal, Seek, SeekFrom};
+
+    let color = io::stdout().is_terminal();
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    let mut position = 0u64;
+    let mut show_continuation = false;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 || !line.ends_with('\n') {
+            if !follow {
+                if read > 0 {
+                    print_log_line(line.trim_end(), filter, color, &mut show_continuation);
+                }
+                return Ok(());
+            }
+
+            // Wait for the rest of a partially written line before parsing it
+            reader.seek(SeekFrom::Start(position))?;
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            if std::fs::metadata(path)?.len() < position {
+                // Rotated or truncated: start over from the beginning
+                reader = BufReader::new(std::fs::File::open(path)?);
+                position = 0;
+            }
+            continue;
+        }
+
+        position += read as u64;
+        print_log_line(line.trim_end(), filter, color, &mut show_continuation);
+    }
+}
+
+/// Print the last `lines` lines of `path`, then keep printing whatever is
+/// appended when following.
+async fn print_file_tail(path: &std::path::Path, lines: usize, follow: bool) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    let text = String::from_utf8_lossy(&contents);
+    let recent: Vec<&str> = text.lines().collect();
+    for line in &recent[recent.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut position = contents.len() as u64;
+    let mut stdout = io::stdout();
+    loop {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let len = std::fs::metadata(path)?.len();
+        if len < position {
+            // Rotated or truncated: start over from the beginning
+            file = std::fs::File::open(path)?;
+            position = 0;
+        }
+        if len > position {
+            file.seek(SeekFrom::Start(position))?;
+            let mut appended = Vec::new();
+            file.read_to_end(&mut appended)?;
+            position += appended.len() as u64;
+            stdout.write_all(&appended)?;
+            stdout.flush()?;
+        }
+    }
+}
+
+fn print_log_line(
+    line: &str,
+    filter: &logger::LogFilter,
+    color: bool,
+    show_continuation: &mut bool,
+) {
+    let Some(record) = logger::parse_log_line(line) else {
+        if *show_continuation {
+            println!("{}", line);
+        }
+        return;
+    };
+
+    *show_continuation = filter.matches(&record);
+    if !*show_continuation {
+        return;
+    }
+
+    let level = format!("{:<5}", record.level);
+    let level = if color {
+        let code = match record.level {
+            tracing::Level::ERROR => "31",
+            tracing::Level::WARN => "33",
+            tracing::Level::INFO => "32",
+            tracing::Level::DEBUG => "34",
+            tracing::Level::TRACE => "2",
+        };
+        format!("\x1b[{}m{}\x1b[0m", code, level)
+    } else {
+        level
+    };
+
+    println!(
+        "{} {} {:<16} {}",
+        record
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        level,
+        record.subsystem(),
+        record.message
+    );
+}
+
+/// Commands with host changes that don't all go through `exec`, so a dry
+/// run would carry some of them out. These refuse `--dry-run` instead.
+fn dry_run_unsupported(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Up { .. } => Some("up"),
+        Commands::InstallService { .. } => Some("install-service"),
+        Commands::Exec { .. } => Some("exec"),
+        Commands::Migrate { .. } => Some("migrate"),
+        Commands::Clone {
+            clone_command: None,
+            ..
+        } => Some("clone"),
+        Commands::Template {
+            template_command: TemplateCommands::CreateFrom { .. },
+        } => Some("template create-from"),
+        Commands::Snapshot {
+            snapshot_command: SnapshotCommands::Export { .. },
+        } => Some("snapshot export"),
+        Commands::Snapshot {
+            snapshot_command: SnapshotCommands::Import { .. },
+        } => Some("snapshot import"),
+        _ => None,
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -952,137 +2468,741 @@ async fn main() -> Result<()> {
             std::env::set_var("RUST_LOG", "nova=debug");
         }
     }
-    logger::init_logger();
 
     let config_path = cli.config.clone();
-
-    // Load configuration
-    let config = if config_path.exists() {
+    if cli.dry_run
+        && let Some(command) = dry_run_unsupported(&cli.command)
+    {
+        return Err(NovaError::ConfigError(format!(
+            "--dry-run is not supported for `nova {}`; it would still change the host",
+            command
+        )));
+    }
+    exec::set_dry_run(cli.dry_run);
+    let progress_style = cli
+        .progress
+        .map(ProgressStyle::from)
+        .unwrap_or_else(ProgressStyle::detect);
+    let quiet = cli.quiet;
+    let reporter = |operation: &str| Reporter::new(operation, progress_style, quiet);
+
+    // Load configuration first so the logger can pick up `[logging] file`
+    let config_found = config_path.exists();
+    let config = if config_found {
         NovaConfig::from_file(&config_path)?
     } else {
+        NovaConfig::default()
+    };
+    let status_symbols = cli.status_symbols || config.ui.colorblind_safe;
+    let log_file = logger::log_file_path(config.logging.file.as_deref());
+    logger::init_logger_with_file(log_file.as_deref());
+    if !config_found {
         logger::warn!(
             "NovaFile not found at {}, using defaults",
             config_path.display()
         );
-        NovaConfig::default()
-    };
+    }
+    naming::configure(config.naming.clone());
 
     // Initialize managers
     let vm_manager = VmManager::new();
-    let container_manager = ContainerManager::new();
+    let container_runtime = cli
+        .runtime
+        .map(RuntimeKind::from)
+        .or(config.container_runtime);
+    let mut container_manager = LazyContainerManager::new(container_runtime, &config);
     let template_manager = TemplateManager::new();
 
     match cli.command {
         Commands::Run {
+            instance_type,
+            names,
+            all,
+            label,
+            boot_iso,
+            boot_order,
+        } => {
+            let selector = instance_selector(names, all, &label)?;
+            if !selector.is_single() {
+                if boot_iso.is_some() || boot_order.is_some() {
+                    return Err(NovaError::ConfigError(
+                        "--boot-iso and --boot-order apply to a single named VM".to_string(),
+                    ));
+                }
+                let targets = select_instances(
+                    &instance_type,
+                    &selector,
+                    &config,
+                    &vm_manager,
+                    &container_manager,
+                )
+                .await?;
+                let kind = match instance_type {
+                    InstanceType::Vm => nova::instance::InstanceType::Vm,
+                    InstanceType::Container => nova::instance::InstanceType::Container,
+                };
+                let targets = autostart::bulk_start_order(&config, kind, &targets)?;
+                let mut report = BulkReport::default();
+                for name in &targets {
+                    let result = match instance_type {
+                        InstanceType::Vm => match ensure_linked_clone_intact(name) {
+                            Ok(()) => vm_manager.start_vm(name, config.get_vm(name)).await,
+                            err => err,
+                        },
+                        InstanceType::Container => {
+                            container_manager
+                                .get()?
+                                .start_container(name, config.get_container(name))
+                                .await
+                        }
+                    };
+                    report.record(name, result);
+                }
+                print!("{}", report.render("started"));
+                return report.into_result("started");
+            }
+
+            let name = selector.patterns[0].clone();
+            match instance_type {
+                InstanceType::Vm => {
+                    ensure_linked_clone_intact(&name)?;
+                    let vm_config = config.get_vm(&name);
+                    let boot = BootOverride {
+                        iso: boot_iso,
+                        order: boot_order
+                            .map(|devices| devices.into_iter().map(BootDevice::from).collect()),
+                    };
+                    let already_running = vm_manager
+                        .get_vm(&name)
+                        .is_some_and(|instance| instance.is_running());
+                    vm_manager
+                        .start_vm_with_boot(&name, vm_config, &boot)
+                        .await?;
+                    if already_running {
+                        println!("VM '{}' is already running", name);
+                    } else {
+                        println!("VM '{}' started successfully", name);
+                    }
+                    if !boot.is_empty() {
+                        let order: Vec<String> = boot
+                            .effective_order()
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect();
+                        if already_running {
+                            println!(
+                                "⚠️  Boot override ({}) not applied: it needs a cold boot. Stop '{}' and run it again with the same flags",
+                                order.join(", "),
+                                name
+                            );
+                        } else {
+                            println!("Boot override for this start only: {}", order.join(", "));
+                        }
+                    }
+                }
+                InstanceType::Container if boot_iso.is_some() || boot_order.is_some() => {
+                    return Err(NovaError::ConfigError(
+                        "--boot-iso and --boot-order only apply to VMs".to_string(),
+                    ));
+                }
+                InstanceType::Container => {
+                    let container_config = config.get_container(&name);
+                    container_manager
+                        .get()?
+                        .start_container(&name, container_config)
+                        .await?;
+                    println!("Container '{}' started successfully", name);
+                }
+            }
+        }
+        Commands::Up {
+            serve_metrics,
+            metrics_port,
+        } => {
+            let order = autostart::startup_order(&config)?;
+            if order.is_empty() {
+                println!(
+                    "No autostart instances defined in {}",
+                    config_path.display()
+                );
+            }
+
+            let mut failed: Vec<String> = Vec::new();
+            for entry in &order {
+                if let Some(dependency) = entry
+                    .depends_on
+                    .iter()
+                    .find(|dependency| failed.contains(dependency))
+                {
+                    println!(
+                        "⏭️  Skipping '{}': dependency '{}' failed to start",
+                        entry.name, dependency
+                    );
+                    failed.push(entry.name.clone());
+                    continue;
+                }
+
+                let result = match entry.instance_type {
+                    nova::instance::InstanceType::Vm => {
+                        match ensure_linked_clone_intact(&entry.name) {
+                            Ok(()) => {
+                                vm_manager
+                                    .start_vm(&entry.name, config.get_vm(&entry.name))
+                                    .await
+                            }
+                            err => err,
+                        }
+                    }
+                    nova::instance::InstanceType::Container => {
+                        container_manager
+                            .get()?
+                            .start_container(&entry.name, config.get_container(&entry.name))
+                            .await
+                    }
+                };
+
+                match result {
+                    Ok(()) => println!("✅ Started '{}'", entry.name),
+                    Err(err) => {
+                        println!("❌ Failed to start '{}': {}", entry.name, err);
+                        failed.push(entry.name.clone());
+                    }
+                }
+            }
+
+            if !failed.is_empty() {
+                return Err(NovaError::ConfigError(format!(
+                    "{} of {} autostart instance(s) did not start: {}",
+                    failed.len(),
+                    order.len(),
+                    failed.join(", ")
+                )));
+            }
+
+            if serve_metrics {
+                println!("Serving Prometheus metrics on 0.0.0.0:{}", metrics_port);
+                PrometheusExporter::new(metrics_port)
+                    .with_container_runtime(container_runtime)
+                    .start()
+                    .await?;
+            }
+        }
+        Commands::InstallService {
+            output,
+            metrics_port,
+        } => {
+            let binary = std::env::current_exe()?;
+            let novafile = std::fs::canonicalize(&config_path).map_err(|_| {
+                NovaError::ConfigError(format!(
+                    "NovaFile not found at {}; pass --config with the file the service should use",
+                    config_path.display()
+                ))
+            })?;
+
+            let unit = autostart::render_systemd_unit(&binary, &novafile, metrics_port);
+            std::fs::write(&output, unit)?;
+
+            let unit_name = output
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "nova.service".to_string());
+            println!("✅ Wrote {}", output.display());
+            println!("Enable it with:");
+            println!("  systemctl daemon-reload");
+            println!("  systemctl enable --now {}", unit_name);
+        }
+        Commands::Exec { name, command } => {
+            use std::io::IsTerminal;
+
+            let tty = io::stdin().is_terminal() && io::stdout().is_terminal();
+            let code = container_manager
+                .get()?
+                .exec_in_container(&name, &command, tty)
+                .await?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Logs {
+            name: Some(name),
+            lines,
+            follow,
+            ..
+        } => {
+            let is_container = container_manager
+                .list_or_warn()
+                .await
+                .iter()
+                .any(|container| container.name == name);
+            if is_container {
+                container_manager
+                    .get()?
+                    .stream_container_logs(&name, lines, follow)
+                    .await?;
+            } else {
+                let path = vm_manager.serial_log_path(&name).map_err(|err| match err {
+                    NovaError::VmNotFound(_) | NovaError::SystemCommandFailed => {
+                        NovaError::ConfigError(format!("'{}' is not a known container or VM", name))
+                    }
+                    err => err,
+                })?;
+                print_file_tail(&path, lines, follow).await?;
+            }
+        }
+        Commands::Logs {
+            name: None,
+            since,
+            level,
+            subsystem,
+            follow,
+            ..
+        } => {
+            let path = log_file.ok_or_else(|| {
+                NovaError::ConfigError(format!(
+                    "File logging is not enabled. Set `file` under [logging] in the NovaFile or export {}.",
+                    logger::LOG_FILE_ENV
+                ))
+            })?;
+            if !path.exists() {
+                return Err(NovaError::ConfigError(format!(
+                    "Log file {} does not exist yet; run a Nova command with file logging enabled first",
+                    path.display()
+                )));
+            }
+
+            let filter = logger::LogFilter {
+                since: since
+                    .as_deref()
+                    .map(|value| logger::parse_since(value, chrono::Utc::now()))
+                    .transpose()?,
+                level: level.map(Into::into),
+                subsystem,
+            };
+            print_nova_logs(&path, &filter, follow).await?;
+        }
+        Commands::Stats { name, interval } => {
+            use std::io::IsTerminal;
+
+            let clear = io::stdout().is_terminal();
+            loop {
+                let rows = match &name {
+                    Some(name) => match container_manager.get()?.container_stats(name).await {
+                        Ok(stats) => vec![(name.clone(), stats)],
+                        Err(_) => {
+                            return Err(NovaError::ConfigError(format!(
+                                "Container '{}' is not running",
+                                name
+                            )));
+                        }
+                    },
+                    None => container_manager.get()?.all_container_stats().await?,
+                };
+
+                if clear {
+                    print!("\x1b[2J\x1b[H");
+                }
+                println!(
+                    "Container stats at {} (every {}s, Ctrl+C to stop)",
+                    chrono::Local::now().format("%H:%M:%S"),
+                    interval
+                );
+                if rows.is_empty() {
+                    println!("No running containers");
+                } else {
+                    print!("{}", render_container_stats(&rows));
+                }
+                if !clear {
+                    println!();
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                    _ = tokio::signal::ctrl_c() => break,
+                }
+            }
+        }
+        Commands::Stop {
+            instance_type,
+            names,
+            all,
+            label,
+        } => {
+            let selector = instance_selector(names, all, &label)?;
+            if !selector.is_single() {
+                let targets = select_instances(
+                    &instance_type,
+                    &selector,
+                    &config,
+                    &vm_manager,
+                    &container_manager,
+                )
+                .await?;
+                let mut report = BulkReport::default();
+                for name in &targets {
+                    let result = match instance_type {
+                        InstanceType::Vm
+                            if vm_manager.get_vm(name).is_none_or(|vm| {
+                                vm.status == nova::instance::InstanceStatus::Stopped
+                            }) =>
+                        {
+                            report.skip(name, "already stopped");
+                            continue;
+                        }
+                        InstanceType::Vm => vm_manager.stop_vm(name).await,
+                        InstanceType::Container => {
+                            container_manager.get()?.stop_container(name).await
+                        }
+                    };
+                    report.record(name, result);
+                }
+                print!("{}", report.render("stopped"));
+                return report.into_result("stopped");
+            }
+
+            let name = &selector.patterns[0];
+            match instance_type {
+                InstanceType::Vm => {
+                    vm_manager.stop_vm(name).await?;
+                    println!("VM '{}' stopped successfully", name);
+                }
+                InstanceType::Container => {
+                    container_manager.get()?.stop_container(name).await?;
+                    println!("Container '{}' stopped successfully", name);
+                }
+            }
+        }
+        Commands::Suspend {
             instance_type,
             name,
         } => match instance_type {
             InstanceType::Vm => {
-                let vm_config = config.get_vm(&name);
-                vm_manager.start_vm(&name, vm_config).await?;
-                println!("VM '{}' started successfully", name);
+                vm_manager.suspend_vm(&name).await?;
+                println!("⏸ VM '{}' suspended", name);
+                println!("Resume it with: nova resume vm {}", name);
             }
             InstanceType::Container => {
-                let container_config = config.get_container(&name);
-                container_manager
-                    .start_container(&name, container_config)
-                    .await?;
-                println!("Container '{}' started successfully", name);
+                return Err(NovaError::ConfigError(
+                    "Suspend is only supported for VMs".to_string(),
+                ));
             }
         },
-        Commands::Stop {
+        Commands::Resume {
             instance_type,
             name,
         } => match instance_type {
             InstanceType::Vm => {
-                vm_manager.stop_vm(&name).await?;
-                println!("VM '{}' stopped successfully", name);
+                vm_manager.resume_vm(&name).await?;
+                println!("▶ VM '{}' resumed", name);
             }
             InstanceType::Container => {
-                container_manager.stop_container(&name).await?;
-                println!("Container '{}' stopped successfully", name);
+                return Err(NovaError::ConfigError(
+                    "Resume is only supported for VMs".to_string(),
+                ));
             }
         },
-        Commands::List => {
+        Commands::List { format } => {
+            vm_manager.refresh_libvirt_states();
             let vms = vm_manager.list_vms();
-            let containers = container_manager.list_containers_async().await;
+            let containers = container_manager.list_or_warn().await;
+
+            if format == OutputFormatArg::Json {
+                let reports: Vec<InstanceReport> = vms
+                    .iter()
+                    .chain(&containers)
+                    .map(InstanceReport::new)
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+                return Ok(());
+            }
+
+            let mut table = Table::new(["NAME", "TYPE", "STATUS", "PID", "MEMORY"]);
+            for (instance, kind) in vms
+                .iter()
+                .map(|vm| (vm, "VM"))
+                .chain(containers.iter().map(|container| (container, "Container")))
+            {
+                table.row(vec![
+                    instance.name.as_str().into(),
+                    kind.into(),
+                    Cell::status(instance.status, status_symbols),
+                    instance
+                        .pid
+                        .map(|p| p.to_string())
+                        .unwrap_or("-".to_string())
+                        .into(),
+                    format!("{}MB", instance.memory_mb).into(),
+                ]);
+            }
+            table.print();
+
+            if vms.is_empty() && containers.is_empty() {
+                println!("No instances running");
+            }
+        }
+        Commands::Status {
+            instance_type,
+            name,
+            format,
+        } => match instance_type {
+            InstanceType::Vm => {
+                vm_manager.refresh_libvirt_states();
+                let autostart = vm_manager.autostart(&name);
+                let net_limits: Vec<_> = vm_manager
+                    .net_limits(&name)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|nic| !nic.limit.is_unlimited())
+                    .collect();
+                let vm = vm_manager.get_vm(&name);
+                let usage = match &vm {
+                    Some(_) => {
+                        let vm_name = name.clone();
+                        tokio::task::spawn_blocking(move || {
+                            performance_monitor::sample_domain_usage(
+                                &vm_name,
+                                VM_USAGE_SAMPLE_WINDOW,
+                            )
+                        })
+                        .await
+                        .ok()
+                        .flatten()
+                    }
+                    None => None,
+                };
+                match vm {
+                    Some(vm) if format == OutputFormatArg::Json => {
+                        let report = InstanceReport {
+                            autostart,
+                            usage,
+                            net_limits,
+                            ..InstanceReport::new(&vm)
+                        };
+                        println!("{}", serde_json::to_string_pretty(&report)?);
+                    }
+                    Some(vm) => {
+                        print_instance_status(&vm);
+                        match usage {
+                            Some(usage) => {
+                                println!("CPU Usage: {:.1}%", usage.cpu_percent);
+                                let in_use = usage.memory_kib / 1024;
+                                match usage.rss_kib {
+                                    Some(rss) => println!(
+                                        "Memory In Use: {}MB (host RSS {}MB)",
+                                        in_use,
+                                        rss / 1024
+                                    ),
+                                    None => println!("Memory In Use: {}MB", in_use),
+                                }
+                            }
+                            None => {
+                                println!("CPU Usage: n/a");
+                                println!("Memory In Use: n/a");
+                            }
+                        }
+                        if let Some(autostart) = autostart {
+                            println!("Autostart: {}", if autostart { "yes" } else { "no" });
+                        }
+                        for nic in &net_limits {
+                            println!("Bandwidth Limit ({}): {}", nic.mac, nic.limit);
+                        }
+                    }
+                    None if format == OutputFormatArg::Json => {
+                        return Err(NovaError::VmNotFound(name));
+                    }
+                    None => println!("VM '{}' not found", name),
+                }
+            }
+            InstanceType::Container => {
+                match container_manager.get()?.container_details(&name).await {
+                    Ok((container, restarts)) if format == OutputFormatArg::Json => {
+                        let report = InstanceReport {
+                            restarts: Some(&restarts),
+                            ..InstanceReport::new(&container)
+                        };
+                        println!("{}", serde_json::to_string_pretty(&report)?);
+                    }
+                    Ok((container, restarts)) => {
+                        print_instance_status(&container);
+                        if let Some(policy) = restarts.restart_policy {
+                            println!("Restart Policy: {}", policy.as_str());
+                        }
+                        println!("Restarts: {}", restarts.restart_count);
+                        if let Some(code) = restarts.exit_code {
+                            println!("Last Exit Code: {}", code);
+                        }
+                        if let Some(reason) = &restarts.exit_reason {
+                            println!("Last Exit Reason: {}", reason);
+                        }
+                        if restarts.crash_looping {
+                            println!(
+                                "⚠️  Crash looping: {} or more restarts within {}s (see [container_crash_loop])",
+                                config.container_crash_loop.max_restarts,
+                                config.container_crash_loop.window_seconds
+                            );
+                        }
+                    }
+                    Err(e) if format == OutputFormatArg::Json => return Err(e),
+                    Err(_) => println!("Container '{}' not found", name),
+                }
+            }
+        },
+        Commands::Inspect {
+            name,
+            resource_type,
+            json,
+        } => {
+            let requested = resource_type.map(ResourceKind::from);
+            let probe = |kind: ResourceKind| requested.is_none_or(|requested| requested == kind);
+            let mut found = Vec::new();
+
+            let mut vm_instance = None;
+            let mut vm_state = None;
+            if probe(ResourceKind::Vm) {
+                vm_instance = vm_manager.get_vm(&name);
+                vm_state = Command::new("virsh")
+                    .args(["domstate", &name])
+                    .output()
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+                if vm_instance.is_some() || vm_state.is_some() || config.vm.contains_key(&name) {
+                    found.push(ResourceKind::Vm);
+                }
+            }
 
-            println!(
-                "{:<20} {:<12} {:<12} {:<8} {:<12}",
-                "NAME", "TYPE", "STATUS", "PID", "MEMORY"
-            );
-            println!("{}", "=".repeat(70));
+            let mut container = None;
+            if probe(ResourceKind::Container) {
+                container = container_manager
+                    .list_or_warn()
+                    .await
+                    .into_iter()
+                    .find(|container| container.name == name);
+                if container.is_some() {
+                    found.push(ResourceKind::Container);
+                }
+            }
 
-            for vm in &vms {
-                println!(
-                    "{:<20} {:<12} {:<12} {:<8} {:<12}",
-                    vm.name,
-                    "VM",
-                    format!("{:?}", vm.status),
-                    vm.pid.map(|p| p.to_string()).unwrap_or("-".to_string()),
-                    format!("{}MB", vm.memory_mb)
-                );
+            let mut network_manager = NetworkManager::new();
+            if probe(ResourceKind::Switch) || probe(ResourceKind::Interface) {
+                if let Err(err) = network_manager.refresh_state().await {
+                    logger::debug!("Skipping network lookup for inspect: {}", err);
+                }
+                if probe(ResourceKind::Switch) && network_manager.get_switch(&name).is_some() {
+                    found.push(ResourceKind::Switch);
+                }
+                if probe(ResourceKind::Interface)
+                    && network_manager.resolve_interface(&name).is_some()
+                {
+                    found.push(ResourceKind::Interface);
+                }
             }
 
-            for container in &containers {
-                println!(
-                    "{:<20} {:<12} {:<12} {:<8} {:<12}",
-                    container.name,
-                    "Container",
-                    format!("{:?}", container.status),
-                    container
-                        .pid
-                        .map(|p| p.to_string())
-                        .unwrap_or("-".to_string()),
-                    format!("{}MB", container.memory_mb)
-                );
+            if probe(ResourceKind::StoragePool) && config.storage.contains_key(&name) {
+                found.push(ResourceKind::StoragePool);
             }
 
-            if vms.is_empty() && containers.is_empty() {
-                println!("No instances running");
+            let mut gpu_manager = GpuManager::new();
+            if probe(ResourceKind::Gpu) {
+                match gpu_manager.discover() {
+                    Ok(()) => {
+                        gpu_manager.refresh_device_status();
+                        if gpu_manager
+                            .list_gpus()
+                            .iter()
+                            .any(|gpu| inspect::matches_pci_address(&gpu.address, &name))
+                        {
+                            found.push(ResourceKind::Gpu);
+                        }
+                    }
+                    Err(err) => logger::debug!("Skipping GPU lookup for inspect: {}", err),
+                }
             }
-        }
-        Commands::Status {
-            instance_type,
-            name,
-        } => match instance_type {
-            InstanceType::Vm => {
-                if let Some(vm) = vm_manager.get_vm(&name) {
-                    println!("VM: {}", vm.name);
-                    println!("Status: {:?}", vm.status);
-                    println!("CPU Cores: {}", vm.cpu_cores);
-                    println!("Memory: {}MB", vm.memory_mb);
-                    println!("Created: {}", vm.created_at.format("%Y-%m-%d %H:%M:%S"));
-                    if let Some(pid) = vm.pid {
-                        println!("PID: {}", pid);
-                    }
-                    if let Some(network) = &vm.network {
-                        println!("Network: {}", network);
+
+            let kind = inspect::resolve(&name, &found, requested)?;
+            let report = match kind {
+                ResourceKind::Vm => InspectReport::Vm {
+                    name: &name,
+                    instance: vm_instance,
+                    libvirt_state: vm_state,
+                    config: config.get_vm(&name),
+                },
+                ResourceKind::Container => InspectReport::Container {
+                    instance: container.as_ref().expect("container was found"),
+                    config: config.get_container(&name),
+                },
+                ResourceKind::Switch => InspectReport::Switch {
+                    switch: network_manager.get_switch(&name).expect("switch was found"),
+                    connected_vms: network_manager.connected_vms(&name),
+                },
+                ResourceKind::Interface => {
+                    let interface = network_manager
+                        .resolve_interface(&name)
+                        .expect("interface was found");
+                    InspectReport::Interface {
+                        interface,
+                        persistent_names: network_manager.persistent_names(&interface.name),
                     }
-                } else {
-                    println!("VM '{}' not found", name);
                 }
+                ResourceKind::StoragePool => InspectReport::StoragePool {
+                    name: &name,
+                    pool: &config.storage[&name],
+                },
+                ResourceKind::Gpu => {
+                    let device = gpu_manager
+                        .list_gpus()
+                        .iter()
+                        .find(|gpu| inspect::matches_pci_address(&gpu.address, &name))
+                        .expect("GPU was found");
+                    InspectReport::Gpu {
+                        device,
+                        capabilities: gpu_manager.capabilities_for(&device.address),
+                    }
+                }
+            };
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                return Ok(());
             }
-            InstanceType::Container => {
-                if let Some(container) = container_manager.get_container(&name) {
-                    println!("Container: {}", container.name);
-                    println!("Status: {:?}", container.status);
-                    println!("Memory: {}MB", container.memory_mb);
-                    println!(
-                        "Created: {}",
-                        container.created_at.format("%Y-%m-%d %H:%M:%S")
-                    );
-                    if let Some(pid) = container.pid {
-                        println!("PID: {}", pid);
+
+            match report {
+                InspectReport::Vm {
+                    name,
+                    instance,
+                    libvirt_state,
+                    config,
+                } => {
+                    match &instance {
+                        Some(instance) => print_instance_status(instance),
+                        None => println!("VM: {}", name),
                     }
-                    if let Some(network) = &container.network {
-                        println!("Network: {}", network);
+                    if let Some(state) = libvirt_state {
+                        println!("Libvirt State: {}", state);
+                    }
+                    if let Some(vm_config) = config {
+                        if instance.is_none() {
+                            println!("CPU Cores: {}", vm_config.cpu);
+                            println!("Memory: {}", vm_config.memory);
+                        }
+                        println!("Defined In: {}", config_path.display());
                     }
-                } else {
-                    println!("Container '{}' not found", name);
                 }
+                InspectReport::Container { instance, .. } => print_instance_status(instance),
+                InspectReport::Switch {
+                    switch,
+                    connected_vms,
+                } => print_switch_details(switch, &connected_vms),
+                InspectReport::Interface {
+                    interface,
+                    persistent_names,
+                } => print_interface_details(interface, &persistent_names),
+                InspectReport::StoragePool { name, pool } => print_storage_pool(name, pool),
+                InspectReport::Gpu {
+                    device,
+                    capabilities,
+                } => print_gpu_info(device, capabilities),
             }
-        },
+        }
         Commands::Version => {
             println!("Nova v0.1.0 - Wayland-Native Virtualization & Container Manager");
             println!("Built with Rust (version not available in this build)");
@@ -1101,19 +3221,17 @@ System Capabilities:"
                 "
 Container Runtimes:"
             );
-            let runtime = container_manager.check_container_runtime();
+            let containers = ContainerManager::selected(container_runtime);
+            let runtime = containers.check_container_runtime();
             println!("  Primary Runtime: {:?}", runtime);
-            println!(
-                "  Bolt Available: {}",
-                container_manager.check_bolt_available()
-            );
+            println!("  Bolt Available: {}", containers.check_bolt_available());
             println!(
                 "  Docker Available: {}",
-                container_manager.check_docker_available()
+                containers.check_docker_available()
             );
             println!(
                 "  Podman Available: {}",
-                container_manager.check_podman_available()
+                containers.check_podman_available()
             );
 
             // Show template availability
@@ -1130,6 +3248,649 @@ Container Templates:"
             WizardCommands::Vm(args) => {
                 handle_vm_wizard(args, &config, &config_path)?;
             }
+            WizardCommands::Container(args) => {
+                handle_container_wizard(args, &config, &config_path)?;
+            }
+        },
+        Commands::Vm { vm_command } => match vm_command {
+            VmCommands::Delete {
+                vm,
+                remove_disks,
+                remove_snapshots,
+                force,
+                yes,
+            } => {
+                let clones = linked_clones_of(Path::new(TEMPLATES_DIR), &vm);
+                if remove_disks && !force && !clones.is_empty() {
+                    return Err(NovaError::ConfigError(format!(
+                        "VM '{}' is the source of linked clone(s) {}, which use its disks as \
+                         backing files. Delete them first, or pass --force",
+                        vm,
+                        clones.join(", ")
+                    )));
+                }
+                if !yes {
+                    let mut extra = Vec::new();
+                    if remove_disks {
+                        extra.push("its pool volumes");
+                    }
+                    if remove_snapshots {
+                        extra.push("its snapshots");
+                    }
+                    let question = if extra.is_empty() {
+                        format!("Delete VM '{}'?", vm)
+                    } else {
+                        format!("Delete VM '{}' and {}?", vm, extra.join(" and "))
+                    };
+                    if !confirm(&question)? {
+                        println!("Delete cancelled");
+                        return Ok(());
+                    }
+                }
+
+                let options = VmDeleteOptions {
+                    remove_disks,
+                    remove_snapshots,
+                    force,
+                };
+                let report = vm_manager.delete_vm(&vm, options).await?;
+                if remove_snapshots {
+                    SnapshotManager::new(PathBuf::from(TEMPLATES_DIR))?.forget_vm_snapshots(&vm)?;
+                }
+                forget_linked_clone(Path::new(TEMPLATES_DIR), &vm)?;
+
+                println!("✅ VM '{}' deleted", vm);
+                if report.nvram {
+                    println!("   Removed UEFI variable store");
+                }
+                if !report.snapshots.is_empty() {
+                    println!("   Removed snapshots: {}", report.snapshots.join(", "));
+                }
+                for volume in &report.removed_volumes {
+                    println!("   Removed volume {}", volume);
+                }
+                for (disk, reason) in &report.kept_disks {
+                    println!("   Kept {} ({})", disk, reason);
+                }
+                if !report.released_gpus.is_empty() {
+                    println!(
+                        "   Released GPU reservation: {}",
+                        report.released_gpus.join(", ")
+                    );
+                }
+            }
+            VmCommands::Dumpxml { vm, inactive } => {
+                print!("{}", vm_manager.dump_xml(&vm, inactive)?);
+            }
+            VmCommands::Edit { vm, rollback } => {
+                if rollback {
+                    let backup = vm_manager.rollback_xml(&vm).await?;
+                    println!("✅ VM '{}' restored from {}", vm, backup.display());
+                    return Ok(());
+                }
+
+                let original = vm_manager.dump_xml(&vm, true)?;
+                let file = tempfile::Builder::new()
+                    .prefix(&format!("nova-{}-", vm))
+                    .suffix(".xml")
+                    .tempfile()?;
+                std::fs::write(file.path(), &original)?;
+
+                loop {
+                    edit_file(file.path())?;
+                    let edited = std::fs::read_to_string(file.path())?;
+                    if edited == original {
+                        println!("No changes made to '{}'", vm);
+                        return Ok(());
+                    }
+                    match vm_manager.define_xml(&vm, file.path()).await {
+                        Ok(backup) => {
+                            println!("✅ VM '{}' redefined", vm);
+                            println!("   Previous XML saved to {}", backup.display());
+                            println!("   Undo with `nova vm edit {} --rollback`", vm);
+                            println!("   A running VM picks up the change on its next cold boot");
+                            return Ok(());
+                        }
+                        Err(err) => {
+                            println!("❌ {}", err);
+                            if !confirm("Edit again?")? {
+                                println!("Edit discarded; '{}' is unchanged", vm);
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+            VmCommands::Disk { disk_command } => match disk_command {
+                VmDiskCommands::AttachBlock {
+                    vm,
+                    device,
+                    target,
+                    live,
+                    persistent,
+                    readonly,
+                    shareable,
+                    cache,
+                } => {
+                    let access = DiskAccess {
+                        readonly,
+                        shareable,
+                        cache: cache.into(),
+                    };
+                    let mode = match (live, persistent) {
+                        (true, false) => DiskAttachMode::Live,
+                        (false, true) => DiskAttachMode::Persistent,
+                        (true, true) => DiskAttachMode::LiveAndPersistent,
+                        (false, false) => {
+                            let running = Command::new("virsh")
+                                .args(["domstate", &vm])
+                                .output()
+                                .map(|output| {
+                                    String::from_utf8_lossy(&output.stdout).trim() == "running"
+                                })
+                                .unwrap_or(false);
+                            if running {
+                                DiskAttachMode::LiveAndPersistent
+                            } else {
+                                DiskAttachMode::Persistent
+                            }
+                        }
+                    };
+
+                    let target = vm_manager
+                        .attach_block_device(&vm, &device, target.as_deref(), mode, access)
+                        .await?;
+                    println!(
+                        "✅ Attached {} to '{}' as {} ({:?})",
+                        device.display(),
+                        vm,
+                        target,
+                        mode
+                    );
+                    if let Some(warning) = access.validate()? {
+                        println!("⚠️  {} is {}", device.display(), warning);
+                    } else if !shareable {
+                        println!(
+                            "⚠️  '{}' now has exclusive access to {}; do not mount it on the host while attached",
+                            vm,
+                            device.display()
+                        );
+                    }
+                }
+            },
+            VmCommands::Devices {
+                vm,
+                disk_bus,
+                net_model,
+                video,
+            } => {
+                let changes = DeviceChanges {
+                    disk_bus: disk_bus.map(Into::into),
+                    net_model: net_model.map(Into::into),
+                    video: video.map(Into::into),
+                };
+                vm_manager.update_devices(&vm, &changes).await?;
+                println!("✅ Updated devices for '{}'", vm);
+                if changes.disk_bus == Some(DiskBus::Virtio) {
+                    println!(
+                        "   The guest needs virtio drivers (virtio-win on Windows) to boot from a virtio disk"
+                    );
+                }
+            }
+            VmCommands::MemAdd { vm, size } => {
+                let layout = vm_manager.hotplug_memory(&vm, size).await?;
+                println!("✅ Added {} MiB DIMM to '{}'", size, vm);
+                print_memory_layout(&layout);
+            }
+            VmCommands::MemRemove { vm, size } => {
+                let layout = vm_manager.unplug_memory(&vm, size).await?;
+                println!("✅ Removed {} MiB DIMM from '{}'", size, vm);
+                print_memory_layout(&layout);
+            }
+            VmCommands::SetMemory { vm, size } => {
+                let size_kib = parse_size(&size)? / 1024;
+                let plan = vm_manager.set_memory(&vm, size_kib).await?;
+                if plan.clamped {
+                    println!(
+                        "⚠️  '{}' can balloon up to {} MiB while running; shut it down to go higher",
+                        vm,
+                        plan.target_kib / 1024
+                    );
+                }
+                println!("✅ '{}' memory set to {} MiB", vm, plan.target_kib / 1024);
+            }
+            VmCommands::SetHugepages { vm, size, off } => {
+                let size = (!off).then(|| HugePageSize::from(size));
+                vm_manager.set_hugepages(&vm, size).await?;
+
+                if config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        vm_config.hugepages = size;
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                match size {
+                    Some(size) => println!("✅ '{}' memory is backed by {} hugepages", vm, size),
+                    None => println!("✅ '{}' memory uses normal pages", vm),
+                }
+            }
+            VmCommands::AttachDisk {
+                vm,
+                pool,
+                volume,
+                bus,
+                readonly,
+                shareable,
+                cache,
+            } => {
+                let access = DiskAccess {
+                    readonly,
+                    shareable,
+                    cache: cache.into(),
+                };
+                let target = vm_manager
+                    .attach_volume(&vm, &pool, &volume, bus.into(), access)
+                    .await?;
+                println!("✅ Attached {}/{} to '{}' as {}", pool, volume, vm, target);
+                if let Some(warning) = access.validate()? {
+                    println!("⚠️  {}/{} is {}", pool, volume, warning);
+                }
+            }
+            VmCommands::DetachDisk { vm, pool, volume } => {
+                let target = vm_manager.detach_volume(&vm, &pool, &volume).await?;
+                println!("✅ Detached {}/{} ({}) from '{}'", pool, volume, target, vm);
+            }
+            VmCommands::SetVcpus { vm, count } => {
+                let (old, new) = vm_manager.set_vcpus(&vm, count).await?;
+                println!("✅ '{}' vCPUs: {} -> {}", vm, old, new);
+            }
+            VmCommands::SetNetlimit {
+                vm,
+                inbound,
+                outbound,
+                unlimited,
+                interface,
+            } => {
+                if !unlimited && inbound.is_none() && outbound.is_none() {
+                    return Err(NovaError::ConfigError(
+                        "Pass --inbound and/or --outbound, or --unlimited to clear the limit"
+                            .to_string(),
+                    ));
+                }
+                let change = if unlimited {
+                    NetLimitChange::unlimited()
+                } else {
+                    NetLimitChange {
+                        inbound_mib: inbound,
+                        outbound_mib: outbound,
+                    }
+                };
+                let changed = vm_manager
+                    .set_net_limit(&vm, &change, interface.as_deref())
+                    .await?;
+
+                // A single-NIC override can't be expressed by the VM-wide setting
+                if interface.is_none() && config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        let limit = change.apply(&vm_config.net_limit.unwrap_or_default());
+                        vm_config.net_limit = (!limit.is_unlimited()).then_some(limit);
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                if changed.iter().all(|iface| iface.limit.is_unlimited()) {
+                    println!("✅ Removed bandwidth limits from '{}'", vm);
+                } else {
+                    println!("✅ Updated bandwidth of '{}'", vm);
+                }
+                for iface in &changed {
+                    println!("   {}: {}", iface.mac, iface.limit);
+                }
+            }
+            VmCommands::AddNic {
+                vm,
+                network,
+                model,
+                mac,
+            } => {
+                let nic = VmNic {
+                    network,
+                    model: model.map(NetModel::from),
+                    mac,
+                };
+                let limit = config.get_vm(&vm).and_then(|vm| vm.net_limit.as_ref());
+                let mac = vm_manager
+                    .add_nic(&vm, &nic, config.device_defaults.net_model, limit)
+                    .await?;
+
+                if config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        vm_config.network.push(VmNic {
+                            mac: (!mac.is_empty()).then(|| mac.clone()),
+                            ..nic.clone()
+                        });
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                println!("✅ Added NIC {} on {} to '{}'", mac, nic.network, vm);
+            }
+            VmCommands::RemoveNic { vm, interface } => {
+                let (mac, source) = vm_manager.remove_nic(&vm, &interface).await?;
+
+                if config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        // NICs without a configured MAC are matched by network
+                        let index = vm_config
+                            .network
+                            .iter()
+                            .position(|nic| {
+                                nic.mac
+                                    .as_deref()
+                                    .is_some_and(|nic_mac| nic_mac.eq_ignore_ascii_case(&mac))
+                            })
+                            .or_else(|| {
+                                vm_config
+                                    .network
+                                    .iter()
+                                    .position(|nic| nic.mac.is_none() && nic.network == source)
+                            });
+                        if let Some(index) = index {
+                            vm_config.network.remove(index);
+                        }
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                println!("✅ Removed NIC {} ({}) from '{}'", mac, source, vm);
+            }
+            VmCommands::Autostart { vm, disable } => {
+                vm_manager.set_autostart(&vm, !disable).await?;
+                if disable {
+                    println!("✅ '{}' will no longer start when the host boots", vm);
+                } else {
+                    println!("✅ '{}' will start when the host boots", vm);
+                }
+            }
+            VmCommands::Pin { vm, pins } => {
+                if pins.is_empty() {
+                    let pins = vm_manager.cpu_pinning(&vm)?;
+                    if pins.is_empty() {
+                        println!("VM '{}' has no pinned vCPUs", vm);
+                    }
+                    for pin in &pins {
+                        println!("vCPU {} -> host CPUs {}", pin.vcpu, pin.cpuset);
+                    }
+                    return Ok(());
+                }
+
+                let pins = pins
+                    .iter()
+                    .map(|pin| pin.parse())
+                    .collect::<Result<Vec<VcpuPin>>>()?;
+                let live = vm_manager.set_cpu_pinning(&vm, &pins).await?;
+
+                if config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        for pin in &pins {
+                            vm_config
+                                .cpuset
+                                .retain(|existing| existing.vcpu != pin.vcpu);
+                            vm_config.cpuset.push(pin.clone());
+                        }
+                        vm_config.cpuset.sort_by_key(|pin| pin.vcpu);
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                for pin in &pins {
+                    println!("✅ '{}' vCPU {} -> host CPUs {}", vm, pin.vcpu, pin.cpuset);
+                }
+                if !live {
+                    println!("Takes effect when '{}' starts", vm);
+                }
+            }
+            VmCommands::Topology {
+                vm,
+                sockets,
+                cores,
+                threads,
+            } => {
+                let (vcpus, current) = vm_manager.cpu_topology(&vm)?;
+                if sockets.is_none() && cores.is_none() && threads.is_none() {
+                    println!("VM '{}': {} vCPUs as {}", vm, vcpus, current);
+                    return Ok(());
+                }
+
+                let sockets = sockets.unwrap_or(1);
+                let threads = threads.unwrap_or(1);
+                let cores = cores.unwrap_or_else(|| vcpus / (sockets * threads).max(1));
+                let topology = CpuTopology {
+                    sockets,
+                    cores,
+                    threads,
+                };
+                vm_manager.set_cpu_topology(&vm, &topology).await?;
+
+                if config.get_vm(&vm).is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        vm_config.cpu_topology = Some(topology);
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+                println!("✅ '{}' now has {} vCPUs as {}", vm, vcpus, topology);
+            }
+            VmCommands::QemuArg { qemu_arg_command } => {
+                let (vm, added) = match qemu_arg_command {
+                    VmQemuArgCommands::List { vm } => {
+                        let args = match vm_manager.qemu_args(&vm) {
+                            Ok(args) => args,
+                            Err(_) => config
+                                .get_vm(&vm)
+                                .map(|vm_config| vm_config.extra_qemu_args.clone())
+                                .ok_or_else(|| NovaError::VmNotFound(vm.clone()))?,
+                        };
+                        if args.is_empty() {
+                            println!("VM '{}' has no raw QEMU arguments", vm);
+                        } else {
+                            println!("Raw QEMU arguments for '{}':", vm);
+                            for arg in &args {
+                                println!("  {}", arg);
+                            }
+                        }
+                        return Ok(());
+                    }
+                    VmQemuArgCommands::Add { vm, args } => {
+                        vm_qemu_args::validate_args(&args)?;
+                        (vm, args)
+                    }
+                    VmQemuArgCommands::Clear { vm } => (vm, Vec::new()),
+                };
+
+                // The libvirt definition wins over the NovaFile when both exist
+                let defined = vm_manager.qemu_args(&vm).ok();
+                let configured = config
+                    .get_vm(&vm)
+                    .map(|vm_config| vm_config.extra_qemu_args.clone());
+                let mut args = match (&defined, &configured) {
+                    (Some(args), _) | (None, Some(args)) => args.clone(),
+                    (None, None) => return Err(NovaError::VmNotFound(vm)),
+                };
+                if added.is_empty() {
+                    args.clear();
+                } else {
+                    args.extend(added);
+                }
+
+                if defined.is_some() {
+                    vm_manager.set_qemu_args(&vm, &args).await?;
+                }
+                if configured.is_some() {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if let Some(vm_config) = file_config.vm.get_mut(&vm) {
+                        vm_config.extra_qemu_args = args.clone();
+                    }
+                    file_config.save_to_file(&config_path)?;
+                }
+
+                if args.is_empty() {
+                    println!("✅ Cleared raw QEMU arguments from '{}'", vm);
+                } else {
+                    println!(
+                        "✅ '{}' now passes {} raw QEMU argument(s): {}",
+                        vm,
+                        args.len(),
+                        args.join(" ")
+                    );
+                    println!("⚠️  {}", vm_qemu_args::UNSUPPORTED_WARNING);
+                    if defined.is_some() {
+                        println!("   A running VM picks them up on its next cold boot");
+                    }
+                }
+            }
+        },
+        Commands::Container { container_command } => match container_command {
+            ContainerCommands::Advise {
+                name,
+                window,
+                interval,
+            } => {
+                let mut thresholds = config.container_scaling.clone();
+                if let Some(window) = window {
+                    thresholds.sustained_seconds = window;
+                }
+                container_manager
+                    .get_mut()?
+                    .set_scaling_thresholds(thresholds.clone());
+
+                let interval = Duration::from_secs(interval.max(1));
+                let window = Duration::from_secs(thresholds.sustained_seconds);
+                println!(
+                    "Sampling '{}' every {}s for {}s (advisory only)...",
+                    name,
+                    interval.as_secs(),
+                    window.as_secs()
+                );
+
+                // Sample until the recorded history itself spans the window;
+                // wall-clock time also counts the first stats call, which the
+                // first sample's timestamp does not
+                let advice = loop {
+                    container_manager.get()?.container_stats(&name).await?;
+                    let advice = container_manager
+                        .get()?
+                        .scaling_hint(&name, config.get_container(&name));
+                    if advice.has_enough_data()
+                        || advice.samples >= container_scaling::HISTORY_CAPACITY
+                    {
+                        break advice;
+                    }
+                    tokio::time::sleep(interval).await;
+                };
+                if !advice.has_enough_data() {
+                    println!(
+                        "⚠️  Insufficient data for '{}': {} samples over {}s, the window is {}s",
+                        name,
+                        advice.samples,
+                        advice.observed.num_seconds(),
+                        advice.window.num_seconds()
+                    );
+                } else if advice.hints.is_empty() {
+                    println!(
+                        "✅ '{}' is within thresholds ({} samples over {}s)",
+                        name,
+                        advice.samples,
+                        advice.observed.num_seconds()
+                    );
+                } else {
+                    for hint in &advice.hints {
+                        println!("💡 {}", hint);
+                    }
+                }
+            }
+            ContainerCommands::RemapPorts { name, ports } => {
+                let remap = container_manager.get()?.remap_ports(&name, &ports).await?;
+                println!(
+                    "✅ Container '{}' now publishes {}",
+                    remap.container,
+                    ports.join(", ")
+                );
+                println!("   ID changed: {} -> {}", remap.old_id, remap.new_id);
+                println!("⚠️  Data in anonymous volumes was not carried over");
+            }
+            ContainerCommands::Pull { image } => {
+                let mut progress = reporter("pull");
+                progress.stage(format!(
+                    "Pulling {} with {}",
+                    image,
+                    container_manager.get()?.check_container_runtime()
+                ));
+                let result = container_manager.get()?.pull_image(&image).await;
+                progress.conclude(&result);
+                result?;
+                println!("✅ Image '{}' pulled", image);
+            }
+            ContainerCommands::Df => {
+                let usage = container_manager.get()?.disk_usage().await?;
+                let mut table = Table::new(["TYPE", "TOTAL", "ACTIVE", "SIZE", "RECLAIMABLE"]);
+                for row in &usage {
+                    let reclaimable = if row.reclaimable_bytes > 0 {
+                        Cell::new(format_bytes(row.reclaimable_bytes), Tone::Warning)
+                    } else {
+                        Cell::new(format_bytes(0), Tone::Muted)
+                    };
+                    table.row(vec![
+                        row.kind.as_str().into(),
+                        row.total.to_string().into(),
+                        row.active.to_string().into(),
+                        format_bytes(row.size_bytes).into(),
+                        reclaimable,
+                    ]);
+                }
+                table.print();
+                let reclaimable: u64 = usage.iter().map(|row| row.reclaimable_bytes).sum();
+                if reclaimable > 0 {
+                    println!(
+                        "\n{} reclaimable; free it with `nova container prune`",
+                        format_bytes(reclaimable)
+                    );
+                }
+            }
+            ContainerCommands::Prune {
+                images,
+                volumes,
+                yes,
+            } => {
+                if !yes {
+                    let mut removes = vec!["stopped containers", "unused networks"];
+                    removes.push(if images {
+                        "all unused images"
+                    } else {
+                        "dangling images"
+                    });
+                    if volumes {
+                        removes.push("unused volumes");
+                    }
+                    removes.push("build cache");
+                    let question = format!(
+                        "Remove {} with {}?",
+                        removes.join(", "),
+                        container_manager.get()?.check_container_runtime()
+                    );
+                    if !confirm(&question)? {
+                        println!("Prune cancelled");
+                        return Ok(());
+                    }
+                }
+
+                let reclaimed = container_manager
+                    .get()?
+                    .prune(PruneOptions { images, volumes })
+                    .await?;
+                println!("✅ Reclaimed {}", format_bytes(reclaimed));
+            }
         },
         Commands::Template { template_command } => match template_command {
             TemplateCommands::List { category: _ } => {
@@ -1207,8 +3968,35 @@ Volumes:"
                 template,
                 project,
                 output,
+                verify,
             } => match template_manager.deploy_template(&template, &project) {
                 Ok(nova_file_content) => {
+                    if verify && let Some(details) = template_manager.get_template(&template) {
+                        println!(
+                            "🔍 Checking images with {}...",
+                            container_manager.get()?.get_runtime_name()
+                        );
+                        let mut unreachable = 0;
+                        for container in &details.containers {
+                            match container_manager.get()?.check_image(&container.image).await {
+                                Ok(()) => println!("  ✅ {}", container.image),
+                                Err(e) => {
+                                    unreachable += 1;
+                                    println!(
+                                        "  ❌ {} ({}): {}",
+                                        container.image, container.name, e
+                                    );
+                                }
+                            }
+                        }
+                        if unreachable > 0 {
+                            println!(
+                                "❌ {} image(s) unreachable; NovaFile not written",
+                                unreachable
+                            );
+                            std::process::exit(1);
+                        }
+                    }
                     std::fs::write(&output, nova_file_content)?;
                     println!("✅ Template '{}' deployed successfully!", template);
                     println!("📄 NovaFile written to: {}", output);
@@ -1218,6 +4006,45 @@ Volumes:"
                     println!("❌ Failed to deploy template: {}", e);
                 }
             },
+            TemplateCommands::CreateFrom {
+                vm,
+                name,
+                description,
+                tags,
+                sysprep,
+            } => {
+                let mut snapshot_manager = SnapshotManager::new(PathBuf::from(TEMPLATES_DIR))?;
+                println!("📦 Capturing VM '{}' as template '{}'...", vm, name);
+                let mut progress = reporter("template");
+                let template_id = snapshot_manager
+                    .create_template_from_vm(&vm, &name, &description, tags, sysprep, &mut progress)
+                    .await;
+                progress.conclude(&template_id);
+                let template_id = template_id?;
+                let Some(template) = snapshot_manager.get_template(&template_id) else {
+                    return Ok(());
+                };
+                println!("✅ Template '{}' created with ID: {}", name, template_id);
+                println!("   Disk: {}", template.disk_path.display());
+                println!("   OS: {:?}", template.os_type);
+                if sysprep {
+                    println!("   Generalized with virt-sysprep");
+                }
+
+                // List the template in the GUI's Quick Start section
+                if config_found {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    file_config
+                        .templates
+                        .custom
+                        .insert(name.clone(), template.quick_start());
+                    file_config.save_to_file(&config_path)?;
+                    println!(
+                        "   Added to [templates.custom] in {}",
+                        config_path.display()
+                    );
+                }
+            }
         },
         Commands::Network { network_command } => match network_command {
             NetworkCommands::List => {
@@ -1267,10 +4094,10 @@ Volumes:"
                 }
 
                 println!(
-                    "\n{:<16} {:<8} {:<12} {:<18} MAC",
-                    "INTERFACE", "STATE", "BRIDGE", "IP"
+                    "\n{:<16} {:<8} {:<12} {:<18} {:<18} ALIAS",
+                    "INTERFACE", "STATE", "BRIDGE", "IP", "MAC"
                 );
-                println!("{}", "-".repeat(80));
+                println!("{}", "-".repeat(90));
 
                 let mut interfaces = network_manager.list_interfaces();
                 interfaces.sort_by(|a, b| a.name.cmp(&b.name));
@@ -1292,50 +4119,72 @@ Volumes:"
                             .map(|ip| ip.to_string())
                             .unwrap_or_else(|| "-".to_string());
 
+                        let alias = iface.alias.as_deref().unwrap_or("-");
+
                         println!(
-                            "{:<16} {:<8} {:<12} {:<18} {}",
-                            iface.name, state, bridge, ip, iface.mac_address
+                            "{:<16} {:<8} {:<12} {:<18} {:<18} {}",
+                            iface.name, state, bridge, ip, iface.mac_address, alias
                         );
                     }
                 }
             }
-            NetworkCommands::Inspect { name } => {
+            NetworkCommands::Inspect { name, watch } => {
                 let mut network_manager = NetworkManager::new();
                 network_manager.refresh_state().await?;
 
-                if let Some(switch) = network_manager.get_switch(&name) {
-                    println!("Bridge: {}", switch.name);
-                    println!("  Type: {:?}", switch.switch_type);
-                    println!("  Status: {:?}", switch.status);
-                    println!("  Origin: {:?}", switch.origin);
-                    println!("  STP Enabled: {}", switch.stp_enabled);
-                    match &switch.profile {
-                        Some(profile) => println!("  Profile: {:?}", profile),
-                        None => println!("  Profile: -"),
-                    }
-                    println!(
-                        "  Interfaces: {}",
-                        if switch.interfaces.is_empty() {
-                            "-".to_string()
-                        } else {
-                            switch.interfaces.join(", ")
+                let connected_vms = network_manager.connected_vms(&name);
+                let interfaces: Vec<String> =
+                    if let Some(switch) = network_manager.get_switch(&name) {
+                        print_switch_details(switch, &connected_vms);
+                        if switch.switch_type == SwitchType::OpenVSwitch {
+                            print_ovs_details(&network_manager, &name);
                         }
+                        std::iter::once(switch.name.clone())
+                            .chain(switch.interfaces.iter().cloned())
+                            .collect()
+                    } else if let Some(iface) = network_manager.resolve_interface(&name) {
+                        let persistent = network_manager.persistent_names(&iface.name);
+                        print_interface_details(iface, &persistent);
+                        vec![iface.name.clone()]
+                    } else {
+                        println!("Network object '{}' not found", name);
+                        return Ok(());
+                    };
+
+                use std::io::IsTerminal;
+                let monitor = nova::NetworkMonitor::new();
+                let window = Duration::from_secs(1);
+                if !watch {
+                    let usage = monitor.sample_bandwidth(&interfaces, window).await?;
+                    println!("\nTraffic (1s sample):");
+                    print!(
+                        "{}",
+                        network_dashboard::render_traffic(&usage, &connected_vms)
                     );
-                } else if let Some(iface) = network_manager.get_interface(&name) {
-                    println!("Interface: {}", iface.name);
-                    println!("  State: {:?}", iface.state);
-                    println!("  MAC: {}", iface.mac_address);
-                    if let Some(ip) = iface.ip_address {
-                        println!("  IPv4: {}", ip);
-                    }
-                    if let Some(bridge) = &iface.bridge {
-                        println!("  Attached Bridge: {}", bridge);
+                    return Ok(());
+                }
+
+                let clear = std::io::stdout().is_terminal();
+                loop {
+                    let usage = tokio::select! {
+                        usage = monitor.sample_bandwidth(&interfaces, window) => usage?,
+                        _ = tokio::signal::ctrl_c() => break,
+                    };
+                    if clear {
+                        print!("\x1b[2J\x1b[H");
                     }
-                    if let Some(speed) = iface.speed {
-                        println!("  Speed: {} Mbps", speed);
+                    println!(
+                        "Traffic on {} at {} (every 1s, Ctrl+C to stop)",
+                        name,
+                        chrono::Local::now().format("%H:%M:%S")
+                    );
+                    print!(
+                        "{}",
+                        network_dashboard::render_traffic(&usage, &connected_vms)
+                    );
+                    if !clear {
+                        println!();
                     }
-                } else {
-                    println!("Network object '{}' not found", name);
                 }
             }
             NetworkCommands::Create {
@@ -1347,7 +4196,18 @@ Volumes:"
                 dhcp_range,
                 attach_interfaces,
                 stp,
+                vlan,
+                mtu,
+                move_ip,
+                force,
             } => {
+                if let Some(mtu) = mtu {
+                    network::validate_mtu(mtu)?;
+                }
+                let route_devices: Vec<String> = network::default_routes()
+                    .into_iter()
+                    .map(|route| route.device)
+                    .collect();
                 let mut network_manager = NetworkManager::new();
                 let switch_type: SwitchType = switch_type.into();
                 let profile_config = if let Some(profile_arg) = profile {
@@ -1361,35 +4221,56 @@ Volumes:"
                 };
                 let profile_clone = profile_config.clone();
                 network_manager
-                    .create_virtual_switch(&name, switch_type.clone(), profile_config)
+                    .create_virtual_switch(
+                        &name,
+                        switch_type.clone(),
+                        profile_config,
+                        vlan,
+                        force || move_ip,
+                    )
                     .await?;
 
-                if stp {
-                    let config = BridgeConfig {
-                        name: name.clone(),
-                        stp: true,
-                        forward_delay: 15,
-                        hello_time: 2,
-                        max_age: 20,
-                        aging_time: 300,
-                        multicast_snooping: true,
-                    };
-                    network_manager.configure_bridge(&config).await?;
-                }
-
                 let uplink_to_skip = profile_clone.as_ref().and_then(|profile| match profile {
                     SwitchProfile::External { uplink } => Some(uplink.clone()),
                     SwitchProfile::Internal | SwitchProfile::Nat { .. } => None,
                 });
 
-                for iface in attach_interfaces {
+                for iface in &attach_interfaces {
                     if uplink_to_skip.as_deref() == Some(iface.as_str()) {
                         continue;
                     }
                     network_manager
-                        .add_interface_to_switch(&name, &iface)
+                        .add_interface_to_switch(&name, iface, force || move_ip)
                         .await?;
                 }
+                if move_ip {
+                    for iface in uplink_to_skip.iter().chain(&attach_interfaces) {
+                        if route_devices.contains(iface) {
+                            let moved = network::move_addresses_to_bridge(iface, &name)?;
+                            println!(
+                                "Moved {} and the default route from '{}' to '{}'",
+                                moved.join(", "),
+                                iface,
+                                name
+                            );
+                        }
+                    }
+                }
+
+                // After attaching, so the MTU reaches every member
+                if stp || mtu.is_some() {
+                    let config = BridgeConfig {
+                        name: name.clone(),
+                        stp,
+                        forward_delay: 15,
+                        hello_time: 2,
+                        max_age: 20,
+                        aging_time: 300,
+                        multicast_snooping: true,
+                        mtu,
+                    };
+                    network_manager.configure_bridge(&config).await?;
+                }
 
                 if let Some(profile) = profile_clone {
                     println!(
@@ -1399,23 +4280,46 @@ Volumes:"
                 } else {
                     println!("Bridge '{}' ({:?}) created successfully", name, switch_type);
                 }
+                if let Some(vid) = vlan {
+                    println!("Ports join VLAN {} untagged", vid);
+                }
+                if let Some(mtu) = mtu {
+                    println!("MTU set to {}", mtu);
+                }
             }
             NetworkCommands::Delete { name } => {
                 let mut network_manager = NetworkManager::new();
                 network_manager.delete_virtual_switch(&name).await?;
                 println!("Bridge '{}' deleted", name);
             }
-            NetworkCommands::Attach { switch, interface } => {
+            NetworkCommands::Attach {
+                switch,
+                interface,
+                move_ip,
+                force,
+            } => {
                 let mut network_manager = NetworkManager::new();
                 network_manager.refresh_state().await?;
 
                 if !network_manager.switch_exists(&switch) {
                     println!("Bridge '{}' not found", switch);
                 } else {
+                    let carries_route = network::default_routes()
+                        .iter()
+                        .any(|route| route.device == interface);
                     network_manager
-                        .add_interface_to_switch(&switch, &interface)
+                        .add_interface_to_switch(&switch, &interface, force || move_ip)
                         .await?;
                     println!("Attached interface '{}' to '{}'", interface, switch);
+                    if move_ip && carries_route {
+                        let moved = network::move_addresses_to_bridge(&interface, &switch)?;
+                        println!(
+                            "Moved {} and the default route from '{}' to '{}'",
+                            moved.join(", "),
+                            interface,
+                            switch
+                        );
+                    }
                 }
             }
             NetworkCommands::Detach { switch, interface } => {
@@ -1431,6 +4335,150 @@ Volumes:"
                     println!("Detached interface '{}' from '{}'", interface, switch);
                 }
             }
+            NetworkCommands::Trunk {
+                switch,
+                interface,
+                vids,
+            } => {
+                let vids = network::parse_vlan_ids(&vids)?;
+                let mut network_manager = NetworkManager::new();
+                network_manager.refresh_state().await?;
+                network_manager
+                    .set_trunk(&switch, &interface, &vids)
+                    .await?;
+                println!(
+                    "✅ '{}' on '{}' now trunks VLANs {}",
+                    interface,
+                    switch,
+                    network::format_vlan_ranges(&vids)
+                );
+            }
+            NetworkCommands::Alias {
+                interface,
+                alias,
+                clear,
+            } => {
+                let mut network_manager = NetworkManager::new();
+                network_manager.refresh_state().await?;
+
+                if clear {
+                    network_manager.clear_interface_alias(&interface).await?;
+                    println!("✅ Cleared alias on '{}'", interface);
+                } else if let Some(alias) = alias {
+                    let persistence = network_manager
+                        .set_interface_alias(&interface, &alias)
+                        .await?;
+                    println!("✅ Set alias '{}' on '{}'", alias, interface);
+                    println!("   Persisted to {}", persistence.path().display());
+                }
+            }
+            NetworkCommands::Topology { format } => {
+                let topology = nova::NetworkMonitor::new().discover_topology().await?;
+                match format {
+                    TopologyFormatArg::Dot => print!("{}", topology.to_dot()),
+                    TopologyFormatArg::Json => {
+                        println!("{}", serde_json::to_string_pretty(&topology)?)
+                    }
+                    TopologyFormatArg::Table => {
+                        if topology.bridges.is_empty() && topology.vms.is_empty() {
+                            println!("No bridges or running VMs found");
+                        }
+                        for bridge in &topology.bridges {
+                            println!(
+                                "{} ({}, {})",
+                                bridge.name,
+                                bridge.bridge_type,
+                                bridge.ip_address.as_deref().unwrap_or("no address")
+                            );
+                            for iface in &bridge.interfaces {
+                                match topology.vms.iter().find(|vm| vm.interfaces.contains(iface)) {
+                                    Some(vm) => println!("  └─ {} -> VM {}", iface, vm.name),
+                                    None => println!("  └─ {}", iface),
+                                }
+                            }
+                        }
+                        let unbridged: Vec<_> = topology
+                            .vms
+                            .iter()
+                            .flat_map(|vm| vm.interfaces.iter().map(move |iface| (iface, vm)))
+                            .filter(|(iface, _)| {
+                                !topology
+                                    .bridges
+                                    .iter()
+                                    .any(|bridge| bridge.interfaces.contains(iface))
+                            })
+                            .collect();
+                        for (iface, vm) in unbridged {
+                            println!("{} -> VM {} (not on a bridge)", iface, vm.name);
+                        }
+                    }
+                }
+            }
+            NetworkCommands::Leases { bridge, format } => {
+                let mut network_manager = NetworkManager::new();
+                network_manager.refresh_state().await?;
+                let leases = network_manager.dhcp_leases(&bridge)?;
+                if format == OutputFormatArg::Json {
+                    println!("{}", serde_json::to_string_pretty(&leases)?);
+                } else if leases.is_empty() {
+                    println!("No DHCP leases on {}", bridge);
+                } else {
+                    let mut table = Table::new(["MAC", "IP", "HOSTNAME", "EXPIRES", "VM"]);
+                    for lease in &leases {
+                        let expires = match lease.expires {
+                            Some(at) => at
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M")
+                                .to_string(),
+                            None => "never".to_string(),
+                        };
+                        table.row(vec![
+                            lease.mac.as_str().into(),
+                            lease.ip.to_string().into(),
+                            lease.hostname.as_deref().unwrap_or("-").into(),
+                            expires.into(),
+                            match &lease.vm {
+                                Some(vm) => Cell::new(vm, Tone::Good),
+                                None => Cell::new("-", Tone::Muted),
+                            },
+                        ]);
+                    }
+                    table.print();
+                }
+            }
+            NetworkCommands::Monitor { interval } => {
+                network_dashboard::run(Duration::from_secs(interval.max(1))).await?;
+            }
+            NetworkCommands::Ovs { command } => match command {
+                OvsCommands::Flows { bridge, table } => {
+                    let mut network_manager = NetworkManager::new();
+                    network_manager.refresh_state().await?;
+                    let flows: Vec<_> = network_manager
+                        .ovs_flows(&bridge)?
+                        .into_iter()
+                        .filter(|flow| table.is_none_or(|table| flow.table == table))
+                        .collect();
+                    if flows.is_empty() {
+                        println!("No flows on {}", bridge);
+                    } else {
+                        println!(
+                            "{:<6} {:<9} {:>10} {:>10}  {:<40} ACTIONS",
+                            "TABLE", "PRIORITY", "PACKETS", "BYTES", "MATCH"
+                        );
+                        for flow in &flows {
+                            println!(
+                                "{:<6} {:<9} {:>10} {:>10}  {:<40} {}",
+                                flow.table,
+                                flow.priority,
+                                flow.n_packets,
+                                format_bytes(flow.n_bytes),
+                                flow.matches,
+                                flow.actions
+                            );
+                        }
+                    }
+                }
+            },
             NetworkCommands::Libvirt { command } => {
                 let mut libvirt_manager = LibvirtManager::new();
                 match command {
@@ -1512,22 +4560,67 @@ Volumes:"
                     std::process::exit(1);
                 }
             }
-            GpuCommands::List => {
+            GpuCommands::List { json, by_group } => {
                 let mut gpu_manager = GpuManager::new();
                 gpu_manager.discover()?;
                 gpu_manager.refresh_device_status();
 
                 let gpus = gpu_manager.list_gpus();
+                let reservations_map = gpu_manager.get_reservations().clone();
+
+                if json {
+                    let payload = if by_group {
+                        serde_json::to_string_pretty(&build_gpu_group_entries(
+                            gpus,
+                            gpu_manager.list_iommu_groups(),
+                            &reservations_map,
+                        ))?
+                    } else {
+                        serde_json::to_string_pretty(&build_gpu_list_entries(
+                            gpus,
+                            &reservations_map,
+                        ))?
+                    };
+                    println!("{}", payload);
+                    return Ok(());
+                }
 
                 if gpus.is_empty() {
                     println!("No GPUs detected");
                     return Ok(());
                 }
 
-                let reservations_map = gpu_manager.get_reservations().clone();
+                if by_group {
+                    let groups = build_gpu_group_entries(
+                        gpus,
+                        gpu_manager.list_iommu_groups(),
+                        &reservations_map,
+                    );
+                    for group in groups {
+                        let label = group
+                            .iommu_group
+                            .map(|g| format!("IOMMU group {}", g))
+                            .unwrap_or_else(|| "No IOMMU group".to_string());
+                        let viability = match group.viable_for_passthrough {
+                            Some(true) => " (viable)",
+                            Some(false) => " (shared with non-GPU devices)",
+                            None => "",
+                        };
+                        println!("{}{}", label, viability);
+                        for device in group.devices {
+                            println!(
+                                "  {:<18} {:<28} {:<13} {}",
+                                device.address,
+                                device.device_name,
+                                device.driver.as_deref().unwrap_or("-"),
+                                device.status
+                            );
+                        }
+                    }
+                    return Ok(());
+                }
 
-                println!(
-                    "{:<18} {:<28} {:<12} {:<13} {:<14} {:<11} {:<8} {:<14}",
+                let mut table = Table::new([
                     "PCI ADDRESS",
                     "GPU MODEL",
                     "IOMMU GROUP",
@@ -1535,10 +4628,8 @@ Volumes:"
                     "GENERATION",
                     "MIN DRIVER",
                     "TCC",
-                    "STATUS"
-                );
-                println!("{}", "=".repeat(128));
-
+                    "STATUS",
+                ]);
                 for gpu in gpus {
                     let iommu = gpu
                         .iommu_group
@@ -1559,32 +4650,21 @@ Volumes:"
                         "no"
                     };
 
-                    let status = if let Some(vm) = reservations_map.get(&gpu.address) {
-                        format!("reserved ({})", vm)
-                    } else if gpu.driver.as_deref() == Some("vfio-pci") {
-                        "vfio".to_string()
-                    } else if let Some(driver) = &gpu.driver {
-                        if gpu.in_use {
-                            format!("host ({driver})")
-                        } else {
-                            format!("available ({driver})")
-                        }
-                    } else {
-                        "unbound".to_string()
-                    };
-
-                    println!(
-                        "{:<18} {:<28} {:<12} {:<13} {:<14} {:<11} {:<8} {:<14}",
-                        gpu.address,
-                        gpu.device_name,
-                        iommu,
-                        driver,
-                        generation,
-                        min_driver,
-                        tcc,
-                        status,
-                    );
+                    table.row(vec![
+                        gpu.address.as_str().into(),
+                        gpu.device_name.as_str().into(),
+                        iommu.into(),
+                        driver.into(),
+                        generation.into(),
+                        min_driver.into(),
+                        tcc.into(),
+                        Cell::new(
+                            gpu_list_status(gpu, &reservations_map),
+                            gpu_status_tone(gpu, &reservations_map),
+                        ),
+                    ]);
                 }
+                table.print();
 
                 if !reservations_map.is_empty() {
                     println!("\nReserved GPUs:");
@@ -1618,12 +4698,12 @@ Volumes:"
                     println!("GPU '{}' not found", device);
                 }
             }
-            GpuCommands::Bind { device } => {
+            GpuCommands::Bind { device, force } => {
                 let mut gpu_manager = GpuManager::new();
                 gpu_manager.discover()?;
                 gpu_manager.refresh_device_status();
                 let before = gpu_manager.binding_info(&device);
-                gpu_manager.bind_device_to_vfio(&device)?;
+                gpu_manager.bind_device_to_vfio(&device, force)?;
                 let after = gpu_manager.binding_info(&device);
                 println!(
                     "{}",
@@ -1642,12 +4722,61 @@ Volumes:"
                     compose_binding_transition_cli("Reattached", &device, before, after)
                 );
             }
-            GpuCommands::Reserve { device, vm_name } => {
+            GpuCommands::ResetHost => {
+                let mut gpu_manager = GpuManager::new();
+                gpu_manager.discover()?;
+                let results = gpu_manager.reset_all_to_host();
+                if results.is_empty() {
+                    println!("No GPUs are bound to vfio-pci");
+                    return Ok(());
+                }
+
+                println!("{:<14} {:<32} {:<10} RESULT", "ADDRESS", "DEVICE", "DRIVER");
+                for result in &results {
+                    let outcome = match &result.outcome {
+                        RestoreOutcome::Restored => "✅ restored".to_string(),
+                        RestoreOutcome::InUse(vm) => format!("⏭️  in use by running VM '{}'", vm),
+                        RestoreOutcome::Failed(reason) => format!("❌ {}", reason),
+                    };
+                    println!(
+                        "{:<14} {:<32} {:<10} {}",
+                        result.address,
+                        result.device_name,
+                        result.driver.as_deref().unwrap_or("-"),
+                        outcome
+                    );
+                }
+
+                let count = |wanted: fn(&RestoreOutcome) -> bool| {
+                    results
+                        .iter()
+                        .filter(|result| wanted(&result.outcome))
+                        .count()
+                };
+                let restored = count(|outcome| matches!(outcome, RestoreOutcome::Restored));
+                let skipped = count(|outcome| matches!(outcome, RestoreOutcome::InUse(_)));
+                let failed = count(|outcome| matches!(outcome, RestoreOutcome::Failed(_)));
+                println!(
+                    "\n{} restored, {} skipped (in use), {} failed",
+                    restored, skipped, failed
+                );
+                if failed > 0 {
+                    return Err(NovaError::ConfigError(format!(
+                        "{} GPU(s) could not be returned to the host",
+                        failed
+                    )));
+                }
+            }
+            GpuCommands::Reserve {
+                device,
+                vm_name,
+                force,
+            } => {
                 let mut gpu_manager = GpuManager::new();
                 gpu_manager.discover()?;
                 gpu_manager.refresh_device_status();
                 let before = gpu_manager.binding_info(&device);
-                gpu_manager.configure_passthrough(&device, &vm_name)?;
+                gpu_manager.configure_passthrough(&device, &vm_name, force)?;
                 let after = gpu_manager.binding_info(&device);
                 println!(
                     "{}",
@@ -1728,6 +4857,40 @@ Volumes:"
                     }
                 }
             }
+            GpuCommands::Mdev { mdev_command } => match mdev_command {
+                GpuMdevCommands::List { device, json } => {
+                    let types = nova::mdev::supported_types_in(
+                        Path::new(nova::mdev::MDEV_BUS_PATH),
+                        &device,
+                    )?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&types)?);
+                        return Ok(());
+                    }
+                    if types.is_empty() {
+                        println!("{} lists no mediated-device profiles", device);
+                        return Ok(());
+                    }
+
+                    println!(
+                        "{:<16} {:<28} {:<10} {:<10}",
+                        "TYPE", "NAME", "AVAILABLE", "API"
+                    );
+                    println!("{}", "=".repeat(66));
+                    for mdev_type in &types {
+                        println!(
+                            "{:<16} {:<28} {:<10} {:<10}",
+                            mdev_type.id,
+                            mdev_type.display_name(),
+                            mdev_type.available_instances,
+                            mdev_type.device_api.as_deref().unwrap_or("-")
+                        );
+                        if let Some(description) = &mdev_type.description {
+                            println!("    {}", description.replace('\n', ", "));
+                        }
+                    }
+                }
+            },
         },
         Commands::Storage { storage_command } => match storage_command {
             StorageCommands::ListPools => {
@@ -1739,12 +4902,8 @@ Volumes:"
                     return Ok(());
                 }
 
-                println!(
-                    "{:<20} {:<10} {:<40} {:<8} {:<5} {:<20}",
-                    "NAME", "TYPE", "DIRECTORY", "FORMAT", "AUTO", "LABELS"
-                );
-                println!("{}", "=".repeat(110));
-
+                let mut table =
+                    Table::new(["NAME", "TYPE", "DIRECTORY", "FORMAT", "AUTO", "LABELS"]);
                 for (name, cfg) in pools {
                     let StoragePoolConfig {
                         pool_type,
@@ -1760,16 +4919,16 @@ Volumes:"
                     } else {
                         labels.join(",")
                     };
-                    println!(
-                        "{:<20} {:<10} {:<40} {:<8} {:<5} {:<20}",
-                        name,
-                        pool_type.as_str(),
-                        directory,
-                        default_format.as_str(),
-                        if auto_create { "yes" } else { "no" },
-                        labels
-                    );
+                    table.row(vec![
+                        name.as_str().into(),
+                        pool_type.as_str().into(),
+                        directory.as_str().into(),
+                        default_format.as_str().into(),
+                        if auto_create { "yes" } else { "no" }.into(),
+                        labels.into(),
+                    ]);
                 }
+                table.print();
             }
             StorageCommands::CreatePool {
                 name,
@@ -1780,6 +4939,9 @@ Volumes:"
                 format,
                 auto_create,
                 labels,
+                dataset,
+                compression,
+                dedup,
             } => {
                 let mut manager = StorageManager::load(&config_path)?;
                 let directory_str = directory.to_string_lossy().into_owned();
@@ -1792,6 +4954,11 @@ Volumes:"
                     default_format: format.into(),
                     auto_create,
                     labels,
+                    zfs: dataset.map(|dataset| ZfsPoolSettings {
+                        dataset,
+                        compression,
+                        dedup,
+                    }),
                 };
 
                 manager.create_pool(&name, pool_cfg)?;
@@ -1821,6 +4988,38 @@ Volumes:"
                     );
                 }
             }
+            StorageCommands::RefreshPool { name } => {
+                let mut storage_manager = StoragePoolManager::new();
+                let capacity = storage_manager.refresh_capacity(&name).await?;
+                println!(
+                    "✅ Pool '{}' refreshed: {} total, {} used, {} available",
+                    name,
+                    format_bytes(capacity.total_bytes),
+                    format_bytes(capacity.used_bytes),
+                    format_bytes(capacity.available_bytes)
+                );
+            }
+            StorageCommands::PoolInfo { name } => {
+                let mut storage_manager = StoragePoolManager::new();
+                storage_manager.refresh_capacity(&name).await?;
+                let pool = storage_manager
+                    .get_pool(&name)
+                    .expect("refreshed pool is cached");
+
+                println!("Storage Pool: {}", pool.name);
+                println!("  Type: {:?}", pool.pool_type);
+                println!("  Path: {}", pool.path.display());
+                println!("  State: {:?}", pool.state);
+                if let Some(capacity) = &pool.capacity {
+                    println!("  Capacity: {}", format_bytes(capacity.total_bytes));
+                    println!(
+                        "  Used: {} ({:.1}%)",
+                        format_bytes(capacity.used_bytes),
+                        capacity.usage_percent()
+                    );
+                    println!("  Available: {}", format_bytes(capacity.available_bytes));
+                }
+            }
             StorageCommands::ListVolumes { pool } => {
                 let storage_manager = StoragePoolManager::new();
                 let volumes = storage_manager.list_volumes(&pool);
@@ -1830,24 +5029,19 @@ Volumes:"
                     return Ok(());
                 }
 
-                println!(
-                    "{:<20} {:<12} {:<15} {:<15}",
-                    "NAME", "FORMAT", "CAPACITY", "ALLOCATION"
-                );
-                println!("{}", "=".repeat(70));
-
+                let mut table = Table::new(["NAME", "FORMAT", "CAPACITY", "ALLOCATION"]);
                 for volume in volumes {
                     let capacity_gb = volume.capacity_bytes as f64 / 1_073_741_824.0;
                     let alloc_gb = volume.allocation_bytes as f64 / 1_073_741_824.0;
 
-                    println!(
-                        "{:<20} {:<12} {:<15} {:<15}",
-                        volume.name,
-                        format!("{:?}", volume.format),
-                        format!("{:.1} GB", capacity_gb),
-                        format!("{:.1} GB", alloc_gb)
-                    );
+                    table.row(vec![
+                        volume.name.as_str().into(),
+                        format!("{:?}", volume.format).into(),
+                        format!("{:.1} GB", capacity_gb).into(),
+                        format!("{:.1} GB", alloc_gb).into(),
+                    ]);
                 }
+                table.print();
             }
             StorageCommands::CreateVolume {
                 pool,
@@ -1865,10 +5059,141 @@ Volumes:"
 
                 println!("✅ Volume '{}' created in pool '{}'", name, pool);
             }
+            StorageCommands::Verify => {
+                let mut storage_manager = StoragePoolManager::new();
+                let mut issues = match storage_manager.verify_pools().await {
+                    Ok(issues) => issues,
+                    Err(e) => {
+                        println!("⚠️  Could not list libvirt pools: {}", e);
+                        Vec::new()
+                    }
+                };
+                let checked = storage_manager.list_pools().len();
+                let novafile_pools = StorageManager::load(&config_path)?;
+                issues.extend(novafile_pools.overlapping_pools());
+                let checked = checked + novafile_pools.list_pools().len();
+
+                if issues.is_empty() {
+                    println!("✅ {} storage pool(s) checked, no problems found", checked);
+                    return Ok(());
+                }
+                for issue in &issues {
+                    println!(
+                        "{} {} ({}): {}",
+                        if issue.is_error() { "❌" } else { "⚠️ " },
+                        issue.pool,
+                        issue.path.display(),
+                        issue.problem
+                    );
+                }
+                let errors = issues.iter().filter(|issue| issue.is_error()).count();
+                if errors > 0 {
+                    return Err(NovaError::ConfigError(format!(
+                        "{} storage pool problem(s) need attention",
+                        errors
+                    )));
+                }
+            }
+            StorageCommands::DeleteVolume { pool, name, force } => {
+                let mut storage_manager = StoragePoolManager::new();
+                storage_manager.delete_volume(&pool, &name, force).await?;
+                println!("✅ Volume '{}' deleted from pool '{}'", name, pool);
+            }
+            StorageCommands::CloneVolume {
+                pool,
+                source,
+                target,
+            } => {
+                let mut storage_manager = StoragePoolManager::new();
+                let volume = storage_manager
+                    .clone_volume(&pool, &source, &target)
+                    .await?;
+                println!(
+                    "✅ Volume '{}' cloned to '{}' ({})",
+                    source,
+                    target,
+                    volume.path.display()
+                );
+            }
+            StorageCommands::ResizeVolume {
+                pool,
+                name,
+                size,
+                vm,
+                target,
+                grow_guest,
+            } => {
+                let size_bytes = parse_size(&size)?;
+
+                let mut storage_manager = StoragePoolManager::new();
+                let live = match (&vm, &target) {
+                    (Some(vm), Some(_)) => vm_manager.domain_running(vm)?,
+                    _ => false,
+                };
+                if !live {
+                    storage_manager
+                        .resize_volume(&pool, &name, size_bytes)
+                        .await?;
+                    println!(
+                        "✅ Volume '{}' in pool '{}' resized to {}",
+                        name, pool, size
+                    );
+                    if let Some(vm) = &vm {
+                        println!(
+                            "VM '{}' is not running; it sees the new size on its next boot.",
+                            vm
+                        );
+                    }
+                }
+
+                if let (true, Some(vm), Some(target)) = (live, vm, target) {
+                    // QEMU owns the image while the domain runs, so it does
+                    // the resize and libvirt's pool view is refreshed after
+                    let report = vm_manager
+                        .notify_disk_resize(&vm, &target, size_bytes, grow_guest)
+                        .await?;
+                    storage_manager.refresh_capacity(&pool).await?;
+                    println!(
+                        "✅ Volume '{}' in pool '{}' resized to {} through VM '{}'",
+                        name, pool, size, vm
+                    );
+                    println!(
+                        "Guest '{}' should now see {} bytes ({:.1} GiB) on {}",
+                        report.vm,
+                        report.guest_size_bytes,
+                        report.guest_size_bytes as f64 / 1_073_741_824.0,
+                        report.target
+                    );
+
+                    if grow_guest {
+                        if !report.guest_agent {
+                            println!(
+                                "⚠️  QEMU guest agent is not responding; grow the partition and filesystem inside the guest manually."
+                            );
+                        } else if report.grow_suggestions.is_empty() {
+                            println!("No filesystems reported on {} by the guest agent.", target);
+                        } else {
+                            println!("Run inside the guest to use the new space:");
+                            for suggestion in &report.grow_suggestions {
+                                println!(
+                                    "  {} ({} on {}): {}",
+                                    suggestion.filesystem.mountpoint,
+                                    suggestion.filesystem.fs_type,
+                                    suggestion.filesystem.device,
+                                    suggestion.command
+                                );
+                            }
+                        }
+                    }
+                }
+            }
         },
         Commands::Snapshot { snapshot_command } => {
             let templates_dir = PathBuf::from("/var/lib/nova/templates");
             let mut snapshot_manager = SnapshotManager::new(templates_dir)?;
+            for (vm, vm_config) in &config.vm {
+                snapshot_manager.set_quota(vm, vm_config.snapshot_quota.clone());
+            }
 
             match snapshot_command {
                 SnapshotCommands::Create {
@@ -1899,25 +5224,130 @@ Volumes:"
                     for snapshot in snapshots {
                         let size_mb = snapshot.size_bytes as f64 / 1_048_576.0;
                         let current = if snapshot.is_current { "✓" } else { "" };
+                        let name = if snapshot.auto_created {
+                            format!("{} (auto)", snapshot.name)
+                        } else {
+                            snapshot.name.clone()
+                        };
+
+                        println!(
+                            "{:<20} {:<30} {:<12} {:<10}",
+                            name,
+                            snapshot.created_at.format("%Y-%m-%d %H:%M:%S"),
+                            format!("{:.1} MB", size_mb),
+                            current
+                        );
+                    }
+
+                    let total_size = snapshot_manager.get_total_snapshot_size(&vm);
+                    println!(
+                        "\nTotal snapshot storage: {:.1} MB",
+                        total_size as f64 / 1_048_576.0
+                    );
+                    if let Some(quota) = snapshot_manager.quota(&vm) {
+                        println!(
+                            "Quota: {}",
+                            format_snapshot_quota_usage(
+                                quota,
+                                snapshot_manager.list_snapshots(&vm).len(),
+                                total_size
+                            )
+                        );
+                    }
+                }
+                SnapshotCommands::Quota {
+                    vm,
+                    max,
+                    max_bytes,
+                    auto_prune,
+                    clear,
+                } => {
+                    let mut updated = config.clone();
+                    let vm_config = updated.vm.get_mut(&vm).ok_or_else(|| {
+                        NovaError::ConfigError(format!(
+                            "VM '{}' is not defined in {}; add a [vm.{}] entry to set a quota",
+                            vm,
+                            config_path.display(),
+                            vm
+                        ))
+                    })?;
+
+                    let changed =
+                        clear || max.is_some() || max_bytes.is_some() || auto_prune.is_some();
+                    if clear {
+                        vm_config.snapshot_quota = Default::default();
+                    }
+                    if let Some(max) = max {
+                        vm_config.snapshot_quota.max_snapshots = Some(max);
+                    }
+                    if let Some(size) = max_bytes {
+                        vm_config.snapshot_quota.max_snapshot_bytes = Some(parse_size(&size)?);
+                    }
+                    if let Some(auto_prune) = auto_prune {
+                        vm_config.snapshot_quota.auto_prune = auto_prune;
+                    }
 
+                    let quota = vm_config.snapshot_quota.clone();
+                    if changed {
+                        updated.save_to_file(&config_path)?;
                         println!(
-                            "{:<20} {:<30} {:<12} {:<10}",
-                            snapshot.name,
-                            snapshot.created_at.format("%Y-%m-%d %H:%M:%S"),
-                            format!("{:.1} MB", size_mb),
-                            current
+                            "✅ Snapshot quota for '{}' saved to {}",
+                            vm,
+                            config_path.display()
                         );
                     }
 
-                    let total_size = snapshot_manager.get_total_snapshot_size(&vm);
-                    println!(
-                        "\nTotal snapshot storage: {:.1} MB",
-                        total_size as f64 / 1_048_576.0
-                    );
+                    if quota.is_unlimited() {
+                        println!("No snapshot quota set for '{}'", vm);
+                    } else {
+                        println!(
+                            "Quota: {}",
+                            format_snapshot_quota_usage(
+                                &quota,
+                                snapshot_manager.list_snapshots(&vm).len(),
+                                snapshot_manager.get_total_snapshot_size(&vm)
+                            )
+                        );
+                    }
                 }
-                SnapshotCommands::Revert { vm, snapshot } => {
-                    snapshot_manager.revert_to_snapshot(&vm, &snapshot).await?;
-                    println!("✅ VM '{}' reverted to snapshot '{}'", vm, snapshot);
+                SnapshotCommands::Revert {
+                    vm,
+                    snapshot,
+                    safe,
+                    yes,
+                } => {
+                    use std::io::IsTerminal;
+                    if !yes && io::stdin().is_terminal() {
+                        let warning = if safe {
+                            "a pre-revert snapshot will be taken first"
+                        } else {
+                            "its current state will be lost"
+                        };
+                        print!(
+                            "Revert '{}' to snapshot '{}'? {} [y/N]: ",
+                            vm, snapshot, warning
+                        );
+                        io::stdout().flush().ok();
+                        let mut answer = String::new();
+                        io::stdin().read_line(&mut answer).map_err(|err| {
+                            NovaError::ConfigError(format!("Failed to read answer: {}", err))
+                        })?;
+                        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                            println!("Revert cancelled");
+                            return Ok(());
+                        }
+                    }
+
+                    if safe {
+                        let safety = snapshot_manager
+                            .revert_to_snapshot_safe(&vm, &snapshot)
+                            .await?;
+                        println!("✅ VM '{}' reverted to snapshot '{}'", vm, snapshot);
+                        println!("   Undo with: nova snapshot revert {} {}", vm, safety);
+                    } else {
+                        snapshot_manager.revert_to_snapshot(&vm, &snapshot).await?;
+                        println!("✅ VM '{}' reverted to snapshot '{}'", vm, snapshot);
+                    }
                 }
                 SnapshotCommands::Delete {
                     vm,
@@ -1929,23 +5359,124 @@ Volumes:"
                         .await?;
                     println!("✅ Snapshot '{}' deleted", snapshot);
                 }
+                SnapshotCommands::Export {
+                    vm,
+                    snapshot,
+                    output,
+                    memory,
+                } => {
+                    let mut progress = reporter("convert");
+                    let export = snapshot_manager
+                        .export_snapshot(&vm, &snapshot, &output, memory, &mut progress)
+                        .await;
+                    progress.conclude(&export);
+                    let export = export?;
+                    println!(
+                        "✅ Snapshot '{}' of '{}' exported to {} ({})",
+                        snapshot,
+                        vm,
+                        export.archive.display(),
+                        format_bytes(export.size_bytes)
+                    );
+                    for file in export.manifest.files() {
+                        println!(
+                            "   {:<8} {:>10}  sha256 {}",
+                            file.name,
+                            format_bytes(file.size_bytes),
+                            file.sha256
+                        );
+                    }
+                    if memory && export.manifest.memory.is_none() {
+                        println!("⚠️  No external memory state to include; exported disks only");
+                    }
+                }
+                SnapshotCommands::Import { archive, vm, as_vm } => {
+                    let target = match as_vm {
+                        Some(name) => ImportTarget::NewVm(name),
+                        None => ImportTarget::Snapshot { vm },
+                    };
+                    let import = snapshot_manager.import_snapshot(&archive, target).await?;
+                    if import.new_vm {
+                        println!(
+                            "✅ VM '{}' defined from snapshot '{}' (disks in {})",
+                            import.vm,
+                            import.snapshot,
+                            import.directory.display()
+                        );
+                    } else {
+                        println!(
+                            "✅ Snapshot '{}' imported into '{}' (images in {})",
+                            import.snapshot,
+                            import.vm,
+                            import.directory.display()
+                        );
+                    }
+                    println!("   Integrity verified (size, SHA-256, qemu-img check)");
+                    if let Some(memory) = &import.memory {
+                        println!(
+                            "⚠️  Memory state is not restored automatically; it is at {}",
+                            memory.display()
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Clone {
+            clone_command: Some(CloneCommands::Verify { name }),
+            ..
+        } => {
+            let snapshot_manager = SnapshotManager::new(PathBuf::from(TEMPLATES_DIR))?;
+            let chains = snapshot_manager.validate_clone(&name)?;
+            for chain in &chains {
+                let files: Vec<String> = chain
+                    .files
+                    .iter()
+                    .map(|file| file.display().to_string())
+                    .collect();
+                match &chain.missing {
+                    Some(missing) => println!(
+                        "❌ {}: {} -> {} (missing)",
+                        chain.target,
+                        files.join(" -> "),
+                        missing.display()
+                    ),
+                    None => println!("✅ {}: {}", chain.target, files.join(" -> ")),
+                }
+            }
+            if chains.iter().any(|chain| chain.is_broken()) {
+                if let Some(source) = linked_clone_source(Path::new(TEMPLATES_DIR), &name) {
+                    println!("   '{}' was cloned from '{}'", name, source);
+                }
+                std::process::exit(1);
             }
         }
         Commands::Clone {
+            clone_command: None,
             source,
             target,
             linked,
         } => {
-            let templates_dir = PathBuf::from("/var/lib/nova/templates");
+            let (Some(source), Some(target)) = (source, target) else {
+                unreachable!("clap requires source and target without a subcommand");
+            };
+            let templates_dir = PathBuf::from(TEMPLATES_DIR);
             let mut snapshot_manager = SnapshotManager::new(templates_dir)?;
 
-            if linked {
+            let mut progress = reporter("clone");
+            let result = if linked {
                 snapshot_manager
-                    .create_linked_clone(&source, &target)
-                    .await?;
+                    .create_linked_clone(&source, &target, &mut progress)
+                    .await
+            } else {
+                snapshot_manager
+                    .clone_vm(&source, &target, true, &mut progress)
+                    .await
+            };
+            progress.conclude(&result);
+            result?;
+            if linked {
                 println!("✅ Linked clone '{}' created from '{}'", target, source);
             } else {
-                snapshot_manager.clone_vm(&source, &target, true).await?;
                 println!("✅ VM '{}' cloned to '{}'", source, target);
             }
         }
@@ -1953,22 +5484,201 @@ Volumes:"
             vm,
             destination,
             offline,
+            post_copy,
+            max_bandwidth,
+            compress,
+            auto_converge,
+            copy_storage,
         } => {
-            let config = MigrationConfig::default();
+            let config = MigrationConfig {
+                post_copy,
+                compress,
+                auto_converge,
+                copy_storage,
+                bandwidth_limit_mbps: max_bandwidth.unwrap_or(0),
+                ..MigrationConfig::default()
+            };
             let mut migration_manager = MigrationManager::new(config, None);
 
             let migration_type = if offline {
                 Some(nova::migration::MigrationType::Offline)
+            } else if post_copy {
+                Some(nova::migration::MigrationType::PostCopy)
             } else {
                 None
             };
 
+            let mut progress = reporter("migrate");
             let job_id = migration_manager
                 .migrate_vm(&vm, &destination, migration_type)
-                .await?;
+                .await;
+            if let Err(err) = &job_id {
+                progress.fail(err.to_string());
+            }
+            let job_id = job_id?;
+
+            // The job runs on this process's runtime; stay until libvirt owns
+            // the transfer so it survives `nova` exiting.
+            let status = migration_manager
+                .wait_until_transferring(&job_id, Duration::from_secs(120), &mut progress)
+                .await;
+            if let MigrationStatus::Failed(reason) = status {
+                progress.fail(&reason);
+                return Err(NovaError::LibvirtError(format!(
+                    "Migration {} failed: {}",
+                    job_id, reason
+                )));
+            }
+            progress.finish(Some(format!("transfer handed off to libvirt ({})", job_id)));
             println!("✅ Migration started (Job ID: {})", job_id);
+            println!("  Phase: {}", status);
+            if let Some(limit) = max_bandwidth {
+                println!("  Bandwidth limit: {} MiB/s", limit);
+            }
+            if post_copy {
+                println!(
+                    "⚠️  Post-copy: once the VM resumes on {}, losing the network or either host before the copy finishes crashes the VM",
+                    destination
+                );
+            }
             println!("Monitor progress with: nova migration status {}", job_id);
         }
+        Commands::Migration { migration_command } => {
+            let mut migration_manager = MigrationManager::new(MigrationConfig::default(), None);
+
+            match migration_command {
+                MigrationCommands::Status { job_id } => {
+                    let metrics = migration_manager.refresh_migration_metrics(&job_id).await?;
+                    let job = migration_manager
+                        .get_migration_job(&job_id)
+                        .expect("job exists after refresh");
+
+                    println!("Migration: {}", job.job_id);
+                    println!("  VM: {}", job.vm_name);
+                    println!("  Route: {} -> {}", job.source_host, job.destination_host);
+                    println!("  Type: {:?}", job.migration_type);
+                    println!("  Phase: {}", job.status);
+                    if job.post_copy {
+                        println!(
+                            "  Post-copy: {}",
+                            if job.post_copy_active {
+                                "active (running on destination, fetching remaining memory)"
+                            } else {
+                                "armed (switches after the first pass)"
+                            }
+                        );
+                    }
+                    println!("  Progress: {:.1}%", job.progress_percent);
+                    println!("  Started: {}", job.started_at.format("%Y-%m-%d %H:%M:%S"));
+                    if let Some(completed) = job.completed_at {
+                        println!("  Finished: {}", completed.format("%Y-%m-%d %H:%M:%S"));
+                    } else if let Some(eta) = job.estimated_completion {
+                        println!("  Estimated Completion: {}", eta.format("%H:%M:%S"));
+                    }
+
+                    match metrics {
+                        Some(metrics) => {
+                            println!(
+                                "  Memory: {} / {} ({} remaining)",
+                                format_bytes(metrics.ram_transferred_bytes),
+                                format_bytes(metrics.ram_total_bytes),
+                                format_bytes(metrics.ram_remaining_bytes)
+                            );
+                            if metrics.disk_total_bytes > 0 {
+                                println!(
+                                    "  Storage: {} / {}",
+                                    format_bytes(metrics.disk_transferred_bytes),
+                                    format_bytes(metrics.disk_total_bytes)
+                                );
+                            }
+                            println!(
+                                "  Rate: {}/s",
+                                format_bytes(metrics.transfer_rate_bytes_per_sec)
+                            );
+                            println!("  Iteration: {}", metrics.iteration);
+                            if metrics.downtime_ms > 0 {
+                                println!("  Expected Downtime: {} ms", metrics.downtime_ms);
+                            }
+                        }
+                        None if !job.status.is_finished() => {
+                            println!("  Libvirt reports no active job for '{}'", job.vm_name);
+                        }
+                        None => {}
+                    }
+                    if let Some(error) = &job.error_message {
+                        println!("  Error: {}", error);
+                    }
+                }
+                MigrationCommands::List { all } => {
+                    let jobs = if all {
+                        migration_manager.list_migrations()
+                    } else {
+                        migration_manager.list_active_migrations()
+                    };
+                    if jobs.is_empty() {
+                        println!("No {}migration jobs", if all { "" } else { "active " });
+                        return Ok(());
+                    }
+
+                    println!(
+                        "{:<38} {:<20} {:<20} {:<24} {:<8}",
+                        "JOB ID", "VM", "DESTINATION", "PHASE", "PROGRESS"
+                    );
+                    println!("{}", "=".repeat(112));
+                    for job in jobs {
+                        let phase = match &job.status {
+                            MigrationStatus::Failed(_) => "failed".to_string(),
+                            status => status.to_string(),
+                        };
+                        println!(
+                            "{:<38} {:<20} {:<20} {:<24} {:>7.1}%",
+                            job.job_id,
+                            job.vm_name,
+                            job.destination_host,
+                            phase,
+                            job.progress_percent
+                        );
+                    }
+                }
+                MigrationCommands::Cancel { job_id } => {
+                    migration_manager.cancel_migration(&job_id).await?;
+                    println!("✅ Migration {} cancelled", job_id);
+                }
+                MigrationCommands::Precheck {
+                    vm,
+                    destination,
+                    copy_storage,
+                } => {
+                    let precheck = migration_manager.precheck(&vm, &destination).await?;
+                    println!("Migration precheck: {} -> {}", vm, destination);
+                    println!(
+                        "  CPU here:        {} {} ({} features)",
+                        precheck.source_cpu.vendor,
+                        precheck.source_cpu.model,
+                        precheck.source_cpu.features.len()
+                    );
+                    println!(
+                        "  CPU destination: {} {} ({} features)",
+                        precheck.destination_cpu.vendor,
+                        precheck.destination_cpu.model,
+                        precheck.destination_cpu.features.len()
+                    );
+                    println!("  Guest CPU mode:  {}", precheck.cpu_mode);
+                    for warning in precheck.warnings() {
+                        println!("⚠️  {}", warning);
+                    }
+                    let issues = precheck.blocking_issues(copy_storage);
+                    if issues.is_empty() {
+                        println!("✅ Ready to migrate");
+                    } else {
+                        for issue in &issues {
+                            println!("❌ {}", issue);
+                        }
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
         Commands::Usb { usb_command } => {
             let mut usb_manager = UsbManager::new();
 
@@ -1983,27 +5693,29 @@ Volumes:"
                         return Ok(());
                     }
 
-                    println!(
-                        "{:<10} {:<20} {:<30} {:<10}",
-                        "BUS:DEV", "VENDOR:PRODUCT", "DEVICE", "STATUS"
-                    );
-                    println!("{}", "=".repeat(75));
-
+                    let mut table = Table::new(["BUS:DEV", "VENDOR:PRODUCT", "DEVICE", "STATUS"]);
                     for device in devices {
                         let id = format!("{}:{}", device.bus, device.device);
                         let ids = format!("{}:{}", device.vendor_id, device.product_id);
-                        let status = device.attached_to_vm.as_deref().unwrap_or("Available");
+                        let status = match device.attached_to_vm.as_deref() {
+                            Some(vm) => Cell::new(vm, Tone::Warning),
+                            None => Cell::new("Available", Tone::Good),
+                        };
 
-                        println!(
-                            "{:<10} {:<20} {:<30} {:<10}",
-                            id, ids, device.product_name, status
-                        );
+                        table.row(vec![
+                            id.into(),
+                            ids.into(),
+                            device.product_name.as_str().into(),
+                            status,
+                        ]);
                     }
+                    table.print();
                 }
                 UsbCommands::Attach {
                     vm,
                     vendor,
                     product,
+                    persistent,
                 } => {
                     usb_manager
                         .discover_devices()
@@ -2011,10 +5723,20 @@ Volumes:"
 
                     if let Some(device) = usb_manager.find_device(&vendor, &product).cloned() {
                         usb_manager
-                            .attach_device(&vm, &device)
+                            .attach_device(&vm, &device, persistent)
                             .await
                             .map_err(NovaError::LibvirtError)?;
-                        println!("✅ USB device attached to VM '{}'", vm);
+                        if persistent {
+                            println!(
+                                "✅ USB device attached to VM '{}' and saved to its definition",
+                                vm
+                            );
+                        } else {
+                            println!("✅ USB device attached to VM '{}'", vm);
+                            println!(
+                                "   It detaches when the VM powers off; use --persistent to keep it"
+                            );
+                        }
                     } else {
                         println!("❌ USB device {}:{} not found", vendor, product);
                     }
@@ -2023,6 +5745,7 @@ Volumes:"
                     vm,
                     vendor,
                     product,
+                    persistent,
                 } => {
                     usb_manager
                         .discover_devices()
@@ -2030,10 +5753,17 @@ Volumes:"
 
                     if let Some(device) = usb_manager.find_device(&vendor, &product).cloned() {
                         usb_manager
-                            .detach_device(&vm, &device)
+                            .detach_device(&vm, &device, persistent)
                             .await
                             .map_err(NovaError::LibvirtError)?;
-                        println!("✅ USB device detached from VM '{}'", vm);
+                        if persistent {
+                            println!(
+                                "✅ USB device detached from VM '{}' and removed from its definition",
+                                vm
+                            );
+                        } else {
+                            println!("✅ USB device detached from VM '{}'", vm);
+                        }
                     } else {
                         println!("❌ USB device {}:{} not found", vendor, product);
                     }
@@ -2054,23 +5784,22 @@ Volumes:"
                         return Ok(());
                     }
 
-                    println!(
-                        "{:<18} {:<30} {:<15} {:<12}",
-                        "PCI ADDRESS", "DEVICE", "CLASS", "DRIVER"
-                    );
-                    println!("{}", "=".repeat(80));
-
+                    let mut table = Table::new(["PCI ADDRESS", "DEVICE", "CLASS", "DRIVER"]);
                     for device in devices {
-                        let driver = device.driver.as_deref().unwrap_or("-");
+                        let driver = match device.driver.as_deref() {
+                            Some("vfio-pci") => Cell::new("vfio-pci", Tone::Good),
+                            Some(driver) => driver.into(),
+                            None => Cell::new("-", Tone::Muted),
+                        };
 
-                        println!(
-                            "{:<18} {:<30} {:<15} {:<12}",
-                            device.address,
-                            device.device_name,
-                            format!("{:?}", device.device_class),
-                            driver
-                        );
+                        table.row(vec![
+                            device.address.as_str().into(),
+                            device.device_name.as_str().into(),
+                            format!("{:?}", device.device_class).into(),
+                            driver,
+                        ]);
                     }
+                    table.print();
                 }
                 PciCommands::Info { device } => {
                     pci_manager
@@ -2083,14 +5812,19 @@ Volumes:"
                         println!("❌ PCI device '{}' not found", device);
                     }
                 }
-                PciCommands::Attach { vm, device } => {
+                PciCommands::Attach { vm, devices, force } => {
                     pci_manager
                         .discover_devices()
                         .map_err(NovaError::ConfigError)?;
+                    let addresses: Vec<&str> = devices.iter().map(String::as_str).collect();
                     pci_manager
-                        .assign_to_vm(&device, &vm)
-                        .map_err(NovaError::LibvirtError)?;
-                    println!("✅ PCI device {} assigned to VM '{}'", device, vm);
+                        .assign_devices_to_vm(&addresses, &vm, force)
+                        .map_err(NovaError::ConfigError)?;
+                    println!(
+                        "✅ PCI device(s) {} assigned to VM '{}'",
+                        devices.join(", "),
+                        vm
+                    );
                 }
                 PciCommands::Detach { device } => {
                     pci_manager
@@ -2126,26 +5860,31 @@ Volumes:"
                         return Ok(());
                     }
 
-                    println!(
-                        "{:<18} {:<30} {:<8} {:<8}",
-                        "PCI ADDRESS", "DEVICE", "MAX VFs", "ACTIVE VFs"
-                    );
-                    println!("{}", "=".repeat(70));
-
+                    let mut table = Table::new(["PCI ADDRESS", "DEVICE", "MAX VFs", "ACTIVE VFs"]);
                     for device in devices {
-                        println!(
-                            "{:<18} {:<30} {:<8} {:<8}",
-                            device.pf_address,
-                            device.device_name,
-                            device.max_vfs,
-                            device.current_vfs
-                        );
+                        let active_tone = if device.current_vfs > 0 {
+                            Tone::Good
+                        } else {
+                            Tone::Muted
+                        };
+                        table.row(vec![
+                            device.pf_address.as_str().into(),
+                            device.device_name.as_str().into(),
+                            device.max_vfs.to_string().into(),
+                            Cell::new(device.current_vfs.to_string(), active_tone),
+                        ]);
                     }
+                    table.print();
                 }
-                SriovCommands::Enable { pf, num_vfs } => {
+                SriovCommands::Enable {
+                    pf,
+                    num_vfs,
+                    timeout,
+                } => {
                     sriov_manager
                         .discover_sriov_devices()
                         .map_err(NovaError::ConfigError)?;
+                    sriov_manager.set_vf_timeout(Duration::from_secs(timeout));
                     sriov_manager
                         .enable_sriov(&pf, num_vfs)
                         .map_err(NovaError::ConfigError)?;
@@ -2174,19 +5913,112 @@ Volumes:"
                 }
             }
         }
+        Commands::Iso { iso_command } => match iso_command {
+            IsoCommands::Fetch {
+                url,
+                dest,
+                name,
+                sha256,
+            } => {
+                let dest_dir = dest
+                    .or_else(|| config.iso.paths.first().cloned())
+                    .ok_or_else(|| {
+                        NovaError::ConfigError(
+                            "No ISO directory configured; pass --dest or set [iso] paths"
+                                .to_string(),
+                        )
+                    })?;
+                let mut progress = reporter("download");
+                let result = nova::iso::fetch(
+                    &url,
+                    &dest_dir,
+                    name.as_deref(),
+                    sha256.as_deref(),
+                    &mut progress,
+                );
+                progress.conclude(&result);
+                let path = result?;
+                if exec::is_dry_run() {
+                    return Ok(());
+                }
+                println!("✅ Downloaded {}", path.display());
+                if sha256.is_some() {
+                    println!("   SHA-256 verified");
+                }
+
+                // Register the ISO so the wizard and GUI dropdowns list it
+                if config_found {
+                    let mut file_config = NovaConfig::from_file(&config_path)?;
+                    if !file_config.iso.paths.contains(&dest_dir) {
+                        file_config.iso.paths.push(dest_dir.clone());
+                    }
+                    let isos = nova::vm_templates::scan_iso_directories(&file_config.iso.paths);
+                    let os_type = isos
+                        .iter()
+                        .find(|iso| iso.path == path)
+                        .map(|iso| iso.os_type.clone())
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    let file_name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    let key = path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| file_name.clone());
+                    println!(
+                        "   Detected OS: {} ({} ISOs available)",
+                        os_type,
+                        isos.len()
+                    );
+                    file_config.iso.known.insert(
+                        key,
+                        nova::config::IsoEntry {
+                            path,
+                            name: file_name,
+                            os_type,
+                            version: None,
+                        },
+                    );
+                    file_config.save_to_file(&config_path)?;
+                } else {
+                    println!(
+                        "   No NovaFile at {}; add {} to [iso] paths to list it in the GUI",
+                        config_path.display(),
+                        dest_dir.display()
+                    );
+                }
+            }
+        },
+        Commands::Mdev { mdev_command } => match mdev_command {
+            MdevCommands::Create { pf, profile } => {
+                let uuid = nova::mdev::create(&pf, &profile)?;
+                println!("✅ Created mdev {} ({}) on {}", uuid, profile, pf);
+                println!("   Assign it with: nova mdev assign {} <vm>", uuid);
+            }
+            MdevCommands::Assign { uuid, vm } => {
+                nova::mdev::assign(&uuid, &vm)?;
+                println!("✅ mdev {} assigned to VM '{}'", uuid, vm);
+                println!("   It takes effect the next time '{}' boots", vm);
+            }
+        },
         Commands::Spice { spice_command } => {
             let mut spice_manager = SpiceManager::new();
 
             match spice_command {
-                SpiceCommands::Connect { vm } => {
-                    let _info = spice_manager
+                SpiceCommands::Connect { vm, print_uri } => {
+                    let info = spice_manager
                         .get_connection_info(&vm)
                         .await
                         .map_err(NovaError::LibvirtError)?;
+                    if print_uri {
+                        println!("{}", info.uri);
+                        return Ok(());
+                    }
                     spice_manager
                         .launch_client(&vm)
                         .await
-                        .map_err(NovaError::LibvirtError)?;
+                        .map_err(NovaError::ConfigError)?;
                     println!("✅ SPICE client launched for VM '{}'", vm);
                 }
                 SpiceCommands::Info { vm } => {
@@ -2240,13 +6072,154 @@ Volumes:"
                 }
             }
         }
+        Commands::Rustdesk { rustdesk_command } => match rustdesk_command {
+            RustdeskCommands::Connect { vm, print } => {
+                let info = RustDeskManager::session_info(&vm, true).await?;
+                print_rustdesk_info(&info);
+                if info.id.is_none() {
+                    std::process::exit(1);
+                }
+                if !print {
+                    RustDeskManager::launch_client(&info)?;
+                    println!("✅ RustDesk client launched for VM '{}'", vm);
+                }
+            }
+            RustdeskCommands::Info { vm } => {
+                let info = RustDeskManager::session_info(&vm, false).await?;
+                print_rustdesk_info(&info);
+                match RustDeskManager::detect_client() {
+                    Some(client) => println!("  Client: {}", client.display()),
+                    None => println!("  Client: not installed"),
+                }
+            }
+        },
+        Commands::Ports { port, format } => {
+            // Host listeners are still shown when the container runtime is missing
+            let containers = match container_manager.get() {
+                Ok(manager) => manager.published_ports().await,
+                Err(err) => {
+                    logger::warn!("{}", err);
+                    Vec::new()
+                }
+            };
+            let rows: Vec<_> = port_monitor::list_port_assignments(&containers)?
+                .into_iter()
+                .filter(|row| port.is_none_or(|port| row.host_port == port))
+                .collect();
+            if format == OutputFormatArg::Json {
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+                return Ok(());
+            }
+            if rows.is_empty() {
+                match port {
+                    Some(port) => println!("Nothing is listening on port {}", port),
+                    None => println!("No listening ports found"),
+                }
+                return Ok(());
+            }
+            println!("{:<6} {:<24} {:<8} OWNER", "PROTO", "HOST", "TARGET");
+            for row in &rows {
+                let proto = match row.protocol {
+                    port_monitor::PortProtocol::Udp => "udp",
+                    _ => "tcp",
+                };
+                let host = format!("{}:{}", row.addresses.join(","), row.host_port);
+                let target = row
+                    .target_port
+                    .map_or("-".to_string(), |port| port.to_string());
+                println!("{:<6} {:<24} {:<8} {}", proto, host, target, row.owner);
+            }
+            if rows
+                .iter()
+                .any(|row| row.owner == port_monitor::PortOwner::Unknown)
+            {
+                println!("\nOwners of '-' rows are only visible to root (sudo nova ports)");
+            }
+        }
+        Commands::Firewall { firewall_command } => match firewall_command {
+            FirewallCommands::Status => {
+                let manager = FirewallManager::with_settings(&config.firewall)?;
+                let source = if manager.preferred_backend().is_some() {
+                    "pinned"
+                } else {
+                    "auto-detected"
+                };
+                println!("Managed backend: {} ({})", manager.get_backend(), source);
+                let active = manager.active_backends();
+                if active.is_empty() {
+                    println!("Active backends: none holding rules");
+                } else {
+                    let names: Vec<String> = active.iter().map(ToString::to_string).collect();
+                    println!("Active backends: {}", names.join(", "));
+                }
+                if let Some(warning) = manager.backend_conflict() {
+                    println!("⚠️  {}", warning);
+                }
+            }
+            FirewallCommands::Backend { backend } => {
+                let preferred = backend.backend();
+                if let Some(backend) = &preferred {
+                    FirewallManager::new()?.set_preferred_backend(backend.clone())?;
+                }
+
+                let mut file_config = if config_path.exists() {
+                    NovaConfig::from_file(&config_path)?
+                } else {
+                    NovaConfig::default()
+                };
+                file_config.firewall.preferred_backend = preferred.clone();
+                file_config.save_to_file(&config_path)?;
+
+                match preferred {
+                    Some(backend) => println!(
+                        "✅ Nova now manages firewall rules through {} ({})",
+                        backend,
+                        config_path.display()
+                    ),
+                    None => {
+                        println!("✅ Cleared the pinned firewall backend; Nova will auto-detect it")
+                    }
+                }
+            }
+            FirewallCommands::Allow(args) => {
+                add_firewall_rule(&config, args.rule(RuleAction::Allow)).await?
+            }
+            FirewallCommands::Deny(args) => {
+                add_firewall_rule(&config, args.rule(RuleAction::Deny)).await?
+            }
+            FirewallCommands::Rules => {
+                let manager = FirewallManager::with_settings(&config.firewall)?;
+                let rules = manager.list_rules().await?;
+                if rules.is_empty() {
+                    println!("No Nova firewall rules in {}", manager.get_backend());
+                }
+                for (index, rule) in rules.iter().enumerate() {
+                    let chain = rule.chain.as_deref().unwrap_or("-");
+                    println!("{:>3}  {:<20} {}", index + 1, chain, rule.tag());
+                }
+            }
+            FirewallCommands::Remove { index } => {
+                let manager = FirewallManager::with_settings(&config.firewall)?;
+                let rules = manager.list_rules().await?;
+                let Some(rule) = index.checked_sub(1).and_then(|i| rules.get(i)) else {
+                    eprintln!(
+                        "❌ No rule #{} ({} Nova rules; see `nova firewall rules`)",
+                        index,
+                        rules.len()
+                    );
+                    std::process::exit(1);
+                };
+                manager.remove_rule(rule).await?;
+                println!("✅ Removed {}", rule.tag());
+            }
+        },
         Commands::Metrics { metrics_command } => match metrics_command {
             MetricsCommands::Serve {
                 port,
                 interval,
                 bind,
             } => {
-                if port == 0 {
+                if port == Some(0) {
                     return Err(NovaError::ConfigError(
                         "metrics --port must be greater than zero".to_string(),
                     ));
@@ -2256,22 +6229,55 @@ Volumes:"
                         "metrics --interval must be greater than zero".to_string(),
                     ));
                 }
+                let (address, bind_port) = parse_bind_addr(&bind, port.unwrap_or(9100))?;
+                if let Some(port) = port
+                    && port != bind_port
+                {
+                    return Err(NovaError::ConfigError(format!(
+                        "--bind {} and --port {} name different ports",
+                        bind, port
+                    )));
+                }
 
                 println!(
-                    "Starting Prometheus exporter on {}:{} (interval {}s). Press Ctrl+C to exit.",
-                    bind, port, interval
+                    "Serving Prometheus metrics on http://{}:{}/metrics (interval {}s). Press Ctrl+C to exit.",
+                    address, bind_port, interval
                 );
-                let exporter = PrometheusExporter::new(port)
-                    .with_bind_addr(bind)
-                    .with_collection_interval(interval);
+                let exporter = PrometheusExporter::new(bind_port)
+                    .with_bind_addr(address)
+                    .with_collection_interval(interval)
+                    .with_container_runtime(container_runtime);
                 exporter.start().await?;
             }
             MetricsCommands::Snapshot => {
-                let exporter = PrometheusExporter::new(0);
+                let exporter = PrometheusExporter::new(0).with_container_runtime(container_runtime);
                 let snapshot = exporter.collect_once().await?;
                 println!("{}", snapshot);
             }
         },
+        Commands::Preflight { fix } => run_preflight_command(fix, &config)?,
+        Commands::Config { config_command } => match config_command {
+            ConfigCommands::Check => {
+                if !config_found {
+                    println!("❌ No NovaFile at {}", config_path.display());
+                    std::process::exit(1);
+                }
+                let (_, warnings) = NovaConfig::check_file(&config_path)?;
+                if warnings.is_empty() {
+                    println!("✅ {} looks good", config_path.display());
+                } else {
+                    println!(
+                        "⚠️  {} problem(s) in {}:",
+                        warnings.len(),
+                        config_path.display()
+                    );
+                    for warning in &warnings {
+                        println!("  {}", warning);
+                    }
+                    std::process::exit(1);
+                }
+            }
+        },
         Commands::Support { support_command } => match support_command {
             SupportCommands::Bundle {
                 output,
@@ -2308,21 +6314,7 @@ Volumes:"
                     }
                 }
             }
-            SupportCommands::Preflight { fix } => {
-                let summary = preflight::run_preflight()?;
-                println!("{}", summary);
-                if !summary.is_ready() {
-                    if fix {
-                        println!("\nAttempting to fix detected issues...\n");
-                        let fix_result = preflight::run_preflight_fix(&summary)?;
-                        println!("{}", fix_result);
-                    } else {
-                        println!(
-                            "Missing prerequisites detected. Run with --fix to attempt automatic remediation."
-                        );
-                    }
-                }
-            }
+            SupportCommands::Preflight { fix } => run_preflight_command(fix, &config)?,
         },
         Commands::Optimize { optimize_command } => {
             use nova::performance::{PerformanceOptimizer, PerformanceProfile};
@@ -2378,16 +6370,32 @@ Volumes:"
             }
         }
         Commands::LookingGlass { lg_command } => {
-            use nova::looking_glass::{
-                LookingGlassConfig, LookingGlassManager, LookingGlassProfile,
-            };
+            use nova::looking_glass::{LookingGlassManager, LookingGlassProfile};
 
             let manager = LookingGlassManager::new();
 
             match lg_command {
-                LookingGlassCommands::Check => {
+                LookingGlassCommands::Check { vm } => {
                     let reqs = manager.check_system_requirements();
                     reqs.print_status();
+                    if let Some(vm) = vm {
+                        let config = LookingGlassManager::load_vm_config(&vm).unwrap_or_default();
+                        match manager.verify_shmem(&vm, &config) {
+                            Ok(check) => println!(
+                                "✅ Shared memory for '{}' fits {}x{} ({:.1} MiB needed)",
+                                vm,
+                                config.resolution.width,
+                                config.resolution.height,
+                                check.required_bytes as f64 / (1024.0 * 1024.0)
+                            ),
+                            Err(problems) => {
+                                for problem in problems.lines() {
+                                    println!("❌ {}", problem);
+                                }
+                                std::process::exit(1);
+                            }
+                        }
+                    }
                 }
                 LookingGlassCommands::Setup {
                     vm,
@@ -2446,13 +6454,17 @@ Volumes:"
                         let _ = std::fs::write(&auto_file, "1");
                         println!("Auto-connect saved to: {}", auto_file.display());
                     } else {
-                        let config = LookingGlassConfig::default();
-                        println!("Launching Looking Glass client for VM: {}", vm);
-                        tokio::runtime::Handle::current().block_on(async {
-                            if let Err(e) = manager.launch_client(&config).await {
-                                eprintln!("Failed to launch client: {}", e);
+                        let config = LookingGlassManager::load_vm_config(&vm).unwrap_or_default();
+                        if let Err(problems) = manager.verify_shmem(&vm, &config) {
+                            for problem in problems.lines() {
+                                eprintln!("❌ {}", problem);
                             }
-                        });
+                            std::process::exit(1);
+                        }
+                        println!("Launching Looking Glass client for VM: {}", vm);
+                        if let Err(e) = manager.launch_client(&config).await {
+                            eprintln!("Failed to launch client: {}", e);
+                        }
                     }
                 }
                 LookingGlassCommands::SetupHugepages { count } => {
@@ -2474,7 +6486,202 @@ Volumes:"
         }
     }
 
-    Ok(())
+    Ok(())
+}
+
+/// Exits with status 1 when a hard requirement fails, so provisioning
+/// scripts can gate on it.
+fn run_preflight_command(fix: bool, config: &NovaConfig) -> Result<()> {
+    let summary = preflight::run_preflight()?;
+    println!(
+        "Nova preflight (kernel: {}, distro: {})\n",
+        summary.kernel_release.as_deref().unwrap_or("unknown"),
+        summary.distribution.as_deref().unwrap_or("unknown")
+    );
+    let mut failed = print_preflight_checks(&summary, config);
+
+    if !summary.is_ready() {
+        if fix {
+            println!("\nAttempting to fix detected issues...\n");
+            let fix_result = preflight::run_preflight_fix(&summary)?;
+            println!("{}", fix_result);
+
+            // Judge the host as it is now, not as it was before the fixes
+            println!("\nRe-checking after fixes...\n");
+            let summary = preflight::run_preflight()?;
+            failed = print_preflight_checks(&summary, config);
+        } else {
+            println!(
+                "Missing prerequisites detected. Run with --fix to attempt automatic remediation."
+            );
+        }
+    }
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print the check table and totals for `summary`; returns the failures.
+fn print_preflight_checks(summary: &preflight::PreflightSummary, config: &NovaConfig) -> usize {
+    let mut checks = summary.checks();
+    checks.extend(preflight::hugepage_checks(config));
+    print!("{}", preflight::render_checks(&checks));
+
+    let count = |level| checks.iter().filter(|check| check.level == level).count();
+    let failed = count(preflight::CheckLevel::Fail);
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(preflight::CheckLevel::Pass),
+        count(preflight::CheckLevel::Warn),
+        failed
+    );
+    failed
+}
+
+fn print_instance_status(instance: &Instance) {
+    match instance.instance_type {
+        nova::InstanceType::Vm => {
+            println!("VM: {}", instance.name);
+            println!("Status: {:?}", instance.status);
+            println!("CPU Cores: {}", instance.cpu_cores);
+        }
+        nova::InstanceType::Container => {
+            println!("Container: {}", instance.name);
+            println!("Status: {:?}", instance.status);
+        }
+    }
+    println!("Memory: {}MB", instance.memory_mb);
+    println!(
+        "Created: {}",
+        instance.created_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    if let Some(pid) = instance.pid {
+        println!("PID: {}", pid);
+    }
+    if let Some(network) = &instance.network {
+        println!("Network: {}", network);
+    }
+}
+
+fn print_switch_details(switch: &VirtualSwitch, connected_vms: &[network::ConnectedVm]) {
+    println!("Bridge: {}", switch.name);
+    println!("  Type: {:?}", switch.switch_type);
+    println!("  Status: {:?}", switch.status);
+    println!("  Origin: {:?}", switch.origin);
+    println!("  STP Enabled: {}", switch.stp_enabled);
+    match switch.vlan_id {
+        Some(vid) => println!("  VLAN: {}", vid),
+        None => println!("  VLAN: -"),
+    }
+    match &switch.profile {
+        Some(profile) => println!("  Profile: {:?}", profile),
+        None => println!("  Profile: -"),
+    }
+    println!(
+        "  Interfaces: {}",
+        if switch.interfaces.is_empty() {
+            "-".to_string()
+        } else {
+            switch.interfaces.join(", ")
+        }
+    );
+    if !switch.trunk_ports.is_empty() {
+        println!("  Trunk Ports:");
+        for (port, vids) in &switch.trunk_ports {
+            println!("    {}: VLANs {}", port, network::format_vlan_ranges(vids));
+        }
+    }
+    if connected_vms.is_empty() {
+        println!("  Connected VMs: -");
+    } else {
+        println!("  Connected VMs:");
+        println!("    {:<20} {:<10} {:<18} IP ADDRESS", "VM", "TAP", "MAC");
+        for vm in connected_vms {
+            println!(
+                "    {:<20} {:<10} {:<18} {}",
+                vm.vm,
+                vm.tap.as_deref().unwrap_or("-"),
+                vm.mac,
+                vm.ip_address.as_deref().unwrap_or("-")
+            );
+        }
+    }
+}
+
+fn print_ovs_details(network_manager: &NetworkManager, bridge: &str) {
+    match network_manager.ovs_ports(bridge) {
+        Ok(ports) if ports.is_empty() => println!("  OVS Ports: -"),
+        Ok(ports) => println!("  OVS Ports: {}", ports.join(", ")),
+        Err(err) => println!("  OVS Ports: unavailable ({})", err),
+    }
+    match network_manager.ovs_flows(bridge) {
+        Ok(flows) => {
+            let tables: std::collections::BTreeSet<u8> =
+                flows.iter().map(|flow| flow.table).collect();
+            let packets: u64 = flows.iter().map(|flow| flow.n_packets).sum();
+            println!(
+                "  OpenFlow: {} flow(s) in {} table(s), {} packets matched (`nova network ovs flows {}`)",
+                flows.len(),
+                tables.len(),
+                packets,
+                bridge
+            );
+        }
+        Err(err) => println!("  OpenFlow: unavailable ({})", err),
+    }
+}
+
+fn print_interface_details(iface: &NetworkInterface, persistent: &PersistentNames) {
+    println!("Interface: {}", iface.name);
+    println!("  State: {:?}", iface.state);
+    println!("  MAC: {}", iface.mac_address);
+    println!("  Alias: {}", iface.alias.as_deref().unwrap_or("-"));
+    if !iface.altnames.is_empty() {
+        println!("  Alternative Names: {}", iface.altnames.join(", "));
+    }
+    println!(
+        "  Persistent Name (PCI): {}",
+        persistent.pci.as_deref().unwrap_or("-")
+    );
+    println!(
+        "  Persistent Name (MAC): {}",
+        persistent.mac.as_deref().unwrap_or("-")
+    );
+    if let Some(ip) = iface.ip_address {
+        println!("  IPv4: {}", ip);
+    }
+    if let Some(bridge) = &iface.bridge {
+        println!("  Attached Bridge: {}", bridge);
+    }
+    if let Some(speed) = iface.speed {
+        println!("  Speed: {} Mbps", speed);
+    }
+}
+
+fn print_storage_pool(name: &str, pool: &StoragePoolConfig) {
+    println!("Storage Pool: {}", name);
+    println!("  Type: {}", pool.pool_type.as_str());
+    println!("  Directory: {}", pool.directory);
+    if let Some(description) = &pool.description {
+        println!("  Description: {}", description);
+    }
+    if let Some(capacity) = &pool.capacity {
+        println!("  Capacity: {}", capacity);
+    }
+    println!("  Default Format: {}", pool.default_format.as_str());
+    println!(
+        "  Auto Create: {}",
+        if pool.auto_create { "yes" } else { "no" }
+    );
+    println!(
+        "  Labels: {}",
+        if pool.labels.is_empty() {
+            "-".to_string()
+        } else {
+            pool.labels.join(", ")
+        }
+    );
 }
 
 fn print_gpu_info(
@@ -2501,6 +6708,24 @@ fn print_gpu_info(
 
     println!("  In Use: {}", if gpu.in_use { "Yes" } else { "No" });
 
+    if gpu.mdev_types.is_empty() {
+        println!("  vGPU Profiles: None (no mdev-capable driver bound)");
+    } else {
+        println!(
+            "  vGPU Profiles: {} (details: nova gpu mdev list {})",
+            gpu.mdev_types.len(),
+            gpu.address
+        );
+        for mdev_type in &gpu.mdev_types {
+            println!(
+                "    {} - {} ({} available)",
+                mdev_type.id,
+                mdev_type.display_name(),
+                mdev_type.available_instances
+            );
+        }
+    }
+
     if let Some(caps) = caps {
         if let Some(generation) = caps.generation.as_ref() {
             println!("  Generation: {}", generation);
@@ -2525,7 +6750,113 @@ fn print_gpu_info(
         if caps.tcc_supported {
             println!("  TCC Mode: Supported (recommended for Looking Glass)");
         }
+
+        if let Some(ref mig) = caps.mig_mode {
+            println!("  MIG Mode: {}", mig);
+        }
+    }
+}
+
+/// GPU entry emitted by `nova gpu list --json`
+#[derive(Debug, serde::Serialize)]
+struct GpuListEntry {
+    address: String,
+    vendor_id: String,
+    device_id: String,
+    vendor_name: String,
+    device_name: String,
+    iommu_group: Option<u32>,
+    driver: Option<String>,
+    in_use: bool,
+    reserved_for: Option<String>,
+    status: String,
+}
+
+/// IOMMU group entry emitted by `nova gpu list --json --by-group`
+#[derive(Debug, serde::Serialize)]
+struct GpuGroupEntry {
+    iommu_group: Option<u32>,
+    isolated: Option<bool>,
+    viable_for_passthrough: Option<bool>,
+    devices: Vec<GpuListEntry>,
+}
+
+fn gpu_list_status(gpu: &PciDevice, reservations: &HashMap<String, String>) -> String {
+    if let Some(vm) = reservations.get(&gpu.address) {
+        format!("reserved ({})", vm)
+    } else if gpu.driver.as_deref() == Some("vfio-pci") {
+        "vfio".to_string()
+    } else if let Some(driver) = &gpu.driver {
+        if gpu.in_use {
+            format!("host ({driver})")
+        } else {
+            format!("available ({driver})")
+        }
+    } else {
+        "unbound".to_string()
+    }
+}
+
+fn gpu_status_tone(gpu: &PciDevice, reservations: &HashMap<String, String>) -> Tone {
+    if reservations.contains_key(&gpu.address) {
+        Tone::Warning
+    } else if gpu.driver.as_deref() == Some("vfio-pci") || !gpu.in_use && gpu.driver.is_some() {
+        Tone::Good
+    } else {
+        Tone::Muted
+    }
+}
+
+fn build_gpu_list_entries(
+    gpus: &[PciDevice],
+    reservations: &HashMap<String, String>,
+) -> Vec<GpuListEntry> {
+    gpus.iter()
+        .map(|gpu| GpuListEntry {
+            address: gpu.address.clone(),
+            vendor_id: gpu.vendor_id.clone(),
+            device_id: gpu.device_id.clone(),
+            vendor_name: gpu.vendor_name.clone(),
+            device_name: gpu.device_name.clone(),
+            iommu_group: gpu.iommu_group,
+            driver: gpu.driver.clone(),
+            in_use: gpu.in_use,
+            reserved_for: reservations.get(&gpu.address).cloned(),
+            status: gpu_list_status(gpu, reservations),
+        })
+        .collect()
+}
+
+fn build_gpu_group_entries(
+    gpus: &[PciDevice],
+    groups: &[nova::gpu_passthrough::IommuGroup],
+    reservations: &HashMap<String, String>,
+) -> Vec<GpuGroupEntry> {
+    let mut grouped: Vec<GpuGroupEntry> = Vec::new();
+
+    for entry in build_gpu_list_entries(gpus, reservations) {
+        if let Some(existing) = grouped
+            .iter_mut()
+            .find(|group| group.iommu_group == entry.iommu_group)
+        {
+            existing.devices.push(entry);
+            continue;
+        }
+
+        let info = entry
+            .iommu_group
+            .and_then(|id| groups.iter().find(|group| group.id == id));
+        grouped.push(GpuGroupEntry {
+            iommu_group: entry.iommu_group,
+            isolated: info.map(|group| group.isolated),
+            viable_for_passthrough: info.map(|group| group.viable_for_passthrough),
+            devices: vec![entry],
+        });
     }
+
+    // Ungrouped devices (IOMMU disabled) sort last
+    grouped.sort_by_key(|group| (group.iommu_group.is_none(), group.iommu_group));
+    grouped
 }
 
 fn binding_state_label(info: &DeviceBindingInfo) -> String {
@@ -2629,40 +6960,111 @@ fn build_gpu_status_output(gpus: &[PciDevice], reservations: &HashMap<String, St
     buffer
 }
 
-fn parse_size(size_str: &str) -> Result<u64> {
-    let size_str = size_str.trim().to_uppercase();
-
-    // Extract number and unit
-    let (num_str, unit) = if size_str.ends_with("TB") || size_str.ends_with("TIB") {
-        (
-            &size_str[..size_str.len() - if size_str.ends_with("TB") { 2 } else { 3 }],
-            1_099_511_627_776u64,
-        )
-    } else if size_str.ends_with("GB") || size_str.ends_with("GIB") {
-        (
-            &size_str[..size_str.len() - if size_str.ends_with("GB") { 2 } else { 3 }],
-            1_073_741_824u64,
-        )
-    } else if size_str.ends_with("MB") || size_str.ends_with("MIB") {
-        (
-            &size_str[..size_str.len() - if size_str.ends_with("MB") { 2 } else { 3 }],
-            1_048_576u64,
-        )
-    } else if size_str.ends_with('T') {
-        (&size_str[..size_str.len() - 1], 1_099_511_627_776u64)
-    } else if size_str.ends_with('G') {
-        (&size_str[..size_str.len() - 1], 1_073_741_824u64)
-    } else if size_str.ends_with('M') {
-        (&size_str[..size_str.len() - 1], 1_048_576u64)
+fn format_snapshot_quota_usage(quota: &SnapshotQuota, count: usize, total_bytes: u64) -> String {
+    let mut parts = Vec::new();
+    if let Some(max) = quota.max_snapshots {
+        parts.push(format!("{}/{} snapshots", count, max));
+    }
+    if let Some(max) = quota.max_snapshot_bytes {
+        parts.push(format!(
+            "{:.1}/{:.1} GB",
+            total_bytes as f64 / 1_073_741_824.0,
+            max as f64 / 1_073_741_824.0
+        ));
+    }
+    if quota.auto_prune {
+        parts.push("auto-prune".to_string());
+    }
+    parts.join(", ")
+}
+
+fn print_memory_layout(layout: &MemoryLayout) {
+    println!("   Memory: {} MiB", layout.memory_kib / 1024);
+    if let Some(max_kib) = layout.max_memory_kib {
+        println!(
+            "   Max Memory: {} MiB ({} MiB free)",
+            max_kib / 1024,
+            layout.headroom_kib() / 1024
+        );
+    }
+    println!(
+        "   DIMM Slots: {} of {} used",
+        layout.dimms.len(),
+        layout.slots
+    );
+}
+
+/// `docker stats`-style table, one row per container.
+fn render_container_stats(rows: &[(String, ContainerStats)]) -> String {
+    let mut out = format!(
+        "{:<20} {:>7} {:>21} {:>7} {:>21} {:>21}\n",
+        "NAME", "CPU %", "MEM USAGE / LIMIT", "MEM %", "NET I/O", "BLOCK I/O"
+    );
+    for (name, stats) in rows {
+        let memory_percent = if stats.memory_limit_mb > 0 {
+            stats.memory_usage_mb as f64 / stats.memory_limit_mb as f64 * 100.0
+        } else {
+            0.0
+        };
+        let pair = |a: u64, b: u64| format!("{} / {}", format_bytes(a), format_bytes(b));
+        let _ = writeln!(
+            out,
+            "{:<20} {:>6.2}% {:>21} {:>6.2}% {:>21} {:>21}",
+            name,
+            stats.cpu_usage_percent,
+            pair(
+                stats.memory_usage_mb * 1024 * 1024,
+                stats.memory_limit_mb * 1024 * 1024
+            ),
+            memory_percent,
+            pair(stats.network_rx_bytes, stats.network_tx_bytes),
+            pair(stats.disk_read_bytes, stats.disk_write_bytes),
+        );
+    }
+    out
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let value = bytes as f64;
+    if value >= KB * KB * KB {
+        format!("{:.1} GiB", value / (KB * KB * KB))
+    } else if value >= KB * KB {
+        format!("{:.1} MiB", value / (KB * KB))
+    } else if value >= KB {
+        format!("{:.1} KiB", value / KB)
     } else {
-        (size_str.as_str(), 1u64)
+        format!("{bytes} B")
+    }
+}
+
+fn parse_size(size_str: &str) -> Result<u64> {
+    let size_str = size_str.trim();
+    let split = size_str
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(size_str.len());
+    let (num_str, suffix) = size_str.split_at(split);
+
+    // Binary multiples throughout, so K = KB = KiB
+    let unit: u64 = match suffix.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        other => {
+            return Err(NovaError::ConfigError(format!(
+                "Unknown size unit '{}' in '{}' (use K, M, G or T)",
+                other, size_str
+            )));
+        }
     };
 
     let num: f64 = num_str
         .parse()
         .map_err(|_| NovaError::ConfigError(format!("Invalid size: {}", size_str)))?;
 
-    Ok((num * unit as f64) as u64)
+    Ok((num * unit as f64).round() as u64)
 }
 
 fn handle_vm_wizard(
@@ -2670,38 +7072,51 @@ fn handle_vm_wizard(
     config: &NovaConfig,
     default_output: &PathBuf,
 ) -> Result<()> {
-    ensure_valid_vm_name(&args.name)?;
+    naming::validate(NameKind::Vm, &args.name)?;
     apply_wizard_preset_defaults(&mut args);
 
-    let selected_network = resolve_wizard_network(&args.name, args.network.clone(), config)?;
-    args.network = Some(selected_network.clone());
+    let selected_networks = if args.networks.is_empty() {
+        vec![resolve_wizard_network(&args.name, None, config)?]
+    } else {
+        args.networks
+            .iter()
+            .map(|network| resolve_wizard_network(&args.name, Some(network.clone()), config))
+            .collect::<Result<Vec<_>>>()?
+    };
+    args.networks = selected_networks.clone();
+
+    let seed_iso = match &args.cloud_init {
+        Some(user_data) => {
+            cloud_init::validate_user_data(user_data)?;
+            Some(cloud_init::seed_iso_path(
+                &args.name,
+                Path::new(&wizard_image_path(&args)),
+            ))
+        }
+        None => None,
+    };
 
-    let mut snippet = build_vm_wizard_snippet(&args, &selected_network);
+    let mut snippet = build_vm_wizard_snippet(&args, &selected_networks, seed_iso.as_deref());
 
     if !args.apply {
-        println!("# NovaFile snippet (dry-run)\n");
-        println!("{}", snippet.trim_end());
-        let guidance = match &args.output {
-            Some(path) => format!(" --output {}", path.display()),
-            None => format!(" (default writes to {})", default_output.display()),
-        };
-        println!(
-            "\nRun again with --apply{} to persist this entry.",
-            guidance
-        );
+        print_wizard_dry_run(&snippet, args.output.as_deref(), default_output);
+        if let Some(seed) = &seed_iso {
+            println!(
+                "The cloud-init seed is built at {} on --apply.",
+                seed.display()
+            );
+        }
         return Ok(());
     }
 
-    let target_path = args
-        .output
-        .clone()
-        .unwrap_or_else(|| default_output.clone());
-
-    let existing_content = if target_path.exists() {
-        Some(std::fs::read_to_string(&target_path)?)
-    } else {
-        None
-    };
+    if let (Some(user_data), Some(seed)) = (&args.cloud_init, &seed_iso) {
+        let instance_id = cloud_init::build_seed_iso(&args.name, user_data, seed)?;
+        println!(
+            "💿 cloud-init seed written to {} (instance-id {})",
+            seed.display(),
+            instance_id
+        );
+    }
 
     if let Some(VmPreset::Windows11) = args.preset {
         snippet.push_str("compliance_profile = \"windows11\"\n");
@@ -2714,19 +7129,186 @@ fn handle_vm_wizard(
         snippet.push_str("version = \"v2-0\"\n\n");
     }
 
-    if let Some(content) = &existing_content {
-        if content.contains(&format!("[vm.{}]", args.name)) {
-            println!(
-                "❌ VM '{}' already exists in {}. Remove it first or update manually.",
-                args.name,
-                target_path.display()
-            );
-            return Ok(());
+    append_wizard_snippet(
+        "VM",
+        &format!("[vm.{}]", args.name),
+        &args.name,
+        config.vm.contains_key(&args.name),
+        &snippet,
+        args.output.as_ref(),
+        default_output,
+    )
+}
+
+fn handle_container_wizard(
+    mut args: WizardContainerArgs,
+    config: &NovaConfig,
+    default_output: &PathBuf,
+) -> Result<()> {
+    naming::validate(NameKind::Container, &args.name)?;
+
+    if args.image.is_none() {
+        args.image = Some(prompt_wizard_image(&args.name)?);
+    }
+    for port in &args.ports {
+        container_ports::parse_port_spec(port)?;
+    }
+    for volume in &args.volumes {
+        if volume.split(':').filter(|part| !part.is_empty()).count() < 2 {
+            return Err(NovaError::ConfigError(format!(
+                "Invalid volume '{}' (expected host:container, e.g. ./data:/data)",
+                volume
+            )));
         }
-    } else if target_path == *default_output && config.vm.contains_key(&args.name) {
+    }
+    let env = parse_wizard_env(&args.env)?;
+    if let Some(network) = &args.network
+        && !config.network.is_empty()
+        && !config.network.contains_key(network)
+    {
+        println!(
+            "⚠️  Network '{}' isn't defined in the current NovaFile, continuing with it anyway.",
+            network
+        );
+    }
+
+    let snippet = build_container_wizard_snippet(&args, &env);
+
+    if !args.apply {
+        print_wizard_dry_run(&snippet, args.output.as_deref(), default_output);
+        return Ok(());
+    }
+
+    append_wizard_snippet(
+        "Container",
+        &format!("[container.{}]", args.name),
+        &args.name,
+        config.container.contains_key(&args.name),
+        &snippet,
+        args.output.as_ref(),
+        default_output,
+    )
+}
+
+/// Open `path` in `$VISUAL` or `$EDITOR` (vi without either) and wait for
+/// the editor to exit.
+fn edit_file(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Through the shell, so editors with arguments like `code --wait` work
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .map_err(|err| NovaError::ConfigError(format!("Failed to run '{}': {}", editor, err)))?;
+    if !status.success() {
+        return Err(NovaError::ConfigError(format!(
+            "Editor '{}' exited with {}; nothing was changed",
+            editor, status
+        )));
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal. Without a terminal to ask on,
+/// the caller's `--yes` is required.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::IsTerminal;
+    if !io::stdin().is_terminal() {
+        return Err(NovaError::ConfigError(
+            "Refusing to continue without confirmation; pass --yes".to_string(),
+        ));
+    }
+    print!("{} [y/N]: ", question);
+    io::stdout().flush().ok();
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|err| NovaError::ConfigError(format!("Failed to read answer: {}", err)))?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn prompt_wizard_image(name: &str) -> Result<String> {
+    print!("Image for container '{}' [ubuntu:latest]: ", name);
+    io::stdout().flush().ok();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|err| NovaError::ConfigError(format!("Failed to read image: {}", err)))?;
+    let image = input.trim();
+    Ok(if image.is_empty() {
+        "ubuntu:latest".to_string()
+    } else {
+        image.to_string()
+    })
+}
+
+/// `KEY=VALUE` pairs in the order given.
+fn parse_wizard_env(values: &[String]) -> Result<Vec<(String, String)>> {
+    let valid_key = |key: &str| {
+        !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    values
+        .iter()
+        .map(|value| match value.split_once('=') {
+            Some((key, value)) if valid_key(key) => Ok((key.to_string(), value.to_string())),
+            _ => Err(NovaError::ConfigError(format!(
+                "Invalid environment variable '{}' (expected KEY=VALUE)",
+                value
+            ))),
+        })
+        .collect()
+}
+
+fn print_wizard_dry_run(snippet: &str, output: Option<&Path>, default_output: &Path) {
+    println!("# NovaFile snippet (dry-run)\n");
+    println!("{}", snippet.trim_end());
+    let guidance = match output {
+        Some(path) => format!(" --output {}", path.display()),
+        None => format!(" (default writes to {})", default_output.display()),
+    };
+    println!(
+        "\nRun again with --apply{} to persist this entry.",
+        guidance
+    );
+}
+
+/// Append a generated entry to the NovaFile, refusing to add a second
+/// `section` with the same name.
+fn append_wizard_snippet(
+    kind: &str,
+    section: &str,
+    name: &str,
+    in_loaded_config: bool,
+    snippet: &str,
+    output: Option<&PathBuf>,
+    default_output: &PathBuf,
+) -> Result<()> {
+    let target_path = output.cloned().unwrap_or_else(|| default_output.clone());
+
+    let existing_content = if target_path.exists() {
+        Some(std::fs::read_to_string(&target_path)?)
+    } else {
+        None
+    };
+
+    let already_defined = match &existing_content {
+        Some(content) => content.contains(section),
+        None => target_path == *default_output && in_loaded_config,
+    };
+    if already_defined {
         println!(
-            "❌ VM '{}' already exists in {}. Remove it first or update manually.",
-            args.name,
+            "❌ {} '{}' already exists in {}. Remove it first or update manually.",
+            kind,
+            name,
             target_path.display()
         );
         return Ok(());
@@ -2747,14 +7329,14 @@ fn handle_vm_wizard(
         new_content.push('\n');
     }
 
-    new_content.push_str(&snippet);
+    new_content.push_str(snippet);
     if !snippet.ends_with('\n') {
         new_content.push('\n');
     }
 
     std::fs::write(&target_path, new_content)?;
 
-    println!("✅ Added VM '{}' to {}", args.name, target_path.display());
+    println!("✅ Added {} '{}' to {}", kind, name, target_path.display());
 
     Ok(())
 }
@@ -2869,11 +7451,18 @@ fn resolve_wizard_network(
     }
 }
 
-fn build_vm_wizard_snippet(args: &WizardVmArgs, network: &str) -> String {
-    let image_path = args
-        .image
+fn wizard_image_path(args: &WizardVmArgs) -> String {
+    args.image
         .clone()
-        .unwrap_or_else(|| format!("/var/lib/nova/images/{}.qcow2", args.name));
+        .unwrap_or_else(|| format!("/var/lib/nova/images/{}.qcow2", args.name))
+}
+
+fn build_vm_wizard_snippet(
+    args: &WizardVmArgs,
+    networks: &[String],
+    seed_iso: Option<&Path>,
+) -> String {
+    let image_path = wizard_image_path(args);
     let cpu = args.resolved_cpu();
     let memory = args.resolved_memory();
 
@@ -2887,28 +7476,203 @@ fn build_vm_wizard_snippet(args: &WizardVmArgs, network: &str) -> String {
         "gpu_passthrough = {}\n",
         if args.gpu { "true" } else { "false" }
     ));
-    snippet.push_str(&format!("network = \"{}\"\n", network));
+    let quoted: Vec<String> = networks
+        .iter()
+        .map(|network| format!("\"{}\"", network))
+        .collect();
+    match quoted.as_slice() {
+        [network] => snippet.push_str(&format!("network = {}\n", network)),
+        _ => snippet.push_str(&format!("network = [{}]\n", quoted.join(", "))),
+    }
     snippet.push_str(&format!(
         "autostart = {}\n",
         if args.autostart { "true" } else { "false" }
     ));
     snippet.push('\n');
+
+    if let Some(seed) = seed_iso {
+        snippet.push_str(&format!("[[vm.{}.disks]]\n", args.name));
+        snippet.push_str("type = \"cdrom\"\n");
+        snippet.push_str(&format!("path = \"{}\"\n\n", seed.display()));
+    }
     snippet
 }
 
-fn ensure_valid_vm_name(name: &str) -> Result<()> {
-    let valid = name
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+fn build_container_wizard_snippet(args: &WizardContainerArgs, env: &[(String, String)]) -> String {
+    let string = |value: &str| toml::Value::String(value.to_string()).to_string();
+    let list = |values: &[String]| {
+        let items: Vec<String> = values.iter().map(|value| string(value)).collect();
+        format!("[{}]", items.join(", "))
+    };
+    let image = args.image.as_deref().unwrap_or("ubuntu:latest");
 
-    if !valid {
-        return Err(NovaError::ConfigError(format!(
-            "VM name '{}' contains unsupported characters. Use letters, numbers, '-' or '_'.",
-            name
-        )));
+    let mut snippet = String::new();
+    snippet.push_str("# Generated with `nova wizard container`\n");
+    snippet.push_str(&format!("[container.{}]\n", args.name));
+    snippet.push_str(&format!("capsule = {}\n", string(image)));
+    if !args.ports.is_empty() {
+        snippet.push_str(&format!("ports = {}\n", list(&args.ports)));
+    }
+    if !args.volumes.is_empty() {
+        snippet.push_str(&format!("volumes = {}\n", list(&args.volumes)));
+    }
+    if let Some(network) = &args.network {
+        snippet.push_str(&format!("network = {}\n", string(network)));
+    }
+    snippet.push_str(&format!(
+        "autostart = {}\n",
+        if args.autostart { "true" } else { "false" }
+    ));
+    if let Some(restart) = args.restart {
+        snippet.push_str(&format!(
+            "restart = {}\n",
+            string(RestartPolicy::from(restart).as_str())
+        ));
+    }
+    snippet.push('\n');
+
+    if !env.is_empty() {
+        snippet.push_str(&format!("[container.{}.env]\n", args.name));
+        for (key, value) in env {
+            snippet.push_str(&format!("{} = {}\n", key, string(value)));
+        }
+        snippet.push('\n');
     }
 
-    Ok(())
+    if args.gpu {
+        snippet.push_str(&format!("[container.{}.bolt]\n", args.name));
+        snippet.push_str("gpu_access = true\n\n");
+    }
+    snippet
+}
+
+fn instance_selector(patterns: Vec<String>, all: bool, labels: &[String]) -> Result<Selector> {
+    Ok(Selector {
+        patterns,
+        all,
+        labels: labels
+            .iter()
+            .map(|spec| LabelSelector::parse(spec))
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// The container manager, built on first use so commands that never touch
+/// containers neither probe for a runtime nor fail when the selected one is
+/// missing.
+struct LazyContainerManager {
+    runtime: Option<RuntimeKind>,
+    scaling: ScalingThresholds,
+    crash_loop: CrashLoopPolicy,
+    manager: OnceCell<ContainerManager>,
+}
+
+impl LazyContainerManager {
+    fn new(runtime: Option<RuntimeKind>, config: &NovaConfig) -> Self {
+        Self {
+            runtime,
+            scaling: config.container_scaling.clone(),
+            crash_loop: config.container_crash_loop.clone(),
+            manager: OnceCell::new(),
+        }
+    }
+
+    fn get(&self) -> Result<&ContainerManager> {
+        if let Some(manager) = self.manager.get() {
+            return Ok(manager);
+        }
+        let mut manager = ContainerManager::with_runtime(self.runtime)?;
+        manager.set_scaling_thresholds(self.scaling.clone());
+        manager.set_crash_loop_policy(self.crash_loop.clone());
+        Ok(self.manager.get_or_init(|| manager))
+    }
+
+    fn get_mut(&mut self) -> Result<&mut ContainerManager> {
+        self.get()?;
+        Ok(self.manager.get_mut().expect("initialized by get"))
+    }
+
+    /// Containers for commands that also cover VMs: a missing runtime is
+    /// reported but does not fail the command.
+    async fn list_or_warn(&self) -> Vec<Instance> {
+        match self.get() {
+            Ok(manager) => manager.list_containers_async().await,
+            Err(err) => {
+                logger::warn!("{}", err);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Instances a `nova run`/`nova stop` selection applies to, picked from the
+/// NovaFile entries and whatever the host already knows about.
+async fn select_instances(
+    instance_type: &InstanceType,
+    selector: &Selector,
+    config: &NovaConfig,
+    vm_manager: &VmManager,
+    container_manager: &LazyContainerManager,
+) -> Result<Vec<String>> {
+    match instance_type {
+        InstanceType::Vm => {
+            vm_manager.refresh_libvirt_states();
+            let vms = vm_manager.list_vms();
+            let known = config
+                .vm
+                .iter()
+                .map(|(name, vm)| (name.as_str(), Some(&vm.labels)))
+                .chain(vms.iter().map(|vm| (vm.name.as_str(), None)));
+            selector.select(nova::instance::InstanceType::Vm, known)
+        }
+        InstanceType::Container => {
+            let containers = container_manager.get()?.list_containers_async().await;
+            let known = config
+                .container
+                .iter()
+                .map(|(name, container)| (name.as_str(), Some(&container.labels)))
+                .chain(
+                    containers
+                        .iter()
+                        .map(|container| (container.name.as_str(), None)),
+                );
+            selector.select(nova::instance::InstanceType::Container, known)
+        }
+    }
+}
+
+/// Refuse to start a VM Nova created as a linked clone when a file in its
+/// backing chain is gone. Problems running the check itself only warn.
+fn ensure_linked_clone_intact(name: &str) -> Result<()> {
+    let Some(source) = linked_clone_source(Path::new(TEMPLATES_DIR), name) else {
+        return Ok(());
+    };
+    let chains = match SnapshotManager::new(PathBuf::from(TEMPLATES_DIR))
+        .and_then(|manager| manager.validate_clone(name))
+    {
+        Ok(chains) => chains,
+        Err(err) => {
+            logger::warn!("Could not verify linked clone '{}': {}", name, err);
+            return Ok(());
+        }
+    };
+    match chains.iter().find_map(|chain| {
+        chain
+            .missing
+            .as_ref()
+            .map(|missing| (&chain.target, missing))
+    }) {
+        Some((target, missing)) => Err(NovaError::ConfigError(format!(
+            "Linked clone '{}' (from '{}') cannot start: backing file {} of {} is missing; \
+             run `nova clone verify {}` for the full chain",
+            name,
+            source,
+            missing.display(),
+            target,
+            name
+        ))),
+        None => Ok(()),
+    }
 }
 
 fn check_kvm_available() -> bool {
@@ -2946,6 +7710,7 @@ mod gpu_cli_tests {
             iommu_group: Some(1),
             driver: driver.map(|d| d.to_string()),
             in_use,
+            mdev_types: Vec::new(),
         }
     }
 
@@ -2975,6 +7740,32 @@ mod gpu_cli_tests {
         );
     }
 
+    #[test]
+    fn gpu_json_groups_devices_and_reports_reservations() {
+        let mut second = sample_gpu("0000:01:00.1", Some("vfio-pci"), false);
+        second.iommu_group = Some(1);
+        let mut other = sample_gpu("0000:02:00.0", None, false);
+        other.iommu_group = Some(0);
+        let gpus = vec![
+            sample_gpu("0000:01:00.0", Some("nvidia"), true),
+            second,
+            other,
+        ];
+        let mut reservations = HashMap::new();
+        reservations.insert("0000:01:00.1".to_string(), "vm-dev".to_string());
+
+        let groups = build_gpu_group_entries(&gpus, &[], &reservations);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].iommu_group, Some(0));
+        assert_eq!(groups[1].devices.len(), 2);
+
+        let payload = serde_json::to_value(&groups).unwrap();
+        let reserved = &payload[1]["devices"][1];
+        assert_eq!(reserved["reserved_for"], "vm-dev");
+        assert_eq!(reserved["status"], "reserved (vm-dev)");
+        assert_eq!(payload[0]["devices"][0]["status"], "unbound");
+    }
+
     #[test]
     fn status_output_handles_no_matches() {
         let output = build_gpu_status_output(&[], &HashMap::new());
@@ -2994,3 +7785,61 @@ mod gpu_cli_tests {
         assert!(output.contains("quick-fix unbind"));
     }
 }
+
+#[cfg(test)]
+mod wizard_tests {
+    use super::*;
+
+    #[test]
+    fn container_snippet_parses_back_into_the_config() {
+        let args = WizardContainerArgs {
+            name: "web".to_string(),
+            image: Some("nginx:latest".to_string()),
+            ports: vec!["8080:80".to_string(), "5353:53/udp".to_string()],
+            volumes: vec!["./site:/usr/share/nginx/html:ro".to_string()],
+            env: Vec::new(),
+            network: Some("nova-net".to_string()),
+            gpu: true,
+            autostart: true,
+            restart: Some(RestartPolicyArg::UnlessStopped),
+            apply: false,
+            output: None,
+        };
+        let env =
+            parse_wizard_env(&["GREETING=say \"hi\"".to_string(), "EMPTY=".to_string()]).unwrap();
+        assert!(parse_wizard_env(&["1BAD=x".to_string()]).is_err());
+        assert!(parse_wizard_env(&["NOVALUE".to_string()]).is_err());
+
+        let snippet = build_container_wizard_snippet(&args, &env);
+        assert!(snippet.starts_with("# Generated with `nova wizard container`\n[container.web]\n"));
+
+        let config: NovaConfig = toml::from_str(&snippet).unwrap();
+        let container = &config.container["web"];
+        assert_eq!(container.capsule.as_deref(), Some("nginx:latest"));
+        assert_eq!(container.ports, args.ports);
+        assert_eq!(container.volumes, args.volumes);
+        assert_eq!(container.network.as_deref(), Some("nova-net"));
+        assert_eq!(container.env["GREETING"], "say \"hi\"");
+        assert_eq!(container.env["EMPTY"], "");
+        assert!(container.autostart && container.bolt.gpu_access);
+        assert_eq!(container.restart, Some(RestartPolicy::UnlessStopped));
+    }
+}
+
+#[cfg(test)]
+mod parse_size_tests {
+    use super::*;
+
+    #[test]
+    fn units_and_fractions() {
+        assert_eq!(parse_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("1.5G").unwrap(), 1_610_612_736);
+        assert_eq!(parse_size("2TiB").unwrap(), 2 * 1_099_511_627_776);
+        assert_eq!(parse_size("0.3K").unwrap(), 307);
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+
+        let err = parse_size("10XB").unwrap_err().to_string();
+        assert!(err.contains("Unknown size unit 'XB'"), "{}", err);
+        assert!(parse_size("G").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Shared access wrapper for the managers driven by the GUI.
+//!
+//! # Locking discipline
+//!
+//! The GUI owns its managers behind `std::sync::Mutex` and drives their async
+//! APIs by calling `block_on` on the UI thread. Two patterns have caused
+//! deadlocks in the past:
+//!
+//! * holding a guard and then calling a helper that locks the same manager
+//!   again (e.g. a user action that triggers a refresh before returning), and
+//! * blocking the UI thread on a lock that a background refresh holds while it
+//!   waits on I/O.
+//!
+//! [`ManagerHandle`] centralizes access so those mistakes cannot be written:
+//!
+//! * Every accessor scopes the guard to a closure, so it is always released
+//!   before control returns to the caller.
+//! * UI code uses [`ManagerHandle::try_with`] / [`ManagerHandle::block_on`],
+//!   which use `try_lock` and report [`ManagerAccessError::Busy`] instead of
+//!   waiting. Re-entrant access from the same thread is reported as busy rather
+//!   than hanging the UI.
+//! * Background workers that may wait use [`ManagerHandle::with`].
+//!
+//! A poisoned mutex is recovered rather than propagated: managers hold cached
+//! discovery state that is safe to keep using after a panic elsewhere.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use tokio::runtime::Handle;
+
+/// Error returned when a manager cannot be accessed without blocking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManagerAccessError {
+    Busy(&'static str),
+}
+
+impl fmt::Display for ManagerAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManagerAccessError::Busy(name) => write!(f, "{} is currently busy", name),
+        }
+    }
+}
+
+impl std::error::Error for ManagerAccessError {}
+
+/// Cloneable handle to a manager shared between the UI and background work.
+pub struct ManagerHandle<T> {
+    name: &'static str,
+    inner: Arc<Mutex<T>>,
+}
+
+impl<T> Clone for ManagerHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> ManagerHandle<T> {
+    /// Wrap a manager. `name` is used in busy feedback (e.g. "Network manager").
+    pub fn new(name: &'static str, manager: T) -> Self {
+        Self::from_shared(name, Arc::new(Mutex::new(manager)))
+    }
+
+    /// Wrap an existing shared manager.
+    pub fn from_shared(name: &'static str, inner: Arc<Mutex<T>>) -> Self {
+        Self { name, inner }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Underlying shared mutex, for components that still take `Arc<Mutex<T>>`.
+    pub fn shared(&self) -> Arc<Mutex<T>> {
+        Arc::clone(&self.inner)
+    }
+
+    /// Run `f` against the manager if it is free, without blocking.
+    pub fn try_with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, ManagerAccessError> {
+        let mut guard = self.try_guard()?;
+        Ok(f(&mut guard))
+    }
+
+    /// Run `f` against the manager, waiting for the lock. Not for the UI thread.
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut guard = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut guard)
+    }
+
+    /// Drive an async manager operation to completion on `runtime`.
+    ///
+    /// The lock is taken with `try_lock` and released before this returns. The
+    /// future runs synchronously on the calling thread, so the guard never
+    /// migrates between threads while it is held.
+    pub fn block_on<R>(
+        &self,
+        runtime: &Handle,
+        f: impl AsyncFnOnce(&mut T) -> R,
+    ) -> Result<R, ManagerAccessError> {
+        let mut guard = self.try_guard()?;
+        Ok(runtime.block_on(f(&mut guard)))
+    }
+
+    fn try_guard(&self) -> Result<MutexGuard<'_, T>, ManagerAccessError> {
+        match self.inner.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Ok(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => Err(ManagerAccessError::Busy(self.name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FakeManager {
+        refreshes: u32,
+        actions: u32,
+    }
+
+    impl FakeManager {
+        async fn refresh(&mut self) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            self.refreshes += 1;
+        }
+    }
+
+    #[test]
+    fn reentrant_access_reports_busy_instead_of_deadlocking() {
+        let handle = ManagerHandle::new("Fake manager", FakeManager::default());
+        let inner = handle.clone();
+
+        let nested = handle.with(|_| inner.try_with(|manager| manager.actions += 1));
+
+        assert_eq!(nested, Err(ManagerAccessError::Busy("Fake manager")));
+        assert_eq!(
+            nested.unwrap_err().to_string(),
+            "Fake manager is currently busy"
+        );
+        assert!(handle.try_with(|_| ()).is_ok());
+    }
+
+    #[test]
+    fn concurrent_refresh_and_user_action_do_not_deadlock() {
+        let handle = ManagerHandle::new("Fake manager", FakeManager::default());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (done_tx, done_rx) = mpsc::channel();
+
+        let refresher = {
+            let handle = handle.clone();
+            let runtime = runtime.handle().clone();
+            thread::spawn(move || {
+                for _ in 0..50 {
+                    while handle
+                        .block_on(&runtime, async |manager| manager.refresh().await)
+                        .is_err()
+                    {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let ui = {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                let mut applied = 0;
+                while applied < 50 {
+                    if handle.try_with(|manager| manager.actions += 1).is_ok() {
+                        applied += 1;
+                    }
+                    thread::yield_now();
+                }
+                done_tx.send(()).unwrap();
+            })
+        };
+
+        done_rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("user actions should complete while refreshes run");
+        refresher.join().unwrap();
+        ui.join().unwrap();
+
+        let (refreshes, actions) = handle.with(|manager| (manager.refreshes, manager.actions));
+        assert_eq!(refreshes, 50);
+        assert_eq!(actions, 50);
+    }
+}
@@ -0,0 +1,270 @@
+//! Mediated devices (mdev) for sharing one GPU between several VMs.
+//!
+//! Drivers that can slice a card, such as the NVIDIA vGPU host driver or
+//! Intel GVT-g, register the parent device under `/sys/class/mdev_bus`.
+//! Each directory in its `mdev_supported_types` is one profile (e.g.
+//! `nvidia-259`, "GRID A100-4C") with a human-readable name, the VFIO API
+//! the guest sees and how many more instances the card can still host.
+//!
+//! Writing a UUID to a profile's `create` file makes a new instance under
+//! `/sys/bus/mdev/devices/<uuid>`, which a VM then gets as an mdev
+//! `<hostdev>`. Instances made this way are gone after a host reboot.
+
+use crate::{NovaError, Result, exec, log_info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use uuid::Uuid;
+
+/// Where the kernel lists devices that can host mediated devices.
+pub const MDEV_BUS_PATH: &str = "/sys/class/mdev_bus";
+
+/// Where created mediated-device instances appear.
+pub const MDEV_DEVICES_PATH: &str = "/sys/bus/mdev/devices";
+
+/// One mediated-device profile a parent device supports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MdevType {
+    /// Directory name under `mdev_supported_types`, e.g. `nvidia-259`
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `vfio-pci` for the guest-visible devices NVIDIA and GVT-g create
+    pub device_api: Option<String>,
+    pub available_instances: u32,
+}
+
+impl MdevType {
+    /// `name` if the driver provides one, otherwise the type ID.
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Profiles the device at `address` supports, or an empty list when it is
+/// not an mdev parent (no mdev-capable driver bound).
+pub fn supported_types(address: &str) -> Vec<MdevType> {
+    supported_types_in(Path::new(MDEV_BUS_PATH), address).unwrap_or_default()
+}
+
+/// Profiles listed under `mdev_bus/<address>/mdev_supported_types`, sorted
+/// by type ID.
+pub fn supported_types_in(mdev_bus: &Path, address: &str) -> Result<Vec<MdevType>> {
+    let types_dir = mdev_bus.join(address).join("mdev_supported_types");
+    let entries = fs::read_dir(&types_dir).map_err(|_| {
+        NovaError::ConfigError(format!(
+            "{} does not support mediated devices; bind the vGPU host driver (e.g. nvidia-vgpu-vfio) first",
+            address
+        ))
+    })?;
+
+    let mut types: Vec<MdevType> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let dir = entry.path();
+            let read = |file: &str| {
+                fs::read_to_string(dir.join(file))
+                    .ok()
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            MdevType {
+                id: entry.file_name().to_string_lossy().into_owned(),
+                name: read("name"),
+                description: read("description"),
+                device_api: read("device_api"),
+                available_instances: read("available_instances")
+                    .and_then(|count| count.parse().ok())
+                    .unwrap_or(0),
+            }
+        })
+        .collect();
+    types.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(types)
+}
+
+/// The profile in `types` whose ID or name is `profile`.
+pub fn find_type<'a>(types: &'a [MdevType], parent: &str, profile: &str) -> Result<&'a MdevType> {
+    types
+        .iter()
+        .find(|mdev_type| mdev_type.id == profile || mdev_type.name.as_deref() == Some(profile))
+        .ok_or_else(|| {
+            NovaError::ConfigError(format!(
+                "{} has no mdev profile '{}'; see `nova gpu mdev list {}`",
+                parent, profile, parent
+            ))
+        })
+}
+
+/// Create an instance of `profile` (type ID or name) on `parent` and return
+/// its UUID.
+pub fn create(parent: &str, profile: &str) -> Result<String> {
+    create_in(
+        Path::new(MDEV_BUS_PATH),
+        parent,
+        profile,
+        &Uuid::new_v4().to_string(),
+    )
+}
+
+/// [`create`] against the mdev bus at `mdev_bus`, with a chosen `uuid`.
+pub fn create_in(mdev_bus: &Path, parent: &str, profile: &str, uuid: &str) -> Result<String> {
+    let types = supported_types_in(mdev_bus, parent)?;
+    let mdev_type = find_type(&types, parent, profile)?;
+    if mdev_type.available_instances == 0 {
+        return Err(NovaError::ConfigError(format!(
+            "{} has no room for another '{}' instance; remove an existing one or pick a smaller profile",
+            parent,
+            mdev_type.display_name()
+        )));
+    }
+
+    let create_file = mdev_bus
+        .join(parent)
+        .join("mdev_supported_types")
+        .join(&mdev_type.id)
+        .join("create");
+    exec::write_file(&create_file, uuid).map_err(|e| {
+        NovaError::ConfigError(format!(
+            "Failed to create a '{}' mdev on {}: {}",
+            mdev_type.display_name(),
+            parent,
+            e
+        ))
+    })?;
+    log_info!(
+        "Created mdev {} ({}) on {}",
+        uuid,
+        mdev_type.display_name(),
+        parent
+    );
+    Ok(uuid.to_string())
+}
+
+/// libvirt `<hostdev>` for the mdev `uuid`; `device_api` is the instance's
+/// VFIO API, which libvirt calls the model.
+pub fn hostdev_xml(uuid: &str, device_api: Option<&str>) -> String {
+    format!(
+        r#"<hostdev mode='subsystem' type='mdev' managed='no' model='{}'>
+  <source>
+    <address uuid='{}'/>
+  </source>
+</hostdev>"#,
+        device_api.unwrap_or("vfio-pci"),
+        uuid
+    )
+}
+
+/// Add the mdev `uuid` to `vm`'s persistent definition. vGPUs can't be
+/// hot-plugged, so a running VM picks it up on its next boot.
+pub fn assign(uuid: &str, vm: &str) -> Result<()> {
+    let uuid = Uuid::parse_str(uuid)
+        .map_err(|_| NovaError::ConfigError(format!("'{}' is not an mdev UUID", uuid)))?
+        .to_string();
+    let device_dir = Path::new(MDEV_DEVICES_PATH).join(&uuid);
+    if !device_dir.exists() && !exec::is_dry_run() {
+        return Err(NovaError::ConfigError(format!(
+            "No mdev {} exists; create one with `nova mdev create <pf> <profile>`",
+            uuid
+        )));
+    }
+    let device_api = fs::read_to_string(device_dir.join("mdev_type/device_api"))
+        .ok()
+        .map(|api| api.trim().to_string());
+
+    let temp_xml = std::env::temp_dir().join(format!("nova-mdev-{}.xml", uuid));
+    fs::write(&temp_xml, hostdev_xml(&uuid, device_api.as_deref()))?;
+    let output = exec::run(Command::new("virsh").args([
+        "attach-device",
+        vm,
+        temp_xml.to_str().unwrap_or_default(),
+        "--config",
+    ]))
+    .map_err(|_| NovaError::SystemCommandFailed);
+    let _ = fs::remove_file(&temp_xml);
+    let output = output?;
+
+    if !output.status.success() {
+        return Err(NovaError::ConfigError(format!(
+            "virsh attach-device failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    log_info!("Assigned mdev {} to VM '{}'", uuid, vm);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_supported_types_from_sysfs() {
+        let bus = tempfile::tempdir().unwrap();
+        let types = bus.path().join("0000:41:00.0/mdev_supported_types");
+        for (id, name, available) in [
+            ("nvidia-260", "GRID A100-8C", "0"),
+            ("nvidia-259", "GRID A100-4C", "10"),
+        ] {
+            let dir = types.join(id);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("name"), format!("{}\n", name)).unwrap();
+            fs::write(dir.join("device_api"), "vfio-pci\n").unwrap();
+            fs::write(dir.join("available_instances"), available).unwrap();
+        }
+
+        let found = supported_types_in(bus.path(), "0000:41:00.0").unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, "nvidia-259");
+        assert_eq!(found[0].display_name(), "GRID A100-4C");
+        assert_eq!(found[0].available_instances, 10);
+        assert_eq!(found[0].device_api.as_deref(), Some("vfio-pci"));
+        assert_eq!(found[1].available_instances, 0);
+        assert_eq!(found[1].description, None);
+
+        let err = supported_types_in(bus.path(), "0000:01:00.0")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not support mediated devices"), "{}", err);
+    }
+
+    #[test]
+    fn create_writes_uuid_to_the_profile() {
+        let bus = tempfile::tempdir().unwrap();
+        let types = bus.path().join("0000:41:00.0/mdev_supported_types");
+        for (id, available) in [("nvidia-259", "2"), ("nvidia-260", "0")] {
+            fs::create_dir_all(types.join(id)).unwrap();
+            fs::write(types.join(id).join("name"), format!("GRID {}", id)).unwrap();
+            fs::write(types.join(id).join("available_instances"), available).unwrap();
+        }
+        let uuid = "0b7d5d3e-6c0e-4f3a-9d7b-6c1e2f3a4b5c";
+
+        // By name as well as by type ID
+        let created = create_in(bus.path(), "0000:41:00.0", "GRID nvidia-259", uuid).unwrap();
+        assert_eq!(created, uuid);
+        assert_eq!(
+            fs::read_to_string(types.join("nvidia-259/create")).unwrap(),
+            uuid
+        );
+
+        let full = create_in(bus.path(), "0000:41:00.0", "nvidia-260", uuid)
+            .unwrap_err()
+            .to_string();
+        assert!(full.contains("no room"), "{}", full);
+        let missing = create_in(bus.path(), "0000:41:00.0", "nvidia-999", uuid)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            missing.contains("no mdev profile 'nvidia-999'"),
+            "{}",
+            missing
+        );
+
+        let xml = hostdev_xml(uuid, None);
+        assert!(xml.contains("type='mdev'"));
+        assert!(xml.contains("model='vfio-pci'"));
+        assert!(xml.contains(&format!("<address uuid='{}'/>", uuid)));
+    }
+}
@@ -1,10 +1,17 @@
-use crate::{NovaError, Result, log_debug, log_error, log_info, log_warn};
+use crate::progress::Reporter;
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::process::Command;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use tokio::time::{Duration, Instant, sleep};
 
+const MIGRATION_STATE_DIR_FALLBACK: &str = "/var/lib/nova/migrations";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MigrationJob {
     pub job_id: String,
@@ -21,6 +28,13 @@ pub struct MigrationJob {
     pub bandwidth_limit_mbps: Option<u32>,
     pub downtime_ms: Option<u64>,
     pub error_message: Option<String>,
+    /// Switch to post-copy after the first pre-copy pass
+    #[serde(default)]
+    pub post_copy: bool,
+    /// Set once libvirt reports the guest running on the destination while
+    /// memory is still being pulled from the source
+    #[serde(default)]
+    pub post_copy_active: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,17 +60,217 @@ pub enum MigrationStatus {
     Cancelled,
 }
 
+impl MigrationStatus {
+    /// Whether the job has reached a terminal state.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            MigrationStatus::Completed | MigrationStatus::Failed(_) | MigrationStatus::Cancelled
+        )
+    }
+}
+
+impl fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationStatus::Queued => f.write_str("queued"),
+            MigrationStatus::PreparingSource => f.write_str("preparing source"),
+            MigrationStatus::PreparingDestination => f.write_str("preparing destination"),
+            MigrationStatus::TransferringMemory => f.write_str("transferring memory"),
+            MigrationStatus::TransferringStorage => f.write_str("transferring storage"),
+            MigrationStatus::SwitchingOver => f.write_str("switching over"),
+            MigrationStatus::Completing => f.write_str("completing"),
+            MigrationStatus::Completed => f.write_str("completed"),
+            MigrationStatus::Failed(reason) => write!(f, "failed: {}", reason),
+            MigrationStatus::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MigrationConfig {
     pub auto_converge: bool,          // Automatically adjust migration speed
     pub compress: bool,               // Enable compression
     pub multifd: bool,                // Use multiple file descriptors
     pub parallel_connections: u32,    // Number of parallel streams
-    pub bandwidth_limit_mbps: u32,    // Bandwidth limit
+    pub bandwidth_limit_mbps: u32,    // Bandwidth limit in MiB/s, 0 for none
     pub downtime_limit_ms: u64,       // Maximum acceptable downtime
     pub timeout_seconds: u64,         // Migration timeout
     pub verify_destination: bool,     // Verify destination before starting
     pub persistent_reservation: bool, // Handle persistent reservations
+    #[serde(default)]
+    pub post_copy: bool, // Switch to post-copy after the first pre-copy pass
+    #[serde(default)]
+    pub copy_storage: bool, // Copy disks to the destination (no shared storage)
+}
+
+/// What [`MigrationManager::precheck`] found before any data is sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationPrecheck {
+    /// Guest CPU mode from the domain XML (`host-passthrough`, `custom`, ...)
+    pub cpu_mode: String,
+    pub source_cpu: HostCpu,
+    pub destination_cpu: HostCpu,
+    /// Why the destination cannot run the guest CPU, as libvirt reports it
+    pub cpu_incompatible: Option<String>,
+    /// Disk images that do not exist at the same path on the destination
+    pub missing_disks: Vec<PathBuf>,
+}
+
+/// The host CPU from `virsh capabilities`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostCpu {
+    pub vendor: String,
+    pub model: String,
+    pub features: Vec<String>,
+}
+
+impl MigrationPrecheck {
+    /// Reasons the migration would fail, each with what to do about it.
+    pub fn blocking_issues(&self, copy_storage: bool) -> Vec<String> {
+        let mut issues = Vec::new();
+        if !self.source_cpu.vendor.is_empty()
+            && !self.destination_cpu.vendor.is_empty()
+            && self.source_cpu.vendor != self.destination_cpu.vendor
+        {
+            issues.push(format!(
+                "CPU vendor differs ({} here, {} on the destination); live migration between vendors is not possible, use --offline",
+                self.source_cpu.vendor, self.destination_cpu.vendor
+            ));
+        } else if let Some(reason) = &self.cpu_incompatible {
+            issues.push(format!(
+                "The destination cannot run this guest's CPU (mode '{}'): {}. Set <cpu mode='custom'> with a model both hosts support (e.g. {})",
+                self.cpu_mode, reason, self.destination_cpu.model
+            ));
+        }
+        if !copy_storage && !self.missing_disks.is_empty() {
+            let disks: Vec<String> = self
+                .missing_disks
+                .iter()
+                .map(|disk| disk.display().to_string())
+                .collect();
+            issues.push(format!(
+                "Disks not found on the destination: {}. Put them on shared storage or pass --copy-storage",
+                disks.join(", ")
+            ));
+        }
+        issues
+    }
+
+    /// Findings worth showing that do not stop the migration.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.source_cpu.model != self.destination_cpu.model
+            && !self.destination_cpu.model.is_empty()
+        {
+            warnings.push(format!(
+                "CPU model differs: {} here, {} on the destination",
+                self.source_cpu.model, self.destination_cpu.model
+            ));
+        }
+        warnings
+    }
+}
+
+/// The `<host><cpu>` section of `virsh capabilities`.
+pub fn parse_host_cpu(capabilities: &str) -> HostCpu {
+    let host = capabilities
+        .find("<host>")
+        .map_or(capabilities, |start| &capabilities[start..]);
+    let cpu = match (host.find("<cpu>"), host.find("</cpu>")) {
+        (Some(start), Some(end)) if start < end => &host[start..end],
+        _ => return HostCpu::default(),
+    };
+
+    let element_text = |tag: &str| {
+        let open = format!("<{}>", tag);
+        let start = cpu.find(&open)? + open.len();
+        let end = cpu[start..].find('<')?;
+        Some(cpu[start..start + end].trim().to_string())
+    };
+
+    let features = cpu
+        .split("<feature ")
+        .skip(1)
+        .filter_map(|feature| {
+            let name = feature.split("name=").nth(1)?;
+            let quote = name.chars().next()?;
+            let name = &name[1..];
+            Some(name[..name.find(quote)?].to_string())
+        })
+        .collect();
+
+    HostCpu {
+        vendor: element_text("vendor").unwrap_or_default(),
+        model: element_text("model").unwrap_or_default(),
+        features,
+    }
+}
+
+/// The `virsh` subcommand that judges whether a destination can run a guest
+/// CPU. `host-passthrough` copies this host's CPU, so it is compared with the
+/// destination host CPU; other modes with what the destination hypervisor can
+/// provide.
+pub fn cpu_compare_subcommand(cpu_mode: &str) -> &'static str {
+    if cpu_mode == "host-passthrough" {
+        "cpu-compare"
+    } else {
+        "hypervisor-cpu-compare"
+    }
+}
+
+/// The verdict of `virsh cpu-compare --error` (or `hypervisor-cpu-compare`):
+/// `Ok(None)` if the destination can run the CPU, `Ok(Some(reason))` if
+/// libvirt found it incompatible, and an error if the comparison itself
+/// failed.
+pub fn parse_cpu_compare(success: bool, stderr: &str) -> Result<Option<String>> {
+    if success {
+        return Ok(None);
+    }
+    let reason = stderr
+        .lines()
+        .map(|line| line.trim().trim_start_matches("error:").trim())
+        .rfind(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    if reason.contains("incompatible") {
+        Ok(Some(reason))
+    } else {
+        Err(NovaError::LibvirtError(format!(
+            "Cannot compare CPUs on the destination: {}",
+            reason
+        )))
+    }
+}
+
+/// The `mode` of a domain's `<cpu>` element; libvirt's default is `custom`.
+pub fn parse_domain_cpu_mode(domain_xml: &str) -> String {
+    domain_xml
+        .find("<cpu ")
+        .and_then(|start| {
+            let element = &domain_xml[start..];
+            let element = &element[..element.find('>')?];
+            let mode = element.split("mode=").nth(1)?;
+            let quote = mode.chars().next()?;
+            let mode = &mode[1..];
+            Some(mode[..mode.find(quote)?].to_string())
+        })
+        .unwrap_or_else(|| "custom".to_string())
+}
+
+/// The libvirt URI and SSH target for a destination given as `host`,
+/// `user@host` or a full `qemu+ssh://` URI.
+pub fn destination_endpoints(destination: &str) -> (String, String) {
+    match destination.split_once("://") {
+        Some((_, rest)) => {
+            let host = rest.split('/').next().unwrap_or(rest);
+            (destination.to_string(), host.to_string())
+        }
+        None => (
+            format!("qemu+ssh://{}/system", destination),
+            destination.to_string(),
+        ),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,12 +327,148 @@ pub struct MigrationMetrics {
     pub disk_total_bytes: u64,
     pub disk_transferred_bytes: u64,
     pub transfer_rate_mbps: f32,
+    #[serde(default)]
+    pub transfer_rate_bytes_per_sec: u64,
     pub pages_per_second: u32,
     pub dirty_rate_per_second: u32,
     pub downtime_ms: u64,
     pub iteration: u32,
 }
 
+fn migration_state_dir() -> PathBuf {
+    if let Some(mut dir) = dirs::data_dir() {
+        dir.push("nova");
+        dir.push("migrations");
+        dir
+    } else {
+        PathBuf::from(MIGRATION_STATE_DIR_FALLBACK)
+    }
+}
+
+fn persist_job(job: &MigrationJob) -> Result<()> {
+    let dir = migration_state_dir();
+    fs::create_dir_all(&dir)?;
+    let payload = serde_json::to_string_pretty(job)?;
+    fs::write(dir.join(format!("{}.json", job.job_id)), payload)?;
+    Ok(())
+}
+
+fn load_persisted_jobs() -> Vec<MigrationJob> {
+    let Ok(entries) = fs::read_dir(migration_state_dir()) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+/// Parse `virsh domjobinfo` output into metrics for `job_id`.
+///
+/// Returns `None` when libvirt reports no job running for the domain.
+pub fn parse_domjobinfo(job_id: &str, output: &str) -> Option<MigrationMetrics> {
+    let fields: HashMap<&str, &str> = output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+
+    match fields.get("Job type") {
+        None | Some(&"None") => return None,
+        Some(_) => {}
+    }
+
+    let bytes = |key: &str| fields.get(key).and_then(|value| parse_job_bytes(value));
+    let number = |key: &str| {
+        fields
+            .get(key)
+            .and_then(|value| value.split_whitespace().next())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+
+    let rate = bytes("Memory bandwidth").unwrap_or(0);
+    Some(MigrationMetrics {
+        job_id: job_id.to_string(),
+        ram_total_bytes: bytes("Memory total").unwrap_or(0),
+        ram_transferred_bytes: bytes("Memory processed").unwrap_or(0),
+        ram_remaining_bytes: bytes("Memory remaining").unwrap_or(0),
+        disk_total_bytes: bytes("File total").unwrap_or(0),
+        disk_transferred_bytes: bytes("File processed").unwrap_or(0),
+        transfer_rate_mbps: (rate as f64 * 8.0 / 1_000_000.0) as f32,
+        transfer_rate_bytes_per_sec: rate,
+        pages_per_second: number("Page size")
+            .filter(|page_size| *page_size > 0)
+            .map(|page_size| (rate / page_size) as u32)
+            .unwrap_or(0),
+        dirty_rate_per_second: number("Dirty rate").unwrap_or(0) as u32,
+        downtime_ms: number("Expected downtime").unwrap_or(0),
+        iteration: number("Iteration").unwrap_or(0) as u32,
+    })
+}
+
+/// Post-copy phase of a migrating domain, from `virsh domstate --reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostCopyState {
+    Inactive,
+    /// The guest runs on the destination and faults pages in from the source
+    Active,
+    /// The connection broke during post-copy; the guest is paused on both hosts
+    Failed,
+}
+
+/// Post-copy phase from `virsh domstate --reason` output such as
+/// `paused (post-copy)`.
+pub fn parse_postcopy_state(domstate: &str) -> PostCopyState {
+    if domstate.contains("post-copy failed") {
+        PostCopyState::Failed
+    } else if domstate.contains("post-copy") {
+        PostCopyState::Active
+    } else {
+        PostCopyState::Inactive
+    }
+}
+
+/// How a migration ended, from `virsh domstate` on the source and the
+/// destination once libvirt reports no job for it. `None` means the domain
+/// is not defined on that side. Returns `None` when the states do not tell.
+pub fn migration_outcome(
+    vm_name: &str,
+    source: Option<&str>,
+    destination: Option<&str>,
+) -> Option<MigrationStatus> {
+    let active = |state: Option<&str>| {
+        state.is_some_and(|state| state.starts_with("running") || state.starts_with("paused"))
+    };
+    match (active(source), active(destination)) {
+        (false, true) => Some(MigrationStatus::Completed),
+        (true, false) => Some(MigrationStatus::Failed(format!(
+            "libvirt has no migration job and '{}' is still {} on the source",
+            vm_name,
+            source.unwrap_or_default()
+        ))),
+        _ => None,
+    }
+}
+
+/// Parse a libvirt size such as `1.250 GiB` or `110.375 MiB/s` into bytes.
+fn parse_job_bytes(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let amount: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()?.trim_end_matches("/s") {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((amount * multiplier) as u64)
+}
+
 pub struct MigrationManager {
     config: MigrationConfig,
     shared_storage: Option<SharedStorageConfig>,
@@ -127,15 +477,110 @@ pub struct MigrationManager {
 }
 
 impl MigrationManager {
+    /// Create a manager, picking up jobs recorded by earlier `nova` processes.
     pub fn new(config: MigrationConfig, shared_storage: Option<SharedStorageConfig>) -> Self {
+        let jobs = load_persisted_jobs()
+            .into_iter()
+            .map(|job| (job.job_id.clone(), job))
+            .collect();
         Self {
             config,
             shared_storage,
-            active_jobs: Arc::new(Mutex::new(HashMap::new())),
+            active_jobs: Arc::new(Mutex::new(jobs)),
             metrics: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Check CPU compatibility and disk availability on the destination
+    /// without transferring anything.
+    pub async fn precheck(&self, vm_name: &str, destination: &str) -> Result<MigrationPrecheck> {
+        let (uri, ssh_host) = destination_endpoints(destination);
+
+        let domain_xml = Command::new("virsh")
+            .args(["dumpxml", "--migratable", vm_name])
+            .output()
+            .map_err(|e| NovaError::LibvirtError(format!("Could not run virsh: {}", e)))?;
+        if !domain_xml.status.success() {
+            return Err(NovaError::VmNotFound(vm_name.to_string()));
+        }
+
+        let source_caps = Command::new("virsh").arg("capabilities").output()?;
+        let destination_caps = Command::new("virsh")
+            .args(["-c", &uri, "capabilities"])
+            .output()?;
+        if !destination_caps.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "Cannot read capabilities from {}: {}",
+                uri,
+                String::from_utf8_lossy(&destination_caps.stderr).trim()
+            )));
+        }
+
+        let source_caps = String::from_utf8_lossy(&source_caps.stdout);
+        let source_cpu = parse_host_cpu(&source_caps);
+        let destination_cpu = parse_host_cpu(&String::from_utf8_lossy(&destination_caps.stdout));
+
+        // Feature lists in `capabilities` are relative to each host's model,
+        // so leave the comparison to libvirt on the destination
+        let domain_xml = String::from_utf8_lossy(&domain_xml.stdout).into_owned();
+        let cpu_mode = parse_domain_cpu_mode(&domain_xml);
+        let subcommand = cpu_compare_subcommand(&cpu_mode);
+        let mut definition = tempfile::NamedTempFile::new()?;
+        definition.write_all(if subcommand == "cpu-compare" {
+            source_caps.as_bytes()
+        } else {
+            domain_xml.as_bytes()
+        })?;
+        let compare = Command::new("virsh")
+            .args(["-c", &uri, subcommand, "--error"])
+            .arg(definition.path())
+            .output()?;
+        let cpu_incompatible = parse_cpu_compare(
+            compare.status.success(),
+            &String::from_utf8_lossy(&compare.stderr),
+        )?;
+
+        let blklist = Command::new("virsh")
+            .args(["domblklist", vm_name, "--details"])
+            .output()?;
+        let disks = crate::templates_snapshots::parse_domblklist_disks(&String::from_utf8_lossy(
+            &blklist.stdout,
+        ));
+        let mut missing_disks = Vec::new();
+        for (_, path) in disks {
+            if !self.disk_exists_on(&ssh_host, &path)? {
+                missing_disks.push(path);
+            }
+        }
+
+        Ok(MigrationPrecheck {
+            cpu_mode,
+            source_cpu,
+            destination_cpu,
+            cpu_incompatible,
+            missing_disks,
+        })
+    }
+
+    fn disk_exists_on(&self, ssh_host: &str, path: &Path) -> Result<bool> {
+        let mut test = Command::new("test");
+        test.arg("-e").arg(path);
+        let output = Command::new("ssh")
+            .args(["-o", "ConnectTimeout=10", "-o", "BatchMode=yes", ssh_host])
+            .arg(exec::render(&test))
+            .output()?;
+        // 255 is ssh's own failure; anything else is the answer from `test`
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(255) | None => Err(NovaError::NetworkError(format!(
+                "Cannot check disks on {} over SSH: {}",
+                ssh_host,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))),
+            Some(_) => Ok(false),
+        }
+    }
+
     /// Start a live migration with intelligent type selection
     pub async fn migrate_vm(
         &mut self,
@@ -149,6 +594,28 @@ impl MigrationManager {
             destination_host
         );
 
+        // Fail here rather than in the background job, where the caller
+        // only sees it once it polls
+        if self.config.verify_destination {
+            self.verify_destination_connectivity(destination_host)
+                .await?;
+
+            if !matches!(force_type, Some(MigrationType::Offline)) {
+                let precheck = self.precheck(vm_name, destination_host).await?;
+                for warning in precheck.warnings() {
+                    log_warn!("Migration precheck for '{}': {}", vm_name, warning);
+                }
+                let issues = precheck.blocking_issues(self.config.copy_storage);
+                if !issues.is_empty() {
+                    return Err(NovaError::ConfigError(format!(
+                        "Migration precheck failed for '{}':\n  - {}",
+                        vm_name,
+                        issues.join("\n  - ")
+                    )));
+                }
+            }
+        }
+
         let job_id = uuid::Uuid::new_v4().to_string();
         let job_id_clone = job_id.clone();
         let source_host = self.get_current_host();
@@ -173,9 +640,11 @@ impl MigrationManager {
             started_at: chrono::Utc::now(),
             completed_at: None,
             estimated_completion: None,
-            bandwidth_limit_mbps: Some(self.config.bandwidth_limit_mbps),
+            bandwidth_limit_mbps: Some(self.config.bandwidth_limit_mbps).filter(|&limit| limit > 0),
             downtime_ms: None,
             error_message: None,
+            post_copy: self.config.post_copy || matches!(migration_type, MigrationType::PostCopy),
+            post_copy_active: false,
         };
 
         // Store job
@@ -183,6 +652,7 @@ impl MigrationManager {
             let mut jobs = self.active_jobs.lock().unwrap();
             jobs.insert(job_id.clone(), job.clone());
         }
+        Self::store_job(&job);
 
         // Start migration process
         let migration_manager = self.clone_for_async();
@@ -267,24 +737,23 @@ impl MigrationManager {
 
     async fn execute_postcopy_migration(&self, job: &mut MigrationJob) -> Result<()> {
         log_info!("Executing post-copy migration for job: {}", job.job_id);
+        job.post_copy = true;
+        if let Some(stored) = self.active_jobs.lock().unwrap().get_mut(&job.job_id) {
+            stored.post_copy = true;
+            Self::store_job(stored);
+        }
 
         // Prepare destination
         self.update_job_status(&job.job_id, MigrationStatus::PreparingDestination)
             .await;
         self.prepare_destination(job).await?;
 
-        // Start VM on destination with minimal memory
-        self.start_postcopy_destination(job).await?;
-
-        // Switch over immediately
-        self.update_job_status(&job.job_id, MigrationStatus::SwitchingOver)
-            .await;
-        self.switch_vm_to_destination(job).await?;
-
-        // Continue transferring memory in background
+        // One pre-copy pass, then libvirt resumes the guest on the
+        // destination and pulls the remaining pages on demand
         self.update_job_status(&job.job_id, MigrationStatus::TransferringMemory)
             .await;
-        self.complete_postcopy_transfer(job).await?;
+        self.start_memory_migration(job).await?;
+        self.monitor_migration_progress(job).await?;
 
         self.update_job_status(&job.job_id, MigrationStatus::Completed)
             .await;
@@ -330,7 +799,11 @@ impl MigrationManager {
 
         // Try live migration first
         let live_start = Instant::now();
-        if self.attempt_live_migration(job).await.is_err() {
+        if let Err(err) = self.attempt_live_migration(job).await {
+            // Post-copy can lose the VM on a network drop, so it needs --post-copy
+            if !self.config.post_copy {
+                return Err(err);
+            }
             log_warn!("Live migration failed, falling back to post-copy");
 
             // Fall back to post-copy if live migration struggles
@@ -356,21 +829,17 @@ impl MigrationManager {
             .analyze_network_to_destination(destination_host)
             .await?;
 
-        // Decision logic
-        if vm_analysis.memory_size_gb > 16.0 && network_analysis.bandwidth_mbps < 1000 {
-            // Large VM on slow network - use post-copy
-            Ok(MigrationType::PostCopy)
-        } else if vm_analysis.memory_dirty_rate > 100 {
-            // MB/s
-            // High memory churn - use post-copy
-            Ok(MigrationType::PostCopy)
-        } else if vm_analysis.is_critical && network_analysis.latency_ms < 5.0 {
-            // Critical VM on fast network - use hybrid
-            Ok(MigrationType::Hybrid)
-        } else {
-            // Standard case - use live migration
-            Ok(MigrationType::Live)
+        let migration_type =
+            choose_migration_type(&vm_analysis, &network_analysis, self.config.post_copy);
+        if matches!(migration_type, MigrationType::Live)
+            && prefers_post_copy(&vm_analysis, &network_analysis)
+        {
+            log_warn!(
+                "'{}' may not converge with pre-copy; pass --post-copy to switch over after the first pass",
+                vm_name
+            );
         }
+        Ok(migration_type)
     }
 
     async fn analyze_vm_for_migration(&self, vm_name: &str) -> Result<VmMigrationAnalysis> {
@@ -388,10 +857,8 @@ impl MigrationManager {
             let info = String::from_utf8_lossy(&output.stdout);
 
             // Parse memory size
-            if let Some(mem_line) = info.lines().find(|line| line.contains("Max memory"))
-                && let Some(mem_str) = mem_line.split_whitespace().nth(2)
-            {
-                analysis.memory_size_gb = mem_str.parse::<u64>().unwrap_or(2048) as f32 / 1024.0;
+            if let Some(memory_gb) = parse_dominfo_max_memory_gb(&info) {
+                analysis.memory_size_gb = memory_gb;
             }
         }
 
@@ -537,29 +1004,13 @@ impl MigrationManager {
 
         let mut migrate_cmd = Command::new("virsh");
         migrate_cmd.args(["migrate", "--live", "--verbose", &job.vm_name, &dest_uri]);
+        migrate_cmd.args(self.config.virsh_args(job.post_copy));
 
-        // Add performance options
-        if self.config.compress {
-            migrate_cmd.arg("--compressed");
+        // Start migration in background; progress is read back via domjobinfo
+        if exec::skip(&migrate_cmd) {
+            return Ok(());
         }
-
-        if self.config.auto_converge {
-            migrate_cmd.arg("--auto-converge");
-        }
-
-        if self.config.parallel_connections > 1 {
-            migrate_cmd.args([
-                "--parallel",
-                "--parallel-connections",
-                &self.config.parallel_connections.to_string(),
-            ]);
-        }
-
-        migrate_cmd.args(["--bandwidth", &self.config.bandwidth_limit_mbps.to_string()]);
-        migrate_cmd.args(["--timeout", &self.config.timeout_seconds.to_string()]);
-
-        // Start migration in background
-        let _child = migrate_cmd.spawn().map_err(|e| {
+        let _child = migrate_cmd.stdout(Stdio::null()).spawn().map_err(|e| {
             log_error!("Failed to start migration: {}", e);
             NovaError::SystemCommandFailed
         })?;
@@ -571,8 +1022,11 @@ impl MigrationManager {
     async fn monitor_migration_progress(&self, job: &MigrationJob) -> Result<()> {
         log_info!("Monitoring migration progress for job: {}", job.job_id);
 
-        loop {
-            let progress = self.get_migration_progress(&job.vm_name).await?;
+        // Libvirt drops the job once the transfer is done
+        while let Some(progress) = self.get_migration_progress(&job.job_id, &job.vm_name)? {
+            if job.post_copy {
+                self.check_postcopy_state(&job.job_id, &job.vm_name).await?;
+            }
 
             // Update metrics
             {
@@ -596,10 +1050,14 @@ impl MigrationManager {
             }
 
             // Check for convergence issues
-            if progress.iteration > 20 && progress.dirty_rate_per_second > progress.pages_per_second
+            if !job.post_copy
+                && progress.iteration > 20
+                && progress.dirty_rate_per_second > progress.pages_per_second
             {
-                log_warn!("Migration may not converge, considering post-copy switch");
-                // Could implement automatic post-copy switch here
+                log_warn!(
+                    "Migration of '{}' may not converge; retry with `nova migrate --post-copy`",
+                    job.vm_name
+                );
             }
 
             sleep(Duration::from_secs(5)).await;
@@ -609,41 +1067,64 @@ impl MigrationManager {
         Ok(())
     }
 
-    async fn get_migration_progress(&self, vm_name: &str) -> Result<MigrationMetrics> {
-        // Get migration statistics from QEMU monitor
+    fn get_migration_progress(
+        &self,
+        job_id: &str,
+        vm_name: &str,
+    ) -> Result<Option<MigrationMetrics>> {
         let output = Command::new("virsh")
-            .args(["qemu-monitor-command", vm_name, "--hmp", "info migrate"])
+            .args(["domjobinfo", vm_name])
             .output()
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
-            return Err(NovaError::SystemCommandFailed);
+            return Err(NovaError::LibvirtError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
         }
 
-        let _info = String::from_utf8_lossy(&output.stdout);
-
-        // Parse migration statistics (simplified)
-        let metrics = MigrationMetrics {
-            job_id: "placeholder".to_string(),
-            ram_total_bytes: 2147483648,       // 2GB placeholder
-            ram_transferred_bytes: 1073741824, // 1GB placeholder
-            ram_remaining_bytes: 1073741824,   // 1GB placeholder
-            disk_total_bytes: 0,
-            disk_transferred_bytes: 0,
-            transfer_rate_mbps: 100.0,
-            pages_per_second: 1000,
-            dirty_rate_per_second: 500,
-            downtime_ms: 50,
-            iteration: 5,
-        };
+        Ok(parse_domjobinfo(
+            job_id,
+            &String::from_utf8_lossy(&output.stdout),
+        ))
+    }
 
-        Ok(metrics)
+    /// Record when post-copy takes over, and fail the job if it broke off.
+    async fn check_postcopy_state(&self, job_id: &str, vm_name: &str) -> Result<()> {
+        let output = Command::new("virsh")
+            .args(["domstate", vm_name, "--reason"])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        match parse_postcopy_state(&String::from_utf8_lossy(&output.stdout)) {
+            PostCopyState::Inactive => Ok(()),
+            PostCopyState::Active => {
+                let mut jobs = self.active_jobs.lock().unwrap();
+                if let Some(job) = jobs.get_mut(job_id)
+                    && !job.post_copy_active
+                {
+                    log_info!(
+                        "Migration {}: post-copy active, '{}' now runs on {}",
+                        job_id,
+                        vm_name,
+                        job.destination_host
+                    );
+                    job.post_copy_active = true;
+                    job.status = MigrationStatus::SwitchingOver;
+                    Self::store_job(job);
+                }
+                Ok(())
+            }
+            PostCopyState::Failed => Err(NovaError::LibvirtError(format!(
+                "post-copy migration of '{}' failed; the VM is paused on both hosts. Restore \
+                 the link and run `virsh migrate --postcopy --postcopy-resume` to continue",
+                vm_name
+            ))),
+        }
     }
 
     // Utility methods
     async fn requires_storage_migration(&self, _vm_name: &str) -> Result<bool> {
-        // Check if VM uses shared storage
-        Ok(self.shared_storage.is_none())
+        Ok(self.config.copy_storage)
     }
 
     fn get_current_host(&self) -> String {
@@ -655,7 +1136,11 @@ impl MigrationManager {
         let mut jobs = self.active_jobs.lock().unwrap();
         if let Some(job) = jobs.get_mut(job_id) {
             job.status = status;
+            if job.status.is_finished() {
+                job.completed_at.get_or_insert_with(chrono::Utc::now);
+            }
             log_debug!("Updated job {} status to: {:?}", job_id, job.status);
+            Self::store_job(job);
         }
     }
 
@@ -671,6 +1156,7 @@ impl MigrationManager {
                 job.estimated_completion =
                     Some(job.started_at + chrono::Duration::seconds(estimated_total as i64));
             }
+            Self::store_job(job);
         }
     }
 
@@ -680,6 +1166,13 @@ impl MigrationManager {
             job.status = MigrationStatus::Failed(error.to_string());
             job.error_message = Some(error.to_string());
             job.completed_at = Some(chrono::Utc::now());
+            Self::store_job(job);
+        }
+    }
+
+    fn store_job(job: &MigrationJob) {
+        if let Err(err) = persist_job(job) {
+            log_warn!("Failed to record migration job {}: {}", job.job_id, err);
         }
     }
 
@@ -698,9 +1191,20 @@ impl MigrationManager {
         jobs.get(job_id).cloned()
     }
 
+    /// Jobs that have not finished yet, oldest first.
     pub fn list_active_migrations(&self) -> Vec<MigrationJob> {
+        self.list_migrations()
+            .into_iter()
+            .filter(|job| !job.status.is_finished())
+            .collect()
+    }
+
+    /// Every recorded job, including finished ones, oldest first.
+    pub fn list_migrations(&self) -> Vec<MigrationJob> {
         let jobs = self.active_jobs.lock().unwrap();
-        jobs.values().cloned().collect()
+        let mut jobs: Vec<MigrationJob> = jobs.values().cloned().collect();
+        jobs.sort_by_key(|job| job.started_at);
+        jobs
     }
 
     pub fn get_migration_metrics(&self, job_id: &str) -> Option<MigrationMetrics> {
@@ -708,23 +1212,136 @@ impl MigrationManager {
         metrics.get(job_id).cloned()
     }
 
+    /// Query libvirt for live progress of an unfinished job and record it.
+    ///
+    /// Returns `None` when the job has finished or libvirt has no job running
+    /// for the domain.
+    pub async fn refresh_migration_metrics(
+        &self,
+        job_id: &str,
+    ) -> Result<Option<MigrationMetrics>> {
+        let job = self.get_migration_job(job_id).ok_or_else(|| {
+            NovaError::ConfigError(format!("Migration job '{}' not found", job_id))
+        })?;
+        if job.status.is_finished() {
+            return Ok(None);
+        }
+        if job.post_copy
+            && let Err(err) = self.check_postcopy_state(job_id, &job.vm_name).await
+        {
+            self.mark_job_failed(job_id, &err.to_string()).await;
+            return Ok(None);
+        }
+
+        // A transient domain is gone from the source once it has moved
+        let progress = self.get_migration_progress(job_id, &job.vm_name);
+        let Ok(Some(metrics)) = progress else {
+            if let Err(err) = self.finalize_job(&job).await {
+                log_warn!("Cannot tell how migration {} ended: {}", job_id, err);
+            }
+            return Ok(None);
+        };
+        if metrics.ram_total_bytes > 0 {
+            let percent =
+                metrics.ram_transferred_bytes as f32 / metrics.ram_total_bytes as f32 * 100.0;
+            self.update_job_progress(job_id, percent).await;
+        }
+        self.metrics
+            .lock()
+            .unwrap()
+            .insert(job_id.to_string(), metrics.clone());
+        Ok(Some(metrics))
+    }
+
+    /// Settle a job that libvirt no longer reports on: completed if the VM
+    /// now runs on the destination, failed if it is still on the source.
+    /// Jobs still being prepared may not have reached libvirt yet.
+    async fn finalize_job(&self, job: &MigrationJob) -> Result<()> {
+        if matches!(
+            job.status,
+            MigrationStatus::Queued
+                | MigrationStatus::PreparingSource
+                | MigrationStatus::PreparingDestination
+        ) {
+            return Ok(());
+        }
+        let (destination_uri, _) = destination_endpoints(&job.destination_host);
+        let source = domain_state(None, &job.vm_name)?;
+        let destination = domain_state(Some(&destination_uri), &job.vm_name)?;
+        match migration_outcome(&job.vm_name, source.as_deref(), destination.as_deref()) {
+            Some(MigrationStatus::Failed(reason)) => {
+                self.mark_job_failed(&job.job_id, &reason).await;
+            }
+            Some(status) => {
+                self.update_job_progress(&job.job_id, 100.0).await;
+                self.update_job_status(&job.job_id, status).await;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Wait until the job has handed off to libvirt (or finished), so the
+    /// transfer outlives a short-lived `nova migrate` process. Each phase
+    /// change is reported as a stage.
+    pub async fn wait_until_transferring(
+        &self,
+        job_id: &str,
+        timeout: Duration,
+        reporter: &mut Reporter,
+    ) -> MigrationStatus {
+        let deadline = Instant::now() + timeout;
+        let mut reported = None;
+        loop {
+            let status = self
+                .get_migration_job(job_id)
+                .map(|job| job.status)
+                .unwrap_or(MigrationStatus::Queued);
+            let phase = status.to_string();
+            if reported.as_ref() != Some(&phase) {
+                reporter.stage(&phase);
+                reported = Some(phase);
+            }
+            let preparing = matches!(
+                status,
+                MigrationStatus::Queued
+                    | MigrationStatus::PreparingSource
+                    | MigrationStatus::PreparingDestination
+            );
+            if !preparing || Instant::now() >= deadline {
+                return status;
+            }
+            sleep(Duration::from_millis(250)).await;
+        }
+    }
+
     pub async fn cancel_migration(&mut self, job_id: &str) -> Result<()> {
         log_info!("Cancelling migration job: {}", job_id);
 
-        if let Some(job) = self.get_migration_job(job_id) {
-            // Cancel the migration
-            let output = Command::new("virsh")
-                .args(["migrate", "--abort", &job.vm_name])
-                .output()
-                .map_err(|_| NovaError::SystemCommandFailed)?;
+        let job = self.get_migration_job(job_id).ok_or_else(|| {
+            NovaError::ConfigError(format!("Migration job '{}' not found", job_id))
+        })?;
+        if job.status.is_finished() {
+            return Err(NovaError::ConfigError(format!(
+                "Migration job '{}' already {}",
+                job_id, job.status
+            )));
+        }
 
-            if output.status.success() {
-                self.update_job_status(job_id, MigrationStatus::Cancelled)
-                    .await;
-                log_info!("Migration job {} cancelled successfully", job_id);
-            }
+        let output = exec::run(Command::new("virsh").args(["domjobabort", &job.vm_name]))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "Failed to abort migration of '{}': {}",
+                job.vm_name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
         }
 
+        self.update_job_status(job_id, MigrationStatus::Cancelled)
+            .await;
+        log_info!("Migration job {} cancelled successfully", job_id);
         Ok(())
     }
 
@@ -741,15 +1358,6 @@ impl MigrationManager {
     async fn cleanup_migration(&self, _job: &MigrationJob) -> Result<()> {
         Ok(())
     }
-    async fn start_postcopy_destination(&self, _job: &MigrationJob) -> Result<()> {
-        Ok(())
-    }
-    async fn switch_vm_to_destination(&self, _job: &MigrationJob) -> Result<()> {
-        Ok(())
-    }
-    async fn complete_postcopy_transfer(&self, _job: &MigrationJob) -> Result<()> {
-        Ok(())
-    }
     async fn shutdown_vm(&self, _vm_name: &str) -> Result<()> {
         Ok(())
     }
@@ -783,6 +1391,70 @@ impl MigrationManager {
     }
 }
 
+/// `Max memory` from `virsh dominfo`, which libvirt reports in KiB, as GiB.
+fn parse_dominfo_max_memory_gb(dominfo: &str) -> Option<f32> {
+    let line = dominfo
+        .lines()
+        .find(|line| line.starts_with("Max memory"))?;
+    let kib: u64 = line.split_whitespace().nth(2)?.parse().ok()?;
+    Some(kib as f32 / 1024.0 / 1024.0)
+}
+
+/// Large guests on a slow link, and guests dirtying memory quickly, may
+/// never converge with pre-copy alone.
+fn prefers_post_copy(vm: &VmMigrationAnalysis, network: &NetworkAnalysis) -> bool {
+    (vm.memory_size_gb > 16.0 && network.bandwidth_mbps < 1000) || vm.memory_dirty_rate > 100
+}
+
+/// Migration type for a VM when none is forced. Post-copy resumes the guest
+/// on the destination before all of its memory has arrived, so a network
+/// drop loses the VM; it (and hybrid, which falls back to it) is only
+/// picked when the user allowed post-copy.
+fn choose_migration_type(
+    vm: &VmMigrationAnalysis,
+    network: &NetworkAnalysis,
+    allow_post_copy: bool,
+) -> MigrationType {
+    if !allow_post_copy {
+        MigrationType::Live
+    } else if prefers_post_copy(vm, network) {
+        MigrationType::PostCopy
+    } else if vm.is_critical && network.latency_ms < 5.0 {
+        MigrationType::Hybrid
+    } else {
+        MigrationType::Live
+    }
+}
+
+/// `virsh domstate` of `vm_name` through `uri` (the local libvirt when
+/// unset), or `None` if the domain is not defined there.
+fn domain_state(uri: Option<&str>, vm_name: &str) -> Result<Option<String>> {
+    let mut cmd = Command::new("virsh");
+    if let Some(uri) = uri {
+        cmd.args(["-c", uri]);
+    }
+    let output = cmd
+        .args(["domstate", vm_name])
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if output.status.success() {
+        return Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("failed to get domain") || stderr.contains("Domain not found") {
+        Ok(None)
+    } else {
+        Err(NovaError::LibvirtError(format!(
+            "Cannot read the state of '{}'{}: {}",
+            vm_name,
+            uri.map(|uri| format!(" on {}", uri)).unwrap_or_default(),
+            stderr.trim()
+        )))
+    }
+}
+
 // Helper structs
 #[derive(Debug, Clone)]
 struct VmMigrationAnalysis {
@@ -831,19 +1503,62 @@ struct HostResources {
 impl Default for MigrationConfig {
     fn default() -> Self {
         Self {
-            auto_converge: true,
-            compress: true,
+            auto_converge: false,
+            compress: false,
             multifd: true,
             parallel_connections: 4,
-            bandwidth_limit_mbps: 1000,
+            bandwidth_limit_mbps: 0,
             downtime_limit_ms: 500,
             timeout_seconds: 1800, // 30 minutes
             verify_destination: true,
             persistent_reservation: false,
+            post_copy: false,
+            copy_storage: false,
         }
     }
 }
 
+impl MigrationConfig {
+    /// Tuning options for `virsh migrate`.
+    pub fn virsh_args(&self, post_copy: bool) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.compress {
+            args.push("--compressed".to_string());
+        }
+
+        if self.auto_converge {
+            args.push("--auto-converge".to_string());
+        }
+
+        if post_copy {
+            args.extend(["--postcopy", "--postcopy-after-precopy"].map(String::from));
+        }
+
+        // QEMU cannot combine multifd streams with post-copy
+        if self.parallel_connections > 1 && !post_copy {
+            args.extend([
+                "--parallel".to_string(),
+                "--parallel-connections".to_string(),
+                self.parallel_connections.to_string(),
+            ]);
+        }
+
+        if self.copy_storage {
+            args.push("--copy-storage-all".to_string());
+        }
+
+        if self.bandwidth_limit_mbps > 0 {
+            args.extend([
+                "--bandwidth".to_string(),
+                self.bandwidth_limit_mbps.to_string(),
+            ]);
+        }
+        args.extend(["--timeout".to_string(), self.timeout_seconds.to_string()]);
+        args
+    }
+}
+
 impl Clone for MigrationConfig {
     fn clone(&self) -> Self {
         Self {
@@ -856,6 +1571,214 @@ impl Clone for MigrationConfig {
             timeout_seconds: self.timeout_seconds,
             verify_destination: self.verify_destination,
             persistent_reservation: self.persistent_reservation,
+            post_copy: self.post_copy,
+            copy_storage: self.copy_storage,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domjobinfo_reports_progress_and_rate() {
+        let output = "Job type:         Unbounded
+Operation:        Outgoing migration
+Time elapsed:     4321         ms
+Data processed:   1.500 GiB
+Data remaining:   2.500 GiB
+Data total:       4.020 GiB
+Memory processed: 1.500 GiB
+Memory remaining: 2.500 GiB
+Memory total:     4.000 GiB
+Memory bandwidth: 110.000 MiB/s
+Dirty rate:       1200         pages/s
+Page size:        4096         bytes
+Iteration:        2
+Expected downtime: 300         ms
+";
+        let metrics = parse_domjobinfo("job-1", output).unwrap();
+        assert_eq!(metrics.ram_total_bytes, 4 * 1024 * 1024 * 1024);
+        assert_eq!(metrics.ram_remaining_bytes, 2_684_354_560);
+        assert_eq!(metrics.transfer_rate_bytes_per_sec, 110 * 1024 * 1024);
+        assert_eq!(metrics.dirty_rate_per_second, 1200);
+        assert_eq!(metrics.iteration, 2);
+        assert_eq!(metrics.downtime_ms, 300);
+
+        assert!(parse_domjobinfo("job-1", "Job type:         None\n").is_none());
+    }
+
+    #[test]
+    fn tuning_flags_map_onto_virsh_migrate() {
+        let args = MigrationConfig::default().virsh_args(false);
+        assert!(
+            !args
+                .iter()
+                .any(|arg| arg == "--bandwidth" || arg == "--compressed")
+        );
+
+        let config = MigrationConfig {
+            bandwidth_limit_mbps: 200,
+            compress: true,
+            auto_converge: true,
+            ..MigrationConfig::default()
+        };
+        let args = config.virsh_args(true);
+        let joined = args.join(" ");
+        assert!(
+            joined.contains("--compressed --auto-converge"),
+            "{}",
+            joined
+        );
+        assert!(joined.contains("--bandwidth 200"), "{}", joined);
+        assert!(!joined.contains("--parallel"), "{}", joined);
+    }
+
+    #[test]
+    fn precheck_compares_host_cpus_and_flags_missing_disks() {
+        let source = parse_host_cpu(
+            "<capabilities>
+  <host>
+    <uuid>1</uuid>
+    <cpu>
+      <arch>x86_64</arch>
+      <model>Cascadelake-Server</model>
+      <vendor>Intel</vendor>
+      <feature name='avx512vnni'/>
+      <feature name='pdpe1gb'/>
+    </cpu>
+  </host>
+  <guest><cpu><model>ignored</model></cpu></guest>
+</capabilities>",
+        );
+        assert_eq!(source.vendor, "Intel");
+        assert_eq!(source.model, "Cascadelake-Server");
+        assert_eq!(source.features, ["avx512vnni", "pdpe1gb"]);
+
+        let mut precheck = MigrationPrecheck {
+            cpu_mode: parse_domain_cpu_mode(
+                "<domain><cpu mode='host-passthrough' check='none'/></domain>",
+            ),
+            destination_cpu: HostCpu {
+                vendor: "Intel".to_string(),
+                model: "Skylake-Server".to_string(),
+                features: vec!["pdpe1gb".to_string()],
+            },
+            cpu_incompatible: parse_cpu_compare(
+                false,
+                "error: Failed to compare host CPU with /tmp/cpu.xml\n\
+                 error: the CPU is incompatible with host CPU: Host CPU does not provide required features: avx512vnni\n",
+            )
+            .unwrap(),
+            missing_disks: vec![PathBuf::from("/var/lib/libvirt/images/web.qcow2")],
+            source_cpu: source,
+        };
+        assert_eq!(precheck.cpu_mode, "host-passthrough");
+        let issues = precheck.blocking_issues(false);
+        assert_eq!(issues.len(), 2, "{:?}", issues);
+        assert!(issues[0].contains("avx512vnni"));
+        assert!(issues[1].contains("--copy-storage"));
+        assert_eq!(precheck.blocking_issues(true).len(), 1);
+
+        assert_eq!(cpu_compare_subcommand(&precheck.cpu_mode), "cpu-compare");
+
+        // Other modes are checked against the destination hypervisor
+        precheck.cpu_mode = parse_domain_cpu_mode("<domain><vcpu>2</vcpu></domain>");
+        assert_eq!(precheck.cpu_mode, "custom");
+        assert_eq!(
+            cpu_compare_subcommand(&precheck.cpu_mode),
+            "hypervisor-cpu-compare"
+        );
+        precheck.cpu_incompatible = parse_cpu_compare(true, "").unwrap();
+        assert!(precheck.blocking_issues(true).is_empty());
+        assert_eq!(precheck.warnings().len(), 1);
+        assert!(parse_cpu_compare(false, "error: failed to connect to the hypervisor\n").is_err());
+
+        assert_eq!(
+            destination_endpoints("root@host2.lan"),
+            (
+                "qemu+ssh://root@host2.lan/system".to_string(),
+                "root@host2.lan".to_string()
+            )
+        );
+        assert_eq!(destination_endpoints("qemu+ssh://host2/system").1, "host2");
+    }
+
+    #[test]
+    fn post_copy_is_only_chosen_when_allowed() {
+        let dominfo = "Id:             3\nName:           web\nMax memory:     33554432 KiB\nUsed memory:    33554432 KiB\n";
+        let vm = VmMigrationAnalysis {
+            memory_size_gb: parse_dominfo_max_memory_gb(dominfo).unwrap(),
+            memory_dirty_rate: 0,
+            is_critical: false,
+        };
+        assert_eq!(vm.memory_size_gb, 32.0);
+
+        let slow = NetworkAnalysis {
+            latency_ms: 8.0,
+            bandwidth_mbps: 100,
+        };
+        assert!(prefers_post_copy(&vm, &slow));
+        assert!(matches!(
+            choose_migration_type(&vm, &slow, false),
+            MigrationType::Live
+        ));
+        assert!(matches!(
+            choose_migration_type(&vm, &slow, true),
+            MigrationType::PostCopy
+        ));
+
+        let critical = VmMigrationAnalysis {
+            memory_size_gb: 4.0,
+            memory_dirty_rate: 0,
+            is_critical: true,
+        };
+        let fast = NetworkAnalysis {
+            latency_ms: 0.5,
+            bandwidth_mbps: 10000,
+        };
+        assert!(matches!(
+            choose_migration_type(&critical, &fast, false),
+            MigrationType::Live
+        ));
+        assert!(matches!(
+            choose_migration_type(&critical, &fast, true),
+            MigrationType::Hybrid
+        ));
+    }
+
+    #[test]
+    fn job_outcome_follows_where_the_domain_runs() {
+        assert!(matches!(
+            migration_outcome("web", Some("shut off"), Some("running")),
+            Some(MigrationStatus::Completed)
+        ));
+        assert!(matches!(
+            migration_outcome("web", None, Some("paused")),
+            Some(MigrationStatus::Completed)
+        ));
+        assert!(matches!(
+            migration_outcome("web", Some("running"), None),
+            Some(MigrationStatus::Failed(reason)) if reason.contains("still running on the source")
+        ));
+        assert!(migration_outcome("web", Some("running"), Some("running")).is_none());
+        assert!(migration_outcome("web", None, None).is_none());
+    }
+
+    #[test]
+    fn domstate_reason_reports_postcopy_phase() {
+        assert_eq!(
+            parse_postcopy_state("running (migrated)\n"),
+            PostCopyState::Inactive
+        );
+        assert_eq!(
+            parse_postcopy_state("paused (post-copy)\n"),
+            PostCopyState::Active
+        );
+        assert_eq!(
+            parse_postcopy_state("paused (post-copy failed)\n"),
+            PostCopyState::Failed
+        );
+    }
+}
@@ -51,6 +51,9 @@ pub struct PacketCaptureConfig {
 pub struct NetworkTopology {
     pub bridges: Vec<TopologyBridge>,
     pub connections: Vec<TopologyConnection>,
+    /// Running VMs and the host devices backing their NICs
+    #[serde(default)]
+    pub vms: Vec<TopologyVm>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +73,104 @@ pub struct TopologyConnection {
     pub bandwidth: Option<f64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyVm {
+    pub name: String,
+    /// Host-side tap (`vnetN`) or macvtap devices
+    pub interfaces: Vec<String>,
+}
+
+/// Node ID of a VM in the DOT graph; prefixed so a VM named like an
+/// interface gets its own node.
+fn dot_vm_node(vm: &str) -> String {
+    format!("vm:{}", vm)
+}
+
+/// A DOT string literal; `\n` in `id` is kept as a line break in labels.
+fn dot_quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('"', "\\\""))
+}
+
+impl NetworkTopology {
+    /// The topology as a graphviz graph: bridges are boxes, VMs components,
+    /// other interfaces ellipses. Render with `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("graph nova {\n  rankdir=LR;\n  node [fontname=\"sans-serif\"];\n");
+        let mut declared = HashSet::new();
+
+        for bridge in &self.bridges {
+            let label = match &bridge.ip_address {
+                Some(ip) => format!("{}\\n{} ({})", bridge.name, ip, bridge.bridge_type),
+                None => format!("{}\\n({})", bridge.name, bridge.bridge_type),
+            };
+            dot.push_str(&format!(
+                "  {} [shape=box, style=bold, label={}];\n",
+                dot_quote(&bridge.name),
+                dot_quote(&label)
+            ));
+            declared.insert(bridge.name.clone());
+        }
+        for vm in &self.vms {
+            dot.push_str(&format!(
+                "  {} [shape=component, label={}];\n",
+                dot_quote(&dot_vm_node(&vm.name)),
+                dot_quote(&vm.name)
+            ));
+        }
+        for connection in &self.connections {
+            let iface = if connection.connection_type == "tap" {
+                &connection.from
+            } else {
+                &connection.to
+            };
+            if declared.insert(iface.clone()) {
+                dot.push_str(&format!("  {} [shape=ellipse];\n", dot_quote(iface)));
+            }
+        }
+
+        for connection in &self.connections {
+            let to = if connection.connection_type == "tap" {
+                dot_vm_node(&connection.to)
+            } else {
+                connection.to.clone()
+            };
+            let style = if connection.connection_type == "tap" {
+                " [style=dashed]"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "  {} -- {}{};\n",
+                dot_quote(&connection.from),
+                dot_quote(&to),
+                style
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// `(interface, source)` pairs from `virsh domiflist` output. NICs without a
+/// host device (user-mode networking) show `-` and are skipped.
+pub fn parse_domiflist(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [iface, _, source, ..] if *iface != "-" => {
+                    Some((iface.to_string(), source.to_string()))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
 pub struct NetworkMonitor {
     _stats_history: HashMap<String, Vec<NetworkStats>>,
     bandwidth_history: HashMap<String, Vec<BandwidthUsage>>,
@@ -99,6 +200,63 @@ pub fn offline_interfaces_from_history(
     offline
 }
 
+/// Counters for exactly `interface` from the contents of `/proc/net/dev`.
+///
+/// Names are compared whole, so `br0` does not match `br0.10` or `virbr0`.
+pub fn parse_proc_net_dev(contents: &str, interface: &str) -> Option<NetworkStats> {
+    let counters = contents.lines().find_map(|line| {
+        let (name, counters) = line.split_once(':')?;
+        (name.trim() == interface).then_some(counters)
+    })?;
+    let fields: Vec<u64> = counters
+        .split_whitespace()
+        .map(|field| field.parse().unwrap_or(0))
+        .collect();
+    if fields.len() < 16 {
+        return None;
+    }
+
+    Some(NetworkStats {
+        interface: interface.to_string(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        rx_bytes: fields[0],
+        rx_packets: fields[1],
+        rx_errors: fields[2],
+        rx_drops: fields[3],
+        tx_bytes: fields[8],
+        tx_packets: fields[9],
+        tx_errors: fields[10],
+        tx_drops: fields[11],
+    })
+}
+
+/// Rates between two samples taken `elapsed_secs` apart. Counters that went
+/// backwards (interface recreated) count as zero.
+pub fn bandwidth_between(
+    previous: &NetworkStats,
+    current: &NetworkStats,
+    elapsed_secs: f64,
+) -> BandwidthUsage {
+    let rate = |now: u64, before: u64| {
+        if elapsed_secs > 0.0 {
+            now.saturating_sub(before) as f64 / elapsed_secs
+        } else {
+            0.0
+        }
+    };
+    BandwidthUsage {
+        interface: current.interface.clone(),
+        timestamp: current.timestamp,
+        rx_bps: rate(current.rx_bytes, previous.rx_bytes),
+        tx_bps: rate(current.tx_bytes, previous.tx_bytes),
+        rx_pps: rate(current.rx_packets, previous.rx_packets),
+        tx_pps: rate(current.tx_packets, previous.tx_packets),
+    }
+}
+
 impl NetworkMonitor {
     pub fn new() -> Self {
         Self {
@@ -141,32 +299,40 @@ impl NetworkMonitor {
         let proc_net_dev =
             std::fs::read_to_string("/proc/net/dev").map_err(|_| NovaError::SystemCommandFailed)?;
 
-        for line in proc_net_dev.lines() {
-            if line.contains(interface) && line.contains(":") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 17 {
-                    let timestamp = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
-
-                    return Ok(NetworkStats {
-                        interface: interface.to_string(),
-                        timestamp,
-                        rx_bytes: parts[1].parse().unwrap_or(0),
-                        rx_packets: parts[2].parse().unwrap_or(0),
-                        rx_errors: parts[3].parse().unwrap_or(0),
-                        rx_drops: parts[4].parse().unwrap_or(0),
-                        tx_bytes: parts[9].parse().unwrap_or(0),
-                        tx_packets: parts[10].parse().unwrap_or(0),
-                        tx_errors: parts[11].parse().unwrap_or(0),
-                        tx_drops: parts[12].parse().unwrap_or(0),
-                    });
-                }
-            }
-        }
+        parse_proc_net_dev(&proc_net_dev, interface)
+            .ok_or_else(|| NovaError::NetworkNotFound(interface.to_string()))
+    }
 
-        Err(NovaError::NetworkNotFound(interface.to_string()))
+    /// Rates of `interfaces` over `window`, from two reads of
+    /// `/proc/net/dev`. Interfaces without counters are left out.
+    pub async fn sample_bandwidth(
+        &self,
+        interfaces: &[String],
+        window: std::time::Duration,
+    ) -> Result<Vec<BandwidthUsage>> {
+        let read = || -> Result<Vec<NetworkStats>> {
+            let proc_net_dev = std::fs::read_to_string("/proc/net/dev")
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+            Ok(interfaces
+                .iter()
+                .filter_map(|interface| parse_proc_net_dev(&proc_net_dev, interface))
+                .collect())
+        };
+        let before = read()?;
+        let started = std::time::Instant::now();
+        tokio::time::sleep(window).await;
+        let after = read()?;
+        let elapsed = started.elapsed().as_secs_f64();
+
+        Ok(after
+            .iter()
+            .filter_map(|current| {
+                let previous = before
+                    .iter()
+                    .find(|stats| stats.interface == current.interface)?;
+                Some(bandwidth_between(previous, current, elapsed))
+            })
+            .collect())
     }
 
     // Calculate bandwidth between two stat samples
@@ -180,20 +346,7 @@ impl NetworkMonitor {
         if time_diff == 0.0 {
             return Err(NovaError::InvalidConfig);
         }
-
-        let rx_bytes_diff = current.rx_bytes.saturating_sub(previous.rx_bytes) as f64;
-        let tx_bytes_diff = current.tx_bytes.saturating_sub(previous.tx_bytes) as f64;
-        let rx_packets_diff = current.rx_packets.saturating_sub(previous.rx_packets) as f64;
-        let tx_packets_diff = current.tx_packets.saturating_sub(previous.tx_packets) as f64;
-
-        Ok(BandwidthUsage {
-            interface: current.interface.clone(),
-            timestamp: current.timestamp,
-            rx_bps: rx_bytes_diff / time_diff,
-            tx_bps: tx_bytes_diff / time_diff,
-            rx_pps: rx_packets_diff / time_diff,
-            tx_pps: tx_packets_diff / time_diff,
-        })
+        Ok(bandwidth_between(previous, current, time_diff))
     }
 
     // Get current bandwidth usage
@@ -304,6 +457,7 @@ impl NetworkMonitor {
         let mut topology = NetworkTopology {
             bridges: Vec::new(),
             connections: Vec::new(),
+            vms: Vec::new(),
         };
 
         // Discover Linux bridges
@@ -316,10 +470,12 @@ impl NetworkMonitor {
 
         // Discover connections between bridges and interfaces
         self.discover_connections(&mut topology).await?;
+        self.discover_vm_attachments(&mut topology);
 
         log_info!(
-            "Discovered {} bridges and {} connections",
+            "Discovered {} bridges, {} VMs and {} connections",
             topology.bridges.len(),
+            topology.vms.len(),
             topology.connections.len()
         );
 
@@ -426,6 +582,43 @@ impl NetworkMonitor {
         Ok(())
     }
 
+    /// Tap devices of running libvirt domains, as `tap` edges from the
+    /// device to the VM. Hosts without libvirt just get no VMs.
+    fn discover_vm_attachments(&self, topology: &mut NetworkTopology) {
+        let Ok(output) = Command::new("virsh").args(["list", "--name"]).output() else {
+            return;
+        };
+        if !output.status.success() {
+            return;
+        }
+
+        for vm in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let interfaces = Command::new("virsh")
+                .args(["domiflist", vm])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| parse_domiflist(&String::from_utf8_lossy(&output.stdout)))
+                .unwrap_or_default();
+            for (iface, _) in &interfaces {
+                topology.connections.push(TopologyConnection {
+                    from: iface.clone(),
+                    to: vm.to_string(),
+                    connection_type: "tap".to_string(),
+                    bandwidth: None,
+                });
+            }
+            topology.vms.push(TopologyVm {
+                name: vm.to_string(),
+                interfaces: interfaces.into_iter().map(|(iface, _)| iface).collect(),
+            });
+        }
+    }
+
     async fn get_interface_ip(&self, interface: &str) -> Result<String> {
         let output = Command::new("ip")
             .args(["-4", "addr", "show", interface])
@@ -528,7 +721,10 @@ impl Default for NetworkMonitor {
 
 #[cfg(test)]
 mod tests {
-    use super::{BandwidthUsage, offline_interfaces_from_history};
+    use super::{
+        BandwidthUsage, NetworkTopology, TopologyBridge, TopologyConnection, TopologyVm,
+        bandwidth_between, offline_interfaces_from_history, parse_domiflist, parse_proc_net_dev,
+    };
     use std::collections::HashMap;
 
     #[test]
@@ -581,4 +777,85 @@ mod tests {
         let offline = offline_interfaces_from_history(&history, 0, 10);
         assert!(offline.is_empty());
     }
+
+    #[test]
+    fn proc_net_dev_matches_whole_interface_names() {
+        let contents = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+virbr0:    4000      40    0    0    0     0          0         0     8000      80    0    0    0     0       0          0
+   br0:1234567890   100    2    1    0     0          0         0     5000      50    3    0    0     0       0          0
+br0.10:      10       1    0    0    0     0          0         0       20       2    0    0    0     0       0          0
+";
+        let br0 = parse_proc_net_dev(contents, "br0").unwrap();
+        assert_eq!(
+            (br0.rx_bytes, br0.rx_errors, br0.rx_drops),
+            (1234567890, 2, 1)
+        );
+        assert_eq!((br0.tx_bytes, br0.tx_packets, br0.tx_errors), (5000, 50, 3));
+        assert_eq!(parse_proc_net_dev(contents, "br0.10").unwrap().rx_bytes, 10);
+        assert!(parse_proc_net_dev(contents, "br").is_none());
+
+        let mut later = br0.clone();
+        later.rx_bytes += 2000;
+        later.tx_bytes = 0;
+        let usage = bandwidth_between(&br0, &later, 2.0);
+        assert_eq!((usage.rx_bps, usage.tx_bps), (1000.0, 0.0));
+    }
+
+    #[test]
+    fn topology_exports_bridges_and_vm_taps_as_dot() {
+        let domiflist = " Interface   Type      Source    Model    MAC
+-------------------------------------------------------------
+ vnet3       bridge    br0       virtio   52:54:00:12:34:56
+ -           user      -         e1000    52:54:00:aa:bb:cc
+";
+        assert_eq!(
+            parse_domiflist(domiflist),
+            vec![("vnet3".to_string(), "br0".to_string())]
+        );
+
+        let topology = NetworkTopology {
+            bridges: vec![TopologyBridge {
+                name: "br0".into(),
+                bridge_type: "linux".into(),
+                interfaces: vec!["eth0".into(), "vnet3".into()],
+                ip_address: Some("192.168.1.20/24".into()),
+                status: "active".into(),
+            }],
+            connections: vec![
+                TopologyConnection {
+                    from: "br0".into(),
+                    to: "eth0".into(),
+                    connection_type: "bridge".into(),
+                    bandwidth: None,
+                },
+                TopologyConnection {
+                    from: "br0".into(),
+                    to: "vnet3".into(),
+                    connection_type: "bridge".into(),
+                    bandwidth: None,
+                },
+                TopologyConnection {
+                    from: "vnet3".into(),
+                    to: "win11".into(),
+                    connection_type: "tap".into(),
+                    bandwidth: None,
+                },
+            ],
+            vms: vec![TopologyVm {
+                name: "win11".into(),
+                interfaces: vec!["vnet3".into()],
+            }],
+        };
+        let dot = topology.to_dot();
+        assert!(dot.starts_with("graph nova {"));
+        assert!(
+            dot.contains(r#""br0" [shape=box, style=bold, label="br0\n192.168.1.20/24 (linux)"];"#)
+        );
+        assert!(dot.contains(r#""vm:win11" [shape=component, label="win11"];"#));
+        assert_eq!(dot.matches(r#""vnet3" [shape=ellipse];"#).count(), 1);
+        assert!(dot.contains(r#""br0" -- "eth0";"#));
+        assert!(dot.contains(r#""vnet3" -- "vm:win11" [style=dashed];"#));
+        assert!(dot.trim_end().ends_with('}'));
+    }
 }
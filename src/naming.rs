@@ -0,0 +1,259 @@
+//! Instance naming rules shared by VMs, containers, and virtual switches.
+//!
+//! Rules come from the `[naming]` section of the NovaFile. Entry points call
+//! [`configure`] once after loading the config; [`validate`] then applies the
+//! active rules everywhere a name is accepted.
+
+use crate::{NovaError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+/// Longest Linux network interface name (`IFNAMSIZ` less the NUL).
+pub const MAX_INTERFACE_NAME: usize = 15;
+
+/// What kind of object a name belongs to, used in error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Vm,
+    Container,
+    Switch,
+}
+
+impl fmt::Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            NameKind::Vm => "VM",
+            NameKind::Container => "Container",
+            NameKind::Switch => "Switch",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamingConfig {
+    /// Maximum name length in characters
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+    /// Characters allowed in addition to ASCII letters and digits
+    #[serde(default = "default_allowed_symbols")]
+    pub allowed_symbols: String,
+    /// Prefixes reserved for Nova itself (e.g. self-test instances)
+    #[serde(default = "default_reserved_prefixes")]
+    pub reserved_prefixes: Vec<String>,
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self {
+            max_length: default_max_length(),
+            allowed_symbols: default_allowed_symbols(),
+            reserved_prefixes: default_reserved_prefixes(),
+        }
+    }
+}
+
+fn default_max_length() -> usize {
+    64
+}
+
+fn default_allowed_symbols() -> String {
+    "-_".to_string()
+}
+
+fn default_reserved_prefixes() -> Vec<String> {
+    vec!["nova-selftest".to_string()]
+}
+
+impl NamingConfig {
+    pub fn validate(&self, kind: NameKind, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(NovaError::ConfigError(format!(
+                "{} name cannot be empty",
+                kind
+            )));
+        }
+
+        let length = name.chars().count();
+        if length > self.max_length {
+            return Err(NovaError::ConfigError(format!(
+                "{} name '{}' is {} characters long; the limit is {}",
+                kind, name, length, self.max_length
+            )));
+        }
+        // A switch is a host bridge, named after it
+        if kind == NameKind::Switch {
+            validate_interface_name(name)?;
+        }
+
+        let valid = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || self.allowed_symbols.contains(c));
+        if !valid {
+            return Err(NovaError::ConfigError(format!(
+                "{} name '{}' contains unsupported characters. Use {}.",
+                kind,
+                name,
+                self.charset_description()
+            )));
+        }
+
+        if let Some(prefix) = self
+            .reserved_prefixes
+            .iter()
+            .find(|prefix| !prefix.is_empty() && name.starts_with(prefix.as_str()))
+        {
+            return Err(NovaError::ConfigError(format!(
+                "{} name '{}' uses the reserved prefix '{}'",
+                kind, name, prefix
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn charset_description(&self) -> String {
+        let symbols: Vec<String> = self
+            .allowed_symbols
+            .chars()
+            .map(|c| format!("'{}'", c))
+            .collect();
+        match symbols.as_slice() {
+            [] => "letters or numbers".to_string(),
+            [only] => format!("letters, numbers or {}", only),
+            [rest @ .., last] => format!("letters, numbers, {} or {}", rest.join(", "), last),
+        }
+    }
+}
+
+/// Refuse names the kernel would reject for a network interface.
+pub fn validate_interface_name(name: &str) -> Result<()> {
+    if name.len() > MAX_INTERFACE_NAME {
+        return Err(NovaError::ConfigError(format!(
+            "Interface name '{}' is {} bytes long; Linux allows at most {}",
+            name,
+            name.len(),
+            MAX_INTERFACE_NAME
+        )));
+    }
+    Ok(())
+}
+
+fn active_rules() -> &'static RwLock<NamingConfig> {
+    static RULES: OnceLock<RwLock<NamingConfig>> = OnceLock::new();
+    RULES.get_or_init(|| RwLock::new(NamingConfig::default()))
+}
+
+/// Replace the process-wide naming rules, typically with `NovaConfig::naming`.
+pub fn configure(config: NamingConfig) {
+    *active_rules()
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+}
+
+/// Validate `name` against the active naming rules.
+pub fn validate(kind: NameKind, name: &str) -> Result<()> {
+    active_rules()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .validate(kind, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_message(result: Result<()>) -> String {
+        match result {
+            Err(NovaError::ConfigError(message)) => message,
+            other => panic!("expected config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn default_rules_accept_typical_names() {
+        let rules = NamingConfig::default();
+        assert!(rules.validate(NameKind::Vm, "win11-gaming_01").is_ok());
+        assert!(rules.validate(NameKind::Switch, "br0").is_ok());
+        assert!(rules.validate(NameKind::Container, "").is_err());
+    }
+
+    #[test]
+    fn max_length_is_enforced() {
+        let rules = NamingConfig {
+            max_length: 8,
+            ..NamingConfig::default()
+        };
+        assert!(rules.validate(NameKind::Container, "api-01").is_ok());
+        // Length counts characters, not bytes
+        let relaxed = NamingConfig {
+            max_length: 3,
+            allowed_symbols: "é".to_string(),
+            ..NamingConfig::default()
+        };
+        assert!(relaxed.validate(NameKind::Vm, "éé").is_ok());
+
+        let message = error_message(rules.validate(NameKind::Container, "api-server"));
+        assert_eq!(
+            message,
+            "Container name 'api-server' is 10 characters long; the limit is 8"
+        );
+    }
+
+    #[test]
+    fn switch_names_fit_an_interface_name() {
+        let defaults = NamingConfig::default();
+        assert!(
+            defaults
+                .validate(NameKind::Switch, "br-lab-uplink01")
+                .is_ok()
+        );
+        assert_eq!(
+            error_message(defaults.validate(NameKind::Switch, "br-lab-uplink012")),
+            "Interface name 'br-lab-uplink012' is 16 bytes long; Linux allows at most 15"
+        );
+        assert!(defaults.validate(NameKind::Vm, "br-lab-uplink012").is_ok());
+    }
+
+    #[test]
+    fn allowed_symbols_control_charset() {
+        let defaults = NamingConfig::default();
+        assert_eq!(
+            error_message(defaults.validate(NameKind::Vm, "lab.vm")),
+            "VM name 'lab.vm' contains unsupported characters. Use letters, numbers, '-' or '_'."
+        );
+
+        let dotted = NamingConfig {
+            allowed_symbols: "-.".to_string(),
+            ..NamingConfig::default()
+        };
+        assert!(dotted.validate(NameKind::Vm, "lab.vm").is_ok());
+        assert!(dotted.validate(NameKind::Vm, "lab_vm").is_err());
+
+        let strict = NamingConfig {
+            allowed_symbols: String::new(),
+            ..NamingConfig::default()
+        };
+        assert!(
+            error_message(strict.validate(NameKind::Switch, "br-0"))
+                .ends_with("Use letters or numbers.")
+        );
+    }
+
+    #[test]
+    fn reserved_prefixes_are_rejected() {
+        let defaults = NamingConfig::default();
+        assert_eq!(
+            error_message(defaults.validate(NameKind::Vm, "nova-selftest-1")),
+            "VM name 'nova-selftest-1' uses the reserved prefix 'nova-selftest'"
+        );
+
+        let custom = NamingConfig {
+            reserved_prefixes: vec!["prod-".to_string(), String::new()],
+            ..NamingConfig::default()
+        };
+        assert!(custom.validate(NameKind::Switch, "prod-uplink").is_err());
+        assert!(custom.validate(NameKind::Switch, "nova-selftest").is_ok());
+    }
+}
@@ -1,13 +1,17 @@
-use crate::{NovaError, Result, log_debug, log_error, log_info, log_warn};
+use crate::{
+    NovaError, Result,
+    arch_integration::ArchNetworkManager,
+    exec, log_debug, log_error, log_info, log_warn,
+    naming::{self, NameKind},
+};
 use dirs;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::io::Write;
 use std::net::Ipv4Addr;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 
@@ -42,6 +46,9 @@ pub struct VirtualSwitch {
     pub origin: SwitchOrigin,
     #[serde(default)]
     pub profile: Option<SwitchProfile>,
+    /// Member ports carrying tagged VLANs, with their VIDs
+    #[serde(default)]
+    pub trunk_ports: BTreeMap<String, Vec<u16>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -78,6 +85,58 @@ pub struct NetworkInterface {
     pub state: InterfaceState,
     pub bridge: Option<String>,
     pub speed: Option<u64>,
+    /// Kernel `ifalias` set via `ip link set ... alias`
+    pub alias: Option<String>,
+    /// Alternative names (systemd assigns the predictable names here)
+    pub altnames: Vec<String>,
+}
+
+/// Stable names udev derives for a NIC, independent of probe order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PersistentNames {
+    /// Onboard/slot/PCI-path name such as `enp3s0`
+    pub pci: Option<String>,
+    /// MAC-based name such as `enx001122334455`
+    pub mac: Option<String>,
+}
+
+/// A running VM with a NIC on a bridge, for `nova network inspect`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectedVm {
+    pub vm: String,
+    /// Host-side tap device such as `vnet3`
+    pub tap: Option<String>,
+    pub mac: String,
+    /// Libvirt network the NIC is attached through, if any
+    pub network: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// One row of `virsh domiflist`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainInterface {
+    pub tap: Option<String>,
+    /// `bridge`, `network`, `direct`, ...
+    pub kind: String,
+    pub source: String,
+    pub mac: String,
+}
+
+/// Where an interface alias was persisted so it survives reboots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasPersistence {
+    /// systemd `.link` file, used on systemd-networkd hosts
+    SystemdLink(PathBuf),
+    /// udev rule setting `ifalias`, used with NetworkManager or manual setups
+    UdevRule(PathBuf),
+}
+
+impl AliasPersistence {
+    pub fn path(&self) -> &Path {
+        match self {
+            AliasPersistence::SystemdLink(path) | AliasPersistence::UdevRule(path) => path,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -102,6 +161,9 @@ pub struct BridgeConfig {
     pub max_age: u32,
     pub aging_time: u32,
     pub multicast_snooping: bool,
+    /// Applied to the bridge and its member interfaces (576-9216)
+    #[serde(default)]
+    pub mtu: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +190,8 @@ struct PersistedSwitch {
     name: String,
     switch_type: SwitchType,
     profile: Option<SwitchProfile>,
+    #[serde(default)]
+    vlan_id: Option<u16>,
 }
 
 fn network_state_dir() -> PathBuf {
@@ -180,6 +244,9 @@ fn load_persisted_switch(name: &str) -> Result<Option<PersistedSwitch>> {
 }
 
 fn persist_switch_state(state: &PersistedSwitch) -> Result<()> {
+    if exec::is_dry_run() {
+        return Ok(());
+    }
     let dir = network_state_dir();
     fs::create_dir_all(&dir)?;
     let path = network_state_file(&state.name);
@@ -190,7 +257,7 @@ fn persist_switch_state(state: &PersistedSwitch) -> Result<()> {
 
 fn remove_persisted_switch(name: &str) -> Result<()> {
     let path = network_state_file(name);
-    if path.exists() {
+    if path.exists() && !exec::is_dry_run() {
         fs::remove_file(path)?;
     }
     Ok(())
@@ -233,6 +300,7 @@ impl NetworkManager {
         self.discover_interfaces().await?;
         self.rebuild_switch_memberships();
         self.hydrate_persisted_switches().await?;
+        self.discover_trunk_ports();
 
         self.last_refresh_instant = Some(Instant::now());
         self.last_refresh_at = Some(chrono::Utc::now());
@@ -253,6 +321,7 @@ impl NetworkManager {
                 existing.origin = SwitchOrigin::Nova;
                 existing.switch_type = state.switch_type.clone();
                 existing.profile = state.profile.clone();
+                existing.vlan_id = state.vlan_id;
             } else {
                 self.switches.insert(
                     switch_name.clone(),
@@ -260,12 +329,13 @@ impl NetworkManager {
                         name: switch_name.clone(),
                         switch_type: state.switch_type.clone(),
                         interfaces: Vec::new(),
-                        vlan_id: None,
+                        vlan_id: state.vlan_id,
                         stp_enabled: false,
                         created_at: chrono::Utc::now(),
                         status: SwitchStatus::Inactive,
                         origin: SwitchOrigin::Nova,
                         profile: state.profile.clone(),
+                        trunk_ports: BTreeMap::new(),
                     },
                 );
             }
@@ -288,6 +358,17 @@ impl NetworkManager {
                         bridge_ready = true;
                     }
                 }
+                if let Some(vid) = state.vlan_id
+                    && let Err(err) =
+                        self.configure_switch_vlan(&switch_name, &state.switch_type, vid)
+                {
+                    log_error!(
+                        "Failed to restore VLAN {} on bridge {}: {}",
+                        vid,
+                        switch_name,
+                        err
+                    );
+                }
             }
 
             if let Some(switch) = self.switches.get_mut(&switch_name)
@@ -336,7 +417,9 @@ impl NetworkManager {
                     .is_some_and(|bridge| bridge == &state.name);
 
                 if !already_attached {
-                    self.add_interface_to_switch(&state.name, uplink).await?;
+                    // The uplink was accepted when the profile was created
+                    self.add_interface_to_switch(&state.name, uplink, true)
+                        .await?;
                 } else if let Some(switch) = self.switches.get_mut(&state.name)
                     && !switch.interfaces.iter().any(|iface| iface == uplink)
                 {
@@ -489,13 +572,25 @@ impl NetworkManager {
     }
 
     // Virtual Switch Management
+    /// Create a bridge, optionally with every port untagged in `vlan_id`
+    /// (the uplink of an external profile carries it tagged).
     pub async fn create_virtual_switch(
         &mut self,
         name: &str,
         switch_type: SwitchType,
         profile: Option<SwitchProfile>,
+        vlan_id: Option<u16>,
+        force: bool,
     ) -> Result<()> {
         log_info!("Creating virtual switch: {} ({:?})", name, switch_type);
+        naming::validate(NameKind::Switch, name)?;
+        if let Some(vid) = vlan_id {
+            validate_vlan_ids(&[vid])?;
+        }
+        // Before creating anything, so a refused uplink leaves no bridge behind
+        if !force && let Some(SwitchProfile::External { uplink }) = &profile {
+            ensure_not_default_route(uplink, name, false)?;
+        }
 
         let has_profile = profile.is_some();
         match &switch_type {
@@ -506,6 +601,15 @@ impl NetworkManager {
                 self.create_ovs_bridge(name).await?;
             }
         }
+        if let Some(vid) = vlan_id
+            && let Err(err) = self.configure_switch_vlan(name, &switch_type, vid)
+        {
+            let _ = match switch_type {
+                SwitchType::LinuxBridge => self.delete_linux_bridge(name).await,
+                SwitchType::OpenVSwitch => self.delete_ovs_bridge(name).await,
+            };
+            return Err(err);
+        }
 
         let profile_to_apply = profile.clone();
 
@@ -513,12 +617,13 @@ impl NetworkManager {
             name: name.to_string(),
             switch_type: switch_type.clone(),
             interfaces: Vec::new(),
-            vlan_id: None,
+            vlan_id,
             stp_enabled: false,
             created_at: chrono::Utc::now(),
             status: SwitchStatus::Active,
             origin: SwitchOrigin::Nova,
             profile: profile.clone(),
+            trunk_ports: BTreeMap::new(),
         };
 
         self.switches.insert(name.to_string(), switch);
@@ -527,7 +632,7 @@ impl NetworkManager {
             match profile {
                 SwitchProfile::Internal => {}
                 SwitchProfile::External { uplink } => {
-                    if let Err(err) = self.add_interface_to_switch(name, &uplink).await {
+                    if let Err(err) = self.add_interface_to_switch(name, &uplink, force).await {
                         let _ = self.delete_virtual_switch(name).await;
                         return Err(err);
                     }
@@ -615,6 +720,7 @@ impl NetworkManager {
             name: name.to_string(),
             switch_type,
             profile,
+            vlan_id,
         };
         persist_switch_state(&persisted)?;
         if has_profile {
@@ -630,13 +736,12 @@ impl NetworkManager {
         }
 
         // Create bridge using ip command (modern approach)
-        let output = Command::new("ip")
-            .args(["link", "add", "name", name, "type", "bridge"])
-            .output()
-            .map_err(|e| {
-                log_error!("Failed to create bridge {}: {}", name, e);
-                NovaError::SystemCommandFailed
-            })?;
+        let output =
+            exec::run(Command::new("ip").args(["link", "add", "name", name, "type", "bridge"]))
+                .map_err(|e| {
+                    log_error!("Failed to create bridge {}: {}", name, e);
+                    NovaError::SystemCommandFailed
+                })?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -645,9 +750,7 @@ impl NetworkManager {
         }
 
         // Bring bridge up
-        let output = Command::new("ip")
-            .args(["link", "set", "dev", name, "up"])
-            .output()
+        let output = exec::run(Command::new("ip").args(["link", "set", "dev", name, "up"]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -671,13 +774,10 @@ impl NetworkManager {
             return Ok(());
         }
 
-        let output = Command::new("ovs-vsctl")
-            .args(["add-br", name])
-            .output()
-            .map_err(|e| {
-                log_error!("Failed to create OVS bridge {}: {}", name, e);
-                NovaError::SystemCommandFailed
-            })?;
+        let output = exec::run(Command::new("ovs-vsctl").args(["add-br", name])).map_err(|e| {
+            log_error!("Failed to create OVS bridge {}: {}", name, e);
+            NovaError::SystemCommandFailed
+        })?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -715,14 +815,12 @@ impl NetworkManager {
                     };
                     let _ = self.configure_nat(&nat_config).await;
                     let _ = self.stop_dhcp(name).await;
-                    let _ = Command::new("ip")
-                        .args(["addr", "flush", "dev", name])
-                        .output();
+                    let _ = exec::run(Command::new("ip").args(["addr", "flush", "dev", name]));
                 }
                 SwitchProfile::External { uplink } => {
-                    let _ = Command::new("ip")
-                        .args(["link", "set", "dev", &uplink, "nomaster"])
-                        .output();
+                    let _ = exec::run(
+                        Command::new("ip").args(["link", "set", "dev", &uplink, "nomaster"]),
+                    );
                 }
                 SwitchProfile::Internal => {}
             }
@@ -754,16 +852,102 @@ impl NetworkManager {
         Ok(())
     }
 
+    /// Put the bridge itself in `vid`. Linux bridges get VLAN filtering with
+    /// `vid` as the default PVID, so ports libvirt adds later (VM taps) join
+    /// it untagged too.
+    fn configure_switch_vlan(&self, name: &str, switch_type: &SwitchType, vid: u16) -> Result<()> {
+        if is_test_mode() {
+            log_debug!("[test] Pretending to tag bridge {} with VLAN {}", name, vid);
+            return Ok(());
+        }
+
+        let vid = vid.to_string();
+        let tag = format!("tag={}", vid);
+        let (program, args) = match switch_type {
+            SwitchType::LinuxBridge => (
+                "ip",
+                vec![
+                    "link",
+                    "set",
+                    "dev",
+                    name,
+                    "type",
+                    "bridge",
+                    "vlan_filtering",
+                    "1",
+                    "vlan_default_pvid",
+                    &vid,
+                ],
+            ),
+            SwitchType::OpenVSwitch => ("ovs-vsctl", vec!["set", "port", name, &tag]),
+        };
+        let output = exec::run(Command::new(program).args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            log_error!(
+                "Failed to set VLAN {} on bridge {}: {}",
+                vid,
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        log_info!("Bridge {} tagged with VLAN {}", name, vid);
+        Ok(())
+    }
+
+    /// Make a newly attached port an access port in `vid`, or for the uplink
+    /// carry `vid` tagged.
+    fn tag_switch_port(
+        &self,
+        switch_type: &SwitchType,
+        interface: &str,
+        vid: u16,
+        uplink: bool,
+    ) -> Result<()> {
+        if is_test_mode() {
+            log_debug!(
+                "[test] Pretending to tag port {} with VLAN {}",
+                interface,
+                vid
+            );
+            return Ok(());
+        }
+
+        let vid = vid.to_string();
+        let tag = format!("tag={}", vid);
+        let (program, args) = match (switch_type, uplink) {
+            // Re-adding the default PVID without flags makes it tagged
+            (SwitchType::LinuxBridge, true) => {
+                ("bridge", vec!["vlan", "add", "dev", interface, "vid", &vid])
+            }
+            // Other Linux bridge ports already joined via vlan_default_pvid
+            (SwitchType::LinuxBridge, false) => return Ok(()),
+            // OVS ports without a tag are trunks for every VLAN
+            (SwitchType::OpenVSwitch, true) => return Ok(()),
+            (SwitchType::OpenVSwitch, false) => ("ovs-vsctl", vec!["set", "port", interface, &tag]),
+        };
+        let output = exec::run(Command::new(program).args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            log_error!(
+                "Failed to tag port {} with VLAN {}: {}",
+                interface,
+                vid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
+        Ok(())
+    }
+
     async fn delete_linux_bridge(&self, name: &str) -> Result<()> {
         // Bring bridge down first
-        let _ = Command::new("ip")
-            .args(["link", "set", "dev", name, "down"])
-            .output();
+        let _ = exec::run(Command::new("ip").args(["link", "set", "dev", name, "down"]));
 
         // Delete bridge
-        let output = Command::new("ip")
-            .args(["link", "delete", name, "type", "bridge"])
-            .output()
+        let output = exec::run(Command::new("ip").args(["link", "delete", name, "type", "bridge"]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -776,9 +960,7 @@ impl NetworkManager {
     }
 
     async fn delete_ovs_bridge(&self, name: &str) -> Result<()> {
-        let output = Command::new("ovs-vsctl")
-            .args(["del-br", name])
-            .output()
+        let output = exec::run(Command::new("ovs-vsctl").args(["del-br", name]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -790,10 +972,14 @@ impl NetworkManager {
         Ok(())
     }
 
+    /// Enslave `interface` to `switch_name`. The interface carrying the
+    /// host's default route is refused unless `force` is set, since the host
+    /// loses its connection as soon as the NIC joins the bridge.
     pub async fn add_interface_to_switch(
         &mut self,
         switch_name: &str,
         interface: &str,
+        force: bool,
     ) -> Result<()> {
         log_info!("Adding interface {} to switch {}", interface, switch_name);
 
@@ -802,6 +988,7 @@ impl NetworkManager {
         } else {
             return Err(NovaError::NetworkNotFound(switch_name.to_string()));
         };
+        ensure_not_default_route(interface, switch_name, force)?;
 
         match switch_type {
             SwitchType::LinuxBridge => {
@@ -814,6 +1001,16 @@ impl NetworkManager {
             }
         }
 
+        if let Some(switch) = self.switches.get(switch_name)
+            && let Some(vid) = switch.vlan_id
+        {
+            let uplink = matches!(
+                &switch.profile,
+                Some(SwitchProfile::External { uplink }) if uplink == interface
+            );
+            self.tag_switch_port(&switch_type, interface, vid, uplink)?;
+        }
+
         if let Some(switch) = self.switches.get_mut(switch_name)
             && !switch.interfaces.iter().any(|i| i == interface)
         {
@@ -842,10 +1039,10 @@ impl NetworkManager {
 
         match switch_type {
             SwitchType::LinuxBridge => {
-                let output = Command::new("ip")
-                    .args(["link", "set", "dev", interface, "nomaster"])
-                    .output()
-                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                let output = exec::run(
+                    Command::new("ip").args(["link", "set", "dev", interface, "nomaster"]),
+                )
+                .map_err(|_| NovaError::SystemCommandFailed)?;
 
                 if !output.status.success() {
                     log_error!(
@@ -857,10 +1054,9 @@ impl NetworkManager {
                 }
             }
             SwitchType::OpenVSwitch => {
-                let output = Command::new("ovs-vsctl")
-                    .args(["del-port", switch_name, interface])
-                    .output()
-                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                let output =
+                    exec::run(Command::new("ovs-vsctl").args(["del-port", switch_name, interface]))
+                        .map_err(|_| NovaError::SystemCommandFailed)?;
 
                 if !output.status.success() {
                     log_error!(
@@ -890,10 +1086,9 @@ impl NetworkManager {
             return Ok(());
         }
 
-        let output = Command::new("ip")
-            .args(["link", "set", "dev", interface, "master", bridge])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output =
+            exec::run(Command::new("ip").args(["link", "set", "dev", interface, "master", bridge]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_error!("Failed to add interface {} to bridge {}", interface, bridge);
@@ -918,9 +1113,7 @@ impl NetworkManager {
             return Ok(());
         }
 
-        let output = Command::new("ovs-vsctl")
-            .args(["add-port", bridge, interface])
-            .output()
+        let output = exec::run(Command::new("ovs-vsctl").args(["add-port", bridge, interface]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -943,6 +1136,9 @@ impl NetworkManager {
     // Bridge Configuration
     pub async fn configure_bridge(&self, config: &BridgeConfig) -> Result<()> {
         log_info!("Configuring bridge: {}", config.name);
+        if let Some(mtu) = config.mtu {
+            validate_mtu(mtu)?;
+        }
 
         // Configure STP
         if config.stp {
@@ -969,24 +1165,34 @@ impl NetworkManager {
                 .await?;
         }
 
+        if let Some(mtu) = config.mtu {
+            // Members first: a bridge can't carry frames larger than its ports
+            let members = self
+                .get_switch(&config.name)
+                .map(|switch| switch.interfaces.clone())
+                .unwrap_or_default();
+            for member in &members {
+                set_link_mtu(member, mtu)?;
+            }
+            set_link_mtu(&config.name, mtu)?;
+        }
+
         log_info!("Bridge {} configured successfully", config.name);
         Ok(())
     }
 
     async fn enable_stp(&self, bridge: &str) -> Result<()> {
-        let output = Command::new("ip")
-            .args([
-                "link",
-                "set",
-                "dev",
-                bridge,
-                "type",
-                "bridge",
-                "stp_state",
-                "1",
-            ])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(Command::new("ip").args([
+            "link",
+            "set",
+            "dev",
+            bridge,
+            "type",
+            "bridge",
+            "stp_state",
+            "1",
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_error!("Failed to enable STP on bridge {}", bridge);
@@ -1000,11 +1206,12 @@ impl NetworkManager {
     async fn set_bridge_parameter(&self, bridge: &str, param: &str, value: &str) -> Result<()> {
         let sysfs_path = format!("/sys/class/net/{}/bridge/{}", bridge, param);
 
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(format!("echo {} > {}", value, sysfs_path))
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(
+            Command::new("sh")
+                .arg("-c")
+                .arg(format!("echo {} > {}", value, sysfs_path)),
+        )
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_warn!(
@@ -1042,14 +1249,10 @@ impl NetworkManager {
                 source,
                 bridge
             );
-            let _ = Command::new("ip")
-                .args(["addr", "flush", "dev", source])
-                .output();
+            let _ = exec::run(Command::new("ip").args(["addr", "flush", "dev", source]));
         }
 
-        let output = Command::new("ip")
-            .args(["addr", "replace", cidr, "dev", bridge])
-            .output()
+        let output = exec::run(Command::new("ip").args(["addr", "replace", cidr, "dev", bridge]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -1057,9 +1260,7 @@ impl NetworkManager {
             return Err(NovaError::SystemCommandFailed);
         }
 
-        let _ = Command::new("ip")
-            .args(["link", "set", "dev", bridge, "up"])
-            .output();
+        let _ = exec::run(Command::new("ip").args(["link", "set", "dev", bridge, "up"]));
 
         log_info!("Assigned address {} to bridge {}", cidr, bridge);
         Ok(())
@@ -1069,8 +1270,9 @@ impl NetworkManager {
     pub async fn discover_interfaces(&mut self) -> Result<()> {
         log_info!("Discovering network interfaces");
 
+        // `-d` adds `linkinfo`, which is how bridges are told apart
         let output = Command::new("ip")
-            .args(["-j", "link", "show"])
+            .args(["-d", "-j", "link", "show"])
             .output()
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
@@ -1129,6 +1331,21 @@ impl NetworkManager {
                         .as_u64()
                         .or_else(|| interface_data["speed"].as_u64());
 
+                    let alias = interface_data["ifalias"]
+                        .as_str()
+                        .filter(|alias| !alias.is_empty())
+                        .map(|alias| alias.to_string());
+
+                    let altnames = interface_data["altnames"]
+                        .as_array()
+                        .map(|names| {
+                            names
+                                .iter()
+                                .filter_map(|name| name.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
                     let interface = NetworkInterface {
                         name: name.to_string(),
                         mac_address,
@@ -1136,6 +1353,8 @@ impl NetworkManager {
                         state: state.clone(),
                         bridge,
                         speed,
+                        alias,
+                        altnames,
                     };
 
                     self.interfaces.insert(name.to_string(), interface);
@@ -1173,6 +1392,7 @@ impl NetworkManager {
                                     status,
                                     origin: SwitchOrigin::System,
                                     profile: None,
+                                    trunk_ports: BTreeMap::new(),
                                 });
                             }
                         }
@@ -1193,21 +1413,19 @@ impl NetworkManager {
         let vlan_name = format!("{}.{}", base_interface, vlan_id);
         log_info!("Creating VLAN interface: {}", vlan_name);
 
-        let output = Command::new("ip")
-            .args([
-                "link",
-                "add",
-                "link",
-                base_interface,
-                "name",
-                &vlan_name,
-                "type",
-                "vlan",
-                "id",
-                &vlan_id.to_string(),
-            ])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(Command::new("ip").args([
+            "link",
+            "add",
+            "link",
+            base_interface,
+            "name",
+            &vlan_name,
+            "type",
+            "vlan",
+            "id",
+            &vlan_id.to_string(),
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_error!("Failed to create VLAN interface {}", vlan_name);
@@ -1215,14 +1433,290 @@ impl NetworkManager {
         }
 
         // Bring VLAN interface up
-        let _ = Command::new("ip")
-            .args(["link", "set", "dev", &vlan_name, "up"])
-            .output();
+        let _ = exec::run(Command::new("ip").args(["link", "set", "dev", &vlan_name, "up"]));
 
         log_info!("VLAN interface {} created successfully", vlan_name);
         Ok(vlan_name)
     }
 
+    /// Make `iface` (an uplink or a VM's tap) a trunk port of `switch_name`
+    /// carrying exactly `vids` tagged.
+    ///
+    /// The bridge must have VLAN filtering enabled; VIDs tagged on the port
+    /// before but missing from `vids` are removed.
+    pub async fn set_trunk(&mut self, switch_name: &str, iface: &str, vids: &[u16]) -> Result<()> {
+        validate_vlan_ids(vids)?;
+        let switch = self
+            .switches
+            .get(switch_name)
+            .ok_or_else(|| NovaError::NetworkNotFound(switch_name.to_string()))?;
+        if switch.switch_type != SwitchType::LinuxBridge {
+            return Err(NovaError::ConfigError(format!(
+                "'{}' is an Open vSwitch bridge; set trunks with `ovs-vsctl set port {} trunks=...`",
+                switch_name, iface
+            )));
+        }
+        if !switch.interfaces.iter().any(|member| member == iface) {
+            return Err(NovaError::ConfigError(format!(
+                "Interface '{}' is not a member of bridge '{}'; attach it first with `nova network attach {} {}`",
+                iface, switch_name, switch_name, iface
+            )));
+        }
+        if !is_test_mode() && !bridge_vlan_filtering(switch_name) {
+            return Err(NovaError::ConfigError(format!(
+                "Bridge '{}' does not have VLAN filtering enabled; enable it with `ip link set dev {} type bridge vlan_filtering 1`",
+                switch_name, switch_name
+            )));
+        }
+
+        let mut vids = vids.to_vec();
+        vids.sort_unstable();
+        vids.dedup();
+        let previous = switch.trunk_ports.get(iface).cloned().unwrap_or_default();
+        let stale: Vec<u16> = previous
+            .into_iter()
+            .filter(|vid| !vids.contains(vid))
+            .collect();
+
+        log_info!(
+            "Configuring {} on {} as a trunk for VLANs {}",
+            iface,
+            switch_name,
+            format_vlan_ranges(&vids)
+        );
+        if !is_test_mode() {
+            for (action, list) in [("del", &stale), ("add", &vids)] {
+                for (start, end) in vlan_ranges(list) {
+                    let vid = format_vlan_range(start, end);
+                    let output = exec::run(
+                        Command::new("bridge").args(["vlan", action, "dev", iface, "vid", &vid]),
+                    )
+                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                    if !output.status.success() {
+                        log_error!(
+                            "bridge vlan {} vid {} on {} failed: {}",
+                            action,
+                            vid,
+                            iface,
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        );
+                        return Err(NovaError::SystemCommandFailed);
+                    }
+                }
+            }
+        }
+
+        if let Some(switch) = self.switches.get_mut(switch_name) {
+            switch.trunk_ports.insert(iface.to_string(), vids);
+        }
+        Ok(())
+    }
+
+    /// Read tagged VLANs of bridge ports from `bridge -j vlan show`.
+    fn discover_trunk_ports(&mut self) {
+        if is_test_mode() {
+            return;
+        }
+        let Some(output) = Command::new("bridge")
+            .args(["-j", "vlan", "show"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+        else {
+            return;
+        };
+        let tagged = parse_bridge_vlan_json(&String::from_utf8_lossy(&output.stdout));
+        for switch in self.switches.values_mut() {
+            switch.trunk_ports = switch
+                .interfaces
+                .iter()
+                .filter_map(|iface| Some((iface.clone(), tagged.get(iface)?.clone())))
+                .collect();
+        }
+    }
+
+    /// Forwarding database of `switch_name`, sorted by port then MAC.
+    pub fn bridge_fdb(&self, switch_name: &str) -> Result<Vec<FdbEntry>> {
+        let switch = self
+            .switches
+            .get(switch_name)
+            .ok_or_else(|| NovaError::NetworkNotFound(switch_name.to_string()))?;
+        if is_test_mode() {
+            return Ok(Vec::new());
+        }
+        let (program, args) = match switch.switch_type {
+            SwitchType::LinuxBridge => ("bridge", vec!["-j", "fdb", "show", "br", switch_name]),
+            SwitchType::OpenVSwitch => ("ovs-appctl", vec!["fdb/show", switch_name]),
+        };
+        let output = Command::new(program)
+            .args(&args)
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            log_error!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut entries = match switch.switch_type {
+            SwitchType::LinuxBridge => parse_bridge_fdb_json(&stdout),
+            SwitchType::OpenVSwitch => parse_ovs_fdb(&stdout),
+        };
+        entries.sort_by(|a, b| (&a.port, &a.mac).cmp(&(&b.port, &b.mac)));
+        Ok(entries)
+    }
+
+    /// Ports of an Open vSwitch bridge from `ovs-vsctl list-ports`.
+    pub fn ovs_ports(&self, switch_name: &str) -> Result<Vec<String>> {
+        self.ensure_ovs_switch(switch_name)?;
+        if is_test_mode() {
+            return Ok(Vec::new());
+        }
+        let stdout = ovs_stdout("ovs-vsctl", &["list-ports", switch_name])?;
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// OpenFlow table of an Open vSwitch bridge from `ovs-ofctl dump-flows`.
+    pub fn ovs_flows(&self, switch_name: &str) -> Result<Vec<OvsFlow>> {
+        self.ensure_ovs_switch(switch_name)?;
+        if is_test_mode() {
+            return Ok(Vec::new());
+        }
+        let stdout = ovs_stdout("ovs-ofctl", &["dump-flows", switch_name])?;
+        Ok(parse_ovs_flows(&stdout))
+    }
+
+    fn ensure_ovs_switch(&self, switch_name: &str) -> Result<()> {
+        let switch = self
+            .switches
+            .get(switch_name)
+            .ok_or_else(|| NovaError::NetworkNotFound(switch_name.to_string()))?;
+        if switch.switch_type != SwitchType::OpenVSwitch {
+            return Err(NovaError::NetworkError(format!(
+                "{} is a Linux bridge; OpenFlow data is only available for Open vSwitch bridges",
+                switch_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// DHCP leases handed out on `bridge`, from Nova's dnsmasq lease file
+    /// for NAT switches or libvirt's for its own networks (`virbr0`).
+    /// Leases whose MAC belongs to a running VM's NIC name that VM.
+    pub fn dhcp_leases(&self, bridge: &str) -> Result<Vec<DhcpLease>> {
+        let nova_file = nova_lease_file(bridge);
+        let libvirt_file = Path::new(LIBVIRT_DNSMASQ_DIR).join(format!("{}.status", bridge));
+        let mut leases = if let Ok(content) = fs::read_to_string(&nova_file) {
+            parse_dnsmasq_leases(&content)
+        } else if let Ok(content) = fs::read_to_string(&libvirt_file) {
+            parse_libvirt_leases(&content)
+        } else if self.switches.contains_key(bridge) || bridge_exists(bridge) {
+            return Err(NovaError::NetworkError(format!(
+                "No DHCP lease file for {}; only NAT switches and libvirt networks run a DHCP server \
+                 (looked for {} and {})",
+                bridge,
+                nova_file.display(),
+                libvirt_file.display()
+            )));
+        } else {
+            return Err(NovaError::NetworkNotFound(bridge.to_string()));
+        };
+
+        let vms: HashMap<String, String> = self
+            .connected_vms(bridge)
+            .into_iter()
+            .map(|nic| (nic.mac, nic.vm))
+            .collect();
+        for lease in &mut leases {
+            lease.vm = vms.get(&lease.mac).cloned();
+        }
+        leases.sort_by_key(|lease| lease.ip);
+        Ok(leases)
+    }
+
+    /// Running VMs with a NIC on `switch_name`.
+    pub fn connected_vms(&self, switch_name: &str) -> Vec<ConnectedVm> {
+        self.connected_vms_by_bridge()
+            .remove(switch_name)
+            .unwrap_or_default()
+    }
+
+    /// Running VMs' NICs grouped by the bridge they sit on. NICs on a libvirt
+    /// network are mapped to that network's bridge (e.g. `default` ->
+    /// `virbr0`); the tap's current master wins when it is known.
+    pub fn connected_vms_by_bridge(&self) -> HashMap<String, Vec<ConnectedVm>> {
+        let mut by_bridge: HashMap<String, Vec<ConnectedVm>> = HashMap::new();
+        if is_test_mode() {
+            return by_bridge;
+        }
+        let Some(running) = virsh_stdout(&["list", "--name"]) else {
+            return by_bridge;
+        };
+
+        let mut network_bridges: HashMap<String, Option<String>> = HashMap::new();
+        for vm in running.lines().map(str::trim).filter(|vm| !vm.is_empty()) {
+            let interfaces = virsh_stdout(&["domiflist", vm])
+                .map(|output| parse_domiflist(&output))
+                .unwrap_or_default();
+            if interfaces.is_empty() {
+                continue;
+            }
+
+            let mut addresses = virsh_stdout(&["domifaddr", vm])
+                .map(|output| parse_domifaddr(&output))
+                .unwrap_or_default();
+            if interfaces
+                .iter()
+                .any(|iface| !addresses.contains_key(&iface.mac))
+            {
+                // Bridged NICs have no libvirt lease; the host ARP table may know them
+                for (mac, ip) in virsh_stdout(&["domifaddr", vm, "--source", "arp"])
+                    .map(|output| parse_domifaddr(&output))
+                    .unwrap_or_default()
+                {
+                    addresses.entry(mac).or_insert(ip);
+                }
+            }
+
+            for iface in interfaces {
+                let network = (iface.kind == "network").then(|| iface.source.clone());
+                let bridge = iface
+                    .tap
+                    .as_ref()
+                    .and_then(|tap| self.interfaces.get(tap))
+                    .and_then(|tap| tap.bridge.clone())
+                    .or_else(|| match iface.kind.as_str() {
+                        "bridge" => Some(iface.source.clone()),
+                        "network" => network_bridges
+                            .entry(iface.source.clone())
+                            .or_insert_with(|| libvirt_network_bridge(&iface.source))
+                            .clone(),
+                        _ => None,
+                    });
+                let Some(bridge) = bridge else {
+                    continue;
+                };
+                by_bridge.entry(bridge).or_default().push(ConnectedVm {
+                    vm: vm.to_string(),
+                    tap: iface.tap,
+                    ip_address: addresses.get(&iface.mac).cloned(),
+                    mac: iface.mac,
+                    network,
+                });
+            }
+        }
+        by_bridge
+    }
+
     // Utility functions
     pub fn list_switches(&self) -> Vec<&VirtualSwitch> {
         self.switches.values().collect()
@@ -1250,6 +1744,135 @@ impl NetworkManager {
             || Path::new(&format!("/sys/class/net/{}", name)).exists()
     }
 
+    /// Find an interface by kernel name, alias, or alternative name.
+    pub fn resolve_interface(&self, reference: &str) -> Option<&NetworkInterface> {
+        self.interfaces.get(reference).or_else(|| {
+            let mut matches: Vec<&NetworkInterface> = self
+                .interfaces
+                .values()
+                .filter(|iface| {
+                    iface.alias.as_deref() == Some(reference)
+                        || iface.altnames.iter().any(|name| name == reference)
+                })
+                .collect();
+            matches.sort_by(|a, b| a.name.cmp(&b.name));
+            matches.into_iter().next()
+        })
+    }
+
+    /// Stable udev names for an interface (PCI path and MAC based).
+    pub fn persistent_names(&self, iface: &str) -> PersistentNames {
+        let mac = self
+            .interfaces
+            .get(iface)
+            .map(|interface| interface.mac_address.clone())
+            .unwrap_or_default();
+
+        let properties = Command::new("udevadm")
+            .args([
+                "info",
+                "--query=property",
+                &format!("/sys/class/net/{}", iface),
+            ])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default();
+
+        parse_udev_net_names(&properties, &mac)
+    }
+
+    /// Set the kernel alias of `iface` and persist it through the active
+    /// network backend so it is reapplied after reboot.
+    pub async fn set_interface_alias(
+        &mut self,
+        iface: &str,
+        alias: &str,
+    ) -> Result<AliasPersistence> {
+        validate_interface_alias(alias)?;
+        let mac = self.interface_mac(iface).await?;
+
+        log_info!("Setting alias '{}' on interface {}", alias, iface);
+        self.apply_kernel_alias(iface, alias)?;
+
+        let persistence = if Self::uses_systemd_networkd().await {
+            let path = Path::new(SYSTEMD_NETWORK_DIR).join(format!("10-nova-alias-{}.link", iface));
+            exec::write_file(&path, &render_alias_link_file(iface, &mac, alias))?;
+            AliasPersistence::SystemdLink(path)
+        } else {
+            let path = Path::new(UDEV_RULES_DIR).join(format!("70-nova-alias-{}.rules", iface));
+            exec::write_file(&path, &render_alias_udev_rule(iface, &mac, alias))?;
+            AliasPersistence::UdevRule(path)
+        };
+
+        if let Some(interface) = self.interfaces.get_mut(iface) {
+            interface.alias = Some(alias.to_string());
+        }
+
+        log_info!(
+            "Alias for {} persisted to {}",
+            iface,
+            persistence.path().display()
+        );
+        Ok(persistence)
+    }
+
+    /// Remove the alias from `iface` along with any persisted definition.
+    pub async fn clear_interface_alias(&mut self, iface: &str) -> Result<()> {
+        self.interface_mac(iface).await?;
+
+        log_info!("Clearing alias on interface {}", iface);
+        self.apply_kernel_alias(iface, "")?;
+
+        for path in [
+            Path::new(SYSTEMD_NETWORK_DIR).join(format!("10-nova-alias-{}.link", iface)),
+            Path::new(UDEV_RULES_DIR).join(format!("70-nova-alias-{}.rules", iface)),
+        ] {
+            if path.exists() {
+                exec::run(Command::new("rm").arg("-f").arg(&path))?;
+            }
+        }
+
+        if let Some(interface) = self.interfaces.get_mut(iface) {
+            interface.alias = None;
+        }
+        Ok(())
+    }
+
+    async fn interface_mac(&mut self, iface: &str) -> Result<String> {
+        if !self.interfaces.contains_key(iface) {
+            self.discover_interfaces().await?;
+        }
+
+        self.interfaces
+            .get(iface)
+            .map(|interface| interface.mac_address.clone())
+            .ok_or_else(|| NovaError::NetworkNotFound(iface.to_string()))
+    }
+
+    fn apply_kernel_alias(&self, iface: &str, alias: &str) -> Result<()> {
+        let output =
+            exec::run(Command::new("ip").args(["link", "set", "dev", iface, "alias", alias]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to set alias on {}: {}", iface, stderr);
+            return Err(NovaError::NetworkError(format!(
+                "Failed to set alias on {}: {}",
+                iface,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn uses_systemd_networkd() -> bool {
+        let mut backend = ArchNetworkManager::new();
+        backend.detect_network_manager().await.is_ok() && backend.is_using_systemd_networkd()
+    }
+
     fn check_ovs_available(&self) -> bool {
         Command::new("ovs-vsctl")
             .arg("--version")
@@ -1284,30 +1907,31 @@ impl NetworkManager {
 
         if self.check_ovs_available() {
             // Use OVS for advanced port mirroring
-            let output = Command::new("ovs-vsctl")
-                .args([
-                    "--",
-                    "--id=@m",
-                    "create",
-                    "mirror",
-                    &format!("name=mirror-{}-{}", source_port, target_port),
-                ])
-                .arg("--")
-                .args(["--id=@in", "get", "port", source_port])
-                .arg("--")
-                .args(["--id=@out", "get", "port", target_port])
-                .arg("--")
-                .args(["set", "bridge", bridge, "mirrors=@m"])
-                .arg("--")
-                .args([
-                    "set",
-                    "mirror",
-                    "@m",
-                    "select_src_port=@in",
-                    "output_port=@out",
-                ])
-                .output()
-                .map_err(|_| NovaError::SystemCommandFailed)?;
+            let output = exec::run(
+                Command::new("ovs-vsctl")
+                    .args([
+                        "--",
+                        "--id=@m",
+                        "create",
+                        "mirror",
+                        &format!("name=mirror-{}-{}", source_port, target_port),
+                    ])
+                    .arg("--")
+                    .args(["--id=@in", "get", "port", source_port])
+                    .arg("--")
+                    .args(["--id=@out", "get", "port", target_port])
+                    .arg("--")
+                    .args(["set", "bridge", bridge, "mirrors=@m"])
+                    .arg("--")
+                    .args([
+                        "set",
+                        "mirror",
+                        "@m",
+                        "select_src_port=@in",
+                        "output_port=@out",
+                    ]),
+            )
+            .map_err(|_| NovaError::SystemCommandFailed)?;
 
             if !output.status.success() {
                 log_error!("Failed to enable OVS port mirroring");
@@ -1338,38 +1962,34 @@ impl NetworkManager {
         match filter.action {
             FilterAction::Allow => {
                 // Use ebtables for bridge-level filtering
-                let output = Command::new("ebtables")
-                    .args([
-                        "-A",
-                        "FORWARD",
-                        "-i",
-                        bridge,
-                        "-p",
-                        &filter.protocol,
-                        "-j",
-                        "ACCEPT",
-                    ])
-                    .output()
-                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                let output = exec::run(Command::new("ebtables").args([
+                    "-A",
+                    "FORWARD",
+                    "-i",
+                    bridge,
+                    "-p",
+                    &filter.protocol,
+                    "-j",
+                    "ACCEPT",
+                ]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
 
                 if !output.status.success() {
                     log_warn!("Failed to apply bridge filter rule");
                 }
             }
             FilterAction::Deny => {
-                let output = Command::new("ebtables")
-                    .args([
-                        "-A",
-                        "FORWARD",
-                        "-i",
-                        bridge,
-                        "-p",
-                        &filter.protocol,
-                        "-j",
-                        "DROP",
-                    ])
-                    .output()
-                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                let output = exec::run(Command::new("ebtables").args([
+                    "-A",
+                    "FORWARD",
+                    "-i",
+                    bridge,
+                    "-p",
+                    &filter.protocol,
+                    "-j",
+                    "DROP",
+                ]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
 
                 if !output.status.success() {
                     log_warn!("Failed to apply bridge filter rule");
@@ -1439,9 +2059,14 @@ impl NetworkManager {
             "log-dhcp
 ",
         );
+        dhcp_conf.push_str(&format!(
+            "dhcp-leasefile={}
+",
+            nova_lease_file(interface).display()
+        ));
 
         // Write configuration
-        std::fs::write(&conf_file, dhcp_conf).map_err(|e| {
+        exec::write_file(&conf_file, &dhcp_conf).map_err(|e| {
             log_error!("Failed to write DHCP config: {}", e);
             NovaError::SystemCommandFailed
         })?;
@@ -1450,15 +2075,16 @@ impl NetworkManager {
         let pid_file = format!("/tmp/nova-dhcp-{}.pid", interface);
         let log_file = format!("/tmp/nova-dhcp-{}.log", interface);
 
-        let output = Command::new("dnsmasq")
-            .args(["-C", &conf_file])
-            .args(["--pid-file", &pid_file])
-            .args(["--log-facility", &log_file])
-            .output()
-            .map_err(|e| {
-                log_error!("Failed to start dnsmasq: {}", e);
-                NovaError::SystemCommandFailed
-            })?;
+        let output = exec::run(
+            Command::new("dnsmasq")
+                .args(["-C", &conf_file])
+                .args(["--pid-file", &pid_file])
+                .args(["--log-facility", &log_file]),
+        )
+        .map_err(|e| {
+            log_error!("Failed to start dnsmasq: {}", e);
+            NovaError::SystemCommandFailed
+        })?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -1483,7 +2109,7 @@ impl NetworkManager {
         if let Ok(pid_content) = std::fs::read_to_string(&pid_file)
             && let Ok(pid) = pid_content.trim().parse::<u32>()
         {
-            let _ = Command::new("kill").arg(pid.to_string()).output();
+            let _ = exec::run(Command::new("kill").arg(pid.to_string()));
         }
 
         // Clean up files
@@ -1529,9 +2155,7 @@ impl NetworkManager {
         }
 
         // Enable IP forwarding globally
-        let output = Command::new("sysctl")
-            .args(["-w", "net.ipv4.ip_forward=1"])
-            .output()
+        let output = exec::run(Command::new("sysctl").args(["-w", "net.ipv4.ip_forward=1"]))
             .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
@@ -1552,19 +2176,17 @@ impl NetworkManager {
 
     async fn apply_nat_with_iptables(&self, config: &NatConfig) -> Result<()> {
         if config.masquerade {
-            let output = Command::new("iptables")
-                .args([
-                    "-t",
-                    "nat",
-                    "-A",
-                    "POSTROUTING",
-                    "-o",
-                    &config.external_interface,
-                    "-j",
-                    "MASQUERADE",
-                ])
-                .output()
-                .map_err(|_| NovaError::SystemCommandFailed)?;
+            let output = exec::run(Command::new("iptables").args([
+                "-t",
+                "nat",
+                "-A",
+                "POSTROUTING",
+                "-o",
+                &config.external_interface,
+                "-j",
+                "MASQUERADE",
+            ]))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
 
             if !output.status.success() {
                 log_error!("Failed to add masquerade rule via iptables");
@@ -1572,42 +2194,38 @@ impl NetworkManager {
             }
         }
 
-        let output = Command::new("iptables")
-            .args([
-                "-A",
-                "FORWARD",
-                "-i",
-                &config.internal_interface,
-                "-o",
-                &config.external_interface,
-                "-j",
-                "ACCEPT",
-            ])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(Command::new("iptables").args([
+            "-A",
+            "FORWARD",
+            "-i",
+            &config.internal_interface,
+            "-o",
+            &config.external_interface,
+            "-j",
+            "ACCEPT",
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_error!("Failed to add forward rule via iptables");
             return Err(NovaError::SystemCommandFailed);
         }
 
-        let output = Command::new("iptables")
-            .args([
-                "-A",
-                "FORWARD",
-                "-i",
-                &config.external_interface,
-                "-o",
-                &config.internal_interface,
-                "-m",
-                "state",
-                "--state",
-                "RELATED,ESTABLISHED",
-                "-j",
-                "ACCEPT",
-            ])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(Command::new("iptables").args([
+            "-A",
+            "FORWARD",
+            "-i",
+            &config.external_interface,
+            "-o",
+            &config.internal_interface,
+            "-m",
+            "state",
+            "--state",
+            "RELATED,ESTABLISHED",
+            "-j",
+            "ACCEPT",
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if !output.status.success() {
             log_error!("Failed to add return forward rule via iptables");
@@ -1619,58 +2237,50 @@ impl NetworkManager {
 
     async fn remove_nat_with_iptables(&self, config: &NatConfig) -> Result<()> {
         if config.masquerade {
-            let _ = Command::new("iptables")
-                .args([
-                    "-t",
-                    "nat",
-                    "-D",
-                    "POSTROUTING",
-                    "-o",
-                    &config.external_interface,
-                    "-j",
-                    "MASQUERADE",
-                ])
-                .output();
-        }
-
-        let _ = Command::new("iptables")
-            .args([
+            let _ = exec::run(Command::new("iptables").args([
+                "-t",
+                "nat",
                 "-D",
-                "FORWARD",
-                "-i",
-                &config.internal_interface,
+                "POSTROUTING",
                 "-o",
                 &config.external_interface,
                 "-j",
-                "ACCEPT",
-            ])
-            .output();
-
-        let _ = Command::new("iptables")
-            .args([
-                "-D",
-                "FORWARD",
-                "-i",
-                &config.external_interface,
-                "-o",
-                &config.internal_interface,
-                "-m",
-                "state",
-                "--state",
-                "RELATED,ESTABLISHED",
-                "-j",
-                "ACCEPT",
-            ])
-            .output();
+                "MASQUERADE",
+            ]));
+        }
+
+        let _ = exec::run(Command::new("iptables").args([
+            "-D",
+            "FORWARD",
+            "-i",
+            &config.internal_interface,
+            "-o",
+            &config.external_interface,
+            "-j",
+            "ACCEPT",
+        ]));
+
+        let _ = exec::run(Command::new("iptables").args([
+            "-D",
+            "FORWARD",
+            "-i",
+            &config.external_interface,
+            "-o",
+            &config.internal_interface,
+            "-m",
+            "state",
+            "--state",
+            "RELATED,ESTABLISHED",
+            "-j",
+            "ACCEPT",
+        ]));
 
         Ok(())
     }
 
     async fn apply_nat_with_nftables(&self, config: &NatConfig) -> Result<()> {
         let table_name = Self::nft_table_name(&config.internal_interface);
-        let _ = Command::new("nft")
-            .args(["delete", "table", "inet", &table_name])
-            .output();
+        let _ = exec::run(Command::new("nft").args(["delete", "table", "inet", &table_name]));
 
         let script = format!(
             r#"table inet {table_name} {{
@@ -1695,20 +2305,7 @@ impl NetworkManager {
             external = config.external_interface
         );
 
-        let mut child = Command::new("nft")
-            .arg("-f")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
-
-        if let Some(stdin) = child.stdin.as_mut() {
-            stdin.write_all(script.as_bytes())?;
-        }
-
-        let output = child
-            .wait_with_output()
+        let output = exec::run_with_input(Command::new("nft").args(["-f", "-"]), &script)
             .map_err(|_| NovaError::SystemCommandFailed)?;
         if !output.status.success() {
             log_error!(
@@ -1723,9 +2320,7 @@ impl NetworkManager {
 
     async fn remove_nat_with_nftables(&self, config: &NatConfig) -> Result<()> {
         let table_name = Self::nft_table_name(&config.internal_interface);
-        let _ = Command::new("nft")
-            .args(["delete", "table", "inet", &table_name])
-            .output();
+        let _ = exec::run(Command::new("nft").args(["delete", "table", "inet", &table_name]));
         Ok(())
     }
 
@@ -1846,10 +2441,637 @@ fn default_dhcp_range(ip: Ipv4Addr, prefix: u8) -> Option<(Ipv4Addr, Ipv4Addr)>
     Some((Ipv4Addr::from(start), Ipv4Addr::from(end)))
 }
 
+/// Where libvirt's dnsmasq keeps a `<bridge>.status` lease file per network.
+const LIBVIRT_DNSMASQ_DIR: &str = "/var/lib/libvirt/dnsmasq";
+
+/// Lease file of the dnsmasq Nova starts for a NAT switch.
+fn nova_lease_file(interface: &str) -> PathBuf {
+    PathBuf::from(format!("/tmp/nova-dhcp-{}.leases", interface))
+}
+
+/// An address a DHCP server handed out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DhcpLease {
+    pub mac: String,
+    pub ip: Ipv4Addr,
+    pub hostname: Option<String>,
+    /// `None` for infinite leases
+    pub expires: Option<chrono::DateTime<chrono::Utc>>,
+    /// Running VM whose NIC has this MAC
+    pub vm: Option<String>,
+}
+
+/// Parse a dnsmasq lease file:
+/// `<expiry epoch> <mac> <ip> <hostname|*> <client-id|*>` per lease.
+/// IPv6 leases (and the `duid` line before them) are skipped.
+pub fn parse_dnsmasq_leases(content: &str) -> Vec<DhcpLease> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [expiry, mac, ip, hostname, ..] = fields.as_slice() else {
+                return None;
+            };
+            Some(DhcpLease {
+                mac: mac.to_lowercase(),
+                ip: ip.parse().ok()?,
+                hostname: Some(hostname.to_string()).filter(|name| name != "*"),
+                expires: lease_expiry(expiry.parse().ok()?),
+                vm: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse libvirt's `<bridge>.status` file, a JSON array of
+/// `{"ip-address", "mac-address", "hostname", "expiry-time"}` objects.
+pub fn parse_libvirt_leases(json: &str) -> Vec<DhcpLease> {
+    let Ok(serde_json::Value::Array(entries)) = serde_json::from_str(json) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            Some(DhcpLease {
+                mac: entry["mac-address"].as_str()?.to_lowercase(),
+                ip: entry["ip-address"].as_str()?.parse().ok()?,
+                hostname: entry["hostname"].as_str().map(str::to_string),
+                expires: lease_expiry(entry["expiry-time"].as_i64()?),
+                vm: None,
+            })
+        })
+        .collect()
+}
+
+fn lease_expiry(epoch: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+    (epoch > 0)
+        .then(|| chrono::DateTime::from_timestamp(epoch, 0))
+        .flatten()
+}
+
 fn bridge_exists(name: &str) -> bool {
     Path::new(&format!("/sys/class/net/{}", name)).exists()
 }
 
+fn virsh_stdout(args: &[&str]) -> Option<String> {
+    Command::new("virsh")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Bridge device behind a libvirt network, from `virsh net-info`.
+fn libvirt_network_bridge(network: &str) -> Option<String> {
+    virsh_stdout(&["net-info", network])?
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("Bridge:")
+                .map(|bridge| bridge.trim().to_string())
+                .filter(|bridge| !bridge.is_empty())
+        })
+}
+
+/// Parse `virsh domiflist` output.
+pub fn parse_domiflist(output: &str) -> Vec<DomainInterface> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Interface Type Source Model MAC
+            if fields.len() < 5 || !fields[4].contains(':') {
+                return None;
+            }
+            Some(DomainInterface {
+                tap: Some(fields[0].to_string()).filter(|tap| tap != "-"),
+                kind: fields[1].to_string(),
+                source: fields[2].to_string(),
+                mac: fields[4].to_lowercase(),
+            })
+        })
+        .collect()
+}
+
+/// IPv4 addresses by MAC from `virsh domifaddr` output.
+pub fn parse_domifaddr(output: &str) -> HashMap<String, String> {
+    let mut addresses = HashMap::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // Name MAC Protocol Address; continuation rows omit name and MAC
+        if fields.len() >= 4 && fields[2] == "ipv4" && fields[1].contains(':') {
+            let ip = fields[3].split('/').next().unwrap_or(fields[3]);
+            addresses
+                .entry(fields[1].to_lowercase())
+                .or_insert_with(|| ip.to_string());
+        }
+    }
+    addresses
+}
+
+fn bridge_vlan_filtering(name: &str) -> bool {
+    fs::read_to_string(format!("/sys/class/net/{}/bridge/vlan_filtering", name))
+        .is_ok_and(|value| value.trim() == "1")
+}
+
+/// Parse a VID list such as `10,20,30` or `100-110,200`.
+pub fn parse_vlan_ids(spec: &str) -> Result<Vec<u16>> {
+    let mut vids = Vec::new();
+    for part in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let invalid = || NovaError::ConfigError(format!("Invalid VLAN ID '{}'", part));
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start: u16 = start.trim().parse().map_err(|_| invalid())?;
+                let end: u16 = end.trim().parse().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                vids.extend(start..=end);
+            }
+            None => vids.push(part.parse().map_err(|_| invalid())?),
+        }
+    }
+    validate_vlan_ids(&vids)?;
+    vids.sort_unstable();
+    vids.dedup();
+    Ok(vids)
+}
+
+/// 802.1Q VIDs 0 and 4095 are reserved.
+pub fn validate_vlan_ids(vids: &[u16]) -> Result<()> {
+    if vids.is_empty() {
+        return Err(NovaError::ConfigError(
+            "At least one VLAN ID is required".to_string(),
+        ));
+    }
+    if let Some(vid) = vids.iter().find(|vid| !(1..=4094).contains(*vid)) {
+        return Err(NovaError::ConfigError(format!(
+            "VLAN ID {} is out of range (1-4094)",
+            vid
+        )));
+    }
+    Ok(())
+}
+
+/// 576 is the smallest MTU an IPv4 host must accept; 9216 is the usual
+/// jumbo-frame ceiling.
+pub fn validate_mtu(mtu: u32) -> Result<()> {
+    if !(576..=9216).contains(&mtu) {
+        return Err(NovaError::ConfigError(format!(
+            "MTU {} is out of range (576-9216)",
+            mtu
+        )));
+    }
+    Ok(())
+}
+
+/// A default route from `ip -j route show default`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultRoute {
+    pub device: String,
+    pub gateway: Option<String>,
+}
+
+pub fn parse_default_routes(json: &str) -> Vec<DefaultRoute> {
+    let Ok(serde_json::Value::Array(routes)) = serde_json::from_str(json) else {
+        return Vec::new();
+    };
+    routes
+        .iter()
+        .filter_map(|route| {
+            Some(DefaultRoute {
+                device: route["dev"].as_str()?.to_string(),
+                gateway: route["gateway"].as_str().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// IPv4 addresses as `address/prefix` from `ip -j -4 addr show dev <iface>`.
+pub fn parse_ipv4_addresses(json: &str) -> Vec<String> {
+    let Ok(serde_json::Value::Array(links)) = serde_json::from_str(json) else {
+        return Vec::new();
+    };
+    links
+        .iter()
+        .filter_map(|link| link["addr_info"].as_array())
+        .flatten()
+        .filter(|addr| addr["family"] == "inet")
+        .filter_map(|addr| {
+            Some(format!(
+                "{}/{}",
+                addr["local"].as_str()?,
+                addr["prefixlen"].as_u64()?
+            ))
+        })
+        .collect()
+}
+
+/// IPv4 and IPv6 default routes on this host.
+pub fn default_routes() -> Vec<DefaultRoute> {
+    if is_test_mode() {
+        return Vec::new();
+    }
+    ["-4", "-6"]
+        .into_iter()
+        .filter_map(|family| {
+            Command::new("ip")
+                .args(["-j", family, "route", "show", "default"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+        })
+        .flat_map(|output| parse_default_routes(&String::from_utf8_lossy(&output.stdout)))
+        .collect()
+}
+
+fn ensure_not_default_route(interface: &str, bridge: &str, force: bool) -> Result<()> {
+    let Some(route) = default_routes()
+        .into_iter()
+        .find(|route| route.device == interface)
+    else {
+        return Ok(());
+    };
+    let via = route
+        .gateway
+        .as_deref()
+        .map(|gateway| format!(" (via {})", gateway))
+        .unwrap_or_default();
+    if force {
+        log_warn!(
+            "Adding {} to {} although it carries the default route{}",
+            interface,
+            bridge,
+            via
+        );
+        return Ok(());
+    }
+    Err(NovaError::NetworkError(format!(
+        "{} carries the host's default route{}. Adding it to {} moves traffic onto the bridge, \
+         which has no address yet, so the host goes offline and remote sessions drop. Move the \
+         IP to the bridge with --move-ip, or pass --force if you have console access.",
+        interface, via, bridge
+    )))
+}
+
+/// Move `interface`'s IPv4 addresses and default route onto `bridge`, for
+/// a NIC that was just added to it. Returns the moved addresses.
+pub fn move_addresses_to_bridge(interface: &str, bridge: &str) -> Result<Vec<String>> {
+    if is_test_mode() {
+        return Ok(Vec::new());
+    }
+    let output = Command::new("ip")
+        .args(["-j", "-4", "addr", "show", "dev", interface])
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    let addresses = parse_ipv4_addresses(&String::from_utf8_lossy(&output.stdout));
+    let gateway = default_routes()
+        .into_iter()
+        .find(|route| route.device == interface)
+        .and_then(|route| route.gateway);
+
+    let mut commands: Vec<Vec<String>> = addresses
+        .iter()
+        .map(|cidr| owned_args(&["addr", "add", cidr, "dev", bridge]))
+        .collect();
+    commands.push(owned_args(&["addr", "flush", "dev", interface]));
+    commands.push(owned_args(&["link", "set", "dev", bridge, "up"]));
+    if let Some(gateway) = &gateway {
+        commands.push(owned_args(&[
+            "route", "replace", "default", "via", gateway, "dev", bridge,
+        ]));
+    }
+    for args in commands {
+        let output = exec::run(Command::new("ip").args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("`ip {}` failed: {}", args.join(" "), stderr.trim());
+            return Err(NovaError::NetworkError(format!(
+                "Moving addresses from {} to {} failed at `ip {}`: {}",
+                interface,
+                bridge,
+                args.join(" "),
+                stderr.trim()
+            )));
+        }
+    }
+    log_info!(
+        "Moved {:?} and the default route from {} to {}",
+        addresses,
+        interface,
+        bridge
+    );
+    Ok(addresses)
+}
+
+fn owned_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn set_link_mtu(device: &str, mtu: u32) -> Result<()> {
+    if is_test_mode() {
+        log_debug!("[test] Pretending to set MTU {} on {}", mtu, device);
+        return Ok(());
+    }
+    let mtu = mtu.to_string();
+    let output = exec::run(Command::new("ip").args(["link", "set", "dev", device, "mtu", &mtu]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log_error!("Failed to set MTU {} on {}: {}", mtu, device, stderr.trim());
+        return Err(NovaError::NetworkError(format!(
+            "Failed to set MTU {} on {}: {}",
+            mtu,
+            device,
+            stderr.trim()
+        )));
+    }
+    log_debug!("Set MTU {} on {}", mtu, device);
+    Ok(())
+}
+
+/// Collapse sorted VIDs into inclusive ranges.
+fn vlan_ranges(vids: &[u16]) -> Vec<(u16, u16)> {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+    for &vid in vids {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == vid => *end = vid,
+            _ => ranges.push((vid, vid)),
+        }
+    }
+    ranges
+}
+
+/// VIDs as `10,20,100-110`.
+pub fn format_vlan_ranges(vids: &[u16]) -> String {
+    vlan_ranges(vids)
+        .into_iter()
+        .map(|(start, end)| format_vlan_range(start, end))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn format_vlan_range(start: u16, end: u16) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{}-{}", start, end)
+    }
+}
+
+/// Tagged VIDs per port from `bridge -j vlan show`; untagged (PVID/native)
+/// entries are left out.
+pub fn parse_bridge_vlan_json(json: &str) -> HashMap<String, Vec<u16>> {
+    let Ok(serde_json::Value::Array(ports)) = serde_json::from_str(json) else {
+        return HashMap::new();
+    };
+    let mut tagged = HashMap::new();
+    for port in ports {
+        let Some(name) = port["ifname"].as_str() else {
+            continue;
+        };
+        let mut vids = Vec::new();
+        for vlan in port["vlans"].as_array().into_iter().flatten() {
+            let untagged = vlan["flags"]
+                .as_array()
+                .is_some_and(|flags| flags.iter().any(|flag| flag == "Egress Untagged"));
+            let Some(start) = vlan["vlan"].as_u64() else {
+                continue;
+            };
+            if untagged {
+                continue;
+            }
+            let end = vlan["vlanEnd"].as_u64().unwrap_or(start);
+            vids.extend((start..=end).filter_map(|vid| u16::try_from(vid).ok()));
+        }
+        if !vids.is_empty() {
+            tagged.insert(name.to_string(), vids);
+        }
+    }
+    tagged
+}
+
+/// A learned or static entry in a switch's forwarding database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FdbEntry {
+    pub mac: String,
+    /// Member port; Open vSwitch reports the OpenFlow port number
+    pub port: String,
+    pub vlan: Option<u16>,
+    /// Static entries (the bridge's own addresses) rather than learned ones
+    pub permanent: bool,
+}
+
+/// Entries from `bridge -j fdb show br <bridge>`.
+pub fn parse_bridge_fdb_json(json: &str) -> Vec<FdbEntry> {
+    let Ok(serde_json::Value::Array(entries)) = serde_json::from_str(json) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            Some(FdbEntry {
+                mac: entry["mac"].as_str()?.to_string(),
+                port: entry["ifname"].as_str()?.to_string(),
+                vlan: entry["vlan"]
+                    .as_u64()
+                    .and_then(|vid| u16::try_from(vid).ok()),
+                permanent: entry["state"].as_str() == Some("permanent"),
+            })
+        })
+        .collect()
+}
+
+/// Entries from `ovs-appctl fdb/show <bridge>`:
+/// ` port  VLAN  MAC                Age` followed by one row per MAC.
+pub fn parse_ovs_fdb(output: &str) -> Vec<FdbEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [port, vlan, mac, age, ..] = fields.as_slice() else {
+                return None;
+            };
+            let vlan: u16 = vlan.parse().ok()?;
+            Some(FdbEntry {
+                mac: mac.to_string(),
+                port: port.to_string(),
+                vlan: (vlan != 0).then_some(vlan),
+                permanent: *age == "static",
+            })
+        })
+        .collect()
+}
+
+/// One OpenFlow rule from `ovs-ofctl dump-flows`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OvsFlow {
+    pub table: u8,
+    pub priority: u16,
+    pub n_packets: u64,
+    pub n_bytes: u64,
+    /// Match fields as printed, `*` for a flow that matches everything
+    pub matches: String,
+    pub actions: String,
+}
+
+/// Statistics and timeouts `ovs-ofctl` prints before a flow's match.
+const OVS_FLOW_STAT_KEYS: &[&str] = &[
+    "cookie",
+    "duration",
+    "table",
+    "n_packets",
+    "n_bytes",
+    "idle_age",
+    "hard_age",
+    "idle_timeout",
+    "hard_timeout",
+    "importance",
+    "reset_counts",
+    "send_flow_rem",
+    "check_overlap",
+];
+
+/// OpenFlow's priority when `ovs-ofctl` leaves it out.
+const OVS_DEFAULT_PRIORITY: u16 = 32768;
+
+/// Flows from `ovs-ofctl dump-flows <bridge>`, e.g.
+/// ` cookie=0x0, duration=8.4s, table=0, n_packets=12, n_bytes=1008, priority=100,ip actions=drop`.
+pub fn parse_ovs_flows(output: &str) -> Vec<OvsFlow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (fields, actions) = line.trim().split_once("actions=")?;
+            let mut flow = OvsFlow {
+                table: 0,
+                priority: OVS_DEFAULT_PRIORITY,
+                n_packets: 0,
+                n_bytes: 0,
+                matches: String::new(),
+                actions: actions.trim().to_string(),
+            };
+            let mut matches = Vec::new();
+            for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+                let (key, value) = field.split_once('=').unwrap_or((field, ""));
+                match key {
+                    "table" => flow.table = value.parse().unwrap_or(0),
+                    "n_packets" => flow.n_packets = value.parse().unwrap_or(0),
+                    "n_bytes" => flow.n_bytes = value.parse().unwrap_or(0),
+                    "priority" => flow.priority = value.parse().unwrap_or(OVS_DEFAULT_PRIORITY),
+                    _ if OVS_FLOW_STAT_KEYS.contains(&key) => {}
+                    _ => matches.push(field),
+                }
+            }
+            flow.matches = if matches.is_empty() {
+                "*".to_string()
+            } else {
+                matches.join(",")
+            };
+            Some(flow)
+        })
+        .collect()
+}
+
+fn ovs_stdout(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output().map_err(|_| {
+        NovaError::NetworkError(format!(
+            "{} not found; install openvswitch to inspect OVS bridges",
+            program
+        ))
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log_error!("{} {} failed: {}", program, args.join(" "), stderr.trim());
+        return Err(NovaError::NetworkError(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+const SYSTEMD_NETWORK_DIR: &str = "/etc/systemd/network";
+const UDEV_RULES_DIR: &str = "/etc/udev/rules.d";
+
+/// Kernel `ifalias` is limited to 255 bytes; quotes and control characters
+/// would break the persisted udev rule.
+pub fn validate_interface_alias(alias: &str) -> Result<()> {
+    if alias.trim().is_empty() {
+        return Err(NovaError::ConfigError(
+            "Interface alias cannot be empty".to_string(),
+        ));
+    }
+    if alias.len() > 255 {
+        return Err(NovaError::ConfigError(
+            "Interface alias must be at most 255 bytes".to_string(),
+        ));
+    }
+    if alias
+        .chars()
+        .any(|c| c == '"' || c == '\\' || c.is_control())
+    {
+        return Err(NovaError::ConfigError(format!(
+            "Interface alias '{}' contains quotes, backslashes or control characters",
+            alias
+        )));
+    }
+    Ok(())
+}
+
+/// Pick the PCI-path and MAC based names out of `udevadm info` properties.
+pub fn parse_udev_net_names(properties: &str, mac_address: &str) -> PersistentNames {
+    let property = |key: &str| {
+        properties.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        })
+    };
+
+    let pci = property("ID_NET_NAME_ONBOARD")
+        .or_else(|| property("ID_NET_NAME_SLOT"))
+        .or_else(|| property("ID_NET_NAME_PATH"));
+
+    let derived_mac = {
+        let hex: String = mac_address.chars().filter(|c| *c != ':').collect();
+        (hex.len() == 12 && mac_address != "00:00:00:00:00:00")
+            .then(|| format!("enx{}", hex.to_lowercase()))
+    };
+    let mac = property("ID_NET_NAME_MAC").or(derived_mac);
+
+    PersistentNames { pci, mac }
+}
+
+pub fn render_alias_link_file(iface: &str, mac: &str, alias: &str) -> String {
+    format!(
+        "# Managed by Nova: alias for {iface}\n\
+         [Match]\n\
+         MACAddress={mac}\n\
+         \n\
+         [Link]\n\
+         Alias={alias}\n\
+         # Keep the default naming policy that this file overrides\n\
+         NamePolicy=keep kernel database onboard slot path\n\
+         AlternativeNamesPolicy=database onboard slot path\n\
+         MACAddressPolicy=persistent\n"
+    )
+}
+
+pub fn render_alias_udev_rule(iface: &str, mac: &str, alias: &str) -> String {
+    format!(
+        "# Managed by Nova: alias for {iface}\n\
+         ACTION==\"add\", SUBSYSTEM==\"net\", ATTR{{address}}==\"{mac}\", ATTR{{ifalias}}=\"{alias}\"\n"
+    )
+}
+
 fn is_test_mode() -> bool {
     cfg!(test) || matches!(env::var("NOVA_TEST_MODE"), Ok(val) if val == "1")
 }
@@ -1921,6 +3143,7 @@ mod tests {
                 dhcp_range_start: None,
                 dhcp_range_end: None,
             }),
+            vlan_id: None,
         };
 
         persist_switch_state(&nat_state).expect("persist nat state");
@@ -1962,6 +3185,7 @@ mod tests {
             profile: Some(SwitchProfile::External {
                 uplink: "enp3s0".to_string(),
             }),
+            vlan_id: Some(30),
         };
 
         persist_switch_state(&ext_state).expect("persist ext state");
@@ -1981,6 +3205,7 @@ mod tests {
             .expect("ext switch exists");
         assert!(matches!(ext_switch.status, SwitchStatus::Active));
         assert!(ext_switch.interfaces.contains(&"enp3s0".to_string()));
+        assert_eq!(ext_switch.vlan_id, Some(30));
         assert!(matches!(
             ext_switch.profile,
             Some(SwitchProfile::External { .. })
@@ -1996,4 +3221,204 @@ mod tests {
 
         teardown_test_env(tmp);
     }
+
+    #[test]
+    fn udev_names_prefer_onboard_and_fall_back_to_mac() {
+        let props =
+            "ID_NET_NAME_MAC=enx001122334455\nID_NET_NAME_PATH=enp3s0\nID_NET_NAME_SLOT=ens1\n";
+        let names = parse_udev_net_names(props, "00:11:22:33:44:55");
+        assert_eq!(names.pci.as_deref(), Some("ens1"));
+        assert_eq!(names.mac.as_deref(), Some("enx001122334455"));
+
+        let bare = parse_udev_net_names("", "AA:BB:CC:DD:EE:FF");
+        assert_eq!(bare.pci, None);
+        assert_eq!(bare.mac.as_deref(), Some("enxaabbccddeeff"));
+    }
+
+    #[test]
+    fn alias_files_match_on_mac() {
+        let link = render_alias_link_file("enp3s0", "00:11:22:33:44:55", "uplink-10g");
+        assert!(link.contains("[Match]\nMACAddress=00:11:22:33:44:55\n"));
+        assert!(link.contains("Alias=uplink-10g\n"));
+
+        let rule = render_alias_udev_rule("enp3s0", "00:11:22:33:44:55", "uplink-10g");
+        assert!(rule.contains(r#"ATTR{address}=="00:11:22:33:44:55", ATTR{ifalias}="uplink-10g""#));
+
+        assert!(validate_interface_alias("uplink \"10g\"").is_err());
+        assert!(validate_interface_alias("").is_err());
+    }
+
+    #[test]
+    fn trunk_vlans_parse_and_read_back() {
+        assert_eq!(parse_vlan_ids("30,10,20,10").unwrap(), vec![10, 20, 30]);
+        let vids = parse_vlan_ids("100-103, 10").unwrap();
+        assert_eq!(format_vlan_ranges(&vids), "10,100-103");
+        assert!(parse_vlan_ids("0").is_err());
+        assert!(parse_vlan_ids("4095").is_err());
+        assert!(parse_vlan_ids("20-10").is_err());
+        assert!(parse_vlan_ids("").is_err());
+
+        let json = r#"[{"ifname":"br0","vlans":[{"vlan":1,"flags":["PVID","Egress Untagged"]}]},
+            {"ifname":"vnet3","vlans":[{"vlan":1,"flags":["PVID","Egress Untagged"]},{"vlan":10},{"vlan":20,"vlanEnd":22}]}]"#;
+        let tagged = parse_bridge_vlan_json(json);
+        assert_eq!(tagged.get("vnet3"), Some(&vec![10, 20, 21, 22]));
+        assert!(!tagged.contains_key("br0"));
+    }
+
+    #[test]
+    fn bridge_mtu_is_bounded_and_optional() {
+        assert!(validate_mtu(576).is_ok());
+        assert!(validate_mtu(9000).is_ok());
+        assert!(validate_mtu(9216).is_ok());
+        assert!(validate_mtu(575).is_err());
+        assert!(validate_mtu(9217).is_err());
+
+        // Configs saved before the field existed still load
+        let config: BridgeConfig = serde_json::from_str(
+            r#"{"name":"br0","stp":true,"forward_delay":15,"hello_time":2,
+                "max_age":20,"aging_time":300,"multicast_snooping":true}"#,
+        )
+        .unwrap();
+        assert_eq!(config.mtu, None);
+    }
+
+    #[test]
+    fn ovs_flow_dump_parses() {
+        let dump = "NXST_FLOW reply (xid=0x4):
+ cookie=0x0, duration=8.412s, table=0, n_packets=12, n_bytes=1008, idle_age=2, priority=100,ip,nw_dst=10.0.0.1 actions=drop
+ cookie=0x2a, duration=120.5s, table=1, n_packets=0, n_bytes=0, in_port=1 actions=output:2,mod_vlan_vid:10
+ cookie=0x0, duration=300s, table=0, n_packets=4096, n_bytes=524288, priority=0 actions=NORMAL
+";
+        let flows = parse_ovs_flows(dump);
+        assert_eq!(flows.len(), 3);
+        assert_eq!(
+            flows[0],
+            OvsFlow {
+                table: 0,
+                priority: 100,
+                n_packets: 12,
+                n_bytes: 1008,
+                matches: "ip,nw_dst=10.0.0.1".to_string(),
+                actions: "drop".to_string(),
+            }
+        );
+        // Priority omitted means the OpenFlow default
+        assert_eq!(flows[1].priority, 32768);
+        assert_eq!(flows[1].table, 1);
+        assert_eq!(flows[1].matches, "in_port=1");
+        assert_eq!(flows[1].actions, "output:2,mod_vlan_vid:10");
+        assert_eq!(flows[2].matches, "*");
+        assert_eq!(flows[2].n_packets, 4096);
+    }
+
+    #[test]
+    fn default_route_and_addresses_parse() {
+        let routes = r#"[{"dst":"default","gateway":"192.168.1.1","dev":"enp5s0","protocol":"dhcp","prefsrc":"192.168.1.20","metric":100,"flags":[]},
+            {"dst":"default","dev":"wg0","scope":"link","flags":[]}]"#;
+        assert_eq!(
+            parse_default_routes(routes),
+            [
+                DefaultRoute {
+                    device: "enp5s0".to_string(),
+                    gateway: Some("192.168.1.1".to_string()),
+                },
+                DefaultRoute {
+                    device: "wg0".to_string(),
+                    gateway: None,
+                },
+            ]
+        );
+        assert!(parse_default_routes("").is_empty());
+
+        let addresses = r#"[{"ifindex":2,"ifname":"enp5s0","addr_info":[
+            {"family":"inet","local":"192.168.1.20","prefixlen":24,"scope":"global"},
+            {"family":"inet","local":"10.0.0.5","prefixlen":16,"scope":"global","secondary":true}]}]"#;
+        assert_eq!(
+            parse_ipv4_addresses(addresses),
+            ["192.168.1.20/24", "10.0.0.5/16"]
+        );
+
+        // Test mode sees no default route, so the guard lets the NIC through
+        assert!(ensure_not_default_route("enp5s0", "br0", false).is_ok());
+    }
+
+    #[test]
+    fn domain_interfaces_and_addresses_parse() {
+        let domiflist = " Interface   Type      Source    Model    MAC
+-------------------------------------------------------------
+ vnet0       network   default   virtio   52:54:00:AA:BB:CC
+ vnet1       bridge    br0       virtio   52:54:00:11:22:33
+ -           bridge    br1       e1000e   52:54:00:44:55:66
+";
+        let interfaces = parse_domiflist(domiflist);
+        assert_eq!(interfaces.len(), 3);
+        assert_eq!(interfaces[0].kind, "network");
+        assert_eq!(interfaces[0].mac, "52:54:00:aa:bb:cc");
+        assert_eq!(interfaces[1].source, "br0");
+        assert_eq!(interfaces[2].tap, None);
+
+        let domifaddr = " Name       MAC address          Protocol     Address
+-------------------------------------------------------------------------------
+ vnet0      52:54:00:aa:bb:cc    ipv4         192.168.122.50/24
+ -          -                    ipv6         fe80::5054:ff:feaa:bbcc/64
+";
+        let addresses = parse_domifaddr(domifaddr);
+        assert_eq!(
+            addresses.get("52:54:00:aa:bb:cc").map(String::as_str),
+            Some("192.168.122.50")
+        );
+        assert_eq!(addresses.len(), 1);
+    }
+
+    #[test]
+    fn dhcp_leases_parse_from_dnsmasq_and_libvirt() {
+        let dnsmasq = "1760480000 52:54:00:AA:BB:CC 192.168.200.23 web01 01:52:54:00:aa:bb:cc
+0 52:54:00:11:22:33 192.168.200.9 * *
+duid 00:01:00:01:2c:5f:7a:10:52:54:00:aa:bb:cc
+1760480000 1234 fd00::23 web01 00:01:00:01
+";
+        let leases = parse_dnsmasq_leases(dnsmasq);
+        assert_eq!(leases.len(), 2);
+        assert_eq!(leases[0].mac, "52:54:00:aa:bb:cc");
+        assert_eq!(leases[0].hostname.as_deref(), Some("web01"));
+        assert_eq!(
+            leases[0].expires.map(|at| at.timestamp()),
+            Some(1_760_480_000)
+        );
+        assert_eq!(
+            (leases[1].hostname.as_ref(), leases[1].expires),
+            (None, None)
+        );
+
+        let status = r#"[{"ip-address":"192.168.122.50","mac-address":"52:54:00:aa:bb:cc",
+            "hostname":"fedora","client-id":"ff:00","expiry-time":1760480000},
+            {"ip-address":"192.168.122.51","mac-address":"52:54:00:11:22:33","expiry-time":1760480100}]"#;
+        let leases = parse_libvirt_leases(status);
+        assert_eq!(leases.len(), 2);
+        assert_eq!(leases[0].ip, Ipv4Addr::new(192, 168, 122, 50));
+        assert_eq!(leases[1].hostname, None);
+        assert!(parse_libvirt_leases("").is_empty());
+    }
+
+    #[test]
+    fn fdb_entries_parse_for_both_switch_types() {
+        let json = r#"[{"mac":"52:54:00:aa:bb:cc","ifname":"vnet0","master":"br0","state":""},
+            {"mac":"52:54:00:11:22:33","ifname":"vnet1","vlan":20,"master":"br0","state":""},
+            {"mac":"3a:1f:2e:00:00:01","ifname":"br0","vlan":1,"flags":["self"],"state":"permanent"}]"#;
+        let entries = parse_bridge_fdb_json(json);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].port, "vnet0");
+        assert_eq!((entries[1].vlan, entries[1].permanent), (Some(20), false));
+        assert!(entries[2].permanent);
+        assert!(parse_bridge_fdb_json("not json").is_empty());
+
+        let ovs = " port  VLAN  MAC                Age
+    1     0  52:54:00:aa:bb:cc    3
+    2    30  52:54:00:11:22:33    static
+";
+        let entries = parse_ovs_fdb(ovs);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].port.as_str(), entries[0].vlan), ("1", None));
+        assert_eq!((entries[1].vlan, entries[1].permanent), (Some(30), true));
+    }
 }
@@ -0,0 +1,697 @@
+//! Live switch dashboard behind `nova network monitor`.
+//!
+//! Every refresh re-reads bridge membership from [`NetworkManager`] and
+//! interface counters through [`NetworkMonitor`], turning the difference
+//! between two samples into throughput. Members whose link is down are drawn
+//! in red and non-zero error or drop counters in yellow. Selecting a switch
+//! and pressing `f` or `l` drills into its forwarding database or the VMs
+//! attached to it. The terminal is switched to raw mode on an alternate
+//! screen for the duration and restored when the dashboard exits, including
+//! on errors.
+
+use crate::monitoring::{BandwidthUsage, NetworkMonitor, NetworkStats, bandwidth_between};
+use crate::network::{
+    ConnectedVm, FdbEntry, InterfaceState, NetworkManager, SwitchStatus, SwitchType,
+};
+use crate::{NovaError, Result};
+use nix::sys::termios::{
+    LocalFlags, SetArg, SpecialCharacterIndices, Termios, tcgetattr, tcsetattr,
+};
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read, Write};
+use std::os::fd::AsFd;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+const RED: &str = "31";
+const YELLOW: &str = "33";
+const BOLD: &str = "1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Fdb,
+    Leases,
+    Back,
+    Quit,
+}
+
+/// Keys in a chunk read from a raw-mode terminal. Arrow keys arrive as
+/// `ESC [ A`/`ESC [ B`; a lone `ESC` goes back. Ctrl+C is a plain byte
+/// because signals are disabled while the dashboard runs.
+pub fn parse_keys(input: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (key, len) = match rest {
+            [0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+            [0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+            [0x1b, b'[', _, ..] => (None, 3),
+            [0x1b, ..] => (Some(Key::Back), 1),
+            [b'k', ..] => (Some(Key::Up), 1),
+            [b'j', ..] => (Some(Key::Down), 1),
+            [b'\r' | b'\n' | b'f', ..] => (Some(Key::Fdb), 1),
+            [b'l', ..] => (Some(Key::Leases), 1),
+            [b'b', ..] => (Some(Key::Back), 1),
+            [b'q' | 0x03, ..] => (Some(Key::Quit), 1),
+            _ => (None, 1),
+        };
+        keys.extend(key);
+        rest = &rest[len..];
+    }
+    keys
+}
+
+/// Rates and error counters of one interface between two refreshes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Traffic {
+    pub rx_bps: f64,
+    pub tx_bps: f64,
+    /// Receive plus transmit errors since the interface came up
+    pub errors: u64,
+    pub drops: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberView {
+    pub name: String,
+    pub state: InterfaceState,
+    pub traffic: Option<Traffic>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchView {
+    pub name: String,
+    pub switch_type: SwitchType,
+    pub up: bool,
+    pub traffic: Option<Traffic>,
+    pub members: Vec<MemberView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum View {
+    Overview,
+    Fdb(String),
+    Leases(String),
+}
+
+/// Selection and current view; switches are addressed by their position
+/// in the overview.
+#[derive(Debug)]
+pub struct Dashboard {
+    pub selected: usize,
+    pub view: View,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            view: View::Overview,
+        }
+    }
+
+    /// Apply a key press; returns `false` once the dashboard should exit.
+    pub fn handle_key(&mut self, key: Key, switches: &[SwitchView]) -> bool {
+        let selected = || {
+            switches
+                .get(self.selected)
+                .map(|switch| switch.name.clone())
+        };
+        match (key, &self.view) {
+            (Key::Quit, _) => return false,
+            (Key::Up, View::Overview) => self.selected = self.selected.saturating_sub(1),
+            (Key::Down, View::Overview) => {
+                self.selected = (self.selected + 1).min(switches.len().saturating_sub(1))
+            }
+            (Key::Fdb, View::Overview) => {
+                if let Some(name) = selected() {
+                    self.view = View::Fdb(name);
+                }
+            }
+            (Key::Leases, View::Overview) => {
+                if let Some(name) = selected() {
+                    self.view = View::Leases(name);
+                }
+            }
+            (Key::Fdb, View::Leases(name)) => self.view = View::Fdb(name.clone()),
+            (Key::Leases, View::Fdb(name)) => self.view = View::Leases(name.clone()),
+            (Key::Back, _) => self.view = View::Overview,
+            _ => {}
+        }
+        true
+    }
+
+    /// Keep the selection on a switch after switches disappear.
+    pub fn clamp(&mut self, switches: &[SwitchView]) {
+        self.selected = self.selected.min(switches.len().saturating_sub(1));
+    }
+}
+
+/// Previous counters per interface, so each refresh yields a rate.
+#[derive(Default)]
+pub struct TrafficSampler {
+    previous: HashMap<String, (NetworkStats, Instant)>,
+}
+
+impl TrafficSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Traffic of `interface` since the last call; rates are zero on the
+    /// first sample and `None` when the interface has no counters.
+    pub async fn sample(&mut self, monitor: &NetworkMonitor, interface: &str) -> Option<Traffic> {
+        let stats = monitor.get_interface_stats(interface).await.ok()?;
+        let now = Instant::now();
+        let (rx_bps, tx_bps) = match self.previous.get(interface) {
+            Some((previous, at)) => {
+                let usage =
+                    bandwidth_between(previous, &stats, now.duration_since(*at).as_secs_f64());
+                (usage.rx_bps, usage.tx_bps)
+            }
+            None => (0.0, 0.0),
+        };
+        let traffic = Traffic {
+            rx_bps,
+            tx_bps,
+            errors: stats.rx_errors + stats.tx_errors,
+            drops: stats.rx_drops + stats.tx_drops,
+        };
+        self.previous.insert(interface.to_string(), (stats, now));
+        Some(traffic)
+    }
+}
+
+/// Switches sorted by name with their members and current traffic.
+pub async fn collect_switches(
+    manager: &NetworkManager,
+    monitor: &NetworkMonitor,
+    sampler: &mut TrafficSampler,
+) -> Vec<SwitchView> {
+    let mut switches = manager.list_switches();
+    switches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut views = Vec::with_capacity(switches.len());
+    for switch in switches {
+        let mut member_names = switch.interfaces.clone();
+        member_names.sort();
+        let mut members = Vec::with_capacity(member_names.len());
+        for name in member_names {
+            let state = manager
+                .get_interface(&name)
+                .map_or(InterfaceState::Unknown, |iface| iface.state.clone());
+            let traffic = sampler.sample(monitor, &name).await;
+            members.push(MemberView {
+                name,
+                state,
+                traffic,
+            });
+        }
+        let bridge_down = manager
+            .get_interface(&switch.name)
+            .is_some_and(|iface| matches!(iface.state, InterfaceState::Down));
+        views.push(SwitchView {
+            name: switch.name.clone(),
+            switch_type: switch.switch_type.clone(),
+            up: matches!(switch.status, SwitchStatus::Active) && !bridge_down,
+            traffic: sampler.sample(monitor, &switch.name).await,
+            members,
+        });
+    }
+    views
+}
+
+/// Switch table with one indented row per member.
+pub fn render_overview(switches: &[SwitchView], selected: usize) -> String {
+    let mut out = paint(
+        &format!(
+            "  {:<18} {:<8} {:<8} {:>12} {:>12} {:>8} {:>8}",
+            "SWITCH/MEMBER", "TYPE", "LINK", "RX/s", "TX/s", "ERRORS", "DROPS"
+        ),
+        BOLD,
+    );
+    out.push('\n');
+    if switches.is_empty() {
+        out.push_str("  (no bridges detected)\n");
+        return out;
+    }
+
+    for (index, switch) in switches.iter().enumerate() {
+        let marker = if index == selected { ">" } else { " " };
+        let kind = match switch.switch_type {
+            SwitchType::LinuxBridge => "bridge",
+            SwitchType::OpenVSwitch => "ovs",
+        };
+        let link = if switch.up { "up" } else { "down" };
+        let row = format!(
+            "{} {:<18} {:<8} {:<8}{}",
+            marker,
+            switch.name,
+            kind,
+            link,
+            traffic_columns(switch.traffic.as_ref())
+        );
+        out.push_str(&if switch.up { row } else { paint(&row, RED) });
+        out.push('\n');
+
+        if switch.members.is_empty() {
+            out.push_str("    (no members)\n");
+        }
+        for member in &switch.members {
+            let (link, down) = match member.state {
+                InterfaceState::Up => ("up", false),
+                InterfaceState::Down => ("down", true),
+                InterfaceState::Unknown => ("unknown", false),
+            };
+            let row = format!(
+                "    {:<16} {:<8} {:<8}{}",
+                member.name,
+                "",
+                link,
+                traffic_columns(member.traffic.as_ref())
+            );
+            out.push_str(&if down { paint(&row, RED) } else { row });
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Forwarding database of `switch`, naming the VM behind each MAC it knows.
+pub fn render_fdb(switch: &str, entries: &[FdbEntry], vms: &[ConnectedVm]) -> String {
+    let mut out = format!(
+        "Forwarding database of {} ({} entries)\n\n",
+        switch,
+        entries.len()
+    );
+    out.push_str(&paint(
+        &format!(
+            "  {:<19} {:<12} {:<6} {:<8} VM",
+            "MAC", "PORT", "VLAN", "KIND"
+        ),
+        BOLD,
+    ));
+    out.push('\n');
+    if entries.is_empty() {
+        out.push_str("  (no entries)\n");
+    }
+    for entry in entries {
+        let vm = vms
+            .iter()
+            .find(|vm| vm.mac.eq_ignore_ascii_case(&entry.mac))
+            .map_or("-", |vm| vm.vm.as_str());
+        out.push_str(&format!(
+            "  {:<19} {:<12} {:<6} {:<8} {}\n",
+            entry.mac,
+            entry.port,
+            entry
+                .vlan
+                .map_or_else(|| "-".to_string(), |vid| vid.to_string()),
+            if entry.permanent { "static" } else { "learned" },
+            vm
+        ));
+    }
+    out
+}
+
+/// VM NICs attached to `switch` with their addresses.
+pub fn render_leases(switch: &str, vms: &[ConnectedVm]) -> String {
+    let mut out = format!("VMs attached to {} ({} NICs)\n\n", switch, vms.len());
+    out.push_str(&paint(
+        &format!(
+            "  {:<20} {:<10} {:<19} {:<12} IP",
+            "VM", "TAP", "MAC", "NETWORK"
+        ),
+        BOLD,
+    ));
+    out.push('\n');
+    if vms.is_empty() {
+        out.push_str("  (no running VMs on this switch)\n");
+    }
+    for vm in vms {
+        out.push_str(&format!(
+            "  {:<20} {:<10} {:<19} {:<12} {}\n",
+            vm.vm,
+            vm.tap.as_deref().unwrap_or("-"),
+            vm.mac,
+            vm.network.as_deref().unwrap_or("-"),
+            vm.ip_address.as_deref().unwrap_or("-")
+        ));
+    }
+    out
+}
+
+/// Plain throughput table for `nova network inspect`, busiest interface
+/// first, naming the VM behind each tap device.
+pub fn render_traffic(usage: &[BandwidthUsage], vms: &[ConnectedVm]) -> String {
+    let mut rows: Vec<&BandwidthUsage> = usage.iter().collect();
+    rows.sort_by(|a, b| (b.rx_bps + b.tx_bps).total_cmp(&(a.rx_bps + a.tx_bps)));
+
+    let mut out = format!(
+        "  {:<16} {:<20} {:>12} {:>12} {:>10} {:>10}\n",
+        "INTERFACE", "VM", "RX/s", "TX/s", "RX pkt/s", "TX pkt/s"
+    );
+    if rows.is_empty() {
+        out.push_str("  (no counters)\n");
+    }
+    for usage in rows {
+        let vm = vms
+            .iter()
+            .find(|vm| vm.tap.as_deref() == Some(usage.interface.as_str()))
+            .map_or("-", |vm| vm.vm.as_str());
+        out.push_str(&format!(
+            "  {:<16} {:<20} {:>12} {:>12} {:>10.0} {:>10.0}\n",
+            usage.interface,
+            vm,
+            format_rate(usage.rx_bps),
+            format_rate(usage.tx_bps),
+            usage.rx_pps,
+            usage.tx_pps
+        ));
+    }
+    out
+}
+
+fn traffic_columns(traffic: Option<&Traffic>) -> String {
+    let Some(traffic) = traffic else {
+        return format!(" {:>12} {:>12} {:>8} {:>8}", "-", "-", "-", "-");
+    };
+    let counter = |value: u64| {
+        let cell = format!("{:>8}", value);
+        if value > 0 {
+            paint(&cell, YELLOW)
+        } else {
+            cell
+        }
+    };
+    format!(
+        " {:>12} {:>12} {} {}",
+        format_rate(traffic.rx_bps),
+        format_rate(traffic.tx_bps),
+        counter(traffic.errors),
+        counter(traffic.drops)
+    )
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    const KB: f64 = 1024.0;
+    if bytes_per_sec >= KB * KB * KB {
+        format!("{:.1} GiB/s", bytes_per_sec / (KB * KB * KB))
+    } else if bytes_per_sec >= KB * KB {
+        format!("{:.1} MiB/s", bytes_per_sec / (KB * KB))
+    } else if bytes_per_sec >= KB {
+        format!("{:.1} KiB/s", bytes_per_sec / KB)
+    } else {
+        format!("{:.0} B/s", bytes_per_sec)
+    }
+}
+
+fn paint(text: &str, code: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// Raw mode on the alternate screen; dropping it restores the terminal.
+struct RawTerminal {
+    original: Termios,
+}
+
+impl RawTerminal {
+    fn enter() -> Result<Self> {
+        let stdin = std::io::stdin();
+        if !stdin.is_terminal() || !std::io::stdout().is_terminal() {
+            return Err(NovaError::ConfigError(
+                "`nova network monitor` needs an interactive terminal; use `nova network list` \
+                 in scripts"
+                    .to_string(),
+            ));
+        }
+        let original = tcgetattr(stdin.as_fd()).map_err(|err| {
+            NovaError::ConfigError(format!("Could not read terminal settings: {}", err))
+        })?;
+        let mut raw = original.clone();
+        raw.local_flags
+            .remove(LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::ISIG);
+        // Reads return after 100ms without input so the key reader can stop
+        raw.control_chars[SpecialCharacterIndices::VMIN as usize] = 0;
+        raw.control_chars[SpecialCharacterIndices::VTIME as usize] = 1;
+        tcsetattr(stdin.as_fd(), SetArg::TCSANOW, &raw).map_err(|err| {
+            NovaError::ConfigError(format!(
+                "Could not switch the terminal to raw mode: {}",
+                err
+            ))
+        })?;
+
+        let mut stdout = std::io::stdout();
+        let _ = write!(stdout, "\x1b[?1049h\x1b[?25l");
+        let _ = stdout.flush();
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut stdout = std::io::stdout();
+        let _ = write!(stdout, "\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        let _ = tcsetattr(std::io::stdin().as_fd(), SetArg::TCSANOW, &self.original);
+    }
+}
+
+/// Run the dashboard until `q` or Ctrl+C is pressed.
+pub async fn run(interval: Duration) -> Result<()> {
+    let terminal = RawTerminal::enter()?;
+
+    let (keys_tx, mut keys_rx) = mpsc::unbounded_channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let reader_stop = Arc::clone(&stop);
+    let reader = std::thread::spawn(move || {
+        let mut buf = [0u8; 32];
+        while !reader_stop.load(Ordering::Relaxed) {
+            match std::io::stdin().read(&mut buf) {
+                Ok(0) => continue,
+                Ok(len) => {
+                    for key in parse_keys(&buf[..len]) {
+                        if keys_tx.send(key).is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => return,
+            }
+        }
+    });
+
+    let result = dashboard_loop(interval, &mut keys_rx).await;
+
+    stop.store(true, Ordering::Relaxed);
+    let _ = reader.join();
+    drop(terminal);
+    result
+}
+
+async fn dashboard_loop(interval: Duration, keys: &mut mpsc::UnboundedReceiver<Key>) -> Result<()> {
+    let mut manager = NetworkManager::new();
+    let monitor = NetworkMonitor::new();
+    let mut sampler = TrafficSampler::new();
+    let mut dashboard = Dashboard::new();
+    let mut switches = Vec::new();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                manager.refresh_state().await?;
+                switches = collect_switches(&manager, &monitor, &mut sampler).await;
+                dashboard.clamp(&switches);
+            }
+            key = keys.recv() => {
+                let Some(key) = key else {
+                    return Ok(());
+                };
+                if !dashboard.handle_key(key, &switches) {
+                    return Ok(());
+                }
+            }
+        }
+        draw(&dashboard, &manager, &switches, interval);
+    }
+}
+
+fn draw(
+    dashboard: &Dashboard,
+    manager: &NetworkManager,
+    switches: &[SwitchView],
+    interval: Duration,
+) {
+    let (body, help) = match &dashboard.view {
+        View::Overview => (
+            render_overview(switches, dashboard.selected),
+            "j/k or arrows select  f/Enter FDB  l leases  q quit",
+        ),
+        View::Fdb(name) => (
+            match manager.bridge_fdb(name) {
+                Ok(entries) => render_fdb(name, &entries, &manager.connected_vms(name)),
+                Err(err) => format!(
+                    "Could not read the forwarding database of {}: {}\n",
+                    name, err
+                ),
+            },
+            "l leases  Esc/b back  q quit",
+        ),
+        View::Leases(name) => (
+            render_leases(name, &manager.connected_vms(name)),
+            "f FDB  Esc/b back  q quit",
+        ),
+    };
+
+    let header = format!(
+        "Nova network monitor  {}  (every {}s)  {}",
+        chrono::Local::now().format("%H:%M:%S"),
+        interval.as_secs_f64(),
+        help
+    );
+    let mut frame = format!("\x1b[H{}\x1b[K\n\n", paint(&header, BOLD));
+    for line in body.lines() {
+        frame.push_str(line);
+        frame.push_str("\x1b[K\n");
+    }
+    frame.push_str("\x1b[J");
+
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(frame.as_bytes());
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_drive_views_and_overview_highlights_problems() {
+        assert_eq!(
+            parse_keys(b"j\x1b[Ak\x1b[Cfq\x1b\x03"),
+            vec![
+                Key::Down,
+                Key::Up,
+                Key::Up,
+                Key::Fdb,
+                Key::Quit,
+                Key::Back,
+                Key::Quit
+            ]
+        );
+
+        let traffic = |errors| Traffic {
+            rx_bps: 2048.0,
+            tx_bps: 10.0,
+            errors,
+            drops: 0,
+        };
+        let switches = vec![
+            SwitchView {
+                name: "br0".to_string(),
+                switch_type: SwitchType::LinuxBridge,
+                up: true,
+                traffic: Some(traffic(0)),
+                members: vec![
+                    MemberView {
+                        name: "enp3s0".to_string(),
+                        state: InterfaceState::Up,
+                        traffic: Some(traffic(4)),
+                    },
+                    MemberView {
+                        name: "vnet0".to_string(),
+                        state: InterfaceState::Down,
+                        traffic: None,
+                    },
+                ],
+            },
+            SwitchView {
+                name: "ovsbr0".to_string(),
+                switch_type: SwitchType::OpenVSwitch,
+                up: false,
+                traffic: None,
+                members: Vec::new(),
+            },
+        ];
+
+        let overview = render_overview(&switches, 0);
+        assert!(overview.contains("> br0"));
+        assert!(overview.contains("2.0 KiB/s"));
+        assert!(overview.contains(&paint(&format!("{:>8}", 4), YELLOW)));
+        let vnet0 = overview
+            .lines()
+            .find(|line| line.contains("vnet0"))
+            .unwrap();
+        assert!(vnet0.starts_with("\x1b[31m"), "{:?}", vnet0);
+        let ovs = overview
+            .lines()
+            .find(|line| line.contains("ovsbr0"))
+            .unwrap();
+        assert!(ovs.starts_with("\x1b[31m"), "{:?}", ovs);
+
+        let mut dashboard = Dashboard::new();
+        assert!(dashboard.handle_key(Key::Down, &switches));
+        assert!(dashboard.handle_key(Key::Down, &switches));
+        assert_eq!(dashboard.selected, 1);
+        assert!(dashboard.handle_key(Key::Leases, &switches));
+        assert_eq!(dashboard.view, View::Leases("ovsbr0".to_string()));
+        assert!(dashboard.handle_key(Key::Fdb, &switches));
+        assert_eq!(dashboard.view, View::Fdb("ovsbr0".to_string()));
+        assert!(dashboard.handle_key(Key::Back, &switches));
+        assert_eq!(dashboard.view, View::Overview);
+        assert!(!dashboard.handle_key(Key::Quit, &switches));
+
+        let vms = vec![ConnectedVm {
+            vm: "web".to_string(),
+            tap: Some("vnet0".to_string()),
+            mac: "52:54:00:aa:bb:cc".to_string(),
+            network: None,
+            ip_address: Some("192.168.122.50".to_string()),
+        }];
+        let fdb = render_fdb(
+            "br0",
+            &[FdbEntry {
+                mac: "52:54:00:AA:BB:CC".to_string(),
+                port: "vnet0".to_string(),
+                vlan: None,
+                permanent: false,
+            }],
+            &vms,
+        );
+        assert!(fdb.contains("learned  web"), "{}", fdb);
+        assert!(render_leases("br0", &vms).contains("192.168.122.50"));
+
+        let usage = |interface: &str, rx_bps| BandwidthUsage {
+            interface: interface.to_string(),
+            timestamp: 0,
+            rx_bps,
+            tx_bps: 0.0,
+            rx_pps: 12.0,
+            tx_pps: 3.0,
+        };
+        let table = render_traffic(
+            &[usage("br0", 100.0), usage("vnet0", 5.0 * 1024.0 * 1024.0)],
+            &vms,
+        );
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(
+            lines[1].contains("vnet0") && lines[1].contains("web"),
+            "{}",
+            table
+        );
+        assert!(lines[1].contains("5.0 MiB/s"));
+        assert!(lines[2].contains("br0") && lines[2].contains(" - "));
+    }
+}
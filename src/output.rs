@@ -0,0 +1,279 @@
+//! Table output for CLI listings.
+//!
+//! Columns are as wide as their widest cell. On a terminal, the table is
+//! shrunk to fit the window: the widest columns give up space first and
+//! cut-off cells end in `…`. Status cells are colored on a terminal unless
+//! `NO_COLOR` is set; piped output stays plain.
+
+use crate::instance::InstanceStatus;
+use std::io::IsTerminal;
+
+/// Narrowest a column is shrunk to, or its header if that is shorter.
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// How a cell is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    Plain,
+    /// Running, active, available
+    Good,
+    /// Stopped, idle
+    Muted,
+    /// Transitional or needs attention
+    Warning,
+    /// Failed
+    Bad,
+}
+
+impl Tone {
+    pub fn for_status(status: InstanceStatus) -> Self {
+        match status {
+            InstanceStatus::Running => Tone::Good,
+            InstanceStatus::Stopped => Tone::Muted,
+            InstanceStatus::Starting | InstanceStatus::Stopping | InstanceStatus::Suspended => {
+                Tone::Warning
+            }
+            InstanceStatus::Error | InstanceStatus::CrashLooping => Tone::Bad,
+        }
+    }
+
+    fn ansi_code(self) -> Option<&'static str> {
+        match self {
+            Tone::Plain => None,
+            Tone::Good => Some("32"),
+            Tone::Muted => Some("90"),
+            Tone::Warning => Some("33"),
+            Tone::Bad => Some("31"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    text: String,
+    tone: Tone,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>, tone: Tone) -> Self {
+        Self {
+            text: text.into(),
+            tone,
+        }
+    }
+
+    /// An instance status, led by its shape with `symbols` (see
+    /// [`InstanceStatus::symbol`]).
+    pub fn status(status: InstanceStatus, symbols: bool) -> Self {
+        let text = if symbols {
+            format!("{} {:?}", status.symbol(), status)
+        } else {
+            format!("{:?}", status)
+        };
+        Self::new(text, Tone::for_status(status))
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::new(text, Tone::Plain)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Self::new(text, Tone::Plain)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new<'a>(headers: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            headers: headers.into_iter().map(str::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row; missing trailing cells are left blank.
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Print to stdout, fitted to the terminal.
+    pub fn print(&self) {
+        print!("{}", self.render(terminal_width(), color_enabled()));
+    }
+
+    /// The table as text, at most `width` columns wide when given.
+    pub fn render(&self, width: Option<usize>, color: bool) -> String {
+        let widths = self.column_widths(width);
+        let mut out = String::new();
+
+        let header: Vec<Cell> = self
+            .headers
+            .iter()
+            .map(|h| Cell::from(h.as_str()))
+            .collect();
+        push_line(&mut out, &header, &widths, false);
+        let rule = widths.iter().sum::<usize>() + widths.len().saturating_sub(1);
+        out.push_str(&"=".repeat(rule));
+        out.push('\n');
+
+        for row in &self.rows {
+            push_line(&mut out, row, &widths, color);
+        }
+        out
+    }
+
+    fn column_widths(&self, max_width: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate().take(widths.len()) {
+                widths[i] = widths[i].max(cell.text.chars().count());
+            }
+        }
+
+        let Some(max_width) = max_width else {
+            return widths;
+        };
+        let minimums: Vec<usize> = self
+            .headers
+            .iter()
+            .zip(&widths)
+            .map(|(header, &width)| width.min(header.chars().count().max(MIN_COLUMN_WIDTH)))
+            .collect();
+        let separators = widths.len().saturating_sub(1);
+        while widths.iter().sum::<usize>() + separators > max_width {
+            let Some((widest, _)) = widths
+                .iter()
+                .enumerate()
+                .filter(|(i, width)| **width > minimums[*i])
+                .max_by_key(|(i, width)| (**width, *i))
+            else {
+                break;
+            };
+            widths[widest] -= 1;
+        }
+        widths
+    }
+}
+
+fn push_line(out: &mut String, cells: &[Cell], widths: &[usize], color: bool) {
+    let blank = Cell::from("");
+    let mut line = String::new();
+    for (i, &width) in widths.iter().enumerate() {
+        let cell = cells.get(i).unwrap_or(&blank);
+        let text = truncate(&cell.text, width);
+        let padding = width - text.chars().count();
+        if i > 0 {
+            line.push(' ');
+        }
+        match cell.tone.ansi_code().filter(|_| color) {
+            Some(code) => line.push_str(&format!("\x1b[{}m{}\x1b[0m", code, text)),
+            None => line.push_str(&text),
+        }
+        line.push_str(&" ".repeat(padding));
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Columns of the terminal on stdout, or `None` when output is piped.
+pub fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut size = nix::libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer
+    let result = unsafe {
+        nix::libc::ioctl(
+            nix::libc::STDOUT_FILENO,
+            nix::libc::TIOCGWINSZ,
+            &mut size as *mut nix::libc::winsize,
+        )
+    };
+    if result == 0 && size.ws_col > 0 {
+        return Some(size.ws_col as usize);
+    }
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+/// Color on a terminal, unless `NO_COLOR` is set to anything non-empty.
+pub fn color_enabled() -> bool {
+    std::io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new(["NAME", "TYPE", "STATUS"]);
+        table.row(vec![
+            "web-frontend-production".into(),
+            "VM".into(),
+            Cell::status(InstanceStatus::Running, false),
+        ]);
+        table.row(vec![
+            "db".into(),
+            "Container".into(),
+            Cell::status(InstanceStatus::Error, true),
+        ]);
+        table
+    }
+
+    #[test]
+    fn columns_fit_content_and_shrink_to_the_terminal() {
+        let plain = table().render(None, false);
+        assert_eq!(
+            plain,
+            "NAME                    TYPE      STATUS
+=========================================
+web-frontend-production VM        Running
+db                      Container ✖ Error
+"
+        );
+
+        let narrow = table().render(Some(30), false);
+        assert!(
+            narrow.lines().all(|line| line.chars().count() <= 30),
+            "{}",
+            narrow
+        );
+        assert!(narrow.contains("web-fronten…"), "{}", narrow);
+        assert!(narrow.contains("Container"), "{}", narrow);
+    }
+
+    #[test]
+    fn status_cells_are_colored_only_when_asked() {
+        let colored = table().render(None, true);
+        assert!(colored.contains("\x1b[32mRunning\x1b[0m"), "{}", colored);
+        assert!(colored.contains("\x1b[31m✖ Error\x1b[0m"), "{}", colored);
+        assert!(!table().render(None, false).contains('\x1b'));
+    }
+}
@@ -1,6 +1,7 @@
 // Generic PCI Device Passthrough
 // Supports GPUs, NICs, NVMe drives, sound cards, and any PCIe device
 
+use crate::exec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -151,17 +152,17 @@ impl PciPassthroughManager {
         // Unbind from current driver if any
         if device.driver.is_some() {
             let unbind_path = format!("/sys/bus/pci/devices/{}/driver/unbind", pci_address);
-            let _ = fs::write(&unbind_path, pci_address);
+            let _ = exec::write_file(&unbind_path, pci_address);
         }
 
         // Override driver to vfio-pci
         let driver_override = format!("/sys/bus/pci/devices/{}/driver_override", pci_address);
-        fs::write(&driver_override, "vfio-pci")
+        exec::write_file(&driver_override, "vfio-pci")
             .map_err(|e| format!("Failed to set driver override: {}", e))?;
 
         // Bind to vfio-pci
         let bind_path = "/sys/bus/pci/drivers/vfio-pci/bind";
-        fs::write(bind_path, pci_address)
+        exec::write_file(bind_path, pci_address)
             .map_err(|e| format!("Failed to bind to vfio-pci: {}", e))?;
 
         println!("✅ Device bound to vfio-pci");
@@ -173,23 +174,81 @@ impl PciPassthroughManager {
         println!("Unbinding {} from vfio-pci", pci_address);
 
         let unbind_path = "/sys/bus/pci/drivers/vfio-pci/unbind";
-        fs::write(unbind_path, pci_address)
+        exec::write_file(unbind_path, pci_address)
             .map_err(|e| format!("Failed to unbind from vfio-pci: {}", e))?;
 
         // Clear driver override
         let driver_override = format!("/sys/bus/pci/devices/{}/driver_override", pci_address);
-        let _ = fs::write(&driver_override, "\n");
+        let _ = exec::write_file(&driver_override, "\n");
 
         // Trigger rescan to rebind to original driver
         let rescan_path = "/sys/bus/pci/rescan";
-        let _ = fs::write(rescan_path, "1");
+        let _ = exec::write_file(rescan_path, "1");
 
         println!("✅ Device unbound from vfio-pci");
         Ok(())
     }
 
-    /// Assign PCI device to VM
-    pub fn assign_to_vm(&mut self, pci_address: &str, vm_name: &str) -> Result<(), String> {
+    /// Assign PCI device to VM. Refuses when another device in its IOMMU
+    /// group is still bound to a host driver, unless `force` is set.
+    pub fn assign_to_vm(
+        &mut self,
+        pci_address: &str,
+        vm_name: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        self.assign_devices_to_vm(&[pci_address], vm_name, force)
+    }
+
+    /// Assign several devices to a VM at once. Devices passed together count
+    /// as passed through when checking each other's IOMMU group, so a GPU and
+    /// its audio function can be attached in one call.
+    pub fn assign_devices_to_vm(
+        &mut self,
+        pci_addresses: &[&str],
+        vm_name: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        for pci_address in pci_addresses {
+            let conflicts = self.group_conflicts(pci_address, pci_addresses, Some(vm_name))?;
+            if conflicts.is_empty() {
+                continue;
+            }
+            let group = self
+                .devices
+                .get(*pci_address)
+                .and_then(|device| device.iommu_group)
+                .unwrap_or_default();
+            let listed = conflicts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            if !force {
+                return Err(format!(
+                    "IOMMU group {} of {} is not isolated: {} still bound to a host driver, so \
+                     QEMU will refuse to start '{}'. Attach {} in the same command, or bind \
+                     them to vfio-pci first. Use --force to attach anyway.",
+                    group,
+                    pci_address,
+                    listed,
+                    vm_name,
+                    if conflicts.len() == 1 { "it" } else { "them" }
+                ));
+            }
+            println!(
+                "⚠️  IOMMU group {} is not isolated ({}); '{}' will fail to start until these are passed through or unbound",
+                group, listed, vm_name
+            );
+        }
+
+        for pci_address in pci_addresses {
+            self.assign_single(pci_address, vm_name)?;
+        }
+        Ok(())
+    }
+
+    fn assign_single(&mut self, pci_address: &str, vm_name: &str) -> Result<(), String> {
         // Check device exists and current driver
         let needs_vfio_bind = {
             let device = self
@@ -282,7 +341,7 @@ impl PciPassthroughManager {
         // Check IOMMU group
         if let Some(iommu_group) = device.iommu_group {
             // Check if IOMMU group has multiple devices
-            let group_devices = self.get_iommu_group_devices(iommu_group);
+            let group_devices = self.iommu_group_members(iommu_group);
             if group_devices.len() > 1 {
                 viability.warnings.push(format!(
                     "IOMMU group {} contains {} devices - all must be passed through together",
@@ -290,6 +349,13 @@ impl PciPassthroughManager {
                     group_devices.len()
                 ));
             }
+            for sibling in self.group_conflicts(pci_address, &[], None)? {
+                viability.viable = false;
+                viability.errors.push(format!(
+                    "IOMMU group {}: {} is still bound to a host driver",
+                    iommu_group, sibling
+                ));
+            }
         } else {
             viability.viable = false;
             viability
@@ -318,6 +384,68 @@ impl PciPassthroughManager {
         Ok(viability)
     }
 
+    /// Devices sharing `pci_address`'s IOMMU group that are still bound to a
+    /// host driver. Devices in `passing` and, with `vm_name`, devices already
+    /// assigned to that VM count as passed through.
+    pub fn group_conflicts(
+        &self,
+        pci_address: &str,
+        passing: &[&str],
+        vm_name: Option<&str>,
+    ) -> Result<Vec<IommuSibling>, String> {
+        let device = self
+            .devices
+            .get(pci_address)
+            .ok_or_else(|| format!("Device {} not found", pci_address))?;
+        let Some(group) = device.iommu_group else {
+            return Ok(Vec::new());
+        };
+
+        Ok(self
+            .iommu_group_members(group)
+            .into_iter()
+            .filter(|sibling| sibling.address != pci_address)
+            .filter(|sibling| !passing.contains(&sibling.address.as_str()))
+            .filter(|sibling| {
+                vm_name.is_none()
+                    || self.assignments.get(&sibling.address).map(String::as_str) != vm_name
+            })
+            .filter(|sibling| is_host_bound(sibling.driver.as_deref()))
+            .collect())
+    }
+
+    /// Every device in an IOMMU group, read from
+    /// `/sys/kernel/iommu_groups/<group>/devices` so devices that failed to
+    /// parse during discovery are not missed.
+    pub fn iommu_group_members(&self, group_id: u32) -> Vec<IommuSibling> {
+        let group_path = PathBuf::from(format!("/sys/kernel/iommu_groups/{}/devices", group_id));
+        let mut members: Vec<IommuSibling> = match fs::read_dir(&group_path) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| {
+                    let address = entry.file_name().to_string_lossy().to_string();
+                    match self.devices.get(&address) {
+                        Some(device) => IommuSibling::from(device),
+                        None => IommuSibling {
+                            driver: entry.path().join("driver").read_link().ok().and_then(|p| {
+                                p.file_name().map(|f| f.to_string_lossy().to_string())
+                            }),
+                            device_name: "Unknown device".to_string(),
+                            address,
+                        },
+                    }
+                })
+                .collect(),
+            Err(_) => self
+                .get_iommu_group_devices(group_id)
+                .into_iter()
+                .map(IommuSibling::from)
+                .collect(),
+        };
+        members.sort_by(|a, b| a.address.cmp(&b.address));
+        members
+    }
+
     /// Get all devices in the same IOMMU group
     pub fn get_iommu_group_devices(&self, group_id: u32) -> Vec<&PciDevice> {
         self.devices
@@ -449,6 +577,47 @@ impl PciPassthroughManager {
     }
 }
 
+/// Drivers VFIO accepts for other devices in a passed-through group:
+/// vfio-pci and pci-stub hold the device for a guest, and PCIe ports bound
+/// to pcieport are exempt.
+const GROUP_SAFE_DRIVERS: &[&str] = &["vfio-pci", "pci-stub", "pcieport"];
+
+/// Whether a device bound to `driver` keeps its IOMMU group from being
+/// handed to a guest. Unbound devices are fine.
+pub fn is_host_bound(driver: Option<&str>) -> bool {
+    driver.is_some_and(|driver| !GROUP_SAFE_DRIVERS.contains(&driver))
+}
+
+/// A member of an IOMMU group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IommuSibling {
+    pub address: String,
+    pub device_name: String,
+    pub driver: Option<String>,
+}
+
+impl From<&PciDevice> for IommuSibling {
+    fn from(device: &PciDevice) -> Self {
+        Self {
+            address: device.address.clone(),
+            device_name: device.device_name.clone(),
+            driver: device.driver.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for IommuSibling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} ({})",
+            self.address,
+            self.device_name,
+            self.driver.as_deref().unwrap_or("no driver")
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct PassthroughViability {
     pub viable: bool,
@@ -536,4 +705,66 @@ mod tests {
         assert!(xml.contains("domain='0x0000'"));
         assert!(xml.contains("bus='0x01'"));
     }
+
+    #[test]
+    fn test_iommu_group_conflicts() {
+        let mut manager = PciPassthroughManager::new();
+        let device = |address: &str, class: PciDeviceClass, driver: Option<&str>| PciDevice {
+            address: address.to_string(),
+            vendor_id: "10de".to_string(),
+            device_id: "2684".to_string(),
+            subsystem_vendor_id: "1043".to_string(),
+            subsystem_device_id: "88e2".to_string(),
+            vendor_name: "NVIDIA".to_string(),
+            device_name: format!("{:?}", class),
+            device_class: class,
+            // High group number so the sysfs lookup finds nothing on the test host
+            iommu_group: Some(9999),
+            driver: driver.map(str::to_string),
+            numa_node: None,
+            assigned_to_vm: None,
+            sysfs_path: PathBuf::from(format!("/sys/bus/pci/devices/{}", address)),
+        };
+        for dev in [
+            device("0000:01:00.0", PciDeviceClass::GPU, Some("nvidia")),
+            device(
+                "0000:01:00.1",
+                PciDeviceClass::AudioDevice,
+                Some("snd_hda_intel"),
+            ),
+            device("0000:00:01.0", PciDeviceClass::Bridge, Some("pcieport")),
+        ] {
+            manager.devices.insert(dev.address.clone(), dev);
+        }
+
+        let conflicts = manager
+            .group_conflicts("0000:01:00.0", &[], Some("win11"))
+            .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].address, "0000:01:00.1");
+        assert!(conflicts[0].to_string().contains("snd_hda_intel"));
+
+        let viability = manager.check_passthrough_viability("0000:01:00.0").unwrap();
+        assert!(!viability.viable);
+        assert!(viability.errors[0].contains("0000:01:00.1"));
+
+        let err = manager
+            .assign_to_vm("0000:01:00.0", "win11", false)
+            .unwrap_err();
+        assert!(err.contains("not isolated"), "{}", err);
+
+        // Attaching both functions together leaves nothing host-bound
+        assert!(
+            manager
+                .group_conflicts(
+                    "0000:01:00.0",
+                    &["0000:01:00.0", "0000:01:00.1"],
+                    Some("win11")
+                )
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!is_host_bound(None));
+        assert!(!is_host_bound(Some("vfio-pci")));
+    }
 }
@@ -454,6 +454,161 @@ impl PerformanceCollector {
     }
 }
 
+/// Counters for one domain from `virsh domstats --raw`. Rates need two
+/// samples of the same domain, see [`DomainSample::cpu_percent_since`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainSample {
+    pub vm_name: String,
+    /// libvirt `virDomainState` code (1 running, 3 paused, 5 shut off, ...)
+    pub state: u32,
+    pub cpu_time_ns: u64,
+    pub vcpus: u32,
+    pub memory_kib: u64,
+    /// Host memory backing the guest, when the balloon driver reports it
+    pub rss_kib: Option<u64>,
+    pub network_rx_bytes: u64,
+    pub network_tx_bytes: u64,
+    pub disk_read_bytes: u64,
+    pub disk_write_bytes: u64,
+    pub taken_at: Instant,
+}
+
+impl DomainSample {
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, 1 | 2)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self.state, 3 | 7)
+    }
+
+    /// CPU usage since `previous`, as a percentage of the VM's vCPUs.
+    pub fn cpu_percent_since(&self, previous: &DomainSample) -> f64 {
+        let elapsed_ns = self.taken_at.duration_since(previous.taken_at).as_nanos() as f64;
+        if elapsed_ns == 0.0 || self.vcpus == 0 {
+            return 0.0;
+        }
+        let cpu_ns = self.cpu_time_ns.saturating_sub(previous.cpu_time_ns) as f64;
+        (cpu_ns / (elapsed_ns * self.vcpus as f64) * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+/// Live CPU and memory use of one VM, for `nova status vm`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DomainUsage {
+    pub cpu_percent: f64,
+    /// Memory currently given to the guest (`balloon.current`)
+    pub memory_kib: u64,
+    /// Host memory actually backing the guest, when reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rss_kib: Option<u64>,
+}
+
+impl DomainUsage {
+    /// Usage between two samples of the same domain, or None unless it was
+    /// running for both.
+    pub fn between(previous: &DomainSample, current: &DomainSample) -> Option<Self> {
+        if !previous.is_running() || !current.is_running() {
+            return None;
+        }
+        Some(Self {
+            cpu_percent: current.cpu_percent_since(previous),
+            memory_kib: current.memory_kib,
+            rss_kib: current.rss_kib,
+        })
+    }
+}
+
+/// Sample every defined domain with one `virsh domstats` call.
+pub fn sample_domains() -> Result<Vec<DomainSample>, String> {
+    run_domstats(&["--interface", "--block"])
+}
+
+/// Sample `vm_name` twice, `window` apart, and return its usage over that
+/// window. None when the domain is not running or cannot be queried.
+pub fn sample_domain_usage(vm_name: &str, window: Duration) -> Option<DomainUsage> {
+    let first = run_domstats(&[vm_name]).ok()?.pop()?;
+    if !first.is_running() {
+        return None;
+    }
+    std::thread::sleep(window);
+    let second = run_domstats(&[vm_name]).ok()?.pop()?;
+    DomainUsage::between(&first, &second)
+}
+
+fn run_domstats(extra_args: &[&str]) -> Result<Vec<DomainSample>, String> {
+    let output = std::process::Command::new("virsh")
+        .args([
+            "domstats",
+            "--raw",
+            "--state",
+            "--cpu-total",
+            "--balloon",
+            "--vcpu",
+        ])
+        .args(extra_args)
+        .output()
+        .map_err(|e| format!("Failed to run virsh domstats: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "virsh domstats failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_domstats(
+        &String::from_utf8_lossy(&output.stdout),
+        Instant::now(),
+    ))
+}
+
+/// Parse `virsh domstats --raw` output. Per-device counters are summed.
+pub fn parse_domstats(output: &str, taken_at: Instant) -> Vec<DomainSample> {
+    let mut samples: Vec<DomainSample> = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Domain:") {
+            samples.push(DomainSample {
+                vm_name: name.trim().trim_matches('\'').to_string(),
+                state: 0,
+                cpu_time_ns: 0,
+                vcpus: 0,
+                memory_kib: 0,
+                rss_kib: None,
+                network_rx_bytes: 0,
+                network_tx_bytes: 0,
+                disk_read_bytes: 0,
+                disk_write_bytes: 0,
+                taken_at,
+            });
+            continue;
+        }
+        let (Some(sample), Some((key, value))) = (samples.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+        match key {
+            "state.state" => sample.state = value as u32,
+            "cpu.time" => sample.cpu_time_ns = value,
+            "vcpu.current" => sample.vcpus = value as u32,
+            "balloon.current" => sample.memory_kib = value,
+            "balloon.rss" => sample.rss_kib = Some(value),
+            _ => {
+                // net.<n>.rx.bytes, block.<n>.rd.bytes, ...
+                let counter = key.split('.').skip(2).collect::<Vec<_>>().join(".");
+                match (key.split('.').next(), counter.as_str()) {
+                    (Some("net"), "rx.bytes") => sample.network_rx_bytes += value,
+                    (Some("net"), "tx.bytes") => sample.network_tx_bytes += value,
+                    (Some("block"), "rd.bytes") => sample.disk_read_bytes += value,
+                    (Some("block"), "wr.bytes") => sample.disk_write_bytes += value,
+                    _ => {}
+                }
+            }
+        }
+    }
+    samples
+}
+
 // Mock virt module for compilation (replace with actual virt crate)
 mod virt {
     // Removed unused import: use std::fmt;
@@ -615,4 +770,55 @@ mod tests {
         let avg = history.get_average_cpu(10);
         assert!((avg - 45.0).abs() < 0.1); // Average of 0,10,20,...,90
     }
+
+    #[test]
+    fn test_domstats_samples_and_cpu_rate() {
+        let start = Instant::now();
+        let output = "Domain: 'web'
+  state.state=1
+  state.reason=1
+  cpu.time=4000000000
+  balloon.current=4194304
+  balloon.rss=2097152
+  vcpu.current=2
+  net.count=2
+  net.0.name=vnet0
+  net.0.rx.bytes=1000
+  net.0.tx.bytes=2000
+  net.1.rx.bytes=500
+  block.0.name=vda
+  block.0.rd.bytes=4096
+  block.0.wr.bytes=8192
+
+Domain: 'idle'
+  state.state=5
+  state.reason=0
+  balloon.current=1048576
+";
+        let samples = parse_domstats(output, start);
+        assert_eq!(samples.len(), 2);
+        let web = &samples[0];
+        assert_eq!(web.vm_name, "web");
+        assert!(web.is_running());
+        assert_eq!((web.vcpus, web.rss_kib), (2, Some(2_097_152)));
+        assert_eq!((web.network_rx_bytes, web.network_tx_bytes), (1500, 2000));
+        assert_eq!((web.disk_read_bytes, web.disk_write_bytes), (4096, 8192));
+        assert!(!samples[1].is_running() && !samples[1].is_paused());
+
+        // One busy vCPU out of two for a second is 50%
+        let later = DomainSample {
+            cpu_time_ns: 5_000_000_000,
+            taken_at: start + Duration::from_secs(1),
+            ..web.clone()
+        };
+        assert!((later.cpu_percent_since(web) - 50.0).abs() < 0.01);
+        assert_eq!(web.cpu_percent_since(web), 0.0);
+
+        let usage = DomainUsage::between(web, &later).unwrap();
+        assert_eq!(
+            (usage.memory_kib, usage.rss_kib),
+            (4_194_304, Some(2_097_152))
+        );
+        assert!(DomainUsage::between(&samples[1], &samples[1]).is_none());
+    }
 }
@@ -34,7 +34,7 @@ pub struct ListeningPort {
     pub allowed_sources: Vec<IpRange>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PortProtocol {
     Tcp,
     Udp,
@@ -1378,3 +1378,314 @@ impl Default for PortMonitor {
         Self::new()
     }
 }
+
+/// A socket listening on the host, from `ss -tulnp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostListener {
+    pub protocol: PortProtocol,
+    pub address: String,
+    pub port: u16,
+    /// Only visible for other users' processes when running as root
+    pub process: Option<String>,
+    pub pid: Option<u32>,
+}
+
+/// A QEMU process and the user-mode forwards on its command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QemuProcess {
+    pub pid: u32,
+    pub vm: String,
+    /// `(protocol, host port, guest port)` from `hostfwd=`
+    pub forwards: Vec<(PortProtocol, u16, u16)>,
+}
+
+/// Who a listening port belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "lowercase")]
+pub enum PortOwner {
+    Vm(String),
+    Container(String),
+    /// A host process that is not a Nova workload
+    Process(String),
+    Unknown,
+}
+
+impl std::fmt::Display for PortOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortOwner::Vm(name) => write!(f, "vm/{}", name),
+            PortOwner::Container(name) => write!(f, "container/{}", name),
+            PortOwner::Process(name) => write!(f, "{} (host)", name),
+            PortOwner::Unknown => f.write_str("-"),
+        }
+    }
+}
+
+/// One row of `nova ports`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortAssignment {
+    pub protocol: PortProtocol,
+    /// Bind addresses, e.g. `0.0.0.0` and `[::]` for a dual-stack listener
+    pub addresses: Vec<String>,
+    pub host_port: u16,
+    /// Port inside the guest or container the traffic is forwarded to
+    pub target_port: Option<u16>,
+    pub owner: PortOwner,
+}
+
+/// Listening sockets from `ss -H -tulnp` output.
+pub fn parse_ss_listeners(output: &str) -> Vec<HostListener> {
+    let users = regex::Regex::new(r#"\(\("([^"]+)",pid=(\d+)"#).expect("valid users pattern");
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let protocol = match *fields.first()? {
+                "tcp" => PortProtocol::Tcp,
+                "udp" => PortProtocol::Udp,
+                _ => return None,
+            };
+            let (address, port) = fields.get(4)?.rsplit_once(':')?;
+            let process = users.captures(line);
+            Some(HostListener {
+                protocol,
+                address: address.to_string(),
+                port: port.parse().ok()?,
+                process: process.as_ref().map(|caps| caps[1].to_string()),
+                pid: process.and_then(|caps| caps[2].parse().ok()),
+            })
+        })
+        .collect()
+}
+
+/// VM name and `hostfwd=` forwards from a QEMU command line. Libvirt passes
+/// the name as `-name guest=win11,debug-threads=on`.
+pub fn parse_qemu_cmdline(pid: u32, args: &[String]) -> Option<QemuProcess> {
+    let program = args.first()?;
+    if !program.rsplit('/').next()?.starts_with("qemu-system") {
+        return None;
+    }
+    let name = args.windows(2).find(|pair| pair[0] == "-name")?[1].clone();
+    let vm = name
+        .split(',')
+        .map(|part| part.strip_prefix("guest=").unwrap_or(part))
+        .next()?
+        .to_string();
+
+    // hostfwd=tcp:127.0.0.1:2222-:22
+    let hostfwd = regex::Regex::new(r"hostfwd=(tcp|udp)?:[^:,]*:(\d+)-[^:,]*:(\d+)")
+        .expect("valid hostfwd pattern");
+    let forwards = args
+        .iter()
+        .flat_map(|arg| hostfwd.captures_iter(arg).collect::<Vec<_>>())
+        .filter_map(|caps| {
+            let protocol = match caps.get(1).map(|m| m.as_str()) {
+                Some("udp") => PortProtocol::Udp,
+                _ => PortProtocol::Tcp,
+            };
+            Some((protocol, caps[2].parse().ok()?, caps[3].parse().ok()?))
+        })
+        .collect();
+    Some(QemuProcess { pid, vm, forwards })
+}
+
+/// Attribute listening sockets to VMs (by QEMU process) and containers (by
+/// published port). Published ports with no listener, as with Docker's
+/// userland proxy off, are listed too since DNAT still delivers them.
+pub fn attribute_ports(
+    listeners: &[HostListener],
+    containers: &[(String, Vec<crate::container_runtime::PortMapping>)],
+    vms: &[QemuProcess],
+) -> Vec<PortAssignment> {
+    use crate::container_runtime::PortProtocol as ContainerProtocol;
+    let container_protocol = |protocol: &ContainerProtocol| match protocol {
+        ContainerProtocol::Tcp => PortProtocol::Tcp,
+        ContainerProtocol::Udp => PortProtocol::Udp,
+    };
+    let published = |protocol: &PortProtocol, port: u16| {
+        containers.iter().find_map(|(name, mappings)| {
+            mappings
+                .iter()
+                .find(|mapping| {
+                    mapping.host_port == port && container_protocol(&mapping.protocol) == *protocol
+                })
+                .map(|mapping| (name, mapping.container_port))
+        })
+    };
+
+    let mut assignments: Vec<PortAssignment> = Vec::new();
+    for listener in listeners {
+        let vm = listener
+            .pid
+            .and_then(|pid| vms.iter().find(|vm| vm.pid == pid));
+        let (target_port, owner) = if let Some(vm) = vm {
+            let guest_port = vm
+                .forwards
+                .iter()
+                .find(|(protocol, host, _)| {
+                    *protocol == listener.protocol && *host == listener.port
+                })
+                .map(|(_, _, guest)| *guest);
+            (guest_port, PortOwner::Vm(vm.vm.clone()))
+        } else if let Some((name, container_port)) = published(&listener.protocol, listener.port) {
+            (Some(container_port), PortOwner::Container(name.clone()))
+        } else {
+            (
+                None,
+                listener
+                    .process
+                    .clone()
+                    .map_or(PortOwner::Unknown, PortOwner::Process),
+            )
+        };
+
+        if let Some(existing) = assignments.iter_mut().find(|existing| {
+            existing.protocol == listener.protocol
+                && existing.host_port == listener.port
+                && existing.owner == owner
+        }) {
+            if !existing.addresses.contains(&listener.address) {
+                existing.addresses.push(listener.address.clone());
+            }
+            continue;
+        }
+        assignments.push(PortAssignment {
+            protocol: listener.protocol.clone(),
+            addresses: vec![listener.address.clone()],
+            host_port: listener.port,
+            target_port,
+            owner,
+        });
+    }
+
+    for (name, mappings) in containers {
+        for mapping in mappings {
+            let protocol = container_protocol(&mapping.protocol);
+            if !assignments.iter().any(|existing| {
+                existing.protocol == protocol && existing.host_port == mapping.host_port
+            }) {
+                assignments.push(PortAssignment {
+                    protocol,
+                    addresses: vec!["*".to_string()],
+                    host_port: mapping.host_port,
+                    target_port: Some(mapping.container_port),
+                    owner: PortOwner::Container(name.clone()),
+                });
+            }
+        }
+    }
+
+    assignments.sort_by_key(|assignment| {
+        (
+            assignment.host_port,
+            assignment.protocol == PortProtocol::Udp,
+        )
+    });
+    assignments
+}
+
+/// Running QEMU processes visible in `/proc`.
+pub fn qemu_processes() -> Vec<QemuProcess> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+            let args: Vec<String> = cmdline
+                .split(|byte| *byte == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect();
+            parse_qemu_cmdline(pid, &args)
+        })
+        .collect()
+}
+
+/// Listening TCP/UDP ports with the VM or container behind each. Process
+/// names of other users' sockets need root; those rows still get matched
+/// to containers by port.
+pub fn list_port_assignments(
+    containers: &[(String, Vec<crate::container_runtime::PortMapping>)],
+) -> Result<Vec<PortAssignment>> {
+    let output = Command::new("ss")
+        .args(["-H", "-tulnp"])
+        .output()
+        .map_err(|_| {
+            NovaError::ConfigError("Listing ports needs `ss` (iproute2) in PATH".to_string())
+        })?;
+    if !output.status.success() {
+        return Err(NovaError::SystemCommandFailed);
+    }
+    let listeners = parse_ss_listeners(&String::from_utf8_lossy(&output.stdout));
+    Ok(attribute_ports(&listeners, containers, &qemu_processes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container_runtime::{PortMapping, PortProtocol as ContainerProtocol};
+
+    #[test]
+    fn listeners_are_attributed_to_vms_and_containers() {
+        let ss = r#"tcp   LISTEN 0      4096         0.0.0.0:8080       0.0.0.0:*    users:(("docker-proxy",pid=2211,fd=4))
+tcp   LISTEN 0      4096            [::]:8080          [::]:*    users:(("docker-proxy",pid=2218,fd=4))
+tcp   LISTEN 0      1          127.0.0.1:2222       0.0.0.0:*    users:(("qemu-system-x86",pid=4100,fd=12))
+tcp   LISTEN 0      1            0.0.0.0:5930       0.0.0.0:*    users:(("qemu-system-x86",pid=4100,fd=20))
+udp   UNCONN 0      0            0.0.0.0:5353       0.0.0.0:*    users:(("avahi-daemon",pid=812,fd=12))
+tcp   LISTEN 0      128             [::]:22            [::]:*
+"#;
+        let listeners = parse_ss_listeners(ss);
+        assert_eq!(listeners.len(), 6);
+        assert_eq!(listeners[1].address, "[::]");
+        assert_eq!(listeners[5].process, None);
+
+        let args: Vec<String> = [
+            "/usr/bin/qemu-system-x86_64",
+            "-name",
+            "guest=devbox,debug-threads=on",
+            "-netdev",
+            "user,id=n0,hostfwd=tcp:127.0.0.1:2222-:22,hostfwd=udp::6000-:60",
+        ]
+        .map(String::from)
+        .to_vec();
+        let vm = parse_qemu_cmdline(4100, &args).unwrap();
+        assert_eq!(vm.vm, "devbox");
+        assert_eq!(
+            vm.forwards,
+            vec![(PortProtocol::Tcp, 2222, 22), (PortProtocol::Udp, 6000, 60)]
+        );
+        assert!(parse_qemu_cmdline(1, &["/usr/bin/bash".to_string()]).is_none());
+
+        let containers = vec![(
+            "web".to_string(),
+            vec![
+                PortMapping {
+                    host_port: 8080,
+                    container_port: 80,
+                    protocol: ContainerProtocol::Tcp,
+                },
+                PortMapping {
+                    host_port: 5432,
+                    container_port: 5432,
+                    protocol: ContainerProtocol::Tcp,
+                },
+            ],
+        )];
+        let rows = attribute_ports(&listeners, &containers, &[vm]);
+        let row = |port: u16| rows.iter().find(|row| row.host_port == port).unwrap();
+        assert_eq!(row(8080).owner, PortOwner::Container("web".into()));
+        assert_eq!(row(8080).addresses, ["0.0.0.0", "[::]"]);
+        assert_eq!(row(8080).target_port, Some(80));
+        assert_eq!(row(2222).owner, PortOwner::Vm("devbox".into()));
+        assert_eq!(row(2222).target_port, Some(22));
+        assert_eq!(row(5930).target_port, None);
+        assert_eq!(row(5353).owner.to_string(), "avahi-daemon (host)");
+        assert_eq!(row(22).owner, PortOwner::Unknown);
+        // Published without a listener (no userland proxy)
+        assert_eq!(row(5432).addresses, ["*"]);
+        assert_eq!(rows.first().unwrap().host_port, 22);
+    }
+}
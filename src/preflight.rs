@@ -1,3 +1,5 @@
+use crate::config::{NovaConfig, parse_memory_to_bytes};
+use crate::vm_hugepages::{self, HugePagePool};
 use crate::{Result, log_info};
 use serde::Serialize;
 use std::fmt;
@@ -16,19 +18,340 @@ pub struct ToolStatus {
     pub available: bool,
 }
 
+/// Whether guests can run their own VMs: the vendor KVM module's `nested`
+/// parameter.
+#[derive(Debug, Clone, Serialize)]
+pub struct NestedVirtStatus {
+    /// `kvm_intel` or `kvm_amd`
+    pub module: &'static str,
+    pub loaded: bool,
+    pub nested: bool,
+}
+
+impl NestedVirtStatus {
+    /// Reloads the module with nesting on (guests must be shut down first)
+    /// and keeps it on across boots.
+    pub fn enable_hint(&self) -> String {
+        format!(
+            "sudo modprobe -r {module} && sudo modprobe {module} nested=1; \
+             echo 'options {module} nested=1' | sudo tee /etc/modprobe.d/kvm-nested.conf",
+            module = self.module
+        )
+    }
+
+    fn describe(&self) -> String {
+        match (self.loaded, self.nested) {
+            (false, _) => "not loaded".to_string(),
+            (true, true) => "loaded, nested enabled".to_string(),
+            (true, false) => format!("loaded, nested disabled (enable: {})", self.enable_hint()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct PreflightSummary {
     pub kernel_release: Option<String>,
     pub distribution: Option<String>,
     pub module_status: Vec<ModuleStatus>,
     pub tool_status: Vec<ToolStatus>,
+    /// `None` when the CPU vendor is unknown and no vendor module is loaded
+    pub nested_virt: Option<NestedVirtStatus>,
+    pub host: HostFacts,
     pub issues: Vec<String>,
 }
 
+/// Host state the readiness checks need besides modules and tools.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HostFacts {
+    pub dev_kvm: bool,
+    pub iommu_groups: usize,
+    pub hugepages: u64,
+    /// `virsh -c qemu:///system version` succeeded
+    pub libvirt_reachable: bool,
+    pub user: Option<String>,
+    pub is_root: bool,
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckLevel {
+    Pass,
+    Warn,
+    /// A hard requirement is missing
+    Fail,
+}
+
+impl fmt::Display for CheckLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            CheckLevel::Pass => "PASS",
+            CheckLevel::Warn => "WARN",
+            CheckLevel::Fail => "FAIL",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub level: CheckLevel,
+    pub detail: String,
+}
+
 impl PreflightSummary {
     pub fn is_ready(&self) -> bool {
         self.issues.is_empty()
     }
+
+    /// Pass/warn/fail verdicts for `nova preflight`. Only what every VM
+    /// needs (KVM, QEMU, libvirt) fails; passthrough and tuning warn.
+    pub fn checks(&self) -> Vec<PreflightCheck> {
+        let loaded = |name: &str| {
+            self.module_status
+                .iter()
+                .any(|module| module.name == name && module.loaded)
+        };
+        let available = |name: &str| {
+            self.tool_status
+                .iter()
+                .any(|tool| tool.name == name && tool.available)
+        };
+        let check = |name, level, detail: String| PreflightCheck {
+            name,
+            level,
+            detail,
+        };
+        let mut checks = Vec::new();
+
+        let vendor_module = ["kvm_intel", "kvm_amd"].into_iter().find(|m| loaded(m));
+        checks.push(match (self.host.dev_kvm, vendor_module) {
+            (true, Some(module)) => check("KVM", CheckLevel::Pass, format!("/dev/kvm present, {} loaded", module)),
+            (false, _) => check(
+                "KVM",
+                CheckLevel::Fail,
+                "/dev/kvm missing; enable virtualization (VT-x/AMD-V) in firmware and load kvm".to_string(),
+            ),
+            (true, None) => check(
+                "KVM",
+                CheckLevel::Fail,
+                "neither kvm_intel nor kvm_amd is loaded; run `sudo modprobe kvm_intel` or `kvm_amd`".to_string(),
+            ),
+        });
+
+        if let Some(nested) = &self.nested_virt
+            && nested.loaded
+        {
+            checks.push(if nested.nested {
+                check(
+                    "Nested virtualization",
+                    CheckLevel::Pass,
+                    format!("enabled in {}", nested.module),
+                )
+            } else {
+                check(
+                    "Nested virtualization",
+                    CheckLevel::Warn,
+                    format!("disabled; enable: {}", nested.enable_hint()),
+                )
+            });
+        }
+
+        checks.push(if available("qemu-system-x86_64") {
+            check(
+                "QEMU",
+                CheckLevel::Pass,
+                "qemu-system-x86_64 available".to_string(),
+            )
+        } else {
+            check(
+                "QEMU",
+                CheckLevel::Fail,
+                format!(
+                    "qemu-system-x86_64 missing; install {}",
+                    get_package_name("qemu-system-x86_64")
+                ),
+            )
+        });
+
+        checks.push(match (available("virsh"), self.host.libvirt_reachable) {
+            (true, true) => check(
+                "libvirt",
+                CheckLevel::Pass,
+                "qemu:///system reachable".to_string(),
+            ),
+            (true, false) => check(
+                "libvirt",
+                CheckLevel::Fail,
+                "cannot connect to qemu:///system; run `sudo systemctl enable --now libvirtd`"
+                    .to_string(),
+            ),
+            (false, _) => check(
+                "libvirt",
+                CheckLevel::Fail,
+                "virsh missing; install libvirt".to_string(),
+            ),
+        });
+
+        checks.push(if self.host.iommu_groups > 0 {
+            check(
+                "IOMMU",
+                CheckLevel::Pass,
+                format!("{} IOMMU groups", self.host.iommu_groups),
+            )
+        } else {
+            check(
+                "IOMMU",
+                CheckLevel::Warn,
+                "no IOMMU groups; PCI/GPU passthrough needs intel_iommu=on or amd_iommu=on"
+                    .to_string(),
+            )
+        });
+
+        let missing_vfio: Vec<&str> = ["vfio_pci", "vfio_iommu_type1"]
+            .into_iter()
+            .filter(|module| !loaded(module))
+            .collect();
+        checks.push(if missing_vfio.is_empty() {
+            check(
+                "VFIO modules",
+                CheckLevel::Pass,
+                "vfio_pci, vfio_iommu_type1 loaded".to_string(),
+            )
+        } else {
+            check(
+                "VFIO modules",
+                CheckLevel::Warn,
+                format!(
+                    "{} not loaded; needed for passthrough",
+                    missing_vfio.join(", ")
+                ),
+            )
+        });
+
+        checks.push(if self.host.hugepages > 0 {
+            check(
+                "Hugepages",
+                CheckLevel::Pass,
+                format!("{} pages reserved", self.host.hugepages),
+            )
+        } else {
+            check(
+                "Hugepages",
+                CheckLevel::Warn,
+                "none reserved (optional, see vm.nr_hugepages)".to_string(),
+            )
+        });
+
+        let missing_groups: Vec<&str> = ["libvirt", "kvm"]
+            .into_iter()
+            .filter(|group| !self.host.groups.iter().any(|g| g == group))
+            .collect();
+        checks.push(if self.host.is_root {
+            check(
+                "Group membership",
+                CheckLevel::Pass,
+                "running as root".to_string(),
+            )
+        } else if missing_groups.is_empty() {
+            check(
+                "Group membership",
+                CheckLevel::Pass,
+                "in libvirt and kvm".to_string(),
+            )
+        } else {
+            check(
+                "Group membership",
+                CheckLevel::Warn,
+                format!(
+                    "{} not in {}; run `sudo usermod -aG {} {}` and log in again",
+                    self.host.user.as_deref().unwrap_or("current user"),
+                    missing_groups.join(", "),
+                    missing_groups.join(","),
+                    self.host.user.as_deref().unwrap_or("$USER")
+                ),
+            )
+        });
+
+        for tool in &self.tool_status {
+            if matches!(tool.name, "virsh" | "qemu-system-x86_64") {
+                continue;
+            }
+            checks.push(if tool.available {
+                check(tool.name, CheckLevel::Pass, "available".to_string())
+            } else {
+                check(
+                    tool.name,
+                    CheckLevel::Warn,
+                    format!("missing; install {}", get_package_name(tool.name)),
+                )
+            });
+        }
+
+        checks
+    }
+}
+
+/// One check per NovaFile VM with `hugepages` set: whether the host has
+/// reserved enough pages of its size to start it.
+pub fn hugepage_checks(config: &NovaConfig) -> Vec<PreflightCheck> {
+    let mut vms: Vec<_> = config
+        .vm
+        .iter()
+        .filter_map(|(name, vm)| Some((name, vm, vm.hugepages?)))
+        .collect();
+    vms.sort_by_key(|(name, _, _)| *name);
+    vms.into_iter()
+        .map(|(name, vm, size)| {
+            let memory_kib = parse_memory_to_bytes(&vm.memory).unwrap_or(0) / 1024;
+            vm_hugepage_check(name, memory_kib, &vm_hugepages::host_pool(size))
+        })
+        .collect()
+}
+
+fn vm_hugepage_check(vm: &str, memory_kib: u64, pool: &HugePagePool) -> PreflightCheck {
+    let needed = pool.size.pages_for(memory_kib);
+    if pool.total >= needed {
+        PreflightCheck {
+            name: "VM hugepages",
+            level: CheckLevel::Pass,
+            detail: format!(
+                "'{}' needs {} {} pages; {} reserved, {} free",
+                vm, needed, pool.size, pool.total, pool.free
+            ),
+        }
+    } else {
+        PreflightCheck {
+            name: "VM hugepages",
+            level: CheckLevel::Warn,
+            detail: format!(
+                "'{}' needs {} {} pages but only {} are reserved; {}",
+                vm,
+                needed,
+                pool.size,
+                pool.total,
+                pool.size.reserve_hint(needed)
+            ),
+        }
+    }
+}
+
+/// `nova preflight` table: one row per check.
+pub fn render_checks(checks: &[PreflightCheck]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("CHECK".len());
+    let mut table = format!("{:<width$}  STATUS  DETAIL\n", "CHECK");
+    for check in checks {
+        table.push_str(&format!(
+            "{:<width$}  {:<6}  {}\n",
+            check.name, check.level, check.detail
+        ));
+    }
+    table
 }
 
 impl fmt::Display for PreflightSummary {
@@ -48,6 +371,10 @@ impl fmt::Display for PreflightSummary {
                 if module.loaded { "loaded" } else { "missing" }
             )?;
         }
+        if let Some(nested) = &self.nested_virt {
+            writeln!(f, "\nNested Virtualization:")?;
+            writeln!(f, "  - {}: {}", nested.module, nested.describe())?;
+        }
         writeln!(f, "\nUserland Tooling:")?;
         for tool in &self.tool_status {
             writeln!(
@@ -126,15 +453,84 @@ pub fn run_preflight() -> Result<PreflightSummary> {
         }
     }
 
+    let cpu_vendor = std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| parse_cpu_vendor(&cpuinfo));
+    let nested_virt = probe_nested_virt(&module_status, cpu_vendor.as_deref());
+
     Ok(PreflightSummary {
         kernel_release,
         distribution,
         module_status,
         tool_status,
+        nested_virt,
+        host: probe_host(),
         issues,
     })
 }
 
+fn probe_host() -> HostFacts {
+    HostFacts {
+        dev_kvm: Path::new("/dev/kvm").exists(),
+        iommu_groups: std::fs::read_dir("/sys/kernel/iommu_groups")
+            .map(|groups| groups.count())
+            .unwrap_or(0),
+        hugepages: std::fs::read_to_string("/proc/sys/vm/nr_hugepages")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0),
+        libvirt_reachable: read_command_output("virsh", &["-c", "qemu:///system", "version"])
+            .is_some(),
+        user: std::env::var("USER").ok(),
+        is_root: read_command_output("id", &["-u"]).as_deref() == Some("0"),
+        groups: read_command_output("id", &["-Gn"])
+            .map(|groups| groups.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+    }
+}
+
+fn probe_nested_virt(
+    modules: &[ModuleStatus],
+    cpu_vendor: Option<&str>,
+) -> Option<NestedVirtStatus> {
+    let loaded = |name: &str| {
+        modules
+            .iter()
+            .any(|module| module.name == name && module.loaded)
+    };
+    let module = if loaded("kvm_intel") {
+        "kvm_intel"
+    } else if loaded("kvm_amd") {
+        "kvm_amd"
+    } else {
+        match cpu_vendor? {
+            "GenuineIntel" => "kvm_intel",
+            "AuthenticAMD" => "kvm_amd",
+            _ => return None,
+        }
+    };
+    let nested = std::fs::read_to_string(format!("/sys/module/{}/parameters/nested", module))
+        .map(|value| nested_enabled(&value))
+        .unwrap_or(false);
+    Some(NestedVirtStatus {
+        module,
+        loaded: loaded(module),
+        nested,
+    })
+}
+
+/// kvm_intel reports `Y`/`N`, kvm_amd `1`/`0`.
+fn nested_enabled(parameter: &str) -> bool {
+    matches!(parameter.trim(), "Y" | "y" | "1")
+}
+
+fn parse_cpu_vendor(cpuinfo: &str) -> Option<String> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "vendor_id").then(|| value.trim().to_string())
+    })
+}
+
 fn probe_module(name: &'static str) -> ModuleStatus {
     ModuleStatus {
         name,
@@ -602,7 +998,11 @@ fn enable_required_services() -> FixAttempt {
 
 #[cfg(test)]
 mod tests {
-    use super::{ModuleStatus, PreflightSummary, ToolStatus};
+    use super::{
+        CheckLevel, HostFacts, ModuleStatus, NestedVirtStatus, PreflightSummary, ToolStatus,
+        nested_enabled, parse_cpu_vendor, render_checks, vm_hugepage_check,
+    };
+    use crate::vm_hugepages::{HugePagePool, HugePageSize};
 
     #[test]
     fn display_formats_readable_summary() {
@@ -629,6 +1029,12 @@ mod tests {
                     available: false,
                 },
             ],
+            nested_virt: Some(NestedVirtStatus {
+                module: "kvm_amd",
+                loaded: true,
+                nested: false,
+            }),
+            host: HostFacts::default(),
             issues: vec!["vfio_pci not loaded".into()],
         };
 
@@ -638,5 +1044,100 @@ mod tests {
         assert!(printed.contains("nmcli: missing"));
         assert!(printed.contains("vfio_pci not loaded"));
         assert!(printed.contains("Nova Preflight Summary"));
+        assert!(printed.contains("kvm_amd: loaded, nested disabled"));
+        assert!(printed.contains("sudo modprobe kvm_amd nested=1"));
+
+        assert!(nested_enabled("Y\n") && nested_enabled("1\n") && !nested_enabled("N\n"));
+        assert_eq!(
+            parse_cpu_vendor("processor\t: 0\nvendor_id\t: AuthenticAMD\ncpu family\t: 25\n")
+                .as_deref(),
+            Some("AuthenticAMD")
+        );
+    }
+
+    #[test]
+    fn checks_fail_only_on_hard_requirements() {
+        let module = |name, loaded| ModuleStatus { name, loaded };
+        let tool = |name, available| ToolStatus { name, available };
+        let mut summary = PreflightSummary {
+            kernel_release: None,
+            distribution: None,
+            module_status: vec![
+                module("kvm", true),
+                module("kvm_amd", true),
+                module("vfio_pci", false),
+            ],
+            tool_status: vec![
+                tool("virsh", true),
+                tool("qemu-system-x86_64", true),
+                tool("nmcli", false),
+            ],
+            nested_virt: None,
+            host: HostFacts {
+                dev_kvm: true,
+                libvirt_reachable: true,
+                user: Some("alex".into()),
+                groups: vec!["wheel".into(), "kvm".into()],
+                ..Default::default()
+            },
+            issues: Vec::new(),
+        };
+
+        let checks = summary.checks();
+        let level = |name: &str| {
+            checks
+                .iter()
+                .find(|check| check.name == name)
+                .unwrap()
+                .level
+        };
+        assert_eq!(level("KVM"), CheckLevel::Pass);
+        assert_eq!(level("libvirt"), CheckLevel::Pass);
+        assert_eq!(level("VFIO modules"), CheckLevel::Warn);
+        assert_eq!(level("Hugepages"), CheckLevel::Warn);
+        assert_eq!(level("nmcli"), CheckLevel::Warn);
+        assert!(checks.iter().all(|check| check.level != CheckLevel::Fail));
+        let groups = checks
+            .iter()
+            .find(|check| check.name == "Group membership")
+            .unwrap();
+        assert!(
+            groups.detail.contains("usermod -aG libvirt alex"),
+            "{}",
+            groups.detail
+        );
+
+        summary.host.libvirt_reachable = false;
+        summary.host.dev_kvm = false;
+        let checks = summary.checks();
+        let failed: Vec<&str> = checks
+            .iter()
+            .filter(|check| check.level == CheckLevel::Fail)
+            .map(|check| check.name)
+            .collect();
+        assert_eq!(failed, vec!["KVM", "libvirt"]);
+
+        let table = render_checks(&checks);
+        assert!(table.starts_with("CHECK "));
+        assert!(
+            table.contains("KVM               FAIL    /dev/kvm missing"),
+            "{}",
+            table
+        );
+
+        let pool = HugePagePool {
+            size: HugePageSize::Size2M,
+            total: 2048,
+            free: 2048,
+        };
+        let fits = vm_hugepage_check("gaming", 4 * 1024 * 1024, &pool);
+        assert_eq!(fits.level, CheckLevel::Pass);
+        let short = vm_hugepage_check("gaming", 8 * 1024 * 1024, &pool);
+        assert_eq!(short.level, CheckLevel::Warn);
+        assert!(
+            short.detail.contains("needs 4096 2M pages"),
+            "{}",
+            short.detail
+        );
     }
 }
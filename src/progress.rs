@@ -0,0 +1,378 @@
+//! Progress reporting for long-running operations.
+//!
+//! Clone, migrate, pull and snapshot export report through a [`Reporter`]
+//! built from the global `--quiet` and `--progress` flags. Progress goes to
+//! stderr so stdout carries only the final result, and `--progress json`
+//! writes one [`ProgressEvent`] per line for UIs and CI to consume.
+
+use crate::Result;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Output, Stdio};
+
+const BAR_WIDTH: usize = 30;
+/// Plain output prints a progress line every this many percent
+const PLAIN_STEP: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStyle {
+    /// One line per stage and every 10% of progress
+    Plain,
+    /// A bar redrawn in place
+    Bar,
+    /// One JSON object per line
+    Json,
+}
+
+impl ProgressStyle {
+    /// A bar on an interactive terminal, plain lines otherwise.
+    pub fn detect() -> Self {
+        if io::stderr().is_terminal() {
+            ProgressStyle::Bar
+        } else {
+            ProgressStyle::Plain
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Stage,
+    Progress,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    /// `clone`, `migrate`, `pull`, `convert`, ...
+    pub operation: String,
+    pub event: EventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    /// Set on progress events of stages started with [`Reporter::stage_bytes`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+}
+
+/// Text for `event` in `style`, without a line terminator; `None` when the
+/// style shows nothing for it.
+pub fn render(style: ProgressStyle, event: &ProgressEvent) -> Option<String> {
+    let mut suffix = String::new();
+    if let (Some(done), Some(total)) = (event.done_bytes, event.total_bytes) {
+        suffix.push_str(&format!(
+            " {} / {}",
+            format_bytes(done),
+            format_bytes(total)
+        ));
+    }
+    if let Some(message) = &event.message {
+        suffix.push_str(&format!(" {}", message));
+    }
+    match (style, event.event) {
+        (ProgressStyle::Json, _) => serde_json::to_string(event).ok(),
+        (_, EventKind::Stage) => Some(format!("[{}]{}", event.operation, suffix)),
+        (ProgressStyle::Plain, EventKind::Progress) => Some(format!(
+            "[{}] {:>3.0}%{}",
+            event.operation,
+            event.percent.unwrap_or(0.0),
+            suffix
+        )),
+        (ProgressStyle::Bar, EventKind::Progress) => {
+            let percent = event.percent.unwrap_or(0.0).clamp(0.0, 100.0);
+            let filled = ((percent / 100.0) * BAR_WIDTH as f64).round() as usize;
+            Some(format!(
+                "[{}] [{}{}] {:>3.0}%{}",
+                event.operation,
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                percent,
+                suffix
+            ))
+        }
+        (_, EventKind::Done) => event
+            .message
+            .as_ref()
+            .map(|_| format!("[{}]{}", event.operation, suffix)),
+        (_, EventKind::Failed) => Some(format!("[{}] failed:{}", event.operation, suffix)),
+    }
+}
+
+/// Progress for one operation. Final results are printed by the caller;
+/// with `quiet`, the reporter prints nothing at all.
+pub struct Reporter {
+    operation: String,
+    style: ProgressStyle,
+    quiet: bool,
+    out: Box<dyn Write + Send>,
+    /// A bar is drawn on the current line and needs ending before other output
+    bar_open: bool,
+    /// Last percent printed in plain style
+    last_plain: Option<f64>,
+    /// Size of the current stage, when it is a copy of known size
+    total_bytes: Option<u64>,
+}
+
+impl Reporter {
+    pub fn new(operation: &str, style: ProgressStyle, quiet: bool) -> Self {
+        Self {
+            operation: operation.to_string(),
+            style,
+            quiet,
+            out: Box::new(io::stderr()),
+            bar_open: false,
+            last_plain: None,
+            total_bytes: None,
+        }
+    }
+
+    /// A reporter that prints nothing, for callers without a terminal.
+    pub fn silent(operation: &str) -> Self {
+        Self::new(operation, ProgressStyle::Plain, true)
+    }
+
+    pub fn with_writer(mut self, out: impl Write + Send + 'static) -> Self {
+        self.out = Box::new(out);
+        self
+    }
+
+    /// Start a new step of the operation.
+    pub fn stage(&mut self, message: impl Into<String>) {
+        self.last_plain = None;
+        self.total_bytes = None;
+        self.emit(EventKind::Stage, Some(message.into()), None);
+    }
+
+    /// Start a step that moves `total_bytes`; its progress is also shown in
+    /// bytes.
+    pub fn stage_bytes(&mut self, message: impl Into<String>, total_bytes: u64) {
+        self.stage(message);
+        self.total_bytes = Some(total_bytes);
+    }
+
+    /// Progress through the current stage, from 0 to 100.
+    pub fn progress(&mut self, percent: f64, message: Option<String>) {
+        if self.style == ProgressStyle::Plain {
+            let due = match self.last_plain {
+                None => true,
+                Some(last) => percent >= last + PLAIN_STEP || (percent >= 100.0 && last < 100.0),
+            };
+            if !due {
+                return;
+            }
+            self.last_plain = Some(percent);
+        }
+        self.emit(EventKind::Progress, message, Some(percent));
+    }
+
+    pub fn finish(&mut self, message: Option<String>) {
+        self.emit(EventKind::Done, message, None);
+    }
+
+    pub fn fail(&mut self, message: impl Into<String>) {
+        self.emit(EventKind::Failed, Some(message.into()), None);
+    }
+
+    /// Finish or fail depending on how the operation ended.
+    pub fn conclude<T>(&mut self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.finish(None),
+            Err(err) => self.fail(err.to_string()),
+        }
+    }
+
+    fn emit(&mut self, event: EventKind, message: Option<String>, percent: Option<f64>) {
+        if self.quiet {
+            return;
+        }
+        let total_bytes = self.total_bytes.filter(|_| event == EventKind::Progress);
+        let event = ProgressEvent {
+            operation: self.operation.clone(),
+            event,
+            message,
+            percent,
+            done_bytes: total_bytes.zip(percent).map(|(total, percent)| {
+                (total as f64 * percent.clamp(0.0, 100.0) / 100.0).round() as u64
+            }),
+            total_bytes,
+        };
+        let Some(line) = render(self.style, &event) else {
+            if self.bar_open && event.event == EventKind::Done {
+                let _ = writeln!(self.out);
+                self.bar_open = false;
+            }
+            return;
+        };
+        // Progress output must never fail the operation itself
+        let _ = if self.style == ProgressStyle::Bar && event.event == EventKind::Progress {
+            self.bar_open = true;
+            write!(self.out, "\r{}\x1b[K", line)
+        } else if std::mem::take(&mut self.bar_open) {
+            writeln!(self.out, "\n{}", line)
+        } else {
+            writeln!(self.out, "{}", line)
+        };
+        let _ = self.out.flush();
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let value = bytes as f64;
+    if value >= KB * KB * KB {
+        format!("{:.1} GiB", value / (KB * KB * KB))
+    } else if value >= KB * KB {
+        format!("{:.1} MiB", value / (KB * KB))
+    } else if value >= KB {
+        format!("{:.1} KiB", value / KB)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+/// Percent from the last `(12.34/100%)` counter in `qemu-img -p` output.
+pub fn parse_qemu_img_progress(text: &str) -> Option<f64> {
+    let end = text.rfind("/100%)")?;
+    let start = text[..end].rfind('(')?;
+    text[start + 1..end].trim().parse().ok()
+}
+
+/// Run a `qemu-img` command that was given `-p`, reporting its progress
+/// counter as it goes. The returned output has stdout without the counter.
+pub fn qemu_img_output(command: &mut Command, reporter: &mut Reporter) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr = std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buffer);
+        buffer
+    });
+
+    let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let mut stdout = Vec::new();
+    let mut pending = String::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let read = stdout_pipe.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        pending.push_str(&String::from_utf8_lossy(&chunk[..read]));
+        // The counter is rewritten in place with carriage returns
+        while let Some(end) = pending.find(['\r', '\n']) {
+            let line: String = pending.drain(..=end).collect();
+            match parse_qemu_img_progress(&line) {
+                Some(percent) => reporter.progress(percent, None),
+                None => stdout.extend_from_slice(line.as_bytes()),
+            }
+        }
+    }
+    stdout.extend_from_slice(pending.as_bytes());
+
+    let status = child.wait()?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn run(style: ProgressStyle, quiet: bool) -> String {
+        let buffer = Buffer::default();
+        let mut reporter = Reporter::new("convert", style, quiet).with_writer(buffer.clone());
+        reporter.stage("Converting vda");
+        for percent in [0.0, 4.0, 12.5, 50.0, 55.0, 100.0] {
+            reporter.progress(percent, None);
+        }
+        reporter.conclude(&Ok(()));
+        buffer.text()
+    }
+
+    #[test]
+    fn styles_render_stages_and_progress() {
+        assert_eq!(parse_qemu_img_progress("    (45.67/100%)\r"), Some(45.67));
+        assert_eq!(
+            parse_qemu_img_progress("    (1.00/100%)\r    (2.50/100%)\r"),
+            Some(2.5)
+        );
+        assert_eq!(parse_qemu_img_progress("No errors were found"), None);
+
+        assert_eq!(run(ProgressStyle::Plain, true), "");
+
+        // Plain throttles to every 10%
+        assert_eq!(
+            run(ProgressStyle::Plain, false),
+            "[convert] Converting vda\n[convert]   0%\n[convert]  12%\n[convert]  50%\n[convert] 100%\n"
+        );
+
+        let bar = run(ProgressStyle::Bar, false);
+        assert!(bar.starts_with("[convert] Converting vda\n\r[convert] [---"));
+        assert!(bar.contains(&format!("[{}] 100%\x1b[K", "#".repeat(BAR_WIDTH))));
+        assert!(bar.ends_with("\x1b[K\n"), "{:?}", bar);
+
+        let json = run(ProgressStyle::Json, false);
+        let events: Vec<serde_json::Value> = json
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 8);
+        assert_eq!(events[0]["event"], "stage");
+        assert_eq!(events[0]["message"], "Converting vda");
+        assert_eq!(events[3]["percent"], 12.5);
+        assert_eq!(events[7]["event"], "done");
+        assert!(events[7].get("percent").is_none());
+
+        let buffer = Buffer::default();
+        let mut reporter =
+            Reporter::new("clone", ProgressStyle::Plain, false).with_writer(buffer.clone());
+        reporter.stage_bytes("Copying vda", 4 << 30);
+        reporter.progress(25.0, None);
+        reporter.stage("Checking vda");
+        reporter.progress(50.0, None);
+        assert_eq!(
+            buffer.text(),
+            "[clone] Copying vda\n[clone]  25% 1.0 GiB / 4.0 GiB\n[clone] Checking vda\n[clone]  50%\n"
+        );
+
+        let buffer = Buffer::default();
+        let mut reporter =
+            Reporter::new("pull", ProgressStyle::Json, false).with_writer(buffer.clone());
+        reporter.conclude::<()>(&Err(crate::NovaError::SystemCommandFailed));
+        assert!(buffer.text().contains(r#""event":"failed""#));
+    }
+}
@@ -1,10 +1,17 @@
+use crate::container::ContainerManager;
+use crate::container_runtime::RuntimeKind;
 use crate::firewall::FirewallManager;
+use crate::instance::InstanceStatus;
+use crate::monitoring::NetworkMonitor;
+use crate::network::{NetworkManager, SwitchStatus};
+use crate::performance_monitor::{self, DomainSample};
 use crate::port_monitor::PortMonitor;
-use crate::{NovaError, Result, log_debug, log_error, log_info};
+use crate::{NovaError, Result, log_debug, log_error, log_info, log_warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
@@ -19,6 +26,52 @@ pub struct PrometheusExporter {
     metrics_registry: Arc<Mutex<MetricsRegistry>>,
     collection_interval_secs: u64,
     enabled: bool,
+    collector_ready: Arc<AtomicBool>,
+    container_runtime: Option<RuntimeKind>,
+}
+
+/// Payload served by the `/healthz` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub libvirt_reachable: bool,
+    pub managers_initialized: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Unhealthy,
+}
+
+impl HealthReport {
+    pub fn new(libvirt_reachable: bool, managers_initialized: bool) -> Self {
+        let status = if libvirt_reachable && managers_initialized {
+            HealthStatus::Healthy
+        } else {
+            HealthStatus::Unhealthy
+        };
+
+        Self {
+            status,
+            libvirt_reachable,
+            managers_initialized,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Healthy
+    }
+
+    /// HTTP status code and reason phrase for this report
+    pub fn http_status(&self) -> (u16, &'static str) {
+        if self.is_healthy() {
+            (200, "OK")
+        } else {
+            (503, "Service Unavailable")
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +132,25 @@ pub struct Quantile {
     pub value: f64,
 }
 
+/// Sources for per-instance metrics. Kept across collections so VM CPU usage
+/// and switch state changes can be derived from consecutive samples.
+struct InstanceCollector {
+    containers: ContainerManager,
+    network: NetworkManager,
+    monitor: NetworkMonitor,
+    cpu_samples: HashMap<String, DomainSample>,
+    switch_up: HashMap<String, bool>,
+}
+
+/// State and traffic of one virtual switch at collection time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchSample {
+    pub name: String,
+    pub up: bool,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
 // Nova-specific metrics for HyperV Manager equivalent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NovaMetrics {
@@ -145,6 +217,8 @@ impl PrometheusExporter {
             metrics_registry: Arc::new(Mutex::new(MetricsRegistry::new())),
             collection_interval_secs: 15, // Collect metrics every 15 seconds
             enabled: true,
+            collector_ready: Arc::new(AtomicBool::new(false)),
+            container_runtime: None,
         }
     }
 
@@ -154,6 +228,12 @@ impl PrometheusExporter {
         self
     }
 
+    /// Collect container metrics from `kind` instead of auto-detecting.
+    pub fn with_container_runtime(mut self, kind: Option<RuntimeKind>) -> Self {
+        self.container_runtime = kind;
+        self
+    }
+
     /// Override the default metrics collection interval in seconds (minimum 1 second).
     pub fn with_collection_interval(mut self, interval_secs: u64) -> Self {
         self.collection_interval_secs = interval_secs.max(1);
@@ -175,17 +255,27 @@ impl PrometheusExporter {
         // Start metrics collection task
         let registry = self.metrics_registry.clone();
         let interval = self.collection_interval_secs;
+        let collector_ready = self.collector_ready.clone();
+        let container_runtime = self.container_runtime;
 
         tokio::spawn(async move {
             let mut collection_interval =
                 tokio::time::interval(tokio::time::Duration::from_secs(interval));
+            let mut instances = InstanceCollector::new(container_runtime);
 
             loop {
                 collection_interval.tick().await;
 
-                if let Err(e) = Self::collect_system_metrics(registry.clone()).await {
-                    log_error!("Failed to collect system metrics: {:?}", e);
+                match Self::collect_system_metrics(registry.clone()).await {
+                    Ok(()) => collector_ready.store(true, Ordering::Relaxed),
+                    Err(e) => {
+                        log_error!("Failed to collect system metrics: {:?}", e);
+                    }
                 }
+
+                let instance_metrics = instances.collect().await;
+                let mut metrics = registry.lock().unwrap();
+                Self::update_instance_metrics(&mut metrics, instance_metrics);
             }
         });
 
@@ -201,18 +291,35 @@ impl PrometheusExporter {
         }
 
         Self::collect_system_metrics(self.metrics_registry.clone()).await?;
+        let instance_metrics = InstanceCollector::new(self.container_runtime)
+            .collect()
+            .await;
 
-        let registry = self.metrics_registry.lock().unwrap();
+        let mut registry = self.metrics_registry.lock().unwrap();
+        Self::update_instance_metrics(&mut registry, instance_metrics);
         Ok(registry.export_prometheus_format())
     }
 
+    /// Build a health report, confirming libvirt connectivity before
+    /// reporting healthy.
+    pub fn health_report(&self) -> HealthReport {
+        Self::build_health_report(&self.collector_ready)
+    }
+
+    fn build_health_report(collector_ready: &AtomicBool) -> HealthReport {
+        HealthReport::new(
+            Self::check_libvirt_connection(),
+            collector_ready.load(Ordering::Relaxed),
+        )
+    }
+
     async fn start_metrics_server(&self) -> Result<()> {
         let listener = TcpListener::bind(format!("{}:{}", self.bind_addr, self.port))
             .await
             .map_err(|_| NovaError::NetworkError("Failed to bind Prometheus server".to_string()))?;
 
         log_info!(
-            "Prometheus metrics server started on http://{}:{}/metrics",
+            "Prometheus metrics server started on http://{}:{}/metrics (health: /healthz)",
             self.bind_addr,
             self.port
         );
@@ -223,8 +330,12 @@ impl PrometheusExporter {
                     log_debug!("Prometheus metrics request from {}", addr);
 
                     let registry = self.metrics_registry.clone();
+                    let collector_ready = self.collector_ready.clone();
                     tokio::spawn(async move {
-                        if let Err(e) = Self::handle_metrics_request(&mut stream, registry).await {
+                        if let Err(e) =
+                            Self::handle_metrics_request(&mut stream, registry, collector_ready)
+                                .await
+                        {
                             log_error!("Failed to handle metrics request: {:?}", e);
                         }
                     });
@@ -239,6 +350,7 @@ impl PrometheusExporter {
     async fn handle_metrics_request(
         stream: &mut tokio::net::TcpStream,
         registry: Arc<Mutex<MetricsRegistry>>,
+        collector_ready: Arc<AtomicBool>,
     ) -> Result<()> {
         let mut reader = BufReader::new(&mut *stream);
         let mut request_line = String::new();
@@ -248,6 +360,17 @@ impl PrometheusExporter {
             .map_err(|_| NovaError::NetworkError("Failed to read request".to_string()))?;
 
         let request_line_trimmed = request_line.trim();
+        if request_line_trimmed.starts_with("GET /healthz") {
+            let report =
+                tokio::task::spawn_blocking(move || Self::build_health_report(&collector_ready))
+                    .await
+                    .map_err(|_| NovaError::SystemCommandFailed)?;
+            let body = serde_json::to_string(&report)?;
+            let (status, reason) = report.http_status();
+            return Self::write_http_response(stream, status, reason, "application/json", &body)
+                .await;
+        }
+
         if !request_line_trimmed.starts_with("GET /metrics") {
             return Self::write_http_response(
                 stream,
@@ -330,6 +453,20 @@ impl PrometheusExporter {
         }
     }
 
+    /// Replace per-VM and per-switch gauges, so instances that disappeared
+    /// stop being reported. Counters accumulate.
+    fn update_instance_metrics(registry: &mut MetricsRegistry, instance_metrics: MetricsRegistry) {
+        registry.gauges.retain(|_, gauge| {
+            !gauge.labels.contains_key("vm_name") && !gauge.labels.contains_key("switch")
+        });
+        for (name, gauge) in instance_metrics.gauges {
+            registry.gauges.insert(name, gauge);
+        }
+        for (_, counter) in instance_metrics.counters {
+            registry.increment_counter(&counter.name, &counter.help, counter.labels, counter.value);
+        }
+    }
+
     async fn collect_host_metrics(registry: &mut MetricsRegistry) -> Result<()> {
         // CPU Usage
         if let Ok(cpu_usage) = Self::get_cpu_usage().await {
@@ -445,7 +582,11 @@ impl PrometheusExporter {
 
     pub async fn collect_vm_metrics(&self, vm_stats: &HashMap<String, VmStats>) -> Result<()> {
         let mut registry = self.metrics_registry.lock().unwrap();
+        Self::record_vm_stats(&mut registry, vm_stats);
+        Ok(())
+    }
 
+    fn record_vm_stats(registry: &mut MetricsRegistry, vm_stats: &HashMap<String, VmStats>) {
         // VM counts by state
         let mut running = 0u64;
         let mut stopped = 0u64;
@@ -533,8 +674,103 @@ impl PrometheusExporter {
             HashMap::new(),
             paused as f64,
         );
+    }
 
-        Ok(())
+    fn record_container_counts(registry: &mut MetricsRegistry, statuses: &[InstanceStatus]) {
+        let running = statuses
+            .iter()
+            .filter(|status| matches!(status, InstanceStatus::Running))
+            .count();
+        let stopped = statuses
+            .iter()
+            .filter(|status| matches!(status, InstanceStatus::Stopped))
+            .count();
+
+        registry.set_gauge(
+            "nova_containers_total",
+            "Total number of containers",
+            HashMap::new(),
+            statuses.len() as f64,
+        );
+        registry.set_gauge(
+            "nova_containers_running",
+            "Number of running containers",
+            HashMap::new(),
+            running as f64,
+        );
+        registry.set_gauge(
+            "nova_containers_stopped",
+            "Number of stopped containers",
+            HashMap::new(),
+            stopped as f64,
+        );
+    }
+
+    /// Per-switch state and traffic, plus a counter of up/down transitions
+    /// since the previous collection. `previous` is updated in place.
+    pub fn record_switch_states(
+        registry: &mut MetricsRegistry,
+        previous: &mut HashMap<String, bool>,
+        switches: &[SwitchSample],
+    ) {
+        let mut up_count = 0;
+        for switch in switches {
+            let mut labels = HashMap::new();
+            labels.insert("switch".to_string(), switch.name.clone());
+
+            registry.set_gauge(
+                "nova_switch_up",
+                "Virtual switch link state (1=up, 0=down)",
+                labels.clone(),
+                if switch.up { 1.0 } else { 0.0 },
+            );
+            registry.set_gauge(
+                "nova_switch_rx_bytes_total",
+                "Virtual switch bytes received",
+                labels.clone(),
+                switch.rx_bytes as f64,
+            );
+            registry.set_gauge(
+                "nova_switch_tx_bytes_total",
+                "Virtual switch bytes transmitted",
+                labels.clone(),
+                switch.tx_bytes as f64,
+            );
+
+            // Present both series from the first scrape so rate() works
+            for direction in ["up", "down"] {
+                let mut transition_labels = labels.clone();
+                transition_labels.insert("direction".to_string(), direction.to_string());
+                let changed = previous
+                    .get(&switch.name)
+                    .is_some_and(|was_up| *was_up != switch.up && switch.up == (direction == "up"));
+                registry.increment_counter(
+                    "nova_switch_transitions_total",
+                    "Virtual switch link state changes observed by Nova",
+                    transition_labels,
+                    if changed { 1.0 } else { 0.0 },
+                );
+            }
+
+            previous.insert(switch.name.clone(), switch.up);
+            if switch.up {
+                up_count += 1;
+            }
+        }
+        previous.retain(|name, _| switches.iter().any(|switch| switch.name == *name));
+
+        registry.set_gauge(
+            "nova_switches_up",
+            "Number of virtual switches that are up",
+            HashMap::new(),
+            up_count as f64,
+        );
+        registry.set_gauge(
+            "nova_switches_down",
+            "Number of virtual switches that are down",
+            HashMap::new(),
+            (switches.len() - up_count) as f64,
+        );
     }
 
     pub async fn collect_network_security_metrics(
@@ -787,6 +1023,61 @@ impl PrometheusExporter {
     }
 }
 
+/// Split a `--bind` value into address and port. `host:port` and
+/// `[v6addr]:port` carry their own port; a bare address uses `default_port`.
+/// IPv6 addresses come back bracketed, ready to join with a port.
+pub fn parse_bind_addr(bind: &str, default_port: u16) -> Result<(String, u16)> {
+    let invalid = || {
+        NovaError::ConfigError(format!(
+            "Invalid bind address '{}' (expected ADDR or ADDR:PORT, e.g. 0.0.0.0:9100)",
+            bind
+        ))
+    };
+    let parse_port = |port: &str| port.parse::<u16>().ok().filter(|port| *port > 0);
+
+    let bind = bind.trim();
+    let (host, port) = if let Some(rest) = bind.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => parse_port(port).ok_or_else(invalid)?,
+            None if rest.is_empty() => default_port,
+            None => return Err(invalid()),
+        };
+        (format!("[{}]", host), port)
+    } else if bind.matches(':').count() > 1 {
+        (format!("[{}]", bind), default_port)
+    } else if let Some((host, port)) = bind.split_once(':') {
+        (host.to_string(), parse_port(port).ok_or_else(invalid)?)
+    } else {
+        (bind.to_string(), default_port)
+    };
+
+    if host.is_empty() || host == "[]" {
+        return Err(invalid());
+    }
+    Ok((host, port))
+}
+
+/// `{key="value",...}` in a stable order, or nothing without labels.
+fn format_labels(labels: &HashMap<String, String>) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let mut pairs: Vec<_> = labels.iter().collect();
+    pairs.sort();
+    let pairs: Vec<String> = pairs
+        .into_iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", key, value)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
 fn parse_meminfo_value(line: &str) -> u64 {
     line.split_whitespace()
         .nth(1)
@@ -874,6 +1165,117 @@ impl PrometheusExporter {
     }
 }
 
+impl InstanceCollector {
+    fn new(container_runtime: Option<RuntimeKind>) -> Self {
+        Self {
+            containers: ContainerManager::selected(container_runtime),
+            network: NetworkManager::new(),
+            monitor: NetworkMonitor::new(),
+            cpu_samples: HashMap::new(),
+            switch_up: HashMap::new(),
+        }
+    }
+
+    /// Collect VM, container and switch metrics. Sources that are not
+    /// available on this host are skipped.
+    async fn collect(&mut self) -> MetricsRegistry {
+        let mut registry = MetricsRegistry::new();
+
+        match self.sample_vms().await {
+            Ok(vm_stats) => PrometheusExporter::record_vm_stats(&mut registry, &vm_stats),
+            Err(e) => {
+                log_debug!("Skipping VM metrics: {}", e);
+            }
+        }
+
+        let statuses: Vec<InstanceStatus> = self
+            .containers
+            .list_containers_async()
+            .await
+            .into_iter()
+            .map(|container| container.status)
+            .collect();
+        PrometheusExporter::record_container_counts(&mut registry, &statuses);
+
+        match self.network.refresh_state().await {
+            Ok(()) => {
+                let mut switches = Vec::new();
+                for switch in self.network.list_switches() {
+                    let stats = self.monitor.get_interface_stats(&switch.name).await.ok();
+                    switches.push(SwitchSample {
+                        name: switch.name.clone(),
+                        up: matches!(switch.status, SwitchStatus::Active),
+                        rx_bytes: stats.as_ref().map(|stats| stats.rx_bytes).unwrap_or(0),
+                        tx_bytes: stats.as_ref().map(|stats| stats.tx_bytes).unwrap_or(0),
+                    });
+                }
+                PrometheusExporter::record_switch_states(
+                    &mut registry,
+                    &mut self.switch_up,
+                    &switches,
+                );
+            }
+            Err(e) => {
+                log_warn!("Skipping switch metrics: {:?}", e);
+            }
+        }
+
+        registry
+    }
+
+    /// Per-VM usage from `virsh domstats`. CPU usage is measured against the
+    /// previous collection; the first one takes a short second sample.
+    async fn sample_vms(&mut self) -> std::result::Result<HashMap<String, VmStats>, String> {
+        let mut samples = tokio::task::spawn_blocking(performance_monitor::sample_domains)
+            .await
+            .map_err(|e| e.to_string())??;
+        if self.cpu_samples.is_empty() && samples.iter().any(DomainSample::is_running) {
+            self.cpu_samples = samples
+                .into_iter()
+                .map(|sample| (sample.vm_name.clone(), sample))
+                .collect();
+            sleep(Duration::from_millis(200)).await;
+            samples = tokio::task::spawn_blocking(performance_monitor::sample_domains)
+                .await
+                .map_err(|e| e.to_string())??;
+        }
+
+        let mut vm_stats = HashMap::new();
+        for sample in &samples {
+            let state = if sample.is_running() {
+                VmState::Running
+            } else if sample.is_paused() {
+                VmState::Paused
+            } else {
+                VmState::Stopped
+            };
+            let cpu_usage_percent = match (&state, self.cpu_samples.get(&sample.vm_name)) {
+                (VmState::Running, Some(previous)) => sample.cpu_percent_since(previous),
+                _ => 0.0,
+            };
+            vm_stats.insert(
+                sample.vm_name.clone(),
+                VmStats {
+                    name: sample.vm_name.clone(),
+                    state,
+                    cpu_usage_percent,
+                    memory_usage_bytes: sample.rss_kib.unwrap_or(sample.memory_kib) * 1024,
+                    network_rx_bytes: sample.network_rx_bytes,
+                    network_tx_bytes: sample.network_tx_bytes,
+                    disk_read_bytes: sample.disk_read_bytes,
+                    disk_write_bytes: sample.disk_write_bytes,
+                },
+            );
+        }
+
+        self.cpu_samples = samples
+            .into_iter()
+            .map(|sample| (sample.vm_name.clone(), sample))
+            .collect();
+        Ok(vm_stats)
+    }
+}
+
 impl Default for MetricsRegistry {
     fn default() -> Self {
         Self::new()
@@ -1059,60 +1461,53 @@ impl MetricsRegistry {
         }
     }
 
-    pub fn export_prometheus_format(&self) -> String {
-        let mut output = String::new();
-
-        // Export counters
-        for counter in self.counters.values() {
-            output.push_str(&format!("# HELP {} {}\n", counter.name, counter.help));
-            output.push_str(&format!("# TYPE {} counter\n", counter.name));
-
-            let label_str = if counter.labels.is_empty() {
-                String::new()
-            } else {
-                format!(
-                    "{{{}}}",
-                    counter
-                        .labels
-                        .iter()
-                        .map(|(k, v)| format!("{}=\"{}\"", k, v))
-                        .collect::<Vec<_>>()
-                        .join(",")
-                )
-            };
-
-            output.push_str(&format!(
-                "{}{} {}\n",
-                counter.name, label_str, counter.value
-            ));
+    fn export_samples<'a>(
+        output: &mut String,
+        metric_type: &str,
+        samples: impl Iterator<Item = (&'a String, &'a String, &'a HashMap<String, String>, f64)>,
+    ) {
+        let mut samples: Vec<_> = samples
+            .map(|(name, help, labels, value)| (name, help, format_labels(labels), value))
+            .collect();
+        samples.sort_by(|a, b| (a.0, &a.2).cmp(&(b.0, &b.2)));
+
+        let mut current: Option<&String> = None;
+        for (name, help, labels, value) in samples {
+            if current != Some(name) {
+                output.push_str(&format!("# HELP {} {}\n", name, help));
+                output.push_str(&format!("# TYPE {} {}\n", name, metric_type));
+                current = Some(name);
+            }
+            output.push_str(&format!("{}{} {}\n", name, labels, value));
         }
+    }
 
-        // Export gauges
-        for gauge in self.gauges.values() {
-            output.push_str(&format!("# HELP {} {}\n", gauge.name, gauge.help));
-            output.push_str(&format!("# TYPE {} gauge\n", gauge.name));
-
-            let label_str = if gauge.labels.is_empty() {
-                String::new()
-            } else {
-                format!(
-                    "{{{}}}",
-                    gauge
-                        .labels
-                        .iter()
-                        .map(|(k, v)| format!("{}=\"{}\"", k, v))
-                        .collect::<Vec<_>>()
-                        .join(",")
-                )
-            };
+    pub fn export_prometheus_format(&self) -> String {
+        let mut output = String::new();
 
-            output.push_str(&format!("{}{} {}\n", gauge.name, label_str, gauge.value));
-        }
+        // Export counters and gauges. Scrapers reject a repeated HELP/TYPE,
+        // so samples are grouped by metric name.
+        let counters = self
+            .counters
+            .values()
+            .map(|counter| (&counter.name, &counter.help, &counter.labels, counter.value));
+        Self::export_samples(&mut output, "counter", counters);
+        let gauges = self
+            .gauges
+            .values()
+            .map(|gauge| (&gauge.name, &gauge.help, &gauge.labels, gauge.value));
+        Self::export_samples(&mut output, "gauge", gauges);
 
         // Export histograms
-        for histogram in self.histograms.values() {
-            output.push_str(&format!("# HELP {} {}\n", histogram.name, histogram.help));
-            output.push_str(&format!("# TYPE {} histogram\n", histogram.name));
+        let mut histograms: Vec<_> = self.histograms.values().collect();
+        histograms.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut current: Option<&String> = None;
+        for histogram in histograms {
+            if current != Some(&histogram.name) {
+                output.push_str(&format!("# HELP {} {}\n", histogram.name, histogram.help));
+                output.push_str(&format!("# TYPE {} histogram\n", histogram.name));
+                current = Some(&histogram.name);
+            }
 
             let base_labels = if histogram.labels.is_empty() {
                 String::new()
@@ -1163,7 +1558,10 @@ impl MetricsRegistry {
 
 #[cfg(test)]
 mod tests {
-    use super::{MetricsRegistry, PrometheusExporter};
+    use super::{
+        HealthReport, HealthStatus, MetricsRegistry, PrometheusExporter, SwitchSample,
+        parse_bind_addr,
+    };
     use std::collections::HashMap;
 
     #[test]
@@ -1172,6 +1570,23 @@ mod tests {
         assert_eq!(exporter.collection_interval_secs, 1);
     }
 
+    #[test]
+    fn health_report_requires_libvirt_and_managers() {
+        let healthy = HealthReport::new(true, true);
+        assert_eq!(healthy.status, HealthStatus::Healthy);
+        assert_eq!(healthy.http_status().0, 200);
+
+        let no_libvirt = HealthReport::new(false, true);
+        assert_eq!(no_libvirt.status, HealthStatus::Unhealthy);
+        assert_eq!(no_libvirt.http_status(), (503, "Service Unavailable"));
+
+        assert!(!HealthReport::new(true, false).is_healthy());
+
+        let payload = serde_json::to_string(&no_libvirt).unwrap();
+        assert!(payload.contains("\"status\":\"unhealthy\""));
+        assert!(payload.contains("\"libvirt_reachable\":false"));
+    }
+
     #[test]
     fn registry_reset_clears_all_metric_buckets() {
         let mut registry = MetricsRegistry::new();
@@ -1220,6 +1635,80 @@ mod tests {
         assert_eq!(tx, 2_000_u64 + 4_000_u64);
     }
 
+    #[test]
+    fn bind_addr_accepts_host_port_and_bare_addresses() {
+        assert_eq!(
+            parse_bind_addr("0.0.0.0:9100", 9640).unwrap(),
+            ("0.0.0.0".to_string(), 9100)
+        );
+        assert_eq!(
+            parse_bind_addr("127.0.0.1", 9640).unwrap(),
+            ("127.0.0.1".to_string(), 9640)
+        );
+        assert_eq!(
+            parse_bind_addr("[::1]:9100", 9640).unwrap(),
+            ("[::1]".to_string(), 9100)
+        );
+        assert_eq!(
+            parse_bind_addr("::", 9100).unwrap(),
+            ("[::]".to_string(), 9100)
+        );
+        for bad in [":9100", "0.0.0.0:0", "0.0.0.0:http", "[::1]9100", ""] {
+            assert!(parse_bind_addr(bad, 9100).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn switch_states_are_gauges_with_transition_counters() {
+        let switch = |name: &str, up: bool| SwitchSample {
+            name: name.to_string(),
+            up,
+            rx_bytes: 1024,
+            tx_bytes: 2048,
+        };
+        let mut registry = MetricsRegistry::new();
+        let mut previous = HashMap::new();
+
+        PrometheusExporter::record_switch_states(
+            &mut registry,
+            &mut previous,
+            &[switch("br0", true), switch("nova-nat", true)],
+        );
+        PrometheusExporter::record_switch_states(
+            &mut registry,
+            &mut previous,
+            &[switch("br0", false), switch("nova-nat", true)],
+        );
+        PrometheusExporter::record_switch_states(
+            &mut registry,
+            &mut previous,
+            &[switch("br0", true), switch("nova-nat", true)],
+        );
+
+        let output = registry.export_prometheus_format();
+        assert!(
+            output.contains("nova_switch_transitions_total{direction=\"down\",switch=\"br0\"} 1\n")
+        );
+        assert!(
+            output.contains("nova_switch_transitions_total{direction=\"up\",switch=\"br0\"} 1\n")
+        );
+        assert!(
+            output.contains(
+                "nova_switch_transitions_total{direction=\"down\",switch=\"nova-nat\"} 0\n"
+            )
+        );
+        assert!(output.contains("nova_switch_up{switch=\"br0\"} 1\n"));
+        assert!(output.contains("nova_switches_up 2\n"));
+        // One HELP/TYPE per metric name, however many label sets it has
+        assert_eq!(
+            output
+                .matches("# TYPE nova_switch_transitions_total counter")
+                .count(),
+            1
+        );
+        assert_eq!(output.matches("# TYPE nova_switch_up gauge").count(), 1);
+    }
+
     #[tokio::test]
     async fn collect_once_emits_metrics_snapshot() {
         let exporter = PrometheusExporter::new(0).with_collection_interval(1);
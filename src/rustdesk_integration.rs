@@ -0,0 +1,373 @@
+//! RustDesk remote desktop for guests.
+//!
+//! RustDesk runs inside the guest and registers with its rendezvous server
+//! under a numeric ID. Nova reads that ID through the QEMU guest agent, sets
+//! a fresh random password the same way and launches the host's RustDesk
+//! client against it, so guests (Windows ones especially) can be reached
+//! without a SPICE display.
+//!
+//! RustDesk's `--password` sets the guest's permanent password, replacing
+//! whatever was configured there; RustDesk has no command line for its
+//! temporary one. The password never goes on the host's command lines:
+//! guest agent requests reach virsh on stdin and the client is launched
+//! without it, for the user to type in.
+
+use crate::{NovaError, Result, log_debug, log_warn};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Shown when the host has no RustDesk client in PATH.
+pub const NO_CLIENT_MESSAGE: &str = "No RustDesk client found in PATH. Install it from \
+     https://rustdesk.com or your distribution (rustdesk / rustdesk-bin in the AUR), or run \
+     `nova rustdesk connect <vm> --print` and enter the ID and password on another machine.";
+
+/// How long a command started through `guest-exec` may run.
+const GUEST_EXEC_TIMEOUT: Duration = Duration::from_secs(15);
+const GUEST_EXEC_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GuestFamily {
+    Windows,
+    Linux,
+}
+
+impl GuestFamily {
+    /// Where the RustDesk installers put the binary.
+    pub fn rustdesk_path(&self) -> &'static str {
+        match self {
+            GuestFamily::Windows => r"C:\Program Files\RustDesk\rustdesk.exe",
+            GuestFamily::Linux => "/usr/bin/rustdesk",
+        }
+    }
+}
+
+/// RustDesk details for one guest. Fields the guest agent could not
+/// provide are `None`, with the reason in `problem`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustDeskInfo {
+    pub vm_name: String,
+    pub guest_agent: bool,
+    pub guest: Option<GuestFamily>,
+    pub id: Option<String>,
+    /// Only set when Nova generated a new permanent password
+    pub password: Option<String>,
+    pub problem: Option<String>,
+}
+
+/// Output of a finished `guest-exec` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestExecResult {
+    pub exit_code: i64,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub struct RustDeskManager;
+
+impl RustDeskManager {
+    /// Look up the guest's RustDesk ID. With `new_password` a random
+    /// password replaces the guest's permanent RustDesk password and is
+    /// returned as well.
+    pub async fn session_info(vm_name: &str, new_password: bool) -> Result<RustDeskInfo> {
+        let mut info = RustDeskInfo {
+            vm_name: vm_name.to_string(),
+            guest_agent: false,
+            guest: None,
+            id: None,
+            password: None,
+            problem: None,
+        };
+
+        if let Err(e) = agent_command(vm_name, r#"{"execute":"guest-ping"}"#) {
+            info.problem = Some(format!(
+                "QEMU guest agent is not responding in '{}' ({}); the VM must be running with \
+                 qemu-guest-agent installed (virtio-win guest tools on Windows)",
+                vm_name, e
+            ));
+            return Ok(info);
+        }
+        info.guest_agent = true;
+
+        let osinfo = agent_command(vm_name, r#"{"execute":"guest-get-osinfo"}"#)?;
+        let guest = parse_guest_family(&osinfo);
+        info.guest = Some(guest);
+        let binary = guest.rustdesk_path();
+
+        let id = guest_exec(vm_name, binary, &["--get-id"]).await;
+        match id.as_ref().map(|result| parse_rustdesk_id(&result.stdout)) {
+            Ok(Some(id)) => info.id = Some(id),
+            Ok(None) => {
+                info.problem = Some(format!(
+                    "RustDesk in '{}' reported no ID; make sure its service is running",
+                    vm_name
+                ));
+                return Ok(info);
+            }
+            Err(e) => {
+                info.problem = Some(format!("Could not run {} in the guest: {}", binary, e));
+                return Ok(info);
+            }
+        }
+
+        if new_password {
+            let password = generate_password();
+            let result = guest_exec(vm_name, binary, &["--password", &password]).await?;
+            if result.exit_code != 0 {
+                return Err(NovaError::ConfigError(format!(
+                    "RustDesk in '{}' refused the new password: {}",
+                    vm_name,
+                    result.stderr.trim()
+                )));
+            }
+            info.password = Some(password);
+        }
+
+        Ok(info)
+    }
+
+    /// Start the host's RustDesk client connected to `info`. The password
+    /// is left for the user to enter so it stays out of the process list.
+    pub fn launch_client(info: &RustDeskInfo) -> Result<()> {
+        let id = info.id.as_deref().ok_or_else(|| {
+            NovaError::ConfigError(
+                info.problem
+                    .clone()
+                    .unwrap_or_else(|| format!("No RustDesk ID known for '{}'", info.vm_name)),
+            )
+        })?;
+        let client = Self::detect_client()
+            .ok_or_else(|| NovaError::ConfigError(NO_CLIENT_MESSAGE.to_string()))?;
+
+        Command::new(&client)
+            .args(["--connect", id])
+            .spawn()
+            .map_err(|e| {
+                NovaError::ConfigError(format!("Failed to launch {}: {}", client.display(), e))
+            })?;
+        Ok(())
+    }
+
+    /// The RustDesk client in PATH.
+    pub fn detect_client() -> Option<PathBuf> {
+        Self::detect_client_in(&std::env::var_os("PATH").unwrap_or_default())
+    }
+
+    /// The RustDesk client in the directories of a PATH-style list.
+    pub fn detect_client_in(path: &OsStr) -> Option<PathBuf> {
+        std::env::split_paths(path)
+            .map(|dir| dir.join("rustdesk"))
+            .find(|candidate| {
+                std::fs::metadata(candidate)
+                    .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                    .unwrap_or(false)
+            })
+    }
+}
+
+fn agent_command(vm_name: &str, command: &str) -> Result<String> {
+    let output = Command::new("virsh")
+        .args(["qemu-agent-command", vm_name, command])
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        log_debug!(
+            "Guest agent command failed for {}: {}",
+            vm_name,
+            error.trim()
+        );
+        return Err(NovaError::LibvirtError(error.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Like `agent_command`, but hands the request to virsh on stdin so that
+/// secrets in it do not appear in the host's process list.
+fn agent_command_stdin(vm_name: &str, command: &str) -> Result<String> {
+    let mut child = Command::new("virsh")
+        .arg("-q")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", virsh_shell_line(vm_name, command))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    // virsh reading commands from stdin does not always fail on errors
+    let error = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || error.contains("error:") {
+        log_debug!(
+            "Guest agent command failed for {}: {}",
+            vm_name,
+            error.trim()
+        );
+        return Err(NovaError::LibvirtError(error.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A `qemu-agent-command` line for the virsh shell. Single quotes keep the
+/// JSON literal; a quote inside it is closed, escaped and reopened.
+pub fn virsh_shell_line(vm_name: &str, command: &str) -> String {
+    let quote = |value: &str| format!("'{}'", value.replace('\'', r"'\''"));
+    format!("qemu-agent-command {} {}", quote(vm_name), quote(command))
+}
+
+/// Run `path args...` in the guest and wait for it to exit. The arguments
+/// may carry secrets, so the request goes to virsh on stdin.
+async fn guest_exec(vm_name: &str, path: &str, args: &[&str]) -> Result<GuestExecResult> {
+    let request = serde_json::json!({
+        "execute": "guest-exec",
+        "arguments": { "path": path, "arg": args, "capture-output": true },
+    });
+    let reply = agent_command_stdin(vm_name, &request.to_string())?;
+    let pid = parse_exec_pid(&reply).ok_or_else(|| {
+        NovaError::LibvirtError(format!("Unexpected guest-exec reply: {}", reply.trim()))
+    })?;
+
+    let status = serde_json::json!({
+        "execute": "guest-exec-status",
+        "arguments": { "pid": pid },
+    })
+    .to_string();
+    let deadline = std::time::Instant::now() + GUEST_EXEC_TIMEOUT;
+    loop {
+        if let Some(result) = parse_exec_status(&agent_command(vm_name, &status)?) {
+            return Ok(result);
+        }
+        if std::time::Instant::now() >= deadline {
+            log_warn!(
+                "{} in '{}' did not exit within {:?}",
+                path,
+                vm_name,
+                GUEST_EXEC_TIMEOUT
+            );
+            return Err(NovaError::LibvirtError(format!(
+                "{} did not exit within {} seconds",
+                path,
+                GUEST_EXEC_TIMEOUT.as_secs()
+            )));
+        }
+        tokio::time::sleep(GUEST_EXEC_POLL).await;
+    }
+}
+
+fn generate_password() -> String {
+    use rand::RngExt;
+    const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnpqrstuvwxyz23456789";
+    const PASSWORD_LEN: usize = 10;
+    let mut rng = rand::rng();
+
+    (0..PASSWORD_LEN)
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// Guest family from a `guest-get-osinfo` reply; anything but Windows is
+/// treated as Linux.
+pub fn parse_guest_family(reply: &str) -> GuestFamily {
+    let id = serde_json::from_str::<serde_json::Value>(reply)
+        .ok()
+        .and_then(|value| value["return"]["id"].as_str().map(str::to_string));
+    match id.as_deref() {
+        Some("mswindows") => GuestFamily::Windows,
+        _ => GuestFamily::Linux,
+    }
+}
+
+/// Process ID from a `guest-exec` reply.
+pub fn parse_exec_pid(reply: &str) -> Option<i64> {
+    let value: serde_json::Value = serde_json::from_str(reply).ok()?;
+    value["return"]["pid"].as_i64()
+}
+
+/// Result from a `guest-exec-status` reply, or `None` while the command runs.
+pub fn parse_exec_status(reply: &str) -> Option<GuestExecResult> {
+    let value: serde_json::Value = serde_json::from_str(reply).ok()?;
+    let status = &value["return"];
+    if !status["exited"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let decode = |field: &str| {
+        status[field]
+            .as_str()
+            .and_then(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    };
+    Some(GuestExecResult {
+        exit_code: status["exitcode"].as_i64().unwrap_or(0),
+        stdout: decode("out-data"),
+        stderr: decode("err-data"),
+    })
+}
+
+/// RustDesk ID printed by `rustdesk --get-id`, ignoring blank lines and
+/// the digit grouping some versions add.
+pub fn parse_rustdesk_id(output: &str) -> Option<String> {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())?;
+    let id: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    id.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        .then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guest_agent_replies_are_parsed() {
+        let osinfo = r#"{"return":{"id":"mswindows","name":"Microsoft Windows","version":"11"}}"#;
+        assert_eq!(parse_guest_family(osinfo), GuestFamily::Windows);
+        assert_eq!(
+            parse_guest_family(r#"{"return":{"id":"fedora"}}"#),
+            GuestFamily::Linux
+        );
+
+        assert_eq!(parse_exec_pid(r#"{"return":{"pid":4412}}"#), Some(4412));
+        assert_eq!(parse_exec_status(r#"{"return":{"exited":false}}"#), None);
+
+        // "MTIzIDQ1NiA3ODkK" is "123 456 789\n"
+        let result = parse_exec_status(
+            r#"{"return":{"exitcode":0,"out-data":"MTIzIDQ1NiA3ODkK","exited":true}}"#,
+        )
+        .unwrap();
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(
+            parse_rustdesk_id(&result.stdout).as_deref(),
+            Some("123456789")
+        );
+        assert_eq!(parse_rustdesk_id("\n  \n"), None);
+        assert_eq!(parse_rustdesk_id("error: no service"), None);
+
+        assert_eq!(generate_password().len(), 10);
+    }
+
+    #[test]
+    fn agent_requests_are_quoted_for_the_virsh_shell() {
+        let request = r#"{"execute":"guest-exec","arguments":{"path":"C:\\Program Files\\RustDesk\\rustdesk.exe"}}"#;
+        assert_eq!(
+            virsh_shell_line("win11", request),
+            format!("qemu-agent-command 'win11' '{}'", request)
+        );
+        assert_eq!(
+            virsh_shell_line("vm", r#"{"arg":["it's"]}"#),
+            r#"qemu-agent-command 'vm' '{"arg":["it'\''s"]}'"#
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Selecting several instances at once for `nova run` and `nova stop`.
+//!
+//! Names may be literal or shell-style globs (`web-*`, `db-?`), `--all`
+//! picks every known instance of the type, and `--label key=value` narrows
+//! the choice to NovaFile entries whose `labels` map matches. Literal names
+//! are kept even when Nova does not know them yet, so the manager reports
+//! the usual "not found" error for a typo instead of a silent no-op.
+
+use crate::{NovaError, Result, instance::InstanceType};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as FmtWrite;
+
+/// A `key=value` label to match against a NovaFile `labels` map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelSelector {
+    pub key: String,
+    pub value: String,
+}
+
+impl LabelSelector {
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok(Self {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(NovaError::ConfigError(format!(
+                "Invalid label selector '{}'; expected key=value",
+                spec
+            ))),
+        }
+    }
+
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        labels.get(&self.key) == Some(&self.value)
+    }
+}
+
+/// Which instances a bulk command applies to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selector {
+    pub patterns: Vec<String>,
+    pub all: bool,
+    pub labels: Vec<LabelSelector>,
+}
+
+/// Whether `name` contains glob metacharacters.
+pub fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Shell-style match where `*` is any run of characters and `?` one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name index it is currently covering
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, covered)) => {
+                    p = star + 1;
+                    n = covered + 1;
+                    backtrack = Some((star, covered + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl Selector {
+    /// A single literal name with no `--all` or `--label`: the classic
+    /// one-instance form of the command.
+    pub fn is_single(&self) -> bool {
+        !self.all
+            && self.labels.is_empty()
+            && self.patterns.len() == 1
+            && !is_pattern(&self.patterns[0])
+    }
+
+    /// Names from `known` (each with its NovaFile labels, if it has an
+    /// entry) picked by this selector, sorted and without duplicates.
+    pub fn select<'a, I>(&self, instance_type: InstanceType, known: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = (&'a str, Option<&'a HashMap<String, String>>)>,
+    {
+        let mut labels_by_name: HashMap<&str, Option<&HashMap<String, String>>> = HashMap::new();
+        for (name, labels) in known {
+            let entry = labels_by_name.entry(name).or_default();
+            if labels.is_some() {
+                *entry = labels;
+            }
+        }
+
+        let mut chosen = BTreeSet::new();
+        if self.all || self.patterns.is_empty() {
+            chosen.extend(labels_by_name.keys().map(|name| name.to_string()));
+        } else {
+            for pattern in &self.patterns {
+                if is_pattern(pattern) {
+                    chosen.extend(
+                        labels_by_name
+                            .keys()
+                            .filter(|name| glob_match(pattern, name))
+                            .map(|name| name.to_string()),
+                    );
+                } else {
+                    chosen.insert(pattern.clone());
+                }
+            }
+        }
+
+        chosen.retain(|name| {
+            self.labels.iter().all(|selector| {
+                labels_by_name
+                    .get(name.as_str())
+                    .copied()
+                    .flatten()
+                    .is_some_and(|labels| selector.matches(labels))
+            })
+        });
+
+        if chosen.is_empty() {
+            return Err(NovaError::ConfigError(format!(
+                "No {} match {}",
+                plural(instance_type),
+                self.describe()
+            )));
+        }
+        Ok(chosen.into_iter().collect())
+    }
+
+    fn describe(&self) -> String {
+        let mut parts: Vec<String> = if self.all {
+            vec!["--all".to_string()]
+        } else {
+            self.patterns
+                .iter()
+                .map(|pattern| format!("'{}'", pattern))
+                .collect()
+        };
+        parts.extend(
+            self.labels
+                .iter()
+                .map(|label| format!("--label {}={}", label.key, label.value)),
+        );
+        if parts.is_empty() {
+            "the selection".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+fn plural(instance_type: InstanceType) -> &'static str {
+    match instance_type {
+        InstanceType::Vm => "VMs",
+        InstanceType::Container => "containers",
+    }
+}
+
+/// Per-instance results of a bulk operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkReport {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    /// Instances left alone, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+impl BulkReport {
+    pub fn record(&mut self, name: &str, result: Result<()>) {
+        match result {
+            Ok(()) => self.succeeded.push(name.to_string()),
+            Err(err) => self.failed.push((name.to_string(), err.to_string())),
+        }
+    }
+
+    /// Note an instance that needed nothing done, e.g. one already stopped.
+    pub fn skip(&mut self, name: &str, reason: &str) {
+        self.skipped.push((name.to_string(), reason.to_string()));
+    }
+
+    /// Summary printed once every instance has been tried; `action` is the
+    /// past tense of the operation, e.g. "started".
+    pub fn render(&self, action: &str) -> String {
+        let mut out = format!(
+            "\n{} of {} {}\n",
+            self.succeeded.len(),
+            self.succeeded.len() + self.failed.len(),
+            action
+        );
+        for name in &self.succeeded {
+            let _ = writeln!(out, "  ✅ {}", name);
+        }
+        for (name, err) in &self.failed {
+            let _ = writeln!(out, "  ❌ {}: {}", name, err);
+        }
+        for (name, reason) in &self.skipped {
+            let _ = writeln!(out, "  ⏭  {}: {}", name, reason);
+        }
+        out
+    }
+
+    /// An error naming the failed instances, if there were any.
+    pub fn into_result(self, action: &str) -> Result<()> {
+        if self.failed.is_empty() {
+            return Ok(());
+        }
+        let names: Vec<String> = self.failed.into_iter().map(|(name, _)| name).collect();
+        Err(NovaError::ConfigError(format!(
+            "{} of {} instance(s) were not {}: {}",
+            names.len(),
+            names.len() + self.succeeded.len(),
+            action,
+            names.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_labels_and_all_select_instances() {
+        assert!(glob_match("web-*", "web-1"));
+        assert!(glob_match("*-db-?", "prod-db-2"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("web-*", "api-1"));
+        assert!(!glob_match("db-?", "db-10"));
+
+        let prod = HashMap::from([("env".to_string(), "prod".to_string())]);
+        let dev = HashMap::from([("env".to_string(), "dev".to_string())]);
+        let known = || {
+            [
+                ("web-1", Some(&prod)),
+                ("web-2", Some(&dev)),
+                ("api", Some(&prod)),
+                ("scratch", None),
+            ]
+        };
+
+        let glob = Selector {
+            patterns: vec!["web-*".to_string()],
+            ..Default::default()
+        };
+        assert!(!glob.is_single());
+        assert_eq!(
+            glob.select(InstanceType::Vm, known()).unwrap(),
+            ["web-1", "web-2"]
+        );
+
+        let labelled = Selector {
+            labels: vec![LabelSelector::parse("env=prod").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(
+            labelled.select(InstanceType::Vm, known()).unwrap(),
+            ["api", "web-1"]
+        );
+        let all = Selector {
+            all: true,
+            ..Default::default()
+        };
+        assert_eq!(all.select(InstanceType::Vm, known()).unwrap().len(), 4);
+
+        // Unknown literal names are passed through for the manager to reject
+        let typo = Selector {
+            patterns: vec!["wbe-1".to_string()],
+            ..Default::default()
+        };
+        assert!(typo.is_single());
+        assert_eq!(typo.select(InstanceType::Vm, known()).unwrap(), ["wbe-1"]);
+
+        let none = Selector {
+            patterns: vec!["db-*".to_string()],
+            ..Default::default()
+        };
+        let err = none
+            .select(InstanceType::Container, known())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("No containers match 'db-*'"), "{}", err);
+        assert!(LabelSelector::parse("env").is_err());
+    }
+
+    #[test]
+    fn bulk_report_summarises_failures() {
+        let mut report = BulkReport::default();
+        report.record("web-1", Ok(()));
+        report.record("web-2", Err(NovaError::VmNotFound("web-2".to_string())));
+        report.skip("web-3", "already stopped");
+        let summary = report.render("stopped");
+        // Skipped instances don't count towards the total
+        assert!(summary.contains("1 of 2 stopped"));
+        assert!(summary.contains("❌ web-2: VM 'web-2' not found"));
+        assert!(summary.contains("⏭  web-3: already stopped"));
+        let err = report.into_result("stopped").unwrap_err().to_string();
+        assert!(err.contains("1 of 2 instance(s) were not stopped: web-2"));
+    }
+}
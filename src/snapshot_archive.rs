@@ -0,0 +1,496 @@
+//! Standalone snapshot archives for `nova snapshot export`/`import`.
+//!
+//! An archive is a tar file holding a `manifest.json`, the domain definition
+//! captured with the snapshot, one flattened qcow2 image per snapshotted disk
+//! and, optionally, the external memory state. Internal snapshots are
+//! converted to standalone images with `qemu-img convert -l`; external ones
+//! are flattened from the image their overlay was created on. Every file is
+//! recorded with its size and SHA-256 so imports can be verified before
+//! anything is defined in libvirt.
+
+use crate::{NovaError, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Archive layout version written to the manifest.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+pub const MANIFEST_NAME: &str = "manifest.json";
+pub const DOMAIN_XML_NAME: &str = "domain.xml";
+pub const MEMORY_NAME: &str = "memory.save";
+
+/// A file stored in the archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// Disk target (`vda`) or `memory`
+    pub name: String,
+    /// Path inside the archive
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub vm: String,
+    pub snapshot: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    pub exported_at: DateTime<Utc>,
+    pub disks: Vec<ArchivedFile>,
+    #[serde(default)]
+    pub memory: Option<ArchivedFile>,
+}
+
+impl ArchiveManifest {
+    pub fn files(&self) -> impl Iterator<Item = &ArchivedFile> {
+        self.disks.iter().chain(self.memory.iter())
+    }
+}
+
+/// Result of `nova snapshot export`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotExport {
+    pub archive: PathBuf,
+    pub size_bytes: u64,
+    pub manifest: ArchiveManifest,
+}
+
+/// Where `nova snapshot import` puts the archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportTarget {
+    /// Redefine as a snapshot of this VM (the archived VM when `None`)
+    Snapshot { vm: Option<String> },
+    /// Define a new VM from the archived domain and disks
+    NewVm(String),
+}
+
+/// Result of `nova snapshot import`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotImport {
+    pub vm: String,
+    pub snapshot: String,
+    pub new_vm: bool,
+    pub directory: PathBuf,
+    pub disks: Vec<PathBuf>,
+    pub memory: Option<PathBuf>,
+}
+
+/// How a disk or the memory was captured by a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureKind {
+    Internal,
+    External,
+    No,
+}
+
+impl CaptureKind {
+    fn from_attribute(value: Option<&str>) -> Self {
+        match value {
+            Some("internal") => CaptureKind::Internal,
+            Some("external") => CaptureKind::External,
+            _ => CaptureKind::No,
+        }
+    }
+}
+
+/// A disk captured by a snapshot, with the image holding its state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDisk {
+    pub target: String,
+    pub kind: CaptureKind,
+    /// Image as it was when the snapshot was taken: the image with the
+    /// internal snapshot, or the one an external overlay was created on
+    pub image: PathBuf,
+}
+
+/// Parts of `virsh snapshot-dumpxml` output needed to export a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotLayout {
+    pub description: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub memory: CaptureKind,
+    pub memory_file: Option<PathBuf>,
+    pub disks: Vec<SnapshotDisk>,
+    pub domain_xml: String,
+}
+
+/// Read a snapshot definition. Disks the snapshot did not capture (CD-ROMs,
+/// `snapshot='no'`) are left out.
+pub fn parse_snapshot_xml(xml: &str) -> Result<SnapshotLayout> {
+    let domain = Regex::new(r"(?s)<domain\b.*</domain>").expect("valid domain pattern");
+    let Some(domain_xml) = domain.find(xml).map(|m| m.as_str().to_string()) else {
+        return Err(NovaError::ConfigError(
+            "Snapshot definition has no <domain> element".to_string(),
+        ));
+    };
+
+    let text = |tag: &str| {
+        Regex::new(&format!(r"(?s)<{tag}>(.*?)</{tag}>"))
+            .expect("valid element pattern")
+            .captures(xml)
+            .map(|caps| unescape(caps[1].trim()))
+    };
+    let memory_tag = Regex::new(r"<memory\b[^>]*snapshot=[^>]*>")
+        .expect("valid memory pattern")
+        .find(xml)
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_default();
+
+    let disks_block = Regex::new(r"(?s)<disks>(.*?)</disks>")
+        .expect("valid disks pattern")
+        .captures(xml)
+        .map(|caps| caps[1].to_string())
+        .unwrap_or_default();
+    let disk_entry = Regex::new(r"<disk\b[^>]*>").expect("valid disk pattern");
+    let images = domain_disks(&domain_xml);
+
+    let mut disks = Vec::new();
+    for entry in disk_entry.find_iter(&disks_block) {
+        let entry = entry.as_str();
+        let Some(target) = attribute(entry, "name") else {
+            continue;
+        };
+        let kind = CaptureKind::from_attribute(attribute(entry, "snapshot").as_deref());
+        if kind == CaptureKind::No {
+            continue;
+        }
+        let image = images
+            .iter()
+            .find(|(dev, _)| *dev == target)
+            .map(|(_, image)| image.clone())
+            .ok_or_else(|| {
+                NovaError::ConfigError(format!(
+                    "Snapshot disk '{}' has no image in the captured domain",
+                    target
+                ))
+            })?;
+        disks.push(SnapshotDisk {
+            target,
+            kind,
+            image,
+        });
+    }
+
+    Ok(SnapshotLayout {
+        description: text("description").unwrap_or_default(),
+        created_at: text("creationTime")
+            .and_then(|seconds| seconds.parse().ok())
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+        memory: CaptureKind::from_attribute(attribute(&memory_tag, "snapshot").as_deref()),
+        memory_file: attribute(&memory_tag, "file").map(PathBuf::from),
+        disks,
+        domain_xml,
+    })
+}
+
+/// `(target, image)` of each disk device in a domain definition.
+pub fn domain_disks(domain_xml: &str) -> Vec<(String, PathBuf)> {
+    let disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+    let device = Regex::new(r"<disk\b[^>]*>").expect("valid disk tag pattern");
+    let source = Regex::new(r"<source\b[^>]*>").expect("valid source pattern");
+    let target = Regex::new(r"<target\b[^>]*>").expect("valid target pattern");
+
+    disk.find_iter(domain_xml)
+        .filter_map(|block| {
+            let block = block.as_str();
+            let tag = device.find(block)?.as_str();
+            if attribute(tag, "device").is_some_and(|device| device != "disk") {
+                return None;
+            }
+            let source = source.find(block)?.as_str();
+            let image = attribute(source, "file").or_else(|| attribute(source, "dev"))?;
+            let dev = attribute(target.find(block)?.as_str(), "dev")?;
+            Some((dev, PathBuf::from(image)))
+        })
+        .collect()
+}
+
+/// Point each listed disk at a standalone qcow2 image, dropping backing
+/// chains that no longer apply.
+pub fn retarget_disks(domain_xml: &str, images: &[(String, PathBuf)]) -> String {
+    let disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+    let target = Regex::new(r"<target\b[^>]*>").expect("valid target pattern");
+    let disk_type = Regex::new(r#"^<disk\b([^>]*?)\btype=(?:'[^']*'|"[^"]*")"#)
+        .expect("valid disk type pattern");
+    let source = Regex::new(r"<source\b[^>]*?/?>").expect("valid source pattern");
+    let driver = Regex::new(r"<driver\b[^>]*?/?>").expect("valid driver pattern");
+    // Greedy, so nested backing chains go with the outermost element
+    let backing = Regex::new(r"(?s)\s*<backingStore\b(?:[^>]*/>|.*</backingStore>)")
+        .expect("valid backing pattern");
+
+    disk.replace_all(domain_xml, |caps: &regex::Captures| {
+        let block = &caps[0];
+        let Some(image) = target
+            .find(block)
+            .and_then(|tag| attribute(tag.as_str(), "dev"))
+            .and_then(|dev| images.iter().find(|(target, _)| *target == dev))
+            .map(|(_, image)| image)
+        else {
+            return block.to_string();
+        };
+        let image = escape(&image.to_string_lossy());
+        let block = disk_type.replace(block, "<disk${1}type='file'");
+        let block = backing.replace_all(&block, "");
+        let block = source.replace(&block, format!("<source file='{}'/>", image).as_str());
+        driver
+            .replace(&block, "<driver name='qemu' type='qcow2'/>")
+            .into_owned()
+    })
+    .into_owned()
+}
+
+/// Give a domain definition a new name, dropping its UUID and MAC addresses
+/// so libvirt generates fresh ones.
+pub fn rename_domain(domain_xml: &str, name: &str) -> String {
+    let name_tag = Regex::new(r"<name>[^<]*</name>").expect("valid name pattern");
+    let uuid = Regex::new(r"\s*<uuid>[^<]*</uuid>").expect("valid uuid pattern");
+    let mac = Regex::new(r"\s*<mac address=[^>]*/>").expect("valid mac pattern");
+
+    let xml = name_tag.replace(
+        domain_xml,
+        format!("<name>{}</name>", escape(name)).as_str(),
+    );
+    let xml = uuid.replace_all(&xml, "");
+    mac.replace_all(&xml, "").into_owned()
+}
+
+/// `virsh snapshot-create --redefine` definition for an imported restore
+/// point: an external disk-only snapshot whose overlays sit on the imported
+/// images.
+pub fn redefine_xml(
+    manifest: &ArchiveManifest,
+    snapshot: &str,
+    overlays: &[(String, PathBuf)],
+    domain_xml: &str,
+) -> String {
+    let mut xml = String::from("<domainsnapshot>\n");
+    xml.push_str(&format!("  <name>{}</name>\n", escape(snapshot)));
+    if !manifest.description.is_empty() {
+        xml.push_str(&format!(
+            "  <description>{}</description>\n",
+            escape(&manifest.description)
+        ));
+    }
+    xml.push_str("  <state>shutoff</state>\n");
+    if let Some(created_at) = manifest.created_at {
+        xml.push_str(&format!(
+            "  <creationTime>{}</creationTime>\n",
+            created_at.timestamp()
+        ));
+    }
+    xml.push_str("  <memory snapshot='no'/>\n  <disks>\n");
+    for (target, overlay) in overlays {
+        xml.push_str(&format!(
+            "    <disk name='{}' snapshot='external' type='file'>\n      <driver type='qcow2'/>\n      <source file='{}'/>\n    </disk>\n",
+            escape(target),
+            escape(&overlay.to_string_lossy())
+        ));
+    }
+    xml.push_str("  </disks>\n");
+    xml.push_str(domain_xml.trim());
+    xml.push_str("\n</domainsnapshot>\n");
+    xml
+}
+
+/// SHA-256 of a file, via `sha256sum`.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let output = Command::new("sha256sum").arg(path).output()?;
+    if !output.status.success() {
+        return Err(NovaError::SystemCommandFailed);
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or(NovaError::SystemCommandFailed)
+}
+
+/// Describe a file for the manifest.
+pub fn archived_file(name: &str, archive_path: &str, file: &Path) -> Result<ArchivedFile> {
+    Ok(ArchivedFile {
+        name: name.to_string(),
+        path: archive_path.to_string(),
+        size_bytes: std::fs::metadata(file)?.len(),
+        sha256: sha256_file(file)?,
+    })
+}
+
+/// Check an extracted file against its manifest entry.
+pub fn verify_file(entry: &ArchivedFile, file: &Path) -> Result<()> {
+    let size = std::fs::metadata(file)
+        .map_err(|_| NovaError::ConfigError(format!("Archive is missing '{}'", entry.path)))?
+        .len();
+    if size != entry.size_bytes {
+        return Err(NovaError::ConfigError(format!(
+            "'{}' is {} bytes, the manifest says {}",
+            entry.path, size, entry.size_bytes
+        )));
+    }
+    if sha256_file(file)? != entry.sha256 {
+        return Err(NovaError::ConfigError(format!(
+            "'{}' does not match its recorded SHA-256",
+            entry.path
+        )));
+    }
+    Ok(())
+}
+
+/// Structural check of a qcow2 image with `qemu-img check`.
+pub fn check_image(image: &Path) -> Result<()> {
+    check_image_as(image, "qcow2")
+}
+
+/// `qemu-img check` for an image in `format`.
+pub fn check_image_as(image: &Path, format: &str) -> Result<()> {
+    let output = Command::new("qemu-img")
+        .args(["check", "-q", "-f", format])
+        .arg(image)
+        .output()?;
+    if !output.status.success() {
+        return Err(NovaError::ConfigError(format!(
+            "qemu-img check failed for {}: {}",
+            image.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    Regex::new(&format!(r#"\b{}=(?:'([^']*)'|"([^"]*)")"#, name))
+        .expect("valid attribute pattern")
+        .captures(tag)
+        .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|value| unescape(value.as_str()))
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&apos;", "'")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = "<domainsnapshot>
+  <name>pre-upgrade</name>
+  <description>Before &amp; after</description>
+  <state>running</state>
+  <creationTime>1790000000</creationTime>
+  <memory snapshot='external' file='/var/lib/nova/snapshots/web/1.mem'/>
+  <disks>
+    <disk name='vda' snapshot='external' type='file'>
+      <driver type='qcow2'/>
+      <source file='/var/lib/nova/snapshots/web/1.qcow2'/>
+    </disk>
+    <disk name='sda' snapshot='no'/>
+  </disks>
+  <domain type='kvm'>
+    <name>web</name>
+    <uuid>6f1c2c0e-1111-2222-3333-444455556666</uuid>
+    <devices>
+      <disk type='file' device='disk'>
+        <driver name='qemu' type='qcow2'/>
+        <source file='/var/lib/libvirt/images/web.qcow2'/>
+        <backingStore type='file'>
+          <format type='qcow2'/>
+          <source file='/var/lib/libvirt/images/base.qcow2'/>
+        </backingStore>
+        <target dev='vda' bus='virtio'/>
+      </disk>
+      <disk type='file' device='cdrom'>
+        <source file='/isos/ubuntu.iso'/>
+        <target dev='sda' bus='sata'/>
+      </disk>
+      <interface type='bridge'>
+        <mac address='52:54:00:12:34:56'/>
+        <source bridge='br0'/>
+      </interface>
+    </devices>
+  </domain>
+</domainsnapshot>";
+
+    #[test]
+    fn snapshot_definition_round_trips_into_an_import() {
+        let layout = parse_snapshot_xml(SNAPSHOT).unwrap();
+        assert_eq!(layout.description, "Before & after");
+        assert_eq!(
+            layout.created_at,
+            DateTime::from_timestamp(1_790_000_000, 0)
+        );
+        assert_eq!(layout.memory, CaptureKind::External);
+        assert_eq!(
+            layout.memory_file.as_deref(),
+            Some(Path::new("/var/lib/nova/snapshots/web/1.mem"))
+        );
+        // The CD-ROM was not captured; vda's state is the pre-overlay image
+        assert_eq!(
+            layout.disks,
+            vec![SnapshotDisk {
+                target: "vda".to_string(),
+                kind: CaptureKind::External,
+                image: PathBuf::from("/var/lib/libvirt/images/web.qcow2"),
+            }]
+        );
+
+        let imported = PathBuf::from("/var/lib/libvirt/images/web-restore/vda.qcow2");
+        let xml = rename_domain(
+            &retarget_disks(&layout.domain_xml, &[("vda".to_string(), imported)]),
+            "web-restore",
+        );
+        assert!(xml.contains("<name>web-restore</name>"));
+        assert!(!xml.contains("<uuid>") && !xml.contains("52:54:00"));
+        assert!(xml.contains("<source file='/var/lib/libvirt/images/web-restore/vda.qcow2'/>"));
+        assert!(!xml.contains("backingStore"));
+        assert!(xml.contains("/isos/ubuntu.iso"));
+        assert_eq!(
+            domain_disks(&xml),
+            vec![(
+                "vda".to_string(),
+                PathBuf::from("/var/lib/libvirt/images/web-restore/vda.qcow2")
+            )]
+        );
+
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            vm: "web".to_string(),
+            snapshot: "pre-upgrade".to_string(),
+            description: layout.description.clone(),
+            created_at: layout.created_at,
+            exported_at: Utc::now(),
+            disks: Vec::new(),
+            memory: None,
+        };
+        let redefine = redefine_xml(
+            &manifest,
+            "pre-upgrade",
+            &[("vda".to_string(), PathBuf::from("/tmp/vda.overlay.qcow2"))],
+            &xml,
+        );
+        let reparsed = parse_snapshot_xml(&redefine).unwrap();
+        assert_eq!(reparsed.description, "Before & after");
+        assert_eq!(reparsed.memory, CaptureKind::No);
+        assert_eq!(reparsed.disks.len(), 1);
+        assert!(parse_snapshot_xml("<domainsnapshot/>").is_err());
+    }
+}
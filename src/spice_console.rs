@@ -1,9 +1,50 @@
+use crate::exec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::Write;
 use std::net::{IpAddr, Ipv4Addr};
-use std::path::PathBuf;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Shown when no SPICE viewer is found in PATH.
+pub const NO_CLIENT_MESSAGE: &str = "No SPICE client found (looked for remote-viewer, spicy and \
+     virt-viewer in PATH). Install one: virt-viewer (Arch, Fedora, Debian/Ubuntu) provides \
+     remote-viewer and virt-viewer; spicy comes with spice-gtk (Arch), spice-gtk-tools (Fedora) \
+     or spice-client-gtk (Debian/Ubuntu). On a headless host, run `nova spice connect <vm> \
+     --print-uri` and open the URI from another machine.";
+
+/// Shown instead of passing a SPICE password on spicy's command line, where
+/// every local user can read it.
+pub const SPICY_PASSWORD_MESSAGE: &str = "spicy only takes a password on its command line, \
+     where other users can read it. Install remote-viewer (virt-viewer package), which reads it \
+     from a private connection file, or run `nova spice connect <vm> --print-uri`.";
+
+/// Graphical SPICE viewers Nova can launch, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpiceClient {
+    RemoteViewer,
+    Spicy,
+    VirtViewer,
+}
+
+impl SpiceClient {
+    pub const ALL: [SpiceClient; 3] = [
+        SpiceClient::RemoteViewer,
+        SpiceClient::Spicy,
+        SpiceClient::VirtViewer,
+    ];
+
+    pub fn binary(&self) -> &'static str {
+        match self {
+            SpiceClient::RemoteViewer => "remote-viewer",
+            SpiceClient::Spicy => "spicy",
+            SpiceClient::VirtViewer => "virt-viewer",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpiceConfig {
     pub enabled: bool,
@@ -286,14 +327,18 @@ impl SpiceManager {
     pub async fn apply_config(&mut self, vm_name: &str) -> Result<(), String> {
         let xml = self.generate_graphics_xml(vm_name)?;
 
-        // Write XML to temp file
-        let temp_file = format!("/tmp/spice-{}.xml", vm_name);
-        std::fs::write(&temp_file, xml).map_err(|e| format!("Failed to write XML: {}", e))?;
+        // Write XML to a private temp file, removed when it goes out of scope
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(&format!("spice-{}-", vm_name))
+            .suffix(".xml")
+            .tempfile()
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        temp_file
+            .write_all(xml.as_bytes())
+            .map_err(|e| format!("Failed to write XML: {}", e))?;
 
         // Update VM definition
-        let output = Command::new("virsh")
-            .args(["define", &temp_file])
-            .output()
+        let output = exec::run(Command::new("virsh").arg("define").arg(temp_file.path()))
             .map_err(|e| format!("Failed to execute virsh: {}", e))?;
 
         if !output.status.success() {
@@ -303,9 +348,6 @@ impl SpiceManager {
             ));
         }
 
-        // Clean up temp file
-        let _ = std::fs::remove_file(&temp_file);
-
         Ok(())
     }
 
@@ -373,26 +415,54 @@ impl SpiceManager {
             .get(vm_name)
             .ok_or_else(|| format!("No active SPICE connection for VM '{}'", vm_name))?;
 
-        // Check if remote-viewer is installed
-        if !self.is_client_installed() {
-            return Err("SPICE client (remote-viewer) not installed. Install with: sudo pacman -S virt-viewer".to_string());
+        let client = Self::detect_client().ok_or_else(|| NO_CLIENT_MESSAGE.to_string())?;
+        if client == SpiceClient::Spicy && info.password.is_some() {
+            return Err(SPICY_PASSWORD_MESSAGE.to_string());
         }
 
-        // Generate .vv file for remote-viewer
-        let vv_content = self.generate_vv_file(info)?;
-        let vv_path = format!("/tmp/{}.vv", vm_name);
-        std::fs::write(&vv_path, vv_content)
-            .map_err(|e| format!("Failed to write .vv file: {}", e))?;
+        let mut cmd = Command::new(client.binary());
+        match client {
+            SpiceClient::RemoteViewer => {
+                // Generate .vv file for remote-viewer
+                let vv_content = self.generate_vv_file(info)?;
+                let vv_path = std::env::temp_dir().join(format!("nova-{}.vv", vm_name));
+                write_private_file(&vv_path, &vv_content)
+                    .map_err(|e| format!("Failed to write .vv file: {}", e))?;
+                cmd.arg(&vv_path);
+            }
+            SpiceClient::Spicy => {
+                cmd.args(["-h", &info.host, "-p", &info.port.to_string()]);
+            }
+            SpiceClient::VirtViewer => {
+                // virt-viewer looks the display up through libvirt itself
+                cmd.args(["--connect", "qemu:///system", vm_name]);
+            }
+        }
 
-        // Launch remote-viewer in background
-        Command::new("remote-viewer")
-            .arg(&vv_path)
-            .spawn()
-            .map_err(|e| format!("Failed to launch remote-viewer: {}", e))?;
+        // Launch the viewer in background
+        cmd.spawn()
+            .map_err(|e| format!("Failed to launch {}: {}", client.binary(), e))?;
 
         Ok(())
     }
 
+    /// First SPICE viewer found in PATH.
+    pub fn detect_client() -> Option<SpiceClient> {
+        Self::detect_client_in(&std::env::var_os("PATH").unwrap_or_default())
+    }
+
+    /// First SPICE viewer found in the directories of a PATH-style list.
+    pub fn detect_client_in(path: &OsStr) -> Option<SpiceClient> {
+        let dirs: Vec<PathBuf> = std::env::split_paths(path).collect();
+        SpiceClient::ALL.into_iter().find(|client| {
+            dirs.iter().any(|dir| {
+                std::fs::metadata(dir.join(client.binary()))
+                    .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                    .unwrap_or(false)
+            })
+        })
+    }
+
     /// Generate .vv file for remote-viewer
     fn generate_vv_file(&self, info: &SpiceConnectionInfo) -> Result<String, String> {
         let mut content = String::new();
@@ -418,17 +488,15 @@ impl SpiceManager {
         content.push_str("usb-filter=-1,-1,-1,-1,0\n");
         content.push_str("secure-attention=ctrl+alt+end\n");
         content.push_str("release-cursor=shift+f12\n");
+        // The viewer removes the file, and the password in it, once read
+        content.push_str("delete-this-file=1\n");
 
         Ok(content)
     }
 
     /// Check if SPICE client is installed
     pub fn is_client_installed(&self) -> bool {
-        Command::new("which")
-            .arg("remote-viewer")
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+        Self::detect_client().is_some()
     }
 
     /// Install SPICE client on Arch Linux
@@ -452,12 +520,9 @@ impl SpiceManager {
     pub fn check_requirements(&self) -> Vec<String> {
         let mut issues = Vec::new();
 
-        // Check for remote-viewer
+        // Check for a graphical viewer
         if !self.is_client_installed() {
-            issues.push(
-                "SPICE client (remote-viewer) not installed. Install: sudo pacman -S virt-viewer"
-                    .to_string(),
-            );
+            issues.push(NO_CLIENT_MESSAGE.to_string());
         }
 
         // Check for spice-vdagent (guest agent)
@@ -594,6 +659,22 @@ impl Default for SpiceManager {
     }
 }
 
+/// Write `contents` readable by the owner only. An existing file is replaced
+/// rather than reused, so neither its permissions nor a symlink planted in
+/// its place carry over.
+pub fn write_private_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -656,5 +737,46 @@ mod tests {
         assert!(vv.contains("host=localhost"));
         assert!(vv.contains("port=5900"));
         assert!(vv.contains("password=secret"));
+        assert!(vv.contains("delete-this-file=1"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test-vm.vv");
+        std::fs::write(&path, "stale").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_private_file(&path, &vv).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), vv);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_client_detection() {
+        let empty = tempfile::tempdir().unwrap();
+        let bin = tempfile::tempdir().unwrap();
+        let install = |name: &str, mode: u32| {
+            let path = bin.path().join(name);
+            std::fs::write(&path, "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        let path = std::env::join_paths([empty.path(), bin.path()]).unwrap();
+
+        assert_eq!(SpiceManager::detect_client_in(&path), None);
+        // Not executable, so not a client
+        install("remote-viewer", 0o644);
+        install("virt-viewer", 0o755);
+        assert_eq!(
+            SpiceManager::detect_client_in(&path),
+            Some(SpiceClient::VirtViewer)
+        );
+        install("spicy", 0o755);
+        assert_eq!(
+            SpiceManager::detect_client_in(&path),
+            Some(SpiceClient::Spicy)
+        );
+        install("remote-viewer", 0o755);
+        assert_eq!(
+            SpiceManager::detect_client_in(&path),
+            Some(SpiceClient::RemoteViewer)
+        );
     }
 }
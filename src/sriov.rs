@@ -5,8 +5,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 // Removed unused import: use std::process::Command;
 
+const PCI_DEVICES_PATH: &str = "/sys/bus/pci/devices";
+
+/// How long `enable_sriov` waits for the kernel to create the VF devices.
+pub const DEFAULT_VF_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SriovDevice {
     pub pf_address: String, // Physical Function address (e.g., "0000:01:00.0")
@@ -43,6 +49,8 @@ pub struct VirtualFunction {
 pub struct SriovManager {
     devices: HashMap<String, SriovDevice>,
     vf_assignments: HashMap<String, String>, // VF address -> VM name
+    pci_devices_path: PathBuf,
+    vf_timeout: Duration,
 }
 
 impl Default for SriovManager {
@@ -56,21 +64,34 @@ impl SriovManager {
         Self {
             devices: HashMap::new(),
             vf_assignments: HashMap::new(),
+            pci_devices_path: PathBuf::from(PCI_DEVICES_PATH),
+            vf_timeout: DEFAULT_VF_TIMEOUT,
         }
     }
 
+    /// Read PCI devices from `path` instead of `/sys/bus/pci/devices`.
+    pub fn with_pci_devices_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pci_devices_path = path.into();
+        self
+    }
+
+    /// How long [`Self::enable_sriov`] waits for the VFs to appear.
+    pub fn set_vf_timeout(&mut self, timeout: Duration) {
+        self.vf_timeout = timeout;
+    }
+
     /// Discover SR-IOV capable devices
     pub fn discover_sriov_devices(&mut self) -> Result<Vec<SriovDevice>, String> {
         let mut devices = Vec::new();
 
         // Scan /sys/bus/pci/devices for SR-IOV capable devices
-        let pci_devices_path = Path::new("/sys/bus/pci/devices");
+        let pci_devices_path = self.pci_devices_path.clone();
 
         if !pci_devices_path.exists() {
             return Err("PCI devices path not found".to_string());
         }
 
-        for entry in fs::read_dir(pci_devices_path)
+        for entry in fs::read_dir(&pci_devices_path)
             .map_err(|e| format!("Failed to read PCI devices: {}", e))?
         {
             let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
@@ -172,7 +193,8 @@ impl SriovManager {
         println!("Enabling SR-IOV on {} with {} VFs", pf_address, num_vfs);
 
         // Write to sriov_numvfs sysfs file
-        let sysfs_path = format!("/sys/bus/pci/devices/{}/sriov_numvfs", pf_address);
+        let pf_path = self.pci_devices_path.join(pf_address);
+        let sysfs_path = pf_path.join("sriov_numvfs");
 
         // First disable any existing VFs
         fs::write(&sysfs_path, "0")
@@ -182,11 +204,21 @@ impl SriovManager {
         fs::write(&sysfs_path, num_vfs.to_string())
             .map_err(|e| format!("Failed to enable VFs: {}. Try running with sudo.", e))?;
 
-        // Wait for VFs to initialize
-        std::thread::sleep(std::time::Duration::from_millis(500));
+        // Some drivers create the VF devices after the write returns
+        Self::wait_for_vfs(&pf_path, num_vfs, self.vf_timeout)?;
 
         // Refresh device state
         self.refresh_device(pf_address)?;
+        let created = self
+            .devices
+            .get(pf_address)
+            .map_or(0, |device| device.vf_list.len());
+        if created != num_vfs as usize {
+            return Err(format!(
+                "Expected {} VFs on {} but found {}",
+                num_vfs, pf_address, created
+            ));
+        }
 
         println!("✅ SR-IOV enabled: {} VFs created", num_vfs);
         Ok(())
@@ -196,7 +228,7 @@ impl SriovManager {
     pub fn disable_sriov(&mut self, pf_address: &str) -> Result<(), String> {
         println!("Disabling SR-IOV on {}", pf_address);
 
-        let sysfs_path = format!("/sys/bus/pci/devices/{}/sriov_numvfs", pf_address);
+        let sysfs_path = self.pci_devices_path.join(pf_address).join("sriov_numvfs");
 
         fs::write(&sysfs_path, "0").map_err(|e| format!("Failed to disable VFs: {}", e))?;
 
@@ -211,6 +243,30 @@ impl SriovManager {
         Ok(())
     }
 
+    /// Poll until `virtfn0` .. `virtfn{num_vfs - 1}` all exist under the PF.
+    fn wait_for_vfs(pf_path: &Path, num_vfs: u32, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let present = (0..num_vfs)
+                .filter(|index| pf_path.join(format!("virtfn{}", index)).exists())
+                .count();
+            if present == num_vfs as usize {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Only {} of {} VFs appeared under {} within {:.1}s; check `dmesg` for \
+                     driver errors",
+                    present,
+                    num_vfs,
+                    pf_path.display(),
+                    timeout.as_secs_f64()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
     /// Enumerate Virtual Functions
     fn enumerate_virtual_functions(
         &self,
@@ -380,7 +436,7 @@ impl SriovManager {
 
     /// Refresh device state
     fn refresh_device(&mut self, pf_address: &str) -> Result<(), String> {
-        let device_path = PathBuf::from(format!("/sys/bus/pci/devices/{}", pf_address));
+        let device_path = self.pci_devices_path.join(pf_address);
 
         let max_vfs = Self::read_sysfs_file(&device_path.join("sriov_totalvfs"))?
             .trim()
@@ -545,4 +601,49 @@ mod tests {
         assert!(xml.contains("slot='0x00'"));
         assert!(xml.contains("function='0x1'"));
     }
+
+    #[test]
+    fn test_enable_waits_for_vfs() {
+        let sysfs = tempfile::tempdir().unwrap();
+        let pf = "0000:01:00.0";
+        let pf_path = sysfs.path().join(pf);
+        fs::create_dir(&pf_path).unwrap();
+        fs::write(pf_path.join("vendor"), "0x8086\n").unwrap();
+        fs::write(pf_path.join("device"), "0x1521\n").unwrap();
+        fs::write(pf_path.join("sriov_totalvfs"), "7\n").unwrap();
+        fs::write(pf_path.join("sriov_numvfs"), "0\n").unwrap();
+
+        let mut manager = SriovManager::new().with_pci_devices_path(sysfs.path());
+        manager.discover_sriov_devices().unwrap();
+        manager.set_vf_timeout(Duration::from_millis(200));
+
+        // No VF devices ever show up
+        let err = manager.enable_sriov(pf, 2).unwrap_err();
+        assert!(err.contains("Only 0 of 2 VFs"), "{}", err);
+
+        // VFs that appear after the write are picked up
+        let creator_path = pf_path.clone();
+        let creator = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            for index in 0..2 {
+                let vf = creator_path.with_file_name(format!("0000:01:10.{}", index));
+                fs::create_dir(&vf).unwrap();
+                std::os::unix::fs::symlink(&vf, creator_path.join(format!("virtfn{}", index)))
+                    .unwrap();
+            }
+        });
+        manager.set_vf_timeout(Duration::from_secs(5));
+        manager.enable_sriov(pf, 2).unwrap();
+        creator.join().unwrap();
+
+        let device = manager.get_device(pf).unwrap();
+        assert_eq!(device.current_vfs, 2);
+        assert!(device.sriov_enabled);
+        let addresses: Vec<&str> = device
+            .vf_list
+            .iter()
+            .map(|vf| vf.vf_address.as_str())
+            .collect();
+        assert_eq!(addresses, vec!["0000:01:10.0", "0000:01:10.1"]);
+    }
 }
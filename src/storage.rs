@@ -1,6 +1,11 @@
 use crate::{
     NovaError, Result,
-    config::{NovaConfig, StoragePoolConfig},
+    config::{NovaConfig, StoragePoolConfig, StoragePoolType},
+    storage_backend::{self, ZfsBackend, parse_zfs_compression},
+    storage_pool::{
+        PathOverlap, PoolConfig, PoolIssue, PoolProblem, PoolState, PoolType, StoragePool,
+        path_overlap,
+    },
 };
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -78,13 +83,94 @@ impl StorageManager {
                 name
             )));
         }
+        self.check_overlap(name, &pool.directory)?;
 
         let normalized = Self::normalize_pool_config(&pool)?;
+        if normalized.pool_type == StoragePoolType::Zfs {
+            // Dataset properties and the libvirt pool the volume commands use
+            storage_backend::backend_for(&PoolType::Zfs)?
+                .create_pool(&Self::zfs_storage_pool(name, &normalized)?)?;
+        }
 
         self.config.storage.insert(name.to_string(), normalized);
         self.save()
     }
 
+    /// The libvirt-side description of a NovaFile ZFS pool.
+    fn zfs_storage_pool(name: &str, pool: &StoragePoolConfig) -> Result<StoragePool> {
+        let zfs = pool.zfs.as_ref().ok_or_else(|| {
+            NovaError::ConfigError("ZFS pools require a dataset (e.g. tank/nova)".to_string())
+        })?;
+        let compression = parse_zfs_compression(&zfs.compression).ok_or_else(|| {
+            NovaError::ConfigError(format!("Unsupported ZFS compression '{}'", zfs.compression))
+        })?;
+        Ok(StoragePool {
+            name: name.to_string(),
+            pool_type: PoolType::Zfs,
+            path: Path::new("/dev/zvol").join(&zfs.dataset),
+            state: PoolState::Inactive,
+            capacity: None,
+            autostart: true,
+            config: PoolConfig::Zfs {
+                dataset: zfs.dataset.clone(),
+                compression,
+                dedup: zfs.dedup,
+            },
+            uuid: uuid::Uuid::new_v4().to_string(),
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    /// Overlapping directories among the NovaFile's pool definitions, each
+    /// pair reported once.
+    pub fn overlapping_pools(&self) -> Vec<PoolIssue> {
+        let pools = self.list_pools();
+        let mut issues = Vec::new();
+        for (index, (name, pool)) in pools.iter().enumerate() {
+            for (other, other_pool) in &pools[index + 1..] {
+                let path = Path::new(&pool.directory);
+                let other_path = Path::new(&other_pool.directory);
+                if let Some(overlap) = path_overlap(path, other_path) {
+                    issues.push(PoolIssue {
+                        pool: name.clone(),
+                        path: path.to_path_buf(),
+                        problem: PoolProblem::Overlap {
+                            other: other.clone(),
+                            overlap,
+                            other_path: other_path.to_path_buf(),
+                        },
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Pools sharing or nesting directories double-count capacity, and
+    /// deleting one with its directory removes the other's volumes.
+    fn check_overlap(&self, name: &str, directory: &str) -> Result<()> {
+        let directory = Path::new(directory);
+        for (other, pool) in &self.config.storage {
+            if other == name {
+                continue;
+            }
+            let relation = match path_overlap(directory, Path::new(&pool.directory)) {
+                None => continue,
+                Some(PathOverlap::Same) => "is the same directory as",
+                Some(PathOverlap::Inside) => "is nested inside",
+                Some(PathOverlap::Contains) => "contains",
+            };
+            return Err(NovaError::ConfigError(format!(
+                "Pool directory {} {} pool '{}' ({})",
+                directory.display(),
+                relation,
+                other,
+                pool.directory
+            )));
+        }
+        Ok(())
+    }
+
     /// Update an existing storage pool definition.
     pub fn update_pool(&mut self, name: &str, pool: StoragePoolConfig) -> Result<()> {
         if !self.config.storage.contains_key(name) {
@@ -93,8 +179,14 @@ impl StorageManager {
                 name
             )));
         }
+        self.check_overlap(name, &pool.directory)?;
 
         let normalized = Self::normalize_pool_config(&pool)?;
+        if let Some(zfs) = &normalized.zfs
+            && normalized.pool_type == StoragePoolType::Zfs
+        {
+            ZfsBackend::verify_dataset(&zfs.dataset)?;
+        }
         self.config.storage.insert(name.to_string(), normalized);
         self.save()
     }
@@ -131,6 +223,22 @@ impl StorageManager {
         Ok(())
     }
 
+    fn validate_zfs_settings(pool: &StoragePoolConfig) -> Result<()> {
+        let zfs = pool.zfs.as_ref().ok_or_else(|| {
+            NovaError::ConfigError("ZFS pools require a dataset (e.g. tank/nova)".to_string())
+        })?;
+
+        if parse_zfs_compression(&zfs.compression).is_none() {
+            return Err(NovaError::ConfigError(format!(
+                "Unsupported ZFS compression '{}'; use off, lz4, gzip-N or zstd-N",
+                zfs.compression
+            )));
+        }
+
+        // The dataset itself is created (or updated) when the pool is created
+        ZfsBackend::ensure_tools()
+    }
+
     fn normalize_pool_config(pool: &StoragePoolConfig) -> Result<StoragePoolConfig> {
         if pool.directory.trim().is_empty() {
             return Err(NovaError::ConfigError(
@@ -138,6 +246,10 @@ impl StorageManager {
             ));
         }
 
+        if pool.pool_type == StoragePoolType::Zfs {
+            Self::validate_zfs_settings(pool)?;
+        }
+
         let mut normalized = pool.clone();
         let path = Path::new(&normalized.directory);
 
@@ -199,4 +311,34 @@ mod tests {
         assert_eq!(removed.directory, pool_cfg.directory);
         assert!(manager.list_pools().is_empty());
     }
+
+    #[test]
+    fn nested_pool_directories_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = StorageManager::load(dir.path().join("NovaFile")).unwrap();
+        let pool = |directory: PathBuf| StoragePoolConfig {
+            directory: directory.to_string_lossy().into_owned(),
+            auto_create: true,
+            ..Default::default()
+        };
+
+        manager
+            .create_pool("vms", pool(dir.path().join("pools/vms")))
+            .unwrap();
+        let nested = manager
+            .create_pool("isos", pool(dir.path().join("pools/vms/isos")))
+            .unwrap_err()
+            .to_string();
+        assert!(nested.contains("nested inside pool 'vms'"), "{}", nested);
+        assert!(
+            manager
+                .create_pool("all", pool(dir.path().join("pools")))
+                .is_err()
+        );
+        // Sibling directories with a common prefix do not overlap
+        manager
+            .create_pool("vms2", pool(dir.path().join("pools/vms2")))
+            .unwrap();
+        assert!(manager.overlapping_pools().is_empty());
+    }
 }
@@ -0,0 +1,935 @@
+//! Per-type storage pool backends
+//!
+//! `StoragePoolManager` keeps the pool/volume cache and dispatches the
+//! type-specific work to a [`StorageBackend`]. Directory, Btrfs and NFS pools
+//! are libvirt-managed and share the default volume handling; LVM drives the
+//! volume group directly and ZFS maps pools to datasets and volumes to zvols.
+//! Adding iSCSI or Ceph means implementing the trait and registering it in
+//! [`backend_for`].
+
+use crate::storage_pool::{
+    BtrfsCompression, PoolCapacity, PoolConfig, PoolType, StoragePool, VolumeFormat, ZfsCompression,
+};
+use crate::{NovaError, Result, exec, log_error, log_info, log_warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Type-specific storage pool operations
+pub trait StorageBackend: Send + Sync {
+    /// Pool type handled by this backend
+    fn pool_type(&self) -> PoolType;
+
+    /// Provision the pool on the host and register it with libvirt
+    fn create_pool(&self, pool: &StoragePool) -> Result<()>;
+
+    /// Create a volume and return its path on the host
+    fn create_volume(
+        &self,
+        pool: &StoragePool,
+        name: &str,
+        size_bytes: u64,
+        format: &VolumeFormat,
+    ) -> Result<PathBuf> {
+        let output = exec::run(Command::new("virsh").args([
+            "vol-create-as",
+            &pool.name,
+            name,
+            &size_bytes.to_string(),
+            "--format",
+            volume_format_str(format),
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to create volume: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        libvirt_volume_path(&pool.name, name)
+    }
+
+    /// Remove a volume and its data
+    fn delete_volume(&self, pool: &StoragePool, name: &str) -> Result<()> {
+        let output =
+            exec::run(Command::new("virsh").args(["vol-delete", name, "--pool", &pool.name]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to delete volume: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Copy `source` to a new volume `target` and return its path
+    fn clone_volume(&self, pool: &StoragePool, source: &str, target: &str) -> Result<PathBuf> {
+        let output = exec::run(Command::new("virsh").args([
+            "vol-clone",
+            source,
+            target,
+            "--pool",
+            &pool.name,
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to clone volume: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        libvirt_volume_path(&pool.name, target)
+    }
+
+    /// Current pool capacity
+    fn capacity(&self, pool: &StoragePool) -> PoolCapacity {
+        filesystem_capacity(&pool.path)
+    }
+}
+
+/// Look up the backend for a pool type
+pub fn backend_for(pool_type: &PoolType) -> Result<Box<dyn StorageBackend>> {
+    match pool_type {
+        PoolType::Directory => Ok(Box::new(DirBackend)),
+        PoolType::Btrfs => Ok(Box::new(BtrfsBackend)),
+        PoolType::Nfs => Ok(Box::new(NfsBackend)),
+        PoolType::Lvm => Ok(Box::new(LvmBackend)),
+        PoolType::Zfs => Ok(Box::new(ZfsBackend)),
+        other => Err(NovaError::ConfigError(format!(
+            "Pool type {:?} not yet implemented",
+            other
+        ))),
+    }
+}
+
+/// Local directory-based pool
+pub struct DirBackend;
+
+impl StorageBackend for DirBackend {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Directory
+    }
+
+    fn create_pool(&self, pool: &StoragePool) -> Result<()> {
+        // Create directory if it doesn't exist
+        if !pool.path.exists() {
+            fs::create_dir_all(&pool.path).map_err(|e| {
+                log_error!("Failed to create pool directory: {}", e);
+                NovaError::SystemCommandFailed
+            })?;
+        }
+
+        define_pool(pool, &directory_pool_xml(pool))?;
+
+        // Start the pool
+        let _ = exec::run(Command::new("virsh").args(["pool-start", &pool.name]));
+
+        // Autostart if requested
+        if pool.autostart {
+            let _ = exec::run(Command::new("virsh").args(["pool-autostart", &pool.name]));
+        }
+
+        log_info!("Directory pool {} created successfully", pool.name);
+        Ok(())
+    }
+
+    fn capacity(&self, pool: &StoragePool) -> PoolCapacity {
+        // libvirt has no btrfs pool type, so btrfs pools come back as dir pools
+        btrfs_capacity(&pool.path).unwrap_or_else(|| filesystem_capacity(&pool.path))
+    }
+}
+
+/// Btrfs filesystem pool with optional subvolume and compression
+pub struct BtrfsBackend;
+
+impl StorageBackend for BtrfsBackend {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Btrfs
+    }
+
+    fn create_pool(&self, pool: &StoragePool) -> Result<()> {
+        log_info!("Creating Btrfs pool: {}", pool.name);
+
+        if let PoolConfig::Btrfs {
+            mount_point,
+            subvolume,
+            compression,
+            ..
+        } = &pool.config
+        {
+            // Create btrfs subvolume if specified
+            if let Some(subvol) = subvolume {
+                let subvol_path = mount_point.join(subvol);
+
+                let output = exec::run(Command::new("btrfs").args([
+                    "subvolume",
+                    "create",
+                    subvol_path.to_str().unwrap(),
+                ]))
+                .map_err(|e| {
+                    log_error!("Failed to create btrfs subvolume: {}", e);
+                    NovaError::SystemCommandFailed
+                })?;
+
+                if !output.status.success() {
+                    let err = String::from_utf8_lossy(&output.stderr);
+                    log_error!("btrfs subvolume create failed: {}", err);
+                    return Err(NovaError::SystemCommandFailed);
+                }
+
+                // Set compression
+                let property = match compression {
+                    BtrfsCompression::Zstd { level } => Some(format!("zstd:{}", level)),
+                    BtrfsCompression::Lzo => Some("lzo".to_string()),
+                    BtrfsCompression::Zlib => Some("zlib".to_string()),
+                    BtrfsCompression::None => None,
+                };
+                if let Some(property) = property {
+                    let _ = exec::run(Command::new("btrfs").args([
+                        "property",
+                        "set",
+                        subvol_path.to_str().unwrap(),
+                        "compression",
+                        &property,
+                    ]));
+                }
+
+                log_info!("Btrfs subvolume created: {}", subvol);
+            }
+
+            // Create directory pool at the subvolume path
+            let dir_pool = StoragePool {
+                config: PoolConfig::Directory { permissions: 0o755 },
+                ..pool.clone()
+            };
+
+            DirBackend.create_pool(&dir_pool)?;
+        }
+
+        Ok(())
+    }
+
+    fn capacity(&self, pool: &StoragePool) -> PoolCapacity {
+        btrfs_capacity(&pool.path).unwrap_or_else(|| filesystem_capacity(&pool.path))
+    }
+}
+
+/// NFS-backed libvirt `netfs` pool
+pub struct NfsBackend;
+
+impl StorageBackend for NfsBackend {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Nfs
+    }
+
+    fn create_pool(&self, pool: &StoragePool) -> Result<()> {
+        log_info!("Creating NFS pool: {}", pool.name);
+
+        if let PoolConfig::Nfs {
+            server,
+            export_path,
+            ..
+        } = &pool.config
+        {
+            define_pool(pool, &nfs_pool_xml(pool, server, export_path))?;
+
+            // Start the pool
+            let output = exec::run(Command::new("virsh").args(["pool-start", &pool.name]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+
+            if !output.status.success() {
+                let err = String::from_utf8_lossy(&output.stderr);
+                log_error!("Failed to start NFS pool: {}", err);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// LVM volume group exposed as a libvirt `logical` pool.
+///
+/// Volumes are raw logical volumes managed with `lvcreate`/`lvremove`; libvirt
+/// is refreshed afterwards so `virsh vol-list` stays in sync.
+pub struct LvmBackend;
+
+impl LvmBackend {
+    fn vg_name(pool: &StoragePool) -> Result<&str> {
+        match &pool.config {
+            PoolConfig::Lvm { vg_name, .. } if !vg_name.is_empty() => Ok(vg_name),
+            _ => Err(NovaError::ConfigError(format!(
+                "Pool '{}' has no LVM volume group configured",
+                pool.name
+            ))),
+        }
+    }
+
+    fn volume_group_exists(vg_name: &str) -> bool {
+        Command::new("vgs")
+            .args(["--noheadings", vg_name])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn refresh_libvirt_pool(pool: &StoragePool) {
+        let _ = Command::new("virsh")
+            .args(["pool-refresh", &pool.name])
+            .output();
+    }
+}
+
+impl StorageBackend for LvmBackend {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Lvm
+    }
+
+    fn create_pool(&self, pool: &StoragePool) -> Result<()> {
+        log_info!("Creating LVM pool: {}", pool.name);
+
+        let vg_name = Self::vg_name(pool)?;
+        let pv_devices = match &pool.config {
+            PoolConfig::Lvm { pv_devices, .. } => pv_devices.as_slice(),
+            _ => &[],
+        };
+
+        if !Self::volume_group_exists(vg_name) {
+            if pv_devices.is_empty() {
+                return Err(NovaError::ConfigError(format!(
+                    "Volume group '{}' does not exist and no physical volumes were given",
+                    vg_name
+                )));
+            }
+
+            let output = exec::run(Command::new("vgcreate").arg(vg_name).args(pv_devices))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+
+            if !output.status.success() {
+                let err = String::from_utf8_lossy(&output.stderr);
+                log_error!("vgcreate failed: {}", err);
+                return Err(NovaError::SystemCommandFailed);
+            }
+        }
+
+        define_pool(pool, &logical_pool_xml(&pool.name, vg_name))?;
+
+        let _ = exec::run(Command::new("virsh").args(["pool-start", &pool.name]));
+
+        if pool.autostart {
+            let _ = exec::run(Command::new("virsh").args(["pool-autostart", &pool.name]));
+        }
+
+        log_info!("LVM pool {} created on volume group {}", pool.name, vg_name);
+        Ok(())
+    }
+
+    fn create_volume(
+        &self,
+        pool: &StoragePool,
+        name: &str,
+        size_bytes: u64,
+        format: &VolumeFormat,
+    ) -> Result<PathBuf> {
+        if *format != VolumeFormat::Raw {
+            return Err(NovaError::ConfigError(format!(
+                "LVM pool '{}' only supports raw volumes",
+                pool.name
+            )));
+        }
+
+        let vg_name = Self::vg_name(pool)?;
+        let output = exec::run(Command::new("lvcreate").args([
+            "-y",
+            "-L",
+            &format!("{}b", size_bytes),
+            "-n",
+            name,
+            vg_name,
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("lvcreate failed: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        Self::refresh_libvirt_pool(pool);
+
+        let output = Command::new("lvs")
+            .args([
+                "--noheadings",
+                "-o",
+                "lv_path",
+                &format!("{}/{}", vg_name, name),
+            ])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !path.is_empty() {
+            Ok(PathBuf::from(path))
+        } else {
+            Ok(Path::new("/dev").join(vg_name).join(name))
+        }
+    }
+
+    fn delete_volume(&self, pool: &StoragePool, name: &str) -> Result<()> {
+        let vg_name = Self::vg_name(pool)?;
+        let output =
+            exec::run(Command::new("lvremove").args(["-f", &format!("{}/{}", vg_name, name)]))
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("lvremove failed: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        Self::refresh_libvirt_pool(pool);
+        Ok(())
+    }
+
+    fn capacity(&self, pool: &StoragePool) -> PoolCapacity {
+        let Ok(vg_name) = Self::vg_name(pool) else {
+            return PoolCapacity::empty();
+        };
+
+        Command::new("vgs")
+            .args([
+                "--noheadings",
+                "--units",
+                "b",
+                "--nosuffix",
+                "-o",
+                "vg_size,vg_free",
+                vg_name,
+            ])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_vgs_capacity(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_else(|| {
+                log_warn!("Unable to read capacity for volume group {}", vg_name);
+                PoolCapacity::empty()
+            })
+    }
+}
+
+/// ZFS dataset pool. Volumes are zvols under the dataset, clones are
+/// copy-on-write `zfs clone`s of a snapshot.
+pub struct ZfsBackend;
+
+impl ZfsBackend {
+    /// Fail with a clear message when the ZFS userland is not installed
+    pub fn ensure_tools() -> Result<()> {
+        let available = Command::new("zfs")
+            .arg("version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+
+        if available {
+            Ok(())
+        } else {
+            Err(NovaError::ConfigError(
+                "ZFS tools not found; install the zfs userland (zfsutils) to use ZFS pools"
+                    .to_string(),
+            ))
+        }
+    }
+
+    pub fn dataset_exists(dataset: &str) -> bool {
+        Command::new("zfs")
+            .args(["list", "-H", "-o", "name", dataset])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Check the tools are present and `dataset` already exists
+    pub fn verify_dataset(dataset: &str) -> Result<()> {
+        Self::ensure_tools()?;
+        if !Self::dataset_exists(dataset) {
+            return Err(NovaError::ConfigError(format!(
+                "ZFS dataset '{}' does not exist (check `zfs list`)",
+                dataset
+            )));
+        }
+        Ok(())
+    }
+
+    fn dataset(pool: &StoragePool) -> Result<&str> {
+        match &pool.config {
+            PoolConfig::Zfs { dataset, .. } if !dataset.is_empty() => Ok(dataset),
+            _ => Err(NovaError::ConfigError(format!(
+                "Pool '{}' has no ZFS dataset configured",
+                pool.name
+            ))),
+        }
+    }
+
+    /// Run a `zfs` subcommand that changes datasets (printed under `--dry-run`)
+    fn run_zfs(args: &[&str]) -> Result<String> {
+        let output = exec::run(Command::new("zfs").args(args))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            log_error!(
+                "zfs {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// `zfs` arguments that create `dataset` with the pool's properties, or
+    /// apply them to it when it already exists
+    pub fn dataset_args(
+        dataset: &str,
+        compression: &ZfsCompression,
+        dedup: bool,
+        exists: bool,
+    ) -> Vec<String> {
+        let compression = format!("compression={}", zfs_compression_property(compression));
+        let dedup = format!("dedup={}", if dedup { "on" } else { "off" });
+        if exists {
+            vec!["set".to_string(), compression, dedup, dataset.to_string()]
+        } else {
+            vec![
+                "create".to_string(),
+                "-p".to_string(),
+                "-o".to_string(),
+                compression,
+                "-o".to_string(),
+                dedup,
+                dataset.to_string(),
+            ]
+        }
+    }
+
+    /// Take a snapshot of a zvol and return its full name (`dataset/vol@snap`)
+    pub fn snapshot_volume(
+        &self,
+        pool: &StoragePool,
+        volume: &str,
+        snapshot: &str,
+    ) -> Result<String> {
+        Self::ensure_tools()?;
+        let name = format!("{}/{}@{}", Self::dataset(pool)?, volume, snapshot);
+        Self::run_zfs(&["snapshot", &name])?;
+        log_info!("ZFS snapshot {} created", name);
+        Ok(name)
+    }
+}
+
+impl StorageBackend for ZfsBackend {
+    fn pool_type(&self) -> PoolType {
+        PoolType::Zfs
+    }
+
+    fn create_pool(&self, pool: &StoragePool) -> Result<()> {
+        log_info!("Creating ZFS pool: {}", pool.name);
+        Self::ensure_tools()?;
+
+        let PoolConfig::Zfs {
+            dataset,
+            compression,
+            dedup,
+        } = &pool.config
+        else {
+            return Err(NovaError::ConfigError(format!(
+                "Pool '{}' has no ZFS dataset configured",
+                pool.name
+            )));
+        };
+
+        // The zpool itself must exist; nested datasets are created on demand
+        let zpool = dataset.split('/').next().unwrap_or_default();
+        if !Self::dataset_exists(zpool) {
+            return Err(NovaError::ConfigError(format!(
+                "ZFS pool '{}' does not exist (check `zpool list`)",
+                zpool
+            )));
+        }
+
+        let args = Self::dataset_args(dataset, compression, *dedup, Self::dataset_exists(dataset));
+        let output = exec::run(Command::new("zfs").args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            log_error!(
+                "zfs {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
+        log_info!("ZFS dataset {} ready for pool {}", dataset, pool.name);
+
+        // Registered with libvirt so volume operations can resolve the pool
+        define_pool(pool, &zfs_pool_xml(&pool.name, dataset))?;
+        for mut cmd in pool_start_commands(pool) {
+            let failure = match exec::run(&mut cmd) {
+                Ok(output) if output.status.success() => continue,
+                Ok(output) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                Err(e) => e.to_string(),
+            };
+            log_warn!("{} failed: {}", exec::render(&cmd), failure);
+        }
+
+        log_info!("ZFS pool {} created on dataset {}", pool.name, dataset);
+        Ok(())
+    }
+
+    fn create_volume(
+        &self,
+        pool: &StoragePool,
+        name: &str,
+        size_bytes: u64,
+        format: &VolumeFormat,
+    ) -> Result<PathBuf> {
+        if *format != VolumeFormat::Raw {
+            return Err(NovaError::ConfigError(format!(
+                "ZFS pool '{}' only supports raw volumes (zvols)",
+                pool.name
+            )));
+        }
+        Self::ensure_tools()?;
+
+        let zvol = format!("{}/{}", Self::dataset(pool)?, name);
+        Self::run_zfs(&["create", "-V", &size_bytes.to_string(), &zvol])?;
+        Ok(zvol_device_path(&zvol))
+    }
+
+    fn delete_volume(&self, pool: &StoragePool, name: &str) -> Result<()> {
+        Self::ensure_tools()?;
+        let zvol = format!("{}/{}", Self::dataset(pool)?, name);
+        // -r also removes the volume's snapshots
+        Self::run_zfs(&["destroy", "-r", &zvol])?;
+        Ok(())
+    }
+
+    fn clone_volume(&self, pool: &StoragePool, source: &str, target: &str) -> Result<PathBuf> {
+        let snapshot = self.snapshot_volume(pool, source, &format!("nova-clone-{}", target))?;
+        let clone = format!("{}/{}", Self::dataset(pool)?, target);
+        Self::run_zfs(&["clone", &snapshot, &clone])?;
+        Ok(zvol_device_path(&clone))
+    }
+
+    fn capacity(&self, pool: &StoragePool) -> PoolCapacity {
+        let Ok(dataset) = Self::dataset(pool) else {
+            return PoolCapacity::empty();
+        };
+
+        Command::new("zfs")
+            .args(["list", "-Hp", "-o", "used,avail", dataset])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_zfs_capacity(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_else(|| {
+                log_warn!("Unable to read capacity for ZFS dataset {}", dataset);
+                PoolCapacity::empty()
+            })
+    }
+}
+
+/// Value for the ZFS `compression` property
+pub fn zfs_compression_property(compression: &ZfsCompression) -> String {
+    match compression {
+        ZfsCompression::Off => "off".to_string(),
+        ZfsCompression::Lz4 => "lz4".to_string(),
+        ZfsCompression::Gzip { level } => format!("gzip-{}", level),
+        ZfsCompression::Zstd { level } => format!("zstd-{}", level),
+    }
+}
+
+/// Parse a ZFS `compression` property value
+pub fn parse_zfs_compression(value: &str) -> Option<ZfsCompression> {
+    let value = value.trim().to_lowercase();
+    let level = |prefix: &str, default: u8| -> Option<u8> {
+        match value.strip_prefix(prefix) {
+            Some("") => Some(default),
+            Some(rest) => rest.strip_prefix('-')?.parse().ok(),
+            None => None,
+        }
+    };
+
+    match value.as_str() {
+        "off" => Some(ZfsCompression::Off),
+        "lz4" | "on" => Some(ZfsCompression::Lz4),
+        _ => {
+            if let Some(level) = level("gzip", 6).filter(|l| (1..=9).contains(l)) {
+                Some(ZfsCompression::Gzip { level })
+            } else {
+                level("zstd", 3)
+                    .filter(|l| (1..=19).contains(l))
+                    .map(|level| ZfsCompression::Zstd { level })
+            }
+        }
+    }
+}
+
+/// Parse `zfs list -Hp -o used,avail` output
+pub fn parse_zfs_capacity(output: &str) -> Option<PoolCapacity> {
+    let mut fields = output.split_whitespace();
+    let used: u64 = fields.next()?.parse().ok()?;
+    let available: u64 = fields.next()?.parse().ok()?;
+
+    Some(PoolCapacity {
+        total_bytes: used + available,
+        used_bytes: used,
+        available_bytes: available,
+        allocation_bytes: used,
+    })
+}
+
+fn zvol_device_path(zvol: &str) -> PathBuf {
+    Path::new("/dev/zvol").join(zvol)
+}
+
+/// Parse `vgs --units b --nosuffix -o vg_size,vg_free` output
+pub fn parse_vgs_capacity(output: &str) -> Option<PoolCapacity> {
+    let mut fields = output.split_whitespace();
+    let total: u64 = fields.next()?.parse().ok()?;
+    let free: u64 = fields.next()?.parse().ok()?;
+    let used = total.saturating_sub(free);
+
+    Some(PoolCapacity {
+        total_bytes: total,
+        used_bytes: used,
+        available_bytes: free,
+        allocation_bytes: used,
+    })
+}
+
+/// Current capacity of `pool` from its backend, falling back to df
+pub fn pool_capacity(pool: &StoragePool) -> PoolCapacity {
+    match backend_for(&pool.pool_type) {
+        Ok(backend) => backend.capacity(pool),
+        Err(_) => filesystem_capacity(&pool.path),
+    }
+}
+
+/// Parse `btrfs filesystem usage -b` output. Sizes are divided by the data
+/// ratio so RAID1 pools report usable rather than raw space.
+pub fn parse_btrfs_usage(output: &str) -> Option<PoolCapacity> {
+    let mut device_size = None;
+    let mut used = None;
+    let mut free = None;
+    let mut ratio = 1.0;
+
+    // Only the `Overall:` block; per-profile sections repeat some keys
+    for line in output
+        .lines()
+        .skip_while(|line| !line.starts_with("Overall:"))
+        .skip(1)
+        .take_while(|line| line.starts_with(char::is_whitespace))
+    {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.split_whitespace().next().unwrap_or_default();
+        match key.trim() {
+            "Device size" => device_size = value.parse::<u64>().ok(),
+            "Used" => used = value.parse::<u64>().ok(),
+            "Free (estimated)" => free = value.parse::<u64>().ok(),
+            "Data ratio" => ratio = value.parse::<f64>().unwrap_or(1.0).max(1.0),
+            _ => {}
+        }
+    }
+
+    let used = (used? as f64 / ratio) as u64;
+    Some(PoolCapacity {
+        total_bytes: (device_size? as f64 / ratio) as u64,
+        used_bytes: used,
+        available_bytes: free?,
+        allocation_bytes: used,
+    })
+}
+
+/// Capacity of the btrfs filesystem holding `path`; `None` when it is not btrfs
+fn btrfs_capacity(path: &Path) -> Option<PoolCapacity> {
+    let fs_type = Command::new("stat")
+        .args(["-f", "-c", "%T"])
+        .arg(path)
+        .output()
+        .ok()?;
+    if String::from_utf8_lossy(&fs_type.stdout).trim() != "btrfs" {
+        return None;
+    }
+
+    let output = Command::new("btrfs")
+        .args(["filesystem", "usage", "-b"])
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        log_warn!(
+            "btrfs filesystem usage failed for {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    parse_btrfs_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Pool capacity of the filesystem holding `path`, using df
+pub(crate) fn filesystem_capacity(path: &Path) -> PoolCapacity {
+    let output = Command::new("df")
+        .args(["-B1", path.to_str().unwrap_or("/")])
+        .output()
+        .ok();
+
+    if let Some(output) = output
+        && output.status.success()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if let Some(line) = stdout.lines().nth(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 4 {
+                let total = parts[1].parse::<u64>().unwrap_or(0);
+                let used = parts[2].parse::<u64>().unwrap_or(0);
+                let available = parts[3].parse::<u64>().unwrap_or(0);
+
+                return PoolCapacity {
+                    total_bytes: total,
+                    used_bytes: used,
+                    available_bytes: available,
+                    allocation_bytes: used,
+                };
+            }
+        }
+    }
+
+    PoolCapacity::empty()
+}
+
+pub(crate) fn volume_format_str(format: &VolumeFormat) -> &'static str {
+    match format {
+        VolumeFormat::Raw => "raw",
+        VolumeFormat::Qcow2 => "qcow2",
+        VolumeFormat::Qed => "qed",
+        VolumeFormat::Vmdk => "vmdk",
+        VolumeFormat::Vdi => "vdi",
+    }
+}
+
+fn libvirt_volume_path(pool_name: &str, volume_name: &str) -> Result<PathBuf> {
+    let output = Command::new("virsh")
+        .args(["vol-path", volume_name, "--pool", pool_name])
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+    if output.status.success() {
+        let path_str = String::from_utf8_lossy(&output.stdout);
+        Ok(PathBuf::from(path_str.trim()))
+    } else {
+        Err(NovaError::SystemCommandFailed)
+    }
+}
+
+/// Define a pool in libvirt from XML via a temporary file
+fn define_pool(pool: &StoragePool, xml: &str) -> Result<()> {
+    let temp_file = format!("/tmp/nova-pool-{}.xml", pool.name);
+    fs::write(&temp_file, xml).map_err(|_| NovaError::SystemCommandFailed)?;
+
+    let output = exec::run(Command::new("virsh").args(["pool-define", &temp_file]))
+        .map_err(|_| NovaError::SystemCommandFailed);
+    fs::remove_file(&temp_file).ok();
+
+    if !output?.status.success() {
+        return Err(NovaError::SystemCommandFailed);
+    }
+    Ok(())
+}
+
+fn directory_pool_xml(pool: &StoragePool) -> String {
+    format!(
+        r#"<pool type='dir'>
+  <name>{}</name>
+  <target>
+    <path>{}</path>
+    <permissions>
+      <mode>0755</mode>
+      <owner>0</owner>
+      <group>0</group>
+    </permissions>
+  </target>
+</pool>"#,
+        pool.name,
+        pool.path.display()
+    )
+}
+
+fn nfs_pool_xml(pool: &StoragePool, server: &str, export_path: &str) -> String {
+    format!(
+        r#"<pool type='netfs'>
+  <name>{}</name>
+  <source>
+    <host name='{}'/>
+    <dir path='{}'/>
+    <format type='nfs'/>
+  </source>
+  <target>
+    <path>{}</path>
+  </target>
+</pool>"#,
+        pool.name,
+        server,
+        export_path,
+        pool.path.display()
+    )
+}
+
+/// `virsh` commands that start `pool` and, when requested, autostart it
+pub fn pool_start_commands(pool: &StoragePool) -> Vec<Command> {
+    let mut start = Command::new("virsh");
+    start.args(["pool-start", &pool.name]);
+    let mut commands = vec![start];
+    if pool.autostart {
+        let mut autostart = Command::new("virsh");
+        autostart.args(["pool-autostart", &pool.name]);
+        commands.push(autostart);
+    }
+    commands
+}
+
+/// libvirt XML for a `zfs` pool over a dataset; its volumes appear under
+/// `/dev/zvol/<dataset>`
+pub fn zfs_pool_xml(pool_name: &str, dataset: &str) -> String {
+    format!(
+        r#"<pool type='zfs'>
+  <name>{}</name>
+  <source>
+    <name>{}</name>
+  </source>
+</pool>"#,
+        pool_name, dataset
+    )
+}
+
+/// libvirt XML for a `logical` pool over an existing volume group
+pub fn logical_pool_xml(pool_name: &str, vg_name: &str) -> String {
+    format!(
+        r#"<pool type='logical'>
+  <name>{}</name>
+  <source>
+    <name>{}</name>
+    <format type='lvm2'/>
+  </source>
+  <target>
+    <path>/dev/{}</path>
+  </target>
+</pool>"#,
+        pool_name, vg_name, vg_name
+    )
+}
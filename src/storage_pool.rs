@@ -1,4 +1,6 @@
-use crate::{NovaError, Result, log_error, log_info, log_warn};
+use crate::storage_backend;
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -12,7 +14,7 @@ pub enum PoolType {
     Directory,
     /// Btrfs filesystem with subvolume support
     Btrfs,
-    /// ZFS filesystem (future)
+    /// ZFS dataset with zvol-backed volumes
     Zfs,
     /// Network File System
     Nfs,
@@ -44,6 +46,15 @@ pub struct PoolCapacity {
 }
 
 impl PoolCapacity {
+    pub fn empty() -> Self {
+        Self {
+            total_bytes: 0,
+            used_bytes: 0,
+            available_bytes: 0,
+            allocation_bytes: 0,
+        }
+    }
+
     pub fn usage_percent(&self) -> f64 {
         if self.total_bytes == 0 {
             0.0
@@ -230,6 +241,8 @@ impl StoragePoolManager {
             PoolType::Lvm
         } else if xml.contains("type='rbd'") {
             PoolType::Ceph
+        } else if xml.contains("type='zfs'") {
+            PoolType::Zfs
         } else {
             PoolType::Directory
         };
@@ -245,9 +258,6 @@ impl StoragePoolManager {
             PathBuf::from("/var/lib/nova/storage")
         };
 
-        // Get capacity info
-        let capacity = self.get_pool_capacity(&path);
-
         // Check if pool is active
         let is_active = self.is_pool_active(name);
         let state = if is_active {
@@ -260,20 +270,43 @@ impl StoragePoolManager {
         let config = match pool_type {
             PoolType::Directory => PoolConfig::Directory { permissions: 0o755 },
             PoolType::Btrfs => self.detect_btrfs_config(&path),
+            PoolType::Lvm => PoolConfig::Lvm {
+                vg_name: Self::source_name(xml).unwrap_or_default(),
+                pv_devices: Vec::new(),
+            },
+            PoolType::Zfs => PoolConfig::Zfs {
+                dataset: Self::source_name(xml).unwrap_or_default(),
+                compression: ZfsCompression::Lz4,
+                dedup: false,
+            },
             _ => PoolConfig::Directory { permissions: 0o755 },
         };
 
-        Ok(StoragePool {
+        let mut pool = StoragePool {
             name: name.to_string(),
             pool_type,
             path,
             state,
-            capacity: Some(capacity),
+            capacity: None,
             autostart: false,
             config,
             uuid: uuid::Uuid::new_v4().to_string(),
             created_at: chrono::Utc::now(),
-        })
+        };
+
+        // Get capacity info
+        pool.capacity = Some(storage_backend::pool_capacity(&pool));
+
+        Ok(pool)
+    }
+
+    /// Extract `<source><name>` (the volume group for logical pools)
+    fn source_name(xml: &str) -> Option<String> {
+        let source = &xml[xml.find("<source>")?..];
+        let source = &source[..source.find("</source>")?];
+        let start = source.find("<name>")? + "<name>".len();
+        let end = source[start..].find("</name>")?;
+        Some(source[start..start + end].trim().to_string())
     }
 
     /// Check if a pool is active
@@ -292,43 +325,6 @@ impl StoragePoolManager {
             .unwrap_or(false)
     }
 
-    /// Get pool capacity using df
-    fn get_pool_capacity(&self, path: &Path) -> PoolCapacity {
-        let output = Command::new("df")
-            .args(["-B1", path.to_str().unwrap_or("/")])
-            .output()
-            .ok();
-
-        if let Some(output) = output
-            && output.status.success()
-        {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if let Some(line) = stdout.lines().nth(1) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 4 {
-                    let total = parts[1].parse::<u64>().unwrap_or(0);
-                    let used = parts[2].parse::<u64>().unwrap_or(0);
-                    let available = parts[3].parse::<u64>().unwrap_or(0);
-
-                    return PoolCapacity {
-                        total_bytes: total,
-                        used_bytes: used,
-                        available_bytes: available,
-                        allocation_bytes: used,
-                    };
-                }
-            }
-        }
-
-        // Default empty capacity
-        PoolCapacity {
-            total_bytes: 0,
-            used_bytes: 0,
-            available_bytes: 0,
-            allocation_bytes: 0,
-        }
-    }
-
     /// Detect Btrfs-specific configuration
     fn detect_btrfs_config(&self, path: &Path) -> PoolConfig {
         // Check if path is on btrfs
@@ -374,242 +370,24 @@ impl StoragePoolManager {
         path.to_path_buf()
     }
 
-    /// Create a new storage pool
+    /// Create a new storage pool. A pool whose path is nested within, or
+    /// contains, an existing pool's path is refused.
     pub async fn create_pool(&mut self, pool: StoragePool) -> Result<()> {
         log_info!("Creating storage pool: {}", pool.name);
 
-        match pool.pool_type {
-            PoolType::Directory => self.create_directory_pool(&pool).await?,
-            PoolType::Btrfs => self.create_btrfs_pool(&pool).await?,
-            PoolType::Nfs => self.create_nfs_pool(&pool).await?,
-            _ => {
-                return Err(NovaError::ConfigError(format!(
-                    "Pool type {:?} not yet implemented",
-                    pool.pool_type
-                )));
-            }
-        }
-
-        self.pools.insert(pool.name.clone(), pool);
-        Ok(())
-    }
-
-    /// Create a directory-based pool
-    async fn create_directory_pool(&self, pool: &StoragePool) -> Result<()> {
-        // Create directory if it doesn't exist
-        if !pool.path.exists() {
-            fs::create_dir_all(&pool.path).map_err(|e| {
-                log_error!("Failed to create pool directory: {}", e);
-                NovaError::SystemCommandFailed
-            })?;
-        }
-
-        // Generate libvirt XML
-        let xml = self.generate_directory_pool_xml(pool)?;
-
-        // Write to temp file
-        let temp_file = format!("/tmp/nova-pool-{}.xml", pool.name);
-        fs::write(&temp_file, xml).map_err(|_| NovaError::SystemCommandFailed)?;
-
-        // Define pool in libvirt
-        let output = Command::new("virsh")
-            .args(["pool-define", &temp_file])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
-
-        if !output.status.success() {
-            fs::remove_file(&temp_file).ok();
-            return Err(NovaError::SystemCommandFailed);
-        }
-
-        // Start the pool
-        let _ = Command::new("virsh")
-            .args(["pool-start", &pool.name])
-            .output();
-
-        // Autostart if requested
-        if pool.autostart {
-            let _ = Command::new("virsh")
-                .args(["pool-autostart", &pool.name])
-                .output();
-        }
-
-        fs::remove_file(&temp_file).ok();
-        log_info!("Directory pool {} created successfully", pool.name);
-        Ok(())
-    }
-
-    /// Create a Btrfs-based pool with subvolumes
-    async fn create_btrfs_pool(&self, pool: &StoragePool) -> Result<()> {
-        log_info!("Creating Btrfs pool: {}", pool.name);
-
-        if let PoolConfig::Btrfs {
-            mount_point,
-            subvolume,
-            compression,
-            ..
-        } = &pool.config
+        if self.pools.is_empty()
+            && let Err(e) = self.discover_pools().await
         {
-            // Create btrfs subvolume if specified
-            if let Some(subvol) = subvolume {
-                let subvol_path = mount_point.join(subvol);
-
-                let output = Command::new("btrfs")
-                    .args(["subvolume", "create", subvol_path.to_str().unwrap()])
-                    .output()
-                    .map_err(|e| {
-                        log_error!("Failed to create btrfs subvolume: {}", e);
-                        NovaError::SystemCommandFailed
-                    })?;
-
-                if !output.status.success() {
-                    let err = String::from_utf8_lossy(&output.stderr);
-                    log_error!("btrfs subvolume create failed: {}", err);
-                    return Err(NovaError::SystemCommandFailed);
-                }
-
-                // Set compression
-                match compression {
-                    BtrfsCompression::Zstd { level } => {
-                        let _ = Command::new("btrfs")
-                            .args([
-                                "property",
-                                "set",
-                                subvol_path.to_str().unwrap(),
-                                "compression",
-                                &format!("zstd:{}", level),
-                            ])
-                            .output();
-                    }
-                    BtrfsCompression::Lzo => {
-                        let _ = Command::new("btrfs")
-                            .args([
-                                "property",
-                                "set",
-                                subvol_path.to_str().unwrap(),
-                                "compression",
-                                "lzo",
-                            ])
-                            .output();
-                    }
-                    BtrfsCompression::Zlib => {
-                        let _ = Command::new("btrfs")
-                            .args([
-                                "property",
-                                "set",
-                                subvol_path.to_str().unwrap(),
-                                "compression",
-                                "zlib",
-                            ])
-                            .output();
-                    }
-                    BtrfsCompression::None => {}
-                }
-
-                log_info!("Btrfs subvolume created: {}", subvol);
-            }
-
-            // Create directory pool at the subvolume path
-            let dir_pool = StoragePool {
-                config: PoolConfig::Directory { permissions: 0o755 },
-                ..pool.clone()
-            };
-
-            self.create_directory_pool(&dir_pool).await?;
+            log_debug!("Could not list existing pools before creating: {:?}", e);
         }
+        check_pool_overlap(&pool, self.pools.values())?;
 
-        Ok(())
-    }
-
-    /// Create an NFS pool
-    async fn create_nfs_pool(&self, pool: &StoragePool) -> Result<()> {
-        log_info!("Creating NFS pool: {}", pool.name);
-
-        if let PoolConfig::Nfs {
-            server,
-            export_path,
-            mount_options,
-        } = &pool.config
-        {
-            // Generate libvirt XML for NFS
-            let xml = self.generate_nfs_pool_xml(pool, server, export_path, mount_options)?;
-
-            let temp_file = format!("/tmp/nova-pool-{}.xml", pool.name);
-            fs::write(&temp_file, xml).map_err(|_| NovaError::SystemCommandFailed)?;
-
-            let output = Command::new("virsh")
-                .args(["pool-define", &temp_file])
-                .output()
-                .map_err(|_| NovaError::SystemCommandFailed)?;
-
-            if !output.status.success() {
-                fs::remove_file(&temp_file).ok();
-                return Err(NovaError::SystemCommandFailed);
-            }
-
-            // Start the pool
-            let output = Command::new("virsh")
-                .args(["pool-start", &pool.name])
-                .output()
-                .map_err(|_| NovaError::SystemCommandFailed)?;
-
-            if !output.status.success() {
-                let err = String::from_utf8_lossy(&output.stderr);
-                log_error!("Failed to start NFS pool: {}", err);
-            }
-
-            fs::remove_file(&temp_file).ok();
-        }
+        storage_backend::backend_for(&pool.pool_type)?.create_pool(&pool)?;
 
+        self.pools.insert(pool.name.clone(), pool);
         Ok(())
     }
 
-    /// Generate libvirt XML for directory pool
-    fn generate_directory_pool_xml(&self, pool: &StoragePool) -> Result<String> {
-        Ok(format!(
-            r#"<pool type='dir'>
-  <name>{}</name>
-  <target>
-    <path>{}</path>
-    <permissions>
-      <mode>0755</mode>
-      <owner>0</owner>
-      <group>0</group>
-    </permissions>
-  </target>
-</pool>"#,
-            pool.name,
-            pool.path.display()
-        ))
-    }
-
-    /// Generate libvirt XML for NFS pool
-    fn generate_nfs_pool_xml(
-        &self,
-        pool: &StoragePool,
-        server: &str,
-        export_path: &str,
-        _mount_options: &[String],
-    ) -> Result<String> {
-        Ok(format!(
-            r#"<pool type='netfs'>
-  <name>{}</name>
-  <source>
-    <host name='{}'/>
-    <dir path='{}'/>
-    <format type='nfs'/>
-  </source>
-  <target>
-    <path>{}</path>
-  </target>
-</pool>"#,
-            pool.name,
-            server,
-            export_path,
-            pool.path.display()
-        ))
-    }
-
     /// Delete a storage pool
     pub async fn delete_pool(&mut self, name: &str, delete_volumes: bool) -> Result<()> {
         log_info!("Deleting storage pool: {}", name);
@@ -655,43 +433,107 @@ impl StoragePoolManager {
     ) -> Result<StorageVolume> {
         log_info!("Creating volume {} in pool {}", volume_name, pool_name);
 
-        let format_str = match format {
-            VolumeFormat::Raw => "raw",
-            VolumeFormat::Qcow2 => "qcow2",
-            VolumeFormat::Qed => "qed",
-            VolumeFormat::Vmdk => "vmdk",
-            VolumeFormat::Vdi => "vdi",
+        let pool = self.resolve_pool(pool_name).await?;
+        let path = storage_backend::backend_for(&pool.pool_type)?.create_volume(
+            &pool,
+            volume_name,
+            size_bytes,
+            &format,
+        )?;
+
+        let volume = StorageVolume {
+            name: volume_name.to_string(),
+            pool_name: pool_name.to_string(),
+            path,
+            format,
+            capacity_bytes: size_bytes,
+            allocation_bytes: 0,
+            backing_store: None,
+            created_at: chrono::Utc::now(),
         };
 
-        let output = Command::new("virsh")
-            .args([
-                "vol-create-as",
-                pool_name,
+        self.volumes
+            .entry(pool_name.to_string())
+            .or_default()
+            .push(volume.clone());
+
+        log_info!("Volume {} created successfully", volume_name);
+        Ok(volume)
+    }
+
+    /// Delete a volume and its backing file or device. Volumes attached to a
+    /// defined VM are refused unless `force` is set.
+    pub async fn delete_volume(
+        &mut self,
+        pool_name: &str,
+        volume_name: &str,
+        force: bool,
+    ) -> Result<()> {
+        log_info!("Deleting volume {} from pool {}", volume_name, pool_name);
+
+        let pool = self.resolve_pool(pool_name).await?;
+        let path = self.volume_path(&pool, volume_name);
+        let users = vms_using_volume(&path, pool_name, volume_name)?;
+        if !users.is_empty() {
+            if !force {
+                return Err(NovaError::ConfigError(format!(
+                    "Volume '{}' in pool '{}' is used by VM(s) {}; detach it first or pass --force",
+                    volume_name,
+                    pool_name,
+                    users.join(", ")
+                )));
+            }
+            log_warn!(
+                "Deleting volume {} still referenced by VM(s) {}",
                 volume_name,
-                &size_bytes.to_string(),
-                "--format",
-                format_str,
-            ])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+                users.join(", ")
+            );
+        }
 
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            log_error!("Failed to create volume: {}", err);
-            return Err(NovaError::SystemCommandFailed);
+        storage_backend::backend_for(&pool.pool_type)?.delete_volume(&pool, volume_name)?;
+
+        if let Some(volumes) = self.volumes.get_mut(pool_name) {
+            volumes.retain(|volume| volume.name != volume_name);
         }
 
-        // Get volume path
-        let path = self.get_volume_path(pool_name, volume_name)?;
+        log_info!("Volume {} deleted successfully", volume_name);
+        Ok(())
+    }
 
+    /// Clone a volume within a pool. ZFS pools use copy-on-write clones.
+    pub async fn clone_volume(
+        &mut self,
+        pool_name: &str,
+        source: &str,
+        target: &str,
+    ) -> Result<StorageVolume> {
+        log_info!(
+            "Cloning volume {} to {} in pool {}",
+            source,
+            target,
+            pool_name
+        );
+
+        let pool = self.resolve_pool(pool_name).await?;
+        let path =
+            storage_backend::backend_for(&pool.pool_type)?.clone_volume(&pool, source, target)?;
+
+        let template = self
+            .volumes
+            .get(pool_name)
+            .and_then(|volumes| volumes.iter().find(|v| v.name == source))
+            .cloned();
         let volume = StorageVolume {
-            name: volume_name.to_string(),
+            name: target.to_string(),
             pool_name: pool_name.to_string(),
             path,
-            format,
-            capacity_bytes: size_bytes,
+            format: template
+                .as_ref()
+                .map(|v| v.format.clone())
+                .unwrap_or(VolumeFormat::Raw),
+            capacity_bytes: template.as_ref().map(|v| v.capacity_bytes).unwrap_or(0),
             allocation_bytes: 0,
-            backing_store: None,
+            backing_store: template.map(|v| v.path),
             created_at: chrono::Utc::now(),
         };
 
@@ -700,25 +542,153 @@ impl StoragePoolManager {
             .or_default()
             .push(volume.clone());
 
-        log_info!("Volume {} created successfully", volume_name);
+        log_info!("Volume {} cloned to {}", source, target);
         Ok(volume)
     }
 
-    /// Get volume path
-    fn get_volume_path(&self, pool_name: &str, volume_name: &str) -> Result<PathBuf> {
+    /// Snapshot a volume in a ZFS pool, returning the snapshot name
+    pub async fn snapshot_volume(
+        &mut self,
+        pool_name: &str,
+        volume_name: &str,
+        snapshot: &str,
+    ) -> Result<String> {
+        let pool = self.resolve_pool(pool_name).await?;
+        if pool.pool_type != PoolType::Zfs {
+            return Err(NovaError::ConfigError(format!(
+                "Volume snapshots are only supported on ZFS pools; '{}' is {:?}",
+                pool_name, pool.pool_type
+            )));
+        }
+
+        storage_backend::ZfsBackend.snapshot_volume(&pool, volume_name, snapshot)
+    }
+
+    /// Host path of a volume: libvirt's view when the pool is defined there,
+    /// otherwise the cached volume or the pool directory.
+    fn volume_path(&self, pool: &StoragePool, volume_name: &str) -> PathBuf {
         let output = Command::new("virsh")
-            .args(["vol-path", volume_name, "--pool", pool_name])
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+            .args(["vol-path", volume_name, "--pool", &pool.name])
+            .output();
+        if let Ok(output) = output
+            && output.status.success()
+        {
+            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !path.is_empty() {
+                return PathBuf::from(path);
+            }
+        }
 
-        if output.status.success() {
-            let path_str = String::from_utf8_lossy(&output.stdout);
-            Ok(PathBuf::from(path_str.trim()))
-        } else {
-            Err(NovaError::SystemCommandFailed)
+        self.volumes
+            .get(&pool.name)
+            .and_then(|volumes| volumes.iter().find(|v| v.name == volume_name))
+            .map(|volume| volume.path.clone())
+            .unwrap_or_else(|| pool.path.join(volume_name))
+    }
+
+    /// `(pool, volume)` libvirt knows the file or device at `path` as, if
+    /// it lives in a defined storage pool.
+    pub fn volume_at(path: &Path) -> Option<(String, String)> {
+        let query = |command: &str| {
+            let output = Command::new("virsh").arg(command).arg(path).output().ok()?;
+            let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !value.is_empty()).then_some(value)
+        };
+        Some((query("vol-pool")?, query("vol-name")?))
+    }
+
+    /// Cached pool, or its libvirt definition when the cache is cold
+    async fn resolve_pool(&self, pool_name: &str) -> Result<StoragePool> {
+        match self.pools.get(pool_name) {
+            Some(pool) => Ok(pool.clone()),
+            None => self.get_pool_info(pool_name).await,
         }
     }
 
+    /// Re-read a pool's capacity after the device or filesystem under it has
+    /// grown. libvirt caches pool sizes, so running pools are refreshed there
+    /// too.
+    pub async fn refresh_capacity(&mut self, name: &str) -> Result<PoolCapacity> {
+        let mut pool = self.resolve_pool(name).await?;
+
+        if pool.state == PoolState::Active {
+            match exec::run(Command::new("virsh").args(["pool-refresh", name])) {
+                Ok(output) if output.status.success() => {}
+                Ok(output) => {
+                    log_warn!(
+                        "virsh pool-refresh {} failed: {}",
+                        name,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Err(e) => {
+                    log_warn!("Failed to run virsh pool-refresh {}: {}", name, e);
+                }
+            }
+        }
+
+        let capacity = storage_backend::pool_capacity(&pool);
+        log_info!(
+            "Pool {} capacity: {} bytes total, {} bytes available",
+            name,
+            capacity.total_bytes,
+            capacity.available_bytes
+        );
+        pool.capacity = Some(capacity.clone());
+        self.pools.insert(name.to_string(), pool);
+        Ok(capacity)
+    }
+
+    /// Grow a volume to `size_bytes`. Only for volumes no running domain has
+    /// open; those are grown with `VmManager::notify_disk_resize` instead.
+    pub async fn resize_volume(
+        &mut self,
+        pool_name: &str,
+        volume_name: &str,
+        size_bytes: u64,
+    ) -> Result<()> {
+        log_info!(
+            "Resizing volume {} in pool {} to {} bytes",
+            volume_name,
+            pool_name,
+            size_bytes
+        );
+
+        let output = exec::run(Command::new("virsh").args([
+            "vol-resize",
+            volume_name,
+            &size_bytes.to_string(),
+            "--pool",
+            pool_name,
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to resize volume: {}", err);
+            return Err(NovaError::SystemCommandFailed);
+        }
+
+        if let Some(volume) = self
+            .volumes
+            .get_mut(pool_name)
+            .and_then(|volumes| volumes.iter_mut().find(|v| v.name == volume_name))
+        {
+            volume.capacity_bytes = size_bytes;
+        }
+
+        log_info!("Volume {} resized successfully", volume_name);
+        Ok(())
+    }
+
+    /// Discover all pools and report overlapping paths and other
+    /// misconfigurations.
+    pub async fn verify_pools(&mut self) -> Result<Vec<PoolIssue>> {
+        self.discover_pools().await?;
+        let pools: Vec<&StoragePool> = self.pools.values().collect();
+        Ok(verify_pools(&pools))
+    }
+
     /// List all pools
     pub fn list_pools(&self) -> Vec<&StoragePool> {
         self.pools.values().collect()
@@ -738,6 +708,280 @@ impl StoragePoolManager {
     }
 }
 
+/// How a pool path relates to another pool's path that it overlaps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathOverlap {
+    /// Both pools use the same directory
+    Same,
+    /// The path is inside the other pool's directory
+    Inside,
+    /// The path contains the other pool's directory
+    Contains,
+}
+
+/// A problem found by [`StoragePoolManager::verify_pools`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolProblem {
+    Overlap {
+        other: String,
+        overlap: PathOverlap,
+        other_path: PathBuf,
+    },
+    MissingPath,
+    NotADirectory,
+    Inactive,
+    UnknownCapacity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolIssue {
+    pub pool: String,
+    pub path: PathBuf,
+    pub problem: PoolProblem,
+}
+
+impl PoolIssue {
+    /// Overlaps and missing paths risk data loss or wrong capacity; the rest
+    /// are informational.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.problem,
+            PoolProblem::Overlap { .. } | PoolProblem::MissingPath | PoolProblem::NotADirectory
+        )
+    }
+}
+
+impl std::fmt::Display for PoolProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolProblem::Overlap {
+                other,
+                overlap,
+                other_path,
+            } => {
+                let relation = match overlap {
+                    PathOverlap::Same => "shares its directory with",
+                    PathOverlap::Inside => "is nested inside",
+                    PathOverlap::Contains => "contains",
+                };
+                write!(
+                    f,
+                    "{} pool '{}' ({}); capacity is double-counted and deleting one pool removes the other's volumes",
+                    relation,
+                    other,
+                    other_path.display()
+                )
+            }
+            PoolProblem::MissingPath => write!(f, "path does not exist"),
+            PoolProblem::NotADirectory => write!(f, "path is not a directory"),
+            PoolProblem::Inactive => write!(f, "pool is defined but not running"),
+            PoolProblem::UnknownCapacity => write!(f, "capacity could not be read"),
+        }
+    }
+}
+
+/// Pool types whose path is a host directory. For iSCSI, Ceph and LVM
+/// pools it names a device or is unused.
+fn has_directory_path(pool_type: &PoolType) -> bool {
+    matches!(
+        pool_type,
+        PoolType::Directory | PoolType::Btrfs | PoolType::Zfs | PoolType::Nfs
+    )
+}
+
+/// A pool path with `.`/`..` removed and symlinks resolved as far as the
+/// path exists, so a pool that is not created yet compares like one that is.
+fn normalize_pool_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    let mut missing = Vec::new();
+    let mut existing = normalized.as_path();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return normalized;
+        };
+        missing.push(name.to_os_string());
+        existing = parent;
+    }
+    let Ok(mut resolved) = fs::canonicalize(existing) else {
+        return normalized;
+    };
+    resolved.extend(missing.iter().rev());
+    resolved
+}
+
+/// Whether `path` overlaps `other`, comparing whole path components.
+pub fn path_overlap(path: &Path, other: &Path) -> Option<PathOverlap> {
+    let path = normalize_pool_path(path);
+    let other = normalize_pool_path(other);
+    if path == other {
+        Some(PathOverlap::Same)
+    } else if path.starts_with(&other) {
+        Some(PathOverlap::Inside)
+    } else if other.starts_with(&path) {
+        Some(PathOverlap::Contains)
+    } else {
+        None
+    }
+}
+
+/// Refuse `pool` if its directory overlaps one of `existing`.
+pub fn check_pool_overlap<'a>(
+    pool: &StoragePool,
+    existing: impl IntoIterator<Item = &'a StoragePool>,
+) -> Result<()> {
+    if !has_directory_path(&pool.pool_type) {
+        return Ok(());
+    }
+    for other in existing {
+        if other.name == pool.name || !has_directory_path(&other.pool_type) {
+            continue;
+        }
+        if let Some(overlap) = path_overlap(&pool.path, &other.path) {
+            let problem = PoolProblem::Overlap {
+                other: other.name.clone(),
+                overlap,
+                other_path: other.path.clone(),
+            };
+            return Err(NovaError::ConfigError(format!(
+                "Pool '{}' at {} {}",
+                pool.name,
+                pool.path.display(),
+                problem
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Misconfigurations across `pools`, ordered by pool name. Each overlapping
+/// pair is reported once, on the pool that sorts first.
+pub fn verify_pools(pools: &[&StoragePool]) -> Vec<PoolIssue> {
+    let mut pools = pools.to_vec();
+    pools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut issues = Vec::new();
+    for (index, pool) in pools.iter().enumerate() {
+        let issue = |problem| PoolIssue {
+            pool: pool.name.clone(),
+            path: pool.path.clone(),
+            problem,
+        };
+
+        if has_directory_path(&pool.pool_type) {
+            for other in &pools[index + 1..] {
+                if !has_directory_path(&other.pool_type) {
+                    continue;
+                }
+                if let Some(overlap) = path_overlap(&pool.path, &other.path) {
+                    issues.push(issue(PoolProblem::Overlap {
+                        other: other.name.clone(),
+                        overlap,
+                        other_path: other.path.clone(),
+                    }));
+                }
+            }
+            if !pool.path.exists() {
+                issues.push(issue(PoolProblem::MissingPath));
+            } else if !pool.path.is_dir() {
+                issues.push(issue(PoolProblem::NotADirectory));
+            }
+        }
+
+        if pool.state == PoolState::Inactive {
+            issues.push(issue(PoolProblem::Inactive));
+        } else if pool.state == PoolState::Active
+            && pool
+                .capacity
+                .as_ref()
+                .is_none_or(|capacity| capacity.total_bytes == 0)
+        {
+            issues.push(issue(PoolProblem::UnknownCapacity));
+        }
+    }
+    issues
+}
+
+/// Defined VMs (running or not) with a disk backed by the volume. Fails
+/// when the domains can't be listed, rather than reporting none.
+fn vms_using_volume(path: &Path, pool_name: &str, volume_name: &str) -> Result<Vec<String>> {
+    let output = Command::new("virsh")
+        .args(["list", "--all", "--name"])
+        .output()
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !output.status.success() {
+        return Err(NovaError::LibvirtError(format!(
+            "Cannot list VMs to check whether volume '{}' is in use: {}",
+            volume_name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let users = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|vm| !vm.is_empty())
+        .filter(|vm| {
+            Command::new("virsh")
+                .args(["dumpxml", "--inactive", vm])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .is_some_and(|output| {
+                    disk_references_volume(
+                        &String::from_utf8_lossy(&output.stdout),
+                        path,
+                        pool_name,
+                        volume_name,
+                    )
+                })
+        })
+        .map(str::to_string)
+        .collect();
+    Ok(users)
+}
+
+/// Whether a domain definition has a disk backed by the volume, either by
+/// path (`file=`/`dev=`) or as a libvirt pool volume.
+pub fn disk_references_volume(
+    domain_xml: &str,
+    path: &Path,
+    pool_name: &str,
+    volume_name: &str,
+) -> bool {
+    let disk = Regex::new(r"(?s)<disk\b.*?</disk>").expect("valid disk pattern");
+    let source = Regex::new(r"<source\b[^>]*>").expect("valid source pattern");
+    let attribute = |tag: &str, name: &str| {
+        Regex::new(&format!(r#"\b{}=(?:'([^']*)'|"([^"]*)")"#, name))
+            .expect("valid attribute pattern")
+            .captures(tag)
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .map(|value| value.as_str().to_string())
+    };
+
+    disk.find_iter(domain_xml)
+        .flat_map(|disk| source.find_iter(disk.as_str()))
+        .any(|tag| {
+            let tag = tag.as_str();
+            let by_path = ["file", "dev"]
+                .iter()
+                .filter_map(|name| attribute(tag, name))
+                .any(|source| Path::new(&source) == path);
+            by_path
+                || (attribute(tag, "pool").as_deref() == Some(pool_name)
+                    && attribute(tag, "volume").as_deref() == Some(volume_name))
+        })
+}
+
 impl Default for StoragePoolManager {
     fn default() -> Self {
         Self::new()
@@ -1,4 +1,10 @@
-use crate::{NovaError, Result, log_error, log_info};
+use crate::naming::{self, NameKind};
+use crate::progress::{self, Reporter};
+use crate::snapshot_archive::{
+    self, ARCHIVE_FORMAT_VERSION, ArchiveManifest, CaptureKind, DOMAIN_XML_NAME, ImportTarget,
+    MANIFEST_NAME, MEMORY_NAME, SnapshotExport, SnapshotImport,
+};
+use crate::{NovaError, Result, exec, log_debug, log_error, log_info, log_warn};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -72,6 +78,9 @@ pub struct VmSnapshot {
     pub parent_snapshot: Option<String>,
     pub children: Vec<String>,
     pub is_current: bool,
+    /// Taken by Nova itself, e.g. the safety snapshot of `revert --safe`
+    #[serde(default)]
+    pub auto_created: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +99,67 @@ pub enum VmState {
     Crashed,
 }
 
+/// Per-VM limits on snapshot count and storage (`[vm.<name>.snapshot_quota]`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotQuota {
+    #[serde(default)]
+    pub max_snapshots: Option<usize>,
+    #[serde(default)]
+    pub max_snapshot_bytes: Option<u64>,
+    /// Delete the oldest snapshots to make room instead of refusing
+    #[serde(default)]
+    pub auto_prune: bool,
+}
+
+impl SnapshotQuota {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_snapshots.is_none() && self.max_snapshot_bytes.is_none()
+    }
+
+    /// Why one more snapshot would not fit, given current usage.
+    pub fn violation(&self, count: usize, total_bytes: u64) -> Option<String> {
+        if let Some(max) = self.max_snapshots
+            && count >= max
+        {
+            return Some(format!("{} of {} snapshots used", count, max));
+        }
+        if let Some(max) = self.max_snapshot_bytes
+            && total_bytes >= max
+        {
+            return Some(format!(
+                "{} of {} bytes of snapshot storage used",
+                total_bytes, max
+            ));
+        }
+        None
+    }
+
+    /// Oldest-first snapshot names to delete so one more snapshot fits.
+    /// The current snapshot and `keep` are never pruned.
+    pub fn prune_candidates(
+        &self,
+        chronological: &[&VmSnapshot],
+        keep: Option<&str>,
+    ) -> Vec<String> {
+        let mut count = chronological.len();
+        let mut total: u64 = chronological.iter().map(|s| s.size_bytes).sum();
+        let mut prune = Vec::new();
+
+        for snapshot in chronological
+            .iter()
+            .filter(|s| !s.is_current && Some(s.name.as_str()) != keep)
+        {
+            if self.violation(count, total).is_none() {
+                break;
+            }
+            prune.push(snapshot.name.clone());
+            count -= 1;
+            total = total.saturating_sub(snapshot.size_bytes);
+        }
+        prune
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnapshotTree {
     pub vm_name: String,
@@ -101,6 +171,7 @@ pub struct TemplateManager {
     templates_dir: PathBuf,
     templates: HashMap<String, VmTemplate>,
     snapshots: HashMap<String, HashMap<String, VmSnapshot>>, // vm_name -> snapshot_id -> snapshot
+    quotas: HashMap<String, SnapshotQuota>,
 }
 
 impl TemplateManager {
@@ -111,20 +182,26 @@ impl TemplateManager {
             templates_dir,
             templates: HashMap::new(),
             snapshots: HashMap::new(),
+            quotas: HashMap::new(),
         };
 
         manager.load_templates()?;
+        manager.snapshots = read_snapshots(&manager.templates_dir);
 
         Ok(manager)
     }
 
-    /// Create a new VM template from an existing VM
+    /// Create a new VM template from an existing VM. With `generalize`, the
+    /// copied disk is run through `virt-sysprep` so clones get fresh machine
+    /// IDs, SSH host keys and logs; the source VM's disk is left untouched.
     pub async fn create_template_from_vm(
         &mut self,
         vm_name: &str,
         template_name: &str,
         description: &str,
         tags: Vec<String>,
+        generalize: bool,
+        reporter: &mut Reporter,
     ) -> Result<String> {
         log_info!(
             "Creating template '{}' from VM '{}'",
@@ -133,6 +210,7 @@ impl TemplateManager {
         );
 
         // Ensure VM is shut down
+        reporter.stage(format!("Making sure '{}' is shut off", vm_name));
         self.ensure_vm_shutdown(vm_name).await?;
 
         // Get VM configuration
@@ -142,44 +220,69 @@ impl TemplateManager {
         let template_dir = self.templates_dir.join(&template_id);
         std::fs::create_dir_all(&template_dir)?;
 
-        // Copy and compress VM disk
-        let source_disk = self.get_vm_disk_path(vm_name).await?;
-        let template_disk = template_dir.join("disk.qcow2");
+        let template = async {
+            // Copy and compress VM disk
+            let source_disk = self.get_vm_disk_path(vm_name).await?;
+            let template_disk = template_dir.join("disk.qcow2");
 
-        log_info!("Compressing VM disk for template...");
-        self.compress_vm_disk(&source_disk, &template_disk).await?;
+            log_info!("Compressing VM disk for template...");
+            self.compress_vm_disk(&source_disk, &template_disk, reporter)
+                .await?;
 
-        // Save VM configuration as template
-        let config_path = template_dir.join("config.xml");
-        self.save_vm_config_as_template(vm_name, &config_path)
-            .await?;
+            // Save VM configuration as template
+            let config_path = template_dir.join("config.xml");
+            self.save_vm_config_as_template(vm_name, &config_path)
+                .await?;
 
-        // Detect OS type from VM
-        let os_type = self.detect_vm_os_type(vm_name).await;
+            // Detect OS type from VM
+            let os_type = self.detect_vm_os_type(vm_name).await;
 
-        let template = VmTemplate {
-            id: template_id.clone(),
-            name: template_name.to_string(),
-            description: description.to_string(),
-            os_type,
-            version: "1.0".to_string(),
-            cpu_cores: vm_info.cpu_cores,
-            memory_mb: vm_info.memory_mb,
-            disk_size_gb: vm_info.disk_size_gb,
-            network_config: NetworkTemplate::default(),
-            disk_path: template_disk,
-            config_path,
-            created_at: Utc::now(),
-            created_by: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
-            tags,
-            size_on_disk: self.get_directory_size(&template_dir)?,
-            guest_tools_installed: self.check_guest_tools_installed(vm_name).await,
-        };
+            if generalize {
+                reporter.stage("Generalizing with virt-sysprep");
+                self.generalize_disk(&template_disk, &os_type).await?;
+            }
 
-        // Save template metadata
-        let metadata_path = template_dir.join("template.json");
-        let template_json = serde_json::to_string_pretty(&template)?;
-        std::fs::write(metadata_path, template_json)?;
+            let template = VmTemplate {
+                id: template_id.clone(),
+                name: template_name.to_string(),
+                description: description.to_string(),
+                os_type,
+                version: "1.0".to_string(),
+                cpu_cores: vm_info.cpu_cores,
+                memory_mb: vm_info.memory_mb,
+                disk_size_gb: vm_info.disk_size_gb,
+                network_config: NetworkTemplate::default(),
+                disk_path: template_disk,
+                config_path,
+                created_at: Utc::now(),
+                created_by: std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()),
+                tags,
+                size_on_disk: self.get_directory_size(&template_dir)?,
+                guest_tools_installed: self.check_guest_tools_installed(vm_name).await,
+            };
+
+            // Save template metadata
+            let metadata_path = template_dir.join("template.json");
+            let template_json = serde_json::to_string_pretty(&template)?;
+            std::fs::write(metadata_path, template_json)?;
+            Ok::<_, NovaError>(template)
+        }
+        .await;
+
+        // Don't leave a half-written template behind for load_templates to trip over
+        let template = match template {
+            Ok(template) => template,
+            Err(err) => {
+                if let Err(cleanup) = std::fs::remove_dir_all(&template_dir) {
+                    log_warn!(
+                        "Could not remove partial template {}: {}",
+                        template_dir.display(),
+                        cleanup
+                    );
+                }
+                return Err(err);
+            }
+        };
 
         self.templates.insert(template_id.clone(), template);
 
@@ -198,9 +301,26 @@ impl TemplateManager {
         snapshot_name: &str,
         description: &str,
         include_memory: bool,
+    ) -> Result<String> {
+        self.create_snapshot_keeping(vm_name, snapshot_name, description, include_memory, None)
+            .await
+    }
+
+    /// Create a snapshot; quota pruning leaves the snapshot named `keep` alone.
+    async fn create_snapshot_keeping(
+        &mut self,
+        vm_name: &str,
+        snapshot_name: &str,
+        description: &str,
+        include_memory: bool,
+        keep: Option<&str>,
     ) -> Result<String> {
         log_info!("Creating snapshot '{}' for VM '{}'", snapshot_name, vm_name);
 
+        validate_snapshot_name(snapshot_name)?;
+        self.ensure_vm_exists(vm_name)?;
+        self.enforce_quota(vm_name, keep).await?;
+
         let snapshot_id = uuid::Uuid::new_v4().to_string();
 
         // Determine snapshot type based on VM state and user preference
@@ -240,9 +360,11 @@ impl TemplateManager {
 
         // Create snapshot directory
         let snapshot_dir = PathBuf::from("/var/lib/nova/snapshots").join(vm_name);
-        std::fs::create_dir_all(&snapshot_dir)?;
+        if !exec::is_dry_run() {
+            std::fs::create_dir_all(&snapshot_dir)?;
+        }
 
-        let output = cmd.output()?;
+        let output = exec::run(&mut cmd)?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -251,7 +373,9 @@ impl TemplateManager {
         }
 
         // Calculate snapshot size
-        let size_bytes = self.calculate_snapshot_size(vm_name, &snapshot_id).await?;
+        let size_bytes = self
+            .calculate_snapshot_size(vm_name, &snapshot_id, snapshot_name, &snapshot_type)
+            .await;
 
         // Get parent snapshot
         let parent_snapshot = self.get_current_snapshot(vm_name).await;
@@ -268,13 +392,16 @@ impl TemplateManager {
             parent_snapshot,
             children: Vec::new(),
             is_current: true,
+            auto_created: false,
         };
 
         // Store snapshot
-        self.snapshots
-            .entry(vm_name.to_string())
-            .or_default()
-            .insert(snapshot_id.clone(), snapshot);
+        let snapshots = self.snapshots.entry(vm_name.to_string()).or_default();
+        for snap in snapshots.values_mut() {
+            snap.is_current = false;
+        }
+        snapshots.insert(snapshot_id.clone(), snapshot);
+        self.save_snapshots()?;
 
         log_info!(
             "Snapshot '{}' created with ID: {}",
@@ -284,6 +411,74 @@ impl TemplateManager {
         Ok(snapshot_id)
     }
 
+    /// Set the snapshot quota for a VM, typically from its NovaFile entry.
+    pub fn set_quota(&mut self, vm_name: &str, quota: SnapshotQuota) {
+        if quota.is_unlimited() {
+            self.quotas.remove(vm_name);
+        } else {
+            self.quotas.insert(vm_name.to_string(), quota);
+        }
+    }
+
+    /// Replace every VM's snapshot quota, e.g. after reloading the NovaFile.
+    pub fn set_quotas<'a>(&mut self, quotas: impl IntoIterator<Item = (&'a str, SnapshotQuota)>) {
+        self.quotas.clear();
+        for (vm_name, quota) in quotas {
+            self.set_quota(vm_name, quota);
+        }
+    }
+
+    pub fn quota(&self, vm_name: &str) -> Option<&SnapshotQuota> {
+        self.quotas.get(vm_name)
+    }
+
+    /// Make room for one more snapshot or refuse, per the VM's quota.
+    /// The snapshot named `keep` is never pruned.
+    async fn enforce_quota(&mut self, vm_name: &str, keep: Option<&str>) -> Result<()> {
+        let Some(quota) = self.quotas.get(vm_name).cloned() else {
+            return Ok(());
+        };
+
+        // Count what is on record now, not what this manager loaded earlier;
+        // another process may have taken snapshots since
+        match read_snapshots(&self.templates_dir).remove(vm_name) {
+            Some(persisted) => self.snapshots.insert(vm_name.to_string(), persisted),
+            None => self.snapshots.remove(vm_name),
+        };
+
+        let count = self.snapshots.get(vm_name).map_or(0, HashMap::len);
+        let Some(reason) = quota.violation(count, self.get_total_snapshot_size(vm_name)) else {
+            return Ok(());
+        };
+
+        if !quota.auto_prune {
+            return Err(NovaError::SnapshotQuotaExceeded(format!(
+                "VM '{}': {}; delete old snapshots or raise the quota",
+                vm_name, reason
+            )));
+        }
+
+        let prune = quota.prune_candidates(&self.list_snapshots_chronological(vm_name), keep);
+        for name in &prune {
+            log_warn!(
+                "Snapshot quota for '{}' reached ({}); pruning oldest snapshot '{}'",
+                vm_name,
+                reason,
+                name
+            );
+            self.remove_snapshot(vm_name, name, false).await?;
+        }
+
+        let count = self.snapshots.get(vm_name).map_or(0, HashMap::len);
+        if let Some(reason) = quota.violation(count, self.get_total_snapshot_size(vm_name)) {
+            return Err(NovaError::SnapshotQuotaExceeded(format!(
+                "VM '{}': {} even after pruning",
+                vm_name, reason
+            )));
+        }
+        Ok(())
+    }
+
     // Template management
     pub fn list_templates(&self) -> Vec<&VmTemplate> {
         self.templates.values().collect()
@@ -327,6 +522,22 @@ impl TemplateManager {
     }
 
     // Helper methods
+    /// Fail with a clear error for VMs libvirt doesn't know, instead of an
+    /// opaque `snapshot-create-as` failure.
+    fn ensure_vm_exists(&self, vm_name: &str) -> Result<()> {
+        match Command::new("virsh").args(["dominfo", vm_name]).output() {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(_) => Err(NovaError::ConfigError(format!(
+                "VM '{}' not found",
+                vm_name
+            ))),
+            Err(e) => Err(NovaError::ConfigError(format!(
+                "VM '{}' not found (could not run virsh: {})",
+                vm_name, e
+            ))),
+        }
+    }
+
     async fn ensure_vm_shutdown(&self, vm_name: &str) -> Result<()> {
         let state = self.get_vm_state(vm_name).await?;
         if matches!(state, VmState::Running) {
@@ -368,14 +579,26 @@ impl TemplateManager {
         }
     }
 
-    async fn compress_vm_disk(&self, source: &Path, target: &Path) -> Result<()> {
+    async fn compress_vm_disk(
+        &self,
+        source: &Path,
+        target: &Path,
+        reporter: &mut Reporter,
+    ) -> Result<()> {
         log_info!("Compressing VM disk: {:?} -> {:?}", source, target);
 
-        let output = Command::new("qemu-img")
-            .args(["convert", "-c", "-O", "qcow2"])
-            .arg(source)
-            .arg(target)
-            .output()?;
+        let info = image_info(source)?;
+        reporter.stage_bytes(
+            format!("Compressing {}", source.display()),
+            info.virtual_size,
+        );
+        let output = progress::qemu_img_output(
+            Command::new("qemu-img")
+                .args(["convert", "-p", "-c", "-O", "qcow2"])
+                .arg(source)
+                .arg(target),
+            reporter,
+        )?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -387,51 +610,110 @@ impl TemplateManager {
         Ok(())
     }
 
-    fn load_templates(&mut self) -> Result<()> {
-        if !self.templates_dir.exists() {
-            return Ok(());
+    /// Reset machine-specific state in a template disk with `virt-sysprep`.
+    async fn generalize_disk(&self, disk: &Path, os_type: &OperatingSystem) -> Result<()> {
+        if matches!(os_type, OperatingSystem::Windows { .. }) {
+            log_warn!(
+                "virt-sysprep cannot reset a Windows SID; run `sysprep /generalize /oobe /shutdown` inside the guest before capturing"
+            );
         }
+        log_info!("Generalizing template disk {:?} with virt-sysprep", disk);
+
+        let output = Command::new("virt-sysprep")
+            .arg("-a")
+            .arg(disk)
+            .output()
+            .map_err(|e| {
+                NovaError::ConfigError(format!(
+                    "Could not run virt-sysprep ({}); install libguestfs or drop --sysprep",
+                    e
+                ))
+            })?;
 
-        for entry in std::fs::read_dir(&self.templates_dir)? {
-            let entry = entry?;
-            let template_dir = entry.path();
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            log_error!("virt-sysprep failed: {}", error);
+            return Err(NovaError::ConfigError(format!(
+                "virt-sysprep failed on {}: {}",
+                disk.display(),
+                error.trim()
+            )));
+        }
+        Ok(())
+    }
 
-            if template_dir.is_dir() {
-                let metadata_file = template_dir.join("template.json");
-                if metadata_file.exists()
-                    && let Ok(content) = std::fs::read_to_string(metadata_file)
-                    && let Ok(template) = serde_json::from_str::<VmTemplate>(&content)
-                {
-                    self.templates.insert(template.id.clone(), template);
-                }
-            }
+    fn load_templates(&mut self) -> Result<()> {
+        for template in read_templates(&self.templates_dir) {
+            self.templates.insert(template.id.clone(), template);
         }
 
         log_info!("Loaded {} templates", self.templates.len());
         Ok(())
     }
 
-    // Placeholder implementations for complex operations
-    async fn get_vm_info(&self, _vm_name: &str) -> Result<VmInfo> {
-        Ok(VmInfo {
-            cpu_cores: 2,
-            memory_mb: 2048,
-            disk_size_gb: 20,
-        })
+    async fn get_vm_info(&self, vm_name: &str) -> Result<VmInfo> {
+        let output = Command::new("virsh").args(["dominfo", vm_name]).output()?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm_name.to_string()));
+        }
+        let mut info = parse_dominfo(&String::from_utf8_lossy(&output.stdout));
+
+        if let Ok(disk) = self.get_vm_disk_path(vm_name).await
+            && let Ok(image) = image_info(&disk)
+        {
+            info.disk_size_gb = image.virtual_size.div_ceil(1 << 30);
+        }
+        Ok(info)
+    }
+
+    async fn get_vm_disk_path(&self, vm_name: &str) -> Result<PathBuf> {
+        self.vm_disks(vm_name)?
+            .into_iter()
+            .next()
+            .map(|(_, path)| path)
+            .ok_or_else(|| {
+                NovaError::ConfigError(format!("VM '{}' has no file-backed disk", vm_name))
+            })
     }
 
-    async fn get_vm_disk_path(&self, _vm_name: &str) -> Result<PathBuf> {
-        Ok(PathBuf::from("/var/lib/libvirt/images/vm.qcow2"))
+    /// File-backed disks of a VM as (target, image path), in device order.
+    fn vm_disks(&self, vm_name: &str) -> Result<Vec<(String, PathBuf)>> {
+        let output = Command::new("virsh")
+            .args(["domblklist", vm_name, "--details"])
+            .output()?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm_name.to_string()));
+        }
+        Ok(parse_domblklist_disks(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
     }
 
-    async fn save_vm_config_as_template(&self, _vm_name: &str, _config_path: &Path) -> Result<()> {
+    async fn save_vm_config_as_template(&self, vm_name: &str, config_path: &Path) -> Result<()> {
+        let output = Command::new("virsh")
+            .args(["dumpxml", "--inactive", vm_name])
+            .output()?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm_name.to_string()));
+        }
+        std::fs::write(config_path, &output.stdout)?;
         Ok(())
     }
 
-    async fn detect_vm_os_type(&self, _vm_name: &str) -> OperatingSystem {
-        OperatingSystem::Linux {
-            distro: LinuxDistro::Ubuntu {
-                version: "22.04".to_string(),
+    /// OS from the libosinfo ID virt-install records in the domain metadata.
+    async fn detect_vm_os_type(&self, vm_name: &str) -> OperatingSystem {
+        let xml = Command::new("virsh")
+            .args(["dumpxml", "--inactive", vm_name])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+
+        match libosinfo_id(&xml) {
+            Some(id) => os_from_libosinfo_id(&id),
+            None => OperatingSystem::Other {
+                name: "unknown".to_string(),
             },
         }
     }
@@ -440,12 +722,69 @@ impl TemplateManager {
         false
     }
 
-    fn get_directory_size(&self, _dir: &Path) -> Result<u64> {
-        Ok(1024 * 1024 * 1024) // 1GB placeholder
+    fn get_directory_size(&self, dir: &Path) -> Result<u64> {
+        let mut total = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let metadata = entry?.metadata()?;
+            if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+        Ok(total)
     }
 
-    async fn calculate_snapshot_size(&self, _vm_name: &str, _snapshot_id: &str) -> Result<u64> {
-        Ok(512 * 1024 * 1024) // 512MB placeholder
+    /// Bytes a snapshot occupies on the host: the allocated size of its
+    /// overlay (plus the memory file) for external snapshots, or the saved VM
+    /// state for internal ones. Failures are logged and count as 0, since
+    /// the snapshot itself already exists.
+    async fn calculate_snapshot_size(
+        &self,
+        vm_name: &str,
+        snapshot_id: &str,
+        snapshot_name: &str,
+        snapshot_type: &SnapshotType,
+    ) -> u64 {
+        if exec::is_dry_run() {
+            return 0;
+        }
+        let snapshot_dir = PathBuf::from("/var/lib/nova/snapshots").join(vm_name);
+        let size = match snapshot_type {
+            SnapshotType::Internal => self.get_vm_disk_path(vm_name).await.and_then(|disk| {
+                let output = Command::new("qemu-img")
+                    .args(["snapshot", "-l", "-U"])
+                    .arg(&disk)
+                    .output()?;
+                if !output.status.success() {
+                    return Err(NovaError::ConfigError(format!(
+                        "qemu-img snapshot -l failed for {}: {}",
+                        disk.display(),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                parse_snapshot_list_size(&String::from_utf8_lossy(&output.stdout), snapshot_name)
+                    .ok_or_else(|| {
+                        NovaError::ConfigError(format!(
+                            "Snapshot '{}' is not listed in {}",
+                            snapshot_name,
+                            disk.display()
+                        ))
+                    })
+            }),
+            _ => image_info(&snapshot_dir.join(format!("{}.qcow2", snapshot_id))).map(|info| {
+                let memory = std::fs::metadata(snapshot_dir.join(format!("{}.mem", snapshot_id)))
+                    .map_or(0, |meta| meta.len());
+                info.actual_size.unwrap_or(0) + memory
+            }),
+        };
+        size.unwrap_or_else(|err| {
+            log_warn!(
+                "Could not measure snapshot '{}' of '{}': {}",
+                snapshot_name,
+                vm_name,
+                err
+            );
+            0
+        })
     }
 
     /// Revert VM to a specific snapshot
@@ -465,7 +804,7 @@ impl TemplateManager {
 
         if was_running {
             log_info!("Stopping VM before snapshot revert");
-            let output = Command::new("virsh").args(["destroy", vm_name]).output()?;
+            let output = exec::run(Command::new("virsh").args(["destroy", vm_name]))?;
 
             if !output.status.success() {
                 log_error!("Failed to stop VM");
@@ -474,10 +813,11 @@ impl TemplateManager {
         }
 
         // Revert snapshot using virsh
-        let output = Command::new("virsh")
-            .args(["snapshot-revert", vm_name, snapshot_name])
-            .arg("--force")
-            .output()?;
+        let output = exec::run(
+            Command::new("virsh")
+                .args(["snapshot-revert", vm_name, snapshot_name])
+                .arg("--force"),
+        )?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -491,11 +831,12 @@ impl TemplateManager {
                 snap.is_current = snap.name == snapshot_name;
             }
         }
+        self.save_snapshots()?;
 
         // Restart VM if it was running
         if was_running {
             log_info!("Restarting VM after snapshot revert");
-            let output = Command::new("virsh").args(["start", vm_name]).output()?;
+            let output = exec::run(Command::new("virsh").args(["start", vm_name]))?;
 
             if !output.status.success() {
                 log_error!("Failed to restart VM");
@@ -506,6 +847,438 @@ impl TemplateManager {
         Ok(())
     }
 
+    /// Revert like [`Self::revert_to_snapshot`], but first take a
+    /// `pre-revert-<timestamp>` snapshot of the current state so the revert
+    /// can be undone. Returns the safety snapshot's name.
+    pub async fn revert_to_snapshot_safe(
+        &mut self,
+        vm_name: &str,
+        snapshot_name: &str,
+    ) -> Result<String> {
+        if !self
+            .list_snapshots(vm_name)
+            .iter()
+            .any(|s| s.name == snapshot_name)
+        {
+            return Err(NovaError::SnapshotNotFound(snapshot_name.to_string()));
+        }
+
+        let safety_name = pre_revert_snapshot_name(Utc::now());
+        let description = format!("Automatic snapshot before reverting to '{}'", snapshot_name);
+        // Quota pruning must not delete the snapshot being reverted to
+        let safety_id = self
+            .create_snapshot_keeping(
+                vm_name,
+                &safety_name,
+                &description,
+                true,
+                Some(snapshot_name),
+            )
+            .await?;
+        if let Some(safety) = self
+            .snapshots
+            .get_mut(vm_name)
+            .and_then(|snapshots| snapshots.get_mut(&safety_id))
+        {
+            safety.auto_created = true;
+        }
+        self.save_snapshots()?;
+
+        self.revert_to_snapshot(vm_name, snapshot_name).await?;
+        Ok(safety_name)
+    }
+
+    fn save_snapshots(&self) -> Result<()> {
+        // A dry run leaves the record as it was, like the domain itself
+        if exec::is_dry_run() {
+            return Ok(());
+        }
+        std::fs::write(
+            self.templates_dir.join(SNAPSHOTS_FILE),
+            serde_json::to_string_pretty(&self.snapshots)?,
+        )?;
+        Ok(())
+    }
+
+    /// Export a snapshot to a standalone tar archive for off-host backup.
+    /// Each captured disk becomes a flattened qcow2 image; with
+    /// `include_memory`, an external memory state is added too. Images are
+    /// checked with `qemu-img check` and their SHA-256 recorded before the
+    /// archive is written.
+    pub async fn export_snapshot(
+        &self,
+        vm_name: &str,
+        snapshot_name: &str,
+        output: &Path,
+        include_memory: bool,
+        reporter: &mut Reporter,
+    ) -> Result<SnapshotExport> {
+        log_info!(
+            "Exporting snapshot '{}' of VM '{}' to {}",
+            snapshot_name,
+            vm_name,
+            output.display()
+        );
+        validate_snapshot_name(snapshot_name)?;
+        self.ensure_vm_exists(vm_name)?;
+        if output.exists() {
+            return Err(NovaError::ConfigError(format!(
+                "{} already exists",
+                output.display()
+            )));
+        }
+
+        let dump = Command::new("virsh")
+            .args(["snapshot-dumpxml", vm_name, snapshot_name])
+            .output()?;
+        if !dump.status.success() {
+            return Err(NovaError::SnapshotNotFound(snapshot_name.to_string()));
+        }
+        let layout = snapshot_archive::parse_snapshot_xml(&String::from_utf8_lossy(&dump.stdout))?;
+        if layout.disks.is_empty() {
+            return Err(NovaError::ConfigError(format!(
+                "Snapshot '{}' captured no disks",
+                snapshot_name
+            )));
+        }
+
+        // Stage next to the output so large images stay on the same filesystem
+        let parent = output
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let staging = tempfile::Builder::new()
+            .prefix(".nova-snapshot-export-")
+            .tempdir_in(parent)?;
+        std::fs::create_dir_all(staging.path().join("disks"))?;
+
+        let mut disk_files = Vec::new();
+        for (index, disk) in layout.disks.iter().enumerate() {
+            let archive_path = format!("disks/{}.qcow2", disk.target);
+            let image = staging.path().join(&archive_path);
+            reporter.stage(format!(
+                "Converting {} ({}/{})",
+                disk.target,
+                index + 1,
+                layout.disks.len()
+            ));
+            let mut convert = Command::new("qemu-img");
+            // -U: the VM may still be running on these images
+            convert.args(["convert", "-p", "-U", "-O", "qcow2"]);
+            if disk.kind == CaptureKind::Internal {
+                convert.args(["-l", &format!("snapshot.name={}", snapshot_name)]);
+            }
+            let output = progress::qemu_img_output(convert.arg(&disk.image).arg(&image), reporter)?;
+            if !output.status.success() {
+                log_error!(
+                    "Failed to convert {} for export: {}",
+                    disk.image.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                return Err(NovaError::SystemCommandFailed);
+            }
+            reporter.stage(format!("Checking {}", disk.target));
+            snapshot_archive::check_image(&image)?;
+            disk_files.push((
+                snapshot_archive::archived_file(&disk.target, &archive_path, &image)?,
+                image,
+            ));
+        }
+
+        let memory = match (&layout.memory, &layout.memory_file) {
+            _ if !include_memory => None,
+            (CaptureKind::External, Some(file)) => Some((
+                snapshot_archive::archived_file("memory", MEMORY_NAME, file)?,
+                file.clone(),
+            )),
+            (CaptureKind::Internal, _) => {
+                log_warn!(
+                    "Snapshot '{}' keeps its memory inside the disk image; exporting disks only",
+                    snapshot_name
+                );
+                None
+            }
+            _ => {
+                log_warn!(
+                    "Snapshot '{}' has no memory state; exporting disks only",
+                    snapshot_name
+                );
+                None
+            }
+        };
+
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            vm: vm_name.to_string(),
+            snapshot: snapshot_name.to_string(),
+            description: layout.description.clone(),
+            created_at: layout.created_at,
+            exported_at: Utc::now(),
+            disks: disk_files.iter().map(|(entry, _)| entry.clone()).collect(),
+            memory: memory.as_ref().map(|(entry, _)| entry.clone()),
+        };
+
+        reporter.stage(format!("Writing {}", output.display()));
+        let partial = staging.path().join("archive.tar");
+        {
+            let mut builder = tar::Builder::new(std::fs::File::create(&partial)?);
+            let mut append = |name: &str, data: &[u8]| -> Result<()> {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(manifest.exported_at.timestamp().max(0) as u64);
+                header.set_cksum();
+                builder.append_data(&mut header, name, data)?;
+                Ok(())
+            };
+            append(MANIFEST_NAME, &serde_json::to_vec_pretty(&manifest)?)?;
+            append(DOMAIN_XML_NAME, layout.domain_xml.as_bytes())?;
+            for (entry, file) in disk_files.iter().chain(memory.iter()) {
+                builder.append_path_with_name(file, &entry.path)?;
+            }
+            builder.into_inner()?.sync_all()?;
+        }
+
+        // Read the archive back: every manifest entry must be there in full
+        let mut archive = tar::Archive::new(std::fs::File::open(&partial)?);
+        let mut stored: HashMap<String, u64> = HashMap::new();
+        for entry in archive.entries()? {
+            let entry = entry?;
+            stored.insert(
+                entry.path()?.to_string_lossy().into_owned(),
+                entry.header().size()?,
+            );
+        }
+        if let Some(entry) = manifest
+            .files()
+            .find(|entry| stored.get(&entry.path) != Some(&entry.size_bytes))
+        {
+            return Err(NovaError::ConfigError(format!(
+                "Exported archive is missing or truncated '{}'",
+                entry.path
+            )));
+        }
+
+        std::fs::rename(&partial, output)?;
+        let size_bytes = std::fs::metadata(output)?.len();
+        log_info!(
+            "Snapshot '{}' exported to {} ({} bytes)",
+            snapshot_name,
+            output.display(),
+            size_bytes
+        );
+        Ok(SnapshotExport {
+            archive: output.to_path_buf(),
+            size_bytes,
+            manifest,
+        })
+    }
+
+    /// Import an archive written by [`Self::export_snapshot`], either as a
+    /// snapshot of an existing VM or as a new VM. Every file is checked
+    /// against the manifest before libvirt is touched.
+    pub async fn import_snapshot(
+        &mut self,
+        archive: &Path,
+        target: ImportTarget,
+    ) -> Result<SnapshotImport> {
+        log_info!("Importing snapshot archive {}", archive.display());
+
+        let (vm_name, directory) = match &target {
+            ImportTarget::NewVm(name) => {
+                naming::validate(NameKind::Vm, name)?;
+                if self.ensure_vm_exists(name).is_ok() {
+                    return Err(NovaError::ConfigError(format!(
+                        "VM '{}' already exists",
+                        name
+                    )));
+                }
+                (
+                    name.clone(),
+                    PathBuf::from("/var/lib/libvirt/images").join(name),
+                )
+            }
+            ImportTarget::Snapshot { vm } => {
+                let manifest = read_manifest(archive)?;
+                // Both names come from the archive and end up in a path
+                validate_snapshot_name(&manifest.snapshot)?;
+                let vm = vm.clone().unwrap_or(manifest.vm);
+                naming::validate(NameKind::Vm, &vm)?;
+                self.ensure_vm_exists(&vm)?;
+                let directory = PathBuf::from("/var/lib/nova/snapshots")
+                    .join(&vm)
+                    .join(format!("imported-{}", manifest.snapshot));
+                (vm, directory)
+            }
+        };
+        if directory.exists() {
+            return Err(NovaError::ConfigError(format!(
+                "{} already exists; remove it or import under another name",
+                directory.display()
+            )));
+        }
+
+        std::fs::create_dir_all(&directory)?;
+        let imported = self.unpack_archive(archive, &directory, &vm_name, &target);
+        if imported.is_err() {
+            let _ = std::fs::remove_dir_all(&directory);
+        }
+        imported
+    }
+
+    fn unpack_archive(
+        &mut self,
+        archive: &Path,
+        directory: &Path,
+        vm_name: &str,
+        target: &ImportTarget,
+    ) -> Result<SnapshotImport> {
+        tar::Archive::new(std::fs::File::open(archive)?).unpack(directory)?;
+
+        let manifest: ArchiveManifest =
+            serde_json::from_slice(&std::fs::read(directory.join(MANIFEST_NAME))?)?;
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err(NovaError::ConfigError(format!(
+                "Archive format {} is newer than this Nova supports ({})",
+                manifest.format_version, ARCHIVE_FORMAT_VERSION
+            )));
+        }
+        for entry in manifest.files() {
+            snapshot_archive::verify_file(entry, &directory.join(&entry.path))?;
+        }
+        let disks: Vec<(String, PathBuf)> = manifest
+            .disks
+            .iter()
+            .map(|entry| (entry.name.clone(), directory.join(&entry.path)))
+            .collect();
+        for (_, image) in &disks {
+            snapshot_archive::check_image(image)?;
+        }
+        let domain_xml = snapshot_archive::retarget_disks(
+            &std::fs::read_to_string(directory.join(DOMAIN_XML_NAME))?,
+            &disks,
+        );
+
+        let snapshot_name = manifest.snapshot.clone();
+        match target {
+            ImportTarget::NewVm(name) => {
+                let xml_path = directory.join(DOMAIN_XML_NAME);
+                std::fs::write(
+                    &xml_path,
+                    snapshot_archive::rename_domain(&domain_xml, name),
+                )?;
+                let output = Command::new("virsh")
+                    .arg("define")
+                    .arg(&xml_path)
+                    .output()?;
+                if !output.status.success() {
+                    return Err(NovaError::ConfigError(format!(
+                        "libvirt rejected the imported VM '{}': {}",
+                        name,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+            }
+            ImportTarget::Snapshot { .. } => {
+                validate_snapshot_name(&snapshot_name)?;
+                let exists = Command::new("virsh")
+                    .args(["snapshot-info", vm_name, &snapshot_name])
+                    .output()
+                    .is_ok_and(|output| output.status.success());
+                if exists {
+                    return Err(NovaError::ConfigError(format!(
+                        "VM '{}' already has a snapshot '{}'",
+                        vm_name, snapshot_name
+                    )));
+                }
+
+                // External snapshots record the overlay created on top of
+                // the captured state; start with an empty one
+                let mut overlays = Vec::new();
+                for (disk_target, image) in &disks {
+                    let overlay = directory.join(format!("{}.overlay.qcow2", disk_target));
+                    let output = Command::new("qemu-img")
+                        .args(["create", "-f", "qcow2", "-F", "qcow2", "-b"])
+                        .arg(image)
+                        .arg(&overlay)
+                        .output()?;
+                    if !output.status.success() {
+                        log_error!(
+                            "Failed to create overlay for {}: {}",
+                            disk_target,
+                            String::from_utf8_lossy(&output.stderr)
+                        );
+                        return Err(NovaError::SystemCommandFailed);
+                    }
+                    overlays.push((disk_target.clone(), overlay));
+                }
+
+                let xml_path = directory.join("snapshot.xml");
+                std::fs::write(
+                    &xml_path,
+                    snapshot_archive::redefine_xml(
+                        &manifest,
+                        &snapshot_name,
+                        &overlays,
+                        &domain_xml,
+                    ),
+                )?;
+                let output = Command::new("virsh")
+                    .args(["snapshot-create", vm_name])
+                    .arg(&xml_path)
+                    .arg("--redefine")
+                    .output()?;
+                if !output.status.success() {
+                    return Err(NovaError::ConfigError(format!(
+                        "libvirt rejected the imported snapshot: {}. Import it as a new VM with --as-vm instead",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+
+                let snapshot_id = uuid::Uuid::new_v4().to_string();
+                self.snapshots
+                    .entry(vm_name.to_string())
+                    .or_default()
+                    .insert(
+                        snapshot_id.clone(),
+                        VmSnapshot {
+                            id: snapshot_id,
+                            vm_name: vm_name.to_string(),
+                            name: snapshot_name.clone(),
+                            description: manifest.description.clone(),
+                            snapshot_type: SnapshotType::External,
+                            created_at: manifest.created_at.unwrap_or_else(Utc::now),
+                            size_bytes: manifest.disks.iter().map(|disk| disk.size_bytes).sum(),
+                            vm_state: VmState::Shutdown,
+                            parent_snapshot: None,
+                            children: Vec::new(),
+                            is_current: false,
+                            auto_created: false,
+                        },
+                    );
+                self.save_snapshots()?;
+            }
+        }
+
+        log_info!(
+            "Imported snapshot '{}' into {} ({})",
+            snapshot_name,
+            vm_name,
+            directory.display()
+        );
+        Ok(SnapshotImport {
+            vm: vm_name.to_string(),
+            snapshot: snapshot_name,
+            new_vm: matches!(target, ImportTarget::NewVm(_)),
+            directory: directory.to_path_buf(),
+            disks: disks.into_iter().map(|(_, image)| image).collect(),
+            memory: manifest
+                .memory
+                .as_ref()
+                .map(|memory| directory.join(&memory.path)),
+        })
+    }
+
     /// Delete a snapshot
     pub async fn delete_snapshot(
         &mut self,
@@ -515,25 +1288,42 @@ impl TemplateManager {
     ) -> Result<()> {
         log_info!("Deleting snapshot '{}' for VM '{}'", snapshot_name, vm_name);
 
-        // Find snapshot and collect info we need
-        let (has_children, parent_snapshot) = {
-            let snapshot = self
-                .snapshots
-                .get(vm_name)
-                .and_then(|snapshots| snapshots.values().find(|s| s.name == snapshot_name))
-                .ok_or_else(|| NovaError::SnapshotNotFound(snapshot_name.to_string()))?;
-
-            (
-                !snapshot.children.is_empty(),
-                snapshot.parent_snapshot.clone(),
-            )
-        };
+        let has_children = !self
+            .snapshots
+            .get(vm_name)
+            .and_then(|snapshots| snapshots.values().find(|s| s.name == snapshot_name))
+            .ok_or_else(|| NovaError::SnapshotNotFound(snapshot_name.to_string()))?
+            .children
+            .is_empty();
 
         // Check for children
         if has_children && !delete_children {
             return Err(NovaError::SnapshotHasChildren);
         }
 
+        self.remove_snapshot(vm_name, snapshot_name, delete_children)
+            .await?;
+
+        log_info!("Snapshot '{}' deleted successfully", snapshot_name);
+        Ok(())
+    }
+
+    /// Delete a snapshot without the child check; without `delete_children`
+    /// its children are re-parented to its parent.
+    async fn remove_snapshot(
+        &mut self,
+        vm_name: &str,
+        snapshot_name: &str,
+        delete_children: bool,
+    ) -> Result<()> {
+        let parent_snapshot = self
+            .snapshots
+            .get(vm_name)
+            .and_then(|snapshots| snapshots.values().find(|s| s.name == snapshot_name))
+            .ok_or_else(|| NovaError::SnapshotNotFound(snapshot_name.to_string()))?
+            .parent_snapshot
+            .clone();
+
         // Delete using virsh
         let mut cmd = Command::new("virsh");
         cmd.args(["snapshot-delete", vm_name, snapshot_name]);
@@ -542,7 +1332,7 @@ impl TemplateManager {
             cmd.arg("--children");
         }
 
-        let output = cmd.output()?;
+        let output = exec::run(&mut cmd)?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -564,8 +1354,16 @@ impl TemplateManager {
                 }
             }
         }
+        self.save_snapshots()?;
 
-        log_info!("Snapshot '{}' deleted successfully", snapshot_name);
+        Ok(())
+    }
+
+    /// Drop the snapshot records of a VM that no longer exists.
+    pub fn forget_vm_snapshots(&mut self, vm_name: &str) -> Result<()> {
+        if self.snapshots.remove(vm_name).is_some() {
+            self.save_snapshots()?;
+        }
         Ok(())
     }
 
@@ -657,32 +1455,107 @@ impl TemplateManager {
         source_vm: &str,
         new_vm_name: &str,
         clone_disks: bool,
+        reporter: &mut Reporter,
     ) -> Result<()> {
         log_info!("Cloning VM '{}' to new VM '{}'", source_vm, new_vm_name);
 
         // Ensure source VM is shut down
+        reporter.stage(format!("Making sure '{}' is shut off", source_vm));
         self.ensure_vm_shutdown(source_vm).await?;
 
-        // Use virt-clone for the operation
-        let mut cmd = Command::new("virt-clone");
-        cmd.args(["--original", source_vm, "--name", new_vm_name]);
-
-        if clone_disks {
-            cmd.arg("--auto-clone");
-        } else {
+        if !clone_disks {
             // Share disks instead of cloning
-            cmd.arg("--preserve-data");
+            reporter.stage("Defining the clone with virt-clone");
+            return self.run_virt_clone(source_vm, new_vm_name, &[]);
         }
 
-        let output = cmd.output()?;
+        // Copy the disks ourselves so the copy can report progress and be
+        // checked, then let virt-clone define the domain around the copies
+        let disks = self.vm_disks(source_vm)?;
+        let mut copies = Vec::new();
+        for (index, (target, image)) in disks.iter().enumerate() {
+            let copy = clone_disk_path(image, new_vm_name, target, index);
+            if copy.exists() {
+                return Err(NovaError::ConfigError(format!(
+                    "{} already exists",
+                    copy.display()
+                )));
+            }
+            copies.push((target.as_str(), image.as_path(), copy));
+        }
+
+        let result = self.copy_disks(&copies, reporter).and_then(|_| {
+            reporter.stage(format!("Defining '{}'", new_vm_name));
+            let files: Vec<&Path> = copies.iter().map(|(_, _, copy)| copy.as_path()).collect();
+            self.run_virt_clone(source_vm, new_vm_name, &files)
+        });
+        if result.is_err() {
+            for (_, _, copy) in &copies {
+                let _ = std::fs::remove_file(copy);
+            }
+        }
+        result?;
+
+        log_info!("VM '{}' cloned successfully", new_vm_name);
+        Ok(())
+    }
+
+    /// Copy each (target, source, copy) disk with `qemu-img convert`,
+    /// keeping its format, and check the copy before moving on.
+    fn copy_disks(&self, copies: &[(&str, &Path, PathBuf)], reporter: &mut Reporter) -> Result<()> {
+        for (index, (target, source, copy)) in copies.iter().enumerate() {
+            let info = image_info(source)?;
+            reporter.stage_bytes(
+                format!("Copying {} ({}/{})", target, index + 1, copies.len()),
+                info.virtual_size,
+            );
+            let output = progress::qemu_img_output(
+                Command::new("qemu-img")
+                    .args(["convert", "-p", "-f", &info.format, "-O", &info.format])
+                    .arg(source)
+                    .arg(copy),
+                reporter,
+            )?;
+            if !output.status.success() {
+                log_error!(
+                    "Failed to copy {}: {}",
+                    source.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                return Err(NovaError::SystemCommandFailed);
+            }
+
+            if info.format == "raw" {
+                log_debug!("Skipping qemu-img check for raw image {}", copy.display());
+            } else {
+                reporter.stage(format!("Checking {}", target));
+                snapshot_archive::check_image_as(copy, &info.format)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `virt-clone` with the given disk images used as they are, or with
+    /// the source disks shared when `files` is empty.
+    fn run_virt_clone(&self, source_vm: &str, new_vm_name: &str, files: &[&Path]) -> Result<()> {
+        let mut cmd = Command::new("virt-clone");
+        cmd.args([
+            "--original",
+            source_vm,
+            "--name",
+            new_vm_name,
+            "--preserve-data",
+        ]);
+        for file in files {
+            cmd.arg("--file").arg(file);
+        }
 
+        let output = cmd.output()?;
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
             log_error!("Failed to clone VM: {}", error);
             return Err(NovaError::SystemCommandFailed);
         }
-
-        log_info!("VM '{}' cloned successfully", new_vm_name);
         Ok(())
     }
 
@@ -721,7 +1594,12 @@ impl TemplateManager {
     }
 
     /// Create linked clone (uses backing store)
-    pub async fn create_linked_clone(&mut self, source_vm: &str, new_vm_name: &str) -> Result<()> {
+    pub async fn create_linked_clone(
+        &mut self,
+        source_vm: &str,
+        new_vm_name: &str,
+        reporter: &mut Reporter,
+    ) -> Result<()> {
         log_info!(
             "Creating linked clone of VM '{}' as '{}'",
             source_vm,
@@ -734,17 +1612,19 @@ impl TemplateManager {
         // Create qcow2 image with backing file
         let new_disk = PathBuf::from(format!("/var/lib/libvirt/images/{}.qcow2", new_vm_name));
 
+        if new_disk.exists() {
+            return Err(NovaError::ConfigError(format!(
+                "{} already exists",
+                new_disk.display()
+            )));
+        }
+
+        reporter.stage(format!("Creating overlay on {}", source_disk.display()));
+        let source_format = image_info(&source_disk)?.format;
         let output = Command::new("qemu-img")
-            .args([
-                "create",
-                "-f",
-                "qcow2",
-                "-F",
-                "qcow2",
-                "-b",
-                source_disk.to_str().unwrap(),
-                new_disk.to_str().unwrap(),
-            ])
+            .args(["create", "-f", "qcow2", "-F", &source_format, "-b"])
+            .arg(&source_disk)
+            .arg(&new_disk)
             .output()?;
 
         if !output.status.success() {
@@ -753,7 +1633,14 @@ impl TemplateManager {
             return Err(NovaError::SystemCommandFailed);
         }
 
+        reporter.stage(format!("Checking {}", new_disk.display()));
+        if let Err(err) = verify_overlay(&new_disk, &source_disk) {
+            let _ = std::fs::remove_file(&new_disk);
+            return Err(err);
+        }
+
         // Clone VM with new disk
+        reporter.stage(format!("Defining '{}'", new_vm_name));
         self.clone_vm_with_custom_disks(
             source_vm,
             new_vm_name,
@@ -761,19 +1648,489 @@ impl TemplateManager {
         )
         .await?;
 
+        if let Err(err) = record_linked_clone(&self.templates_dir, new_vm_name, source_vm) {
+            log_warn!(
+                "Failed to record '{}' as a linked clone: {}",
+                new_vm_name,
+                err
+            );
+        }
+
         log_info!("Linked clone created successfully");
         Ok(())
     }
+
+    /// Follow the backing chain of every file-backed disk of `vm_name`.
+    /// Chains with a missing file are reported through
+    /// [`BackingChain::missing`] rather than as an error.
+    pub fn validate_clone(&self, vm_name: &str) -> Result<Vec<BackingChain>> {
+        let disks = self.vm_disks(vm_name)?;
+        if disks.is_empty() {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' has no file-backed disk",
+                vm_name
+            )));
+        }
+        disks
+            .into_iter()
+            .map(|(target, disk)| {
+                // One call covers a healthy chain; it fails as soon as any
+                // layer is missing, so only then walk it to find the break
+                let output = Command::new("qemu-img")
+                    .args(["info", "-U", "--backing-chain", "--output=json"])
+                    .arg(&disk)
+                    .output()?;
+                if output.status.success() {
+                    let layers = parse_backing_chain(&String::from_utf8_lossy(&output.stdout))?;
+                    return Ok(BackingChain {
+                        target,
+                        files: layers
+                            .into_iter()
+                            .map(|l| PathBuf::from(l.filename))
+                            .collect(),
+                        missing: None,
+                    });
+                }
+                walk_backing_chain(target, &disk, image_info)
+            })
+            .collect()
+    }
+}
+
+/// A disk and the files behind it, overlay first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackingChain {
+    pub target: String,
+    /// Files that exist, starting with the disk image itself
+    pub files: Vec<PathBuf>,
+    /// Backing file named by the last entry in `files` that does not exist
+    pub missing: Option<PathBuf>,
+}
+
+impl BackingChain {
+    pub fn is_broken(&self) -> bool {
+        self.missing.is_some()
+    }
+}
+
+/// Record of linked clones and their sources, kept in the templates directory.
+const LINKED_CLONES_FILE: &str = "linked-clones.json";
+
+fn read_linked_clones(templates_dir: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(templates_dir.join(LINKED_CLONES_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn record_linked_clone(templates_dir: &Path, clone: &str, source: &str) -> Result<()> {
+    let mut clones = read_linked_clones(templates_dir);
+    clones.insert(clone.to_string(), source.to_string());
+    std::fs::write(
+        templates_dir.join(LINKED_CLONES_FILE),
+        serde_json::to_string_pretty(&clones)?,
+    )?;
+    Ok(())
+}
+
+const SNAPSHOTS_FILE: &str = "snapshots.json";
+
+/// Snapshot metadata recorded by earlier runs, keyed by VM then snapshot ID.
+fn read_snapshots(templates_dir: &Path) -> HashMap<String, HashMap<String, VmSnapshot>> {
+    std::fs::read_to_string(templates_dir.join(SNAPSHOTS_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Name of the safety snapshot `revert --safe` takes at `now`.
+pub fn pre_revert_snapshot_name(now: DateTime<Utc>) -> String {
+    format!("pre-revert-{}", now.format("%Y%m%d-%H%M%S"))
+}
+
+/// Linked clones Nova created from `source`, sorted by name.
+pub fn linked_clones_of(templates_dir: &Path, source: &str) -> Vec<String> {
+    let mut clones: Vec<String> = read_linked_clones(templates_dir)
+        .into_iter()
+        .filter(|(_, clone_source)| clone_source == source)
+        .map(|(clone, _)| clone)
+        .collect();
+    clones.sort();
+    clones
+}
+
+/// Drop a deleted VM from the linked-clone record.
+pub fn forget_linked_clone(templates_dir: &Path, vm_name: &str) -> Result<()> {
+    let mut clones = read_linked_clones(templates_dir);
+    if clones.remove(vm_name).is_some() && !exec::is_dry_run() {
+        std::fs::write(
+            templates_dir.join(LINKED_CLONES_FILE),
+            serde_json::to_string_pretty(&clones)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// Source VM of `vm_name` if Nova created it as a linked clone.
+pub fn linked_clone_source(templates_dir: &Path, vm_name: &str) -> Option<String> {
+    read_linked_clones(templates_dir).remove(vm_name)
+}
+
+/// Layers from `qemu-img info --backing-chain --output=json`, overlay first.
+pub fn parse_backing_chain(json: &str) -> Result<Vec<ImageInfo>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Follow backing files from `disk` one header at a time, stopping at the
+/// first file that does not exist.
+fn walk_backing_chain(
+    target: String,
+    disk: &Path,
+    info: impl Fn(&Path) -> Result<ImageInfo>,
+) -> Result<BackingChain> {
+    const MAX_DEPTH: usize = 64;
+
+    let mut chain = BackingChain {
+        target,
+        files: Vec::new(),
+        missing: None,
+    };
+    let mut current = disk.to_path_buf();
+    for _ in 0..MAX_DEPTH {
+        if !current.exists() {
+            chain.missing = Some(current);
+            return Ok(chain);
+        }
+        let layer = info(&current)?;
+        chain.files.push(current.clone());
+        let Some(backing) = layer.full_backing_filename.or(layer.backing_filename) else {
+            return Ok(chain);
+        };
+        // Relative backing files are relative to the overlay's directory
+        current = match current.parent() {
+            Some(parent) => parent.join(backing),
+            None => PathBuf::from(backing),
+        };
+    }
+    Err(NovaError::ConfigError(format!(
+        "Backing chain of {} is deeper than {} images",
+        disk.display(),
+        MAX_DEPTH
+    )))
+}
+
+/// Fields of `qemu-img info --output=json` that cloning relies on.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImageInfo {
+    pub filename: String,
+    pub format: String,
+    #[serde(rename = "virtual-size")]
+    pub virtual_size: u64,
+    /// Backing file as recorded in the image header
+    #[serde(rename = "backing-filename")]
+    pub backing_filename: Option<String>,
+    /// The recorded backing file resolved against the image's directory
+    #[serde(rename = "full-backing-filename")]
+    pub full_backing_filename: Option<String>,
+    /// Bytes allocated on the host for this image alone
+    #[serde(rename = "actual-size", default)]
+    pub actual_size: Option<u64>,
+}
+
+/// VM SIZE of the internal snapshot tagged `name` in `qemu-img snapshot -l`
+/// output. Newer QEMU prints the size as `1.02 GiB`, older as `1.0G`.
+pub fn parse_snapshot_list_size(output: &str, name: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 || fields[1] != name || fields[0].parse::<u64>().is_err() {
+            return None;
+        }
+        let (number, unit) = match fields.get(3) {
+            Some(unit) if unit.chars().all(|c| c.is_ascii_alphabetic()) => {
+                (fields[2], unit.trim_end_matches("iB").trim_end_matches('B'))
+            }
+            _ => {
+                let split = fields[2]
+                    .find(|c: char| c.is_ascii_alphabetic())
+                    .unwrap_or(fields[2].len());
+                (&fields[2][..split], &fields[2][split..])
+            }
+        };
+        let shift = match unit {
+            "" => 0,
+            "K" | "k" => 10,
+            "M" => 20,
+            "G" => 30,
+            "T" => 40,
+            _ => return None,
+        };
+        let value: f64 = number.parse().ok()?;
+        Some((value * (1u64 << shift) as f64).round() as u64)
+    })
+}
+
+pub fn parse_image_info(json: &str) -> Result<ImageInfo> {
+    Ok(serde_json::from_str(json)?)
+}
+
+fn image_info(image: &Path) -> Result<ImageInfo> {
+    // -U: reading the header is safe while a VM holds the image
+    let output = Command::new("qemu-img")
+        .args(["info", "-U", "--output=json"])
+        .arg(image)
+        .output()?;
+    if !output.status.success() {
+        return Err(NovaError::ConfigError(format!(
+            "qemu-img info failed for {}: {}",
+            image.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    parse_image_info(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Check a new linked-clone overlay and that its header points at `backing`.
+fn verify_overlay(overlay: &Path, backing: &Path) -> Result<()> {
+    snapshot_archive::check_image(overlay)?;
+    let info = image_info(overlay)?;
+    let recorded = info
+        .full_backing_filename
+        .as_deref()
+        .or(info.backing_filename.as_deref());
+    if recorded.map(Path::new) != Some(backing) {
+        return Err(NovaError::ConfigError(format!(
+            "Overlay {} has backing file {} instead of {}",
+            overlay.display(),
+            recorded.unwrap_or("(none)"),
+            backing.display()
+        )));
+    }
+    Ok(())
+}
+
+/// File-backed disks from `virsh domblklist --details`, as (target, source).
+pub(crate) fn parse_domblklist_disks(output: &str) -> Vec<(String, PathBuf)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["file", "disk", target, source @ ..] if !source.is_empty() && source[0] != "-" => {
+                    Some((target.to_string(), PathBuf::from(source.join(" "))))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Where a full clone's copy of `source` goes: next to it, named after the
+/// new VM (with the target appended for every disk but the first).
+fn clone_disk_path(source: &Path, new_vm_name: &str, target: &str, index: usize) -> PathBuf {
+    let extension = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("img");
+    let name = if index == 0 {
+        format!("{}.{}", new_vm_name, extension)
+    } else {
+        format!("{}-{}.{}", new_vm_name, target, extension)
+    };
+    source.with_file_name(name)
 }
 
 // Helper structs
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct VmInfo {
     cpu_cores: u32,
     memory_mb: u64,
     disk_size_gb: u64,
 }
 
+/// vCPUs and maximum memory from `virsh dominfo`; the disk size is filled
+/// in separately from the image.
+fn parse_dominfo(output: &str) -> VmInfo {
+    let mut info = VmInfo {
+        cpu_cores: 1,
+        memory_mb: 0,
+        disk_size_gb: 0,
+    };
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "CPU(s)" => info.cpu_cores = value.parse().unwrap_or(info.cpu_cores),
+            "Max memory" => {
+                if let Some(kib) = value
+                    .strip_suffix("KiB")
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                {
+                    info.memory_mb = kib / 1024;
+                }
+            }
+            _ => {}
+        }
+    }
+    info
+}
+
+/// The `<libosinfo:os id="..."/>` of a domain XML, if virt-install set one.
+fn libosinfo_id(xml: &str) -> Option<String> {
+    let start = xml.find("<libosinfo:os ")?;
+    let element = &xml[start..];
+    let element = &element[..element.find('>')?];
+    let id = element.split("id=").nth(1)?;
+    let quote = id.chars().next()?;
+    let id = &id[1..];
+    Some(id[..id.find(quote)?].to_string())
+}
+
+/// Map a libosinfo ID such as `http://ubuntu.com/ubuntu/22.04` or
+/// `http://microsoft.com/win/11` onto a template OS.
+pub fn os_from_libosinfo_id(id: &str) -> OperatingSystem {
+    let path = id
+        .trim_start_matches("http://")
+        .trim_start_matches("https://");
+    let parts: Vec<&str> = path.split('/').collect();
+    let version = parts.get(2).map(|v| v.to_string()).unwrap_or_default();
+
+    match parts.as_slice() {
+        ["microsoft.com", "win", ver, ..] => {
+            let version = match *ver {
+                "11" => Some(WindowsVersion::Windows11),
+                "10" => Some(WindowsVersion::Windows10),
+                "2k22" => Some(WindowsVersion::WindowsServer2022),
+                "2k19" => Some(WindowsVersion::WindowsServer2019),
+                "2k16" => Some(WindowsVersion::WindowsServer2016),
+                _ => None,
+            };
+            match version {
+                Some(version) => OperatingSystem::Windows { version },
+                None => OperatingSystem::Other {
+                    name: format!("windows {}", ver),
+                },
+            }
+        }
+        ["ubuntu.com", "ubuntu", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::Ubuntu { version },
+        },
+        ["archlinux.org", "archlinux", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::Arch,
+        },
+        ["fedoraproject.org", "fedora", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::Fedora { version },
+        },
+        ["debian.org", "debian", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::Debian { version },
+        },
+        ["centos.org", "centos" | "centos-stream", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::CentOS { version },
+        },
+        ["opensuse.org", "opensuse" | "opensuse-tumbleweed", ..] => OperatingSystem::Linux {
+            distro: LinuxDistro::OpenSUSE { version },
+        },
+        _ => OperatingSystem::Other {
+            name: parts
+                .get(1)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| id.to_string()),
+        },
+    }
+}
+
+/// Every template under `templates_dir` with readable metadata.
+pub fn read_templates(templates_dir: &Path) -> Vec<VmTemplate> {
+    let Ok(entries) = std::fs::read_dir(templates_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path().join("template.json"))
+        .filter_map(|metadata| std::fs::read_to_string(metadata).ok())
+        .filter_map(|content| serde_json::from_str::<VmTemplate>(&content).ok())
+        .collect()
+}
+
+impl VmTemplate {
+    /// The New VM dialog's "Quick Start" entry for a captured template.
+    pub fn quick_start(&self) -> crate::config::VmTemplateConfig {
+        let os_type = match &self.os_type {
+            OperatingSystem::Windows { .. } => "windows".to_string(),
+            OperatingSystem::Linux { .. } => "linux".to_string(),
+            OperatingSystem::Other { name } => name.clone(),
+        };
+        // Firmware and TPM follow the captured domain XML
+        let xml = std::fs::read_to_string(&self.config_path).unwrap_or_default();
+        let mut tags = self.tags.clone();
+        if !tags.iter().any(|tag| tag == "captured") {
+            tags.push("captured".to_string());
+        }
+        crate::config::VmTemplateConfig {
+            name: self.name.clone(),
+            description: if self.description.is_empty() {
+                format!("Captured template ({})", self.disk_path.display())
+            } else {
+                self.description.clone()
+            },
+            os_type,
+            cpu: self.cpu_cores,
+            memory: format!("{}M", self.memory_mb),
+            disk_size: format!("{}G", self.disk_size_gb),
+            gpu_passthrough: false,
+            uefi: xml.contains("<loader") || xml.contains("firmware='efi'"),
+            secure_boot: xml.contains("secure='yes'"),
+            tpm: xml.contains("<tpm"),
+            iso_pattern: None,
+            network: self.network_config.network_name.clone(),
+            tags,
+        }
+    }
+}
+
+/// Manifest of a snapshot archive, read without unpacking the images.
+fn read_manifest(path: &Path) -> Result<ArchiveManifest> {
+    let mut archive = tar::Archive::new(std::fs::File::open(path)?);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if entry.path()?.as_os_str() == MANIFEST_NAME {
+            return Ok(serde_json::from_reader(entry)?);
+        }
+    }
+    Err(NovaError::ConfigError(format!(
+        "{} is not a Nova snapshot archive (no {})",
+        path.display(),
+        MANIFEST_NAME
+    )))
+}
+
+/// Check a snapshot name before it is handed to `virsh snapshot-create-as`:
+/// letters, digits, `.`, `_` and `-`, not starting with `-` or `.`.
+pub fn validate_snapshot_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(NovaError::ConfigError(
+            "Snapshot name cannot be empty".to_string(),
+        ));
+    }
+    if name.starts_with(['-', '.']) {
+        return Err(NovaError::ConfigError(format!(
+            "Snapshot name '{}' cannot start with '-' or '.'",
+            name
+        )));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+    {
+        return Err(NovaError::ConfigError(format!(
+            "Snapshot name '{}' contains unsupported character {:?}. Use letters, numbers, '.', '_' or '-'.",
+            name, c
+        )));
+    }
+    Ok(())
+}
+
 impl Default for NetworkTemplate {
     fn default() -> Self {
         Self {
@@ -784,3 +2141,400 @@ impl Default for NetworkTemplate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(name: &str, minutes: i64, size_bytes: u64, is_current: bool) -> VmSnapshot {
+        VmSnapshot {
+            auto_created: false,
+            id: name.to_string(),
+            vm_name: "web".to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            snapshot_type: SnapshotType::DiskOnly,
+            created_at: DateTime::from_timestamp(minutes * 60, 0).unwrap(),
+            size_bytes,
+            vm_state: VmState::Shutdown,
+            parent_snapshot: None,
+            children: Vec::new(),
+            is_current,
+        }
+    }
+
+    #[test]
+    fn quota_reports_violations_and_prunes_oldest() {
+        let quota = SnapshotQuota {
+            max_snapshots: Some(3),
+            max_snapshot_bytes: Some(1000),
+            auto_prune: true,
+        };
+        assert!(quota.violation(2, 500).is_none());
+        assert_eq!(
+            quota.violation(3, 500).as_deref(),
+            Some("3 of 3 snapshots used")
+        );
+        assert!(quota.violation(1, 1000).is_some());
+
+        // Oldest is current, so it is kept and the next two go instead
+        let snapshots = [
+            snapshot("base", 0, 100, true),
+            snapshot("pre-upgrade", 1, 400, false),
+            snapshot("nightly-1", 2, 400, false),
+            snapshot("nightly-2", 3, 50, false),
+        ];
+        let chronological: Vec<&VmSnapshot> = snapshots.iter().collect();
+        assert_eq!(
+            quota.prune_candidates(&chronological, None),
+            vec!["pre-upgrade".to_string(), "nightly-1".to_string()]
+        );
+
+        // A revert target is skipped like the current snapshot
+        assert_eq!(
+            quota.prune_candidates(&chronological, Some("pre-upgrade")),
+            vec!["nightly-1".to_string(), "nightly-2".to_string()]
+        );
+        assert!(SnapshotQuota::default().is_unlimited());
+    }
+
+    #[test]
+    fn linked_clone_record_tracks_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        record_linked_clone(dir.path(), "web-2", "web").unwrap();
+        record_linked_clone(dir.path(), "web-1", "web").unwrap();
+        record_linked_clone(dir.path(), "db-1", "db").unwrap();
+        assert_eq!(linked_clones_of(dir.path(), "web"), ["web-1", "web-2"]);
+        assert_eq!(
+            linked_clone_source(dir.path(), "db-1").as_deref(),
+            Some("db")
+        );
+
+        forget_linked_clone(dir.path(), "web-1").unwrap();
+        assert_eq!(linked_clones_of(dir.path(), "web"), ["web-2"]);
+        assert!(linked_clone_source(dir.path(), "web-1").is_none());
+        forget_linked_clone(dir.path(), "missing").unwrap();
+    }
+
+    #[test]
+    fn clone_disks_are_found_named_and_inspected() {
+        let blklist = " Type   Device   Target   Source
+------------------------------------------------------------------
+ file   disk     vda      /var/lib/libvirt/images/web.qcow2
+ file   disk     vdb      /srv/vm disks/web-data.raw
+ file   cdrom    sda      -
+ block  disk     vdc      /dev/sdb
+";
+        let disks = parse_domblklist_disks(blklist);
+        assert_eq!(
+            disks,
+            vec![
+                (
+                    "vda".to_string(),
+                    PathBuf::from("/var/lib/libvirt/images/web.qcow2")
+                ),
+                (
+                    "vdb".to_string(),
+                    PathBuf::from("/srv/vm disks/web-data.raw")
+                ),
+            ]
+        );
+        assert_eq!(
+            clone_disk_path(&disks[0].1, "web2", "vda", 0),
+            PathBuf::from("/var/lib/libvirt/images/web2.qcow2")
+        );
+        assert_eq!(
+            clone_disk_path(&disks[1].1, "web2", "vdb", 1),
+            PathBuf::from("/srv/vm disks/web2-vdb.raw")
+        );
+
+        let info = parse_image_info(
+            r#"{
+    "virtual-size": 214748364800,
+    "filename": "/var/lib/libvirt/images/web2.qcow2",
+    "format": "qcow2",
+    "actual-size": 200704,
+    "backing-filename": "/var/lib/libvirt/images/web.qcow2",
+    "full-backing-filename": "/var/lib/libvirt/images/web.qcow2",
+    "backing-filename-format": "qcow2",
+    "dirty-flag": false
+}"#,
+        )
+        .unwrap();
+        assert_eq!(info.format, "qcow2");
+        assert_eq!(info.virtual_size, 200 << 30);
+        assert_eq!(info.actual_size, Some(200704));
+        assert_eq!(
+            info.full_backing_filename.as_deref(),
+            Some("/var/lib/libvirt/images/web.qcow2")
+        );
+        let standalone =
+            parse_image_info(r#"{"virtual-size": 1024, "filename": "a.raw", "format": "raw"}"#)
+                .unwrap();
+        assert!(standalone.backing_filename.is_none());
+        assert!(standalone.actual_size.is_none());
+    }
+
+    #[test]
+    fn internal_snapshot_size_comes_from_the_vm_size_column() {
+        let current = "Snapshot list:
+ID        TAG               VM SIZE                DATE     VM CLOCK     ICOUNT
+1         clean                 0 B 2026-10-01 09:12:44 00:00:00.000          0
+2         running-app      1.02 GiB 2026-10-02 18:03:10 02:14:51.337          0
+";
+        assert_eq!(parse_snapshot_list_size(current, "clean"), Some(0));
+        assert_eq!(
+            parse_snapshot_list_size(current, "running-app"),
+            Some((1.02 * (1u64 << 30) as f64).round() as u64)
+        );
+        assert_eq!(parse_snapshot_list_size(current, "missing"), None);
+        // The header row is not a snapshot called "TAG"
+        assert_eq!(parse_snapshot_list_size(current, "TAG"), None);
+
+        let legacy = "Snapshot list:
+ID        TAG                 VM SIZE                DATE       VM CLOCK
+1         before-upgrade         512M 2019-05-04 10:00:00   00:01:02.003
+";
+        assert_eq!(
+            parse_snapshot_list_size(legacy, "before-upgrade"),
+            Some(512 << 20)
+        );
+    }
+
+    #[test]
+    fn captured_vm_details_come_from_dominfo_and_osinfo() {
+        let dominfo = "Id:             -
+Name:           web-base
+UUID:           0d3f2f4e-8f30-4a57-9a8b-3b1c2f3d4e5f
+OS Type:        hvm
+State:          shut off
+CPU(s):         4
+Max memory:     8388608 KiB
+Used memory:    8388608 KiB
+";
+        assert_eq!(
+            parse_dominfo(dominfo),
+            VmInfo {
+                cpu_cores: 4,
+                memory_mb: 8192,
+                disk_size_gb: 0,
+            }
+        );
+
+        let xml = r#"<domain type='kvm'>
+  <metadata>
+    <libosinfo:libosinfo xmlns:libosinfo="http://libosinfo.org/xmlns/libvirt/domain/1.0">
+      <libosinfo:os id="http://ubuntu.com/ubuntu/24.04"/>
+    </libosinfo:libosinfo>
+  </metadata>
+</domain>"#;
+        let id = libosinfo_id(xml).unwrap();
+        assert_eq!(id, "http://ubuntu.com/ubuntu/24.04");
+        assert!(matches!(
+            os_from_libosinfo_id(&id),
+            OperatingSystem::Linux {
+                distro: LinuxDistro::Ubuntu { version }
+            } if version == "24.04"
+        ));
+        assert!(matches!(
+            os_from_libosinfo_id("http://microsoft.com/win/2k22"),
+            OperatingSystem::Windows {
+                version: WindowsVersion::WindowsServer2022
+            }
+        ));
+        assert!(libosinfo_id("<domain type='kvm'/>").is_none());
+    }
+
+    #[test]
+    fn broken_backing_chains_name_the_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.qcow2");
+        let middle = dir.path().join("middle.qcow2");
+        let overlay = dir.path().join("clone.qcow2");
+        for file in [&middle, &overlay] {
+            std::fs::write(file, b"").unwrap();
+        }
+        // clone -> middle (relative) -> base
+        let headers = HashMap::from([
+            (overlay.clone(), Some("middle.qcow2".to_string())),
+            (middle.clone(), Some(base.display().to_string())),
+            (base.clone(), None),
+        ]);
+        let info = |path: &Path| -> Result<ImageInfo> {
+            Ok(ImageInfo {
+                filename: path.display().to_string(),
+                format: "qcow2".to_string(),
+                virtual_size: 1 << 30,
+                backing_filename: headers[path].clone(),
+                full_backing_filename: None,
+                actual_size: None,
+            })
+        };
+
+        let chain = walk_backing_chain("vda".to_string(), &overlay, info).unwrap();
+        assert_eq!(chain.files, vec![overlay.clone(), middle.clone()]);
+        assert_eq!(chain.missing.as_ref(), Some(&base));
+        assert!(chain.is_broken());
+
+        std::fs::write(&base, b"").unwrap();
+        let chain = walk_backing_chain("vda".to_string(), &overlay, info).unwrap();
+        assert_eq!(chain.files, vec![overlay, middle, base]);
+        assert!(!chain.is_broken());
+
+        let layers = parse_backing_chain(
+            r#"[{"virtual-size": 1024, "filename": "clone.qcow2", "format": "qcow2",
+                 "backing-filename": "base.qcow2", "full-backing-filename": "/images/base.qcow2"},
+                {"virtual-size": 1024, "filename": "/images/base.qcow2", "format": "qcow2"}]"#,
+        )
+        .unwrap();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].filename, "/images/base.qcow2");
+
+        record_linked_clone(dir.path(), "web2", "web").unwrap();
+        assert_eq!(
+            linked_clone_source(dir.path(), "web2").as_deref(),
+            Some("web")
+        );
+        assert!(linked_clone_source(dir.path(), "web").is_none());
+    }
+
+    #[tokio::test]
+    async fn snapshot_create_rejects_unknown_vm_and_unsafe_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+
+        let err = manager
+            .create_snapshot("nova-no-such-vm", "pre-upgrade", "", false)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, NovaError::ConfigError(msg) if msg.starts_with("VM 'nova-no-such-vm' not found")),
+            "{}",
+            err
+        );
+
+        // Rejected before libvirt is consulted
+        let err = manager
+            .create_snapshot("web", "before upgrade", "", false)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unsupported character ' '"), "{}", err);
+        assert!(validate_snapshot_name("--help").is_err());
+        assert!(validate_snapshot_name("nightly-2026.10.14_a").is_ok());
+    }
+
+    #[tokio::test]
+    async fn snapshot_import_rejects_unsafe_manifest_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+        let write_archive = |vm: &str, snapshot: &str| {
+            let manifest = serde_json::to_vec(&ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                vm: vm.to_string(),
+                snapshot: snapshot.to_string(),
+                description: String::new(),
+                created_at: None,
+                exported_at: Utc::now(),
+                disks: Vec::new(),
+                memory: None,
+            })
+            .unwrap();
+            let path = dir.path().join("web.nova-snapshot.tar");
+            let mut builder = tar::Builder::new(std::fs::File::create(&path).unwrap());
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, MANIFEST_NAME, manifest.as_slice())
+                .unwrap();
+            builder.finish().unwrap();
+            path
+        };
+
+        let archive = write_archive("web", "../../../etc/cron.d/x");
+        let err = manager
+            .import_snapshot(&archive, ImportTarget::Snapshot { vm: None })
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cannot start with"), "{}", err);
+
+        let archive = write_archive("../web", "pre-upgrade");
+        let err = manager
+            .import_snapshot(&archive, ImportTarget::Snapshot { vm: None })
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unsupported characters"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn quota_counts_snapshots_recorded_by_other_managers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut gui = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+        gui.set_quotas([(
+            "web",
+            SnapshotQuota {
+                max_snapshots: Some(1),
+                ..SnapshotQuota::default()
+            },
+        )]);
+        assert!(gui.enforce_quota("web", None).await.is_ok());
+
+        let mut cli = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+        cli.snapshots
+            .entry("web".to_string())
+            .or_default()
+            .insert("base".to_string(), snapshot("base", 1, 10, true));
+        cli.save_snapshots().unwrap();
+
+        let err = gui.enforce_quota("web", None).await.unwrap_err();
+        assert!(
+            matches!(&err, NovaError::SnapshotQuotaExceeded(msg) if msg.contains("1 of 1")),
+            "{}",
+            err
+        );
+        gui.set_quotas([]);
+        assert!(gui.quota("web").is_none());
+    }
+
+    #[test]
+    fn snapshot_metadata_survives_a_new_manager() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+        let mut safety = snapshot(
+            &pre_revert_snapshot_name(DateTime::from_timestamp(1_760_400_000, 0).unwrap()),
+            2,
+            10,
+            false,
+        );
+        assert_eq!(safety.name, "pre-revert-20251014-000000");
+        assert!(validate_snapshot_name(&safety.name).is_ok());
+        safety.auto_created = true;
+        let web = manager.snapshots.entry("web".to_string()).or_default();
+        web.insert("base".to_string(), snapshot("base", 1, 10, true));
+        web.insert(safety.id.clone(), safety);
+        manager.save_snapshots().unwrap();
+
+        let reloaded = TemplateManager::new(dir.path().to_path_buf()).unwrap();
+        let names: Vec<_> = reloaded
+            .list_snapshots_chronological("web")
+            .iter()
+            .map(|s| (s.name.as_str(), s.auto_created))
+            .collect();
+        assert_eq!(
+            names,
+            [("base", false), ("pre-revert-20251014-000000", true)]
+        );
+
+        // Records written before `auto_created` existed still load
+        let legacy = r#"{"id":"1","vm_name":"web","name":"old","description":"","snapshot_type":"DiskOnly","created_at":"2026-01-01T00:00:00Z","size_bytes":0,"vm_state":"Shutdown","parent_snapshot":null,"children":[],"is_current":false}"#;
+        assert!(
+            !serde_json::from_str::<VmSnapshot>(legacy)
+                .unwrap()
+                .auto_created
+        );
+    }
+}
@@ -1,4 +1,8 @@
 use egui::{self, Color32};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
 
 // ===== TOKYO NIGHT - NIGHT VARIANT (Default) =====
 // The classic Tokyo Night with deep blue backgrounds
@@ -135,6 +139,16 @@ pub const DRACULA_ORANGE: Color32 = Color32::from_rgb(255, 184, 108); // #ffb86c
 pub const DRACULA_YELLOW: Color32 = Color32::from_rgb(241, 250, 140); // #f1fa8c
 pub const DRACULA_RED: Color32 = Color32::from_rgb(255, 85, 85); // #ff5555
 
+// ===== COLORBLIND-SAFE STATUS PALETTE =====
+// Okabe-Ito colors: distinguishable with protanopia, deuteranopia and tritanopia
+
+pub const CB_BLUE: Color32 = Color32::from_rgb(0, 114, 178); // #0072b2
+pub const CB_SKY_BLUE: Color32 = Color32::from_rgb(86, 180, 233); // #56b4e9
+pub const CB_VERMILLION: Color32 = Color32::from_rgb(213, 94, 0); // #d55e00
+pub const CB_ORANGE: Color32 = Color32::from_rgb(230, 159, 0); // #e69f00
+pub const CB_YELLOW: Color32 = Color32::from_rgb(240, 228, 66); // #f0e442
+pub const CB_GREY: Color32 = Color32::from_rgb(153, 153, 153); // #999999
+
 // ===== THEME VARIANT ENUM =====
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -151,6 +165,150 @@ pub enum GuiTheme {
     CatppuccinMocha,
     Dracula,
     Ocean,
+    /// Loaded from a TOML file by [`load_custom_themes`]
+    Custom(&'static CustomTheme),
+}
+
+/// A user palette from `~/.config/nova/themes/<name>.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomTheme {
+    /// File stem, used as the theme name in the NovaFile
+    pub name: String,
+    pub label: String,
+    pub background: Color32,
+    pub background_dark: Color32,
+    pub highlight: Color32,
+    pub text: Color32,
+    pub text_dim: Color32,
+    pub border: Color32,
+    pub accent: Color32,
+    pub accent_hover: Color32,
+    pub success: Color32,
+    pub danger: Color32,
+    pub warning: Color32,
+    pub info: Color32,
+}
+
+/// On-disk form of [`CustomTheme`]; colors are `#rrggbb` strings.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomThemeFile {
+    label: Option<String>,
+    background: String,
+    background_dark: String,
+    highlight: String,
+    text: String,
+    text_dim: String,
+    border: String,
+    accent: String,
+    accent_hover: String,
+    success: String,
+    danger: String,
+    warning: String,
+    info: String,
+}
+
+fn parse_hex_color(field: &str, value: &str) -> Result<Color32, String> {
+    let hex = value.trim().trim_start_matches('#');
+    let invalid = || format!("{} = {:?} is not a #rrggbb color", field, value);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    Ok(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+impl CustomTheme {
+    /// Parse a theme file's contents; `name` is the file stem.
+    pub fn from_toml(name: &str, contents: &str) -> Result<Self, String> {
+        let file: CustomThemeFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+        Ok(Self {
+            name: name.to_string(),
+            label: file.label.unwrap_or_else(|| name.to_string()),
+            background: parse_hex_color("background", &file.background)?,
+            background_dark: parse_hex_color("background_dark", &file.background_dark)?,
+            highlight: parse_hex_color("highlight", &file.highlight)?,
+            text: parse_hex_color("text", &file.text)?,
+            text_dim: parse_hex_color("text_dim", &file.text_dim)?,
+            border: parse_hex_color("border", &file.border)?,
+            accent: parse_hex_color("accent", &file.accent)?,
+            accent_hover: parse_hex_color("accent_hover", &file.accent_hover)?,
+            success: parse_hex_color("success", &file.success)?,
+            danger: parse_hex_color("danger", &file.danger)?,
+            warning: parse_hex_color("warning", &file.warning)?,
+            info: parse_hex_color("info", &file.info)?,
+        })
+    }
+}
+
+fn custom_theme_registry() -> &'static RwLock<Vec<&'static CustomTheme>> {
+    static THEMES: OnceLock<RwLock<Vec<&'static CustomTheme>>> = OnceLock::new();
+    THEMES.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// `~/.config/nova/themes`
+pub fn custom_themes_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("nova").join("themes"))
+}
+
+/// Load every `*.toml` in `dir` and make them available to
+/// [`GuiTheme::from_name`] and [`all_themes`]. Files that fail to parse, or
+/// that reuse a built-in or already loaded name, are skipped with a warning.
+/// Meant to be called once at startup; themes stay loaded for the process.
+pub fn load_custom_themes(dir: &Path) -> Vec<GuiTheme> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    let mut loaded = Vec::new();
+    for path in paths {
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if GuiTheme::from_name(name).is_some() {
+            crate::log_warn!(
+                "Skipping custom theme {}: '{}' is already a theme name",
+                path.display(),
+                name
+            );
+            continue;
+        }
+        let theme = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| CustomTheme::from_toml(name, &contents));
+        match theme {
+            Ok(theme) => {
+                let theme: &'static CustomTheme = Box::leak(Box::new(theme));
+                custom_theme_registry()
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(theme);
+                loaded.push(GuiTheme::Custom(theme));
+            }
+            Err(err) => {
+                crate::log_warn!("Skipping invalid theme {}: {}", path.display(), err);
+            }
+        }
+    }
+    loaded
+}
+
+/// Built-in themes followed by any loaded custom ones.
+pub fn all_themes() -> Vec<GuiTheme> {
+    let custom = custom_theme_registry()
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    ALL_THEMES
+        .iter()
+        .copied()
+        .chain(custom.iter().map(|theme| GuiTheme::Custom(theme)))
+        .collect()
 }
 
 impl Default for GuiTheme {
@@ -203,7 +361,7 @@ pub const ALL_THEMES: [GuiTheme; 6] = [
 ];
 
 impl GuiTheme {
-    pub const fn name(self) -> &'static str {
+    pub fn name(self) -> &'static str {
         match self {
             GuiTheme::TokyoNight(TokyoNightVariant::Night) => "tokyo-night-night",
             GuiTheme::TokyoNight(TokyoNightVariant::Storm) => "tokyo-night-storm",
@@ -211,10 +369,11 @@ impl GuiTheme {
             GuiTheme::CatppuccinMocha => "catppuccin-mocha",
             GuiTheme::Dracula => "dracula",
             GuiTheme::Ocean => "ocean",
+            GuiTheme::Custom(theme) => theme.name.as_str(),
         }
     }
 
-    pub const fn label(self) -> &'static str {
+    pub fn label(self) -> &'static str {
         match self {
             GuiTheme::TokyoNight(TokyoNightVariant::Night) => "Tokyo Night (Night)",
             GuiTheme::TokyoNight(TokyoNightVariant::Storm) => "Tokyo Night (Storm)",
@@ -222,6 +381,7 @@ impl GuiTheme {
             GuiTheme::CatppuccinMocha => "Catppuccin (Mocha)",
             GuiTheme::Dracula => "Dracula",
             GuiTheme::Ocean => "Material Ocean",
+            GuiTheme::Custom(theme) => theme.label.as_str(),
         }
     }
 
@@ -233,7 +393,12 @@ impl GuiTheme {
             "catppuccin-mocha" => Some(GuiTheme::CatppuccinMocha),
             "dracula" => Some(GuiTheme::Dracula),
             "ocean" => Some(GuiTheme::Ocean),
-            _ => None,
+            _ => custom_theme_registry()
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .iter()
+                .find(|theme| theme.name == name)
+                .map(|theme| GuiTheme::Custom(theme)),
         }
     }
 }
@@ -244,6 +409,7 @@ pub fn apply_theme(ctx: &egui::Context, theme: GuiTheme) {
         GuiTheme::CatppuccinMocha => configure_catppuccin_theme(ctx),
         GuiTheme::Dracula => configure_dracula_theme(ctx),
         GuiTheme::Ocean => configure_ocean_theme(ctx),
+        GuiTheme::Custom(theme) => configure_custom_theme(ctx, theme),
     }
 }
 
@@ -545,12 +711,29 @@ fn ocean_button_palette(role: ButtonRole) -> ButtonPalette {
     }
 }
 
+fn custom_button_palette(theme: &CustomTheme, role: ButtonRole) -> ButtonPalette {
+    let (fill, hover, text) = match role {
+        ButtonRole::Primary => (theme.accent, theme.accent_hover, theme.background_dark),
+        ButtonRole::Start => (theme.success, theme.accent_hover, theme.background_dark),
+        ButtonRole::Stop => (theme.danger, theme.warning, theme.background_dark),
+        ButtonRole::Restart => (theme.warning, theme.accent_hover, theme.background_dark),
+        ButtonRole::Secondary => (theme.highlight, theme.accent, theme.text),
+    };
+    ButtonPalette {
+        fill,
+        hover,
+        stroke: theme.accent,
+        text,
+    }
+}
+
 pub fn button_palette(theme: GuiTheme, role: ButtonRole) -> ButtonPalette {
     match theme {
         GuiTheme::TokyoNight(variant) => tokyo_button_palette(variant, role),
         GuiTheme::CatppuccinMocha => catppuccin_button_palette(role),
         GuiTheme::Dracula => dracula_button_palette(role),
         GuiTheme::Ocean => ocean_button_palette(role),
+        GuiTheme::Custom(theme) => custom_button_palette(theme, role),
     }
 }
 
@@ -949,6 +1132,40 @@ pub fn configure_dracula_theme(ctx: &egui::Context) {
     apply_modern_theme_style(&mut visuals, &mut style, ctx);
 }
 
+pub fn configure_custom_theme(ctx: &egui::Context, theme: &CustomTheme) {
+    let mut style = (*ctx.global_style()).clone();
+    let mut visuals = egui::Visuals::dark();
+
+    visuals.window_fill = theme.background;
+    visuals.panel_fill = theme.background;
+    visuals.extreme_bg_color = theme.background_dark;
+    visuals.faint_bg_color = theme.highlight;
+
+    visuals.override_text_color = Some(theme.text);
+    visuals.hyperlink_color = theme.accent;
+
+    visuals.widgets.noninteractive.bg_fill = theme.highlight;
+    visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, theme.border);
+    visuals.widgets.noninteractive.fg_stroke = egui::Stroke::new(1.0, theme.text);
+
+    visuals.widgets.inactive.bg_fill = theme.highlight;
+    visuals.widgets.inactive.bg_stroke = egui::Stroke::new(1.0, theme.border);
+    visuals.widgets.inactive.fg_stroke = egui::Stroke::new(1.0, theme.text_dim);
+
+    visuals.widgets.hovered.bg_fill = theme.highlight;
+    visuals.widgets.hovered.bg_stroke = egui::Stroke::new(2.0, theme.accent_hover);
+    visuals.widgets.hovered.fg_stroke = egui::Stroke::new(1.5, theme.accent_hover);
+
+    visuals.widgets.active.bg_fill = theme.accent;
+    visuals.widgets.active.bg_stroke = egui::Stroke::new(2.0, theme.accent_hover);
+    visuals.widgets.active.fg_stroke = egui::Stroke::new(1.5, theme.background_dark);
+
+    visuals.selection.bg_fill = theme.accent;
+    visuals.selection.stroke = egui::Stroke::new(1.5, theme.accent_hover);
+
+    apply_modern_theme_style(&mut visuals, &mut style, ctx);
+}
+
 fn apply_modern_theme_style(
     visuals: &mut egui::Visuals,
     style: &mut egui::Style,
@@ -1008,58 +1225,110 @@ pub fn get_status_color_tokyo_night(
             InstanceStatus::Running => TN_NIGHT_GREEN,
             InstanceStatus::Stopped => TN_NIGHT_DARK3,
             InstanceStatus::Starting | InstanceStatus::Stopping => TN_NIGHT_CYAN,
-            InstanceStatus::Error => TN_NIGHT_RED,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => TN_NIGHT_RED,
             InstanceStatus::Suspended => TN_NIGHT_ORANGE,
         },
         TokyoNightVariant::Storm => match status {
             InstanceStatus::Running => TN_STORM_GREEN,
             InstanceStatus::Stopped => TN_STORM_DARK3,
             InstanceStatus::Starting | InstanceStatus::Stopping => TN_STORM_CYAN,
-            InstanceStatus::Error => TN_STORM_RED,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => TN_STORM_RED,
             InstanceStatus::Suspended => TN_STORM_ORANGE,
         },
         TokyoNightVariant::Moon => match status {
             InstanceStatus::Running => TN_MOON_GREEN,
             InstanceStatus::Stopped => TN_MOON_DARK3,
             InstanceStatus::Starting | InstanceStatus::Stopping => TN_MOON_CYAN,
-            InstanceStatus::Error => TN_MOON_RED,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => TN_MOON_RED,
             InstanceStatus::Suspended => TN_MOON_ORANGE,
         },
     }
 }
 
+static COLORBLIND_SAFE: AtomicBool = AtomicBool::new(false);
+
+/// Switch status colors and icons to the colorblind-safe set, typically
+/// from `[ui] colorblind_safe`. Applies on top of whichever theme is active.
+pub fn set_colorblind_safe(enabled: bool) {
+    COLORBLIND_SAFE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn colorblind_safe() -> bool {
+    COLORBLIND_SAFE.load(Ordering::Relaxed)
+}
+
+pub fn get_status_color_colorblind(status: &crate::instance::InstanceStatus) -> Color32 {
+    use crate::instance::InstanceStatus;
+    match status {
+        InstanceStatus::Running => CB_BLUE,
+        InstanceStatus::Stopped => CB_GREY,
+        InstanceStatus::Starting | InstanceStatus::Stopping => CB_SKY_BLUE,
+        InstanceStatus::Error => CB_VERMILLION,
+        InstanceStatus::CrashLooping => CB_ORANGE,
+        InstanceStatus::Suspended => CB_YELLOW,
+    }
+}
+
 pub fn get_status_color(status: &crate::instance::InstanceStatus, theme: GuiTheme) -> Color32 {
+    status_color(status, theme, colorblind_safe())
+}
+
+/// Status color for `theme`, or from the colorblind-safe set when `colorblind`.
+pub fn status_color(
+    status: &crate::instance::InstanceStatus,
+    theme: GuiTheme,
+    colorblind: bool,
+) -> Color32 {
     use crate::instance::InstanceStatus;
+    if colorblind {
+        return get_status_color_colorblind(status);
+    }
     match theme {
         GuiTheme::TokyoNight(variant) => get_status_color_tokyo_night(status, variant),
         GuiTheme::CatppuccinMocha => match status {
             InstanceStatus::Running => CAT_GREEN,
             InstanceStatus::Stopped => CAT_RED,
             InstanceStatus::Starting | InstanceStatus::Stopping => CAT_TEAL,
-            InstanceStatus::Error => CAT_RED,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => CAT_RED,
             InstanceStatus::Suspended => CAT_YELLOW,
         },
         GuiTheme::Dracula => match status {
             InstanceStatus::Running => DRACULA_GREEN,
             InstanceStatus::Stopped => DRACULA_RED,
             InstanceStatus::Starting | InstanceStatus::Stopping => DRACULA_CYAN,
-            InstanceStatus::Error => DRACULA_RED,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => DRACULA_RED,
             InstanceStatus::Suspended => DRACULA_YELLOW,
         },
         GuiTheme::Ocean => get_status_color_ocean(status),
+        GuiTheme::Custom(theme) => match status {
+            InstanceStatus::Running => theme.success,
+            InstanceStatus::Stopped => theme.text_dim,
+            InstanceStatus::Starting | InstanceStatus::Stopping => theme.info,
+            InstanceStatus::Error | InstanceStatus::CrashLooping => theme.danger,
+            InstanceStatus::Suspended => theme.warning,
+        },
     }
 }
 
 // Helper function for status icon (same for all variants)
 pub fn get_status_icon(status: &crate::instance::InstanceStatus) -> &'static str {
+    status_icon(status, colorblind_safe())
+}
+
+/// Status icon, using the distinct colorblind-safe shapes when `colorblind`.
+pub fn status_icon(status: &crate::instance::InstanceStatus, colorblind: bool) -> &'static str {
     use crate::instance::InstanceStatus;
+    if colorblind {
+        return status.symbol();
+    }
     match status {
-        InstanceStatus::Running => "●",   // Filled circle
-        InstanceStatus::Stopped => "○",   // Empty circle
-        InstanceStatus::Starting => "◐",  // Half circle
-        InstanceStatus::Stopping => "◑",  // Half circle
-        InstanceStatus::Error => "✕",     // X mark
-        InstanceStatus::Suspended => "⏸", // Pause symbol
+        InstanceStatus::Running => "●",      // Filled circle
+        InstanceStatus::Stopped => "○",      // Empty circle
+        InstanceStatus::Starting => "◐",     // Half circle
+        InstanceStatus::Stopping => "◑",     // Half circle
+        InstanceStatus::Error => "✕",        // X mark
+        InstanceStatus::Suspended => "⏸",    // Pause symbol
+        InstanceStatus::CrashLooping => "↻", // Restart arrow
     }
 }
 
@@ -1114,6 +1383,33 @@ pub const STATUS_WARNING: Color32 = MO_STATUS_WARNING;
 pub const STATUS_SUSPENDED: Color32 = MO_STATUS_SUSPENDED;
 pub const STATUS_UNKNOWN: Color32 = MO_STATUS_UNKNOWN;
 
+/// `STATUS_RUNNING`, or its colorblind-safe counterpart when that is on.
+pub fn status_running_color() -> Color32 {
+    if colorblind_safe() {
+        CB_BLUE
+    } else {
+        STATUS_RUNNING
+    }
+}
+
+/// `STATUS_STOPPED`, or its colorblind-safe counterpart when that is on.
+pub fn status_stopped_color() -> Color32 {
+    if colorblind_safe() {
+        CB_VERMILLION
+    } else {
+        STATUS_STOPPED
+    }
+}
+
+/// `STATUS_WARNING`, or its colorblind-safe counterpart when that is on.
+pub fn status_warning_color() -> Color32 {
+    if colorblind_safe() {
+        CB_ORANGE
+    } else {
+        STATUS_WARNING
+    }
+}
+
 pub const ACTION_PRIMARY: Color32 = MO_ACTION_PRIMARY;
 pub const ACTION_PRIMARY_HOVER: Color32 = MO_ACTION_PRIMARY_HOVER;
 pub const ACTION_SECONDARY: Color32 = MO_ACTION_SECONDARY;
@@ -1199,7 +1495,7 @@ pub fn get_status_color_ocean(status: &crate::instance::InstanceStatus) -> Color
         InstanceStatus::Running => MO_STATUS_RUNNING,
         InstanceStatus::Stopped => MO_STATUS_STOPPED,
         InstanceStatus::Starting | InstanceStatus::Stopping => MO_STATUS_WARNING,
-        InstanceStatus::Error => MO_STATUS_STOPPED,
+        InstanceStatus::Error | InstanceStatus::CrashLooping => MO_STATUS_STOPPED,
         InstanceStatus::Suspended => MO_STATUS_SUSPENDED,
     }
 }
@@ -1208,6 +1504,81 @@ pub fn get_status_color_ocean(status: &crate::instance::InstanceStatus) -> Color
 mod tests {
     use super::*;
 
+    #[test]
+    fn custom_themes_load_from_toml_and_skip_bad_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let palette = r##"
+label = "Solarized Dark"
+background = "#002b36"
+background_dark = "#00212b"
+highlight = "#073642"
+text = "#839496"
+text_dim = "#586e75"
+border = "#586e75"
+accent = "#268bd2"
+accent_hover = "#2aa198"
+success = "#859900"
+danger = "#dc322f"
+warning = "#b58900"
+info = "#2aa198"
+"##;
+        std::fs::write(dir.path().join("solarized.toml"), palette).unwrap();
+        std::fs::write(
+            dir.path().join("broken.toml"),
+            palette.replace("#dc322f", "red"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("dracula.toml"), palette).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let loaded = load_custom_themes(dir.path());
+        assert_eq!(loaded.len(), 1);
+        let theme = GuiTheme::from_name("solarized").unwrap();
+        assert_eq!(theme, loaded[0]);
+        assert_eq!(theme.label(), "Solarized Dark");
+        assert!(all_themes().contains(&theme));
+        assert_eq!(GuiTheme::from_name("dracula"), Some(GuiTheme::Dracula));
+        assert_eq!(
+            button_palette(theme, ButtonRole::Stop).fill,
+            Color32::from_rgb(0xdc, 0x32, 0x2f)
+        );
+
+        let err = CustomTheme::from_toml("x", &palette.replace("#dc322f", "#dc32")).unwrap_err();
+        assert!(err.contains("danger"), "{}", err);
+    }
+
+    #[test]
+    fn colorblind_palette_changes_colors_and_shapes() {
+        use crate::instance::InstanceStatus;
+        let statuses = [
+            InstanceStatus::Running,
+            InstanceStatus::Stopped,
+            InstanceStatus::Starting,
+            InstanceStatus::Stopping,
+            InstanceStatus::Error,
+            InstanceStatus::Suspended,
+            InstanceStatus::CrashLooping,
+        ];
+        let symbols: std::collections::HashSet<_> =
+            statuses.iter().map(|status| status.symbol()).collect();
+        assert_eq!(symbols.len(), statuses.len());
+
+        assert_eq!(
+            status_color(&InstanceStatus::Running, GuiTheme::Dracula, true),
+            CB_BLUE
+        );
+        assert_eq!(
+            status_color(&InstanceStatus::Error, GuiTheme::Dracula, true),
+            CB_VERMILLION
+        );
+        assert_eq!(status_icon(&InstanceStatus::Error, true), "✖");
+        assert_eq!(
+            status_color(&InstanceStatus::Running, GuiTheme::Dracula, false),
+            DRACULA_GREEN
+        );
+        assert_eq!(status_icon(&InstanceStatus::Error, false), "✕");
+    }
+
     #[test]
     fn intent_spec_generates_expected_labels() {
         let create_spec = intent_spec(ButtonIntent::Create);
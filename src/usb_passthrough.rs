@@ -1,6 +1,7 @@
 // USB Passthrough Support
 // Hot-plug USB devices to VMs with automatic detection
 
+use crate::exec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -154,8 +155,14 @@ impl UsbManager {
         })
     }
 
-    /// Attach USB device to VM
-    pub async fn attach_device(&mut self, vm_name: &str, device: &UsbDevice) -> Result<(), String> {
+    /// Attach USB device to VM. A `persistent` attach is also written to the
+    /// domain definition so the device comes back after the VM power-cycles.
+    pub async fn attach_device(
+        &mut self,
+        vm_name: &str,
+        device: &UsbDevice,
+        persistent: bool,
+    ) -> Result<(), String> {
         println!(
             "Attaching USB device {:04x}:{:04x} to VM '{}'",
             u16::from_str_radix(&device.vendor_id, 16).unwrap_or(0),
@@ -164,16 +171,22 @@ impl UsbManager {
         );
 
         // Generate libvirt XML
-        let xml = self.generate_usb_xml(device);
+        let xml = if persistent {
+            self.generate_persistent_usb_xml(device)
+        } else {
+            self.generate_usb_xml(device)
+        };
 
         // Attach device using virsh
         let temp_xml = format!("/tmp/nova-usb-{}-{}.xml", device.bus, device.device);
         fs::write(&temp_xml, &xml).map_err(|e| format!("Failed to write temp XML: {}", e))?;
 
-        let output = Command::new("virsh")
-            .args(["attach-device", vm_name, &temp_xml, "--live"])
-            .output()
-            .map_err(|e| format!("Failed to execute virsh: {}", e))?;
+        let output = exec::run(
+            Command::new("virsh")
+                .args(["attach-device", vm_name, &temp_xml])
+                .args(Self::hotplug_flags(vm_name, persistent)),
+        )
+        .map_err(|e| format!("Failed to execute virsh: {}", e))?;
 
         // Clean up temp file
         let _ = fs::remove_file(&temp_xml);
@@ -194,20 +207,32 @@ impl UsbManager {
         Ok(())
     }
 
-    /// Detach USB device from VM
-    pub async fn detach_device(&mut self, vm_name: &str, device: &UsbDevice) -> Result<(), String> {
+    /// Detach USB device from VM. A `persistent` detach also removes it from
+    /// the domain definition.
+    pub async fn detach_device(
+        &mut self,
+        vm_name: &str,
+        device: &UsbDevice,
+        persistent: bool,
+    ) -> Result<(), String> {
         println!("Detaching USB device from VM '{}'", vm_name);
 
         // Generate libvirt XML (same as attach)
-        let xml = self.generate_usb_xml(device);
+        let xml = if persistent {
+            self.generate_persistent_usb_xml(device)
+        } else {
+            self.generate_usb_xml(device)
+        };
 
         let temp_xml = format!("/tmp/nova-usb-{}-{}.xml", device.bus, device.device);
         fs::write(&temp_xml, &xml).map_err(|e| format!("Failed to write temp XML: {}", e))?;
 
-        let output = Command::new("virsh")
-            .args(["detach-device", vm_name, &temp_xml, "--live"])
-            .output()
-            .map_err(|e| format!("Failed to execute virsh: {}", e))?;
+        let output = exec::run(
+            Command::new("virsh")
+                .args(["detach-device", vm_name, &temp_xml])
+                .args(Self::hotplug_flags(vm_name, persistent)),
+        )
+        .map_err(|e| format!("Failed to execute virsh: {}", e))?;
 
         let _ = fs::remove_file(&temp_xml);
 
@@ -241,6 +266,39 @@ impl UsbManager {
         )
     }
 
+    /// Hostdev XML for the domain definition. The bus/device address changes
+    /// whenever the device is replugged or the host reboots, so the device
+    /// is matched by vendor and product ID alone.
+    pub fn generate_persistent_usb_xml(&self, device: &UsbDevice) -> String {
+        format!(
+            r#"<hostdev mode='subsystem' type='usb' managed='yes'>
+  <source>
+    <vendor id='0x{}'/>
+    <product id='0x{}'/>
+  </source>
+</hostdev>"#,
+            device.vendor_id, device.product_id
+        )
+    }
+
+    /// `virsh attach-device`/`detach-device` flags. Persistent changes go to
+    /// the definition, and to the running domain only when it is running.
+    fn hotplug_flags(vm_name: &str, persistent: bool) -> Vec<&'static str> {
+        if !persistent {
+            return vec!["--live"];
+        }
+        let running = Command::new("virsh")
+            .args(["domstate", vm_name])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "running")
+            .unwrap_or(false);
+        if running {
+            vec!["--config", "--live"]
+        } else {
+            vec!["--config"]
+        }
+    }
+
     /// Pass entire USB controller to VM
     pub fn pass_usb_controller(&self, vm_name: &str, pci_address: &str) -> Result<(), String> {
         println!("Passing USB controller {} to VM '{}'", pci_address, vm_name);
@@ -264,10 +322,9 @@ impl UsbManager {
         let temp_xml = "/tmp/nova-usb-controller.xml";
         fs::write(temp_xml, &xml).map_err(|e| format!("Failed to write XML: {}", e))?;
 
-        let output = Command::new("virsh")
-            .args(["attach-device", vm_name, temp_xml, "--config"])
-            .output()
-            .map_err(|e| format!("Failed to execute virsh: {}", e))?;
+        let output =
+            exec::run(Command::new("virsh").args(["attach-device", vm_name, temp_xml, "--config"]))
+                .map_err(|e| format!("Failed to execute virsh: {}", e))?;
 
         let _ = fs::remove_file(temp_xml);
 
@@ -424,6 +481,12 @@ mod tests {
         assert!(xml.contains("type='usb'"));
         assert!(xml.contains("vendor id='0x046d'"));
         assert!(xml.contains("product id='0xc52b'"));
+        assert!(xml.contains("address bus='1' device='5'"));
+
+        // Bus/device numbers don't survive a replug, so the definition omits them
+        let persistent = manager.generate_persistent_usb_xml(&device);
+        assert!(persistent.contains("vendor id='0x046d'"));
+        assert!(!persistent.contains("<address"));
     }
 
     #[test]
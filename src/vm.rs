@@ -1,13 +1,30 @@
 use crate::{
     NovaError, Result,
-    config::{DiskFormat, VmBootType, VmConfig, VmFirmwareConfig, VmTpmConfig, VmTpmVersion},
+    config::{
+        DiskCache, DiskFormat, VmBootType, VmConfig, VmDiskConfig, VmFirmwareConfig, VmTpmConfig,
+        VmTpmVersion,
+    },
+    exec,
     gpu_passthrough::{DisplayMode, GpuManager, GpuPassthroughConfig},
-    instance::Instance,
+    instance::{Instance, InstanceStatus, InstanceType},
     log_debug, log_error, log_info, log_warn,
     looking_glass::{LookingGlassConfig, LookingGlassManager},
+    network,
+    storage_pool::StoragePoolManager,
+    templates_snapshots,
+    vm_boot::BootOverride,
+    vm_cpu::{self, CpuTopology, VcpuPin},
+    vm_devices::{self, DeviceChanges, DiskBus, NetModel},
+    vm_hugepages::{self, HugePageSize},
+    vm_memory::{self, BalloonPlan, MemoryLayout},
+    vm_netlimit::{self, InterfaceNetLimit, NetLimit, NetLimitChange},
+    vm_nics::{self, VmNic},
+    vm_qemu_args, vm_xml,
 };
+use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
@@ -28,10 +45,80 @@ struct TpmArtifacts {
 }
 
 struct ManagedTpm {
-    child: Child,
+    /// `None` under `--dry-run`, where swtpm is only printed
+    child: Option<Child>,
     artifacts: TpmArtifacts,
 }
 
+/// Outcome of pushing a resized disk to a running guest.
+#[derive(Debug, Clone)]
+pub struct DiskResizeReport {
+    pub vm: String,
+    pub target: String,
+    /// Capacity libvirt reports for the block device after the resize
+    pub guest_size_bytes: u64,
+    pub guest_agent: bool,
+    /// Filesystems on the resized disk and the command needed to grow each
+    pub grow_suggestions: Vec<GuestGrowSuggestion>,
+}
+
+/// A filesystem the guest agent reported on a resized disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestFilesystem {
+    pub device: String,
+    pub mountpoint: String,
+    pub fs_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestGrowSuggestion {
+    pub filesystem: GuestFilesystem,
+    pub command: String,
+}
+
+/// What [`VmManager::delete_vm`] removes besides the domain definition.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VmDeleteOptions {
+    /// Delete disks that are storage pool volumes
+    pub remove_disks: bool,
+    /// Delete the VM's snapshots; a VM that has any is refused without it
+    pub remove_snapshots: bool,
+    /// Delete disks even when another domain uses them as a backing file
+    pub force: bool,
+}
+
+/// What a [`VmManager::delete_vm`] call removed and left behind.
+#[derive(Debug, Clone, Default)]
+pub struct VmDeleteReport {
+    /// UEFI variable store removed with `--nvram`
+    pub nvram: bool,
+    pub snapshots: Vec<String>,
+    /// `pool/volume` (or path) of each deleted disk and backing image
+    pub removed_volumes: Vec<String>,
+    /// Disks left in place, with the reason
+    pub kept_disks: Vec<(String, String)>,
+    /// PCI addresses of GPUs no longer reserved for the VM
+    pub released_gpus: Vec<String>,
+}
+
+/// Where a disk's data lives in a domain definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiskSource {
+    /// `file=` or `dev=`
+    Path(PathBuf),
+    /// `<source pool= volume=>`
+    Volume { pool: String, volume: String },
+}
+
+impl std::fmt::Display for DiskSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiskSource::Path(path) => write!(f, "{}", path.display()),
+            DiskSource::Volume { pool, volume } => write!(f, "{}/{}", pool, volume),
+        }
+    }
+}
+
 pub struct VmManager {
     instances: Arc<Mutex<HashMap<String, Instance>>>,
     processes: Arc<Mutex<HashMap<String, Child>>>,
@@ -58,7 +145,19 @@ impl VmManager {
     }
 
     pub async fn start_vm(&self, name: &str, config: Option<&VmConfig>) -> Result<()> {
+        self.start_vm_with_boot(name, config, &BootOverride::default())
+            .await
+    }
+
+    /// Start a VM with `boot` applied to this start only.
+    pub async fn start_vm_with_boot(
+        &self,
+        name: &str,
+        config: Option<&VmConfig>,
+        boot: &BootOverride,
+    ) -> Result<()> {
         log_info!("Starting VM: {}", name);
+        boot.validate()?;
 
         // Check if VM is already running
         {
@@ -67,30 +166,55 @@ impl VmManager {
                 && instance.is_running()
             {
                 log_warn!("VM '{}' is already running", name);
+                if !boot.is_empty() {
+                    log_warn!(
+                        "VM '{}' boot override ignored; it only applies to a cold boot",
+                        name
+                    );
+                }
                 return Ok(());
             }
         }
 
         let vm_config = config.cloned().unwrap_or_default();
+        if !vm_config.cpuset.is_empty() {
+            log_warn!("VM '{}': {}", name, vm_cpu::QEMU_PINNING_WARNING);
+        }
 
         // Create QEMU command
         let mut cmd = Command::new("qemu-system-x86_64");
 
+        let memory_mb = self.parse_memory_mb(&vm_config.memory)?;
+        let memory_arg = match &vm_config.max_memory {
+            Some(max_memory) => format!(
+                "size={}M,slots={},maxmem={}M",
+                memory_mb,
+                vm_config
+                    .memory_slots
+                    .unwrap_or(vm_memory::DEFAULT_MEMORY_SLOTS),
+                self.parse_memory_mb(max_memory)?
+            ),
+            None => format!("{}M", memory_mb),
+        };
+
         // Basic configuration
         cmd.arg("-name")
             .arg(name)
             .arg("-m")
-            .arg(format!("{}M", self.parse_memory_mb(&vm_config.memory)?))
+            .arg(memory_arg)
             .arg("-cpu")
             .arg("host")
             .arg("-enable-kvm")
             .arg("-smp")
-            .arg(vm_config.cpu.to_string())
+            .arg(vm_cpu::effective_topology(vm_config.cpu, vm_config.cpu_topology)?.smp_arg())
             .arg("-daemonize")
             .arg("-monitor")
             .arg("none")
             .arg("-display")
             .arg("none");
+        if let Some(size) = vm_config.hugepages {
+            cmd.args(vm_hugepages::qemu_args(name, memory_mb * 1024, size)?);
+        }
 
         let (disk_path, disk_format) = prepare_vm_disk(name, &vm_config).await?;
         cmd.arg("-drive").arg(format!(
@@ -99,6 +223,57 @@ impl VmManager {
             disk_format.as_str()
         ));
 
+        for (index, disk) in vm_config.disks.iter().enumerate() {
+            match disk {
+                VmDiskConfig::BlockDevice {
+                    path,
+                    readonly,
+                    shareable,
+                    cache,
+                    ..
+                } => {
+                    let access = DiskAccess {
+                        readonly: *readonly,
+                        shareable: *shareable,
+                        cache: *cache,
+                    };
+                    if let Some(warning) = access.validate()? {
+                        log_warn!("VM '{}' disk {}: {}", name, path, warning);
+                    }
+                    validate_block_device(Path::new(path))?;
+                    if !access.shareable {
+                        log_warn!(
+                            "VM '{}' gets exclusive raw access to {}; keep it unmounted on the host",
+                            name,
+                            path
+                        );
+                    }
+                    cmd.arg("-drive").arg(format!(
+                        "file={},format=raw,if=virtio,{}",
+                        path,
+                        access.qemu_drive_options()
+                    ));
+                }
+                VmDiskConfig::Cdrom { path } => {
+                    if !Path::new(path).is_file() {
+                        return Err(NovaError::ConfigError(format!(
+                            "VM '{}' CD-ROM image {} does not exist",
+                            name, path
+                        )));
+                    }
+                    let id = format!("nova-cdrom{}", index);
+                    cmd.arg("-drive")
+                        .arg(format!(
+                            "file={},media=cdrom,readonly=on,if=none,id={}",
+                            path.replace(',', ",,"),
+                            id
+                        ))
+                        .arg("-device")
+                        .arg(format!("ide-cd,drive={}", id));
+                }
+            }
+        }
+
         // GPU passthrough and Looking Glass support
         self.apply_gpu_passthrough(name, &vm_config, &mut cmd)
             .await?;
@@ -110,19 +285,42 @@ impl VmManager {
         self.configure_tpm(name, &vm_config.tpm, &mut cmd)?;
 
         // Network configuration
-        if let Some(network) = &vm_config.network {
-            cmd.arg("-netdev")
-                .arg(format!("bridge,id=net0,br={}", network))
-                .arg("-device")
-                .arg("virtio-net-pci,netdev=net0");
-        } else {
+        if vm_config.net_limit.is_some() {
+            log_warn!("VM '{}': {}", name, vm_netlimit::QEMU_WARNING);
+        }
+        for (index, nic) in vm_config.network.iter().enumerate() {
+            cmd.args(vm_nics::qemu_bridge_args(
+                index,
+                &nic.network,
+                nic,
+                NetModel::Virtio,
+            ));
+        }
+        if vm_config.network.is_empty() {
             cmd.arg("-netdev")
                 .arg("user,id=net0")
                 .arg("-device")
                 .arg("virtio-net-pci,netdev=net0");
         }
 
+        if !boot.is_empty() {
+            log_info!(
+                "VM '{}' boot override for this start: {:?}",
+                name,
+                boot.effective_order()
+            );
+            cmd.args(boot.qemu_args());
+        }
+
+        if !vm_config.extra_qemu_args.is_empty() {
+            log_warn!("VM '{}': {}", name, vm_qemu_args::UNSUPPORTED_WARNING);
+            cmd.args(&vm_config.extra_qemu_args);
+        }
+
         log_debug!("QEMU command: {:?}", cmd);
+        if exec::skip(&cmd) {
+            return Ok(());
+        }
 
         // Start the VM process
         let child = match cmd
@@ -154,7 +352,7 @@ impl VmManager {
         instance.update_status(crate::instance::InstanceStatus::Starting);
         instance.cpu_cores = vm_config.cpu;
         instance.memory_mb = self.parse_memory_mb(&vm_config.memory)?;
-        instance.network = vm_config.network.clone();
+        instance.network = vm_config.primary_network();
 
         {
             let mut instances = self.instances.lock().unwrap();
@@ -226,11 +424,12 @@ impl VmManager {
         }
 
         // Alternative: use pkill to find and kill QEMU process
-        let output = Command::new("pkill")
-            .arg("-f")
-            .arg(format!("qemu.*{}", name))
-            .output()
-            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let output = exec::run(
+            Command::new("pkill")
+                .arg("-f")
+                .arg(format!("qemu.*{}", name)),
+        )
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
         if output.status.success() {
             log_info!("VM '{}' stopped successfully", name);
@@ -271,6 +470,334 @@ impl VmManager {
         }
     }
 
+    /// Pause a running libvirt domain. Guest memory stays resident, so it
+    /// resumes exactly where it left off but stops consuming host CPU.
+    pub async fn suspend_vm(&self, name: &str) -> Result<()> {
+        log_info!("Suspending VM: {}", name);
+        self.change_domain_run_state(name, "suspend", InstanceStatus::Running)?;
+        self.record_status(name, InstanceStatus::Suspended);
+        log_info!("VM '{}' suspended", name);
+        Ok(())
+    }
+
+    /// Resume a domain paused by [`Self::suspend_vm`].
+    pub async fn resume_vm(&self, name: &str) -> Result<()> {
+        log_info!("Resuming VM: {}", name);
+        self.change_domain_run_state(name, "resume", InstanceStatus::Suspended)?;
+        self.record_status(name, InstanceStatus::Running);
+        log_info!("VM '{}' resumed", name);
+        Ok(())
+    }
+
+    fn change_domain_run_state(
+        &self,
+        name: &str,
+        action: &str,
+        required: InstanceStatus,
+    ) -> Result<()> {
+        let state = Command::new("virsh")
+            .args(["domstate", name])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(name.to_string()));
+        }
+        let state = String::from_utf8_lossy(&state.stdout).trim().to_string();
+        if instance_status_from_domstate(&state) != Some(required) {
+            return Err(NovaError::ConfigError(format!(
+                "Cannot {} VM '{}' while it is {}",
+                action, name, state
+            )));
+        }
+
+        let output = exec::run(Command::new("virsh").args([action, name]))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "virsh {} {} failed: {}",
+                action,
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn record_status(&self, name: &str, status: InstanceStatus) {
+        let mut instances = self.instances.lock().unwrap();
+        instances
+            .entry(name.to_string())
+            .or_insert_with(|| Instance::new(name.to_string(), InstanceType::Vm))
+            .update_status(status);
+    }
+
+    /// Merge libvirt's view of every defined domain into the tracked
+    /// instances, so suspended or externally started VMs show up in listings.
+    pub fn refresh_libvirt_states(&self) {
+        let Ok(output) = Command::new("virsh").args(["list", "--all"]).output() else {
+            return;
+        };
+        if !output.status.success() {
+            log_debug!(
+                "virsh list failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            return;
+        }
+
+        for (name, state) in parse_virsh_list(&String::from_utf8_lossy(&output.stdout)) {
+            let Some(status) = instance_status_from_domstate(&state) else {
+                continue;
+            };
+            let tracked = self.instances.lock().unwrap().contains_key(&name);
+            self.record_status(&name, status);
+            if tracked {
+                continue;
+            }
+
+            // Size untracked domains from their definition
+            if let Ok(info) = Command::new("virsh").args(["dominfo", &name]).output()
+                && info.status.success()
+            {
+                let (cpus, memory_kib) = parse_dominfo_size(&String::from_utf8_lossy(&info.stdout));
+                let mut instances = self.instances.lock().unwrap();
+                if let Some(instance) = instances.get_mut(&name) {
+                    if let Some(cpus) = cpus {
+                        instance.cpu_cores = cpus;
+                    }
+                    if let Some(memory_kib) = memory_kib {
+                        instance.memory_mb = memory_kib / 1024;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether libvirt starts the domain when the host boots; `None` when
+    /// it isn't a libvirt domain.
+    pub fn autostart(&self, vm: &str) -> Option<bool> {
+        let output = Command::new("virsh").args(["dominfo", vm]).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_dominfo_autostart(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Turn libvirt autostart on or off with `virsh autostart [--disable]`.
+    pub async fn set_autostart(&self, vm: &str, enable: bool) -> Result<()> {
+        let mut cmd = Command::new("virsh");
+        cmd.arg("autostart");
+        if !enable {
+            cmd.arg("--disable");
+        }
+        cmd.arg(vm);
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("failed to get domain") {
+                return Err(NovaError::VmNotFound(vm.to_string()));
+            }
+            log_error!("virsh autostart failed for '{}': {}", vm, stderr.trim());
+            return Err(NovaError::LibvirtError(format!(
+                "Failed to {} autostart for '{}': {}",
+                if enable { "enable" } else { "disable" },
+                vm,
+                stderr.trim()
+            )));
+        }
+
+        log_info!(
+            "Autostart {} for VM '{}'",
+            if enable { "enabled" } else { "disabled" },
+            vm
+        );
+        Ok(())
+    }
+
+    /// Remove a shut-off VM: `virsh undefine` (with `--nvram` for UEFI
+    /// guests), then optionally its snapshots and pool-backed disks. GPU
+    /// reservations held by the VM are always released.
+    ///
+    /// Disks that are not volumes of a libvirt storage pool, such as raw
+    /// block devices, are never deleted; they are listed in the report
+    /// instead.
+    pub async fn delete_vm(&self, name: &str, options: VmDeleteOptions) -> Result<VmDeleteReport> {
+        let state = Command::new("virsh")
+            .args(["domstate", name])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(name.to_string()));
+        }
+        let state = String::from_utf8_lossy(&state.stdout).trim().to_string();
+        if state != "shut off" {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' is {}; stop it before deleting",
+                name, state
+            )));
+        }
+
+        let output = Command::new("virsh")
+            .args(["dumpxml", "--inactive", name])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(name.to_string()));
+        }
+        let xml = String::from_utf8_lossy(&output.stdout).into_owned();
+
+        // Linked clones keep this VM's disks as their qcow2 backing files
+        if options.remove_disks && !options.force {
+            let dependents = backing_file_users(name, &domain_disk_sources(&xml));
+            if !dependents.is_empty() {
+                return Err(NovaError::ConfigError(format!(
+                    "Disks of VM '{}' back {}; deleting them would break those VMs. Delete them \
+                     first, or pass --force",
+                    name,
+                    dependents.join(", ")
+                )));
+            }
+        }
+
+        let mut report = VmDeleteReport {
+            nvram: xml.contains("<nvram"),
+            snapshots: virsh_snapshot_names(name, false),
+            ..Default::default()
+        };
+        if !report.snapshots.is_empty() && !options.remove_snapshots {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' has {} snapshot(s); pass --remove-snapshots to delete them too",
+                name,
+                report.snapshots.len()
+            )));
+        }
+
+        // Deleting each tree removes internal snapshot data from the disks.
+        // Dropping only the metadata would orphan that data, so stop here
+        // while the domain is still defined.
+        for root in virsh_snapshot_names(name, true) {
+            let output = exec::run(Command::new("virsh").args([
+                "snapshot-delete",
+                name,
+                &root,
+                "--children",
+            ]))
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+            if !output.status.success() {
+                return Err(NovaError::LibvirtError(format!(
+                    "Could not delete snapshot '{}' of '{}', so the VM was kept: {}",
+                    root,
+                    name,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+        }
+
+        let mut cmd = Command::new("virsh");
+        cmd.args(["undefine", name]);
+        if report.nvram {
+            cmd.arg("--nvram");
+        }
+        if !report.snapshots.is_empty() {
+            cmd.arg("--snapshots-metadata");
+        }
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "virsh undefine {} failed: {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        log_info!("Undefined VM '{}'", name);
+
+        let disks = domain_disk_sources(&xml);
+        if options.remove_disks {
+            let mut storage = StoragePoolManager::new();
+            let snapshot_dir = Path::new("/var/lib/nova/snapshots").join(name);
+            for disk in disks {
+                let Some(top) = disk_source_path(&disk) else {
+                    report
+                        .kept_disks
+                        .push((disk.to_string(), "path could not be resolved".to_string()));
+                    continue;
+                };
+                // The disk, then the images under it: snapshot overlays
+                // leave the original volume as a backing file
+                let mut chain = vec![top.clone()];
+                chain.extend(backing_files(&top));
+
+                let (removable, kept) = removable_chain(&chain, |layer| {
+                    if *layer != top {
+                        let users = backing_file_users(name, &[DiskSource::Path(layer.into())]);
+                        if !users.is_empty() {
+                            return Some(format!("also backs {}", users.join(", ")));
+                        }
+                    }
+                    (StoragePoolManager::volume_at(layer).is_none()
+                        && !layer.starts_with(&snapshot_dir))
+                    .then(|| "not a storage pool volume".to_string())
+                });
+
+                let mut failed = None;
+                for layer in removable {
+                    let removed = match StoragePoolManager::volume_at(layer) {
+                        Some((pool, volume)) => storage
+                            .delete_volume(&pool, &volume, false)
+                            .await
+                            .map(|()| format!("{}/{}", pool, volume)),
+                        None => match exec::run(Command::new("rm").arg("-f").arg(layer)) {
+                            Ok(output) if output.status.success() => {
+                                Ok(layer.display().to_string())
+                            }
+                            Ok(output) => Err(NovaError::ConfigError(
+                                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                            )),
+                            Err(err) => Err(err.into()),
+                        },
+                    };
+                    match removed {
+                        Ok(removed) => report.removed_volumes.push(removed),
+                        Err(err) => {
+                            failed = Some((layer.clone(), err.to_string()));
+                            break;
+                        }
+                    }
+                }
+                // Whatever sits below a layer that stays still backs it
+                if let Some((layer, reason)) = failed.or(kept) {
+                    let below = chain.iter().skip_while(|other| **other != layer).skip(1);
+                    report
+                        .kept_disks
+                        .push((layer.display().to_string(), reason));
+                    for backing in below {
+                        report.kept_disks.push((
+                            backing.display().to_string(),
+                            format!("backs {}", layer.display()),
+                        ));
+                    }
+                }
+            }
+        } else {
+            report.kept_disks = disks
+                .into_iter()
+                .map(|disk| (disk.to_string(), "--remove-disks not given".to_string()))
+                .collect();
+        }
+
+        report.released_gpus = self
+            .gpu_manager
+            .lock()
+            .unwrap()
+            .clear_reservations_for(name);
+        self.gpu_allocations.lock().unwrap().remove(name);
+        self.looking_glass_configs.lock().unwrap().remove(name);
+        self.instances.lock().unwrap().remove(name);
+
+        log_info!("VM '{}' deleted", name);
+        Ok(report)
+    }
+
     async fn apply_gpu_passthrough(
         &self,
         name: &str,
@@ -307,7 +834,11 @@ impl VmManager {
                 config.device_address
             );
 
-            manager.configure_passthrough(&config.device_address, name)?;
+            manager.configure_passthrough(
+                &config.device_address,
+                name,
+                config.allow_host_display,
+            )?;
 
             Some(config)
         } else {
@@ -451,8 +982,10 @@ impl VmManager {
                 guard.remove(name)
             };
             if let Some(mut existing) = managed {
-                let _ = existing.child.kill();
-                let _ = existing.child.wait();
+                if let Some(child) = existing.child.as_mut() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
                 Self::cleanup_tpm_artifacts(existing.artifacts);
             }
             return Ok(());
@@ -611,13 +1144,17 @@ impl VmManager {
             .arg("not-need-init");
         command.stdin(Stdio::null()).stdout(Stdio::null());
 
-        let child = command.spawn().map_err(|err| {
-            log_error!("Failed to launch swtpm for VM '{}': {}", name, err);
-            NovaError::SystemCommandFailed
-        })?;
-
-        // Give swtpm a brief moment to create the socket
-        std::thread::sleep(std::time::Duration::from_millis(150));
+        let child = if exec::skip(&command) {
+            None
+        } else {
+            let child = command.spawn().map_err(|err| {
+                log_error!("Failed to launch swtpm for VM '{}': {}", name, err);
+                NovaError::SystemCommandFailed
+            })?;
+            // Give swtpm a brief moment to create the socket
+            std::thread::sleep(std::time::Duration::from_millis(150));
+            Some(child)
+        };
 
         Ok(ManagedTpm {
             child,
@@ -717,10 +1254,12 @@ impl VmManager {
         };
 
         if let Some(mut tpm) = managed_tpm {
-            if let Err(err) = tpm.child.kill() {
-                log_warn!("Failed to terminate swtpm for VM '{}': {}", name, err);
+            if let Some(child) = tpm.child.as_mut() {
+                if let Err(err) = child.kill() {
+                    log_warn!("Failed to terminate swtpm for VM '{}': {}", name, err);
+                }
+                let _ = child.wait();
             }
-            let _ = tpm.child.wait();
             Self::cleanup_tpm_artifacts(tpm.artifacts);
         }
     }
@@ -742,76 +1281,2261 @@ impl VmManager {
         Ok(bytes / (1024 * 1024)) // Convert to MB
     }
 
-    // Check if libvirt is available and try to use it
-    pub fn check_libvirt(&self) -> bool {
-        Command::new("virsh")
-            .arg("--version")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
-    }
-}
+    /// Grow the disk behind `target` (e.g. `vda`) of a running domain.
+    ///
+    /// Issues `virsh blockresize`, which grows the image through QEMU and lets
+    /// the guest see the new capacity; the volume must not be resized behind
+    /// QEMU's back with `vol-resize` while the domain has it open. With
+    /// `grow_guest`, the QEMU guest agent is asked which filesystems live on the
+    /// disk and a growth command is suggested for each; nothing is run inside
+    /// the guest.
+    pub async fn notify_disk_resize(
+        &self,
+        vm: &str,
+        target: &str,
+        size_bytes: u64,
+        grow_guest: bool,
+    ) -> Result<DiskResizeReport> {
+        log_info!(
+            "Notifying VM '{}' that disk {} is now {} bytes",
+            vm,
+            target,
+            size_bytes
+        );
 
-impl Default for VmManager {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+        // blockresize defaults to KiB without a suffix
+        let output = exec::run(Command::new("virsh").args([
+            "blockresize",
+            vm,
+            target,
+            &format!("{}B", size_bytes),
+        ]))
+        .map_err(|_| NovaError::SystemCommandFailed)?;
 
-pub(crate) async fn prepare_vm_disk(
-    vm_name: &str,
-    config: &VmConfig,
-) -> Result<(PathBuf, DiskFormat)> {
-    if let Some(image_path) = &config.image {
-        return Ok((PathBuf::from(image_path), config.storage.format));
-    }
+        if !output.status.success() {
+            log_error!(
+                "virsh blockresize failed for {}:{}: {}",
+                vm,
+                target,
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return Err(NovaError::SystemCommandFailed);
+        }
 
-    let storage_cfg = config.storage.clone();
-    let disk_path = storage_cfg.resolve_disk_path(vm_name);
+        let guest_size_bytes = Command::new("virsh")
+            .args(["domblkinfo", vm, target])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_domblkinfo_capacity(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(size_bytes);
 
-    if tokio::fs::metadata(&disk_path).await.is_ok() {
-        return Ok((disk_path, storage_cfg.format));
-    }
+        let guest_agent = guest_agent_command(vm, r#"{"execute":"guest-ping"}"#).is_some();
+        let mut grow_suggestions = Vec::new();
 
-    if !storage_cfg.create_if_missing {
-        return Err(NovaError::ConfigError(format!(
-            "Disk image '{}' not found and auto-creation disabled",
-            disk_path.display()
-        )));
-    }
+        if grow_guest {
+            if !guest_agent {
+                log_warn!(
+                    "QEMU guest agent not responding in '{}'; grow the filesystem manually",
+                    vm
+                );
+            } else if let Some(fsinfo) =
+                guest_agent_command(vm, r#"{"execute":"guest-get-fsinfo"}"#)
+            {
+                let serial = self
+                    .domain_xml(vm)
+                    .ok()
+                    .and_then(|(xml, _)| disk_serial(&xml, target));
+                grow_suggestions = parse_guest_fsinfo(&fsinfo, target, serial.as_deref())
+                    .into_iter()
+                    .map(|filesystem| GuestGrowSuggestion {
+                        command: guest_grow_command(&filesystem),
+                        filesystem,
+                    })
+                    .collect();
+            }
+        }
 
-    if let Some(parent) = disk_path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
+        Ok(DiskResizeReport {
+            vm: vm.to_string(),
+            target: target.to_string(),
+            guest_size_bytes,
+            guest_agent,
+            grow_suggestions,
+        })
     }
 
-    log_info!(
-        "Provisioning disk for VM '{}' at {} (format={}, size={})",
-        vm_name,
-        disk_path.display(),
-        storage_cfg.format.as_str(),
-        storage_cfg.size
-    );
+    /// Pass a whole host block device (e.g. `/dev/sdb`) to a libvirt domain.
+    ///
+    /// The device must be a block device with no mounted filesystem, swap, or
+    /// device-mapper holder on it or its partitions. Returns the guest target
+    /// the disk was attached as.
+    pub async fn attach_block_device(
+        &self,
+        vm: &str,
+        device: &Path,
+        target: Option<&str>,
+        mode: DiskAttachMode,
+        access: DiskAccess,
+    ) -> Result<String> {
+        if let Some(warning) = access.validate()? {
+            log_warn!("{}: {}", device.display(), warning);
+        }
+        validate_block_device(device)?;
+
+        let target = match target {
+            Some(target) => target.to_string(),
+            None => {
+                let output = Command::new("virsh")
+                    .args(["domblklist", vm])
+                    .output()
+                    .map_err(|_| NovaError::SystemCommandFailed)?;
+                if !output.status.success() {
+                    return Err(NovaError::VmNotFound(vm.to_string()));
+                }
+                next_virtio_target(&parse_domblklist_targets(&String::from_utf8_lossy(
+                    &output.stdout,
+                )))
+                .ok_or_else(|| {
+                    NovaError::ConfigError(format!("No free virtio disk target on '{}'", vm))
+                })?
+            }
+        };
 
-    let output = Command::new("qemu-img")
-        .args([
-            "create",
-            "-f",
-            storage_cfg.format.as_str(),
-            &disk_path.to_string_lossy(),
-            &storage_cfg.size,
-        ])
-        .output()
-        .map_err(|_| NovaError::SystemCommandFailed)?;
+        if access.shareable {
+            log_info!(
+                "Attaching {} to VM '{}' as {} (shareable{})",
+                device.display(),
+                vm,
+                target,
+                if access.readonly { ", read-only" } else { "" }
+            );
+        } else {
+            log_warn!(
+                "Attaching {} to VM '{}' as {}; the VM gets exclusive access to the device",
+                device.display(),
+                vm,
+                target
+            );
+        }
 
-    if !output.status.success() {
-        log_error!(
-            "Failed to create disk {}: {}",
-            disk_path.display(),
-            String::from_utf8_lossy(&output.stderr)
-        );
-        return Err(NovaError::SystemCommandFailed);
+        let xml = block_disk_xml(device, &target, &access);
+        let temp_xml = std::env::temp_dir().join(format!("nova-block-{}-{}.xml", vm, target));
+        fs::write(&temp_xml, &xml)?;
+
+        let mut args = vec!["attach-device", vm, temp_xml.to_str().unwrap_or_default()];
+        args.extend(mode.virsh_flags());
+        let output = exec::run(Command::new("virsh").args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed);
+        let _ = fs::remove_file(&temp_xml);
+        let output = output?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!(
+                "Failed to attach {} to '{}': {}",
+                device.display(),
+                vm,
+                stderr
+            );
+            return Err(NovaError::ConfigError(format!(
+                "virsh attach-device failed: {}",
+                stderr.trim()
+            )));
+        }
+
+        log_info!("Attached {} to '{}' as {}", device.display(), vm, target);
+        Ok(target)
     }
 
-    log_debug!("Disk created at {}", disk_path.display());
-    Ok((disk_path, storage_cfg.format))
+    /// Attach a storage pool volume as a new disk on the next free target of
+    /// `bus` (virtio or SCSI). Applies live when the VM is running and always
+    /// persists to the definition. Returns the guest target.
+    pub async fn attach_volume(
+        &self,
+        vm: &str,
+        pool: &str,
+        volume: &str,
+        bus: DiskBus,
+        access: DiskAccess,
+    ) -> Result<String> {
+        if !matches!(bus, DiskBus::Virtio | DiskBus::Scsi) {
+            return Err(NovaError::ConfigError(format!(
+                "Disks can only be hot-plugged on the virtio or scsi bus, not {}",
+                bus.as_str()
+            )));
+        }
+
+        let output = Command::new("virsh")
+            .args(["vol-dumpxml", "--pool", pool, volume])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::ConfigError(format!(
+                "Volume '{}' not found in pool '{}'",
+                volume, pool
+            )));
+        }
+        let format = parse_volume_format(&String::from_utf8_lossy(&output.stdout));
+        if let Some(warning) = access.validate_format(&format)? {
+            log_warn!("{}/{}: {}", pool, volume, warning);
+        }
+
+        let (xml, running) = self.domain_xml(vm)?;
+        if find_volume_disk(&xml, pool, volume).is_some() {
+            return Err(NovaError::ConfigError(format!(
+                "Volume '{}/{}' is already attached to '{}'",
+                pool, volume, vm
+            )));
+        }
+        let mode = if running {
+            DiskAttachMode::LiveAndPersistent
+        } else {
+            DiskAttachMode::Persistent
+        };
+
+        if bus == DiskBus::Scsi && !xml.contains("<controller type='scsi'") {
+            log_info!("Adding a virtio-scsi controller to VM '{}'", vm);
+            self.attach_device_xml(vm, "<controller type='scsi' model='virtio-scsi'/>", mode)?;
+        }
+
+        let output = Command::new("virsh")
+            .args(["domblklist", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let used = parse_domblklist_targets(&String::from_utf8_lossy(&output.stdout));
+        let target = next_disk_target(&used, bus).ok_or_else(|| {
+            NovaError::ConfigError(format!("No free {} disk target on '{}'", bus.as_str(), vm))
+        })?;
+
+        let disk = volume_disk_xml(pool, volume, &format, &target, bus, &access);
+        self.attach_device_xml(vm, &disk, mode)?;
+
+        log_info!(
+            "Attached volume {}/{} to '{}' as {}",
+            pool,
+            volume,
+            vm,
+            target
+        );
+        Ok(target)
+    }
+
+    /// Detach a pool volume attached with [`Self::attach_volume`]. When the
+    /// QEMU guest agent answers, a disk the guest still has mounted is refused.
+    /// Returns the guest target it was attached as.
+    pub async fn detach_volume(&self, vm: &str, pool: &str, volume: &str) -> Result<String> {
+        let (xml, running) = self.domain_xml(vm)?;
+        let (target, serial) = find_volume_disk(&xml, pool, volume).ok_or_else(|| {
+            NovaError::ConfigError(format!(
+                "Volume '{}/{}' is not attached to '{}'",
+                pool, volume, vm
+            ))
+        })?;
+
+        if running {
+            match guest_agent_command(vm, r#"{"execute":"guest-get-fsinfo"}"#) {
+                Some(fsinfo) => {
+                    let mounts = guest_mounts_on_disk(&fsinfo, &target, serial.as_deref());
+                    if !mounts.is_empty() {
+                        return Err(NovaError::ConfigError(format!(
+                            "'{}' still has {} mounted from {}; unmount it in the guest first",
+                            vm,
+                            mounts.join(", "),
+                            target
+                        )));
+                    }
+                }
+                None => {
+                    log_warn!(
+                        "QEMU guest agent not responding in '{}'; cannot check whether {} is mounted",
+                        vm,
+                        target
+                    );
+                }
+            }
+        }
+
+        let mut cmd = Command::new("virsh");
+        cmd.args(["detach-disk", vm, &target]);
+        if running {
+            cmd.arg("--live");
+        }
+        cmd.arg("--config");
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "virsh detach-disk failed for '{}': {}",
+                vm,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        log_info!(
+            "Detached volume {}/{} ({}) from '{}'",
+            pool,
+            volume,
+            target,
+            vm
+        );
+        Ok(target)
+    }
+
+    /// Live domain XML and whether the domain is running.
+    fn domain_xml(&self, vm: &str) -> Result<(String, bool)> {
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let running = String::from_utf8_lossy(&state.stdout).trim() == "running";
+
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok((
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            running,
+        ))
+    }
+
+    fn attach_device_xml(&self, vm: &str, xml: &str, mode: DiskAttachMode) -> Result<()> {
+        let temp_xml = std::env::temp_dir().join(format!("nova-device-{}.xml", vm));
+        fs::write(&temp_xml, xml)?;
+
+        let mut args = vec!["attach-device", vm, temp_xml.to_str().unwrap_or_default()];
+        args.extend(mode.virsh_flags());
+        let output = exec::run(Command::new("virsh").args(&args))
+            .map_err(|_| NovaError::SystemCommandFailed);
+        let _ = fs::remove_file(&temp_xml);
+        let output = output?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to attach device to '{}': {}", vm, stderr);
+            return Err(NovaError::ConfigError(format!(
+                "virsh attach-device failed: {}",
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Switch the disk bus, NIC model, or video adapter of a defined domain.
+    ///
+    /// The VM must be shut off; the guest needs drivers for the new devices
+    /// (e.g. virtio-win for Windows) before it will boot from a virtio disk.
+    pub async fn update_devices(&self, vm: &str, changes: &DeviceChanges) -> Result<()> {
+        if changes.is_empty() {
+            return Err(NovaError::ConfigError(
+                "No device changes requested".to_string(),
+            ));
+        }
+
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let state = String::from_utf8_lossy(&state.stdout).trim().to_string();
+        if state != "shut off" {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' is {}; shut it down before changing devices",
+                vm, state
+            )));
+        }
+
+        let xml = vm_devices::apply_device_changes(&self.dump_xml(vm, true)?, changes)?;
+        self.redefine(vm, &xml)?;
+
+        log_info!("Updated devices for VM '{}': {:?}", vm, changes);
+        Ok(())
+    }
+
+    /// Raw QEMU arguments in a defined domain's `<qemu:commandline>`.
+    pub fn qemu_args(&self, vm: &str) -> Result<Vec<String>> {
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm, "--inactive"])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok(vm_qemu_args::parse_args(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Replace the raw QEMU arguments of a defined domain, redefining it so
+    /// libvirt validates the result. A running VM picks them up on its next
+    /// cold boot.
+    pub async fn set_qemu_args(&self, vm: &str, args: &[String]) -> Result<()> {
+        let xml = vm_qemu_args::apply_args(&self.dump_xml(vm, true)?, args)?;
+        self.redefine(vm, &xml)?;
+
+        if !args.is_empty() {
+            log_warn!("VM '{}': {}", vm, vm_qemu_args::UNSUPPORTED_WARNING);
+        }
+        log_info!("Set {} raw QEMU argument(s) on VM '{}'", args.len(), vm);
+        Ok(())
+    }
+
+    /// A domain's XML as `virsh dumpxml` prints it. With `inactive`, the
+    /// persistent definition rather than the running configuration.
+    pub fn dump_xml(&self, vm: &str, inactive: bool) -> Result<String> {
+        let mut cmd = Command::new("virsh");
+        cmd.args(["dumpxml", vm]);
+        if inactive {
+            // Keep graphics passwords, or redefining the XML would drop them
+            cmd.args(["--inactive", "--security-info"]);
+        }
+        let output = cmd.output().map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Replace a domain's definition with the hand-edited XML in `file`.
+    /// The XML must be well-formed and keep the domain's name; libvirt then
+    /// validates it against its schema. The previous definition is backed
+    /// up first and the backup's path returned. A running VM picks the
+    /// change up on its next cold boot.
+    pub async fn define_xml(&self, vm: &str, file: &Path) -> Result<PathBuf> {
+        vm_xml::check_domain_xml(&fs::read_to_string(file)?, vm)?;
+        let previous = self.dump_xml(vm, true)?;
+        let backup = vm_xml::save_backup(vm, &previous)?;
+        self.define_validated(vm, file)?;
+        log_info!(
+            "Redefined VM '{}' from edited XML (backup {})",
+            vm,
+            backup.display()
+        );
+        Ok(backup)
+    }
+
+    /// Redefine a domain from its most recent XML backup, removing that
+    /// backup so repeated rollbacks walk further back. Returns the backup
+    /// that was restored.
+    pub async fn rollback_xml(&self, vm: &str) -> Result<PathBuf> {
+        let backup = vm_xml::list_backups(vm).pop().ok_or_else(|| {
+            NovaError::ConfigError(format!("No XML backups of VM '{}' to roll back to", vm))
+        })?;
+        vm_xml::check_domain_xml(&fs::read_to_string(&backup)?, vm)?;
+        self.define_validated(vm, &backup)?;
+        let _ = fs::remove_file(&backup);
+        log_info!("Rolled VM '{}' back to {}", vm, backup.display());
+        Ok(backup)
+    }
+
+    /// Define `vm` from the XML in `file`, which the caller already checked.
+    fn define_validated(&self, vm: &str, file: &Path) -> Result<()> {
+        let output = exec::run(
+            Command::new("virsh")
+                .args(["define", "--validate"])
+                .arg(file),
+        )
+        .map_err(|_| NovaError::SystemCommandFailed)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to redefine '{}' from XML: {}", vm, stderr);
+            return Err(NovaError::ConfigError(format!(
+                "virsh define rejected the XML: {}",
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Define `vm` from generated XML, staged in a private temporary file
+    /// that is removed once libvirt has read it.
+    fn redefine(&self, vm: &str, xml: &str) -> Result<()> {
+        let mut file = tempfile::Builder::new()
+            .prefix(&format!("nova-{}-", vm))
+            .suffix(".xml")
+            .tempfile()?;
+        file.write_all(xml.as_bytes())?;
+        self.define_validated(vm, file.path())
+    }
+
+    /// Add a NIC on `nic.network` (a libvirt network, or a host bridge of
+    /// that name), live when the VM is running and in its persistent
+    /// definition, capped by `limit` like the VM's other NICs. Returns the
+    /// NIC's MAC, which libvirt picks when `nic.mac` is unset.
+    pub async fn add_nic(
+        &self,
+        vm: &str,
+        nic: &VmNic,
+        default_model: NetModel,
+        limit: Option<&NetLimit>,
+    ) -> Result<String> {
+        let running = self.domain_running(vm)?;
+        let before = self.domain_interfaces(vm)?;
+        if let Some(mac) = &nic.mac {
+            vm_nics::validate_mac(mac)?;
+            if before
+                .iter()
+                .any(|iface| iface.mac.eq_ignore_ascii_case(mac))
+            {
+                return Err(NovaError::ConfigError(format!(
+                    "VM '{}' already has a NIC with MAC {}",
+                    vm, mac
+                )));
+            }
+        }
+
+        let kind = if vm_nics::is_host_bridge(&nic.network) {
+            "bridge"
+        } else {
+            "network"
+        };
+        let model = nic.model.unwrap_or(default_model);
+        let mut cmd = Command::new("virsh");
+        cmd.args(["attach-interface", vm, kind, &nic.network])
+            .args(["--model", model.as_str()]);
+        if let Some(mac) = &nic.mac {
+            cmd.args(["--mac", mac]);
+        }
+        cmd.arg("--config");
+        if running {
+            cmd.arg("--live");
+        }
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!(
+                "Failed to add a NIC on {} to '{}': {}",
+                nic.network,
+                vm,
+                stderr
+            );
+            return Err(NovaError::LibvirtError(format!(
+                "virsh attach-interface {} {} {} failed: {}",
+                vm,
+                kind,
+                nic.network,
+                stderr.trim()
+            )));
+        }
+
+        let mac = match &nic.mac {
+            Some(mac) => mac.to_lowercase(),
+            None => self
+                .domain_interfaces(vm)?
+                .into_iter()
+                .map(|iface| iface.mac)
+                .find(|mac| !before.iter().any(|iface| &iface.mac == mac))
+                .unwrap_or_default(),
+        };
+        if let Some(limit) = limit.filter(|limit| !limit.is_unlimited())
+            && !mac.is_empty()
+        {
+            self.set_net_limit(vm, &NetLimitChange::replace(limit), Some(&mac))
+                .await?;
+        }
+        log_info!(
+            "Added {} NIC {} on {} to VM '{}'",
+            model,
+            mac,
+            nic.network,
+            vm
+        );
+        Ok(mac)
+    }
+
+    /// Remove the NIC with this MAC or tap name, live when the VM is
+    /// running and from its persistent definition. Returns the removed
+    /// NIC's MAC and the network or bridge it was on.
+    pub async fn remove_nic(&self, vm: &str, interface: &str) -> Result<(String, String)> {
+        let running = self.domain_running(vm)?;
+        let iface = self
+            .domain_interfaces(vm)?
+            .into_iter()
+            .find(|iface| {
+                iface.mac.eq_ignore_ascii_case(interface) || iface.tap.as_deref() == Some(interface)
+            })
+            .ok_or_else(|| {
+                NovaError::ConfigError(format!("VM '{}' has no interface '{}'", vm, interface))
+            })?;
+
+        let mut cmd = Command::new("virsh");
+        cmd.args(["detach-interface", vm, &iface.kind, "--mac", &iface.mac])
+            .arg("--config");
+        if running {
+            cmd.arg("--live");
+        }
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!(
+                "Failed to remove NIC {} from '{}': {}",
+                iface.mac,
+                vm,
+                stderr
+            );
+            return Err(NovaError::LibvirtError(format!(
+                "virsh detach-interface {} {} failed: {}",
+                vm,
+                iface.mac,
+                stderr.trim()
+            )));
+        }
+
+        log_info!(
+            "Removed NIC {} ({}) from VM '{}'",
+            iface.mac,
+            iface.source,
+            vm
+        );
+        Ok((iface.mac, iface.source))
+    }
+
+    /// Whether the domain is currently running
+    pub fn domain_running(&self, vm: &str) -> Result<bool> {
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok(String::from_utf8_lossy(&state.stdout).trim() == "running")
+    }
+
+    fn domain_interfaces(&self, vm: &str) -> Result<Vec<network::DomainInterface>> {
+        let output = Command::new("virsh")
+            .args(["domiflist", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok(network::parse_domiflist(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Bandwidth limit on each of a defined domain's interfaces.
+    pub fn net_limits(&self, vm: &str) -> Result<Vec<InterfaceNetLimit>> {
+        let output = Command::new("virsh")
+            .args(["domiflist", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+
+        let mut limits = Vec::new();
+        for iface in network::parse_domiflist(&String::from_utf8_lossy(&output.stdout)) {
+            let output = Command::new("virsh")
+                .args(["domiftune", vm, &iface.mac])
+                .output()
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+            if !output.status.success() {
+                continue;
+            }
+            limits.push(InterfaceNetLimit {
+                limit: vm_netlimit::parse_domiftune(&String::from_utf8_lossy(&output.stdout)),
+                mac: iface.mac,
+            });
+        }
+        Ok(limits)
+    }
+
+    /// Apply `change` to the domain's interfaces, or only to `interface` (a
+    /// MAC or tap name), live when the VM is running and in its persistent
+    /// definition. Directions the change leaves out keep each interface's
+    /// current rate. Returns the interfaces changed with their new limits.
+    pub async fn set_net_limit(
+        &self,
+        vm: &str,
+        change: &NetLimitChange,
+        interface: Option<&str>,
+    ) -> Result<Vec<InterfaceNetLimit>> {
+        vm_netlimit::validate(&change.apply(&NetLimit::default()))?;
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let running = String::from_utf8_lossy(&state.stdout).trim() == "running";
+
+        let output = Command::new("virsh")
+            .args(["domiflist", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        let interfaces: Vec<_> = network::parse_domiflist(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|iface| {
+                interface.is_none_or(|wanted| {
+                    iface.mac.eq_ignore_ascii_case(wanted) || iface.tap.as_deref() == Some(wanted)
+                })
+            })
+            .collect();
+        if interfaces.is_empty() {
+            return Err(NovaError::ConfigError(match interface {
+                Some(wanted) => format!("VM '{}' has no interface '{}'", vm, wanted),
+                None => format!("VM '{}' has no network interfaces", vm),
+            }));
+        }
+
+        let mut changed = Vec::new();
+        for iface in interfaces {
+            let current = Command::new("virsh")
+                .args(["domiftune", vm, &iface.mac, "--config"])
+                .output()
+                .map_err(|_| NovaError::SystemCommandFailed)?;
+            if !current.status.success() {
+                return Err(NovaError::LibvirtError(format!(
+                    "virsh domiftune {} {} failed: {}",
+                    vm,
+                    iface.mac,
+                    String::from_utf8_lossy(&current.stderr).trim()
+                )));
+            }
+            let limit = change.apply(&vm_netlimit::parse_domiftune(&String::from_utf8_lossy(
+                &current.stdout,
+            )));
+
+            let mut cmd = Command::new("virsh");
+            cmd.args(["domiftune", vm, &iface.mac])
+                .args(limit.domiftune_args())
+                .arg("--config");
+            if running {
+                cmd.arg("--live");
+            }
+            let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log_error!(
+                    "Failed to set bandwidth on {} of '{}': {}",
+                    iface.mac,
+                    vm,
+                    stderr
+                );
+                return Err(NovaError::LibvirtError(format!(
+                    "virsh domiftune {} {} failed: {}",
+                    vm,
+                    iface.mac,
+                    stderr.trim()
+                )));
+            }
+            log_info!("Set bandwidth of {} on VM '{}' to {}", iface.mac, vm, limit);
+            changed.push(InterfaceNetLimit {
+                mac: iface.mac,
+                limit,
+            });
+        }
+        Ok(changed)
+    }
+
+    /// CPU topology of a defined domain: the declared `<topology>`, or the
+    /// one-socket-per-vCPU layout libvirt uses without one.
+    pub fn cpu_topology(&self, vm: &str) -> Result<(u32, CpuTopology)> {
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm, "--inactive"])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let xml = String::from_utf8_lossy(&output.stdout);
+        let vcpus = vm_cpu::parse_vcpus(&xml).ok_or_else(|| {
+            NovaError::LibvirtError(format!("Domain '{}' has no <vcpu> element", vm))
+        })?;
+        let topology = vm_cpu::parse_topology(&xml).unwrap_or(CpuTopology {
+            sockets: vcpus,
+            cores: 1,
+            threads: 1,
+        });
+        Ok((vcpus, topology))
+    }
+
+    /// Set the number of online vCPUs, live when the VM is running and always
+    /// in its definition. Returns the old and new counts.
+    pub async fn set_vcpus(&self, vm: &str, count: u32) -> Result<(u32, u32)> {
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let running = String::from_utf8_lossy(&state.stdout).trim() == "running";
+
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let xml = String::from_utf8_lossy(&output.stdout);
+        let (Some(maximum), Some(current)) =
+            (vm_cpu::parse_vcpus(&xml), vm_cpu::parse_current_vcpus(&xml))
+        else {
+            return Err(NovaError::LibvirtError(format!(
+                "Domain '{}' has no <vcpu> element",
+                vm
+            )));
+        };
+        vm_cpu::check_vcpu_target(vm, count, maximum)?;
+
+        let mut cmd = Command::new("virsh");
+        cmd.args(["setvcpus", vm, &count.to_string()]);
+        if running {
+            cmd.arg("--live");
+        }
+        cmd.arg("--config");
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("virsh setvcpus failed for '{}': {}", vm, stderr.trim());
+            if stderr.contains("not supported") || stderr.contains("unsupported") {
+                return Err(NovaError::ConfigError(format!(
+                    "vCPU hotplug is not supported for '{}' (needs a q35/pc machine type with \
+                     hotpluggable vCPUs and a guest that can online CPUs): {}",
+                    vm,
+                    stderr.trim()
+                )));
+            }
+            return Err(NovaError::LibvirtError(format!(
+                "virsh setvcpus failed for '{}': {}",
+                vm,
+                stderr.trim()
+            )));
+        }
+
+        log_info!("Set VM '{}' vCPUs from {} to {}", vm, current, count);
+        Ok((current, count))
+    }
+
+    /// vCPU pins in a VM's definition.
+    pub fn cpu_pinning(&self, vm: &str) -> Result<Vec<VcpuPin>> {
+        let (xml, _) = self.domain_xml(vm)?;
+        Ok(vm_cpu::parse_vcpu_pins(&xml))
+    }
+
+    /// Pin vCPUs to host CPUs with `virsh vcpupin`, live when the VM is
+    /// running and always in its definition. Returns whether it was live.
+    pub async fn set_cpu_pinning(&self, vm: &str, pins: &[VcpuPin]) -> Result<bool> {
+        let (xml, running) = self.domain_xml(vm)?;
+        let vcpus = vm_cpu::parse_vcpus(&xml).ok_or_else(|| {
+            NovaError::LibvirtError(format!("Domain '{}' has no <vcpu> element", vm))
+        })?;
+        vm_cpu::validate_pins(pins, vcpus, Some(&vm_cpu::host_online_cpus()))?;
+
+        for pin in pins {
+            let mut cmd = Command::new("virsh");
+            cmd.args(["vcpupin", vm, &pin.vcpu.to_string(), &pin.cpuset]);
+            if running {
+                cmd.arg("--live");
+            }
+            cmd.arg("--config");
+            let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed)?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                log_error!(
+                    "virsh vcpupin failed for '{}' {}: {}",
+                    vm,
+                    pin,
+                    stderr.trim()
+                );
+                return Err(NovaError::LibvirtError(format!(
+                    "Failed to pin vCPU {} of '{}' to {}: {}",
+                    pin.vcpu,
+                    vm,
+                    pin.cpuset,
+                    stderr.trim()
+                )));
+            }
+        }
+
+        log_info!(
+            "Pinned vCPUs of VM '{}': {}",
+            vm,
+            pins.iter()
+                .map(VcpuPin::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        Ok(running)
+    }
+
+    /// Change the sockets/cores/threads layout of a shut-off VM. The product
+    /// must match the VM's vCPU count.
+    pub async fn set_cpu_topology(&self, vm: &str, topology: &CpuTopology) -> Result<()> {
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let state = String::from_utf8_lossy(&state.stdout).trim().to_string();
+        if state != "shut off" {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' is {}; shut it down before changing its CPU topology",
+                vm, state
+            )));
+        }
+
+        let xml = self.dump_xml(vm, true)?;
+        let vcpus = vm_cpu::parse_vcpus(&xml).ok_or_else(|| {
+            NovaError::LibvirtError(format!("Domain '{}' has no <vcpu> element", vm))
+        })?;
+        topology.validate(vcpus)?;
+
+        let xml = vm_cpu::apply_topology(&xml, topology)?;
+        self.redefine(vm, &xml)?;
+
+        log_info!("Set CPU topology of VM '{}' to {}", vm, topology);
+        Ok(())
+    }
+
+    /// Back a shut-off VM's memory with `size` hugepages, or return it to
+    /// normal pages with `None`. Refuses when the host pool is too small to
+    /// start it afterwards.
+    pub async fn set_hugepages(&self, vm: &str, size: Option<HugePageSize>) -> Result<()> {
+        let (xml, running) = self.domain_xml(vm)?;
+        if running {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' is running; shut it down before changing its memory backing",
+                vm
+            )));
+        }
+        if let Some(size) = size {
+            let memory_kib = vm_memory::parse_memory_layout(&xml).memory_kib;
+            vm_hugepages::host_pool(size).check(vm, memory_kib)?;
+        }
+
+        let xml = vm_hugepages::apply_hugepages(&xml, size)?;
+        let temp_xml = std::env::temp_dir().join(format!("nova-hugepages-{}.xml", vm));
+        fs::write(&temp_xml, xml)?;
+
+        let output = exec::run(
+            Command::new("virsh").args(["define", temp_xml.to_str().unwrap_or_default()]),
+        )
+        .map_err(|_| NovaError::SystemCommandFailed);
+        let _ = fs::remove_file(&temp_xml);
+        let output = output?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log_error!("Failed to redefine '{}' with hugepages: {}", vm, stderr);
+            return Err(NovaError::ConfigError(format!(
+                "virsh define rejected the memory backing: {}",
+                stderr.trim()
+            )));
+        }
+
+        match size {
+            Some(size) => {
+                log_info!("Backed VM '{}' with {} hugepages", vm, size);
+            }
+            None => {
+                log_info!("VM '{}' no longer uses hugepages", vm);
+            }
+        }
+        Ok(())
+    }
+
+    /// Hot-add a DIMM of `size_mb` MiB, raising the guest's memory above its
+    /// boot allocation. Applies live when the domain is running and always
+    /// persists to the definition.
+    pub async fn hotplug_memory(&self, vm: &str, size_mb: u64) -> Result<MemoryLayout> {
+        let (layout, running) = self.memory_layout(vm)?;
+        layout.check_hotplug(vm, size_mb)?;
+
+        log_info!("Hot-adding {} MiB DIMM to VM '{}'", size_mb, vm);
+        self.apply_dimm_change(
+            vm,
+            "attach-device",
+            &vm_memory::dimm_xml(size_mb, 0),
+            running,
+        )?;
+        Ok(self.memory_layout(vm)?.0)
+    }
+
+    /// Remove the most recently added DIMM of `size_mb` MiB. The guest must
+    /// be able to offline that memory, so this can fail under load.
+    pub async fn unplug_memory(&self, vm: &str, size_mb: u64) -> Result<MemoryLayout> {
+        let (layout, running) = self.memory_layout(vm)?;
+        let dimm = layout.dimm_to_unplug(size_mb).ok_or_else(|| {
+            let sizes: Vec<String> = layout
+                .dimms
+                .iter()
+                .map(|dimm| format!("{} MiB", dimm.size_kib / 1024))
+                .collect();
+            NovaError::ConfigError(format!(
+                "VM '{}' has no {} MiB DIMM to unplug (plugged: {})",
+                vm,
+                size_mb,
+                if sizes.is_empty() {
+                    "none".to_string()
+                } else {
+                    sizes.join(", ")
+                }
+            ))
+        })?;
+
+        log_info!("Unplugging {} MiB DIMM from VM '{}'", size_mb, vm);
+        self.apply_dimm_change(vm, "detach-device", &dimm.xml, running)?;
+        Ok(self.memory_layout(vm)?.0)
+    }
+
+    /// Balloon the VM to `size_kib`, live when it is running and always in
+    /// its definition. A running VM is clamped to its `<memory>` ceiling; a
+    /// shut-off one has the ceiling raised with `setmaxmem` instead.
+    pub async fn set_memory(&self, vm: &str, size_kib: u64) -> Result<BalloonPlan> {
+        let (layout, running) = self.memory_layout(vm)?;
+        let plan = layout.plan_balloon(vm, size_kib, running)?;
+
+        if let Some(max_kib) = plan.raise_max_to_kib {
+            run_virsh_memory(vm, "setmaxmem", max_kib, &["--config"])?;
+        }
+        if !running {
+            run_virsh_memory(vm, "setmem", plan.target_kib, &["--config"])?;
+            log_info!("Set memory of VM '{}' to {} KiB", vm, plan.target_kib);
+            return Ok(plan);
+        }
+
+        let before = self.balloon_actual(vm);
+        run_virsh_memory(vm, "setmem", plan.target_kib, &["--live", "--config"])?;
+
+        // The guest driver moves the balloon; without it `actual` never changes
+        if !exec::is_dry_run() && before.is_some_and(|actual| actual != plan.target_kib) {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            let mut moved = false;
+            while std::time::Instant::now() < deadline {
+                sleep(Duration::from_millis(500)).await;
+                if self.balloon_actual(vm) != before {
+                    moved = true;
+                    break;
+                }
+            }
+            if !moved {
+                return Err(NovaError::ConfigError(format!(
+                    "The guest in '{}' did not respond to the balloon request; its virtio-balloon \
+                     driver is not loaded (install virtio-win guest tools on Windows, or load \
+                     virtio_balloon on Linux). The new size is saved and applies on next boot.",
+                    vm
+                )));
+            }
+        }
+
+        log_info!("Ballooned VM '{}' to {} KiB", vm, plan.target_kib);
+        Ok(plan)
+    }
+
+    fn balloon_actual(&self, vm: &str) -> Option<u64> {
+        let output = Command::new("virsh")
+            .args(["dommemstat", vm])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        vm_memory::parse_balloon_actual(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// File the VM's serial console is logged to, for `nova logs`.
+    pub fn serial_log_path(&self, vm: &str) -> Result<PathBuf> {
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        parse_serial_log_path(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+            NovaError::ConfigError(format!(
+                "VM '{}' does not log its serial console. Add <log file='/var/log/libvirt/qemu/{}-serial.log' \
+                 append='on'/> to its <serial> device with `virsh edit {}`, or attach with `virsh console {}`",
+                vm, vm, vm, vm
+            ))
+        })
+    }
+
+    fn memory_layout(&self, vm: &str) -> Result<(MemoryLayout, bool)> {
+        let state = Command::new("virsh")
+            .args(["domstate", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !state.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        let running = String::from_utf8_lossy(&state.stdout).trim() == "running";
+
+        let output = Command::new("virsh")
+            .args(["dumpxml", vm])
+            .output()
+            .map_err(|_| NovaError::SystemCommandFailed)?;
+        if !output.status.success() {
+            return Err(NovaError::VmNotFound(vm.to_string()));
+        }
+        Ok((
+            vm_memory::parse_memory_layout(&String::from_utf8_lossy(&output.stdout)),
+            running,
+        ))
+    }
+
+    fn apply_dimm_change(&self, vm: &str, action: &str, xml: &str, running: bool) -> Result<()> {
+        let xml_path = std::env::temp_dir().join(format!("nova-{}-dimm.xml", vm));
+        fs::write(&xml_path, xml)?;
+
+        let mut cmd = Command::new("virsh");
+        cmd.args([action, vm]).arg(&xml_path);
+        if running {
+            cmd.arg("--live");
+        }
+        cmd.arg("--config");
+        let output = exec::run(&mut cmd).map_err(|_| NovaError::SystemCommandFailed);
+        let _ = fs::remove_file(&xml_path);
+        let output = output?;
+
+        if !output.status.success() {
+            return Err(NovaError::LibvirtError(format!(
+                "virsh {} failed for '{}': {}",
+                action,
+                vm,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    // Check if libvirt is available and try to use it
+    pub fn check_libvirt(&self) -> bool {
+        Command::new("virsh")
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+impl Default for VmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which domain definition a hot-plugged device is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskAttachMode {
+    /// Running domain only; gone after the VM is shut down
+    Live,
+    /// Persistent definition only; takes effect on next boot
+    Persistent,
+    /// Both the running domain and the persistent definition
+    LiveAndPersistent,
+}
+
+impl DiskAttachMode {
+    fn virsh_flags(self) -> Vec<&'static str> {
+        match self {
+            DiskAttachMode::Live => vec!["--live"],
+            DiskAttachMode::Persistent => vec!["--config"],
+            DiskAttachMode::LiveAndPersistent => vec!["--live", "--config"],
+        }
+    }
+}
+
+/// Read-only, shareable and cache settings of an attached disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiskAccess {
+    pub readonly: bool,
+    pub shareable: bool,
+    pub cache: DiskCache,
+}
+
+/// Warning for a disk several VMs can write to at once.
+pub const WRITABLE_SHARED_WARNING: &str = "shareable and writable: every VM attached to it can write \
+     at the same time, which corrupts ordinary filesystems (ext4, XFS, NTFS); use a cluster \
+     filesystem such as GFS2 or OCFS2, or attach it --readonly";
+
+impl DiskAccess {
+    /// Refuse sharing through the host page cache, where VMs would read
+    /// stale data. A writable shared disk is allowed but returns a warning.
+    pub fn validate(&self) -> Result<Option<&'static str>> {
+        if self.shareable && !self.cache.is_direct() {
+            return Err(NovaError::ConfigError(format!(
+                "Shareable disks must bypass the host page cache; cache mode '{}' is not allowed \
+                 (use 'none' or 'directsync')",
+                self.cache.as_str()
+            )));
+        }
+        Ok((self.shareable && !self.readonly).then_some(WRITABLE_SHARED_WARNING))
+    }
+
+    /// [`Self::validate`] for an image in `format`. Only raw images can be
+    /// shared; QEMU keeps qcow2 metadata cached per VM.
+    pub fn validate_format(&self, format: &str) -> Result<Option<&'static str>> {
+        if self.shareable && format != "raw" {
+            return Err(NovaError::ConfigError(format!(
+                "Only raw images can be shareable, not {}",
+                format
+            )));
+        }
+        self.validate()
+    }
+
+    /// `<readonly/>` and `<shareable/>` lines of a libvirt `<disk>`.
+    fn disk_xml_flags(&self) -> String {
+        let mut flags = String::new();
+        if self.readonly {
+            flags.push_str("\n  <readonly/>");
+        }
+        if self.shareable {
+            flags.push_str("\n  <shareable/>");
+        }
+        flags
+    }
+
+    /// Options appended to a QEMU `-drive` when Nova launches QEMU directly.
+    fn qemu_drive_options(&self) -> String {
+        let mut options = format!("cache={},aio={}", self.cache.as_str(), self.aio());
+        if self.readonly {
+            options.push_str(",readonly=on");
+        }
+        if self.shareable {
+            // QEMU otherwise locks the image against a second VM opening it
+            options.push_str(",file.locking=off");
+        }
+        options
+    }
+
+    /// Native AIO needs O_DIRECT, which only the direct cache modes use.
+    fn aio(&self) -> &'static str {
+        if self.cache.is_direct() {
+            "native"
+        } else {
+            "threads"
+        }
+    }
+}
+
+/// Map a `virsh domstate` string to an instance status.
+pub fn instance_status_from_domstate(state: &str) -> Option<InstanceStatus> {
+    match state.trim() {
+        "running" | "idle" | "blocked" => Some(InstanceStatus::Running),
+        "paused" | "pmsuspended" => Some(InstanceStatus::Suspended),
+        "shut off" => Some(InstanceStatus::Stopped),
+        "in shutdown" => Some(InstanceStatus::Stopping),
+        "crashed" => Some(InstanceStatus::Error),
+        _ => None,
+    }
+}
+
+/// Log file of the first `<serial>` or `<console>` device that has a `<log>`.
+pub fn parse_serial_log_path(xml: &str) -> Option<PathBuf> {
+    let device = Regex::new(r"(?s)<(serial|console)\b[^>]*>(.*?)</(?:serial|console)>")
+        .expect("valid serial pattern");
+    let log = Regex::new(r#"<log\b[^>]*\bfile=['"]([^'"]+)['"]"#).expect("valid log pattern");
+    device
+        .captures_iter(xml)
+        .find_map(|caps| Some(PathBuf::from(&log.captures(caps.get(2)?.as_str())?[1])))
+}
+
+/// Parse the `Id Name State` table from `virsh list --all` into
+/// `(name, state)` pairs.
+fn parse_virsh_list(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _id = fields.next()?;
+            let name = fields.next()?;
+            let state = fields.collect::<Vec<_>>().join(" ");
+            (!state.is_empty()).then(|| (name.to_string(), state))
+        })
+        .collect()
+}
+
+/// vCPU count and maximum memory (KiB) from `virsh dominfo`.
+fn parse_dominfo_size(output: &str) -> (Option<u32>, Option<u64>) {
+    let value = |key: &str| {
+        output
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(field, _)| field.trim() == key)
+            .and_then(|(_, value)| value.split_whitespace().next())
+            .map(str::to_string)
+    };
+    (
+        value("CPU(s)").and_then(|cpus| cpus.parse().ok()),
+        value("Max memory").and_then(|memory| memory.parse().ok()),
+    )
+}
+
+/// The `Autostart:` field of `virsh dominfo` (`enable` or `disable`).
+fn parse_dominfo_autostart(output: &str) -> Option<bool> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(field, _)| field.trim() == "Autostart")
+        .and_then(|(_, value)| match value.trim() {
+            "enable" => Some(true),
+            "disable" => Some(false),
+            _ => None,
+        })
+}
+
+/// Refuse anything that is not an unused block device.
+pub fn validate_block_device(device: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = fs::metadata(device).map_err(|_| {
+        NovaError::ConfigError(format!("Block device {} does not exist", device.display()))
+    })?;
+    if !metadata.file_type().is_block_device() {
+        return Err(NovaError::ConfigError(format!(
+            "{} is not a block device",
+            device.display()
+        )));
+    }
+
+    let canonical = fs::canonicalize(device)?;
+    let mounts = fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let swaps = fs::read_to_string("/proc/swaps").unwrap_or_default();
+    let mut users = block_device_users(&canonical, &mounts, &swaps);
+
+    // Partitions are listed in sysfs whatever they are named, so their
+    // mounts are found even where the name doesn't extend the disk's
+    let sysfs = Path::new("/sys/class/block");
+    let mut names: Vec<String> = canonical
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .into_iter()
+        .collect();
+    if let Some(disk) = names.first() {
+        let partitions = block_device_partitions(&sysfs.join(disk));
+        for partition in &partitions {
+            let path = Path::new("/dev").join(partition);
+            users.extend(block_device_users(&path, &mounts, &swaps));
+        }
+        names.extend(partitions);
+    }
+
+    // LVM, dm-crypt and md arrays show up as holders in sysfs, of the disk
+    // or of one of its partitions
+    for name in &names {
+        if let Ok(entries) = fs::read_dir(sysfs.join(name).join("holders")) {
+            users.extend(
+                entries
+                    .flatten()
+                    .map(|entry| format!("held by {}", entry.file_name().to_string_lossy())),
+            );
+        }
+    }
+    users.sort();
+    users.dedup();
+
+    if !users.is_empty() {
+        return Err(NovaError::ConfigError(format!(
+            "{} is in use on the host ({}); refusing to pass it to a VM to avoid corruption",
+            device.display(),
+            users.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Partitions of the disk at `sysfs_dir` (`/sys/class/block/<disk>`):
+/// the subdirectories that carry a `partition` file.
+pub fn block_device_partitions(sysfs_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(sysfs_dir) else {
+        return Vec::new();
+    };
+    let mut partitions: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join("partition").is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    partitions.sort();
+    partitions
+}
+
+/// Mounts and swap areas in `/proc/mounts` / `/proc/swaps` that live on
+/// `device` or one of its partitions.
+pub fn block_device_users(device: &Path, mounts: &str, swaps: &str) -> Vec<String> {
+    let device = device.to_string_lossy();
+    let on_device = |source: &str| {
+        let source = fs::canonicalize(source)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| source.to_string());
+        match source.strip_prefix(device.as_ref()) {
+            Some("") => true,
+            Some(rest) => {
+                let rest = rest.strip_prefix('p').unwrap_or(rest);
+                !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+            }
+            None => false,
+        }
+    };
+
+    let mut users: Vec<String> = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let source = fields.next()?;
+            let mountpoint = fields.next()?;
+            on_device(source).then(|| format!("{} mounted on {}", source, mountpoint))
+        })
+        .collect();
+
+    users.extend(swaps.lines().skip(1).filter_map(|line| {
+        let source = line.split_whitespace().next()?;
+        on_device(source).then(|| format!("{} used as swap", source))
+    }));
+
+    users
+}
+
+/// Libvirt `<disk>` element for a raw block device.
+pub fn block_disk_xml(device: &Path, target: &str, access: &DiskAccess) -> String {
+    format!(
+        r#"<disk type='block' device='disk'>
+  <driver name='qemu' type='raw' cache='{}' io='{}'/>
+  <source dev='{}'/>
+  <target dev='{}' bus='virtio'/>{}
+</disk>"#,
+        access.cache.as_str(),
+        access.aio(),
+        device.display(),
+        target,
+        access.disk_xml_flags()
+    )
+}
+
+fn parse_domblklist_targets(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split_whitespace().next().map(str::to_string))
+        .collect()
+}
+
+/// First unused `vdX` target.
+pub fn next_virtio_target(used: &[String]) -> Option<String> {
+    next_disk_target(used, DiskBus::Virtio)
+}
+
+/// First unused target for `bus`, e.g. `sdb` when `sda` is taken.
+pub fn next_disk_target(used: &[String], bus: DiskBus) -> Option<String> {
+    bus.next_target(used)
+}
+
+/// Serial a pool volume is attached with, so the guest agent can tell which
+/// guest disk it is. QEMU caps virtio-blk serials at 20 characters.
+pub fn volume_disk_serial(volume: &str) -> String {
+    volume
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .take(20)
+        .collect()
+}
+
+/// Libvirt `<disk>` element for a storage pool volume.
+pub fn volume_disk_xml(
+    pool: &str,
+    volume: &str,
+    format: &str,
+    target: &str,
+    bus: DiskBus,
+    access: &DiskAccess,
+) -> String {
+    format!(
+        r#"<disk type='volume' device='disk'>
+  <driver name='qemu' type='{}' cache='{}'/>
+  <source pool='{}' volume='{}'/>
+  <target dev='{}' bus='{}'/>
+  <serial>{}</serial>{}
+</disk>"#,
+        format,
+        access.cache.as_str(),
+        pool,
+        volume,
+        target,
+        bus.as_str(),
+        volume_disk_serial(volume),
+        access.disk_xml_flags()
+    )
+}
+
+/// Image format from `virsh vol-dumpxml`; raw when the pool doesn't say.
+pub fn parse_volume_format(xml: &str) -> String {
+    Regex::new(r"<format type='([^']+)'")
+        .expect("valid format pattern")
+        .captures(xml)
+        .map(|caps| caps[1].to_string())
+        .filter(|format| format != "none")
+        .unwrap_or_else(|| "raw".to_string())
+}
+
+/// Sources of the `device='disk'` disks in a domain definition. CD-ROMs
+/// and floppies are skipped, so installer ISOs shared between VMs are never
+/// picked up for deletion.
+pub fn domain_disk_sources(xml: &str) -> Vec<DiskSource> {
+    let disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+    let source = Regex::new(r"<source\b[^>]*>").expect("valid source pattern");
+    let attribute = |tag: &str, name: &str| {
+        Regex::new(&format!(r#"\b{}=(?:'([^']*)'|"([^"]*)")"#, name))
+            .expect("valid attribute pattern")
+            .captures(tag)
+            .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .map(|value| value.as_str().to_string())
+    };
+
+    disk.find_iter(xml)
+        .map(|block| block.as_str())
+        .filter(|block| {
+            let tag = &block[..block.find('>').unwrap_or(block.len())];
+            attribute(tag, "device").is_none_or(|device| device == "disk")
+        })
+        .filter_map(|block| {
+            let tag = source.find(block)?.as_str();
+            if let (Some(pool), Some(volume)) = (attribute(tag, "pool"), attribute(tag, "volume")) {
+                return Some(DiskSource::Volume { pool, volume });
+            }
+            attribute(tag, "file")
+                .or_else(|| attribute(tag, "dev"))
+                .map(|path| DiskSource::Path(PathBuf::from(path)))
+        })
+        .collect()
+}
+
+/// Names of a domain's libvirt snapshots, or only the tree roots.
+/// Host path of a disk, resolving pool volumes with `virsh vol-path`.
+fn disk_source_path(disk: &DiskSource) -> Option<PathBuf> {
+    match disk {
+        DiskSource::Path(path) => Some(path.clone()),
+        DiskSource::Volume { pool, volume } => Command::new("virsh")
+            .args(["vol-path", volume, "--pool", pool])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())),
+    }
+}
+
+/// Other domains with one of `disks` in a disk's backing chain, e.g. linked
+/// clones of `vm`.
+fn backing_file_users(vm: &str, disks: &[DiskSource]) -> Vec<String> {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let disks: Vec<PathBuf> = disks
+        .iter()
+        .filter_map(disk_source_path)
+        .map(|path| canonical(&path))
+        .collect();
+    if disks.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(output) = Command::new("virsh")
+        .args(["list", "--all", "--name"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|other| !other.is_empty() && *other != vm)
+        .filter(|other| {
+            let Some(xml) = Command::new("virsh")
+                .args(["dumpxml", "--inactive", other])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            else {
+                return false;
+            };
+            domain_disk_sources(&xml)
+                .iter()
+                .filter_map(disk_source_path)
+                .any(|overlay| {
+                    backing_files(&overlay)
+                        .iter()
+                        .any(|backing| disks.contains(&canonical(backing)))
+                })
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split a disk's backing chain, top first, into the layers that can be
+/// deleted and the first one `keep` gives a reason to leave in place.
+/// Everything under a kept layer still backs it, so deletion stops there.
+fn removable_chain(
+    chain: &[PathBuf],
+    keep: impl Fn(&Path) -> Option<String>,
+) -> (&[PathBuf], Option<(PathBuf, String)>) {
+    for (index, layer) in chain.iter().enumerate() {
+        if let Some(reason) = keep(layer) {
+            return (&chain[..index], Some((layer.clone(), reason)));
+        }
+    }
+    (chain, None)
+}
+
+/// Backing files below `image`, from `qemu-img info --backing-chain`.
+fn backing_files(image: &Path) -> Vec<PathBuf> {
+    Command::new("qemu-img")
+        .args(["info", "-U", "--backing-chain", "--output=json"])
+        .arg(image)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            templates_snapshots::parse_backing_chain(&String::from_utf8_lossy(&output.stdout)).ok()
+        })
+        .map(|layers| {
+            layers
+                .into_iter()
+                .skip(1)
+                .map(|layer| PathBuf::from(layer.filename))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn virsh_snapshot_names(vm: &str, roots: bool) -> Vec<String> {
+    let mut cmd = Command::new("virsh");
+    cmd.args(["snapshot-list", vm, "--name"]);
+    if roots {
+        cmd.arg("--roots");
+    }
+    match cmd.output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Target and serial of the `<disk>` backed by `pool`/`volume`.
+pub fn find_volume_disk(xml: &str, pool: &str, volume: &str) -> Option<(String, Option<String>)> {
+    let disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+    let source = format!("pool='{}' volume='{}'", pool, volume);
+    let block = disk
+        .find_iter(xml)
+        .map(|block| block.as_str())
+        .find(|block| block.contains(&source))?;
+    let target = Regex::new(r"<target dev='([^']+)'")
+        .expect("valid target pattern")
+        .captures(block)?[1]
+        .to_string();
+    let serial = Regex::new(r"<serial>([^<]+)</serial>")
+        .expect("valid serial pattern")
+        .captures(block)
+        .map(|caps| caps[1].to_string());
+    Some((target, serial))
+}
+
+/// Serial of the `<disk>` attached as `target`, when it has one.
+pub fn disk_serial(xml: &str, target: &str) -> Option<String> {
+    let disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+    let dev = format!("<target dev='{}'", target);
+    let block = disk
+        .find_iter(xml)
+        .map(|block| block.as_str())
+        .find(|block| block.contains(&dev))?;
+    Regex::new(r"<serial>([^<]+)</serial>")
+        .expect("valid serial pattern")
+        .captures(block)
+        .map(|caps| caps[1].to_string())
+}
+
+/// Whether a disk in a `guest-get-fsinfo` entry is the one attached as
+/// `target`. A serial both sides know decides; otherwise the guest device
+/// node must be `target` itself or a partition of it (`vda1`, `nvme0n1p2`),
+/// which assumes the guest names the disk like the host does.
+fn guest_disk_is(disk: &serde_json::Value, target: &str, serial: Option<&str>) -> bool {
+    if let (Some(serial), Some(reported)) = (serial, disk["serial"].as_str()) {
+        return reported == serial;
+    }
+    let Some(rest) = disk["dev"]
+        .as_str()
+        .and_then(|dev| dev.strip_prefix("/dev/"))
+        .and_then(|dev| dev.strip_prefix(target))
+    else {
+        return false;
+    };
+    let number = match rest.strip_prefix('p') {
+        Some(number) if target.ends_with(|c: char| c.is_ascii_digit()) => number,
+        _ => rest,
+    };
+    rest.is_empty() || (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Mountpoints in a `guest-get-fsinfo` reply on the disk attached as
+/// `target`, matched by serial (any guest) or device node (Linux).
+pub fn guest_mounts_on_disk(reply: &str, target: &str, serial: Option<&str>) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(reply) else {
+        return Vec::new();
+    };
+    value["return"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            entry["disk"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|disk| guest_disk_is(disk, target, serial))
+        })
+        .filter_map(|entry| entry["mountpoint"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// `virsh setmem`/`setmaxmem` with a size in KiB.
+fn run_virsh_memory(vm: &str, action: &str, size_kib: u64, flags: &[&str]) -> Result<()> {
+    let output = exec::run(
+        Command::new("virsh")
+            .args([action, vm, &size_kib.to_string()])
+            .args(flags),
+    )
+    .map_err(|_| NovaError::SystemCommandFailed)?;
+    if !output.status.success() {
+        return Err(NovaError::LibvirtError(format!(
+            "virsh {} failed for '{}': {}",
+            action,
+            vm,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn guest_agent_command(vm: &str, command: &str) -> Option<String> {
+    let output = Command::new("virsh")
+        .args(["qemu-agent-command", vm, command])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_domblkinfo_capacity(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("Capacity:")?;
+        value.trim().parse().ok()
+    })
+}
+
+/// Pick the filesystems from a `guest-get-fsinfo` reply that sit on `target`,
+/// the disk with `serial` when it has one.
+///
+/// Linux guests report device nodes such as `/dev/vda1`; Windows guests only
+/// expose volume GUIDs, so without a serial every NTFS volume is returned.
+pub fn parse_guest_fsinfo(reply: &str, target: &str, serial: Option<&str>) -> Vec<GuestFilesystem> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(reply) else {
+        return Vec::new();
+    };
+    let Some(entries) = value.get("return").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let field = |key: &str| entry.get(key).and_then(|v| v.as_str()).unwrap_or("");
+            let fs_type = field("type").to_lowercase();
+            let disks = entry.get("disk").and_then(|v| v.as_array());
+            let disk = disks
+                .into_iter()
+                .flatten()
+                .find(|disk| guest_disk_is(disk, target, serial));
+
+            let device = match disk
+                .and_then(|disk| disk.get("dev"))
+                .and_then(|v| v.as_str())
+            {
+                Some(device) => device.to_string(),
+                None if disk.is_some() || (serial.is_none() && fs_type == "ntfs") => {
+                    field("name").to_string()
+                }
+                None => return None,
+            };
+
+            Some(GuestFilesystem {
+                device,
+                mountpoint: field("mountpoint").to_string(),
+                fs_type,
+            })
+        })
+        .collect()
+}
+
+/// Command an operator should run in the guest to use the new space.
+pub fn guest_grow_command(filesystem: &GuestFilesystem) -> String {
+    let device = &filesystem.device;
+    let mountpoint = &filesystem.mountpoint;
+
+    // "/dev/vda1" -> growpart /dev/vda 1; "/dev/nvme0n1p2" -> growpart /dev/nvme0n1 2
+    let split = device.trim_end_matches(|c: char| c.is_ascii_digit());
+    let part_number = &device[split.len()..];
+    let growpart = if part_number.is_empty() || !device.starts_with("/dev/") {
+        String::new()
+    } else {
+        let disk = if split.ends_with('p')
+            && split[..split.len() - 1].ends_with(|c: char| c.is_ascii_digit())
+        {
+            &split[..split.len() - 1]
+        } else {
+            split
+        };
+        format!("growpart {} {} && ", disk, part_number)
+    };
+
+    match filesystem.fs_type.as_str() {
+        "ext2" | "ext3" | "ext4" => format!("{}resize2fs {}", growpart, device),
+        "xfs" => format!("{}xfs_growfs {}", growpart, mountpoint),
+        "btrfs" => format!("{}btrfs filesystem resize max {}", growpart, mountpoint),
+        "ntfs" => format!(
+            "Resize-Partition -DriveLetter {} -Size (Get-PartitionSupportedSize -DriveLetter {}).SizeMax",
+            drive_letter(mountpoint),
+            drive_letter(mountpoint)
+        ),
+        other => format!(
+            "{}grow the {} filesystem on {} with its native tool",
+            growpart, other, device
+        ),
+    }
+}
+
+fn drive_letter(mountpoint: &str) -> String {
+    mountpoint
+        .chars()
+        .next()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase().to_string())
+        .unwrap_or_else(|| "C".to_string())
+}
+
+pub(crate) async fn prepare_vm_disk(
+    vm_name: &str,
+    config: &VmConfig,
+) -> Result<(PathBuf, DiskFormat)> {
+    if let Some(image_path) = &config.image {
+        return Ok((PathBuf::from(image_path), config.storage.format));
+    }
+
+    let storage_cfg = config.storage.clone();
+    let disk_path = storage_cfg.resolve_disk_path(vm_name);
+
+    if tokio::fs::metadata(&disk_path).await.is_ok() {
+        return Ok((disk_path, storage_cfg.format));
+    }
+
+    if !storage_cfg.create_if_missing {
+        return Err(NovaError::ConfigError(format!(
+            "Disk image '{}' not found and auto-creation disabled",
+            disk_path.display()
+        )));
+    }
+
+    if let Some(parent) = disk_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    log_info!(
+        "Provisioning disk for VM '{}' at {} (format={}, size={})",
+        vm_name,
+        disk_path.display(),
+        storage_cfg.format.as_str(),
+        storage_cfg.size
+    );
+
+    let output = exec::run(Command::new("qemu-img").args([
+        "create",
+        "-f",
+        storage_cfg.format.as_str(),
+        &disk_path.to_string_lossy(),
+        &storage_cfg.size,
+    ]))
+    .map_err(|_| NovaError::SystemCommandFailed)?;
+
+    if !output.status.success() {
+        log_error!(
+            "Failed to create disk {}: {}",
+            disk_path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return Err(NovaError::SystemCommandFailed);
+    }
+
+    log_debug!("Disk created at {}", disk_path.display());
+    Ok((disk_path, storage_cfg.format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virsh_list_states_map_to_instance_status() {
+        let output = " Id   Name      State
+-------------------------------
+ 3    dev-box   paused
+ 7    win11     running
+ -    archive   shut off
+
+";
+        let domains = parse_virsh_list(output);
+        assert_eq!(
+            domains,
+            vec![
+                ("dev-box".to_string(), "paused".to_string()),
+                ("win11".to_string(), "running".to_string()),
+                ("archive".to_string(), "shut off".to_string()),
+            ]
+        );
+        let statuses: Vec<_> = domains
+            .iter()
+            .map(|(_, state)| instance_status_from_domstate(state))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(InstanceStatus::Suspended),
+                Some(InstanceStatus::Running),
+                Some(InstanceStatus::Stopped),
+            ]
+        );
+        assert_eq!(instance_status_from_domstate("bogus"), None);
+
+        let dominfo = "Id:             7\nName:           win11\nState:          running\nCPU(s):         8\nMax memory:     16777216 KiB\nAutostart:      enable\n";
+        assert_eq!(parse_dominfo_size(dominfo), (Some(8), Some(16777216)));
+        assert_eq!(parse_dominfo_autostart(dominfo), Some(true));
+        assert_eq!(
+            parse_dominfo_autostart("Autostart:      disable\n"),
+            Some(false)
+        );
+        assert_eq!(parse_dominfo_autostart("Name: x\n"), None);
+    }
+
+    #[test]
+    fn serial_log_path_comes_from_the_serial_device() {
+        let xml = "<devices>
+    <serial type='pty'>
+      <target type='isa-serial' port='0'/>
+    </serial>
+    <console type='pty'>
+      <log file='/var/log/libvirt/qemu/web-serial.log' append='on'/>
+      <target type='serial' port='0'/>
+    </console>
+  </devices>";
+        assert_eq!(
+            parse_serial_log_path(xml),
+            Some(PathBuf::from("/var/log/libvirt/qemu/web-serial.log"))
+        );
+        assert_eq!(
+            parse_serial_log_path("<devices><serial type='pty'/></devices>"),
+            None
+        );
+    }
+
+    #[test]
+    fn pool_volumes_attach_on_the_next_target() {
+        let used = vec!["vda".to_string(), "sda".to_string()];
+        assert_eq!(
+            next_disk_target(&used, DiskBus::Virtio).as_deref(),
+            Some("vdb")
+        );
+        assert_eq!(
+            next_disk_target(&used, DiskBus::Scsi).as_deref(),
+            Some("sdb")
+        );
+
+        assert_eq!(
+            parse_volume_format("<target><format type='qcow2'/></target>"),
+            "qcow2"
+        );
+        assert_eq!(parse_volume_format("<target></target>"), "raw");
+        assert_eq!(
+            volume_disk_serial("build cache volume 2024"),
+            "buildcachevolume2024"
+        );
+
+        let disk = volume_disk_xml(
+            "fast",
+            "data.qcow2",
+            "qcow2",
+            "vdb",
+            DiskBus::Virtio,
+            &DiskAccess::default(),
+        );
+        let domain = format!(
+            "<devices>\n<disk type='file' device='disk'>\n  <target dev='vda' bus='virtio'/>\n</disk>\n{}\n</devices>",
+            disk
+        );
+        assert_eq!(
+            find_volume_disk(&domain, "fast", "data.qcow2"),
+            Some(("vdb".to_string(), Some("data.qcow2".to_string())))
+        );
+        assert_eq!(find_volume_disk(&domain, "fast", "other"), None);
+
+        let fsinfo = r#"{"return":[
+            {"name":"vdb1","mountpoint":"/srv/data","type":"ext4","disk":[{"dev":"/dev/vdb1","serial":"data.qcow2"}]},
+            {"name":"vda2","mountpoint":"/","type":"xfs","disk":[{"dev":"/dev/vda2"}]},
+            {"name":"\\\\?\\Volume{1}","mountpoint":"E:\\","type":"NTFS","disk":[{"serial":"data.qcow2"}]}]}"#;
+        assert_eq!(
+            guest_mounts_on_disk(fsinfo, "vdb", Some("data.qcow2")),
+            vec!["/srv/data".to_string(), "E:\\".to_string()]
+        );
+        assert!(guest_mounts_on_disk(fsinfo, "vdc", None).is_empty());
+        // vdaa is a different disk, not a partition of vda
+        let vdaa = r#"{"return":[{"name":"vdaa1","mountpoint":"/big","type":"ext4","disk":[{"dev":"/dev/vdaa1"}]}]}"#;
+        assert!(guest_mounts_on_disk(vdaa, "vda", None).is_empty());
+        assert_eq!(guest_mounts_on_disk(vdaa, "vdaa", None), vec!["/big"]);
+    }
+
+    #[test]
+    fn guest_grow_suggestions_for_resized_disk() {
+        let reply = r#"{"return":[
+            {"name":"vda1","mountpoint":"/","type":"ext4","disk":[{"dev":"/dev/vda1"}]},
+            {"name":"vdb1","mountpoint":"/srv","type":"xfs","disk":[{"dev":"/dev/vdb1"}]},
+            {"name":"vdaa1","mountpoint":"/big","type":"ext4","disk":[{"dev":"/dev/vdaa1"}]},
+            {"name":"nvme0n1p2","mountpoint":"/data","type":"btrfs","disk":[{"dev":"/dev/vda2"}]}
+        ]}"#;
+
+        let filesystems = parse_guest_fsinfo(reply, "vda", None);
+        assert_eq!(
+            filesystems.len(),
+            2,
+            "vdb and vdaa filesystems must be ignored"
+        );
+        assert_eq!(
+            guest_grow_command(&filesystems[0]),
+            "growpart /dev/vda 1 && resize2fs /dev/vda1"
+        );
+        assert_eq!(
+            guest_grow_command(&filesystems[1]),
+            "growpart /dev/vda 2 && btrfs filesystem resize max /data"
+        );
+
+        let nvme = parse_guest_fsinfo(
+            r#"{"return":[{"name":"nvme0n1p3","mountpoint":"/","type":"xfs","disk":[{"dev":"/dev/nvme0n1p3"}]}]}"#,
+            "nvme0n1",
+            None,
+        );
+        assert_eq!(
+            guest_grow_command(&nvme[0]),
+            "growpart /dev/nvme0n1 3 && xfs_growfs /"
+        );
+
+        assert!(parse_guest_fsinfo("not json", "vda", None).is_empty());
+    }
+
+    #[test]
+    fn guest_filesystems_follow_the_disk_serial() {
+        // The host attached the disk as vdb, but the guest enumerated it as vdc
+        let reply = r#"{"return":[
+            {"name":"vdc1","mountpoint":"/srv","type":"xfs","disk":[{"dev":"/dev/vdc1","serial":"data.qcow2"}]},
+            {"name":"vdb1","mountpoint":"/var","type":"ext4","disk":[{"dev":"/dev/vdb1","serial":"other"}]},
+            {"name":"\\\\?\\Volume{1}","mountpoint":"C:\\","type":"NTFS","disk":[{"serial":"system"}]},
+            {"name":"\\\\?\\Volume{2}","mountpoint":"E:\\","type":"NTFS","disk":[{"serial":"data.qcow2"}]}
+        ]}"#;
+
+        let filesystems = parse_guest_fsinfo(reply, "vdb", Some("data.qcow2"));
+        let mountpoints: Vec<&str> = filesystems
+            .iter()
+            .map(|fs| fs.mountpoint.as_str())
+            .collect();
+        assert_eq!(mountpoints, ["/srv", "E:\\"]);
+        assert_eq!(filesystems[0].device, "/dev/vdc1");
+
+        let domain = "<devices>\n<disk type='file' device='disk'>\n  <target dev='vda' bus='virtio'/>\n</disk>\n<disk type='volume' device='disk'>\n  <target dev='vdb' bus='virtio'/>\n  <serial>data.qcow2</serial>\n</disk>\n</devices>";
+        assert_eq!(disk_serial(domain, "vdb").as_deref(), Some("data.qcow2"));
+        assert_eq!(disk_serial(domain, "vda"), None);
+    }
+
+    #[test]
+    fn disk_removal_stops_at_the_first_kept_layer() {
+        let chain: Vec<PathBuf> = [
+            "/var/lib/nova/snapshots/web/s2.qcow2",
+            "/var/lib/nova/snapshots/web/s1.qcow2",
+            "/var/lib/libvirt/images/web.qcow2",
+            "/var/lib/nova/templates/ubuntu.qcow2",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let (removable, kept) = removable_chain(&chain, |layer| {
+            layer
+                .starts_with("/var/lib/nova/templates")
+                .then(|| "also backs api".to_string())
+        });
+        assert_eq!(removable, &chain[..3]);
+        assert_eq!(kept, Some((chain[3].clone(), "also backs api".to_string())));
+
+        // A kept overlay protects everything beneath it
+        let (removable, kept) = removable_chain(&chain, |layer| {
+            (layer == chain[1]).then(|| "not a storage pool volume".to_string())
+        });
+        assert_eq!(removable, &chain[..1]);
+        assert_eq!(kept.unwrap().0, chain[1]);
+
+        let (removable, kept) = removable_chain(&chain[..1], |_| None);
+        assert_eq!(removable, &chain[..1]);
+        assert!(kept.is_none());
+    }
+
+    #[test]
+    fn deletion_only_considers_real_disks() {
+        let xml = r#"<devices>
+    <disk type='file' device='disk'>
+      <source file='/var/lib/libvirt/images/web.qcow2'/>
+      <target dev='vda' bus='virtio'/>
+    </disk>
+    <disk type='volume' device='disk'>
+      <source pool="fast" volume="web-data"/>
+      <target dev='vdb' bus='virtio'/>
+    </disk>
+    <disk type='block' device='disk'>
+      <source dev='/dev/nvme1n1'/>
+      <target dev='vdc' bus='virtio'/>
+    </disk>
+    <disk type='file' device='cdrom'>
+      <source file='/var/lib/nova/iso/ubuntu.iso'/>
+      <target dev='sda' bus='sata'/>
+    </disk>
+  </devices>"#;
+        assert_eq!(
+            domain_disk_sources(xml),
+            vec![
+                DiskSource::Path(PathBuf::from("/var/lib/libvirt/images/web.qcow2")),
+                DiskSource::Volume {
+                    pool: "fast".to_string(),
+                    volume: "web-data".to_string()
+                },
+                DiskSource::Path(PathBuf::from("/dev/nvme1n1")),
+            ]
+        );
+        assert_eq!(
+            DiskSource::Volume {
+                pool: "fast".to_string(),
+                volume: "web-data".to_string()
+            }
+            .to_string(),
+            "fast/web-data"
+        );
+    }
+
+    #[test]
+    fn block_device_passthrough_checks() {
+        let mounts = "/dev/sdb1 /mnt/data ext4 rw 0 0\n/dev/sdc /srv xfs rw 0 0\n/dev/sdb10 /backup ext4 rw 0 0\n";
+        let swaps = "Filename Type Size Used Priority\n/dev/nvme1n1p2 partition 8388604 0 -2\n";
+
+        let users = block_device_users(Path::new("/dev/sdb"), mounts, swaps);
+        assert_eq!(
+            users,
+            vec![
+                "/dev/sdb1 mounted on /mnt/data".to_string(),
+                "/dev/sdb10 mounted on /backup".to_string()
+            ]
+        );
+        // "b1" is not a partition suffix, so /dev/sdb1 does not belong to /dev/sd
+        assert!(block_device_users(Path::new("/dev/sd"), mounts, "").is_empty());
+        assert_eq!(
+            block_device_users(Path::new("/dev/nvme1n1"), "", swaps),
+            vec!["/dev/nvme1n1p2 used as swap".to_string()]
+        );
+        assert!(block_device_users(Path::new("/dev/sdd"), mounts, swaps).is_empty());
+
+        let sysfs = tempfile::TempDir::new().unwrap();
+        for (entry, partition) in [("sdb1", true), ("sdb2", true), ("holders", false)] {
+            std::fs::create_dir(sysfs.path().join(entry)).unwrap();
+            if partition {
+                std::fs::write(sysfs.path().join(entry).join("partition"), "1\n").unwrap();
+            }
+        }
+        assert_eq!(block_device_partitions(sysfs.path()), ["sdb1", "sdb2"]);
+        assert!(block_device_partitions(&sysfs.path().join("missing")).is_empty());
+
+        let xml = block_disk_xml(Path::new("/dev/sdd"), "vdb", &DiskAccess::default());
+        assert!(xml.starts_with("<disk type='block' device='disk'>"));
+        assert!(xml.contains("<driver name='qemu' type='raw' cache='none' io='native'/>"));
+        assert!(xml.contains("<source dev='/dev/sdd'/>"));
+        assert!(xml.contains("<target dev='vdb' bus='virtio'/>"));
+        assert!(!xml.contains("<shareable/>") && !xml.contains("<readonly/>"));
+
+        let shared = DiskAccess {
+            readonly: true,
+            shareable: true,
+            cache: DiskCache::None,
+        };
+        assert_eq!(shared.validate().unwrap(), None);
+        let xml = block_disk_xml(Path::new("/dev/sdd"), "vdc", &shared);
+        assert!(
+            xml.contains(
+                "<target dev='vdc' bus='virtio'/>\n  <readonly/>\n  <shareable/>\n</disk>"
+            )
+        );
+
+        // Writable sharing is allowed with a warning; sharing through the page cache is not
+        let writable = DiskAccess {
+            readonly: false,
+            ..shared
+        };
+        assert!(writable.validate().unwrap().is_some());
+        let cached = DiskAccess {
+            cache: DiskCache::Writeback,
+            ..shared
+        };
+        assert!(cached.validate().is_err());
+        let xml = block_disk_xml(
+            Path::new("/dev/sdd"),
+            "vdd",
+            &DiskAccess {
+                cache: DiskCache::Writeback,
+                ..DiskAccess::default()
+            },
+        );
+        assert!(xml.contains("cache='writeback' io='threads'"));
+
+        // Pool volumes take the same flags, but only raw images can be shared
+        assert_eq!(shared.validate_format("raw").unwrap(), None);
+        assert!(shared.validate_format("qcow2").is_err());
+        assert!(DiskAccess::default().validate_format("qcow2").is_ok());
+        let xml = volume_disk_xml(
+            "fast",
+            "dataset.img",
+            "raw",
+            "vdb",
+            DiskBus::Virtio,
+            &shared,
+        );
+        assert!(xml.contains("<driver name='qemu' type='raw' cache='none'/>"));
+        assert!(
+            xml.contains("<serial>dataset.img</serial>\n  <readonly/>\n  <shareable/>\n</disk>")
+        );
+
+        assert_eq!(
+            next_virtio_target(&["vda".to_string(), "sda".to_string()]).as_deref(),
+            Some("vdb")
+        );
+    }
 }
@@ -0,0 +1,177 @@
+//! One-off boot overrides for `nova run vm --boot-iso/--boot-order`.
+//!
+//! Nova builds the QEMU command line from the NovaFile on every start, so an
+//! override only applies to the start it is passed to and nothing is written
+//! back. A rescue ISO is attached as an IDE CD-ROM with `bootindex=0` when it
+//! boots first, which both SeaBIOS and OVMF honour; `-boot order=` is also
+//! emitted for an explicit order, though OVMF ignores it for devices that
+//! have no boot index.
+
+use crate::{NovaError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// QEMU id of the drive backing `--boot-iso`.
+const BOOT_ISO_DRIVE_ID: &str = "nova-boot-iso";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BootDevice {
+    Hd,
+    Cdrom,
+    Network,
+}
+
+impl BootDevice {
+    /// Letter used by QEMU's `-boot order=`.
+    pub fn qemu_letter(&self) -> char {
+        match self {
+            BootDevice::Hd => 'c',
+            BootDevice::Cdrom => 'd',
+            BootDevice::Network => 'n',
+        }
+    }
+}
+
+impl fmt::Display for BootDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BootDevice::Hd => "hd",
+            BootDevice::Cdrom => "cdrom",
+            BootDevice::Network => "network",
+        })
+    }
+}
+
+/// Boot settings for a single start.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootOverride {
+    pub iso: Option<PathBuf>,
+    /// Explicit order; with only an ISO the CD-ROM boots first
+    pub order: Option<Vec<BootDevice>>,
+}
+
+impl BootOverride {
+    pub fn is_empty(&self) -> bool {
+        self.iso.is_none() && self.order.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(iso) = &self.iso
+            && !iso.is_file()
+        {
+            return Err(NovaError::ConfigError(format!(
+                "Boot ISO {} does not exist or is not a file",
+                iso.display()
+            )));
+        }
+        if let Some(order) = &self.order {
+            if order.is_empty() {
+                return Err(NovaError::ConfigError(
+                    "--boot-order needs at least one device".to_string(),
+                ));
+            }
+            if let Some((index, device)) = order
+                .iter()
+                .enumerate()
+                .find(|(index, device)| order[..*index].contains(device))
+            {
+                return Err(NovaError::ConfigError(format!(
+                    "Boot device '{}' is listed twice (position {})",
+                    device,
+                    index + 1
+                )));
+            }
+            if order.contains(&BootDevice::Cdrom) && self.iso.is_none() {
+                return Err(NovaError::ConfigError(
+                    "--boot-order includes cdrom but no ISO is attached; pass --boot-iso"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The effective order: the explicit one, or CD-ROM then disk when only
+    /// an ISO is given.
+    pub fn effective_order(&self) -> Vec<BootDevice> {
+        match (&self.order, &self.iso) {
+            (Some(order), _) => order.clone(),
+            (None, Some(_)) => vec![BootDevice::Cdrom, BootDevice::Hd],
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Arguments appended to the QEMU command line.
+    pub fn qemu_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let order = self.effective_order();
+        if let Some(iso) = &self.iso {
+            args.push("-drive".to_string());
+            args.push(format!(
+                "file={},media=cdrom,readonly=on,if=none,id={}",
+                // QEMU option values escape commas by doubling them
+                iso.display().to_string().replace(',', ",,"),
+                BOOT_ISO_DRIVE_ID
+            ));
+            args.push("-device".to_string());
+            let mut device = format!("ide-cd,drive={}", BOOT_ISO_DRIVE_ID);
+            if order.first() == Some(&BootDevice::Cdrom) {
+                device.push_str(",bootindex=0");
+            }
+            args.push(device);
+        }
+        if self.order.is_some() {
+            args.push("-boot".to_string());
+            args.push(format!(
+                "order={}",
+                order
+                    .iter()
+                    .map(BootDevice::qemu_letter)
+                    .collect::<String>()
+            ));
+        }
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boot_overrides_become_qemu_arguments() {
+        let iso = tempfile::NamedTempFile::new().unwrap();
+        let rescue = BootOverride {
+            iso: Some(iso.path().to_path_buf()),
+            order: None,
+        };
+        assert!(rescue.validate().is_ok());
+        let args = rescue.qemu_args();
+        assert_eq!(args.len(), 4);
+        assert!(args[1].starts_with("file=") && args[1].contains("media=cdrom,readonly=on"));
+        assert_eq!(args[3], "ide-cd,drive=nova-boot-iso,bootindex=0");
+
+        // Disk first keeps the ISO attached without making it boot
+        let disk_first = BootOverride {
+            order: Some(vec![BootDevice::Hd, BootDevice::Cdrom]),
+            ..rescue.clone()
+        };
+        let args = disk_first.qemu_args();
+        assert_eq!(args[3], "ide-cd,drive=nova-boot-iso");
+        assert_eq!(&args[4..], ["-boot", "order=cd"]);
+
+        let no_iso = BootOverride {
+            iso: None,
+            order: Some(vec![BootDevice::Cdrom]),
+        };
+        assert!(no_iso.validate().is_err());
+        let twice = BootOverride {
+            iso: None,
+            order: Some(vec![BootDevice::Hd, BootDevice::Hd]),
+        };
+        assert!(twice.validate().unwrap_err().to_string().contains("twice"));
+        assert!(BootOverride::default().qemu_args().is_empty());
+    }
+}
@@ -0,0 +1,496 @@
+//! Guest CPU topology (sockets, cores and threads).
+//!
+//! Libvirt presents every vCPU as its own socket unless the domain's `<cpu>`
+//! element carries a `<topology>`, which trips up guests that license or
+//! schedule per socket (Windows client editions use at most two). Nova VMs
+//! default to one socket with one core per vCPU; `cpu_topology` under
+//! `[vm.<name>]` or `nova vm topology` overrides that. The topology is fixed
+//! while the domain runs, so changing it requires the VM to be shut off.
+//!
+//! vCPUs can also be pinned to host CPUs with `cpuset = ["0=2", "1=3"]` or
+//! `nova vm pin`, which becomes `<cputune>` `<vcpupin>` entries. Unlike the
+//! topology, pinning can change while the VM runs.
+
+use crate::{NovaError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// `cpu_topology = { sockets = 1, cores = 4, threads = 2 }`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CpuTopology {
+    pub sockets: u32,
+    pub cores: u32,
+    pub threads: u32,
+}
+
+impl CpuTopology {
+    /// Single socket with one core per vCPU.
+    pub fn default_for(vcpus: u32) -> Self {
+        Self {
+            sockets: 1,
+            cores: vcpus.max(1),
+            threads: 1,
+        }
+    }
+
+    pub fn vcpus(&self) -> u32 {
+        self.sockets
+            .saturating_mul(self.cores)
+            .saturating_mul(self.threads)
+    }
+
+    /// The topology must account for exactly `vcpus` vCPUs.
+    pub fn validate(&self, vcpus: u32) -> Result<()> {
+        if self.sockets == 0 || self.cores == 0 || self.threads == 0 {
+            return Err(NovaError::ConfigError(format!(
+                "CPU topology {} must have at least one socket, core and thread",
+                self
+            )));
+        }
+        if self.vcpus() != vcpus {
+            return Err(NovaError::ConfigError(format!(
+                "CPU topology {} gives {} vCPUs but the VM has {}; sockets * cores * threads \
+                 must equal the vCPU count",
+                self,
+                self.vcpus(),
+                vcpus
+            )));
+        }
+        Ok(())
+    }
+
+    /// `<topology>` element placed inside `<cpu>`.
+    pub fn topology_xml(&self) -> String {
+        format!(
+            "<topology sockets='{}' dies='1' cores='{}' threads='{}'/>",
+            self.sockets, self.cores, self.threads
+        )
+    }
+
+    /// QEMU `-smp` value when Nova launches QEMU directly.
+    pub fn smp_arg(&self) -> String {
+        format!(
+            "{},sockets={},cores={},threads={}",
+            self.vcpus(),
+            self.sockets,
+            self.cores,
+            self.threads
+        )
+    }
+}
+
+impl fmt::Display for CpuTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} socket(s) x {} core(s) x {} thread(s)",
+            self.sockets, self.cores, self.threads
+        )
+    }
+}
+
+/// The configured topology, or the single-socket default, checked against
+/// the VM's vCPU count.
+pub fn effective_topology(vcpus: u32, configured: Option<CpuTopology>) -> Result<CpuTopology> {
+    let topology = configured.unwrap_or_else(|| CpuTopology::default_for(vcpus));
+    topology.validate(vcpus)?;
+    Ok(topology)
+}
+
+/// vCPU count from a domain's `<vcpu>` element.
+pub fn parse_vcpus(xml: &str) -> Option<u32> {
+    let vcpu = Regex::new(r"<vcpu\b[^>]*>\s*(\d+)\s*</vcpu>").expect("valid vcpu pattern");
+    vcpu.captures(xml)?.get(1)?.as_str().parse().ok()
+}
+
+/// Online vCPUs from `<vcpu current='N'>`, or every vCPU without it.
+pub fn parse_current_vcpus(xml: &str) -> Option<u32> {
+    let vcpu = Regex::new(r"<vcpu\b([^>]*)>\s*(\d+)\s*</vcpu>").expect("valid vcpu pattern");
+    let caps = vcpu.captures(xml)?;
+    let current = Regex::new(r#"\bcurrent=['"](\d+)['"]"#).expect("valid current pattern");
+    current
+        .captures(&caps[1])
+        .map(|current| current[1].to_string())
+        .unwrap_or_else(|| caps[2].to_string())
+        .parse()
+        .ok()
+}
+
+/// A vCPU hotplug target must be between one and the domain's maximum,
+/// which is fixed when it is defined.
+pub fn check_vcpu_target(vm: &str, count: u32, maximum: u32) -> Result<()> {
+    if count == 0 {
+        return Err(NovaError::ConfigError(
+            "A VM needs at least one vCPU".to_string(),
+        ));
+    }
+    if count > maximum {
+        return Err(NovaError::ConfigError(format!(
+            "VM '{}' is defined with at most {} vCPUs. Shut it down and raise the limit with \
+             `virsh setvcpus {} <max> --maximum --config` (and `nova vm topology` to match)",
+            vm, maximum, vm
+        )));
+    }
+    Ok(())
+}
+
+/// Topology declared in a domain definition, if any.
+pub fn parse_topology(xml: &str) -> Option<CpuTopology> {
+    let element = Regex::new(r"<topology\b[^>]*/>").expect("valid topology pattern");
+    let tag = element.find(xml)?.as_str();
+    let attribute = |name: &str| -> Option<u32> {
+        Regex::new(&format!(r#"\b{}=['"](\d+)['"]"#, name))
+            .expect("valid attribute pattern")
+            .captures(tag)?
+            .get(1)?
+            .as_str()
+            .parse()
+            .ok()
+    };
+    Some(CpuTopology {
+        sockets: attribute("sockets")?,
+        cores: attribute("cores")?,
+        threads: attribute("threads")?,
+    })
+}
+
+/// Set the `<topology>` of a domain definition, replacing an existing one
+/// and creating the `<cpu>` element when the domain has none.
+pub fn apply_topology(xml: &str, topology: &CpuTopology) -> Result<String> {
+    let element = topology.topology_xml();
+
+    let existing = Regex::new(r"<topology\b[^>]*/>").expect("valid topology pattern");
+    if existing.is_match(xml) {
+        return Ok(existing.replace(xml, element.as_str()).into_owned());
+    }
+
+    let self_closing = Regex::new(r"(?m)^([ \t]*)<cpu\b([^>]*?)\s*/>").expect("valid cpu pattern");
+    if let Some(caps) = self_closing.captures(xml) {
+        let indent = caps.get(1).map_or("", |m| m.as_str());
+        let expanded = format!(
+            "{indent}<cpu{}>\n{indent}  {}\n{indent}</cpu>",
+            &caps[2], element
+        );
+        return Ok(self_closing.replace(xml, expanded.as_str()).into_owned());
+    }
+
+    let open = Regex::new(r"(?m)^([ \t]*)<cpu\b[^>]*>").expect("valid cpu pattern");
+    if let Some(caps) = open.captures(xml) {
+        let tag = caps.get(0).expect("whole match");
+        let indent = caps.get(1).map_or("", |m| m.as_str());
+        let mut xml = xml.to_string();
+        xml.insert_str(tag.end(), &format!("\n{indent}  {}", element));
+        return Ok(xml);
+    }
+
+    let Some(end) = xml.find("</vcpu>") else {
+        return Err(NovaError::ConfigError(
+            "Domain XML has no <vcpu> element".to_string(),
+        ));
+    };
+    let mut xml = xml.to_string();
+    xml.insert_str(
+        end + "</vcpu>".len(),
+        &format!(
+            "\n  <cpu mode='host-passthrough' check='none'>\n    {}\n  </cpu>",
+            element
+        ),
+    );
+    Ok(xml)
+}
+
+/// Logged when a VM with `cpuset` is started as a bare QEMU process, which
+/// has no per-vCPU thread placement.
+pub const QEMU_PINNING_WARNING: &str = "cpuset is applied to libvirt domains only and is \
+     ignored for a directly launched QEMU; define the VM in libvirt or pin with `nova vm pin`";
+
+/// One vCPU pinned to a set of host CPUs, written `vcpu=cpuset` (`0=2`,
+/// `1=4-5`, `2=6,8`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct VcpuPin {
+    pub vcpu: u32,
+    /// Host CPU list in libvirt's cpuset syntax
+    pub cpuset: String,
+}
+
+impl FromStr for VcpuPin {
+    type Err = NovaError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (vcpu, cpuset) = value.split_once('=').ok_or_else(|| {
+            NovaError::ConfigError(format!(
+                "CPU pin '{}' must look like <vcpu>=<host cpus>, e.g. 0=2 or 1=4-5",
+                value
+            ))
+        })?;
+        let vcpu = vcpu.trim().parse().map_err(|_| {
+            NovaError::ConfigError(format!("CPU pin '{}' has an invalid vCPU number", value))
+        })?;
+        let cpuset = cpuset.trim().to_string();
+        parse_cpuset(&cpuset)?;
+        Ok(Self { vcpu, cpuset })
+    }
+}
+
+impl TryFrom<String> for VcpuPin {
+    type Error = NovaError;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<VcpuPin> for String {
+    fn from(pin: VcpuPin) -> Self {
+        pin.to_string()
+    }
+}
+
+impl fmt::Display for VcpuPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.vcpu, self.cpuset)
+    }
+}
+
+/// CPUs in a cpuset such as `2,4-7`, in the order given.
+pub fn parse_cpuset(cpuset: &str) -> Result<Vec<u32>> {
+    let invalid = || {
+        NovaError::ConfigError(format!(
+            "Invalid CPU list '{}'; use numbers and ranges like 2,4-7",
+            cpuset
+        ))
+    };
+    let mut cpus = Vec::new();
+    for part in cpuset.split(',').map(str::trim) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+                let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(part.parse().map_err(|_| invalid())?),
+        }
+    }
+    Ok(cpus)
+}
+
+/// Compact list of `cpus` in the `2,4-7` form [`parse_cpuset`] reads.
+pub fn format_cpuset(cpus: &[u32]) -> String {
+    let mut cpus = cpus.to_vec();
+    cpus.sort_unstable();
+    cpus.dedup();
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Online host CPUs, from `/sys/devices/system/cpu/online`. The set can have
+/// gaps when CPUs are taken offline.
+pub fn host_online_cpus() -> Vec<u32> {
+    std::fs::read_to_string("/sys/devices/system/cpu/online")
+        .ok()
+        .and_then(|online| parse_cpuset(online.trim()).ok())
+        .filter(|cpus| !cpus.is_empty())
+        .unwrap_or_else(|| {
+            (0..std::thread::available_parallelism().map_or(1, |count| count.get() as u32))
+                .collect()
+        })
+}
+
+/// Pins must name existing vCPUs, each at most once, and host CPUs in
+/// `online_cpus` when it is given.
+pub fn validate_pins(pins: &[VcpuPin], vcpus: u32, online_cpus: Option<&[u32]>) -> Result<()> {
+    for (index, pin) in pins.iter().enumerate() {
+        if pin.vcpu >= vcpus {
+            return Err(NovaError::ConfigError(format!(
+                "Cannot pin vCPU {}: the VM has {} vCPUs (0-{})",
+                pin.vcpu,
+                vcpus,
+                vcpus.saturating_sub(1)
+            )));
+        }
+        if pins[..index].iter().any(|other| other.vcpu == pin.vcpu) {
+            return Err(NovaError::ConfigError(format!(
+                "vCPU {} is pinned twice",
+                pin.vcpu
+            )));
+        }
+        let cpus = parse_cpuset(&pin.cpuset)?;
+        if let Some(online_cpus) = online_cpus
+            && let Some(cpu) = cpus.into_iter().find(|cpu| !online_cpus.contains(cpu))
+        {
+            return Err(NovaError::ConfigError(format!(
+                "Host CPU {} in '{}' is not online; online CPUs are {}",
+                cpu,
+                pin,
+                format_cpuset(online_cpus)
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `<cputune>` block for a domain definition; empty without pins.
+pub fn cputune_xml(pins: &[VcpuPin]) -> String {
+    if pins.is_empty() {
+        return String::new();
+    }
+    let mut xml = String::from("  <cputune>\n");
+    for pin in pins {
+        xml.push_str(&format!(
+            "    <vcpupin vcpu='{}' cpuset='{}'/>\n",
+            pin.vcpu, pin.cpuset
+        ));
+    }
+    xml.push_str("  </cputune>\n");
+    xml
+}
+
+/// `<vcpupin>` entries of a domain definition.
+pub fn parse_vcpu_pins(xml: &str) -> Vec<VcpuPin> {
+    let vcpupin = Regex::new(r#"<vcpupin\s+vcpu=['"](\d+)['"]\s+cpuset=['"]([^'"]+)['"]\s*/>"#)
+        .expect("valid vcpupin pattern");
+    vcpupin
+        .captures_iter(xml)
+        .filter_map(|caps| {
+            Some(VcpuPin {
+                vcpu: caps[1].parse().ok()?,
+                cpuset: caps[2].to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vcpu_pins_parse_validate_and_render() {
+        let pins: Vec<VcpuPin> = ["0=2", "1=4-5", "2=6,8"]
+            .iter()
+            .map(|pin| pin.parse().unwrap())
+            .collect();
+        assert_eq!(pins[1].to_string(), "1=4-5");
+        assert_eq!(parse_cpuset("6,8-10").unwrap(), [6, 8, 9, 10]);
+        assert!("0".parse::<VcpuPin>().is_err());
+        assert!("0=5-3".parse::<VcpuPin>().is_err());
+        assert!("x=1".parse::<VcpuPin>().is_err());
+
+        let host: Vec<u32> = (0..16).collect();
+        assert!(validate_pins(&pins, 4, Some(&host)).is_ok());
+        let err = validate_pins(&pins, 2, Some(&host))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("vCPU 2"), "{}", err);
+        let err = validate_pins(&pins, 4, Some(&host[..8]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Host CPU 8"), "{}", err);
+        let twice = vec![pins[0].clone(), pins[0].clone()];
+        assert!(validate_pins(&twice, 4, None).is_err());
+
+        // CPU 5 taken offline: the highest online CPU is still 15
+        let gap = parse_cpuset("0-4,6-15").unwrap();
+        let err = validate_pins(&pins, 4, Some(&gap)).unwrap_err().to_string();
+        assert!(
+            err.contains("Host CPU 5 in '1=4-5' is not online"),
+            "{}",
+            err
+        );
+        assert!(err.contains("online CPUs are 0-4,6-15"), "{}", err);
+        assert_eq!(format_cpuset(&[7, 0, 1, 2, 9, 10]), "0-2,7,9-10");
+        assert_eq!(format_cpuset(&[]), "");
+
+        let xml = cputune_xml(&pins);
+        assert!(xml.contains("<vcpupin vcpu='1' cpuset='4-5'/>"));
+        assert_eq!(parse_vcpu_pins(&xml), pins);
+        assert_eq!(cputune_xml(&[]), "");
+
+        // NovaFile form
+        #[derive(Deserialize)]
+        struct Vm {
+            cpuset: Vec<VcpuPin>,
+        }
+        let vm: Vm = toml::from_str(r#"cpuset = ["0=2", "1=3"]"#).unwrap();
+        assert_eq!(vm.cpuset[1].cpuset, "3");
+        assert!(toml::from_str::<Vm>(r#"cpuset = ["0"]"#).is_err());
+    }
+
+    #[test]
+    fn topology_is_validated_and_applied() {
+        let topology = CpuTopology {
+            sockets: 1,
+            cores: 4,
+            threads: 2,
+        };
+        assert!(topology.validate(8).is_ok());
+        let err = topology.validate(6).unwrap_err().to_string();
+        assert!(err.contains("gives 8 vCPUs"), "{}", err);
+        assert!(effective_topology(4, None).unwrap() == CpuTopology::default_for(4));
+        assert!(effective_topology(4, Some(topology)).is_err());
+        assert_eq!(topology.smp_arg(), "8,sockets=1,cores=4,threads=2");
+
+        let domain = "<domain type='kvm'>\n  <vcpu placement='static'>8</vcpu>\n  <cpu mode='host-passthrough' check='none'/>\n  <devices/>\n</domain>\n";
+        assert_eq!(parse_vcpus(domain), Some(8));
+        assert_eq!(parse_topology(domain), None);
+
+        let xml = apply_topology(domain, &topology).unwrap();
+        assert!(xml.contains(
+            "  <cpu mode='host-passthrough' check='none'>\n    <topology sockets='1' dies='1' cores='4' threads='2'/>\n  </cpu>\n"
+        ));
+        assert_eq!(parse_topology(&xml), Some(topology));
+
+        // Re-applying replaces the element instead of adding another
+        let xml = apply_topology(&xml, &CpuTopology::default_for(8)).unwrap();
+        assert_eq!(xml.matches("<topology").count(), 1);
+        assert_eq!(parse_topology(&xml), Some(CpuTopology::default_for(8)));
+
+        // A <cpu> with children keeps them
+        let numa = "  <vcpu>4</vcpu>\n  <cpu mode='host-passthrough'>\n    <numa>\n      <cell id='0' cpus='0-3' memory='1' unit='KiB'/>\n    </numa>\n  </cpu>\n";
+        let xml = apply_topology(numa, &CpuTopology::default_for(4)).unwrap();
+        assert!(xml.contains("<cpu mode='host-passthrough'>\n    <topology"));
+        assert!(xml.contains("<numa>"));
+
+        let bare = apply_topology(
+            "<domain>\n  <vcpu>2</vcpu>\n</domain>",
+            &CpuTopology::default_for(2),
+        )
+        .unwrap();
+        assert_eq!(parse_topology(&bare), Some(CpuTopology::default_for(2)));
+    }
+
+    #[test]
+    fn hotplug_counts_respect_the_maximum() {
+        let domain = "<domain>\n  <vcpu placement='static' current='4'>16</vcpu>\n</domain>";
+        assert_eq!(parse_vcpus(domain), Some(16));
+        assert_eq!(parse_current_vcpus(domain), Some(4));
+        assert_eq!(parse_current_vcpus("<vcpu>2</vcpu>"), Some(2));
+
+        assert!(check_vcpu_target("build", 16, 16).is_ok());
+        assert!(check_vcpu_target("build", 0, 16).is_err());
+        let err = check_vcpu_target("build", 24, 16).unwrap_err().to_string();
+        assert!(err.contains("at most 16"), "{}", err);
+    }
+}
@@ -0,0 +1,392 @@
+//! Device models for new VMs and for switching an existing VM between
+//! emulated and paravirtualized (virtio) devices.
+//!
+//! The `[device_defaults]` section of the NovaFile picks the disk bus, NIC
+//! model, and video adapter used by the GUI's `virt-install` path and the
+//! libvirt domain builder. Virtio is the default everywhere; guests without
+//! virtio drivers (e.g. a stock Windows installer) need `sata`/`e1000e`/`qxl`
+//! or the virtio-win driver ISO attached during install.
+
+use crate::{NovaError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskBus {
+    #[default]
+    Virtio,
+    Sata,
+    Scsi,
+    Ide,
+}
+
+impl DiskBus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiskBus::Virtio => "virtio",
+            DiskBus::Sata => "sata",
+            DiskBus::Scsi => "scsi",
+            DiskBus::Ide => "ide",
+        }
+    }
+
+    /// Guest device name prefix libvirt expects for this bus.
+    pub fn target_prefix(&self) -> &'static str {
+        match self {
+            DiskBus::Virtio => "vd",
+            DiskBus::Sata | DiskBus::Scsi => "sd",
+            DiskBus::Ide => "hd",
+        }
+    }
+
+    /// First target on this bus not in `used`, e.g. `sdb` when `sda` is taken.
+    pub fn next_target(&self, used: &[String]) -> Option<String> {
+        ('a'..='z')
+            .map(|letter| format!("{}{}", self.target_prefix(), letter))
+            .find(|target| !used.contains(target))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NetModel {
+    #[default]
+    Virtio,
+    E1000e,
+    Rtl8139,
+}
+
+impl NetModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetModel::Virtio => "virtio",
+            NetModel::E1000e => "e1000e",
+            NetModel::Rtl8139 => "rtl8139",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoModel {
+    /// virtio-gpu
+    #[default]
+    Virtio,
+    Qxl,
+    Vga,
+    None,
+}
+
+impl VideoModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoModel::Virtio => "virtio",
+            VideoModel::Qxl => "qxl",
+            VideoModel::Vga => "vga",
+            VideoModel::None => "none",
+        }
+    }
+}
+
+impl fmt::Display for DiskBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for NetModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for VideoModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceDefaults {
+    #[serde(default)]
+    pub disk_bus: DiskBus,
+    #[serde(default)]
+    pub net_model: NetModel,
+    #[serde(default)]
+    pub video: VideoModel,
+    /// Add a virtio-rng device fed from the host's /dev/urandom
+    #[serde(default = "default_true")]
+    pub rng: bool,
+    /// Add a virtio memory balloon
+    #[serde(default = "default_true")]
+    pub balloon: bool,
+}
+
+impl Default for DeviceDefaults {
+    fn default() -> Self {
+        Self {
+            disk_bus: DiskBus::default(),
+            net_model: NetModel::default(),
+            video: VideoModel::default(),
+            rng: true,
+            balloon: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl DeviceDefaults {
+    /// `virt-install` options for the RNG, balloon, and video devices. Disk bus
+    /// and NIC model are suffixes on `--disk` / `--network` (see
+    /// [`Self::disk_option`] and [`Self::network_option`]).
+    pub fn virt_install_args(&self) -> Vec<String> {
+        let mut args = vec!["--video".to_string(), self.video.as_str().to_string()];
+        if self.rng {
+            args.extend(["--rng".to_string(), "/dev/urandom".to_string()]);
+        }
+        args.extend([
+            "--memballoon".to_string(),
+            if self.balloon { "virtio" } else { "none" }.to_string(),
+        ]);
+        args
+    }
+
+    pub fn disk_option(&self, disk: &str) -> String {
+        format!("{},bus={}", disk, self.disk_bus)
+    }
+
+    pub fn network_option(&self, network: &str) -> String {
+        format!("{},model={}", network, self.net_model)
+    }
+
+    /// `<video>`, `<rng>` and `<memballoon>` elements for a domain definition.
+    pub fn domain_devices_xml(&self) -> String {
+        let mut xml = String::new();
+        match self.video {
+            VideoModel::None => {
+                xml.push_str("    <video>\n      <model type='none'/>\n    </video>\n")
+            }
+            video => xml.push_str(&format!(
+                "    <video>\n      <model type='{}' heads='1' primary='yes'/>\n    </video>\n",
+                video
+            )),
+        }
+        if self.rng {
+            xml.push_str(
+                "    <rng model='virtio'>\n      <backend model='random'>/dev/urandom</backend>\n    </rng>\n",
+            );
+        }
+        xml.push_str(&format!(
+            "    <memballoon model='{}'/>\n",
+            if self.balloon { "virtio" } else { "none" }
+        ));
+        xml
+    }
+}
+
+/// Device changes requested by `nova vm devices`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceChanges {
+    pub disk_bus: Option<DiskBus>,
+    pub net_model: Option<NetModel>,
+    pub video: Option<VideoModel>,
+}
+
+impl DeviceChanges {
+    pub fn is_empty(&self) -> bool {
+        self.disk_bus.is_none() && self.net_model.is_none() && self.video.is_none()
+    }
+}
+
+/// Rewrite a domain definition (`virsh dumpxml --inactive`) with `changes`.
+///
+/// Disks are given the free targets of the new bus in order (`vda` ->
+/// `sda`, or `sdb` when a CD-ROM already sits at `sda`) and their addresses
+/// dropped so libvirt assigns ones valid for the new controller. CD-ROMs and
+/// other non-disk devices are left alone.
+pub fn apply_device_changes(xml: &str, changes: &DeviceChanges) -> Result<String> {
+    let mut xml = xml.to_string();
+
+    if let Some(bus) = changes.disk_bus {
+        let any_disk = Regex::new(r"(?s)<disk\b[^>]*>.*?</disk>").expect("valid disk pattern");
+        let disk_block = Regex::new(r"(?s)<disk\b[^>]*device='disk'[^>]*>.*?</disk>")
+            .expect("valid disk pattern");
+        let target = Regex::new(r"<target dev='([a-z]+)'\s+bus='[a-z]+'\s*/>")
+            .expect("valid target pattern");
+        let address = Regex::new(r"\s*<address\b[^>]*/>").expect("valid address pattern");
+
+        // Targets kept by the devices that stay where they are
+        let mut used: Vec<String> = any_disk
+            .find_iter(&xml)
+            .map(|block| block.as_str())
+            .filter(|block| !disk_block.is_match(block))
+            .filter_map(|block| target.captures(block).map(|caps| caps[1].to_string()))
+            .collect();
+
+        let mut found = false;
+        let mut exhausted = false;
+        xml = disk_block
+            .replace_all(&xml, |caps: &regex::Captures| {
+                found = true;
+                let Some(dev) = bus.next_target(&used) else {
+                    exhausted = true;
+                    return caps[0].to_string();
+                };
+                let block = target.replace(
+                    &caps[0],
+                    format!("<target dev='{}' bus='{}'/>", dev, bus).as_str(),
+                );
+                used.push(dev);
+                address.replace_all(&block, "").into_owned()
+            })
+            .into_owned();
+        if !found {
+            return Err(NovaError::ConfigError(
+                "Domain has no disks to change the bus of".to_string(),
+            ));
+        }
+        if exhausted {
+            return Err(NovaError::ConfigError(format!(
+                "Not enough free {} disk targets for every disk",
+                bus
+            )));
+        }
+    }
+
+    if let Some(model) = changes.net_model {
+        let interface_block =
+            Regex::new(r"(?s)<interface\b[^>]*>.*?</interface>").expect("valid interface pattern");
+        let model_tag = Regex::new(r"<model type='[^']*'\s*/>").expect("valid model pattern");
+        let source_tag = Regex::new(r"(<source\b[^>]*/>)").expect("valid source pattern");
+        xml = interface_block
+            .replace_all(&xml, |caps: &regex::Captures| {
+                let replacement = format!("<model type='{}'/>", model);
+                if model_tag.is_match(&caps[0]) {
+                    model_tag
+                        .replace(&caps[0], replacement.as_str())
+                        .into_owned()
+                } else {
+                    source_tag
+                        .replace(&caps[0], format!("$1\n      {}", replacement).as_str())
+                        .into_owned()
+                }
+            })
+            .into_owned();
+    }
+
+    if let Some(video) = changes.video {
+        let video_block = Regex::new(r"(?s)<video>.*?</video>").expect("valid video pattern");
+        let replacement = match video {
+            VideoModel::None => "<video>\n      <model type='none'/>\n    </video>".to_string(),
+            video => format!(
+                "<video>\n      <model type='{}' heads='1' primary='yes'/>\n    </video>",
+                video
+            ),
+        };
+        xml = if video_block.is_match(&xml) {
+            video_block.replace(&xml, replacement.as_str()).into_owned()
+        } else {
+            xml.replacen("</devices>", &format!("  {}\n  </devices>", replacement), 1)
+        };
+    }
+
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOMAIN: &str = "<domain type='kvm'>
+  <devices>
+    <disk type='file' device='disk'>
+      <driver name='qemu' type='qcow2'/>
+      <source file='/var/lib/nova/disks/win11.qcow2'/>
+      <target dev='vda' bus='virtio'/>
+      <address type='pci' domain='0x0000' bus='0x04' slot='0x00' function='0x0'/>
+    </disk>
+    <disk type='file' device='cdrom'>
+      <target dev='sdb' bus='sata'/>
+    </disk>
+    <interface type='network'>
+      <source network='default'/>
+      <model type='virtio'/>
+    </interface>
+    <video>
+      <model type='qxl' ram='65536' vram='65536' heads='1' primary='yes'/>
+    </video>
+  </devices>
+</domain>";
+
+    #[test]
+    fn defaults_are_virtio() {
+        let defaults = DeviceDefaults::default();
+        assert_eq!(defaults.disk_option("size=64"), "size=64,bus=virtio");
+        assert_eq!(
+            defaults.network_option("bridge=br0"),
+            "bridge=br0,model=virtio"
+        );
+        assert_eq!(
+            defaults.virt_install_args(),
+            vec![
+                "--video",
+                "virtio",
+                "--rng",
+                "/dev/urandom",
+                "--memballoon",
+                "virtio"
+            ]
+        );
+        let xml = defaults.domain_devices_xml();
+        assert!(xml.contains("<model type='virtio' heads='1' primary='yes'/>"));
+        assert!(xml.contains("<rng model='virtio'>"));
+    }
+
+    #[test]
+    fn existing_domain_switches_to_emulated_devices() {
+        let changes = DeviceChanges {
+            disk_bus: Some(DiskBus::Sata),
+            net_model: Some(NetModel::E1000e),
+            video: Some(VideoModel::Virtio),
+        };
+        let xml = apply_device_changes(DOMAIN, &changes).unwrap();
+
+        assert!(xml.contains("<target dev='sda' bus='sata'/>"));
+        assert!(!xml.contains("bus='0x04'"));
+        // The CD-ROM keeps its target
+        assert!(xml.contains("<target dev='sdb' bus='sata'/>"));
+        assert!(xml.contains("<model type='e1000e'/>"));
+        assert!(xml.contains("<model type='virtio' heads='1' primary='yes'/>"));
+        assert!(!xml.contains("qxl"));
+    }
+
+    #[test]
+    fn bus_change_skips_targets_taken_by_a_cdrom() {
+        let domain = "<devices>
+    <disk type='file' device='cdrom'>
+      <target dev='sda' bus='sata'/>
+    </disk>
+    <disk type='file' device='disk'>
+      <target dev='vda' bus='virtio'/>
+    </disk>
+    <disk type='file' device='disk'>
+      <target dev='vdc' bus='virtio'/>
+    </disk>
+  </devices>";
+        let changes = DeviceChanges {
+            disk_bus: Some(DiskBus::Sata),
+            ..DeviceChanges::default()
+        };
+        let xml = apply_device_changes(domain, &changes).unwrap();
+
+        assert_eq!(xml.matches("<target dev='sda' bus='sata'/>").count(), 1);
+        assert!(xml.contains("device='disk'>\n      <target dev='sdb' bus='sata'/>"));
+        assert!(xml.contains("<target dev='sdc' bus='sata'/>"));
+        assert!(!xml.contains("dev='vd"));
+    }
+}
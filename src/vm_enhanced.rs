@@ -8,6 +8,11 @@ use crate::{
     log_debug, log_error, log_info, log_warn,
     looking_glass::{LookingGlassConfig, LookingGlassManager},
     network::NetworkManager,
+    vm_cpu,
+    vm_devices::DeviceDefaults,
+    vm_hugepages, vm_memory, vm_netlimit,
+    vm_nics::{self, VmNic},
+    vm_qemu_args,
 };
 use std::collections::HashMap;
 use std::path::Path;
@@ -79,6 +84,10 @@ impl EnhancedVmManager {
         if !self.check_libvirt_domain_exists(name).await {
             self.create_libvirt_domain(name, &vm_config).await?;
         }
+        if let Some(size) = vm_config.hugepages {
+            vm_hugepages::host_pool(size)
+                .check(name, self.parse_memory_mb(&vm_config.memory)? * 1024)?;
+        }
 
         let output = Command::new("virsh")
             .args(["start", name])
@@ -96,7 +105,7 @@ impl EnhancedVmManager {
         instance.update_status(crate::instance::InstanceStatus::Running);
         instance.cpu_cores = vm_config.cpu;
         instance.memory_mb = self.parse_memory_mb(&vm_config.memory)?;
-        instance.network = vm_config.network.clone();
+        instance.network = vm_config.primary_network();
 
         {
             let mut instances = self.instances.lock().unwrap();
@@ -122,6 +131,9 @@ impl EnhancedVmManager {
         }
 
         let vm_config = config.cloned().unwrap_or_default();
+        if !vm_config.cpuset.is_empty() {
+            log_warn!("VM '{}': {}", name, vm_cpu::QEMU_PINNING_WARNING);
+        }
 
         // Create QEMU command
         let mut cmd = Command::new("qemu-system-x86_64");
@@ -135,12 +147,19 @@ impl EnhancedVmManager {
             .arg("host")
             .arg("-enable-kvm")
             .arg("-smp")
-            .arg(vm_config.cpu.to_string())
+            .arg(vm_cpu::effective_topology(vm_config.cpu, vm_config.cpu_topology)?.smp_arg())
             .arg("-daemonize")
             .arg("-monitor")
             .arg("none")
             .arg("-display")
             .arg("none");
+        if let Some(size) = vm_config.hugepages {
+            cmd.args(vm_hugepages::qemu_args(
+                name,
+                self.parse_memory_mb(&vm_config.memory)? * 1024,
+                size,
+            )?);
+        }
 
         let (disk_path, disk_format) = prepare_vm_disk(name, &vm_config).await?;
         cmd.arg("-drive").arg(format!(
@@ -153,32 +172,41 @@ impl EnhancedVmManager {
             .await?;
 
         // Enhanced network configuration
-        if let Some(network) = &vm_config.network {
+        if vm_config.net_limit.is_some() {
+            log_warn!("VM '{}': {}", name, vm_netlimit::QEMU_WARNING);
+        }
+        for (index, nic) in vm_config.network.iter().enumerate() {
             let use_bridge = {
                 let manager = self.network_manager.lock().unwrap();
-                manager.switch_exists(network)
+                manager.switch_exists(&nic.network)
             };
 
-            if use_bridge {
-                cmd.arg("-netdev")
-                    .arg(format!("bridge,id=net0,br={}", network))
-                    .arg("-device")
-                    .arg("virtio-net-pci,netdev=net0,mac=52:54:00:12:34:56");
-                log_info!("Using bridge network '{}' for VM '{}'", network, name);
+            let bridge = if use_bridge {
+                log_info!("Using bridge network '{}' for VM '{}'", nic.network, name);
+                nic.network.as_str()
             } else {
-                log_warn!("Network '{}' not found, using default bridge", network);
-                cmd.arg("-netdev")
-                    .arg("bridge,id=net0,br=virbr0")
-                    .arg("-device")
-                    .arg("virtio-net-pci,netdev=net0");
-            }
-        } else {
+                log_warn!("Network '{}' not found, using default bridge", nic.network);
+                "virbr0"
+            };
+            cmd.args(vm_nics::qemu_bridge_args(
+                index,
+                bridge,
+                nic,
+                DeviceDefaults::default().net_model,
+            ));
+        }
+        if vm_config.network.is_empty() {
             cmd.arg("-netdev")
                 .arg("user,id=net0")
                 .arg("-device")
                 .arg("virtio-net-pci,netdev=net0");
         }
 
+        if !vm_config.extra_qemu_args.is_empty() {
+            log_warn!("VM '{}': {}", name, vm_qemu_args::UNSUPPORTED_WARNING);
+            cmd.args(&vm_config.extra_qemu_args);
+        }
+
         log_debug!("QEMU command: {:?}", cmd);
 
         // Start the VM process
@@ -211,7 +239,7 @@ impl EnhancedVmManager {
         instance.update_status(crate::instance::InstanceStatus::Starting);
         instance.cpu_cores = vm_config.cpu;
         instance.memory_mb = self.parse_memory_mb(&vm_config.memory)?;
-        instance.network = vm_config.network.clone();
+        instance.network = vm_config.primary_network();
 
         {
             let mut instances = self.instances.lock().unwrap();
@@ -397,17 +425,34 @@ impl EnhancedVmManager {
         let mut xml = String::new();
 
         xml.push_str("<?xml version='1.0' encoding='UTF-8'?>\n");
-        xml.push_str("<domain type='kvm'>\n");
+        xml.push_str(&vm_qemu_args::domain_open_tag(&config.extra_qemu_args));
         xml.push_str(&format!("  <name>{}</name>\n", name));
+        if let Some(max_memory) = &config.max_memory {
+            xml.push_str(&vm_memory::max_memory_xml(
+                self.parse_memory_mb(max_memory)? * 1024,
+                config
+                    .memory_slots
+                    .unwrap_or(vm_memory::DEFAULT_MEMORY_SLOTS),
+            ));
+        }
         xml.push_str(&format!("  <memory unit='KiB'>{}</memory>\n", memory_kb));
         xml.push_str(&format!(
             "  <currentMemory unit='KiB'>{}</currentMemory>\n",
             memory_kb
         ));
+        if let Some(size) = config.hugepages {
+            xml.push_str(&vm_hugepages::memory_backing_xml(size));
+        }
         xml.push_str(&format!(
             "  <vcpu placement='static'>{}</vcpu>\n",
             config.cpu
         ));
+        vm_cpu::validate_pins(
+            &config.cpuset,
+            config.cpu,
+            Some(&vm_cpu::host_online_cpus()),
+        )?;
+        xml.push_str(&vm_cpu::cputune_xml(&config.cpuset));
 
         xml.push_str("  <os>\n");
         xml.push_str("    <type arch='x86_64' machine='pc-q35-4.2'>hvm</type>\n");
@@ -419,7 +464,16 @@ impl EnhancedVmManager {
         xml.push_str("    <apic/>\n");
         xml.push_str("  </features>\n");
 
-        xml.push_str("  <cpu mode='host-passthrough' check='none'/>\n");
+        let topology = vm_cpu::effective_topology(config.cpu, config.cpu_topology)?;
+        if config.max_memory.is_some() {
+            // DIMM hotplug needs a guest NUMA cell
+            xml.push_str(&vm_memory::numa_cpu_xml(&topology, memory_kb));
+        } else {
+            xml.push_str(&format!(
+                "  <cpu mode='host-passthrough' check='none'>\n    {}\n  </cpu>\n",
+                topology.topology_xml()
+            ));
+        }
 
         xml.push_str("  <clock offset='utc'>\n");
         xml.push_str("    <timer name='rtc' tickpolicy='catchup'/>\n");
@@ -433,43 +487,58 @@ impl EnhancedVmManager {
 
         xml.push_str("  <devices>\n");
         xml.push_str("    <emulator>/usr/bin/qemu-system-x86_64</emulator>\n");
+        let devices = DeviceDefaults::default();
         xml.push_str("    <disk type='file' device='disk'>\n");
         xml.push_str(&format!(
-            "      <driver name='qemu' type='{}'/\n",
+            "      <driver name='qemu' type='{}'/>\n",
             disk_format.as_str()
         ));
-        xml.push_str(&format!("      <source file='{}'/\n", disk_path.display()));
-        xml.push_str("      <target dev='vda' bus='virtio'/\n");
+        xml.push_str(&format!("      <source file='{}'/>\n", disk_path.display()));
+        xml.push_str(&format!(
+            "      <target dev='{}a' bus='{}'/>\n",
+            devices.disk_bus.target_prefix(),
+            devices.disk_bus
+        ));
         xml.push_str("    </disk>\n");
 
-        if let Some(network) = &config.network {
-            xml.push_str("    <interface type='network'>\n");
-            xml.push_str(&format!("      <source network='{}'/\n", network));
-            xml.push_str("      <model type='virtio'/\n");
-            xml.push_str("    </interface>\n");
+        if let Some(limit) = &config.net_limit {
+            vm_netlimit::validate(limit)?;
+        }
+        let default_nic = [VmNic::new("default")];
+        let nics = if config.network.is_empty() {
+            &default_nic[..]
         } else {
-            xml.push_str("    <interface type='network'>\n");
-            xml.push_str("      <source network='default'/\n");
-            xml.push_str("      <model type='virtio'/\n");
-            xml.push_str("    </interface>\n");
+            &config.network
+        };
+        for nic in nics {
+            xml.push_str(&vm_nics::interface_xml(
+                nic,
+                devices.net_model,
+                config.net_limit.as_ref(),
+            ));
         }
 
-        xml.push_str("    <graphics type='vnc' port='-1' autoport='yes'/\n");
-        xml.push_str("    <input type='tablet' bus='usb'/\n");
-        xml.push_str("    <input type='mouse' bus='ps2'/\n");
-        xml.push_str("    <input type='keyboard' bus='ps2'/\n");
+        xml.push_str("    <graphics type='vnc' port='-1' autoport='yes'/>\n");
+        xml.push_str(&devices.domain_devices_xml());
+        xml.push_str("    <input type='tablet' bus='usb'/>\n");
+        xml.push_str("    <input type='mouse' bus='ps2'/>\n");
+        xml.push_str("    <input type='keyboard' bus='ps2'/>\n");
 
         if config.gpu_passthrough {
             xml.push_str("    <hostdev mode='subsystem' type='pci' managed='yes'>\n");
             xml.push_str("      <source>\n");
             xml.push_str(
-                "        <address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/\n",
+                "        <address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/>\n",
             );
             xml.push_str("      </source>\n");
             xml.push_str("    </hostdev>\n");
         }
 
         xml.push_str("  </devices>\n");
+        if !config.extra_qemu_args.is_empty() {
+            log_warn!("VM '{}': {}", name, vm_qemu_args::UNSUPPORTED_WARNING);
+            xml.push_str(&vm_qemu_args::commandline_xml(&config.extra_qemu_args));
+        }
         xml.push_str("</domain>\n");
 
         Ok(xml)
@@ -530,7 +599,11 @@ impl EnhancedVmManager {
                 config.device_address
             );
 
-            manager.configure_passthrough(&config.device_address, name)?;
+            manager.configure_passthrough(
+                &config.device_address,
+                name,
+                config.allow_host_display,
+            )?;
 
             Some(config)
         } else {
@@ -0,0 +1,402 @@
+//! Hugepage-backed guest memory.
+//!
+//! With `hugepages = true` (2 MiB pages) or `hugepages = "1G"` under
+//! `[vm.<name>]`, the domain gets `<memoryBacking><hugepages/>` and QEMU
+//! maps guest RAM from the host's hugepage pool instead of 4 KiB pages,
+//! which cuts TLB misses for passthrough gaming and DPDK guests. The pool
+//! is not grown on demand: pages have to be reserved up front (2 MiB pages
+//! through `nr_hugepages`, 1 GiB pages usually on the kernel command line),
+//! and a domain whose pool is too small fails to start with an opaque QEMU
+//! error. Nova checks the pool before defining or switching a VM and says
+//! how many pages are missing.
+
+use crate::{NovaError, Result};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Page sizes x86_64 hosts offer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HugePageSize {
+    #[serde(rename = "2M")]
+    Size2M,
+    #[serde(rename = "1G")]
+    Size1G,
+}
+
+impl HugePageSize {
+    pub fn size_kib(&self) -> u64 {
+        match self {
+            HugePageSize::Size2M => 2048,
+            HugePageSize::Size1G => 1024 * 1024,
+        }
+    }
+
+    fn from_kib(size_kib: u64) -> Option<Self> {
+        [HugePageSize::Size2M, HugePageSize::Size1G]
+            .into_iter()
+            .find(|size| size.size_kib() == size_kib)
+    }
+
+    /// Pages needed to back `memory_kib`, rounded up.
+    pub fn pages_for(&self, memory_kib: u64) -> u64 {
+        memory_kib.div_ceil(self.size_kib())
+    }
+
+    /// sysfs directory of this size's pool.
+    pub fn sysfs_dir(&self) -> String {
+        format!("/sys/kernel/mm/hugepages/hugepages-{}kB", self.size_kib())
+    }
+
+    /// How to reserve `total` pages of this size.
+    pub fn reserve_hint(&self, total: u64) -> String {
+        match self {
+            HugePageSize::Size2M => format!(
+                "echo {} | sudo tee {}/nr_hugepages (persist with vm.nr_hugepages = {} in /etc/sysctl.d)",
+                total,
+                self.sysfs_dir(),
+                total
+            ),
+            // Runtime allocation of 1 GiB pages rarely succeeds once memory
+            // is fragmented
+            HugePageSize::Size1G => format!(
+                "add `hugepagesz=1G hugepages={}` to the kernel command line and reboot",
+                total
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HugePageSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            HugePageSize::Size2M => "2M",
+            HugePageSize::Size1G => "1G",
+        })
+    }
+}
+
+impl FromStr for HugePageSize {
+    type Err = NovaError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "2m" | "2mi" | "2mib" | "2048k" | "2048kb" => Ok(HugePageSize::Size2M),
+            "1g" | "1gi" | "1gib" | "1024m" | "1024mib" => Ok(HugePageSize::Size1G),
+            _ => Err(NovaError::ConfigError(format!(
+                "Unsupported hugepage size '{}' (use 2M or 1G)",
+                value
+            ))),
+        }
+    }
+}
+
+/// `hugepages = true | false | "2M" | "1G"`; `true` means 2 MiB pages.
+pub fn deserialize_setting<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<HugePageSize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Setting {
+        Enabled(bool),
+        Size(String),
+    }
+
+    match Option::<Setting>::deserialize(deserializer)? {
+        None | Some(Setting::Enabled(false)) => Ok(None),
+        Some(Setting::Enabled(true)) => Ok(Some(HugePageSize::Size2M)),
+        Some(Setting::Size(size)) => size.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// One of the host's hugepage pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HugePagePool {
+    pub size: HugePageSize,
+    /// Pages reserved (`HugePages_Total`)
+    pub total: u64,
+    /// Reserved pages not yet backing a guest or other process
+    pub free: u64,
+}
+
+impl HugePagePool {
+    /// Check that the pool can back a VM with `memory_kib` of RAM. A running
+    /// VM already holds its pages, so callers check shut-off VMs only.
+    pub fn check(&self, vm: &str, memory_kib: u64) -> Result<()> {
+        let needed = self.size.pages_for(memory_kib);
+        if needed <= self.free {
+            return Ok(());
+        }
+        let in_use = self.total.saturating_sub(self.free);
+        Err(NovaError::ConfigError(format!(
+            "VM '{}' needs {} {} hugepages ({} MiB) but only {} of {} reserved are free; {}",
+            vm,
+            needed,
+            self.size,
+            memory_kib / 1024,
+            self.free,
+            self.total,
+            self.size.reserve_hint(in_use + needed)
+        )))
+    }
+}
+
+/// The default-size pool from `/proc/meminfo` (`HugePages_Total`,
+/// `HugePages_Free`, `Hugepagesize`).
+pub fn parse_meminfo(meminfo: &str) -> Option<HugePagePool> {
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.split_whitespace().next()?.parse::<u64>().ok())?
+        })
+    };
+    Some(HugePagePool {
+        size: HugePageSize::from_kib(field("Hugepagesize")?)?,
+        total: field("HugePages_Total")?,
+        free: field("HugePages_Free")?,
+    })
+}
+
+/// The host's pool of `size` pages: `/proc/meminfo` when it is the default
+/// size, sysfs otherwise. Sizes the CPU or kernel lack read as empty.
+pub fn host_pool(size: HugePageSize) -> HugePagePool {
+    if let Some(pool) = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| parse_meminfo(&meminfo))
+        .filter(|pool| pool.size == size)
+    {
+        return pool;
+    }
+    let read = |file: &str| {
+        std::fs::read_to_string(format!("{}/{}", size.sysfs_dir(), file))
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0)
+    };
+    HugePagePool {
+        size,
+        total: read("nr_hugepages"),
+        free: read("free_hugepages"),
+    }
+}
+
+/// Mount point of a hugetlbfs serving `size` pages in `/proc/mounts`
+/// output. Mounts without `pagesize=` use the default size `default`.
+pub fn parse_hugetlbfs_mount(
+    mounts: &str,
+    size: HugePageSize,
+    default: Option<HugePageSize>,
+) -> Option<String> {
+    mounts.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, mount_point, "hugetlbfs", options, ..] = fields.as_slice() else {
+            return None;
+        };
+        let page_size = options
+            .split(',')
+            .find_map(|option| option.strip_prefix("pagesize="))
+            .map_or(default, |value| value.parse().ok());
+        (page_size == Some(size)).then(|| mount_point.to_string())
+    })
+}
+
+/// `-mem-path` arguments for a VM started as a bare QEMU process, after
+/// checking the pool has room for `memory_kib`.
+pub fn qemu_args(vm: &str, memory_kib: u64, size: HugePageSize) -> Result<Vec<String>> {
+    host_pool(size).check(vm, memory_kib)?;
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let default = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| parse_meminfo(&meminfo))
+        .map(|pool| pool.size);
+    let mount = parse_hugetlbfs_mount(&mounts, size, default).ok_or_else(|| {
+        NovaError::ConfigError(format!(
+            "No hugetlbfs mount for {} pages; QEMU needs one for `hugepages` outside libvirt \
+             (e.g. `sudo mount -t hugetlbfs -o pagesize={} none /dev/hugepages{}`)",
+            size, size, size
+        ))
+    })?;
+    Ok(vec![
+        "-mem-path".to_string(),
+        mount,
+        "-mem-prealloc".to_string(),
+    ])
+}
+
+/// `<memoryBacking>` element for a new domain.
+pub fn memory_backing_xml(size: HugePageSize) -> String {
+    format!(
+        "  <memoryBacking>\n    {}\n  </memoryBacking>\n",
+        hugepages_element(size)
+    )
+}
+
+fn hugepages_element(size: HugePageSize) -> String {
+    format!(
+        "<hugepages>\n      <page size='{}' unit='KiB'/>\n    </hugepages>",
+        size.size_kib()
+    )
+}
+
+/// Hugepage size a domain is backed by, if any. `<hugepages/>` without a
+/// `<page>` uses the host's default size, taken to be 2 MiB.
+pub fn parse_hugepages(xml: &str) -> Option<HugePageSize> {
+    let backing =
+        Regex::new(r"(?s)<memoryBacking>.*?</memoryBacking>").expect("valid memoryBacking pattern");
+    let page = Regex::new(r"<page\b[^>]*size='(\d+)'([^>]*)/>").expect("valid page pattern");
+
+    let block = backing.find(xml)?.as_str();
+    if !block.contains("<hugepages") {
+        return None;
+    }
+    Some(
+        page.captures(block)
+            .and_then(|caps| {
+                let value: u64 = caps[1].parse().ok()?;
+                let size_kib = match caps[2].split_once("unit='").map(|(_, rest)| rest) {
+                    Some(unit) if unit.starts_with("MiB") || unit.starts_with("M'") => value * 1024,
+                    Some(unit) if unit.starts_with("GiB") || unit.starts_with("G'") => {
+                        value * 1024 * 1024
+                    }
+                    _ => value,
+                };
+                HugePageSize::from_kib(size_kib)
+            })
+            .unwrap_or(HugePageSize::Size2M),
+    )
+}
+
+/// Switch a domain definition to `size` hugepages, or back to normal pages
+/// with `None`. Other `<memoryBacking>` settings (e.g. shared memory for
+/// virtiofs) are kept.
+pub fn apply_hugepages(xml: &str, size: Option<HugePageSize>) -> Result<String> {
+    let backing = Regex::new(r"(?s)([ \t]*)<memoryBacking>(.*?)</memoryBacking>\n?")
+        .expect("valid memoryBacking pattern");
+    let hugepages = Regex::new(r"(?s)[ \t]*<hugepages\s*/>\n?|[ \t]*<hugepages>.*?</hugepages>\n?")
+        .expect("valid hugepages pattern");
+
+    if let Some(caps) = backing.captures(xml) {
+        let whole = caps.get(0).expect("whole match");
+        let indent = &caps[1];
+        let others = hugepages.replace(&caps[2], "");
+        let mut children: Vec<String> = size
+            .map(|size| format!("{indent}  {}", hugepages_element(size)))
+            .into_iter()
+            .collect();
+        children.extend(
+            others
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string),
+        );
+        let replacement = if children.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "{indent}<memoryBacking>\n{}\n{indent}</memoryBacking>\n",
+                children.join("\n")
+            )
+        };
+        let mut xml = xml.to_string();
+        xml.replace_range(whole.range(), &replacement);
+        return Ok(xml);
+    }
+
+    let Some(size) = size else {
+        return Ok(xml.to_string());
+    };
+    let anchor = ["</currentMemory>", "</memory>"]
+        .into_iter()
+        .find_map(|tag| xml.find(tag).map(|start| start + tag.len()))
+        .ok_or_else(|| NovaError::ConfigError("Domain XML has no <memory> element".to_string()))?;
+    let line_end = xml[anchor..]
+        .find('\n')
+        .map_or(xml.len(), |offset| anchor + offset + 1);
+    let mut xml = xml.to_string();
+    xml.insert_str(line_end, &memory_backing_xml(size));
+    Ok(xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_and_domain_xml() {
+        let meminfo = "MemTotal:       65768520 kB\nHugePages_Total:    4096\nHugePages_Free:     1024\nHugePages_Rsvd:        0\nHugepagesize:       2048 kB\n";
+        let pool = parse_meminfo(meminfo).unwrap();
+        assert_eq!(pool.size, HugePageSize::Size2M);
+        assert_eq!((pool.total, pool.free), (4096, 1024));
+        assert!(pool.check("small", 2 * 1024 * 1024).is_ok());
+        let err = pool
+            .check("gaming", 8 * 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("needs 4096 2M hugepages"), "{}", err);
+        assert!(err.contains("echo 7168 | sudo tee"), "{}", err);
+        assert_eq!(HugePageSize::Size1G.pages_for(1536 * 1024), 2);
+        assert_eq!(
+            "1GiB".parse::<HugePageSize>().unwrap(),
+            HugePageSize::Size1G
+        );
+        assert!("4M".parse::<HugePageSize>().is_err());
+
+        let domain = "<domain type='kvm'>\n  <name>gaming</name>\n  <memory unit='KiB'>8388608</memory>\n  <currentMemory unit='KiB'>8388608</currentMemory>\n  <vcpu placement='static'>8</vcpu>\n</domain>";
+        assert_eq!(parse_hugepages(domain), None);
+        let backed = apply_hugepages(domain, Some(HugePageSize::Size1G)).unwrap();
+        assert!(backed.contains(
+            "</currentMemory>\n  <memoryBacking>\n    <hugepages>\n      <page size='1048576' unit='KiB'/>"
+        ));
+        assert_eq!(parse_hugepages(&backed), Some(HugePageSize::Size1G));
+        let switched = apply_hugepages(&backed, Some(HugePageSize::Size2M)).unwrap();
+        assert_eq!(parse_hugepages(&switched), Some(HugePageSize::Size2M));
+        assert_eq!(switched.matches("<hugepages>").count(), 1);
+        assert_eq!(apply_hugepages(&switched, None).unwrap(), domain);
+
+        let virtiofs = "<domain>\n  <memory unit='KiB'>4194304</memory>\n  <memoryBacking>\n    <hugepages/>\n    <access mode='shared'/>\n  </memoryBacking>\n</domain>";
+        assert_eq!(parse_hugepages(virtiofs), Some(HugePageSize::Size2M));
+        let off = apply_hugepages(virtiofs, None).unwrap();
+        assert!(off.contains("<memoryBacking>") && off.contains("<access mode='shared'/>"));
+        assert_eq!(parse_hugepages(&off), None);
+
+        #[derive(Deserialize)]
+        struct Vm {
+            #[serde(default, deserialize_with = "deserialize_setting")]
+            hugepages: Option<HugePageSize>,
+        }
+        let vm: Vm = toml::from_str("hugepages = true").unwrap();
+        assert_eq!(vm.hugepages, Some(HugePageSize::Size2M));
+        let vm: Vm = toml::from_str(r#"hugepages = "1G""#).unwrap();
+        assert_eq!(vm.hugepages, Some(HugePageSize::Size1G));
+        let vm: Vm = toml::from_str("").unwrap();
+        assert_eq!(vm.hugepages, None);
+
+        let mounts = "proc /proc proc rw 0 0\nhugetlbfs /dev/hugepages hugetlbfs rw,relatime,pagesize=2M 0 0\nnone /dev/hugepages1G hugetlbfs rw,relatime,pagesize=1024M 0 0\n";
+        assert_eq!(
+            parse_hugetlbfs_mount(mounts, HugePageSize::Size1G, None).as_deref(),
+            Some("/dev/hugepages1G")
+        );
+        let default_only = "hugetlbfs /dev/hugepages hugetlbfs rw,relatime 0 0\n";
+        assert_eq!(
+            parse_hugetlbfs_mount(
+                default_only,
+                HugePageSize::Size2M,
+                Some(HugePageSize::Size2M)
+            )
+            .as_deref(),
+            Some("/dev/hugepages")
+        );
+        assert_eq!(
+            parse_hugetlbfs_mount(
+                default_only,
+                HugePageSize::Size1G,
+                Some(HugePageSize::Size2M)
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,360 @@
+//! Memory hotplug with DIMM devices.
+//!
+//! Unlike ballooning, which can only hand back memory below the boot
+//! allocation, hot-added DIMMs raise a guest's memory up to the domain's
+//! `<maxMemory>`. That ceiling and its slot count are fixed when the domain
+//! is defined, so VMs that should grow need `max_memory` (and optionally
+//! `memory_slots`) set in the NovaFile before they are created. Libvirt also
+//! requires a guest NUMA cell, which the domain builder adds alongside.
+//!
+//! Ballooning (`nova vm set-memory`) works the other way: the guest's
+//! virtio-balloon driver gives memory back to the host, or reclaims it up to
+//! the domain's `<memory>`. That ceiling can only be raised while the VM is
+//! shut off.
+
+use crate::vm_cpu::CpuTopology;
+use crate::{NovaError, Result};
+use regex::Regex;
+
+/// Slots reserved when `max_memory` is set without `memory_slots`.
+pub const DEFAULT_MEMORY_SLOTS: u32 = 4;
+
+/// Smallest balloon target; below this most guests stop responding.
+pub const MIN_BALLOON_MIB: u64 = 128;
+
+/// A hot-plugged DIMM as it appears in the domain XML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimmDevice {
+    pub size_kib: u64,
+    /// Full `<memory model='dimm'>` element, used to detach it again
+    pub xml: String,
+}
+
+/// Hotplug-relevant parts of a domain definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryLayout {
+    /// `<maxMemory>` ceiling, if the domain was defined with one
+    pub max_memory_kib: Option<u64>,
+    pub slots: u32,
+    /// Total memory including hot-plugged DIMMs
+    pub memory_kib: u64,
+    /// `<currentMemory>`, the balloon target
+    pub current_memory_kib: u64,
+    pub dimms: Vec<DimmDevice>,
+    pub has_numa: bool,
+    /// A `<memballoon>` device other than `model='none'`
+    pub has_balloon: bool,
+}
+
+/// How `nova vm set-memory` applies a requested size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalloonPlan {
+    pub target_kib: u64,
+    /// New `<memory>` ceiling, for a shut-off VM asked for more than it has
+    pub raise_max_to_kib: Option<u64>,
+    /// The request was above the ceiling of a running VM and was lowered to it
+    pub clamped: bool,
+}
+
+impl MemoryLayout {
+    pub fn free_slots(&self) -> u32 {
+        self.slots.saturating_sub(self.dimms.len() as u32)
+    }
+
+    /// Memory that can still be hot-added before reaching `<maxMemory>`.
+    pub fn headroom_kib(&self) -> u64 {
+        self.max_memory_kib
+            .map(|max| max.saturating_sub(self.memory_kib))
+            .unwrap_or(0)
+    }
+
+    /// Check that a DIMM of `size_mib` fits, explaining how to make room if not.
+    pub fn check_hotplug(&self, vm: &str, size_mib: u64) -> Result<()> {
+        if size_mib == 0 {
+            return Err(NovaError::ConfigError(
+                "DIMM size must be greater than zero".to_string(),
+            ));
+        }
+
+        let Some(max_kib) = self.max_memory_kib.filter(|_| self.slots > 0) else {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' has no <maxMemory> slots, so memory cannot be hot-added. Set \
+                 `max_memory` (and optionally `memory_slots`) under [vm.{}] before creating \
+                 the VM, or add <maxMemory slots='{}' unit='GiB'>..</maxMemory> and a NUMA \
+                 cell with `virsh edit {}` while it is shut off.",
+                vm, vm, DEFAULT_MEMORY_SLOTS, vm
+            )));
+        };
+        if !self.has_numa {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' has no guest NUMA cell; libvirt needs one for DIMM hotplug. Add \
+                 <numa><cell id='0' cpus='0-N' memory='..' unit='KiB'/></numa> under <cpu> \
+                 with `virsh edit {}`.",
+                vm, vm
+            )));
+        }
+        if self.free_slots() == 0 {
+            return Err(NovaError::ConfigError(format!(
+                "All {} memory slots of VM '{}' are in use; unplug a DIMM first",
+                self.slots, vm
+            )));
+        }
+        if size_mib * 1024 > self.headroom_kib() {
+            return Err(NovaError::ConfigError(format!(
+                "Adding {} MiB would take VM '{}' past its {} MiB maxMemory ({} MiB free)",
+                size_mib,
+                vm,
+                max_kib / 1024,
+                self.headroom_kib() / 1024
+            )));
+        }
+        Ok(())
+    }
+
+    /// Work out the balloon target for `size_kib`. A running VM can't grow
+    /// past `<memory>`, so the request is clamped to it.
+    pub fn plan_balloon(&self, vm: &str, size_kib: u64, running: bool) -> Result<BalloonPlan> {
+        if !self.has_balloon {
+            return Err(NovaError::ConfigError(format!(
+                "VM '{}' has no memory balloon device (<memballoon model='none'/>). Change it to \
+                 <memballoon model='virtio'/> with `virsh edit {}` while it is shut off.",
+                vm, vm
+            )));
+        }
+        if size_kib < MIN_BALLOON_MIB * 1024 {
+            return Err(NovaError::ConfigError(format!(
+                "Memory must be at least {} MiB",
+                MIN_BALLOON_MIB
+            )));
+        }
+
+        if size_kib <= self.memory_kib {
+            return Ok(BalloonPlan {
+                target_kib: size_kib,
+                raise_max_to_kib: None,
+                clamped: false,
+            });
+        }
+        Ok(if running {
+            BalloonPlan {
+                target_kib: self.memory_kib,
+                raise_max_to_kib: None,
+                clamped: true,
+            }
+        } else {
+            BalloonPlan {
+                target_kib: size_kib,
+                raise_max_to_kib: Some(size_kib),
+                clamped: false,
+            }
+        })
+    }
+
+    /// The most recently added DIMM of `size_mib`, which is the one to unplug.
+    pub fn dimm_to_unplug(&self, size_mib: u64) -> Option<&DimmDevice> {
+        self.dimms
+            .iter()
+            .rev()
+            .find(|dimm| dimm.size_kib == size_mib * 1024)
+    }
+}
+
+/// Read the memory layout out of `virsh dumpxml` output.
+pub fn parse_memory_layout(xml: &str) -> MemoryLayout {
+    let max_memory = Regex::new(r"<maxMemory\b([^>]*)>\s*(\d+)\s*</maxMemory>")
+        .expect("valid maxMemory pattern");
+    let memory =
+        Regex::new(r"<memory\b([^>]*)>\s*(\d+)\s*</memory>").expect("valid memory pattern");
+    let dimm = Regex::new(r"(?s)<memory\b[^>]*model='dimm'[^>]*>.*?</memory>")
+        .expect("valid dimm pattern");
+    let size = Regex::new(r"<size\b([^>]*)>\s*(\d+)\s*</size>").expect("valid size pattern");
+    let slots = Regex::new(r"slots='(\d+)'").expect("valid slots pattern");
+    let current = Regex::new(r"<currentMemory\b([^>]*)>\s*(\d+)\s*</currentMemory>")
+        .expect("valid currentMemory pattern");
+    let balloon =
+        Regex::new(r"<memballoon\b[^>]*model='([^']+)'").expect("valid memballoon pattern");
+
+    let mut layout = MemoryLayout {
+        has_numa: xml.contains("<numa>"),
+        has_balloon: balloon.captures(xml).is_some_and(|caps| &caps[1] != "none"),
+        ..MemoryLayout::default()
+    };
+
+    if let Some(caps) = max_memory.captures(xml) {
+        layout.max_memory_kib = caps[2]
+            .parse()
+            .ok()
+            .map(|value| to_kib(value, unit_attribute(&caps[1])));
+        layout.slots = slots
+            .captures(&caps[1])
+            .and_then(|slots| slots[1].parse().ok())
+            .unwrap_or(0);
+    }
+    if let Some(caps) = memory.captures(xml) {
+        layout.memory_kib = caps[2]
+            .parse()
+            .map(|value| to_kib(value, unit_attribute(&caps[1])))
+            .unwrap_or(0);
+    }
+    layout.current_memory_kib = current
+        .captures(xml)
+        .and_then(|caps| {
+            caps[2]
+                .parse()
+                .ok()
+                .map(|value| to_kib(value, unit_attribute(&caps[1])))
+        })
+        .unwrap_or(layout.memory_kib);
+    layout.dimms = dimm
+        .find_iter(xml)
+        .filter_map(|block| {
+            let caps = size.captures(block.as_str())?;
+            let value = caps[2].parse().ok()?;
+            Some(DimmDevice {
+                size_kib: to_kib(value, unit_attribute(&caps[1])),
+                xml: block.as_str().to_string(),
+            })
+        })
+        .collect();
+
+    layout
+}
+
+/// The balloon's current size from `virsh dommemstat` (`actual 4194304`).
+/// It only moves once the guest driver acknowledges a new target.
+pub fn parse_balloon_actual(dommemstat: &str) -> Option<u64> {
+    dommemstat.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once(char::is_whitespace)?;
+        (key == "actual").then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Device XML for a DIMM of `size_mib` on guest NUMA node `node`.
+pub fn dimm_xml(size_mib: u64, node: u32) -> String {
+    format!(
+        "<memory model='dimm'>\n  <target>\n    <size unit='MiB'>{}</size>\n    <node>{}</node>\n  </target>\n</memory>\n",
+        size_mib, node
+    )
+}
+
+/// `<maxMemory>` element for a new domain.
+pub fn max_memory_xml(max_memory_kib: u64, slots: u32) -> String {
+    format!(
+        "  <maxMemory slots='{}' unit='KiB'>{}</maxMemory>\n",
+        slots, max_memory_kib
+    )
+}
+
+/// `<cpu>` element with the single NUMA cell DIMM hotplug needs.
+pub fn numa_cpu_xml(topology: &CpuTopology, memory_kib: u64) -> String {
+    format!(
+        "  <cpu mode='host-passthrough' check='none'>\n    {}\n    <numa>\n      <cell id='0' cpus='0-{}' memory='{}' unit='KiB'/>\n    </numa>\n  </cpu>\n",
+        topology.topology_xml(),
+        topology.vcpus().saturating_sub(1),
+        memory_kib
+    )
+}
+
+fn unit_attribute(attributes: &str) -> &str {
+    attributes
+        .split_once("unit='")
+        .and_then(|(_, rest)| rest.split_once('\''))
+        .map(|(unit, _)| unit)
+        .unwrap_or("KiB")
+}
+
+fn to_kib(value: u64, unit: &str) -> u64 {
+    match unit {
+        "b" | "bytes" => value / 1024,
+        "MiB" | "M" => value * 1024,
+        "GiB" | "G" => value * 1024 * 1024,
+        "TiB" | "T" => value * 1024 * 1024 * 1024,
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOMAIN: &str = "<domain type='kvm'>
+  <name>dev-box</name>
+  <maxMemory slots='4' unit='KiB'>16777216</maxMemory>
+  <memory unit='KiB'>6291456</memory>
+  <currentMemory unit='KiB'>6291456</currentMemory>
+  <cpu mode='host-passthrough' check='none'>
+    <numa>
+      <cell id='0' cpus='0-3' memory='4194304' unit='KiB'/>
+    </numa>
+  </cpu>
+  <devices>
+    <memory model='dimm'>
+      <target>
+        <size unit='KiB'>2097152</size>
+        <node>0</node>
+      </target>
+      <alias name='dimm0'/>
+      <address type='dimm' slot='0' base='0x100000000'/>
+    </memory>
+    <memballoon model='virtio'>
+      <stats period='5'/>
+    </memballoon>
+  </devices>
+</domain>";
+
+    #[test]
+    fn layout_tracks_slots_and_headroom() {
+        let layout = parse_memory_layout(DOMAIN);
+        assert_eq!(layout.max_memory_kib, Some(16 * 1024 * 1024));
+        assert_eq!(layout.memory_kib, 6 * 1024 * 1024);
+        assert_eq!(layout.free_slots(), 3);
+        assert_eq!(layout.headroom_kib(), 10 * 1024 * 1024);
+        assert!(layout.check_hotplug("dev-box", 4096).is_ok());
+        assert!(layout.check_hotplug("dev-box", 12288).is_err());
+
+        let dimm = layout.dimm_to_unplug(2048).unwrap();
+        assert!(dimm.xml.contains("slot='0'"));
+        assert!(layout.dimm_to_unplug(4096).is_none());
+
+        let fixed = parse_memory_layout(
+            "<domain><memory unit='GiB'>4</memory><devices></devices></domain>",
+        );
+        assert_eq!(fixed.memory_kib, 4 * 1024 * 1024);
+        let err = fixed.check_hotplug("fixed", 1024).unwrap_err().to_string();
+        assert!(err.contains("max_memory"), "{}", err);
+    }
+
+    #[test]
+    fn balloon_targets_clamp_to_memory_while_running() {
+        let layout = parse_memory_layout(DOMAIN);
+        assert!(layout.has_balloon);
+        assert_eq!(layout.current_memory_kib, 6 * 1024 * 1024);
+
+        let shrink = layout
+            .plan_balloon("dev-box", 4 * 1024 * 1024, true)
+            .unwrap();
+        assert_eq!(shrink.target_kib, 4 * 1024 * 1024);
+        assert!(!shrink.clamped);
+
+        let grow_live = layout
+            .plan_balloon("dev-box", 8 * 1024 * 1024, true)
+            .unwrap();
+        assert_eq!(grow_live.target_kib, 6 * 1024 * 1024);
+        assert!(grow_live.clamped);
+        let grow_off = layout
+            .plan_balloon("dev-box", 8 * 1024 * 1024, false)
+            .unwrap();
+        assert_eq!(grow_off.raise_max_to_kib, Some(8 * 1024 * 1024));
+        assert!(layout.plan_balloon("dev-box", 64 * 1024, true).is_err());
+
+        let none = parse_memory_layout(
+            "<domain><memory unit='GiB'>4</memory><devices><memballoon model='none'/></devices></domain>",
+        );
+        let err = none.plan_balloon("nb", 1024 * 1024, true).unwrap_err();
+        assert!(err.to_string().contains("balloon"));
+
+        let stats = "actual 4194304\nswap_in 0\nrss 1833220\n";
+        assert_eq!(parse_balloon_actual(stats), Some(4194304));
+        assert_eq!(parse_balloon_actual("rss 10\n"), None);
+    }
+}
@@ -0,0 +1,230 @@
+//! Per-NIC bandwidth limits.
+//!
+//! `net_limit = { inbound_mib = 100, outbound_mib = 20 }` under
+//! `[vm.<name>]` (or `nova vm set-netlimit`) caps each of the VM's
+//! interfaces with libvirt `<bandwidth>` QoS, so one guest can't starve the
+//! others on a shared uplink. Directions are as the guest sees them:
+//! inbound is what it receives, outbound what it sends. Libvirt works in
+//! KiB/s; Nova takes whole MiB/s.
+
+use crate::{NovaError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Logged when a VM with `net_limit` is launched without libvirt.
+pub const QEMU_WARNING: &str =
+    "net_limit is libvirt QoS and is not applied when Nova launches QEMU directly";
+
+/// Average rate caps in MiB/s; `None` leaves a direction unlimited.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetLimit {
+    #[serde(default)]
+    pub inbound_mib: Option<u32>,
+    #[serde(default)]
+    pub outbound_mib: Option<u32>,
+}
+
+impl NetLimit {
+    /// Limit from CLI values, where 0 means unlimited like `domiftune`.
+    pub fn new(inbound_mib: Option<u32>, outbound_mib: Option<u32>) -> Self {
+        Self {
+            inbound_mib: inbound_mib.filter(|rate| *rate > 0),
+            outbound_mib: outbound_mib.filter(|rate| *rate > 0),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.inbound_mib.is_none() && self.outbound_mib.is_none()
+    }
+
+    /// `--inbound`/`--outbound` for `virsh domiftune`; 0 clears a direction.
+    pub fn domiftune_args(&self) -> Vec<String> {
+        vec![
+            "--inbound".to_string(),
+            mib_to_kib(self.inbound_mib).to_string(),
+            "--outbound".to_string(),
+            mib_to_kib(self.outbound_mib).to_string(),
+        ]
+    }
+}
+
+impl fmt::Display for NetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = |mib: Option<u32>| match mib {
+            Some(mib) => format!("{} MiB/s", mib),
+            None => "unlimited".to_string(),
+        };
+        write!(
+            f,
+            "in {}, out {}",
+            rate(self.inbound_mib),
+            rate(self.outbound_mib)
+        )
+    }
+}
+
+/// A change to a limit in MiB/s: `None` keeps a direction's current rate
+/// and `Some(0)` clears it, as with `nova vm set-netlimit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetLimitChange {
+    pub inbound_mib: Option<u32>,
+    pub outbound_mib: Option<u32>,
+}
+
+impl NetLimitChange {
+    /// Clear both directions.
+    pub fn unlimited() -> Self {
+        Self {
+            inbound_mib: Some(0),
+            outbound_mib: Some(0),
+        }
+    }
+
+    /// Replace both directions with `limit`.
+    pub fn replace(limit: &NetLimit) -> Self {
+        Self {
+            inbound_mib: Some(limit.inbound_mib.unwrap_or(0)),
+            outbound_mib: Some(limit.outbound_mib.unwrap_or(0)),
+        }
+    }
+
+    /// The limit after this change is applied to `current`.
+    pub fn apply(&self, current: &NetLimit) -> NetLimit {
+        NetLimit::new(
+            self.inbound_mib.or(current.inbound_mib),
+            self.outbound_mib.or(current.outbound_mib),
+        )
+    }
+}
+
+/// The limit on one interface, identified by MAC.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct InterfaceNetLimit {
+    pub mac: String,
+    #[serde(flatten)]
+    pub limit: NetLimit,
+}
+
+fn mib_to_kib(mib: Option<u32>) -> u64 {
+    mib.map_or(0, |mib| u64::from(mib) * 1024)
+}
+
+/// Reject limits that don't fit `domiftune`'s 32-bit KiB/s fields.
+pub fn validate(limit: &NetLimit) -> Result<()> {
+    const MAX_MIB: u32 = u32::MAX / 1024;
+    for (direction, rate) in [
+        ("inbound", limit.inbound_mib),
+        ("outbound", limit.outbound_mib),
+    ] {
+        if rate.is_some_and(|rate| rate > MAX_MIB) {
+            return Err(NovaError::ConfigError(format!(
+                "{} limit must be at most {} MiB/s",
+                direction, MAX_MIB
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Averages from `virsh domiftune <vm> <iface>`, rounded up to MiB/s:
+///
+/// ```text
+/// inbound.average: 102400
+/// inbound.peak   : 0
+/// outbound.average: 0
+/// ```
+pub fn parse_domiftune(output: &str) -> NetLimit {
+    let average = |direction: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            if key.trim() != format!("{}.average", direction) {
+                return None;
+            }
+            let kib: u64 = value.trim().parse().ok()?;
+            (kib > 0).then(|| u32::try_from(kib.div_ceil(1024)).unwrap_or(u32::MAX))
+        })
+    };
+    NetLimit {
+        inbound_mib: average("inbound"),
+        outbound_mib: average("outbound"),
+    }
+}
+
+/// `<bandwidth>` element for an `<interface>`; empty when unlimited.
+pub fn bandwidth_xml(limit: &NetLimit) -> String {
+    if limit.is_unlimited() {
+        return String::new();
+    }
+    let mut xml = String::from("      <bandwidth>\n");
+    if let Some(mib) = limit.inbound_mib {
+        xml.push_str(&format!(
+            "        <inbound average='{}'/>\n",
+            u64::from(mib) * 1024
+        ));
+    }
+    if let Some(mib) = limit.outbound_mib {
+        xml.push_str(&format!(
+            "        <outbound average='{}'/>\n",
+            u64::from(mib) * 1024
+        ));
+    }
+    xml.push_str("      </bandwidth>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_round_trip_through_domiftune() {
+        let limit = NetLimit::new(Some(100), Some(0));
+        assert_eq!(limit.outbound_mib, None);
+        assert_eq!(
+            limit.domiftune_args(),
+            ["--inbound", "102400", "--outbound", "0"]
+        );
+        assert_eq!(limit.to_string(), "in 100 MiB/s, out unlimited");
+
+        let output = "inbound.average: 102400\ninbound.peak   : 0\ninbound.burst  : 0\n\
+                      inbound.floor  : 0\noutbound.average: 500\noutbound.peak  : 0\n";
+        assert_eq!(
+            parse_domiftune(output),
+            NetLimit {
+                inbound_mib: Some(100),
+                outbound_mib: Some(1),
+            }
+        );
+        assert!(parse_domiftune("inbound.average: 0\n").is_unlimited());
+
+        assert!(bandwidth_xml(&NetLimit::default()).is_empty());
+        let xml = bandwidth_xml(&limit);
+        assert!(xml.contains("<inbound average='102400'/>"));
+        assert!(!xml.contains("outbound"));
+
+        assert!(validate(&NetLimit::new(Some(u32::MAX), None)).is_err());
+    }
+
+    #[test]
+    fn changes_keep_directions_they_leave_out() {
+        let current = NetLimit::new(Some(100), Some(20));
+        let outbound_only = NetLimitChange {
+            inbound_mib: None,
+            outbound_mib: Some(50),
+        };
+        assert_eq!(
+            outbound_only.apply(&current),
+            NetLimit::new(Some(100), Some(50))
+        );
+
+        let clear_inbound = NetLimitChange {
+            inbound_mib: Some(0),
+            outbound_mib: None,
+        };
+        assert_eq!(clear_inbound.apply(&current), NetLimit::new(None, Some(20)));
+        assert!(NetLimitChange::unlimited().apply(&current).is_unlimited());
+
+        let limit = NetLimit::new(Some(10), None);
+        assert_eq!(NetLimitChange::replace(&limit).apply(&current), limit);
+    }
+}
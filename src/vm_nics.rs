@@ -0,0 +1,261 @@
+//! VM network interfaces.
+//!
+//! `network` under `[vm.<name>]` is either a single network name, the
+//! original form, or a list with one entry per NIC for multi-homed guests
+//! such as a firewall VM:
+//!
+//! ```toml
+//! network = ["lan", { network = "wan", model = "e1000e", mac = "52:54:00:12:34:56" }]
+//! ```
+//!
+//! A NIC without a model uses `[device_defaults] net_model`; one without a
+//! MAC gets one from libvirt (or QEMU) when the VM is defined.
+
+use crate::{NovaError, Result, vm_devices::NetModel, vm_netlimit};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+/// One NIC of a VM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VmNic {
+    /// Libvirt network or host bridge the NIC is plugged into
+    pub network: String,
+    #[serde(default)]
+    pub model: Option<NetModel>,
+    #[serde(default)]
+    pub mac: Option<String>,
+}
+
+impl VmNic {
+    pub fn new(network: impl Into<String>) -> Self {
+        Self {
+            network: network.into(),
+            model: None,
+            mac: None,
+        }
+    }
+
+    /// A NIC that the scalar `network = "..."` form can describe.
+    fn is_plain(&self) -> bool {
+        self.model.is_none() && self.mac.is_none()
+    }
+}
+
+impl fmt::Display for VmNic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.network)?;
+        if let Some(model) = self.model {
+            write!(f, " ({})", model)?;
+        }
+        if let Some(mac) = &self.mac {
+            write!(f, " [{}]", mac)?;
+        }
+        Ok(())
+    }
+}
+
+/// `network = "lan"` or `network = ["lan", { network = "wan", ... }]`.
+pub fn deserialize_networks<'de, D>(deserializer: D) -> std::result::Result<Vec<VmNic>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Name(String),
+        Nic(VmNic),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Setting {
+        Name(String),
+        List(Vec<Entry>),
+    }
+
+    let nics = match Option::<Setting>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(Setting::Name(name)) => vec![VmNic::new(name)],
+        Some(Setting::List(entries)) => entries
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::Name(name) => VmNic::new(name),
+                Entry::Nic(nic) => nic,
+            })
+            .collect(),
+    };
+    for nic in &nics {
+        if let Some(mac) = &nic.mac {
+            validate_mac(mac).map_err(serde::de::Error::custom)?;
+        }
+    }
+    Ok(nics)
+}
+
+/// Write a single plain NIC back in the scalar form, so saving a NovaFile
+/// doesn't rewrite `network = "lan"` as a list.
+pub fn serialize_networks<S>(nics: &[VmNic], serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match nics {
+        [nic] if nic.is_plain() => serializer.serialize_str(&nic.network),
+        nics => nics.serialize(serializer),
+    }
+}
+
+/// Accept a unicast MAC such as `52:54:00:12:34:56`.
+pub fn validate_mac(mac: &str) -> Result<()> {
+    let octets: Vec<&str> = mac.split(':').collect();
+    let well_formed = octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()));
+    if !well_formed {
+        return Err(NovaError::ConfigError(format!(
+            "Invalid MAC address '{}' (expected six hex pairs like 52:54:00:12:34:56)",
+            mac
+        )));
+    }
+    let first = u8::from_str_radix(octets[0], 16).unwrap_or(0);
+    if first & 1 == 1 {
+        return Err(NovaError::ConfigError(format!(
+            "MAC address '{}' is multicast; a NIC needs a unicast address",
+            mac
+        )));
+    }
+    Ok(())
+}
+
+/// QEMU `-device` name for a NIC model.
+pub fn qemu_device(model: NetModel) -> &'static str {
+    match model {
+        NetModel::Virtio => "virtio-net-pci",
+        NetModel::E1000e => "e1000e",
+        NetModel::Rtl8139 => "rtl8139",
+    }
+}
+
+/// `-netdev`/`-device` arguments for NIC number `index` on a host bridge.
+pub fn qemu_bridge_args(index: usize, bridge: &str, nic: &VmNic, default: NetModel) -> Vec<String> {
+    let mut device = format!(
+        "{},netdev=net{}",
+        qemu_device(nic.model.unwrap_or(default)),
+        index
+    );
+    if let Some(mac) = &nic.mac {
+        device.push_str(&format!(",mac={}", mac));
+    }
+    vec![
+        "-netdev".to_string(),
+        format!("bridge,id=net{},br={}", index, bridge),
+        "-device".to_string(),
+        device,
+    ]
+}
+
+/// Whether `name` is a host bridge rather than a libvirt network.
+pub fn is_host_bridge(name: &str) -> bool {
+    Path::new(&format!("/sys/class/net/{}/bridge", name)).exists()
+}
+
+/// `<interface>` element for a domain definition, on a host bridge when
+/// one has the NIC's network name and on a libvirt network otherwise.
+pub fn interface_xml(
+    nic: &VmNic,
+    default: NetModel,
+    limit: Option<&vm_netlimit::NetLimit>,
+) -> String {
+    source_interface_xml(nic, is_host_bridge(&nic.network), default, limit)
+}
+
+fn source_interface_xml(
+    nic: &VmNic,
+    bridge: bool,
+    default: NetModel,
+    limit: Option<&vm_netlimit::NetLimit>,
+) -> String {
+    let kind = if bridge { "bridge" } else { "network" };
+    let mut xml = format!("    <interface type='{}'>\n", kind);
+    xml.push_str(&format!("      <source {}='{}'/>\n", kind, nic.network));
+    if let Some(mac) = &nic.mac {
+        xml.push_str(&format!("      <mac address='{}'/>\n", mac));
+    }
+    xml.push_str(&format!(
+        "      <model type='{}'/>\n",
+        nic.model.unwrap_or(default)
+    ));
+    if let Some(limit) = limit {
+        xml.push_str(&vm_netlimit::bandwidth_xml(limit));
+    }
+    xml.push_str("    </interface>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Vm {
+        #[serde(
+            default,
+            deserialize_with = "deserialize_networks",
+            serialize_with = "serialize_networks",
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        network: Vec<VmNic>,
+    }
+
+    #[test]
+    fn scalar_and_list_forms_parse_and_round_trip() {
+        let vm: Vm = toml::from_str("network = \"lan\"").unwrap();
+        assert_eq!(vm.network, vec![VmNic::new("lan")]);
+        assert_eq!(toml::to_string(&vm).unwrap().trim(), "network = \"lan\"");
+
+        let vm: Vm = toml::from_str(
+            "network = [\"lan\", { network = \"wan\", model = \"e1000e\", mac = \"52:54:00:12:34:56\" }]",
+        )
+        .unwrap();
+        assert_eq!(vm.network.len(), 2);
+        assert_eq!(vm.network[1].model, Some(NetModel::E1000e));
+        let again: Vm = toml::from_str(&toml::to_string(&vm).unwrap()).unwrap();
+        assert_eq!(again.network, vm.network);
+
+        let vm: Vm = toml::from_str("").unwrap();
+        assert!(vm.network.is_empty());
+        assert!(toml::from_str::<Vm>("network = [{ network = \"wan\", mac = \"zz\" }]").is_err());
+    }
+
+    #[test]
+    fn macs_and_device_arguments() {
+        assert!(validate_mac("52:54:00:AB:cd:01").is_ok());
+        assert!(validate_mac("52:54:00:ab:cd").is_err());
+        assert!(validate_mac("01:00:5e:00:00:01").is_err());
+
+        let nic = VmNic {
+            mac: Some("52:54:00:00:00:02".to_string()),
+            ..VmNic::new("br-wan")
+        };
+        assert_eq!(
+            qemu_bridge_args(1, "br-wan", &nic, NetModel::E1000e),
+            [
+                "-netdev",
+                "bridge,id=net1,br=br-wan",
+                "-device",
+                "e1000e,netdev=net1,mac=52:54:00:00:00:02"
+            ]
+        );
+
+        let xml = source_interface_xml(&nic, false, NetModel::Virtio, None);
+        assert!(xml.contains("<interface type='network'>"));
+        assert!(xml.contains("<source network='br-wan'/>"));
+        assert!(xml.contains("<mac address='52:54:00:00:00:02'/>"));
+        assert!(xml.contains("<model type='virtio'/>"));
+
+        let xml = source_interface_xml(&nic, true, NetModel::Virtio, None);
+        assert!(xml.contains("<interface type='bridge'>"));
+        assert!(xml.contains("<source bridge='br-wan'/>"));
+    }
+}
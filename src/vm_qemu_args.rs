@@ -0,0 +1,165 @@
+//! Raw QEMU arguments for features Nova doesn't model.
+//!
+//! `extra_qemu_args` under `[vm.<name>]` (or `nova vm qemu-arg add`) is
+//! emitted as a `<qemu:commandline>` block in the libvirt domain, and
+//! appended as-is when Nova launches QEMU directly. Libvirt does not know
+//! what these arguments do: devices added this way are invisible to hotplug,
+//! snapshots and migration, and a destination host without the same backing
+//! resources will fail to start the VM. Treat this as an unsupported escape
+//! hatch.
+
+use crate::{NovaError, Result};
+use regex::Regex;
+
+/// XML namespace libvirt requires for `<qemu:commandline>`.
+pub const QEMU_NAMESPACE: &str = "http://libvirt.org/schemas/domain/qemu/1.0";
+
+/// Warning shown whenever passthrough arguments are set.
+pub const UNSUPPORTED_WARNING: &str = "Raw QEMU arguments are unsupported: libvirt cannot \
+     account for them, and they can break live migration, snapshots and device hotplug";
+
+/// Reject arguments that cannot be passed through.
+pub fn validate_args(args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        return Err(NovaError::ConfigError(
+            "No QEMU arguments given; pass them after `--`".to_string(),
+        ));
+    }
+    if let Some(arg) = args.iter().find(|arg| arg.trim().is_empty()) {
+        return Err(NovaError::ConfigError(format!(
+            "Empty QEMU argument {:?} is not allowed",
+            arg
+        )));
+    }
+    if let Some(arg) = args.iter().find(|arg| arg.contains('\0')) {
+        return Err(NovaError::ConfigError(format!(
+            "QEMU argument {:?} contains a NUL byte",
+            arg
+        )));
+    }
+    Ok(())
+}
+
+/// `<qemu:commandline>` block for a domain definition; empty without args.
+pub fn commandline_xml(args: &[String]) -> String {
+    if args.is_empty() {
+        return String::new();
+    }
+    let mut xml = String::from("  <qemu:commandline>\n");
+    for arg in args {
+        xml.push_str(&format!(
+            "    <qemu:arg value='{}'/>\n",
+            escape_attribute(arg)
+        ));
+    }
+    xml.push_str("  </qemu:commandline>\n");
+    xml
+}
+
+/// Opening `<domain>` tag, declaring the qemu namespace when args are set.
+pub fn domain_open_tag(args: &[String]) -> String {
+    if args.is_empty() {
+        "<domain type='kvm'>\n".to_string()
+    } else {
+        format!("<domain type='kvm' xmlns:qemu='{}'>\n", QEMU_NAMESPACE)
+    }
+}
+
+/// Passthrough arguments in an existing domain definition.
+pub fn parse_args(xml: &str) -> Vec<String> {
+    let arg = Regex::new(r#"<qemu:arg\s+value=(?:'([^']*)'|"([^"]*)")\s*/>"#)
+        .expect("valid qemu:arg pattern");
+    arg.captures_iter(xml)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|value| unescape_attribute(value.as_str()))
+        .collect()
+}
+
+/// Replace the passthrough arguments of a domain definition with `args`.
+/// An empty `args` removes the `<qemu:commandline>` block.
+pub fn apply_args(xml: &str, args: &[String]) -> Result<String> {
+    let block = Regex::new(r"(?s)[ \t]*<qemu:commandline>.*?</qemu:commandline>\s*\n?")
+        .expect("valid qemu:commandline pattern");
+    let mut xml = block.replace_all(xml, "").into_owned();
+    if args.is_empty() {
+        return Ok(xml);
+    }
+
+    let domain_tag = Regex::new(r"<domain\b[^>]*>").expect("valid domain pattern");
+    let Some(tag) = domain_tag.find(&xml) else {
+        return Err(NovaError::ConfigError(
+            "Domain XML has no <domain> element".to_string(),
+        ));
+    };
+    if !tag.as_str().contains("xmlns:qemu") {
+        let with_namespace = format!(
+            "{} xmlns:qemu='{}'>",
+            tag.as_str().trim_end_matches('>'),
+            QEMU_NAMESPACE
+        );
+        xml.replace_range(tag.range(), &with_namespace);
+    }
+
+    let Some(end) = xml.rfind("</domain>") else {
+        return Err(NovaError::ConfigError(
+            "Domain XML has no closing </domain>".to_string(),
+        ));
+    };
+    xml.insert_str(end, &commandline_xml(args));
+    Ok(xml)
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
+}
+
+fn unescape_attribute(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&apos;", "'")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_round_trip_through_domain_xml() {
+        let domain =
+            "<domain type='kvm'>\n  <name>win11</name>\n  <devices>\n  </devices>\n</domain>\n";
+        let args = vec![
+            "-device".to_string(),
+            "ivshmem-plain,id=shmem0,memdev=looking-glass".to_string(),
+            "-set".to_string(),
+            "device.hostdev0.x-msix-relocation='bar2'&on".to_string(),
+        ];
+
+        let xml = apply_args(domain, &args).unwrap();
+        assert!(xml.starts_with(&format!(
+            "<domain type='kvm' xmlns:qemu='{}'>",
+            QEMU_NAMESPACE
+        )));
+        assert!(xml.contains("&apos;bar2&apos;&amp;on"));
+        assert_eq!(parse_args(&xml), args);
+
+        // Re-applying replaces rather than appends
+        let xml = apply_args(&xml, &args[..2]).unwrap();
+        assert_eq!(parse_args(&xml), args[..2].to_vec());
+        assert_eq!(xml.matches("xmlns:qemu").count(), 1);
+
+        let cleared = apply_args(&xml, &[]).unwrap();
+        assert!(!cleared.contains("qemu:commandline"));
+        assert!(cleared.ends_with("</devices>\n</domain>\n"));
+
+        assert!(validate_args(&[]).is_err());
+        assert!(validate_args(&[" ".to_string()]).is_err());
+    }
+}
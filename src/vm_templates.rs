@@ -2,8 +2,12 @@
 // GPU Passthrough optimized templates for gaming/workstation VMs
 
 use crate::config::VmTemplateConfig;
+use crate::gpu_passthrough::PciDevice;
 use std::collections::HashMap;
 
+const NVIDIA_VENDOR_ID: &str = "10de";
+const AMD_VENDOR_ID: &str = "1002";
+
 /// Get all built-in VM templates
 pub fn builtin_templates() -> HashMap<String, VmTemplateConfig> {
     let mut templates = HashMap::new();
@@ -295,6 +299,69 @@ pub fn builtin_templates() -> HashMap<String, VmTemplateConfig> {
     templates
 }
 
+/// GPU selected for a passthrough template along with recommended VM options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateGpuSelection {
+    pub device_address: String,
+    pub device_name: String,
+    pub vendor_id: String,
+    /// Machine type to request from virt-install (passthrough needs q35)
+    pub machine: String,
+    /// Hide the hypervisor from the guest (NVIDIA drivers refuse to load otherwise)
+    pub hide_hypervisor: bool,
+}
+
+/// Pick a GPU suitable for a passthrough template.
+///
+/// Templates tagged `nvidia` only accept NVIDIA GPUs; other passthrough
+/// templates accept any NVIDIA or AMD GPU. GPUs reserved for another VM are
+/// skipped, and devices already bound to vfio-pci are preferred over ones the
+/// host is actively using.
+pub fn select_template_gpu(
+    template: &VmTemplateConfig,
+    gpus: &[PciDevice],
+    reservations: &HashMap<String, String>,
+) -> Option<TemplateGpuSelection> {
+    if !template.gpu_passthrough {
+        return None;
+    }
+
+    let nvidia_only = template.tags.iter().any(|tag| tag == "nvidia");
+
+    let mut candidates: Vec<&PciDevice> = gpus
+        .iter()
+        .filter(|gpu| !reservations.contains_key(&gpu.address))
+        .filter(|gpu| {
+            let vendor = gpu.vendor_id.to_ascii_lowercase();
+            if nvidia_only {
+                vendor == NVIDIA_VENDOR_ID
+            } else {
+                vendor == NVIDIA_VENDOR_ID || vendor == AMD_VENDOR_ID
+            }
+        })
+        .collect();
+
+    candidates.sort_by_key(|gpu| {
+        let rank = match gpu.driver.as_deref() {
+            Some("vfio-pci") => 0,
+            _ if !gpu.in_use => 1,
+            _ => 2,
+        };
+        (rank, gpu.address.clone())
+    });
+
+    candidates.first().map(|gpu| {
+        let is_nvidia = gpu.vendor_id.eq_ignore_ascii_case(NVIDIA_VENDOR_ID);
+        TemplateGpuSelection {
+            device_address: gpu.address.clone(),
+            device_name: gpu.device_name.clone(),
+            vendor_id: gpu.vendor_id.clone(),
+            machine: "q35".to_string(),
+            hide_hypervisor: is_nvidia,
+        }
+    })
+}
+
 /// Scan directories for ISO files
 pub fn scan_iso_directories(paths: &[std::path::PathBuf]) -> Vec<IsoFile> {
     let mut isos = Vec::new();
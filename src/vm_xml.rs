@@ -0,0 +1,175 @@
+//! Viewing and hand-editing a VM's libvirt domain XML.
+//!
+//! `nova vm edit` checks the edited XML is well-formed and still describes
+//! the same domain before `virsh define --validate` sees it. The previous
+//! definition is saved under `~/.local/share/nova/xml-backups/<vm>/` first,
+//! so `nova vm edit <vm> --rollback` can restore it after a bad edit.
+
+use crate::{NovaError, Result};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+
+const BACKUP_DIR_FALLBACK: &str = "/var/lib/nova/xml-backups";
+
+/// Backups kept per VM; older ones are removed when a new one is saved.
+pub const MAX_BACKUPS: usize = 10;
+
+/// Check `xml` is a well-formed `<domain>` whose `<name>` is still `vm`.
+/// Renaming through an edit would define a second domain instead.
+pub fn check_domain_xml(xml: &str, vm: &str) -> Result<()> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut root = None;
+    let mut name = None;
+
+    loop {
+        let event = reader.read_event().map_err(|err| {
+            NovaError::ConfigError(format!(
+                "Edited XML is not well-formed at byte {}: {}",
+                reader.error_position(),
+                err
+            ))
+        })?;
+        match event {
+            Event::Start(tag) => {
+                let tag = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+                if path.is_empty() {
+                    if root.is_some() {
+                        return Err(NovaError::ConfigError(format!(
+                            "Edited XML has a second root element <{}>",
+                            tag
+                        )));
+                    }
+                    root = Some(tag.clone());
+                }
+                path.push(tag);
+            }
+            Event::Empty(tag) if path.is_empty() => {
+                root = Some(String::from_utf8_lossy(tag.name().as_ref()).into_owned());
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(text) if path.len() == 2 && path[1] == "name" => {
+                let text = text.decode().map_err(|err| {
+                    NovaError::ConfigError(format!("Edited XML has an unreadable <name>: {}", err))
+                })?;
+                name = Some(text.trim().to_string());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if let Some(open) = path.last() {
+        return Err(NovaError::ConfigError(format!(
+            "Edited XML is not well-formed: <{}> is never closed",
+            open
+        )));
+    }
+    if root.as_deref() != Some("domain") {
+        return Err(NovaError::ConfigError(
+            "Edited XML must have <domain> as its root element".to_string(),
+        ));
+    }
+    match name {
+        Some(name) if name == vm => Ok(()),
+        Some(name) => Err(NovaError::ConfigError(format!(
+            "Edited XML renames the domain to '{}'; use `nova clone` for a copy instead",
+            name
+        ))),
+        None => Err(NovaError::ConfigError(
+            "Edited XML has no <name> element".to_string(),
+        )),
+    }
+}
+
+fn backup_dir(vm: &str) -> PathBuf {
+    let mut dir = if let Some(mut dir) = dirs::data_dir() {
+        dir.push("nova");
+        dir.push("xml-backups");
+        dir
+    } else {
+        PathBuf::from(BACKUP_DIR_FALLBACK)
+    };
+    dir.push(vm);
+    dir
+}
+
+/// Save `xml` as the newest backup of `vm`, dropping all but the last
+/// [`MAX_BACKUPS`]. Returns the backup's path.
+pub fn save_backup(vm: &str, xml: &str) -> Result<PathBuf> {
+    let dir = backup_dir(vm);
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Backups keep graphics passwords (`--security-info`), so only the
+    // owner may read them
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .recursive(true)
+        .create(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+    let path = dir.join(format!(
+        "{}.xml",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?
+        .write_all(xml.as_bytes())?;
+
+    let backups = list_backups(vm);
+    for old in backups
+        .iter()
+        .take(backups.len().saturating_sub(MAX_BACKUPS))
+    {
+        let _ = fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+/// Backups of `vm`, oldest first.
+pub fn list_backups(vm: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(backup_dir(vm)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    // Timestamped names sort chronologically
+    backups.sort();
+    backups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edited_xml_must_be_the_same_well_formed_domain() {
+        let xml = "<domain type='kvm'>\n  <name>web</name>\n  <memory unit='KiB'>2097152</memory>\n  \
+                   <devices><disk type='file'/></devices>\n</domain>\n";
+        assert!(check_domain_xml(xml, "web").is_ok());
+
+        let renamed = xml.replace("<name>web</name>", "<name>web2</name>");
+        assert!(check_domain_xml(&renamed, "web").is_err());
+
+        let unclosed = xml.replace("</devices>", "");
+        assert!(check_domain_xml(&unclosed, "web").is_err());
+
+        let truncated = &xml[..xml.len() - "</domain>\n".len()];
+        assert!(check_domain_xml(truncated, "web").is_err());
+
+        assert!(check_domain_xml("<network><name>web</name></network>", "web").is_err());
+        assert!(check_domain_xml("<domain type='kvm'></domain>", "web").is_err());
+    }
+}
@@ -121,6 +121,42 @@ fn test_gpu_doctor_diagnostics() {
         check_names.contains(&"GPU Detection"),
         "Should check GPU detection"
     );
+    assert!(
+        check_names.contains(&"AMD Reset"),
+        "Should check the AMD reset bug"
+    );
+}
+
+#[test]
+fn test_amd_reset_check() {
+    let gpu = |address: &str, methods: &[&str]| {
+        (
+            address.to_string(),
+            methods.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+        )
+    };
+
+    assert_eq!(evaluate_amd_reset(&[], false).status, CheckStatus::Pass);
+    let flr = [gpu("0000:03:00.0", &["flr", "bus"])];
+    assert_eq!(evaluate_amd_reset(&flr, false).status, CheckStatus::Pass);
+
+    let vega = [gpu("0000:0c:00.0", &["bus"])];
+    let check = evaluate_amd_reset(&vega, false);
+    assert_eq!(check.status, CheckStatus::Warn);
+    assert!(check.message.contains("AMD reset bug"), "{}", check.message);
+    assert!(check.fix_command.unwrap().contains("modprobe vendor-reset"));
+    assert_eq!(evaluate_amd_reset(&vega, true).status, CheckStatus::Warn);
+
+    let handled = [gpu("0000:0c:00.0", &["device_specific", "bus"])];
+    assert_eq!(evaluate_amd_reset(&handled, true).status, CheckStatus::Pass);
+
+    let second = [gpu("0000:0c:00.0", &["bus", "device_specific"])];
+    let check = evaluate_amd_reset(&second, true);
+    assert_eq!(check.status, CheckStatus::Warn);
+    assert_eq!(
+        check.fix_command.as_deref(),
+        Some("echo device_specific | sudo tee /sys/bus/pci/devices/0000:0c:00.0/reset_method")
+    );
 }
 
 #[test]
@@ -230,6 +266,7 @@ fn test_libvirt_xml_generation() {
         usb_controller: None,
         x_vga: false,
         display: DisplayMode::None,
+        allow_host_display: false,
     };
 
     let manager = GpuManager::new();
@@ -284,6 +321,144 @@ fn test_passthrough_mode_variants() {
     assert_eq!(modes.len(), 4, "Should have 4 passthrough modes");
 }
 
+#[test]
+fn test_template_gpu_selection() {
+    use nova::vm_templates::{builtin_templates, select_template_gpu};
+    use std::collections::HashMap;
+
+    let gpu = |address: &str, vendor: &str, driver: &str, in_use: bool| PciDevice {
+        address: address.to_string(),
+        vendor_id: vendor.to_string(),
+        device_id: "0000".to_string(),
+        vendor_name: String::new(),
+        device_name: format!("GPU {}", address),
+        iommu_group: Some(1),
+        driver: Some(driver.to_string()),
+        in_use,
+        mdev_types: Vec::new(),
+    };
+
+    let templates = builtin_templates();
+    let nv_arch = &templates["nv-arch"];
+    let gpus = vec![
+        gpu("0000:03:00.0", "1002", "vfio-pci", false),
+        gpu("0000:01:00.0", "10de", "nvidia", true),
+        gpu("0000:02:00.0", "10de", "vfio-pci", false),
+    ];
+
+    // NVIDIA templates skip AMD cards and prefer devices already on vfio-pci
+    let selection = select_template_gpu(nv_arch, &gpus, &HashMap::new()).unwrap();
+    assert_eq!(selection.device_address, "0000:02:00.0");
+    assert_eq!(selection.machine, "q35");
+    assert!(selection.hide_hypervisor);
+
+    // Reserved GPUs are never offered
+    let mut reservations = HashMap::new();
+    reservations.insert("0000:02:00.0".to_string(), "other-vm".to_string());
+    let selection = select_template_gpu(nv_arch, &gpus, &reservations).unwrap();
+    assert_eq!(selection.device_address, "0000:01:00.0");
+
+    // Vendor-neutral templates accept AMD cards without hiding the hypervisor
+    let gaming = &templates["windows11-gaming"];
+    let selection = select_template_gpu(gaming, &gpus[..1], &HashMap::new()).unwrap();
+    assert_eq!(selection.device_address, "0000:03:00.0");
+    assert!(!selection.hide_hypervisor);
+
+    // No compatible GPU, or a template without passthrough, yields nothing
+    assert!(select_template_gpu(nv_arch, &gpus[..1], &HashMap::new()).is_none());
+    assert!(select_template_gpu(&templates["arch"], &gpus, &HashMap::new()).is_none());
+}
+
+#[test]
+fn test_device_readiness_badge() {
+    let gpu = PciDevice {
+        address: "0000:01:00.0".to_string(),
+        vendor_id: "10de".to_string(),
+        device_id: "2684".to_string(),
+        vendor_name: "NVIDIA Corporation".to_string(),
+        device_name: "GeForce RTX 4090".to_string(),
+        iommu_group: Some(14),
+        driver: Some("vfio-pci".to_string()),
+        in_use: false,
+        mdev_types: Vec::new(),
+    };
+    let group = IommuGroup {
+        id: 14,
+        devices: vec![gpu.clone()],
+        isolated: true,
+        viable_for_passthrough: true,
+    };
+    let flr = vec!["flr".to_string(), "bus".to_string()];
+    let idle = HostDisplayUse::default();
+
+    let ready = evaluate_device(&gpu, Some(&group), &idle, &flr, false, None);
+    assert_eq!(ready.status, CheckStatus::Pass);
+    assert_eq!(ready.label(), "Ready");
+    assert!(ready.summary().contains("Reset via flr, bus"));
+
+    // A reservation or missing reset method is a caution, not a blocker
+    let reserved = evaluate_device(&gpu, Some(&group), &idle, &flr, false, Some("win11"));
+    assert_eq!(reserved.status, CheckStatus::Warn);
+    let no_reset = evaluate_device(&gpu, Some(&group), &idle, &[], false, None);
+    assert_eq!(no_reset.status, CheckStatus::Warn);
+
+    // Driving the host display or sharing a group blocks passthrough
+    let host_gpu = PciDevice {
+        driver: Some("nvidia".to_string()),
+        ..gpu.clone()
+    };
+    let display = HostDisplayUse {
+        boot_vga: true,
+        active_connectors: Vec::new(),
+    };
+    let blocked = evaluate_device(&host_gpu, Some(&group), &display, &flr, false, None);
+    assert_eq!(blocked.status, CheckStatus::Fail);
+    assert!(blocked.summary().contains("--force"));
+
+    let shared = IommuGroup {
+        isolated: false,
+        viable_for_passthrough: false,
+        ..group.clone()
+    };
+    let blocked = evaluate_device(&gpu, Some(&shared), &idle, &flr, false, None);
+    assert_eq!(blocked.label(), "Blocked");
+    assert_eq!(
+        evaluate_device(&gpu, None, &idle, &flr, false, None).status,
+        CheckStatus::Fail
+    );
+}
+
+#[test]
+fn test_running_vm_hostdev_addresses() {
+    let domain = "<domain type='kvm'>
+  <devices>
+    <hostdev mode='subsystem' type='pci' managed='yes'>
+      <driver name='vfio'/>
+      <source>
+        <address domain='0x0000' bus='0x01' slot='0x00' function='0x0'/>
+      </source>
+      <address type='pci' domain='0x0000' bus='0x06' slot='0x00' function='0x0'/>
+    </hostdev>
+    <hostdev mode='subsystem' type='usb' managed='yes'>
+      <source><vendor id='0x046d'/><product id='0xc52b'/></source>
+    </hostdev>
+    <hostdev mode='subsystem' type='pci' managed='yes'>
+      <source>
+        <address domain='0x0000' bus='0x0a' slot='0x1f' function='0x1'/>
+      </source>
+    </hostdev>
+  </devices>
+</domain>";
+
+    assert_eq!(
+        hostdev_pci_addresses(domain),
+        vec!["0000:01:00.0".to_string(), "0000:0a:1f.1".to_string()]
+    );
+    assert_eq!(host_driver_for_vendor("0x10de"), Some("nvidia"));
+    assert_eq!(host_driver_for_vendor("1002"), Some("amdgpu"));
+    assert_eq!(host_driver_for_vendor("1af4"), None);
+}
+
 #[cfg(feature = "integration")]
 #[test]
 fn test_full_gpu_passthrough_workflow() {
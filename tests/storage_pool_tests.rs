@@ -388,3 +388,300 @@ async fn test_concurrent_pool_operations() {
     assert!(pools1.is_empty(), "Manager one should not share state");
     assert!(pools2.is_empty(), "Manager two should not share state");
 }
+
+#[test]
+fn test_storage_backend_dispatch() {
+    use nova::storage_backend::backend_for;
+
+    for pool_type in [
+        PoolType::Directory,
+        PoolType::Btrfs,
+        PoolType::Nfs,
+        PoolType::Lvm,
+        PoolType::Zfs,
+    ] {
+        let backend = backend_for(&pool_type).expect("backend should be registered");
+        assert_eq!(backend.pool_type(), pool_type);
+    }
+
+    assert!(backend_for(&PoolType::Ceph).is_err());
+}
+
+#[test]
+fn test_lvm_backend_volumes_and_capacity() {
+    use nova::storage_backend::{LvmBackend, StorageBackend, logical_pool_xml, parse_vgs_capacity};
+
+    let pool = StoragePool {
+        name: "lvm-pool".to_string(),
+        pool_type: PoolType::Lvm,
+        path: "/dev/nova-vg".into(),
+        state: PoolState::Active,
+        capacity: None,
+        autostart: false,
+        config: PoolConfig::Lvm {
+            vg_name: "nova-vg".to_string(),
+            pv_devices: Vec::new(),
+        },
+        uuid: uuid::Uuid::new_v4().to_string(),
+        created_at: chrono::Utc::now(),
+    };
+
+    // Logical volumes are raw block devices
+    assert!(
+        LvmBackend
+            .create_volume(&pool, "disk0", 1 << 30, &VolumeFormat::Qcow2)
+            .is_err()
+    );
+
+    let capacity = parse_vgs_capacity("  107374182400 42949672960\n").unwrap();
+    assert_eq!(capacity.total_bytes, 107_374_182_400);
+    assert_eq!(capacity.available_bytes, 42_949_672_960);
+    assert_eq!(capacity.used_bytes, 64_424_509_440);
+    assert!(parse_vgs_capacity("").is_none());
+
+    let xml = logical_pool_xml("lvm-pool", "nova-vg");
+    assert!(xml.contains("<pool type='logical'>"));
+    assert!(xml.contains("<path>/dev/nova-vg</path>"));
+}
+
+#[test]
+fn test_zfs_backend_properties_and_capacity() {
+    use nova::storage_backend::{
+        StorageBackend, ZfsBackend, parse_zfs_capacity, parse_zfs_compression,
+        zfs_compression_property,
+    };
+
+    for value in ["off", "lz4", "gzip-9", "zstd-19"] {
+        let parsed = parse_zfs_compression(value).expect("valid compression");
+        assert_eq!(zfs_compression_property(&parsed), value);
+    }
+    assert!(matches!(
+        parse_zfs_compression("zstd"),
+        Some(ZfsCompression::Zstd { level: 3 })
+    ));
+    assert!(parse_zfs_compression("gzip-12").is_none());
+    assert!(parse_zfs_compression("brotli").is_none());
+
+    let capacity = parse_zfs_capacity("21474836480\t85899345920\n").unwrap();
+    assert_eq!(capacity.total_bytes, 107_374_182_400);
+    assert_eq!(capacity.used_bytes, 21_474_836_480);
+
+    let pool = StoragePool {
+        name: "tank".to_string(),
+        pool_type: PoolType::Zfs,
+        path: "/tank/nova".into(),
+        state: PoolState::Active,
+        capacity: None,
+        autostart: false,
+        config: PoolConfig::Zfs {
+            dataset: "tank/nova".to_string(),
+            compression: ZfsCompression::Lz4,
+            dedup: false,
+        },
+        uuid: uuid::Uuid::new_v4().to_string(),
+        created_at: chrono::Utc::now(),
+    };
+
+    // zvols are raw block devices
+    assert!(
+        ZfsBackend
+            .create_volume(&pool, "disk0", 1 << 30, &VolumeFormat::Qcow2)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_zfs_pool_provisioning_commands() {
+    use nova::exec::render;
+    use nova::storage_backend::{ZfsBackend, pool_start_commands, zfs_pool_xml};
+
+    let zstd = ZfsCompression::Zstd { level: 3 };
+    assert_eq!(
+        ZfsBackend::dataset_args("tank/nova", &zstd, true, false),
+        [
+            "create",
+            "-p",
+            "-o",
+            "compression=zstd-3",
+            "-o",
+            "dedup=on",
+            "tank/nova"
+        ]
+    );
+    assert_eq!(
+        ZfsBackend::dataset_args("tank/nova", &ZfsCompression::Off, false, true),
+        ["set", "compression=off", "dedup=off", "tank/nova"]
+    );
+
+    let xml = zfs_pool_xml("fast", "tank/nova");
+    assert!(xml.starts_with("<pool type='zfs'>"));
+    assert!(xml.contains("<name>fast</name>"));
+    assert!(xml.contains("<source>\n    <name>tank/nova</name>\n  </source>"));
+
+    let pool = StoragePool {
+        name: "fast".to_string(),
+        pool_type: PoolType::Zfs,
+        path: "/dev/zvol/tank/nova".into(),
+        state: PoolState::Inactive,
+        capacity: None,
+        autostart: true,
+        config: PoolConfig::Zfs {
+            dataset: "tank/nova".to_string(),
+            compression: zstd,
+            dedup: true,
+        },
+        uuid: uuid::Uuid::new_v4().to_string(),
+        created_at: chrono::Utc::now(),
+    };
+    let commands: Vec<String> = pool_start_commands(&pool).iter().map(render).collect();
+    assert_eq!(
+        commands,
+        ["virsh pool-start fast", "virsh pool-autostart fast"]
+    );
+}
+
+#[test]
+fn test_btrfs_usage_parsing() {
+    use nova::storage_backend::parse_btrfs_usage;
+
+    let single = "Overall:
+    Device size:\t\t        1000204886016
+    Device allocated:\t\t         216754323456
+    Device unallocated:\t\t         783450562560
+    Device missing:\t\t                    0
+    Device slack:\t\t                    0
+    Used:\t\t\t         200000000000
+    Free (estimated):\t\t         799000000000\t(min: 407274217472)
+    Free (statfs, df):\t\t         798000000000
+    Data ratio:\t\t\t                 1.00
+    Metadata ratio:\t\t                 2.00
+
+Data,single: Size:210000000000, Used:195000000000 (92.86%)
+   /dev/nvme0n1p2\t 210000000000
+";
+    let capacity = parse_btrfs_usage(single).unwrap();
+    assert_eq!(capacity.total_bytes, 1_000_204_886_016);
+    assert_eq!(capacity.used_bytes, 200_000_000_000);
+    assert_eq!(capacity.available_bytes, 799_000_000_000);
+
+    // RAID1 reports raw device space; usable space is half
+    let raid1 = single.replace(
+        "Data ratio:\t\t\t                 1.00",
+        "Data ratio:\t\t\t                 2.00",
+    );
+    let capacity = parse_btrfs_usage(&raid1).unwrap();
+    assert_eq!(capacity.total_bytes, 500_102_443_008);
+    assert_eq!(capacity.used_bytes, 100_000_000_000);
+
+    assert!(parse_btrfs_usage("ERROR: not a btrfs filesystem").is_none());
+}
+
+#[test]
+fn test_disk_references_volume() {
+    use std::path::Path;
+
+    let domain = "<domain type='kvm'>
+  <name>web01</name>
+  <devices>
+    <disk type='file' device='disk'>
+      <source file='/var/lib/nova/pool1/vm-disk1.qcow2'/>
+      <target dev='vda' bus='virtio'/>
+    </disk>
+    <disk type='volume' device='disk'>
+      <source pool=\"fast\" volume=\"scratch\"/>
+      <target dev='vdb' bus='virtio'/>
+    </disk>
+    <interface type='bridge'>
+      <source bridge='br0'/>
+    </interface>
+  </devices>
+</domain>";
+
+    let disk1 = Path::new("/var/lib/nova/pool1/vm-disk1.qcow2");
+    assert!(disk_references_volume(
+        domain,
+        disk1,
+        "pool1",
+        "vm-disk1.qcow2"
+    ));
+    assert!(disk_references_volume(
+        domain,
+        Path::new("/dev/fast/scratch"),
+        "fast",
+        "scratch"
+    ));
+    assert!(!disk_references_volume(
+        domain,
+        Path::new("/var/lib/nova/pool1/vm-disk2.qcow2"),
+        "pool1",
+        "vm-disk2.qcow2"
+    ));
+    // Non-disk sources never count
+    assert!(!disk_references_volume(
+        domain,
+        Path::new("br0"),
+        "pool1",
+        "br0"
+    ));
+}
+
+#[test]
+fn test_nested_pool_paths_are_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let pool = |name: &str, path: std::path::PathBuf, pool_type: PoolType| StoragePool {
+        name: name.to_string(),
+        pool_type,
+        path,
+        state: PoolState::Active,
+        capacity: None,
+        autostart: false,
+        config: PoolConfig::Directory { permissions: 0o755 },
+        uuid: uuid::Uuid::new_v4().to_string(),
+        created_at: chrono::Utc::now(),
+    };
+    let images = pool(
+        "images",
+        temp_dir.path().join("images"),
+        PoolType::Directory,
+    );
+    std::fs::create_dir(&images.path).unwrap();
+
+    let nested = pool(
+        "isos",
+        temp_dir.path().join("images/./isos"),
+        PoolType::Directory,
+    );
+    let err = check_pool_overlap(&nested, [&images])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("nested inside pool 'images'"), "{}", err);
+
+    let parent = pool("everything", temp_dir.path().to_path_buf(), PoolType::Nfs);
+    assert!(check_pool_overlap(&parent, [&images]).is_err());
+
+    let sibling = pool(
+        "images2",
+        temp_dir.path().join("images2"),
+        PoolType::Directory,
+    );
+    assert!(check_pool_overlap(&sibling, [&images]).is_ok());
+    // LVM pool paths name a volume group device, not a directory
+    let lvm = pool("vg", temp_dir.path().join("images/vg"), PoolType::Lvm);
+    assert!(check_pool_overlap(&lvm, [&images]).is_ok());
+
+    let issues = verify_pools(&[&nested, &images, &sibling]);
+    assert_eq!(
+        issues
+            .iter()
+            .filter(|issue| matches!(issue.problem, PoolProblem::Overlap { .. }))
+            .count(),
+        1
+    );
+    assert_eq!(issues[0].pool, "images");
+    assert!(issues[0].is_error());
+    assert!(
+        issues
+            .iter()
+            .any(|issue| issue.pool == "images2" && issue.problem == PoolProblem::MissingPath)
+    );
+}